@@ -0,0 +1,508 @@
+/// Módulo del helper privilegiado.
+///
+/// Sin privilegios, `ss` no ve los PIDs de otros usuarios y cada kill
+/// rechazado abre un diálogo de `pkexec`. El helper es un proceso que
+/// se lanza una sola vez con privilegios (ej: `pkexec portslayer
+/// helper`, o una unidad de systemd como root) y atiende por un socket
+/// Unix las peticiones que los necesitan:
+///
+/// - `SS <flags>`: la salida de `ss`, con los PIDs de todos los procesos,
+/// - `KILL <pid>`: enviar `SIGKILL`,
+/// - `FUSER <puerto> <protocolo>`: matar al dueño de un puerto sin PID.
+///
+/// Los kills de procesos críticos (init, `systemd-*`, `sshd`, el gestor
+/// de sesiones, el propio helper y quien lo lanzó) se rechazan siempre,
+/// también si son el dueño del puerto de un `FUSER`: cualquier proceso
+/// del usuario puede hablar con el socket.
+///
+/// El socket vive en `/run/portslayer` (solo root escribe ahí) y solo se
+/// atiende al usuario que lo lanzó, comprobado con `SO_PEERCRED`. Los
+/// flags de `ss` se limitan a los de consulta: nada de `-K` (cerrar
+/// sockets) ni de opciones que lean o escriban archivos.
+///
+/// Si no hay helper escuchando, [`request`] devuelve `None` y cada
+/// llamador sigue con su método de siempre (`sudo -n`, `pkexec`).
+use nix::errno::Errno;
+use nix::sys::signal::{self, Signal};
+use nix::sys::socket::{getsockopt, sockopt};
+use nix::unistd::Pid;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::Duration;
+
+use crate::sources::{self, SOURCE_TIMEOUT};
+
+/// Directorio de los sockets del helper (de root, legible por todos)
+pub const SOCKET_DIR: &str = "/run/portslayer";
+
+/// Tiempo máximo de una petición (`fuser` puede tardar más que `ss`)
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Cada cuánto comprueba el helper si su proceso padre sigue vivo
+const PARENT_CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Flags de `ss` que el helper acepta (solo consultas)
+const ALLOWED_SS_FLAGS: &str = "tuanlpieHm46";
+
+/// Error de un kill cuyo proceso ya no existe
+pub const NO_SUCH_PROCESS: &str = "esrch";
+
+/// Comienzo del error de un kill que el helper rechaza por ser un
+/// proceso crítico (init, `sshd`, `systemd-*`, el gestor de sesiones...)
+pub const PROTECTED_PROCESS: &str = "protegido";
+
+/// Gestores de sesiones gráficas y servidores gráficos: matarlos cierra
+/// el escritorio
+pub const DISPLAY_MANAGERS: [&str; 9] = [
+    "gdm", "gdm3", "sddm", "lightdm", "lxdm", "xdm", "greetd", "Xorg", "Xwayland",
+];
+
+/// Petición al helper
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Request {
+    /// Ejecutar `ss` con estos flags (ej: "-tlnpH")
+    Ss(String),
+    /// Enviar `SIGKILL` a un proceso
+    Kill(u32),
+    /// Matar con `fuser` al dueño de un puerto
+    Fuser(u16, String),
+}
+
+impl Request {
+    /// Línea que la representa en el socket.
+    fn encode(&self) -> String {
+        match self {
+            Request::Ss(flags) => format!("SS {}\n", flags),
+            Request::Kill(pid) => format!("KILL {}\n", pid),
+            Request::Fuser(port, protocol) => format!("FUSER {} {}\n", port, protocol),
+        }
+    }
+
+    /// Interpreta y valida una línea recibida.
+    ///
+    /// # Returns
+    /// `Err(String)` si la petición es desconocida o sus argumentos no
+    /// están permitidos.
+    fn parse(line: &str) -> Result<Self, String> {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        match parts.as_slice() {
+            ["SS", flags] => {
+                let allowed = flags.strip_prefix('-').is_some_and(|f| {
+                    !f.is_empty() && f.chars().all(|c| ALLOWED_SS_FLAGS.contains(c))
+                });
+                if allowed {
+                    Ok(Request::Ss(flags.to_string()))
+                } else {
+                    Err(format!("Flags de ss no permitidos: {}", flags))
+                }
+            }
+            ["KILL", pid] => match pid.parse::<u32>() {
+                // PID 1 es init: ni siquiera root debería cerrarlo desde aquí
+                Ok(pid) if pid > 1 && i32::try_from(pid).is_ok() => Ok(Request::Kill(pid)),
+                _ => Err(format!("PID no permitido: {}", pid)),
+            },
+            ["FUSER", port, protocol @ ("tcp" | "udp")] => port
+                .parse()
+                .map(|port| Request::Fuser(port, protocol.to_string()))
+                .map_err(|_| format!("Puerto inválido: {}", port)),
+            _ => Err(format!("Petición desconocida: {}", line.trim())),
+        }
+    }
+
+    /// Ejecuta la petición con los privilegios del helper.
+    ///
+    /// # Returns
+    /// La salida de `ss` (vacía en los kills), o `Err(String)` con el
+    /// motivo del fallo ([`NO_SUCH_PROCESS`] si el proceso ya no existe).
+    fn execute(&self) -> Result<String, String> {
+        match self {
+            Request::Ss(flags) => {
+                let mut ss = Command::new("ss");
+                ss.arg(flags);
+                let output = sources::output_with_timeout(&mut ss, SOURCE_TIMEOUT)?;
+                String::from_utf8(output.stdout).map_err(|e| e.to_string())
+            }
+            Request::Kill(pid) => {
+                refuse_protected(*pid)?;
+                kill(*pid).map(|()| String::new())
+            }
+            Request::Fuser(port, protocol) => {
+                // Se buscan los dueños y se matan uno a uno (en lugar de
+                // `fuser -k`) para rechazar antes a los procesos críticos
+                let owners = port_owners(*port, protocol)?;
+                for &pid in &owners {
+                    refuse_protected(pid)?;
+                }
+                for pid in owners {
+                    match kill(pid) {
+                        Ok(()) => {}
+                        Err(e) if e == NO_SUCH_PROCESS => {}
+                        Err(e) => return Err(e),
+                    }
+                }
+                Ok(String::new())
+            }
+        }
+    }
+}
+
+/// Envía `SIGKILL` a un proceso.
+///
+/// # Returns
+/// `Err(String)` con [`NO_SUCH_PROCESS`] si ya no existe, o el motivo.
+fn kill(pid: u32) -> Result<(), String> {
+    match signal::kill(Pid::from_raw(pid as i32), Signal::SIGKILL) {
+        Ok(()) => Ok(()),
+        Err(Errno::ESRCH) => Err(NO_SUCH_PROCESS.to_string()),
+        Err(errno) => Err(errno.desc().to_string()),
+    }
+}
+
+/// PIDs de los procesos que usan un puerto, según `fuser` (sin matarlos).
+///
+/// # Returns
+/// `Err(String)` si `fuser` no se pudo ejecutar o ningún proceso lo usa.
+fn port_owners(port: u16, protocol: &str) -> Result<Vec<u32>, String> {
+    let output = Command::new("fuser")
+        .args(["-n", protocol, &port.to_string()])
+        .output()
+        .map_err(|e| format!("Error ejecutando fuser: {}", e))?;
+    // Los PIDs salen por stdout; el puerto y los modos de acceso, por stderr
+    let owners: Vec<u32> = String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .filter_map(|pid| {
+            pid.trim_end_matches(|c: char| !c.is_ascii_digit())
+                .parse()
+                .ok()
+        })
+        .collect();
+    if owners.is_empty() {
+        Err(format!(
+            "Ningún proceso usa el puerto {}/{}",
+            port, protocol
+        ))
+    } else {
+        Ok(owners)
+    }
+}
+
+/// Indica por qué el helper no mata a un proceso, si es crítico: init,
+/// `systemd` y sus servicios, `sshd`, el gestor de sesiones gráficas,
+/// PortSlayer (el helper y la aplicación que lo lanzó) o el padre del
+/// helper (`pkexec`, `sudo`).
+///
+/// Es la misma lista que protege `safety` en la aplicación, pero aquí no
+/// se puede confirmar: el socket acepta a cualquier proceso del usuario,
+/// así que esos kills tienen que pasar por `pkexec`, que pide contraseña.
+fn protected_target(pid: u32, process_name: &str) -> Option<&'static str> {
+    let family =
+        |name: &str| process_name == name || process_name.starts_with(&format!("{}-", name));
+    if pid <= 1 {
+        Some("init")
+    } else if pid == std::process::id()
+        || pid == std::os::unix::process::parent_id()
+        || process_name == "portslayer"
+    {
+        Some("PortSlayer o quien lanzó el helper")
+    } else if family("sshd") {
+        Some("acceso remoto")
+    } else if family("systemd") {
+        Some("servicio del sistema")
+    } else if DISPLAY_MANAGERS.iter().any(|dm| family(dm)) {
+        Some("sesión gráfica")
+    } else {
+        None
+    }
+}
+
+/// Rechaza el kill de un proceso crítico (ver [`protected_target`]).
+///
+/// # Returns
+/// `Err(String)` que empieza por [`PROTECTED_PROCESS`] si es crítico.
+fn refuse_protected(pid: u32) -> Result<(), String> {
+    let name = fs::read_to_string(format!("/proc/{}/comm", pid)).unwrap_or_default();
+    let name = name.trim();
+    match protected_target(pid, name) {
+        Some(reason) => {
+            log::warn!("Helper: kill rechazado de {} ({}): {}", pid, name, reason);
+            Err(format!(
+                "{}: {} (PID {}, {})",
+                PROTECTED_PROCESS, name, pid, reason
+            ))
+        }
+        None => Ok(()),
+    }
+}
+
+/// Socket del helper de un usuario.
+pub fn socket_path(uid: u32) -> PathBuf {
+    Path::new(SOCKET_DIR).join(format!("helper-{}.sock", uid))
+}
+
+/// Indica si hay un helper atendiendo al usuario actual.
+pub fn running() -> bool {
+    UnixStream::connect(socket_path(nix::unistd::getuid().as_raw())).is_ok()
+}
+
+/// Envía una petición al helper del usuario actual.
+///
+/// # Returns
+/// `None` si no hay helper escuchando; si no, su respuesta.
+pub fn request(request: &Request) -> Option<Result<String, String>> {
+    request_at(&socket_path(nix::unistd::getuid().as_raw()), request)
+}
+
+/// Envía una petición al helper que escucha en `path`.
+fn request_at(path: &Path, request: &Request) -> Option<Result<String, String>> {
+    let stream = UnixStream::connect(path).ok()?;
+    let response = match exchange(stream, request) {
+        Ok(response) => response,
+        Err(e) => return Some(Err(format!("El helper no respondió: {}", e))),
+    };
+    log::debug!("Petición {:?} atendida por el helper", request);
+    Some(match response.split_once('\n') {
+        Some(("OK", payload)) => Ok(payload.to_string()),
+        Some((status, _)) => Err(status.strip_prefix("ERR ").unwrap_or(status).to_string()),
+        None => Err(format!("Respuesta inválida del helper: {}", response)),
+    })
+}
+
+/// Escribe la petición y lee la respuesta completa.
+fn exchange(mut stream: UnixStream, request: &Request) -> io::Result<String> {
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    stream.write_all(request.encode().as_bytes())?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+    Ok(response)
+}
+
+// ─────────────────────────────────────────────────────────────
+// Servidor (el proceso privilegiado)
+// ─────────────────────────────────────────────────────────────
+
+/// Helper escuchando en su socket
+#[derive(Debug)]
+pub struct Helper {
+    /// Socket donde se aceptan las peticiones
+    listener: UnixListener,
+    /// Ruta del socket (se borra al terminar)
+    path: PathBuf,
+    /// Usuario al que se atiende (además de root)
+    uid: u32,
+}
+
+impl Helper {
+    /// Crea el socket del helper para un usuario.
+    ///
+    /// El socket admite conexiones de cualquiera (0666): quién puede
+    /// pedir algo lo decide la comprobación de `SO_PEERCRED`.
+    ///
+    /// # Returns
+    /// `Err(String)` si no se pudo crear el directorio o el socket (ej:
+    /// sin permisos de root para `/run/portslayer`).
+    pub fn bind(path: &Path, uid: u32) -> Result<Self, String> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| format!("No se pudo crear {}: {}", dir.display(), e))?;
+        }
+        // Un socket de una ejecución anterior impediría el bind
+        let _ = fs::remove_file(path);
+        let listener = UnixListener::bind(path)
+            .map_err(|e| format!("No se pudo escuchar en {}: {}", path.display(), e))?;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o666))
+            .map_err(|e| format!("No se pudo abrir {}: {}", path.display(), e))?;
+        log::info!(
+            "Helper escuchando en {} para el UID {}",
+            path.display(),
+            uid
+        );
+        Ok(Self {
+            listener,
+            path: path.to_path_buf(),
+            uid,
+        })
+    }
+
+    /// Atiende peticiones hasta que termine el proceso `parent` (o para
+    /// siempre si es `None`).
+    ///
+    /// Atarlo a la aplicación que lo lanzó evita que quede un proceso
+    /// privilegiado huérfano al cerrarla.
+    pub fn serve(&self, parent: Option<u32>) {
+        let alive = |pid: u32| Path::new(&format!("/proc/{}", pid)).exists();
+        if let Err(e) = self.listener.set_nonblocking(parent.is_some()) {
+            log::error!("No se pudo configurar el socket del helper: {}", e);
+            return;
+        }
+        loop {
+            match self.listener.accept() {
+                Ok((stream, _)) => self.handle(stream),
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                    if parent.is_some_and(|pid| !alive(pid)) {
+                        log::info!("La aplicación terminó, cerrando el helper");
+                        return;
+                    }
+                    std::thread::sleep(PARENT_CHECK_INTERVAL);
+                }
+                Err(e) => log::warn!("Error aceptando una conexión del helper: {}", e),
+            }
+        }
+    }
+
+    /// Atiende una conexión: una petición y su respuesta.
+    fn handle(&self, stream: UnixStream) {
+        let peer = getsockopt(&stream, sockopt::PeerCredentials).map(|cred| cred.uid());
+        let response = match peer {
+            Ok(uid) if uid == self.uid || uid == 0 => self.respond(&stream),
+            Ok(uid) => {
+                log::warn!("Petición al helper rechazada del UID {}", uid);
+                Err(format!("UID {} no autorizado", uid))
+            }
+            Err(e) => Err(format!("No se pudo identificar al cliente: {}", e)),
+        };
+        let message = match response {
+            Ok(payload) => format!("OK\n{}", payload),
+            Err(e) => format!("ERR {}\n", e.replace('\n', " ").trim()),
+        };
+        let _ = (&stream).write_all(message.as_bytes());
+    }
+
+    /// Lee la petición de una conexión autorizada y la ejecuta.
+    fn respond(&self, stream: &UnixStream) -> Result<String, String> {
+        // Las conexiones aceptadas no heredan el modo no bloqueante
+        let _ = stream.set_nonblocking(false);
+        let _ = stream.set_read_timeout(Some(REQUEST_TIMEOUT));
+        let mut line = String::new();
+        BufReader::new(stream)
+            .read_line(&mut line)
+            .map_err(|e| e.to_string())?;
+        let request = Request::parse(&line)?;
+        log::info!("Helper: {:?}", request);
+        request.execute()
+    }
+}
+
+impl Drop for Helper {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verifica la validación de las peticiones
+    #[test]
+    fn test_parse_requests() {
+        assert_eq!(
+            Request::parse("SS -tlnpH\n"),
+            Ok(Request::Ss("-tlnpH".into()))
+        );
+        assert_eq!(Request::parse("KILL 4242"), Ok(Request::Kill(4242)));
+        assert_eq!(
+            Request::parse("FUSER 3000 tcp"),
+            Ok(Request::Fuser(3000, "tcp".into()))
+        );
+        for rejected in [
+            "SS -K",
+            "SS -tD/etc/passwd",
+            "SS tln",
+            "KILL 1",
+            "KILL -1",
+            "FUSER 3000 sctp",
+            "RM /",
+        ] {
+            assert!(Request::parse(rejected).is_err(), "{}", rejected);
+        }
+        let request = Request::Fuser(53, "udp".into());
+        assert_eq!(Request::parse(&request.encode()), Ok(request));
+    }
+
+    /// Verifica que el helper rechaza matar procesos críticos, también
+    /// cuando son el dueño del puerto de un `FUSER`
+    #[test]
+    fn test_refuses_protected_targets() {
+        for (pid, name) in [
+            (880, "sshd"),
+            (881, "sshd-session"),
+            (420, "systemd-journal"),
+            (1200, "systemd"),
+            (777, "gdm-session-wor"),
+            (1300, "Xwayland"),
+            (1400, "portslayer"),
+            (std::os::unix::process::parent_id(), "cargo"),
+        ] {
+            assert!(protected_target(pid, name).is_some(), "{}", name);
+        }
+        assert_eq!(protected_target(4242, "node"), None);
+        assert_eq!(protected_target(4242, "sshfs"), None);
+
+        // Si el rechazo fallara, el kill terminaría este mismo test
+        let Err(e) = Request::Kill(std::process::id()).execute() else {
+            panic!("el helper aceptó matar al propio helper");
+        };
+        assert!(e.starts_with(PROTECTED_PROCESS), "{}", e);
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        match Request::Fuser(port, "tcp".into()).execute() {
+            Err(e) if e.starts_with("Error ejecutando fuser") => {}
+            // Sin privilegios fuser puede no ver el socket, pero nunca matarlo
+            Err(e) => assert!(
+                e.starts_with(PROTECTED_PROCESS) || e.starts_with("Ningún proceso"),
+                "{}",
+                e
+            ),
+            Ok(_) => panic!("el helper mató al dueño del puerto {}", port),
+        }
+    }
+
+    /// Verifica una petición completa por el socket
+    #[test]
+    fn test_helper_round_trip() {
+        use std::os::unix::process::ExitStatusExt;
+
+        let path = std::env::temp_dir().join(format!(
+            "portslayer-helper-test-{}.sock",
+            std::process::id()
+        ));
+        let helper = Helper::bind(&path, nix::unistd::getuid().as_raw()).unwrap();
+        let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+        let pid = child.id();
+
+        std::thread::scope(|scope| {
+            scope.spawn(|| {
+                for _ in 0..3 {
+                    let (stream, _) = helper.listener.accept().unwrap();
+                    helper.handle(stream);
+                }
+            });
+            assert_eq!(
+                request_at(&path, &Request::Kill(pid)),
+                Some(Ok(String::new()))
+            );
+            assert_eq!(child.wait().unwrap().signal(), Some(9));
+            assert_eq!(
+                request_at(&path, &Request::Kill(pid)),
+                Some(Err(NO_SUCH_PROCESS.to_string()))
+            );
+            let Some(Err(e)) = request_at(&path, &Request::Ss("-K".into())) else {
+                panic!("el helper aceptó ss -K");
+            };
+            assert!(e.contains("no permitidos"));
+        });
+
+        drop(helper);
+        assert!(!path.exists());
+        assert_eq!(request_at(&path, &Request::Kill(pid)), None);
+    }
+}
@@ -0,0 +1,174 @@
+/// Módulo de kills de procesos.
+///
+/// [`kill`] envía `SIGKILL` con `kill(2)`, sin lanzar procesos; si el
+/// sistema lo rechaza por permisos, se lo pide al helper privilegiado
+/// (ver `helper`) o, si no hay, lo reintenta con `pkexec kill -9`, que
+/// pide la contraseña de forma gráfica. [`kill_by_port`] cierra el
+/// dueño de un puerto con `fuser` cuando no se conoce su PID.
+use nix::errno::Errno;
+use nix::sys::signal::{self, Signal};
+use nix::unistd::Pid;
+use std::fmt;
+use std::process::Command;
+
+use crate::helper::{self, Request, NO_SUCH_PROCESS, PROTECTED_PROCESS};
+
+/// Motivo por el que no se pudo matar un proceso
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum KillError {
+    /// PID 0 o fuera de rango (se interpretaría como grupo de procesos)
+    InvalidPid,
+    /// El proceso ya no existe
+    NoSuchProcess,
+    /// Sin permisos y el reintento con privilegios falló o se canceló,
+    /// con el detalle del reintento
+    PermissionDenied(String),
+    /// El sistema lo rechazó, con el detalle del error
+    Failed(String),
+}
+
+impl fmt::Display for KillError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KillError::InvalidPid => write!(f, "PID inválido"),
+            KillError::NoSuchProcess => write!(f, "el proceso ya no existe"),
+            KillError::PermissionDenied(detail) | KillError::Failed(detail) => {
+                write!(f, "{}", detail)
+            }
+        }
+    }
+}
+
+/// Mata un proceso con `SIGKILL`, con el helper o `pkexec` si no hay
+/// permisos (ver [`elevate`]).
+///
+/// La señal se envía directamente, sin lanzar `kill`: solo el reintento
+/// con `pkexec` necesita otro proceso.
+pub fn kill(pid: u32) -> Result<(), KillError> {
+    kill_with(pid, elevate)
+}
+
+/// Como [`kill`], pero reintentando con `elevate` si el sistema rechaza
+/// la señal por permisos (ej: con un servicio que pregunte a polkit). Si
+/// el reintento falla, el error es [`KillError::PermissionDenied`].
+pub fn kill_with(
+    pid: u32,
+    elevate: impl FnOnce(u32) -> Result<(), KillError>,
+) -> Result<(), KillError> {
+    log::info!("Intentando matar proceso con PID: {}", pid);
+
+    let raw = i32::try_from(pid)
+        .ok()
+        .filter(|raw| *raw > 0)
+        .ok_or(KillError::InvalidPid)?;
+    match signal::kill(Pid::from_raw(raw), Signal::SIGKILL) {
+        Ok(()) => {
+            log::info!("Proceso {} terminado exitosamente", pid);
+            Ok(())
+        }
+        Err(Errno::EPERM) => {
+            log::warn!("Kill sin permisos falló, reintentando con privilegios...");
+            elevate(pid).map_err(|e| match e {
+                KillError::Failed(detail) => KillError::PermissionDenied(detail),
+                other => other,
+            })
+        }
+        Err(Errno::ESRCH) => Err(KillError::NoSuchProcess),
+        Err(errno) => Err(KillError::Failed(errno.desc().to_string())),
+    }
+}
+
+/// Reintento con privilegios por defecto: el helper si está en marcha
+/// (no pide contraseña) y, si no o si el helper lo rechaza por ser un
+/// proceso crítico, `pkexec kill -9` (prompt gráfico).
+pub fn elevate(pid: u32) -> Result<(), KillError> {
+    match helper::request(&Request::Kill(pid)) {
+        // Los procesos críticos no los mata el helper: pkexec pide contraseña
+        Some(Err(e)) if e.starts_with(PROTECTED_PROCESS) => {
+            log::warn!("El helper rechazó el kill ({}), usando pkexec", e);
+        }
+        Some(result) => {
+            log::info!("Proceso {} terminado vía helper: {:?}", pid, result);
+            return result.map(|_| ()).map_err(|e| match e.as_str() {
+                NO_SUCH_PROCESS => KillError::NoSuchProcess,
+                _ => KillError::Failed(e),
+            });
+        }
+        None => {}
+    }
+
+    let elevated = Command::new("pkexec")
+        .args(["kill", "-9", &pid.to_string()])
+        .output()
+        .map_err(|e| KillError::Failed(format!("Error ejecutando pkexec: {}", e)))?;
+    if elevated.status.success() {
+        log::info!("Proceso {} terminado con permisos elevados", pid);
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&elevated.stderr);
+        Err(KillError::Failed(stderr.into_owned()))
+    }
+}
+
+/// Mata el proceso asociado a un puerto cuando no se conoce el PID, usando `fuser`.
+///
+/// Utiliza el helper privilegiado si está en marcha y, si no, `pkexec`
+/// para solicitar permisos gráficos.
+///
+/// # Returns
+/// `Err(String)` con el error de `fuser` si no se pudo cerrar.
+pub fn kill_by_port(port: u16, protocol: &str) -> Result<(), String> {
+    log::info!("Intentando cerrar puerto {}/{} vía fuser", port, protocol);
+
+    match helper::request(&Request::Fuser(port, protocol.to_string())) {
+        Some(Err(e)) if e.starts_with(PROTECTED_PROCESS) => {
+            log::warn!("El helper rechazó el kill ({}), usando pkexec", e);
+        }
+        Some(result) => {
+            log::info!(
+                "Puerto {}/{} cerrado vía helper: {:?}",
+                port,
+                protocol,
+                result
+            );
+            return result.map(|_| ());
+        }
+        None => {}
+    }
+
+    let elevated = Command::new("pkexec")
+        .args(["fuser", "-k", "-9", "-n", protocol, &port.to_string()])
+        .output()
+        .map_err(|e| format!("Error ejecutando pkexec fuser: {}", e))?;
+
+    if elevated.status.success() {
+        log::info!("Puerto {}/{} cerrado con fuser", port, protocol);
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&elevated.stderr).into_owned())
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verifica que el kill se envía sin lanzar procesos y sus errores
+    #[test]
+    fn test_kill() {
+        use std::os::unix::process::ExitStatusExt;
+
+        let mut child = Command::new("sleep").arg("30").spawn().unwrap();
+        assert_eq!(kill(child.id()), Ok(()));
+        assert_eq!(child.wait().unwrap().signal(), Some(9));
+
+        // Ya recogido: no existe (sin reintentar con pkexec)
+        assert_eq!(kill(child.id()), Err(KillError::NoSuchProcess));
+        assert_eq!(kill(u32::MAX), Err(KillError::InvalidPid));
+        assert_eq!(kill(0), Err(KillError::InvalidPid));
+    }
+}
@@ -0,0 +1,48 @@
+//! Motor de escaneo de PortSlayer.
+//!
+//! Detecta los puertos abiertos (`ss` + `/proc/net`, con los namespaces
+//! de red de los contenedores y, en WSL2, los del Windows anfitrión, ver
+//! `wsl`), los filtra y pagina, y mata a sus dueños; también escanea los
+//! de otras máquinas (ver `remote`, con la feature `remote`, activa por
+//! defecto). Con la feature `tokio`, los escaneos también se pueden
+//! esperar con `.await` (`scan_open_ports_async`). No depende del tray
+//! ni de la configuración de PortSlayer, así que se puede usar desde
+//! otros programas:
+//!
+//! ```no_run
+//! use portslayer_core::{filter_ports, scan_open_ports, ProtocolFilter, RangeFilter, StateFilter};
+//!
+//! let ports = scan_open_ports();
+//! let dev = filter_ports(&ports, ProtocolFilter::Tcp, RangeFilter::Dev, StateFilter::All);
+//! for port in dev {
+//!     println!("{}", port);
+//! }
+//! ```
+//!
+//! `portslayer` (tray, CLI y daemon) usa este mismo motor y le añade la
+//! reproducción de sesiones, el registro de operaciones y los mensajes
+//! traducidos.
+pub mod helper;
+pub mod kill;
+pub mod ports;
+#[cfg(feature = "remote")]
+pub mod remote;
+pub mod scan;
+pub mod sources;
+pub mod wsl;
+
+pub use kill::{kill, kill_by_port, KillError};
+pub use ports::{
+    ephemeral_range, filter_ports, format_uptime, get_page, glob_match, ports_in_range,
+    total_pages, user_name, ListenQueue, OwnerFilter, PortInfo, PortOrigin, ProcessFilter,
+    ProtocolFilter, RangeFilter, ScopeFilter, SocketState, StateFilter,
+};
+#[cfg(feature = "remote")]
+pub use remote::{scan_remote, scan_remote_with, RemoteOptions, RemotePort, RemoteScan};
+pub use scan::{
+    execute_ss_command, find_free_port, last_fd_scan, scan_all_sockets, scan_checksum,
+    scan_open_ports, set_namespace_scan, set_unknown_process_name, ChangeDetector, FdScanReport,
+};
+#[cfg(feature = "tokio")]
+pub use scan::{scan_all_sockets_async, scan_open_ports_async};
+pub use wsl::{is_wsl, set_windows_scan};
@@ -0,0 +1,918 @@
+/// Módulo de los puertos escaneados y sus filtros.
+///
+/// [`PortInfo`] describe un socket (protocolo, puerto, dirección, dueño
+/// y estado); los filtros por protocolo, rango, estado, usuario y
+/// alcance y la paginación trabajan sobre listas de ellos, sin volver a
+/// escanear.
+use nix::unistd::{Uid, User};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::net::IpAddr;
+use std::sync::Mutex;
+
+/// Filtro de protocolo para los puertos escaneados
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProtocolFilter {
+    /// Mostrar todos los protocolos
+    All,
+    /// Solo puertos TCP
+    Tcp,
+    /// Solo puertos UDP
+    Udp,
+}
+
+/// Rango efímero por defecto del kernel Linux
+const DEFAULT_EPHEMERAL_RANGE: (u16, u16) = (32768, 60999);
+
+/// Filtro por rango de puertos para los puertos escaneados
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RangeFilter {
+    /// Todos los puertos
+    All,
+    /// Puertos privilegiados (1–1023)
+    WellKnown,
+    /// Puertos habituales de desarrollo (3000–9999)
+    Dev,
+    /// Rango efímero del kernel (`ip_local_port_range`)
+    Ephemeral,
+    /// Rango definido por el usuario (extremos incluidos)
+    Custom(u16, u16),
+}
+
+impl RangeFilter {
+    /// Extremos del rango (incluidos).
+    pub fn bounds(&self) -> (u16, u16) {
+        match self {
+            RangeFilter::All => (0, u16::MAX),
+            RangeFilter::WellKnown => (1, 1023),
+            RangeFilter::Dev => (3000, 9999),
+            RangeFilter::Ephemeral => ephemeral_range(),
+            RangeFilter::Custom(start, end) => (*start, *end),
+        }
+    }
+}
+
+/// Estado de un socket, con los nombres del kernel (`include/net/tcp_states.h`)
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum SocketState {
+    /// TCP esperando conexiones
+    #[default]
+    Listen,
+    /// UDP sin conectar (el equivalente a "escuchando")
+    Unconn,
+    /// Conexión activa
+    Established,
+    /// Conexión saliente esperando respuesta
+    SynSent,
+    /// Conexión entrante a medio establecer
+    SynRecv,
+    /// Cierre iniciado localmente
+    FinWait1,
+    /// Cierre local confirmado, esperando al otro extremo
+    FinWait2,
+    /// Conexión cerrada que retiene el puerto hasta 60 s
+    TimeWait,
+    /// Socket cerrado
+    Close,
+    /// El otro extremo cerró pero el proceso no
+    CloseWait,
+    /// Esperando la última confirmación del cierre
+    LastAck,
+    /// Ambos extremos cerrando a la vez
+    Closing,
+}
+
+impl SocketState {
+    /// Todos los estados, en el orden del kernel.
+    pub const ALL: [SocketState; 12] = [
+        SocketState::Listen,
+        SocketState::Unconn,
+        SocketState::Established,
+        SocketState::SynSent,
+        SocketState::SynRecv,
+        SocketState::FinWait1,
+        SocketState::FinWait2,
+        SocketState::TimeWait,
+        SocketState::Close,
+        SocketState::CloseWait,
+        SocketState::LastAck,
+        SocketState::Closing,
+    ];
+
+    /// Nombre del estado (ej: "TIME_WAIT").
+    pub fn label(&self) -> &'static str {
+        match self {
+            SocketState::Listen => "LISTEN",
+            SocketState::Unconn => "UNCONN",
+            SocketState::Established => "ESTABLISHED",
+            SocketState::SynSent => "SYN_SENT",
+            SocketState::SynRecv => "SYN_RECV",
+            SocketState::FinWait1 => "FIN_WAIT1",
+            SocketState::FinWait2 => "FIN_WAIT2",
+            SocketState::TimeWait => "TIME_WAIT",
+            SocketState::Close => "CLOSE",
+            SocketState::CloseWait => "CLOSE_WAIT",
+            SocketState::LastAck => "LAST_ACK",
+            SocketState::Closing => "CLOSING",
+        }
+    }
+
+    /// Estado de un puerto abierto del protocolo (LISTEN o UNCONN).
+    pub fn open_for(protocol: &str) -> SocketState {
+        if protocol == "udp" {
+            SocketState::Unconn
+        } else {
+            SocketState::Listen
+        }
+    }
+
+    /// Indica si el socket cuenta como puerto abierto.
+    pub fn is_open(&self) -> bool {
+        matches!(self, SocketState::Listen | SocketState::Unconn)
+    }
+
+    /// Interpreta el nombre de un estado, tanto el del kernel
+    /// ("TIME_WAIT") como el de `ss` ("TIME-WAIT", "ESTAB"), sin
+    /// distinguir mayúsculas.
+    pub fn parse(name: &str) -> Option<SocketState> {
+        let name = name.trim().to_uppercase().replace('-', "_");
+        match name.as_str() {
+            "ESTAB" => Some(SocketState::Established),
+            "FIN_WAIT_1" => Some(SocketState::FinWait1),
+            "FIN_WAIT_2" => Some(SocketState::FinWait2),
+            _ => SocketState::ALL.into_iter().find(|s| s.label() == name),
+        }
+    }
+
+    /// Estado de la columna `st` de /proc/net (hexadecimal).
+    ///
+    /// UDP reutiliza los códigos de TCP: 07 (CLOSE) es un socket sin
+    /// conectar y 01 uno conectado.
+    pub(crate) fn from_proc(protocol: &str, code: &str) -> Option<SocketState> {
+        let state = match u8::from_str_radix(code, 16).ok()? {
+            0x01 => SocketState::Established,
+            0x02 => SocketState::SynSent,
+            0x03 => SocketState::SynRecv,
+            0x04 => SocketState::FinWait1,
+            0x05 => SocketState::FinWait2,
+            0x06 => SocketState::TimeWait,
+            0x07 if protocol == "udp" => SocketState::Unconn,
+            0x07 => SocketState::Close,
+            0x08 => SocketState::CloseWait,
+            0x09 => SocketState::LastAck,
+            0x0A => SocketState::Listen,
+            0x0B => SocketState::Closing,
+            _ => return None,
+        };
+        Some(state)
+    }
+}
+
+/// Filtro por estado de los sockets escaneados
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StateFilter {
+    /// Cualquier estado
+    All,
+    /// Solo los sockets en un estado
+    Only(SocketState),
+}
+
+impl StateFilter {
+    /// Interpreta "all" o el nombre de un estado (ver [`SocketState::parse`]).
+    pub fn parse(name: &str) -> Option<StateFilter> {
+        if name.eq_ignore_ascii_case("all") {
+            return Some(StateFilter::All);
+        }
+        SocketState::parse(name).map(StateFilter::Only)
+    }
+
+    /// Indica si un estado pasa el filtro.
+    pub fn matches(&self, state: SocketState) -> bool {
+        match self {
+            StateFilter::All => true,
+            StateFilter::Only(only) => *only == state,
+        }
+    }
+}
+
+/// Filtro por usuario dueño de los sockets escaneados
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OwnerFilter {
+    /// Cualquier usuario
+    All,
+    /// Solo los sockets del usuario actual
+    Mine,
+    /// Solo los sockets de root
+    Root,
+    /// Solo los sockets de un UID
+    User(u32),
+}
+
+impl OwnerFilter {
+    /// Interpreta "all", "me", "root", un UID o un nombre de usuario.
+    pub fn parse(text: &str) -> Option<OwnerFilter> {
+        match text.trim() {
+            "all" => Some(OwnerFilter::All),
+            "me" | "mine" => Some(OwnerFilter::Mine),
+            "root" | "0" => Some(OwnerFilter::Root),
+            other => match other.parse::<u32>() {
+                Ok(uid) => Some(OwnerFilter::User(uid)),
+                Err(_) => User::from_name(other)
+                    .ok()
+                    .flatten()
+                    .map(|user| OwnerFilter::User(user.uid.as_raw())),
+            },
+        }
+    }
+
+    /// Indica si un socket pasa el filtro. Los sockets con dueño
+    /// desconocido (ej: de una sesión reproducida) solo pasan "Todos".
+    pub fn matches(&self, uid: Option<u32>) -> bool {
+        match self {
+            OwnerFilter::All => true,
+            OwnerFilter::Mine => uid == Some(Uid::current().as_raw()),
+            OwnerFilter::Root => uid == Some(0),
+            OwnerFilter::User(only) => uid == Some(*only),
+        }
+    }
+}
+
+/// Filtro por alcance de la dirección en la que escuchan los sockets
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScopeFilter {
+    /// Cualquier dirección
+    All,
+    /// Solo los que escuchan en loopback (127.0.0.0/8, ::1): locales
+    Loopback,
+    /// Solo los accesibles desde la red: "0.0.0.0", "::" o la dirección
+    /// de una interfaz
+    Exposed,
+}
+
+impl ScopeFilter {
+    /// Interpreta "all", "loopback" (o "local") y "exposed" (o "external").
+    pub fn parse(text: &str) -> Option<ScopeFilter> {
+        match text.trim().to_lowercase().as_str() {
+            "all" => Some(ScopeFilter::All),
+            "loopback" | "local" => Some(ScopeFilter::Loopback),
+            "exposed" | "external" => Some(ScopeFilter::Exposed),
+            _ => None,
+        }
+    }
+
+    /// Indica si un socket pasa el filtro.
+    ///
+    /// Los puertos internos de un contenedor no son ni una cosa ni la
+    /// otra: solo pasan "Todos".
+    pub fn matches(&self, port: &PortInfo) -> bool {
+        match self {
+            ScopeFilter::All => true,
+            ScopeFilter::Loopback => port.netns.is_none() && port.is_loopback(),
+            ScopeFilter::Exposed => port.netns.is_none() && !port.is_loopback(),
+        }
+    }
+}
+
+/// Filtro por nombre o línea de comandos del proceso dueño
+#[derive(Debug, Clone, Default)]
+pub enum ProcessFilter {
+    /// Cualquier proceso
+    #[default]
+    All,
+    /// Patrón con comodines `*` y `?` (ver [`glob_match`])
+    Glob(String),
+    /// Expresión regular, buscada en cualquier parte del texto
+    Regex(Regex),
+}
+
+impl ProcessFilter {
+    /// Filtro por una expresión regular.
+    ///
+    /// # Returns
+    /// `Err(String)` con el motivo si la expresión no es válida.
+    pub fn regex(pattern: &str) -> Result<ProcessFilter, String> {
+        Regex::new(pattern)
+            .map(ProcessFilter::Regex)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Indica si el dueño de un socket pasa el filtro: por su nombre o,
+    /// con PID, por su línea de comandos completa (`/proc/[pid]/cmdline`,
+    /// con los argumentos separados por espacios).
+    pub fn matches(&self, port: &PortInfo) -> bool {
+        if matches!(self, ProcessFilter::All) || self.matches_text(&port.process_name) {
+            return true;
+        }
+        if port.pid == 0 || !port.origin.is_linux() {
+            return false;
+        }
+        let Ok(cmdline) = fs::read(format!("/proc/{}/cmdline", port.pid)) else {
+            return false;
+        };
+        let cmdline: Vec<String> = cmdline
+            .split(|b| *b == 0)
+            .filter(|arg| !arg.is_empty())
+            .map(|arg| String::from_utf8_lossy(arg).into_owned())
+            .collect();
+        !cmdline.is_empty() && self.matches_text(&cmdline.join(" "))
+    }
+
+    /// Compara el filtro con un texto.
+    fn matches_text(&self, text: &str) -> bool {
+        match self {
+            ProcessFilter::All => true,
+            ProcessFilter::Glob(pattern) => glob_match(pattern, text),
+            ProcessFilter::Regex(regex) => regex.is_match(text),
+        }
+    }
+}
+
+/// Compara un texto con un patrón con comodines `*` (cualquier secuencia)
+/// y `?` (un carácter).
+///
+/// # Arguments
+/// * `pattern` - Patrón (ej: "postgres*")
+/// * `text` - Texto a comparar
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    // Algoritmo iterativo con retroceso al último '*' visto
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = star {
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+/// Nombres de usuario ya resueltos (UID → nombre)
+static USER_NAMES: Mutex<Option<HashMap<u32, String>>> = Mutex::new(None);
+
+/// Nombre del usuario de un UID, o el UID si no tiene nombre (ej: el
+/// usuario de un contenedor).
+pub fn user_name(uid: u32) -> String {
+    let mut cache = USER_NAMES.lock().unwrap_or_else(|e| e.into_inner());
+    cache
+        .get_or_insert_with(HashMap::new)
+        .entry(uid)
+        .or_insert_with(|| {
+            User::from_uid(Uid::from_raw(uid))
+                .ok()
+                .flatten()
+                .map(|user| user.name)
+                .unwrap_or_else(|| uid.to_string())
+        })
+        .clone()
+}
+
+/// Rango de puertos efímeros del kernel.
+///
+/// Se lee de `/proc/sys/net/ipv4/ip_local_port_range`; si no está
+/// disponible se usa el valor por defecto de Linux.
+pub fn ephemeral_range() -> (u16, u16) {
+    fs::read_to_string("/proc/sys/net/ipv4/ip_local_port_range")
+        .ok()
+        .and_then(|content| parse_port_range_pair(&content))
+        .unwrap_or(DEFAULT_EPHEMERAL_RANGE)
+}
+
+/// Interpreta el contenido de `ip_local_port_range` ("32768\t60999").
+fn parse_port_range_pair(content: &str) -> Option<(u16, u16)> {
+    let mut parts = content.split_whitespace().map(|p| p.parse::<u16>());
+    match (parts.next(), parts.next()) {
+        (Some(Ok(start)), Some(Ok(end))) if start <= end => Some((start, end)),
+        _ => None,
+    }
+}
+
+/// Cola de conexiones de un socket TCP en escucha: las columnas Recv-Q y
+/// Send-Q de `ss`, que en LISTEN son la cola de `accept()` y su tamaño.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct ListenQueue {
+    /// Conexiones ya establecidas que el proceso aún no aceptó
+    pub depth: u32,
+    /// Tamaño máximo de la cola (el backlog de `listen()`)
+    pub backlog: u32,
+}
+
+impl ListenQueue {
+    /// Indica si la cola está llena: el proceso acepta conexiones más
+    /// despacio de lo que llegan (o no las acepta) y el kernel empieza a
+    /// descartar las nuevas.
+    pub fn is_full(&self) -> bool {
+        self.depth > 0 && self.depth >= self.backlog
+    }
+}
+
+/// Sistema en el que está abierto un puerto: el propio o, en WSL2, el
+/// Windows anfitrión (ver `wsl`)
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum PortOrigin {
+    #[default]
+    Linux,
+    Windows,
+}
+
+impl PortOrigin {
+    /// Indica si el puerto es del propio Linux.
+    pub fn is_linux(&self) -> bool {
+        *self == PortOrigin::Linux
+    }
+}
+
+/// Información de un puerto abierto en el sistema (se lee de vuelta de
+/// las instantáneas guardadas en JSON).
+///
+/// `PortInfo::default()` es un puerto TCP/UDP vacío y en escucha, sin
+/// PID ni dueño conocido; sirve de base para rellenar solo los campos
+/// que importan (`..Default::default()`).
+#[derive(Debug, Clone, Default, Hash, Serialize, Deserialize)]
+pub struct PortInfo {
+    /// Protocolo del puerto (tcp, udp)
+    pub protocol: String,
+    /// Número del puerto
+    pub port: u16,
+    /// Dirección local donde escucha (ej: "0.0.0.0", "127.0.0.1", "::",
+    /// "fe80::1%eth0")
+    pub local_address: String,
+    /// PID del proceso que usa el puerto (0 si no se pudo determinar)
+    pub pid: u32,
+    /// Nombre del proceso asociado ("desconocido"/"unknown" según el
+    /// idioma si no se pudo determinar)
+    pub process_name: String,
+    /// Inode del namespace de red si no es el del host (puerto interno
+    /// de un contenedor)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub netns: Option<u64>,
+    /// UID del dueño del socket (de /proc/net; `None` si no se conoce)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub uid: Option<u32>,
+    /// Sin PID porque el modo seguro no recorrió todos los descriptores:
+    /// el dueño puede existir aunque no se muestre
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub approximate: bool,
+    /// Inicio del proceso dueño (segundos desde UNIX epoch, de
+    /// `/proc/[pid]/stat`; `None` sin PID o si ya terminó)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<u64>,
+    /// Cola de conexiones pendientes de un socket TCP en escucha (de
+    /// `ss`; `None` en los demás o si solo se vio en /proc/net)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub queue: Option<ListenQueue>,
+    /// Sistema en el que está abierto (en WSL2, también los de Windows)
+    #[serde(default, skip_serializing_if = "PortOrigin::is_linux")]
+    pub origin: PortOrigin,
+    /// Estado del socket (LISTEN o UNCONN salvo en `scan_all_sockets`)
+    pub state: SocketState,
+}
+
+impl PortInfo {
+    /// Indica si escucha en todas las interfaces ("0.0.0.0" o "::"),
+    /// es decir, si es accesible desde la red y no solo en local.
+    ///
+    /// Los puertos internos de un contenedor solo son accesibles desde
+    /// su namespace, así que nunca cuentan.
+    pub fn listens_on_all_interfaces(&self) -> bool {
+        self.netns.is_none() && matches!(self.local_address.as_str(), "0.0.0.0" | "::")
+    }
+
+    /// Indica si escucha solo en loopback (127.0.0.0/8, "::1" o
+    /// "::ffff:127.0.0.1"), es decir, si solo es accesible en local.
+    pub fn is_loopback(&self) -> bool {
+        // Sin la zona de las direcciones de enlace local ("fe80::1%eth0")
+        let address = self.local_address.split('%').next().unwrap_or_default();
+        match address.parse::<IpAddr>() {
+            Ok(IpAddr::V4(ip)) => ip.is_loopback(),
+            Ok(IpAddr::V6(ip)) => {
+                ip.is_loopback() || ip.to_ipv4_mapped().is_some_and(|ip| ip.is_loopback())
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Segundos que lleva en marcha el proceso dueño en el momento `now`
+    /// (segundos desde UNIX epoch).
+    pub fn uptime(&self, now: u64) -> Option<u64> {
+        self.started_at.map(|started| now.saturating_sub(started))
+    }
+}
+
+/// Formatea una duración en segundos para mostrarla: "45s", "5m",
+/// "2h 10m", "3d 4h". Solo las dos unidades más grandes.
+pub fn format_uptime(seconds: u64) -> String {
+    let (days, hours) = (seconds / 86_400, seconds / 3600 % 24);
+    let minutes = seconds / 60 % 60;
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}m", minutes)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+impl std::fmt::Display for PortInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Formato: "TCP 8080 (0.0.0.0) → node [PID 1234]"; los puertos de
+        // un contenedor llevan su namespace: "(0.0.0.0, netns 4026532301)"
+        let proto_upper = self.protocol.to_uppercase();
+        let address = match self.netns {
+            Some(netns) => format!("{}, netns {}", self.local_address, netns),
+            None => self.local_address.clone(),
+        };
+        if self.pid > 0 {
+            write!(
+                f,
+                "{} {} ({}) → {} [PID {}]",
+                proto_upper, self.port, address, self.process_name, self.pid
+            )
+        } else {
+            // Sin PID, al menos el dueño del socket: "→ desconocido [👤 postgres]".
+            // "≈": el dueño puede estar entre los descriptores no leídos
+            let owner = self
+                .uid
+                .map(|uid| format!(" [👤 {}]", user_name(uid)))
+                .unwrap_or_default();
+            let approximate = if self.approximate { " ≈" } else { "" };
+            write!(
+                f,
+                "{} {} ({}) → {}{}{}",
+                proto_upper, self.port, address, self.process_name, owner, approximate
+            )
+        }
+    }
+}
+
+/// Filtra una lista de puertos según los filtros de protocolo, rango y
+/// estado.
+///
+/// # Arguments
+/// * `ports` - Referencia a los puertos a filtrar
+/// * `filter` - Filtro de protocolo a aplicar
+/// * `range` - Filtro de rango de puertos a aplicar
+/// * `state` - Filtro de estado del socket a aplicar
+///
+/// # Returns
+/// Vector filtrado con solo los puertos que coinciden con los tres filtros.
+pub fn filter_ports(
+    ports: &[PortInfo],
+    filter: ProtocolFilter,
+    range: RangeFilter,
+    state: StateFilter,
+) -> Vec<PortInfo> {
+    let (start, end) = range.bounds();
+    ports
+        .iter()
+        .filter(|p| match filter {
+            ProtocolFilter::All => true,
+            ProtocolFilter::Tcp => p.protocol == "tcp",
+            ProtocolFilter::Udp => p.protocol == "udp",
+        })
+        .filter(|p| p.port >= start && p.port <= end)
+        .filter(|p| state.matches(p.state))
+        .cloned()
+        .collect()
+}
+
+/// Calcula el número total de páginas para la paginación.
+///
+/// # Arguments
+/// * `total_items` - Cantidad total de elementos
+/// * `page_size` - Elementos por página
+///
+/// # Returns
+/// Número total de páginas (mínimo 1).
+pub fn total_pages(total_items: usize, page_size: usize) -> usize {
+    if total_items == 0 || page_size == 0 {
+        return 1;
+    }
+    total_items.div_ceil(page_size)
+}
+
+/// Obtiene una página de puertos para mostrar en el menú.
+///
+/// # Arguments
+/// * `ports` - Lista completa de puertos (ya filtrados)
+/// * `page` - Número de página (base 0)
+/// * `page_size` - Cantidad de puertos por página
+///
+/// # Returns
+/// Slice del vector correspondiente a la página solicitada.
+pub fn get_page(ports: &[PortInfo], page: usize, page_size: usize) -> Vec<PortInfo> {
+    if page_size == 0 {
+        return Vec::new();
+    }
+    let start = page * page_size;
+    if start >= ports.len() {
+        return Vec::new();
+    }
+    let end = (start + page_size).min(ports.len());
+    ports[start..end].to_vec()
+}
+
+/// Filtra los puertos comprendidos en un rango (extremos incluidos).
+///
+/// # Arguments
+/// * `ports` - Puertos a filtrar
+/// * `start` - Primer puerto del rango
+/// * `end` - Último puerto del rango
+pub fn ports_in_range(ports: &[PortInfo], start: u16, end: u16) -> Vec<PortInfo> {
+    ports
+        .iter()
+        .filter(|p| p.port >= start && p.port <= end)
+        .cloned()
+        .collect()
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verifica el filtrado por protocolo
+    #[test]
+    fn test_filter_ports() {
+        let ports = vec![
+            PortInfo {
+                protocol: "tcp".into(),
+                port: 80,
+                local_address: "0.0.0.0".into(),
+                pid: 1,
+                process_name: "nginx".into(),
+                ..Default::default()
+            },
+            PortInfo {
+                protocol: "udp".into(),
+                port: 53,
+                local_address: "0.0.0.0".into(),
+                pid: 2,
+                process_name: "dnsmasq".into(),
+                state: SocketState::Unconn,
+                ..Default::default()
+            },
+        ];
+
+        assert_eq!(
+            filter_ports(
+                &ports,
+                ProtocolFilter::Tcp,
+                RangeFilter::All,
+                StateFilter::All
+            )
+            .len(),
+            1
+        );
+        assert_eq!(
+            filter_ports(
+                &ports,
+                ProtocolFilter::Udp,
+                RangeFilter::All,
+                StateFilter::All
+            )
+            .len(),
+            1
+        );
+        assert_eq!(
+            filter_ports(
+                &ports,
+                ProtocolFilter::All,
+                RangeFilter::All,
+                StateFilter::All
+            )
+            .len(),
+            2
+        );
+    }
+
+    /// Verifica el filtro por rangos combinado con el de protocolo
+    #[test]
+    fn test_filter_ports_by_range() {
+        let ports: Vec<PortInfo> = [("tcp", 22), ("tcp", 3000), ("udp", 5353), ("tcp", 45000)]
+            .iter()
+            .map(|&(protocol, port)| PortInfo {
+                protocol: protocol.into(),
+                port,
+                local_address: "0.0.0.0".into(),
+                pid: 1,
+                process_name: "x".into(),
+                state: SocketState::open_for(protocol),
+                ..Default::default()
+            })
+            .collect();
+        let ports_of = |filter, range| -> Vec<u16> {
+            filter_ports(&ports, filter, range, StateFilter::All)
+                .iter()
+                .map(|p| p.port)
+                .collect()
+        };
+
+        assert_eq!(
+            ports_of(ProtocolFilter::All, RangeFilter::WellKnown),
+            vec![22]
+        );
+        assert_eq!(
+            ports_of(ProtocolFilter::All, RangeFilter::Dev),
+            vec![3000, 5353]
+        );
+        assert_eq!(ports_of(ProtocolFilter::Tcp, RangeFilter::Dev), vec![3000]);
+        assert_eq!(
+            ports_of(ProtocolFilter::All, RangeFilter::Custom(40000, 50000)),
+            vec![45000]
+        );
+        assert_eq!(
+            parse_port_range_pair("32768\t60999\n"),
+            Some((32768, 60999))
+        );
+        assert_eq!(parse_port_range_pair("60999 32768"), None);
+    }
+
+    /// Verifica el filtro por usuario dueño del socket
+    #[test]
+    fn test_owner_filter() {
+        let me = Uid::current().as_raw();
+        assert!(OwnerFilter::Mine.matches(Some(me)));
+        assert!(OwnerFilter::Root.matches(Some(0)));
+        assert!(!OwnerFilter::Root.matches(None));
+        assert!(OwnerFilter::All.matches(None));
+        assert_eq!(OwnerFilter::parse("1000"), Some(OwnerFilter::User(1000)));
+        assert_eq!(OwnerFilter::parse("root"), Some(OwnerFilter::Root));
+        assert_eq!(OwnerFilter::parse("no-existe-este-usuario"), None);
+        assert_eq!(user_name(0), "root");
+    }
+
+    /// Verifica que un puerto sin PID muestre al menos el dueño del socket
+    #[test]
+    fn test_display_owner() {
+        let mut port = PortInfo {
+            protocol: "tcp".into(),
+            port: 5432,
+            local_address: "127.0.0.1".into(),
+            process_name: "desconocido".into(),
+            uid: Some(0),
+            ..Default::default()
+        };
+        assert_eq!(
+            port.to_string(),
+            "TCP 5432 (127.0.0.1) → desconocido [👤 root]"
+        );
+        port.pid = 880;
+        port.process_name = "postgres".into();
+        assert_eq!(
+            port.to_string(),
+            "TCP 5432 (127.0.0.1) → postgres [PID 880]"
+        );
+    }
+
+    /// Verifica el filtro por alcance (loopback o expuesto a la red)
+    #[test]
+    fn test_scope_filter() {
+        let port = |local_address: &str, netns: Option<u64>| PortInfo {
+            protocol: "tcp".into(),
+            port: 8080,
+            local_address: local_address.into(),
+            pid: 1,
+            process_name: "node".into(),
+            netns,
+            ..Default::default()
+        };
+        let scope_of = |p: &PortInfo| {
+            [ScopeFilter::Loopback, ScopeFilter::Exposed]
+                .into_iter()
+                .find(|scope| scope.matches(p))
+        };
+        assert_eq!(
+            scope_of(&port("127.0.0.1", None)),
+            Some(ScopeFilter::Loopback)
+        );
+        assert_eq!(
+            scope_of(&port("127.0.0.53", None)),
+            Some(ScopeFilter::Loopback)
+        );
+        assert_eq!(scope_of(&port("::1", None)), Some(ScopeFilter::Loopback));
+        assert_eq!(
+            scope_of(&port("::ffff:127.0.0.1", None)),
+            Some(ScopeFilter::Loopback)
+        );
+        assert_eq!(scope_of(&port("0.0.0.0", None)), Some(ScopeFilter::Exposed));
+        assert_eq!(scope_of(&port("::", None)), Some(ScopeFilter::Exposed));
+        assert_eq!(
+            scope_of(&port("fe80::1%eth0", None)),
+            Some(ScopeFilter::Exposed)
+        );
+        assert_eq!(scope_of(&port("0.0.0.0", Some(4026532301))), None);
+        assert_eq!(ScopeFilter::parse("Local"), Some(ScopeFilter::Loopback));
+        assert_eq!(ScopeFilter::parse("lan"), None);
+    }
+
+    /// Verifica el filtro por proceso: comodines y expresiones regulares
+    /// sobre el nombre y la línea de comandos
+    #[test]
+    fn test_process_filter() {
+        assert!(glob_match("postgres*", "postgres"));
+        assert!(glob_match("postgres*", "postgres-14"));
+        assert!(glob_match("*node*", "nodemon"));
+        assert!(glob_match("n?de", "node"));
+        assert!(!glob_match("node", "nodemon"));
+        assert!(!glob_match("redis*", "postgres"));
+
+        let port = |pid: u32, process_name: &str| PortInfo {
+            protocol: "tcp".into(),
+            port: 5173,
+            local_address: "127.0.0.1".into(),
+            pid,
+            process_name: process_name.into(),
+            ..Default::default()
+        };
+        let dev = ProcessFilter::regex("^(node|vite|webpack)").unwrap();
+        assert!(dev.matches(&port(0, "vite")));
+        assert!(!dev.matches(&port(0, "nginx")));
+        assert!(ProcessFilter::Glob("node*".into()).matches(&port(0, "nodemon")));
+        assert!(ProcessFilter::All.matches(&port(0, "nginx")));
+        assert!(ProcessFilter::regex("^(node").is_err());
+
+        // La línea de comandos de este mismo test lleva el nombre del crate
+        let by_cmdline = ProcessFilter::regex("portslayer_core").unwrap();
+        assert!(by_cmdline.matches(&port(std::process::id(), "otro")));
+        assert!(!by_cmdline.matches(&port(0, "otro")));
+    }
+
+    /// Verifica el filtrado por rango de puertos (extremos incluidos)
+    #[test]
+    fn test_ports_in_range() {
+        let ports: Vec<PortInfo> = [2999, 3000, 3500, 3999, 4000]
+            .iter()
+            .map(|&port| PortInfo {
+                protocol: "tcp".into(),
+                port,
+                local_address: "0.0.0.0".into(),
+                pid: port as u32,
+                process_name: "node".into(),
+                ..Default::default()
+            })
+            .collect();
+
+        let in_range: Vec<u16> = ports_in_range(&ports, 3000, 3999)
+            .iter()
+            .map(|p| p.port)
+            .collect();
+        assert_eq!(in_range, vec![3000, 3500, 3999]);
+    }
+
+    /// Verifica la paginación
+    #[test]
+    fn test_pagination() {
+        let ports: Vec<PortInfo> = (1..=25)
+            .map(|i| PortInfo {
+                protocol: "tcp".into(),
+                port: i as u16,
+                local_address: "0.0.0.0".into(),
+                pid: i,
+                process_name: format!("proc{}", i),
+                ..Default::default()
+            })
+            .collect();
+
+        // 25 items, 10 por página = 3 páginas
+        assert_eq!(total_pages(25, 10), 3);
+
+        // Página 0: puertos 1-10
+        let page0 = get_page(&ports, 0, 10);
+        assert_eq!(page0.len(), 10);
+        assert_eq!(page0[0].port, 1);
+
+        // Página 2: puertos 21-25
+        let page2 = get_page(&ports, 2, 10);
+        assert_eq!(page2.len(), 5);
+        assert_eq!(page2[0].port, 21);
+
+        // Página fuera de rango
+        let page_oob = get_page(&ports, 5, 10);
+        assert!(page_oob.is_empty());
+    }
+}
@@ -0,0 +1,202 @@
+/// Módulo de escaneo de puertos de otras máquinas.
+///
+/// El resto del motor mira los sockets de este host; [`scan_remote`]
+/// responde "qué hay abierto en mi servidor de staging" con un escaneo
+/// TCP connect: intenta conectar a cada puerto del rango y cuenta como
+/// abiertos los que aceptan. No necesita privilegios (no usa paquetes
+/// crudos), pero el otro extremo ve las conexiones en sus logs.
+///
+/// Las conexiones se reparten entre un número limitado de hilos
+/// ([`RemoteOptions::concurrency`]) y cada una espera como mucho
+/// [`RemoteOptions::timeout`]: así un host que descarta los paquetes
+/// tarda lo mismo que uno que los rechaza, sin abrir miles de sockets.
+use serde::Serialize;
+use std::net::{IpAddr, SocketAddr, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Conexiones simultáneas por defecto
+pub const DEFAULT_CONCURRENCY: usize = 64;
+
+/// Conexiones simultáneas como máximo
+pub const MAX_CONCURRENCY: usize = 1024;
+
+/// Espera máxima por defecto de cada conexión
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// Límites del escaneo
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RemoteOptions {
+    /// Conexiones simultáneas (de 1 a [`MAX_CONCURRENCY`])
+    pub concurrency: usize,
+    /// Espera máxima de cada conexión
+    pub timeout: Duration,
+}
+
+impl Default for RemoteOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: DEFAULT_CONCURRENCY,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+}
+
+/// Puerto que aceptó la conexión
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RemotePort {
+    pub port: u16,
+    /// Tiempo hasta aceptar la conexión (ms)
+    pub latency_ms: u64,
+}
+
+/// Resultado del escaneo de un host
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RemoteScan {
+    /// Host tal como se pidió
+    pub host: String,
+    /// Dirección a la que se conectó
+    pub address: IpAddr,
+    /// Rango escaneado (extremos incluidos)
+    pub start: u16,
+    pub end: u16,
+    /// Puertos abiertos, de menor a mayor
+    pub open: Vec<RemotePort>,
+    /// Duración del escaneo (ms)
+    pub elapsed_ms: u64,
+}
+
+/// Escanea un rango de puertos TCP de otro host con los límites por
+/// defecto (ver [`scan_remote_with`]).
+pub fn scan_remote(host: &str, range: (u16, u16)) -> Result<RemoteScan, String> {
+    scan_remote_with(host, range, RemoteOptions::default())
+}
+
+/// Escanea un rango de puertos TCP de otro host.
+///
+/// # Returns
+/// `Err(String)` si el rango no es válido o el host no se resuelve.
+pub fn scan_remote_with(
+    host: &str,
+    (start, end): (u16, u16),
+    options: RemoteOptions,
+) -> Result<RemoteScan, String> {
+    if start == 0 || start > end {
+        return Err(format!("Rango inválido: {}-{}", start, end));
+    }
+    let address = resolve(host)?;
+    let workers = options
+        .concurrency
+        .clamp(1, MAX_CONCURRENCY)
+        .min(usize::from(end - start) + 1);
+    log::info!(
+        "Escaneando {} ({}) puertos {}-{} con {} conexiones simultáneas",
+        host,
+        address,
+        start,
+        end,
+        workers
+    );
+
+    let began = Instant::now();
+    let next = AtomicU32::new(u32::from(start));
+    let open = Mutex::new(Vec::new());
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                let port = next.fetch_add(1, Ordering::Relaxed);
+                if port > u32::from(end) {
+                    break;
+                }
+                let port = port as u16;
+                if let Some(latency) = probe(SocketAddr::new(address, port), options.timeout) {
+                    let latency_ms = latency.as_millis() as u64;
+                    open.lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .push(RemotePort { port, latency_ms });
+                }
+            });
+        }
+    });
+
+    let mut open = open.into_inner().unwrap_or_else(|e| e.into_inner());
+    open.sort_by_key(|p| p.port);
+    let elapsed_ms = began.elapsed().as_millis() as u64;
+    log::info!(
+        "Escaneo de {} terminado: {} abiertos en {} ms",
+        host,
+        open.len(),
+        elapsed_ms
+    );
+    Ok(RemoteScan {
+        host: host.to_string(),
+        address,
+        start,
+        end,
+        open,
+        elapsed_ms,
+    })
+}
+
+/// Resuelve un host (nombre o IP), prefiriendo IPv4.
+fn resolve(host: &str) -> Result<IpAddr, String> {
+    // "[::1]" se acepta como en las URLs
+    let bare = host.trim_start_matches('[').trim_end_matches(']');
+    let addresses: Vec<SocketAddr> = (bare, 0)
+        .to_socket_addrs()
+        .map_err(|e| format!("No se pudo resolver {}: {}", host, e))?
+        .collect();
+    addresses
+        .iter()
+        .find(|a| a.is_ipv4())
+        .or(addresses.first())
+        .map(|a| a.ip())
+        .ok_or_else(|| format!("No se pudo resolver {}", host))
+}
+
+/// Intenta conectar a un puerto.
+///
+/// # Returns
+/// El tiempo hasta aceptar, o `None` si rechazó o no respondió a tiempo.
+fn probe(address: SocketAddr, timeout: Duration) -> Option<Duration> {
+    let began = Instant::now();
+    TcpStream::connect_timeout(&address, timeout)
+        .ok()
+        .map(|_| began.elapsed())
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    /// Verifica que se encuentran los puertos que escuchan en loopback
+    #[test]
+    fn test_scan_remote() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let start = port.saturating_sub(5).max(1);
+        let end = port.saturating_add(5);
+
+        let scan = scan_remote("127.0.0.1", (start, end)).unwrap();
+        assert_eq!(scan.address, IpAddr::from([127, 0, 0, 1]));
+        assert!(scan.open.iter().any(|p| p.port == port));
+
+        drop(listener);
+        let options = RemoteOptions {
+            concurrency: 1,
+            timeout: Duration::from_millis(200),
+        };
+        let scan = scan_remote_with("localhost", (port, port), options).unwrap();
+        assert!(scan.open.is_empty());
+
+        assert!(scan_remote("127.0.0.1", (10, 5)).is_err());
+        assert!(scan_remote("127.0.0.1", (0, 5)).is_err());
+    }
+}
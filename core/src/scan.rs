@@ -0,0 +1,1787 @@
+/// Módulo de escaneo de puertos de red.
+///
+/// Usa múltiples fuentes para garantizar la detección completa:
+/// 1. Comando `ss` (fuente principal, incluye nombres de procesos)
+/// 2. Archivos `/proc/net/tcp*` y `/proc/net/udp*` (fallback, detecta
+///    puertos de Docker y otros que `ss` sin permisos no muestra)
+/// 3. Con [`set_namespace_scan`], los `/proc/<pid>/net/*` de cada
+///    namespace de red ajeno (contenedores), cuyos sockets no aparecen
+///    en los del host
+/// 4. En WSL2, con `wsl::set_windows_scan`, los del Windows anfitrión
+///
+/// Consulta las fuentes a la vez, las combina y elimina duplicados para
+/// ofrecer una vista completa de todos los puertos abiertos en el sistema.
+use serde::Serialize;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::net::{Ipv4Addr, Ipv6Addr, TcpListener};
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Mutex, OnceLock, RwLock};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::helper::{self, Request};
+use crate::ports::{ListenQueue, PortInfo, PortOrigin, SocketState};
+use crate::sources::{self, SOURCE_TIMEOUT};
+use crate::wsl;
+
+/// Archivos /proc/net a leer con su protocolo correspondiente
+const PROC_NET_FILES: [(&str, &str); 4] = [
+    ("/proc/net/tcp", "tcp"),
+    ("/proc/net/tcp6", "tcp"),
+    ("/proc/net/udp", "udp"),
+    ("/proc/net/udp6", "udp"),
+];
+
+/// Escanear también los namespaces de red ajenos (ver `set_namespace_scan`)
+static NAMESPACE_SCAN: AtomicBool = AtomicBool::new(false);
+
+/// Descriptores abiertos en todo el sistema (`/proc/sys/fs/file-nr`) a
+/// partir de los cuales `/proc/*/fd` se recorre en modo seguro
+const SAFE_MODE_FDS: u64 = 200_000;
+
+/// Descriptores que se leen como máximo de cada proceso en modo seguro
+const MAX_FDS_PER_PROCESS: usize = 4096;
+
+/// Tiempo máximo dedicado a los descriptores de un proceso en modo seguro
+const PROCESS_FD_BUDGET: Duration = Duration::from_millis(100);
+
+/// Tiempo máximo para recorrer `/proc/*/fd` en modo seguro
+const FD_SCAN_BUDGET: Duration = Duration::from_secs(2);
+
+/// Hilos como máximo para recorrer `/proc` en paralelo
+const MAX_SCAN_THREADS: usize = 8;
+
+/// Informe del último recorrido de `/proc/*/fd`
+static LAST_FD_SCAN: Mutex<Option<FdScanReport>> = Mutex::new(None);
+
+/// Sockets de cada proceso leídos en recorridos anteriores (ver
+/// `build_inode_pid_map`)
+static FD_CACHE: Mutex<Option<HashMap<u32, CachedFds>>> = Mutex::new(None);
+
+/// Tiempo tras el que los descriptores de un proceso se vuelven a leer
+/// aunque no haya cambiado su huella
+const FD_CACHE_MAX_AGE: Duration = Duration::from_secs(60);
+
+/// Comprobaciones sin cambios tras las que se fuerza un escaneo completo
+/// (refresca conexiones y nombres de procesos aunque no cambien los
+/// sockets en escucha)
+pub const FULL_SCAN_EVERY: u32 = 6;
+
+/// Nombre de los procesos que no se pueden leer (ver
+/// [`set_unknown_process_name`])
+static UNKNOWN_PROCESS: RwLock<&str> = RwLock::new("desconocido");
+
+/// Cambia el nombre que reciben los procesos desconocidos (ej: "unknown"
+/// en una interfaz en inglés).
+pub fn set_unknown_process_name(name: &'static str) {
+    if let Ok(mut unknown) = UNKNOWN_PROCESS.write() {
+        *unknown = name;
+    }
+}
+
+/// Nombre de los procesos desconocidos.
+fn unknown_process_name() -> &'static str {
+    UNKNOWN_PROCESS.read().map_or("desconocido", |name| *name)
+}
+
+// ─────────────────────────────────────────────────────────────
+// Escaneo principal: combina ss + /proc/net para cobertura total
+// ─────────────────────────────────────────────────────────────
+
+/// Escanea los puertos TCP y UDP abiertos en el sistema.
+///
+/// Usa dos fuentes de datos para cobertura completa:
+/// - `ss -tlnpH` / `ss -ulnpH` → detecta PIDs si hay permisos
+/// - `/proc/net/tcp*` y `/proc/net/udp*` → detecta TODOS los sockets
+///   incluyendo Docker, que `ss` sin sudo no muestra con PID
+///
+/// Los resultados se combinan priorizando la info de `ss` (tiene PID)
+/// y complementando con `/proc/net` para puertos sin PID visible. Los
+/// puertos de otros namespaces de red (si se activó su escaneo) se
+/// añaden aparte: el 80 de un contenedor no es el 80 del host.
+///
+/// # Returns
+/// Vector ordenado por puerto con la información de cada puerto abierto.
+pub fn scan_open_ports() -> Vec<PortInfo> {
+    scan_sockets(false)
+}
+
+/// Escanea los sockets TCP y UDP en cualquier estado (TIME_WAIT,
+/// CLOSE_WAIT, ESTABLISHED...), uno por puerto y estado.
+///
+/// Sirve para diagnosticar un puerto "ocupado" sin nadie escuchando:
+/// un TIME_WAIT lo retiene aunque el proceso ya no exista.
+pub fn scan_all_sockets() -> Vec<PortInfo> {
+    scan_sockets(true)
+}
+
+/// [`scan_open_ports`] para programas async: el escaneo (procesos,
+/// lecturas de `/proc`) corre en el pool de hilos bloqueantes de tokio,
+/// sin parar el hilo que lo espera. Feature `tokio`.
+///
+/// # Returns
+/// `Err(String)` si el escaneo entró en pánico o se canceló el runtime.
+#[cfg(feature = "tokio")]
+pub async fn scan_open_ports_async() -> Result<Vec<PortInfo>, String> {
+    tokio::task::spawn_blocking(scan_open_ports)
+        .await
+        .map_err(|e| format!("El escaneo no terminó: {}", e))
+}
+
+/// [`scan_all_sockets`] para programas async (ver
+/// [`scan_open_ports_async`]). Feature `tokio`.
+#[cfg(feature = "tokio")]
+pub async fn scan_all_sockets_async() -> Result<Vec<PortInfo>, String> {
+    tokio::task::spawn_blocking(scan_all_sockets)
+        .await
+        .map_err(|e| format!("El escaneo no terminó: {}", e))
+}
+
+/// Escaneo combinado de `ss` y /proc/net (ver [`scan_open_ports`]).
+fn scan_sockets(all_states: bool) -> Vec<PortInfo> {
+    let began = Instant::now();
+    // Las fuentes no dependen entre sí: los dos `ss` y el recorrido de
+    // /proc se lanzan a la vez y el escaneo tarda lo que la más lenta
+    let flags = if all_states {
+        [("-tanpH", "tcp"), ("-uanpH", "udp")]
+    } else {
+        [("-tlnpH", "tcp"), ("-ulnpH", "udp")]
+    };
+    let (ss_outputs, proc_ports, windows_ports) = std::thread::scope(|scope| {
+        let ss: Vec<_> = flags
+            .iter()
+            .map(|(flag, protocol)| scope.spawn(move || (execute_ss_command(flag), *protocol)))
+            .collect();
+        let windows =
+            wsl::windows_scan().then(|| scope.spawn(move || wsl::scan_windows_ports(all_states)));
+        let proc_ports = scan_proc_net_ports(all_states);
+        let ss_outputs: Vec<(Option<String>, &str)> = ss
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|e| std::panic::resume_unwind(e))
+            })
+            .collect();
+        let windows_ports = windows
+            .map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|e| std::panic::resume_unwind(e))
+            })
+            .unwrap_or_default();
+        (ss_outputs, proc_ports, windows_ports)
+    });
+
+    // Fase 1: Resultados de ss (incluye PIDs cuando hay permisos)
+    type SocketKey = (String, u16, Option<u64>, SocketState);
+    let mut ports_map: HashMap<SocketKey, PortInfo> = HashMap::new();
+    for (output, protocol) in ss_outputs {
+        if let Some(raw_output) = output {
+            let parsed = parse_ss_output(&raw_output, protocol);
+            for port_info in parsed {
+                let key = (
+                    port_info.protocol.clone(),
+                    port_info.port,
+                    None,
+                    port_info.state,
+                );
+                // Priorizar entradas con PID conocido sobre las sin PID
+                ports_map
+                    .entry(key)
+                    .and_modify(|existing| {
+                        if existing.pid == 0 && port_info.pid > 0 {
+                            *existing = port_info.clone();
+                        }
+                    })
+                    .or_insert(port_info);
+            }
+        }
+    }
+
+    // Fase 2: Complementar con /proc/net (y los namespaces de los
+    // contenedores) para puertos que ss no muestra
+    for port_info in proc_ports {
+        let key = (
+            port_info.protocol.clone(),
+            port_info.port,
+            port_info.netns,
+            port_info.state,
+        );
+        // Solo insertar si no existe ya (ss tiene mejor info), pero `ss`
+        // no muestra el UID del socket: se toma de /proc/net
+        let uid = port_info.uid;
+        ports_map
+            .entry(key)
+            .and_modify(|existing| existing.uid = existing.uid.or(uid))
+            .or_insert(port_info);
+    }
+
+    // Convertir a vector y ordenar por número de puerto; los de Windows
+    // van aparte aunque coincida el puerto
+    let mut ports: Vec<PortInfo> = ports_map.into_values().chain(windows_ports).collect();
+    ports.sort_by_key(|p| (p.port, p.protocol.clone(), p.origin, p.netns, p.state));
+
+    // Hora de inicio de los dueños (un proceso suele tener varios puertos)
+    let mut started: HashMap<u32, Option<u64>> = HashMap::new();
+    for port in ports.iter_mut().filter(|p| p.pid > 0) {
+        port.started_at = *started
+            .entry(port.pid)
+            .or_insert_with(|| process_started_at(port.pid));
+    }
+
+    log::info!(
+        duration_ms = began.elapsed().as_millis() as u64,
+        ports = ports.len();
+        "Escaneo completado: {} puertos encontrados",
+        ports.len()
+    );
+    ports
+}
+
+// ─────────────────────────────────────────────────────────────
+// Fuente 1: Comando `ss` del sistema
+// ─────────────────────────────────────────────────────────────
+
+/// Fuente `ss` con `sudo -n` (ve los PIDs de todos los procesos)
+const SUDO_SS_SOURCE: &str = "sudo ss";
+
+/// Fuente `ss` sin privilegios
+const SS_SOURCE: &str = "ss";
+
+/// Ejecuta el comando `ss` con los flags indicados.
+///
+/// Si hay un helper privilegiado en marcha (ver `helper`), se lo pide a
+/// él. Si no, intenta con `sudo -n` (sin password) para ver PIDs de
+/// todos los procesos, y si falla, ejecuta sin sudo como fallback.
+///
+/// Cada intento tiene un tiempo máximo; las variantes que no responden
+/// varias veces seguidas quedan en cuarentena (ver `sources`) y el
+/// escaneo sigue solo con `/proc/net`.
+///
+/// # Arguments
+/// * `flags` - Flags para el comando ss (ej: "-tlnpH")
+///
+/// # Returns
+/// `Some(String)` con la salida del comando, o `None` si falla.
+pub fn execute_ss_command(flags: &str) -> Option<String> {
+    match helper::request(&Request::Ss(flags.to_string())) {
+        Some(Ok(output)) => return Some(output),
+        Some(Err(e)) => log::warn!("El helper no pudo ejecutar ss {}: {}", flags, e),
+        None => {}
+    }
+
+    // Intentar primero con sudo para ver PIDs de todos los procesos.
+    // Que sudo pida contraseña es lo normal; solo cuenta como fallo
+    // que no se pueda lanzar o que no responda.
+    if sources::available(SUDO_SS_SOURCE) {
+        let mut sudo = Command::new("sudo");
+        sudo.args(["-n", "ss", flags]);
+        match sources::output_with_timeout(&mut sudo, SOURCE_TIMEOUT) {
+            Ok(output) if output.status.success() => {
+                sources::record_success(SUDO_SS_SOURCE);
+                return String::from_utf8(output.stdout).ok();
+            }
+            Ok(_) => sources::record_success(SUDO_SS_SOURCE),
+            Err(e) => sources::record_failure(SUDO_SS_SOURCE, &e),
+        }
+    }
+
+    // Fallback sin sudo (solo verá procesos propios)
+    if !sources::available(SS_SOURCE) {
+        return None;
+    }
+    log::warn!("Ejecutando ss sin sudo - algunos PIDs no serán visibles");
+    let mut ss = Command::new("ss");
+    ss.arg(flags);
+    match sources::output_with_timeout(&mut ss, SOURCE_TIMEOUT) {
+        Ok(fallback) => {
+            sources::record_success(SS_SOURCE);
+            String::from_utf8(fallback.stdout).ok()
+        }
+        Err(e) => {
+            sources::record_failure(SS_SOURCE, &e);
+            None
+        }
+    }
+}
+
+/// Parsea la salida del comando `ss` para extraer información de puertos.
+///
+/// Ahora acepta líneas SIN información de proceso (users:((...))),
+/// asignando PID=0 y nombre="desconocido" para esos puertos.
+/// Esto es crucial para detectar puertos de Docker y otros servicios
+/// del sistema que no muestran PID sin privilegios de root.
+///
+/// # Arguments
+/// * `output` - Salida cruda del comando ss
+/// * `protocol` - Protocolo a asignar ("tcp" o "udp")
+///
+/// # Returns
+/// Vector con la información parseada de cada puerto.
+fn parse_ss_output(output: &str, protocol: &str) -> Vec<PortInfo> {
+    output
+        .lines()
+        .filter_map(|line| parse_single_ss_line(line, protocol))
+        .collect()
+}
+
+/// Parsea una línea individual de la salida de `ss`.
+///
+/// Extrae el puerto y la dirección local. Si hay sección `users:((...))`
+/// extrae PID y nombre del proceso; si no, usa valores por defecto.
+///
+/// Formato esperado de ss -tlnpH:
+/// ```text
+/// LISTEN  0  128  0.0.0.0:8080  0.0.0.0:*  users:(("node",pid=1234,fd=5))
+/// LISTEN  0  4096       *:8069        *:*
+/// ```
+///
+/// # Arguments
+/// * `line` - Línea individual de la salida de ss
+/// * `protocol` - Protocolo a asignar
+///
+/// # Returns
+/// `Some(PortInfo)` si se pudo parsear exitosamente, `None` si la línea
+/// es vacía o no contiene información de puerto válida.
+fn parse_single_ss_line(line: &str, protocol: &str) -> Option<PortInfo> {
+    let line = line.trim();
+    if line.is_empty() {
+        return None;
+    }
+
+    // Extraer dirección local y puerto
+    let (local_address, port) = extract_address_and_port(line)?;
+
+    // Extraer PID y nombre del proceso (OPCIONAL - puede no existir)
+    let (pid, process_name) =
+        extract_process_info(line).unwrap_or_else(|| (0, unknown_process_name().to_string()));
+
+    // Primera columna: estado ("LISTEN", "UNCONN", "TIME-WAIT"...)
+    let mut columns = line.split_whitespace();
+    let state = columns
+        .next()
+        .and_then(SocketState::parse)
+        .unwrap_or_else(|| SocketState::open_for(protocol));
+
+    // Recv-Q y Send-Q: en LISTEN son la cola de accept() y su tamaño (en
+    // los demás estados, bytes sin leer y sin confirmar)
+    let queue = match (columns.next(), columns.next()) {
+        (Some(depth), Some(backlog)) if state == SocketState::Listen => {
+            match (depth.parse(), backlog.parse()) {
+                (Ok(depth), Ok(backlog)) => Some(ListenQueue { depth, backlog }),
+                _ => None,
+            }
+        }
+        _ => None,
+    };
+
+    Some(PortInfo {
+        protocol: protocol.to_string(),
+        port,
+        local_address,
+        pid,
+        process_name,
+        netns: None,
+        uid: None,
+        approximate: false,
+        started_at: None,
+        queue,
+        origin: PortOrigin::Linux,
+        state,
+    })
+}
+
+/// Extrae la dirección local y el número de puerto de una línea de `ss`.
+///
+/// Maneja múltiples formatos de dirección:
+/// - IPv4: `0.0.0.0:8080`, `127.0.0.1:5432`
+/// - IPv6: `[::]:8080`, `[::1]:631`, `[fe80::1]%eth0:22`
+/// - Wildcard: `*:8069`
+///
+/// # Arguments
+/// * `line` - Línea de ss con la información del socket
+///
+/// # Returns
+/// Tupla `(dirección_local, puerto)` o `None` si no se puede extraer.
+fn extract_address_and_port(line: &str) -> Option<(String, u16)> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+
+    // Formato ss: [Estado, RecvQ, SendQ, DirLocal, DirRemota, ...]
+    // DirLocal puede ser: "0.0.0.0:8080", "[::]:8080", "*:8069",
+    //                     "127.0.0.53%lo:53"
+    for part in &parts {
+        // Identificar campos que parecen direcciones de socket
+        let is_address = part.contains('.')
+            || part.contains('[')
+            || part.contains("::")
+            || part.starts_with('*');
+
+        if !is_address {
+            continue;
+        }
+
+        // Extraer dirección y puerto después del último ':'
+        if let Some(colon_pos) = part.rfind(':') {
+            let addr_part = &part[..colon_pos];
+            let port_str = &part[colon_pos + 1..];
+
+            // Ignorar el campo de dirección remota (contiene '*')
+            if port_str == "*" {
+                continue;
+            }
+
+            if let Ok(port) = port_str.parse::<u16>() {
+                if port > 0 {
+                    // Limpiar la dirección para presentación
+                    let clean_addr = clean_address(addr_part);
+                    return Some((clean_addr, port));
+                }
+            }
+        }
+    }
+
+    None
+}
+
+/// Limpia una dirección de red para presentación legible.
+///
+/// Remueve los corchetes IPv6 y deja las direcciones IPv6 en notación
+/// comprimida estándar, conservando la zona (`[fe80::1]%eth0` →
+/// `fe80::1%eth0`), que distingue las de enlace local de cada interfaz.
+/// En IPv4 el sufijo de interfaz se quita (`127.0.0.53%lo`).
+///
+/// # Arguments
+/// * `addr` - Dirección cruda del socket
+///
+/// # Returns
+/// String con la dirección limpia para mostrar al usuario.
+pub fn clean_address(addr: &str) -> String {
+    // ss escribe la zona tras los corchetes: "[fe80::1]%eth0"
+    let (host, scope) = match addr.split_once('%') {
+        Some((host, scope)) => (host, Some(scope.trim_end_matches(']'))),
+        None => (addr, None),
+    };
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+
+    match (host.parse::<Ipv6Addr>(), scope) {
+        (Ok(ip), Some(scope)) => format!("{}%{}", ip, scope),
+        (Ok(ip), None) => ip.to_string(),
+        (Err(_), _) if host == "*" => "0.0.0.0".to_string(),
+        (Err(_), _) => host.to_string(),
+    }
+}
+
+/// Extrae el PID y nombre del proceso de la sección "users:" de ss.
+///
+/// Busca el patrón: `users:(("nombre",pid=1234,fd=5))`
+///
+/// # Arguments
+/// * `line` - Línea completa de ss
+///
+/// # Returns
+/// Tupla (PID, nombre_proceso) si se encuentra, `None` si la línea
+/// no contiene información de proceso.
+pub fn extract_process_info(line: &str) -> Option<(u32, String)> {
+    // Buscar la sección users:((...)
+    let users_start = line.find("users:((")?;
+    let users_section = &line[users_start..];
+
+    // Extraer el nombre del proceso entre comillas: (("nombre"
+    let name_start = users_section.find("((\"")? + 3;
+    let name_end = users_section[name_start..].find('"')? + name_start;
+    let process_name = users_section[name_start..name_end].to_string();
+
+    // Extraer el PID del patrón pid=NUMERO
+    let pid_marker = "pid=";
+    let pid_start = users_section.find(pid_marker)? + pid_marker.len();
+    let pid_end = users_section[pid_start..]
+        .find(|c: char| !c.is_ascii_digit())
+        .map(|i| i + pid_start)
+        .unwrap_or(users_section.len());
+    let pid: u32 = users_section[pid_start..pid_end].parse().ok()?;
+
+    Some((pid, process_name))
+}
+
+// ─────────────────────────────────────────────────────────────
+// Fuente 2: /proc/net/* (detecta Docker y sockets sin PID visible)
+// ─────────────────────────────────────────────────────────────
+
+/// Escanea puertos desde los archivos /proc/net/ del kernel.
+///
+/// Lee `/proc/net/tcp`, `/proc/net/tcp6`, `/proc/net/udp`, `/proc/net/udp6`
+/// para encontrar sockets en estado LISTEN (0x0A para TCP) o abiertos (UDP),
+/// o en cualquier estado con `all_states`. Esta fuente siempre está
+/// disponible y detecta TODOS los sockets, incluyendo los de Docker,
+/// independientemente de los permisos.
+///
+/// Los inodes de los sockets son únicos en todo el sistema, así que el
+/// mismo mapa inode→PID resuelve también los puertos de los contenedores.
+///
+/// # Returns
+/// Vector con los puertos encontrados. PID y nombre serán 0/"desconocido"
+/// a menos que se pueda determinar escaneando /proc/[pid]/fd; si el modo
+/// seguro dejó descriptores sin leer, esos puertos se marcan como
+/// aproximados.
+fn scan_proc_net_ports(all_states: bool) -> Vec<PortInfo> {
+    let mut ports: Vec<PortInfo> = Vec::new();
+
+    // Con muchos namespaces hay muchas tablas: se leen en paralelo. Se
+    // leen antes que los descriptores para saber qué sockets siguen vivos
+    let sources = proc_net_sources();
+    let contents = parallel_map(&sources, scan_threads(), |(_, path, _)| {
+        fs::read_to_string(path).unwrap_or_default()
+    });
+    let live = listed_inodes(&contents);
+
+    // Mapeo inode→PID para intentar resolver procesos
+    let (inode_to_pid, report) = build_inode_pid_map(&live);
+    let approximate = !report.complete();
+    if let Ok(mut last) = LAST_FD_SCAN.lock() {
+        *last = Some(report);
+    }
+
+    let tables: Vec<(&String, &str)> = contents
+        .iter()
+        .zip(&sources)
+        .map(|(content, (_, _, protocol))| (content, *protocol))
+        .collect();
+    let tables = parallel_map(&tables, scan_threads(), |(content, protocol)| {
+        parse_proc_net_file(content, protocol, &inode_to_pid)
+    });
+    for ((netns, _, _), parsed) in sources.into_iter().zip(tables) {
+        ports.extend(
+            parsed
+                .into_iter()
+                .filter(|p| all_states || p.state.is_open())
+                .map(|p| PortInfo {
+                    netns,
+                    // Un TIME_WAIT no tiene dueño aunque se lean todos
+                    approximate: approximate && p.pid == 0 && p.state != SocketState::TimeWait,
+                    ..p
+                }),
+        );
+    }
+
+    ports
+}
+
+// ─────────────────────────────────────────────────────────────
+// Namespaces de red: puertos internos de los contenedores
+// ─────────────────────────────────────────────────────────────
+
+/// Activa o desactiva el escaneo de los namespaces de red ajenos
+/// (`scan_namespaces` en la configuración).
+pub fn set_namespace_scan(enabled: bool) {
+    NAMESPACE_SCAN.store(enabled, Ordering::Relaxed);
+}
+
+/// Archivos /proc/net a leer: (namespace, ruta, protocolo).
+///
+/// Los del host siempre; con el escaneo de namespaces activado, además
+/// los de un proceso representante de cada namespace de red ajeno
+/// (`/proc/<pid>/net/tcp` muestra la tabla del namespace del proceso).
+fn proc_net_sources() -> Vec<(Option<u64>, String, &'static str)> {
+    let mut sources: Vec<(Option<u64>, String, &'static str)> = PROC_NET_FILES
+        .iter()
+        .map(|(path, protocol)| (None, path.to_string(), *protocol))
+        .collect();
+    if !NAMESPACE_SCAN.load(Ordering::Relaxed) {
+        return sources;
+    }
+    for (netns, pid) in foreign_namespaces() {
+        for (path, protocol) in &PROC_NET_FILES {
+            let path = path.replacen("/proc/", &format!("/proc/{}/", pid), 1);
+            sources.push((Some(netns), path, *protocol));
+        }
+    }
+    sources
+}
+
+/// Namespaces de red distintos del propio, con el primer PID de cada uno.
+///
+/// Solo se ven los namespaces de los procesos accesibles para el
+/// usuario (los contenedores de Docker requieren root; los de Podman sin
+/// root, no).
+fn foreign_namespaces() -> Vec<(u64, u32)> {
+    let own = namespace_inode("self");
+    let mut namespaces: BTreeMap<u64, u32> = BTreeMap::new();
+    for pid in process_ids() {
+        match namespace_inode(&pid.to_string()) {
+            Some(netns) if Some(netns) != own => {
+                namespaces.entry(netns).or_insert(pid);
+            }
+            _ => {}
+        }
+    }
+    namespaces.into_iter().collect()
+}
+
+/// Inode del namespace de red de un proceso (`self` o un PID).
+fn namespace_inode(pid: &str) -> Option<u64> {
+    let link = fs::read_link(format!("/proc/{}/ns/net", pid)).ok()?;
+    extract_namespace_inode(&link.to_string_lossy())
+}
+
+/// Extrae el inode de un symlink con formato `net:[INODE]`.
+fn extract_namespace_inode(link: &str) -> Option<u64> {
+    link.strip_prefix("net:[")?.strip_suffix(']')?.parse().ok()
+}
+
+/// PIDs de todos los procesos (directorios numéricos de /proc).
+fn process_ids() -> Vec<u32> {
+    match fs::read_dir("/proc") {
+        Ok(dir) => dir
+            .flatten()
+            .filter_map(|entry| entry.file_name().to_string_lossy().parse().ok())
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Parsea un archivo /proc/net/tcp o similar.
+///
+/// Formato de cada línea (después del header):
+/// ```text
+///   sl  local_address rem_address   st tx_queue rx_queue ...  inode
+///    0: 00000000:0BB8 00000000:0000 0A ...                    22881
+/// ```
+///
+/// Campos relevantes:
+/// - Campo 1 (local_address): dirección IP en hex + puerto hex
+/// - Campo 3 (st): estado del socket (0A = LISTEN para TCP)
+/// - Campo 7 (uid): UID del dueño del socket
+/// - Campo 9 (inode): inode del socket para resolver PID
+///
+/// # Arguments
+/// * `content` - Contenido del archivo /proc/net/*
+/// * `protocol` - Protocolo ("tcp" o "udp")
+/// * `inode_to_pid` - Mapa de inode a (PID, nombre_proceso)
+///
+/// # Returns
+/// Vector de PortInfo para cada socket enlazado, con su estado.
+fn parse_proc_net_file(
+    content: &str,
+    protocol: &str,
+    inode_to_pid: &HashMap<u64, (u32, String)>,
+) -> Vec<PortInfo> {
+    content
+        .lines()
+        .skip(1) // Saltar el header
+        .filter_map(|line| parse_proc_net_line(line, protocol, inode_to_pid))
+        .collect()
+}
+
+/// Parsea una línea individual de /proc/net/tcp o similar.
+///
+/// # Arguments
+/// * `line` - Línea del archivo /proc/net/*
+/// * `protocol` - Protocolo a asignar
+/// * `inode_to_pid` - Mapa para resolver inode → PID
+///
+/// # Returns
+/// `Some(PortInfo)` si es un socket enlazado a un puerto, `None` en caso
+/// contrario.
+fn parse_proc_net_line(
+    line: &str,
+    protocol: &str,
+    inode_to_pid: &HashMap<u64, (u32, String)>,
+) -> Option<PortInfo> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    if parts.len() < 10 {
+        return None;
+    }
+
+    // Campo 3 (índice 3): estado del socket
+    let state = SocketState::from_proc(protocol, parts[3])?;
+
+    // Campo 1 (índice 1): dirección local en formato HEX:PORT_HEX
+    let local_addr_raw = parts[1];
+    let (local_address, port) = parse_hex_address(local_addr_raw)?;
+
+    // Ignorar puertos 0 (sockets no enlazados)
+    if port == 0 {
+        return None;
+    }
+
+    // Campo 7 (índice 7): UID del dueño del socket
+    let uid = parts[7].parse().ok();
+
+    // Campo 9 (índice 9): inode del socket
+    let inode: u64 = parts[9].parse().unwrap_or(0);
+
+    // Intentar resolver PID y nombre del proceso usando el inode
+    let (pid, process_name) = if inode > 0 {
+        inode_to_pid
+            .get(&inode)
+            .cloned()
+            .unwrap_or_else(|| (0, unknown_process_name().to_string()))
+    } else {
+        (0, unknown_process_name().to_string())
+    };
+
+    Some(PortInfo {
+        protocol: protocol.to_string(),
+        port,
+        local_address,
+        pid,
+        process_name,
+        netns: None,
+        uid,
+        approximate: false,
+        started_at: None,
+        queue: None,
+        origin: PortOrigin::Linux,
+        state,
+    })
+}
+
+/// Indica si el estado de un socket de /proc/net cuenta como puerto abierto.
+///
+/// 0A = LISTEN (TCP), 07 = CLOSE (UDP no tiene LISTEN, pero los sockets
+/// UDP sin conectar se consideran "abiertos").
+fn is_open_state(protocol: &str, state: &str) -> bool {
+    SocketState::from_proc(protocol, state).is_some_and(|s| s.is_open())
+}
+
+/// Convierte una dirección hexadecimal de /proc/net a formato legible.
+///
+/// Formato de entrada: `HEX_IP:HEX_PORT`
+/// - IPv4: `00000000:0BB8` → ("0.0.0.0", 3000)
+/// - IPv6: `00000000000000000000000000000000:0BB8` → ("::", 3000)
+/// - IPv6: `000080FE00000000FF27000AA1664EFE:0016` → ("fe80::a00:27ff:fe4e:66a1", 22)
+///
+/// `/proc/net` no incluye la zona de las direcciones de enlace local;
+/// `ss`, que sí la muestra, tiene prioridad al combinar las fuentes.
+///
+/// # Arguments
+/// * `hex_addr` - Dirección en formato hexadecimal de /proc/net
+///
+/// # Returns
+/// Tupla `(dirección_legible, puerto)` o `None` si el formato es inválido.
+fn parse_hex_address(hex_addr: &str) -> Option<(String, u16)> {
+    let parts: Vec<&str> = hex_addr.split(':').collect();
+    if parts.len() != 2 {
+        return None;
+    }
+
+    // Parsear el puerto (siempre es hex de 4 caracteres)
+    let port = u16::from_str_radix(parts[1], 16).ok()?;
+
+    // Parsear la dirección IP
+    let addr_hex = parts[0];
+    // El kernel imprime la dirección como palabras de 32 bits en el orden
+    // de bytes del host: se vuelven a bytes en ese mismo orden
+    let address = if addr_hex.len() == 8 {
+        // IPv4: una palabra (ej: "0100007F" → 127.0.0.1 en little-endian)
+        let word = u32::from_str_radix(addr_hex, 16).ok()?;
+        Ipv4Addr::from(word.to_ne_bytes()).to_string()
+    } else if addr_hex.len() == 32 && addr_hex.is_ascii() {
+        // IPv6: cuatro palabras, en notación comprimida estándar
+        let mut octets = [0u8; 16];
+        for (i, chunk) in octets.chunks_mut(4).enumerate() {
+            let word = u32::from_str_radix(&addr_hex[i * 8..i * 8 + 8], 16).ok()?;
+            chunk.copy_from_slice(&word.to_ne_bytes());
+        }
+        Ipv6Addr::from(octets).to_string()
+    } else {
+        return None;
+    };
+
+    Some((address, port))
+}
+
+/// Informe de precisión del recorrido de `/proc/*/fd`
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct FdScanReport {
+    /// Se recorrió en modo seguro (muchísimos descriptores abiertos)
+    pub safe_mode: bool,
+    /// Procesos recorridos
+    pub processes: usize,
+    /// Procesos de los que solo se leyó una muestra de descriptores
+    pub sampled: usize,
+    /// Procesos sin recorrer por agotar el tiempo
+    pub skipped: usize,
+    /// Procesos cuyos sockets se tomaron de la caché sin releerlos
+    pub cached: usize,
+    /// Descriptores leídos
+    pub fds_read: usize,
+    /// Descriptores de los procesos recorridos
+    pub fds_total: usize,
+    /// Duración del recorrido en milisegundos
+    pub elapsed_ms: u64,
+}
+
+impl FdScanReport {
+    /// Indica si se leyeron todos los descriptores (resultados exactos).
+    pub fn complete(&self) -> bool {
+        self.sampled == 0 && self.skipped == 0
+    }
+}
+
+/// Informe del último recorrido de `/proc/*/fd` (`None` antes del primero).
+pub fn last_fd_scan() -> Option<FdScanReport> {
+    LAST_FD_SCAN.lock().ok().and_then(|last| last.clone())
+}
+
+/// Sockets de un proceso leídos en un recorrido anterior
+#[derive(Debug, Clone)]
+struct CachedFds {
+    /// Huella de `/proc/<pid>/fd` al leerlo (ver `fd_fingerprint`)
+    fingerprint: (SystemTime, u64),
+    /// Momento de la lectura
+    read_at: Instant,
+    /// Descriptores abiertos al leerlo
+    fds: usize,
+    /// Inodes de los sockets del proceso
+    inodes: Vec<u64>,
+    /// Los de `inodes` que estaban en las tablas de `/proc/net` al
+    /// leerlos (los TCP y UDP; los sockets Unix nunca aparecen)
+    listed: Vec<u64>,
+}
+
+impl CachedFds {
+    /// Indica si la lectura sigue valiendo: misma huella, leída hace poco
+    /// y sin ningún socket TCP o UDP cerrado desde entonces.
+    ///
+    /// La huella no basta: cerrar un socket y abrir otro deja igual el
+    /// número de descriptores (y procfs no cambia la fecha del
+    /// directorio), pero el socket cerrado desaparece de `live`.
+    fn is_fresh(&self, fingerprint: (SystemTime, u64), live: &HashSet<u64>) -> bool {
+        self.fingerprint == fingerprint
+            && self.read_at.elapsed() < FD_CACHE_MAX_AGE
+            && self.listed.iter().all(|inode| live.contains(inode))
+    }
+}
+
+/// Inodes de los sockets de unas tablas de `/proc/net`, en cualquier
+/// estado.
+fn listed_inodes(contents: &[String]) -> HashSet<u64> {
+    contents
+        .iter()
+        .flat_map(|content| content.lines().skip(1)) // Saltar el header
+        .filter_map(|line| line.split_whitespace().nth(9)?.parse().ok())
+        .filter(|inode| *inode > 0)
+        .collect()
+}
+
+/// Huella de un directorio `/proc/<pid>/fd`: fecha de modificación y
+/// tamaño.
+///
+/// En procfs la fecha es la de creación del directorio (distinta si el
+/// PID se reutiliza) y, desde Linux 6.2, el tamaño es el número de
+/// descriptores abiertos, que cambia al abrir o cerrar uno. En kernels
+/// anteriores el tamaño no sigue a los descriptores y la caché no se usa
+/// (ver [`fd_count_supported`]).
+fn fd_fingerprint(fd_path: &str) -> Option<(SystemTime, u64)> {
+    let metadata = fs::metadata(fd_path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Indica si el kernel informa el número de descriptores abiertos como
+/// tamaño de `/proc/<pid>/fd` (Linux 6.2 o posterior).
+///
+/// Se comprueba una sola vez comparando el tamaño con las entradas del
+/// directorio de nuestro propio proceso; se reintenta por si otro hilo
+/// abre o cierra un descriptor entre las dos lecturas.
+fn fd_count_supported() -> bool {
+    static SUPPORTED: OnceLock<bool> = OnceLock::new();
+    *SUPPORTED.get_or_init(|| {
+        let fd_path = format!("/proc/{}/fd", std::process::id());
+        (0..3).any(|_| {
+            // El descriptor del propio read_dir cuenta en ambas lecturas
+            let Ok(dir) = fs::read_dir(&fd_path) else {
+                return false;
+            };
+            let Ok(metadata) = fs::metadata(&fd_path) else {
+                return false;
+            };
+            metadata.len() > 0 && metadata.len() == dir.count() as u64
+        })
+    })
+}
+
+/// Construye un mapa de inode → (PID, nombre_proceso).
+///
+/// Escanea `/proc/[pid]/fd/` buscando symlinks a `socket:[inode]`
+/// para poder resolver qué proceso posee cada socket.
+///
+/// Solo escanea procesos accesibles para el usuario actual.
+///
+/// Leer cada symlink de cada proceso en cada escaneo es muy costoso en
+/// sistemas cargados, así que los sockets de cada proceso se guardan
+/// entre escaneos y solo se releen los procesos nuevos, los que
+/// cambiaron de huella (ver `fd_fingerprint`), los leídos hace más de
+/// [`FD_CACHE_MAX_AGE`] y los que tenían un socket que ya no está en
+/// `live` (las tablas de `/proc/net` de este escaneo). Los PIDs que ya
+/// no existen salen de la caché, y en kernels sin contador en la huella
+/// no se reutiliza nada (ver [`fd_count_supported`]). Los procesos se
+/// recorren en paralelo, con un hilo por núcleo.
+///
+/// En hosts con cientos de miles de descriptores abiertos (hosts de
+/// contenedores) leer cada symlink tarda demasiado, así que se pasa a
+/// modo seguro: de cada proceso se lee como máximo una muestra de
+/// [`MAX_FDS_PER_PROCESS`] descriptores durante [`PROCESS_FD_BUDGET`], y
+/// el recorrido entero se corta al cumplirse [`FD_SCAN_BUDGET`].
+///
+/// # Returns
+/// HashMap donde la clave es el inode del socket y el valor
+/// es la tupla (PID, nombre del proceso), junto con el informe de
+/// precisión del recorrido.
+fn build_inode_pid_map(live: &HashSet<u64>) -> (HashMap<u64, (u32, String)>, FdScanReport) {
+    let mut map: HashMap<u64, (u32, String)> = HashMap::new();
+    let started = Instant::now();
+    let mut report = FdScanReport {
+        safe_mode: open_fds().is_some_and(|fds| fds >= SAFE_MODE_FDS),
+        ..Default::default()
+    };
+    let mut cache = FD_CACHE.lock().unwrap_or_else(|e| e.into_inner());
+    let previous = cache.take().unwrap_or_default();
+    let mut current: HashMap<u32, CachedFds> = HashMap::new();
+
+    // Recorrer todos los directorios numéricos en /proc (cada uno es un
+    // PID) en paralelo; los resultados se combinan en el orden de /proc
+    let pids = process_ids();
+    let scans = parallel_map(&pids, scan_threads(), |pid| {
+        scan_process_fds(*pid, previous.get(pid), live, report.safe_mode, started)
+    });
+    for (pid, scan) in pids.into_iter().zip(scans) {
+        let (name, fds, fds_read, from_cache, truncated) = match scan {
+            ProcessFds::Skipped => {
+                report.skipped += 1;
+                continue;
+            }
+            ProcessFds::Unreadable => continue,
+            ProcessFds::Read {
+                name,
+                fds,
+                fds_read,
+                from_cache,
+                truncated,
+            } => (name, fds, fds_read, from_cache, truncated),
+        };
+        report.processes += 1;
+        report.fds_total += fds.fds;
+        report.fds_read += fds_read;
+        if from_cache {
+            report.cached += 1;
+        }
+        for inode in &fds.inodes {
+            map.insert(*inode, (pid, name.clone()));
+        }
+        // Una muestra incompleta no se guarda: se vuelve a leer
+        if truncated {
+            report.sampled += 1;
+        } else {
+            current.insert(pid, fds);
+        }
+    }
+    *cache = Some(current);
+
+    report.elapsed_ms = started.elapsed().as_millis() as u64;
+    if !report.complete() {
+        log::warn!(
+            "Escaneo aproximado de /proc/*/fd: {} de {} descriptores leídos, {} procesos muestreados y {} sin recorrer ({} ms)",
+            report.fds_read,
+            report.fds_total,
+            report.sampled,
+            report.skipped,
+            report.elapsed_ms
+        );
+    }
+    (map, report)
+}
+
+/// Resultado de recorrer los descriptores de un proceso
+#[derive(Debug)]
+enum ProcessFds {
+    /// Sin recorrer por agotar el tiempo del modo seguro
+    Skipped,
+    /// Proceso terminado o sin permisos
+    Unreadable,
+    /// Sockets encontrados
+    Read {
+        /// Nombre del proceso
+        name: String,
+        /// Huella, descriptores y sockets del proceso
+        fds: CachedFds,
+        /// Descriptores leídos en este recorrido
+        fds_read: usize,
+        /// Se reutilizó la lectura anterior
+        from_cache: bool,
+        /// Solo se leyó una muestra de los descriptores (modo seguro)
+        truncated: bool,
+    },
+}
+
+/// Recorre los descriptores de un proceso buscando sockets, o reutiliza
+/// la lectura anterior si su huella no cambió.
+fn scan_process_fds(
+    pid: u32,
+    previous: Option<&CachedFds>,
+    live: &HashSet<u64>,
+    safe_mode: bool,
+    started: Instant,
+) -> ProcessFds {
+    if safe_mode && started.elapsed() >= FD_SCAN_BUDGET {
+        return ProcessFds::Skipped;
+    }
+
+    // Leer el nombre del proceso desde /proc/[pid]/comm
+    let name = read_process_name(pid);
+
+    // Sin cambios desde el último recorrido: reutilizar sus sockets. Sin
+    // contador de descriptores en la huella un socket nuevo pasaría
+    // inadvertido, así que entonces siempre se relee
+    let fd_path = format!("/proc/{}/fd", pid);
+    let Some(fingerprint) = fd_fingerprint(&fd_path) else {
+        return ProcessFds::Unreadable;
+    };
+    let previous = previous.filter(|_| fd_count_supported());
+    if let Some(cached) = previous.filter(|c| c.is_fresh(fingerprint, live)) {
+        return ProcessFds::Read {
+            name,
+            fds: cached.clone(),
+            fds_read: 0,
+            from_cache: true,
+            truncated: false,
+        };
+    }
+
+    // Escanear los file descriptors buscando sockets
+    let fd_dir = match fs::read_dir(&fd_path) {
+        Ok(dir) => dir,
+        Err(_) => return ProcessFds::Unreadable,
+    };
+    let fds: Vec<PathBuf> = fd_dir.flatten().map(|entry| entry.path()).collect();
+    let mut inodes = Vec::new();
+
+    let selected = if safe_mode {
+        sample_indices(fds.len(), MAX_FDS_PER_PROCESS)
+    } else {
+        (0..fds.len()).collect()
+    };
+    let mut truncated = selected.len() < fds.len();
+    let mut fds_read = 0;
+    let process_started = Instant::now();
+    for index in selected {
+        if safe_mode && process_started.elapsed() >= PROCESS_FD_BUDGET {
+            truncated = true;
+            break;
+        }
+        fds_read += 1;
+        // Leer el symlink del FD (ej: "socket:[22881]")
+        if let Ok(link) = fs::read_link(&fds[index]) {
+            let link_str = link.to_string_lossy().to_string();
+            if let Some(inode) = extract_socket_inode(&link_str) {
+                inodes.push(inode);
+            }
+        }
+    }
+
+    ProcessFds::Read {
+        name,
+        fds: CachedFds {
+            fingerprint,
+            read_at: Instant::now(),
+            fds: fds.len(),
+            listed: inodes
+                .iter()
+                .copied()
+                .filter(|i| live.contains(i))
+                .collect(),
+            inodes,
+        },
+        fds_read,
+        from_cache: false,
+        truncated,
+    }
+}
+
+/// Hilos para recorrer `/proc` en paralelo (uno por núcleo, con tope).
+fn scan_threads() -> usize {
+    std::thread::available_parallelism()
+        .map_or(1, |n| n.get())
+        .min(MAX_SCAN_THREADS)
+}
+
+/// Aplica `f` a cada elemento repartiéndolos entre `threads` hilos y
+/// devuelve los resultados en el orden original.
+///
+/// Cada hilo toma el siguiente elemento pendiente al terminar el
+/// anterior, así un proceso con muchísimos descriptores no retrasa a
+/// los demás.
+fn parallel_map<T: Sync, R: Send>(
+    items: &[T],
+    threads: usize,
+    f: impl Fn(&T) -> R + Sync,
+) -> Vec<R> {
+    if threads <= 1 || items.len() < 2 {
+        return items.iter().map(f).collect();
+    }
+    let next = AtomicUsize::new(0);
+    let mut results: Vec<(usize, R)> = std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads.min(items.len()))
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut done = Vec::new();
+                    loop {
+                        let index = next.fetch_add(1, Ordering::Relaxed);
+                        let Some(item) = items.get(index) else {
+                            return done;
+                        };
+                        done.push((index, f(item)));
+                    }
+                })
+            })
+            .collect();
+        workers
+            .into_iter()
+            .flat_map(|worker| {
+                worker
+                    .join()
+                    .unwrap_or_else(|e| std::panic::resume_unwind(e))
+            })
+            .collect()
+    });
+    results.sort_unstable_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// Descriptores abiertos en todo el sistema (primer campo de
+/// `/proc/sys/fs/file-nr`).
+fn open_fds() -> Option<u64> {
+    fs::read_to_string("/proc/sys/fs/file-nr")
+        .ok()?
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// Índices de los descriptores a leer de un proceso con `len` abiertos.
+///
+/// Si hay más de `max`, se leen los primeros `max / 2` (los sockets en
+/// escucha suelen abrirse al arrancar, con números bajos) y el resto de
+/// la cuota se reparte a intervalos regulares entre los demás.
+fn sample_indices(len: usize, max: usize) -> Vec<usize> {
+    if len <= max {
+        return (0..len).collect();
+    }
+    let head = max / 2;
+    let rest = max - head;
+    let step = (len - head).div_ceil(rest);
+    (0..head).chain((head..len).step_by(step.max(1))).collect()
+}
+
+/// Lee el nombre del proceso desde /proc/[pid]/comm.
+///
+/// # Arguments
+/// * `pid` - ID del proceso
+///
+/// # Returns
+/// Nombre del proceso o "desconocido" si no se puede leer.
+fn read_process_name(pid: u32) -> String {
+    let comm_path = format!("/proc/{}/comm", pid);
+    fs::read_to_string(comm_path)
+        .map(|s| s.trim().to_string())
+        .unwrap_or_else(|_| unknown_process_name().to_string())
+}
+
+/// Hora de inicio de un proceso (segundos desde UNIX epoch).
+///
+/// `/proc/[pid]/stat` da el inicio en ticks de reloj desde el arranque;
+/// se le suma la hora de arranque (`btime` de `/proc/stat`).
+///
+/// # Returns
+/// `None` si el proceso ya no existe o no se pudo leer.
+pub fn process_started_at(pid: u32) -> Option<u64> {
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    let ticks = parse_start_ticks(&stat)?;
+    Some(boot_time()? + ticks / clock_ticks())
+}
+
+/// Extrae el campo `starttime` (el 22) de `/proc/[pid]/stat`.
+///
+/// El nombre del proceso (campo 2, entre paréntesis) puede llevar
+/// espacios y paréntesis, así que se cuenta desde el último ')'.
+fn parse_start_ticks(stat: &str) -> Option<u64> {
+    let rest = &stat[stat.rfind(')')? + 1..];
+    // Tras el nombre empieza el campo 3 (estado): starttime es el 20.º
+    rest.split_whitespace().nth(19)?.parse().ok()
+}
+
+/// Hora de arranque del sistema (segundos desde UNIX epoch), de la
+/// línea `btime` de `/proc/stat`.
+fn boot_time() -> Option<u64> {
+    static BOOT_TIME: OnceLock<Option<u64>> = OnceLock::new();
+    *BOOT_TIME.get_or_init(|| {
+        let stat = fs::read_to_string("/proc/stat").ok()?;
+        stat.lines()
+            .find_map(|line| line.strip_prefix("btime "))?
+            .trim()
+            .parse()
+            .ok()
+    })
+}
+
+/// Ticks de reloj por segundo (`_SC_CLK_TCK`, casi siempre 100).
+fn clock_ticks() -> u64 {
+    use nix::unistd::{sysconf, SysconfVar};
+    match sysconf(SysconfVar::CLK_TCK) {
+        Ok(Some(ticks)) if ticks > 0 => ticks as u64,
+        _ => 100,
+    }
+}
+
+/// Extrae el inode de un symlink con formato `socket:[INODE]`.
+///
+/// # Arguments
+/// * `link` - Contenido del symlink (ej: "socket:[22881]")
+///
+/// # Returns
+/// `Some(inode)` si el formato es correcto, `None` en caso contrario.
+fn extract_socket_inode(link: &str) -> Option<u64> {
+    if link.starts_with("socket:[") && link.ends_with(']') {
+        let inode_str = &link[8..link.len() - 1];
+        inode_str.parse().ok()
+    } else {
+        None
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// Búsqueda de puertos libres
+// ─────────────────────────────────────────────────────────────
+
+/// Busca el primer puerto TCP libre de un rango (extremos incluidos),
+/// para levantar un servicio sin adivinar.
+///
+/// Un puerto cuenta como libre si ningún socket del host escucha en él
+/// según el escaneo y además se puede abrir de verdad en `0.0.0.0`: el
+/// escaneo no ve lo que se abrió después ni los puertos que el kernel
+/// retiene por otros motivos. Los puertos de los contenedores (otro
+/// namespace de red) no ocupan los del host.
+///
+/// # Returns
+/// `None` si el rango no es válido o no queda ninguno libre.
+pub fn find_free_port((start, end): (u16, u16)) -> Option<u16> {
+    if start == 0 || start > end {
+        return None;
+    }
+    let used: HashSet<u16> = scan_open_ports()
+        .into_iter()
+        .filter(|p| p.protocol == "tcp" && p.netns.is_none())
+        .map(|p| p.port)
+        .collect();
+    first_free(&used, (start, end), |port| {
+        let bindable = TcpListener::bind((Ipv4Addr::UNSPECIFIED, port)).is_ok();
+        if !bindable {
+            log::debug!(
+                "El puerto {} no aparece en el escaneo pero no se puede abrir",
+                port
+            );
+        }
+        bindable
+    })
+}
+
+/// Primer puerto del rango que no está en `used` y pasa `bindable`.
+fn first_free(
+    used: &HashSet<u16>,
+    (start, end): (u16, u16),
+    bindable: impl Fn(u16) -> bool,
+) -> Option<u16> {
+    (start..=end).find(|port| !used.contains(port) && bindable(*port))
+}
+
+// ─────────────────────────────────────────────────────────────
+// Detección barata de cambios (evita escaneos completos en reposo)
+// ─────────────────────────────────────────────────────────────
+
+/// Decide si hace falta un escaneo completo.
+///
+/// `/proc/net` no emite eventos de inotify, así que en lugar de eso se
+/// compara una huella de los sockets abiertos (dirección local e inode)
+/// leída directamente de `/proc/net/*`. Es mucho más barato que
+/// `scan_open_ports`, que ejecuta `ss` y recorre `/proc/*/fd` para
+/// resolver los PIDs. Un proceso reiniciado en el mismo puerto cambia
+/// el inode, así que también cuenta como cambio.
+///
+/// Cada [`FULL_SCAN_EVERY`] comprobaciones sin cambios se escanea igual,
+/// para que conexiones y reglas de inactividad no queden desfasadas.
+#[derive(Debug, Default)]
+pub struct ChangeDetector {
+    /// Huella de la última comprobación (`None` antes de la primera)
+    last: Option<u64>,
+    /// Comprobaciones seguidas sin cambios
+    unchanged: u32,
+}
+
+impl ChangeDetector {
+    /// Comprueba `/proc/net` y decide si escanear.
+    ///
+    /// # Returns
+    /// `true` si los sockets cambiaron o toca el escaneo de seguridad.
+    pub fn should_scan(&mut self) -> bool {
+        let scan = self.check(listening_fingerprint());
+        if !scan {
+            log::debug!("Sin cambios en /proc/net, se omite el escaneo completo");
+        }
+        scan
+    }
+
+    /// Registra una huella y decide si escanear.
+    fn check(&mut self, fingerprint: u64) -> bool {
+        let changed = self.last != Some(fingerprint);
+        self.last = Some(fingerprint);
+        if changed || self.unchanged + 1 >= FULL_SCAN_EVERY {
+            self.unchanged = 0;
+            true
+        } else {
+            self.unchanged += 1;
+            false
+        }
+    }
+}
+
+/// Suma de control de un resultado de escaneo, independiente del orden.
+///
+/// Permite no reconstruir el menú, emitir eventos ni escribir en el
+/// historial cuando un escaneo devuelve lo mismo que el anterior, que es
+/// lo habitual en una máquina en reposo.
+pub fn scan_checksum<T: Hash>(items: &[T]) -> u64 {
+    items.iter().fold(items.len() as u64, |sum, item| {
+        let mut hasher = DefaultHasher::new();
+        item.hash(&mut hasher);
+        sum.wrapping_add(hasher.finish())
+    })
+}
+
+/// Huella de los sockets abiertos según `/proc/net/*` (y los de los
+/// namespaces de red ajenos, si se escanean).
+pub fn listening_fingerprint() -> u64 {
+    let mut keys: Vec<(&str, String, String)> = Vec::new();
+    for (_, path, protocol) in proc_net_sources() {
+        if let Ok(content) = fs::read_to_string(&path) {
+            keys.extend(
+                open_socket_keys(&content, protocol)
+                    .into_iter()
+                    .map(|(address, inode)| (protocol, address.to_string(), inode.to_string())),
+            );
+        }
+    }
+    // El orden de /proc/net depende de la tabla hash del kernel
+    keys.sort();
+    let mut hasher = DefaultHasher::new();
+    keys.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Dirección local e inode de los sockets abiertos de un archivo /proc/net.
+fn open_socket_keys<'a>(content: &'a str, protocol: &str) -> Vec<(&'a str, &'a str)> {
+    content
+        .lines()
+        .skip(1) // Saltar el header
+        .filter_map(|line| {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            if parts.len() < 10 || !is_open_state(protocol, parts[3]) {
+                return None;
+            }
+            Some((parts[1], parts[9]))
+        })
+        .collect()
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ports::{filter_ports, ProtocolFilter, RangeFilter, StateFilter};
+
+    /// Verifica que el parser maneja líneas vacías correctamente
+    #[test]
+    fn test_parse_empty_line() {
+        assert!(parse_single_ss_line("", "tcp").is_none());
+        assert!(parse_single_ss_line("   ", "tcp").is_none());
+    }
+
+    /// Verifica el parsing de una línea con info de proceso
+    #[test]
+    fn test_parse_ss_line_with_process() {
+        let line = r#"LISTEN 0 128 0.0.0.0:8080 0.0.0.0:* users:(("node",pid=12345,fd=19))"#;
+        let result = parse_single_ss_line(line, "tcp");
+        assert!(result.is_some());
+
+        let info = result.unwrap();
+        assert_eq!(info.port, 8080);
+        assert_eq!(info.pid, 12345);
+        assert_eq!(info.process_name, "node");
+        assert_eq!(info.protocol, "tcp");
+        assert_eq!(info.local_address, "0.0.0.0");
+    }
+
+    /// Verifica la cola de accept() de los LISTEN (Recv-Q/Send-Q), que en
+    /// los demás estados no se interpreta
+    #[test]
+    fn test_parse_ss_listen_queue() {
+        let line = r#"LISTEN 129 128 0.0.0.0:8080 0.0.0.0:* users:(("node",pid=12345,fd=19))"#;
+        let queue = parse_single_ss_line(line, "tcp").unwrap().queue.unwrap();
+        assert_eq!(
+            queue,
+            ListenQueue {
+                depth: 129,
+                backlog: 128
+            }
+        );
+        assert!(queue.is_full());
+        let idle = parse_single_ss_line("LISTEN 0 4096 *:8069 *:*", "tcp");
+        assert!(!idle.unwrap().queue.unwrap().is_full());
+
+        let unconn = parse_single_ss_line("UNCONN 2048 0 0.0.0.0:53 0.0.0.0:*", "udp");
+        assert_eq!(unconn.unwrap().queue, None);
+    }
+
+    /// Verifica el parsing de una línea SIN info de proceso (caso Docker)
+    #[test]
+    fn test_parse_ss_line_without_process() {
+        let line = "LISTEN 0 4096       *:8069        *:*";
+        let result = parse_single_ss_line(line, "tcp");
+        assert!(result.is_some());
+
+        let info = result.unwrap();
+        assert_eq!(info.port, 8069);
+        assert_eq!(info.pid, 0);
+        assert_eq!(info.process_name, "desconocido");
+    }
+
+    /// Verifica parsing de línea con wildcard IPv4/IPv6
+    #[test]
+    fn test_parse_ss_wildcard_address() {
+        let line = "LISTEN 0 4096  0.0.0.0:3000  0.0.0.0:*";
+        let result = parse_single_ss_line(line, "tcp");
+        assert!(result.is_some());
+
+        let info = result.unwrap();
+        assert_eq!(info.port, 3000);
+        assert_eq!(info.local_address, "0.0.0.0");
+    }
+
+    /// Verifica extracción de info de proceso
+    #[test]
+    fn test_extract_process_info() {
+        let line = r#"LISTEN 0 5 127.0.0.1:5432 0.0.0.0:* users:(("postgres",pid=987,fd=3))"#;
+        let (pid, name) = extract_process_info(line).unwrap();
+        assert_eq!(pid, 987);
+        assert_eq!(name, "postgres");
+    }
+
+    /// Verifica que extract_process_info retorna None sin sección users
+    #[test]
+    fn test_extract_process_info_none() {
+        let line = "LISTEN 0 4096  *:8069  *:*";
+        assert!(extract_process_info(line).is_none());
+    }
+
+    /// Verifica conversión de dirección hex IPv4
+    #[test]
+    fn test_parse_hex_address_ipv4() {
+        // 00000000:0BB8 = 0.0.0.0:3000
+        let (addr, port) = parse_hex_address("00000000:0BB8").unwrap();
+        assert_eq!(port, 3000);
+        assert_eq!(addr, "0.0.0.0");
+    }
+
+    /// Verifica conversión de dirección hex IPv4 loopback
+    #[test]
+    fn test_parse_hex_address_loopback() {
+        // 0100007F:1538 = 127.0.0.1:5432
+        let (addr, port) = parse_hex_address("0100007F:1538").unwrap();
+        assert_eq!(port, 5432);
+        assert_eq!(addr, "127.0.0.1");
+    }
+
+    /// Verifica conversión de direcciones hex IPv6 (palabras en little-endian)
+    #[test]
+    fn test_parse_hex_address_ipv6() {
+        let addr = |hex: &str| parse_hex_address(hex).unwrap().0;
+        assert_eq!(addr("00000000000000000000000000000000:0BB8"), "::");
+        assert_eq!(addr("00000000000000000000000001000000:0277"), "::1");
+        // fe80::a00:27ff:fe4e:66a1
+        assert_eq!(
+            addr("000080FE00000000FF27000AA1664EFE:0016"),
+            "fe80::a00:27ff:fe4e:66a1"
+        );
+        // ::ffff:10.0.0.2 (IPv4 mapeada)
+        assert_eq!(
+            addr("0000000000000000FFFF00000200000A:1538"),
+            "::ffff:10.0.0.2"
+        );
+    }
+
+    /// Verifica extracción de inode de socket
+    #[test]
+    fn test_extract_socket_inode() {
+        assert_eq!(extract_socket_inode("socket:[22881]"), Some(22881));
+        assert_eq!(extract_socket_inode("pipe:[123]"), None);
+        assert_eq!(extract_socket_inode("anon_inode:"), None);
+    }
+
+    /// Verifica la hora de inicio de /proc/[pid]/stat, con un nombre de
+    /// proceso que lleva espacios y paréntesis
+    #[test]
+    fn test_parse_start_ticks() {
+        let stat = "4242 (node (dev) x) S 1 4242 4242 0 -1 4194560 \
+                    1 0 0 0 5 2 0 0 20 0 11 0 123456 1000 200 18446744073709551615";
+        assert_eq!(parse_start_ticks(stat), Some(123456));
+        assert_eq!(parse_start_ticks("4242 (node) S 1"), None);
+
+        let started = process_started_at(std::process::id()).unwrap();
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        assert!(started <= now + 1 && now.saturating_sub(started) < 3600);
+        assert_eq!(crate::ports::format_uptime(0), "0s");
+        assert_eq!(crate::ports::format_uptime(300), "5m");
+        assert_eq!(crate::ports::format_uptime(3 * 86_400 + 4 * 3600), "3d 4h");
+    }
+
+    /// Verifica la muestra de descriptores del modo seguro
+    #[test]
+    fn test_sample_indices() {
+        assert_eq!(sample_indices(3, 8), vec![0, 1, 2]);
+        let sample = sample_indices(100_000, 4096);
+        assert!(sample.len() <= 4096);
+        assert_eq!(&sample[..3], &[0, 1, 2]);
+        assert!(sample.windows(2).all(|w| w[0] < w[1]));
+        assert!(*sample.last().unwrap() > 90_000);
+    }
+
+    /// Verifica que la caché de descriptores ve los sockets nuevos
+    #[test]
+    fn test_inode_pid_cache() {
+        use std::os::fd::AsRawFd;
+
+        // Antes de Linux 6.2 la caché no se usa
+        if !fd_count_supported() {
+            return;
+        }
+
+        let pid = std::process::id();
+        let socket_inode = |listener: &std::net::TcpListener| {
+            let link = fs::read_link(format!("/proc/{}/fd/{}", pid, listener.as_raw_fd()));
+            extract_socket_inode(&link.unwrap().to_string_lossy()).unwrap()
+        };
+        build_inode_pid_map(&live_inodes());
+
+        // Un socket nuevo cambia la huella del proceso y se relee
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let inode = socket_inode(&listener);
+        let (map, _) = build_inode_pid_map(&live_inodes());
+        assert_eq!(map.get(&inode).map(|(p, _)| *p), Some(pid));
+
+        // Cerrar un socket y abrir otro (que reutiliza su descriptor) deja
+        // la misma huella, pero el cerrado ya no está en /proc/net
+        drop(listener);
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let swapped = socket_inode(&listener);
+        let (map, _) = build_inode_pid_map(&live_inodes());
+        assert_eq!(map.get(&swapped).map(|(p, _)| *p), Some(pid));
+        drop(listener);
+
+        let fingerprint = fd_fingerprint(&format!("/proc/{}/fd", pid)).unwrap();
+        let cached = CachedFds {
+            fingerprint,
+            read_at: Instant::now(),
+            fds: 1,
+            inodes: vec![inode, 7],
+            listed: vec![inode],
+        };
+        let live: HashSet<u64> = [inode, swapped].into();
+        assert!(cached.is_fresh(fingerprint, &live));
+        assert!(!cached.is_fresh((fingerprint.0, fingerprint.1 + 1), &live));
+        // El socket Unix (7) no cuenta; el TCP cerrado sí
+        assert!(!cached.is_fresh(fingerprint, &[swapped].into()));
+    }
+
+    /// Inodes de los sockets que aparecen ahora en /proc/net
+    fn live_inodes() -> HashSet<u64> {
+        let contents: Vec<String> = proc_net_sources()
+            .into_iter()
+            .map(|(_, path, _)| fs::read_to_string(path).unwrap_or_default())
+            .collect();
+        listed_inodes(&contents)
+    }
+
+    /// Verifica el namespace de los puertos de contenedores
+    #[test]
+    fn test_namespace_ports() {
+        assert_eq!(
+            extract_namespace_inode("net:[4026532301]"),
+            Some(4026532301)
+        );
+        assert_eq!(extract_namespace_inode("mnt:[4026531841]"), None);
+
+        let port = PortInfo {
+            protocol: "tcp".into(),
+            port: 80,
+            local_address: "0.0.0.0".into(),
+            pid: 2211,
+            process_name: "nginx".into(),
+            netns: Some(4026532301),
+            ..Default::default()
+        };
+        assert_eq!(
+            port.to_string(),
+            "TCP 80 (0.0.0.0, netns 4026532301) → nginx [PID 2211]"
+        );
+        // Solo accesible dentro del contenedor
+        assert!(!port.listens_on_all_interfaces());
+    }
+
+    /// Verifica limpieza de direcciones
+    #[test]
+    fn test_clean_address() {
+        assert_eq!(clean_address("[::1]"), "::1");
+        assert_eq!(
+            clean_address("[fe80::0a00:27ff:fe4e:66a1]%enp0s3"),
+            "fe80::a00:27ff:fe4e:66a1%enp0s3"
+        );
+        assert_eq!(clean_address("127.0.0.53%lo"), "127.0.0.53");
+        assert_eq!(clean_address("*"), "0.0.0.0");
+        assert_eq!(clean_address("0.0.0.0"), "0.0.0.0");
+    }
+
+    /// Verifica que solo los sockets abiertos cuentan para la huella y
+    /// el escaneo de seguridad del detector de cambios
+    #[test]
+    fn test_change_detector() {
+        let content = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000:0BB8 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 22881
+   1: 0100007F:A1B2 0100007F:0BB8 01 00000000:00000000 00:00000000 00000000  1000        0 22990";
+        assert_eq!(
+            open_socket_keys(content, "tcp"),
+            vec![("00000000:0BB8", "22881")]
+        );
+
+        let mut detector = ChangeDetector::default();
+        assert!(detector.check(1));
+        for _ in 1..FULL_SCAN_EVERY {
+            assert!(!detector.check(1));
+        }
+        assert!(detector.check(1));
+        assert!(detector.check(2));
+    }
+
+    /// Verifica el estado de los sockets de /proc/net y de ss, y su filtro
+    #[test]
+    fn test_socket_states() {
+        let content = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode
+   0: 00000000:0BB8 00000000:0000 0A 00000000:00000000 00:00000000 00000000  1000        0 22881
+   1: 0100007F:0BB8 0100007F:A1B2 06 00000000:00000000 03:00000F2A 00000000     0        0 0";
+        let sockets = parse_proc_net_file(content, "tcp", &HashMap::new());
+        let states: Vec<SocketState> = sockets.iter().map(|p| p.state).collect();
+        assert_eq!(states, vec![SocketState::Listen, SocketState::TimeWait]);
+        let uids: Vec<Option<u32>> = sockets.iter().map(|p| p.uid).collect();
+        assert_eq!(uids, vec![Some(1000), Some(0)]);
+        assert_eq!(
+            filter_ports(
+                &sockets,
+                ProtocolFilter::All,
+                RangeFilter::All,
+                StateFilter::parse("time-wait").unwrap()
+            )
+            .len(),
+            1
+        );
+
+        let line = r#"CLOSE-WAIT 1 0 127.0.0.1:3000 127.0.0.1:41234 users:(("node",pid=42,fd=21))"#;
+        assert_eq!(
+            parse_single_ss_line(line, "tcp").map(|p| p.state),
+            Some(SocketState::CloseWait)
+        );
+        assert_eq!(SocketState::parse("ESTAB"), Some(SocketState::Established));
+        assert_eq!(
+            SocketState::from_proc("udp", "07"),
+            Some(SocketState::Unconn)
+        );
+        assert_eq!(StateFilter::parse("bogus"), None);
+    }
+
+    /// Verifica que el recorrido en paralelo conserva el orden y reparte
+    /// el trabajo (con 4 hilos hay varias tareas a la vez, pero nunca más
+    /// de 4)
+    #[test]
+    fn test_parallel_map() {
+        let items: Vec<u32> = (0..16).collect();
+        let running = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let slow = |n: &u32| {
+            let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+            peak.fetch_max(now, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(20));
+            running.fetch_sub(1, Ordering::SeqCst);
+            n * 2
+        };
+        let doubled = parallel_map(&items, 4, slow);
+        let peak = peak.load(Ordering::SeqCst);
+        assert!(peak > 1 && peak <= 4, "{} hilos a la vez", peak);
+        assert_eq!(doubled, items.iter().map(|n| n * 2).collect::<Vec<_>>());
+        assert_eq!(parallel_map(&items, 1, |n| n + 1)[15], 16);
+    }
+
+    /// Verifica que la suma de control no depende del orden del escaneo
+    #[test]
+    fn test_scan_checksum() {
+        let port = |port: u16| PortInfo {
+            protocol: "tcp".into(),
+            port,
+            local_address: "0.0.0.0".into(),
+            pid: 1,
+            process_name: "node".into(),
+            ..Default::default()
+        };
+        let a = vec![port(3000), port(5432)];
+        let b = vec![port(5432), port(3000)];
+        assert_eq!(scan_checksum(&a), scan_checksum(&b));
+        assert_ne!(scan_checksum(&a), scan_checksum(&a[..1]));
+        assert_ne!(scan_checksum(&a), scan_checksum(&[port(3000), port(3000)]));
+    }
+
+    /// Verifica que el puerto libre salta los del escaneo y los que no se
+    /// pueden abrir
+    #[test]
+    fn test_find_free_port() {
+        let used: HashSet<u16> = [3000, 3001].into();
+        assert_eq!(first_free(&used, (3000, 3010), |_| true), Some(3002));
+        assert_eq!(first_free(&used, (3000, 3010), |p| p > 3005), Some(3006));
+        assert_eq!(first_free(&used, (3000, 3001), |_| true), None);
+
+        let listener = TcpListener::bind((Ipv4Addr::UNSPECIFIED, 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        assert_ne!(find_free_port((port, port.saturating_add(20))), Some(port));
+        assert_eq!(find_free_port((10, 5)), None);
+    }
+
+    /// Verifica que el escaneo async ve un puerto recién abierto
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn test_scan_open_ports_async() {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        let ports = runtime.block_on(scan_open_ports_async()).unwrap();
+        assert!(ports.iter().any(|p| p.port == port && p.protocol == "tcp"));
+    }
+}
@@ -0,0 +1,266 @@
+/// Módulo de salud de las fuentes de escaneo.
+///
+/// Cada fuente externa (`ss` y, en WSL2, `netstat.exe`) se ejecuta con
+/// un tiempo máximo. Si falla o no responde [`FAILURES_BEFORE_QUARANTINE`]
+/// veces seguidas, se pone en cuarentena: durante un tiempo de espera no
+/// se consulta y el escaneo sigue con el resto de fuentes (`/proc/net`),
+/// así una fuente colgada no bloquea cada actualización.
+///
+/// Al terminar la cuarentena se prueba una vez: si vuelve a fallar, la
+/// espera se duplica (hasta [`MAX_BACKOFF`]); si responde, se olvida el
+/// historial de fallos. El estado se consulta con [`status`] (PortSlayer
+/// lo muestra en el tray y en `GET /sources` de la API).
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::process::{Command, Output, Stdio};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Tiempo máximo de una consulta a una fuente externa
+pub const SOURCE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Fallos seguidos tras los que una fuente entra en cuarentena
+pub const FAILURES_BEFORE_QUARANTINE: u32 = 3;
+
+/// Primera cuarentena; se duplica con cada recaída
+pub const BASE_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Cuarentena máxima
+pub const MAX_BACKOFF: Duration = Duration::from_secs(600);
+
+/// Cada cuánto se comprueba si el proceso terminó
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// Estado de todas las fuentes, por nombre
+static REGISTRY: OnceLock<Mutex<BTreeMap<&'static str, SourceHealth>>> = OnceLock::new();
+
+/// Estado de una fuente para mostrar en el tray y la API
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SourceStatus {
+    /// Nombre de la fuente (ej: "ss")
+    pub source: String,
+    /// En cuarentena: el escaneo continúa sin ella
+    pub degraded: bool,
+    /// Fallos seguidos desde la última respuesta correcta
+    pub failures: u32,
+    /// Segundos hasta el próximo intento (0 si no está en cuarentena)
+    pub retry_in_secs: u64,
+    /// Último error registrado
+    pub last_error: Option<String>,
+}
+
+/// Historial de fallos de una fuente
+#[derive(Debug, Clone, Default)]
+struct SourceHealth {
+    /// Fallos seguidos
+    failures: u32,
+    /// Cuarentenas seguidas (determina la espera de la siguiente)
+    quarantines: u32,
+    /// Fin de la cuarentena actual
+    quarantined_until: Option<Instant>,
+    /// Último error registrado
+    last_error: Option<String>,
+}
+
+impl SourceHealth {
+    /// Indica si la fuente se puede consultar.
+    fn available(&self, now: Instant) -> bool {
+        self.quarantined_until.is_none_or(|until| now >= until)
+    }
+
+    /// Registra una respuesta correcta y olvida los fallos.
+    fn success(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Registra un fallo.
+    ///
+    /// # Returns
+    /// Duración de la cuarentena si la fuente entra en ella.
+    fn failure(&mut self, error: String, now: Instant) -> Option<Duration> {
+        self.failures += 1;
+        self.last_error = Some(error);
+        // Tras una cuarentena basta un fallo en la prueba para recaer
+        if self.failures < FAILURES_BEFORE_QUARANTINE && self.quarantines == 0 {
+            return None;
+        }
+        let backoff = BASE_BACKOFF
+            .saturating_mul(2u32.saturating_pow(self.quarantines))
+            .min(MAX_BACKOFF);
+        self.quarantines += 1;
+        self.quarantined_until = Some(now + backoff);
+        Some(backoff)
+    }
+
+    /// Estado legible de la fuente.
+    fn status(&self, source: &str, now: Instant) -> SourceStatus {
+        let retry = self
+            .quarantined_until
+            .map(|until| until.saturating_duration_since(now))
+            .unwrap_or_default();
+        SourceStatus {
+            source: source.to_string(),
+            degraded: !self.available(now),
+            failures: self.failures,
+            retry_in_secs: retry.as_secs(),
+            last_error: self.last_error.clone(),
+        }
+    }
+}
+
+/// Estado compartido de las fuentes.
+fn registry() -> &'static Mutex<BTreeMap<&'static str, SourceHealth>> {
+    REGISTRY.get_or_init(|| Mutex::new(BTreeMap::new()))
+}
+
+/// Indica si una fuente se puede consultar (no está en cuarentena).
+pub fn available(source: &'static str) -> bool {
+    match registry().lock() {
+        Ok(registry) => registry
+            .get(source)
+            .is_none_or(|health| health.available(Instant::now())),
+        Err(_) => true,
+    }
+}
+
+/// Registra una respuesta correcta de una fuente.
+pub fn record_success(source: &'static str) {
+    if let Ok(mut registry) = registry().lock() {
+        if let Some(health) = registry.get_mut(source) {
+            if health.quarantines > 0 {
+                log::info!("La fuente {} vuelve a responder", source);
+            }
+            health.success();
+        }
+    }
+}
+
+/// Registra un fallo de una fuente (y la pone en cuarentena si toca).
+pub fn record_failure(source: &'static str, error: &str) {
+    if let Ok(mut registry) = registry().lock() {
+        let health = registry.entry(source).or_default();
+        if let Some(backoff) = health.failure(error.to_string(), Instant::now()) {
+            log::warn!(
+                "Fuente {} en cuarentena {} s tras fallar: {}",
+                source,
+                backoff.as_secs(),
+                error
+            );
+        }
+    }
+}
+
+/// Estado de las fuentes que han fallado alguna vez.
+pub fn status() -> Vec<SourceStatus> {
+    let now = Instant::now();
+    match registry().lock() {
+        Ok(registry) => registry
+            .iter()
+            .map(|(source, health)| health.status(source, now))
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Nombres de las fuentes en cuarentena.
+pub fn degraded() -> Vec<String> {
+    status()
+        .into_iter()
+        .filter(|s| s.degraded)
+        .map(|s| s.source)
+        .collect()
+}
+
+/// Ejecuta un comando con un tiempo máximo, matándolo si no termina.
+///
+/// La salida estándar se lee en otro hilo para que un volcado grande no
+/// bloquee al proceso mientras se espera.
+///
+/// # Returns
+/// `Err(String)` si no se pudo lanzar o si se agotó el tiempo.
+pub fn output_with_timeout(command: &mut Command, timeout: Duration) -> Result<Output, String> {
+    let mut child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| e.to_string())?;
+
+    let mut stdout = child.stdout.take();
+    let reader = std::thread::spawn(move || {
+        let mut buffer = Vec::new();
+        if let Some(stdout) = stdout.as_mut() {
+            let _ = stdout.read_to_end(&mut buffer);
+        }
+        buffer
+    });
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) if Instant::now() < deadline => std::thread::sleep(POLL_INTERVAL),
+            Ok(None) => {
+                // No se espera al lector: un nieto (ej: `ss` bajo `sudo`)
+                // podría mantener abierta la salida
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("sin respuesta en {} s", timeout.as_secs_f32()));
+            }
+            Err(e) => return Err(e.to_string()),
+        }
+    };
+
+    Ok(Output {
+        status,
+        stdout: reader.join().unwrap_or_default(),
+        stderr: Vec::new(),
+    })
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verifica la entrada en cuarentena, la espera creciente y la recuperación
+    #[test]
+    fn test_quarantine_backoff() {
+        let now = Instant::now();
+        let mut health = SourceHealth::default();
+        assert_eq!(health.failure("timeout".into(), now), None);
+        assert_eq!(health.failure("timeout".into(), now), None);
+        assert_eq!(health.failure("timeout".into(), now), Some(BASE_BACKOFF));
+        assert!(!health.available(now));
+        assert!(health.status("ss", now).degraded);
+
+        // Al terminar la cuarentena se prueba; un fallo duplica la espera
+        let later = now + BASE_BACKOFF;
+        assert!(health.available(later));
+        assert_eq!(
+            health.failure("timeout".into(), later),
+            Some(BASE_BACKOFF * 2)
+        );
+
+        health.success();
+        assert!(health.available(later));
+        assert_eq!(health.failures, 0);
+    }
+
+    /// Verifica que un comando colgado se corta al agotar el tiempo
+    #[test]
+    fn test_output_with_timeout() {
+        let started = Instant::now();
+        let result =
+            output_with_timeout(Command::new("sleep").arg("5"), Duration::from_millis(100));
+        assert!(result.is_err());
+        assert!(started.elapsed() < Duration::from_secs(2));
+
+        let output = output_with_timeout(Command::new("echo").arg("hola"), SOURCE_TIMEOUT).unwrap();
+        assert_eq!(output.stdout, b"hola\n");
+    }
+}
@@ -0,0 +1,207 @@
+/// Módulo de los puertos del Windows anfitrión en WSL2.
+///
+/// Dentro de WSL2, `ss` y `/proc/net` solo ven los sockets de la máquina
+/// virtual: los servidores de Windows (y los reenvíos de `localhost` de
+/// WSL, `wslrelay.exe`) no aparecen. Con [`set_windows_scan`] el escaneo
+/// pregunta también al lado Windows por interop:
+///
+/// - `netstat.exe -ano` da los sockets TCP y UDP con su PID de Windows,
+/// - `tasklist.exe /fo csv /nh` da el nombre de cada PID.
+///
+/// Los puertos de Windows se añaden aparte (`origin = windows`): el 3000
+/// de Windows no es el 3000 de Linux. Sus PIDs no son de este sistema,
+/// así que van con PID 0 y el de Windows en el nombre del proceso
+/// ("node.exe (PID Windows 4242)"); un kill desde Linux no los alcanza.
+///
+/// `netstat.exe` traduce los nombres de los estados según el idioma de
+/// Windows, así que un TCP en escucha se reconoce por su dirección
+/// remota (puerto 0), no por "LISTENING".
+use std::collections::HashMap;
+use std::fs;
+use std::process::Command;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::ports::{PortInfo, PortOrigin, SocketState};
+use crate::scan::clean_address;
+use crate::sources::{self, SOURCE_TIMEOUT};
+
+/// Fuente del lado Windows (ver `sources`)
+const NETSTAT_SOURCE: &str = "netstat.exe";
+
+/// Escanear también los puertos de Windows (ver [`set_windows_scan`])
+static WINDOWS_SCAN: AtomicBool = AtomicBool::new(false);
+
+/// Indica si el proceso corre dentro de WSL (el kernel de Microsoft).
+pub fn is_wsl() -> bool {
+    fs::read_to_string("/proc/sys/kernel/osrelease")
+        .is_ok_and(|release| release.to_lowercase().contains("microsoft"))
+}
+
+/// Activa o desactiva el escaneo de los puertos del Windows anfitrión
+/// (`scan_windows` en la configuración de PortSlayer).
+pub fn set_windows_scan(enabled: bool) {
+    WINDOWS_SCAN.store(enabled, Ordering::Relaxed);
+}
+
+/// Indica si el escaneo incluye los puertos de Windows.
+pub(crate) fn windows_scan() -> bool {
+    WINDOWS_SCAN.load(Ordering::Relaxed)
+}
+
+/// Escanea los sockets del lado Windows.
+///
+/// # Returns
+/// Los puertos abiertos (o, con `all_states`, en cualquier estado), o
+/// una lista vacía si `netstat.exe` no está disponible.
+pub(crate) fn scan_windows_ports(all_states: bool) -> Vec<PortInfo> {
+    if !sources::available(NETSTAT_SOURCE) {
+        return Vec::new();
+    }
+    let mut netstat = Command::new("netstat.exe");
+    netstat.arg("-ano");
+    let output = match sources::output_with_timeout(&mut netstat, SOURCE_TIMEOUT) {
+        Ok(output) => {
+            sources::record_success(NETSTAT_SOURCE);
+            output
+        }
+        Err(e) => {
+            sources::record_failure(NETSTAT_SOURCE, &e);
+            return Vec::new();
+        }
+    };
+    let mut tasklist = Command::new("tasklist.exe");
+    tasklist.args(["/fo", "csv", "/nh"]);
+    let names = sources::output_with_timeout(&mut tasklist, SOURCE_TIMEOUT)
+        .map(|output| parse_tasklist(&String::from_utf8_lossy(&output.stdout)))
+        .unwrap_or_default();
+    parse_netstat(&String::from_utf8_lossy(&output.stdout), &names)
+        .into_iter()
+        .filter(|p| all_states || p.state.is_open())
+        .collect()
+}
+
+/// Interpreta la salida de `netstat.exe -ano`.
+///
+/// ```text
+///   TCP    0.0.0.0:135        0.0.0.0:0          LISTENING       1104
+///   TCP    [::1]:3000         [::]:0             LISTENING       4242
+///   UDP    0.0.0.0:5353       *:*                                2516
+/// ```
+///
+/// Las cabeceras (traducidas) y las líneas de otros protocolos se
+/// ignoran.
+fn parse_netstat(output: &str, names: &HashMap<u32, String>) -> Vec<PortInfo> {
+    let mut ports: Vec<PortInfo> = Vec::new();
+    for line in output.lines() {
+        let columns: Vec<&str> = line.split_whitespace().collect();
+        let (protocol, local, remote, pid, state) = match columns.as_slice() {
+            [proto, local, remote, state, pid] if proto.eq_ignore_ascii_case("tcp") => {
+                ("tcp", *local, *remote, *pid, Some(*state))
+            }
+            [proto, local, remote, pid] if proto.eq_ignore_ascii_case("udp") => {
+                ("udp", *local, *remote, *pid, None)
+            }
+            _ => continue,
+        };
+        let Some((address, port)) = split_address(local) else {
+            continue;
+        };
+        let Ok(pid) = pid.parse::<u32>() else {
+            continue;
+        };
+        let state = match state {
+            None => SocketState::Unconn,
+            Some(_) if split_address(remote).is_some_and(|(_, port)| port == 0) => {
+                SocketState::Listen
+            }
+            Some(state) => match SocketState::parse(state) {
+                Some(state) => state,
+                None => continue,
+            },
+        };
+        let name = names.get(&pid).map_or("?", String::as_str);
+        let info = PortInfo {
+            protocol: protocol.to_string(),
+            port,
+            local_address: address,
+            pid: 0,
+            process_name: format!("{} (PID Windows {})", name, pid),
+            netns: None,
+            uid: None,
+            approximate: false,
+            started_at: None,
+            queue: None,
+            origin: PortOrigin::Windows,
+            state,
+        };
+        // Los sockets TCP salientes repiten puerto local: uno por estado
+        let duplicate = ports.iter().any(|p| {
+            (&p.protocol, p.port, &p.local_address, p.state)
+                == (&info.protocol, info.port, &info.local_address, info.state)
+        });
+        if !duplicate {
+            ports.push(info);
+        }
+    }
+    ports
+}
+
+/// Dirección y puerto de una columna de `netstat.exe` ("0.0.0.0:135",
+/// "[::]:445", "[fe80::1%12]:139"). `None` para "*:*".
+fn split_address(text: &str) -> Option<(String, u16)> {
+    let (address, port) = text.rsplit_once(':')?;
+    Some((clean_address(address), port.parse().ok()?))
+}
+
+/// Nombre de cada PID de Windows según `tasklist.exe /fo csv /nh`:
+/// `"svchost.exe","1104","Services","0","12.345 K"`.
+fn parse_tasklist(output: &str) -> HashMap<u32, String> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split("\",\"");
+            let name = fields.next()?.trim_start_matches('"');
+            let pid = fields.next()?.parse().ok()?;
+            Some((pid, name.to_string()))
+        })
+        .collect()
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verifica la lectura de `netstat.exe` en inglés y en español, con
+    /// los nombres de `tasklist.exe`
+    #[test]
+    fn test_parse_netstat() {
+        let names = parse_tasklist(
+            "\"System\",\"4\",\"Services\",\"0\",\"148 K\"\r\n\
+             \"node.exe\",\"4242\",\"Console\",\"1\",\"51.220 K\"\r\n",
+        );
+        assert_eq!(names.get(&4242).map(String::as_str), Some("node.exe"));
+
+        let output = "\r\nConexiones activas\r\n\r\n  Proto  Dirección local  Dirección remota  Estado  PID\r\n\
+            \x20 TCP    0.0.0.0:135        0.0.0.0:0          ESCUCHANDO      1104\r\n\
+            \x20 TCP    [::1]:3000         [::]:0             LISTENING       4242\r\n\
+            \x20 TCP    192.168.1.5:52100  140.82.112.3:443   ESTABLISHED     4242\r\n\
+            \x20 UDP    0.0.0.0:5353       *:*                                2516\r\n";
+        let ports = parse_netstat(output, &names);
+        assert_eq!(ports.len(), 4);
+        assert_eq!(ports[0].state, SocketState::Listen);
+        assert_eq!(ports[0].process_name, "? (PID Windows 1104)");
+        assert_eq!(
+            (ports[1].local_address.as_str(), ports[1].port),
+            ("::1", 3000)
+        );
+        assert_eq!(ports[1].process_name, "node.exe (PID Windows 4242)");
+        assert_eq!(ports[1].origin, PortOrigin::Windows);
+        assert_eq!(ports[1].pid, 0);
+        assert_eq!(ports[2].state, SocketState::Established);
+        assert_eq!(ports[3].state, SocketState::Unconn);
+    }
+}
@@ -96,6 +96,15 @@ echo -e "${BLUE}[3/5]${NC} Instalando binario en $INSTALL_DIR..."
 sudo cp "target/release/$BINARY_NAME" "$INSTALL_DIR/$BINARY_NAME"
 sudo chmod +x "$INSTALL_DIR/$BINARY_NAME"
 
+# Política de polkit y servicio de D-Bus para los kills con permisos:
+# el diálogo muestra qué proceso y puertos se van a cerrar
+sudo install -Dm644 assets/polkit/io.github.artur282.portslayer.policy \
+    /usr/share/polkit-1/actions/io.github.artur282.portslayer.policy
+sudo install -Dm644 assets/dbus/io.github.artur282.PortSlayer1.service \
+    /usr/share/dbus-1/system-services/io.github.artur282.PortSlayer1.service
+sudo install -Dm644 assets/dbus/io.github.artur282.PortSlayer1.conf \
+    /usr/share/dbus-1/system.d/io.github.artur282.PortSlayer1.conf
+
 echo -e "${GREEN}✓ Binario instalado${NC}"
 
 # ─── Configurar autostart ─────────────────────────────
@@ -40,6 +40,9 @@ if [ -f "$INSTALL_DIR/$BINARY_NAME" ]; then
 else
     echo -e "${GREEN}✓ Binario no encontrado (ya eliminado)${NC}"
 fi
+sudo rm -f /usr/share/polkit-1/actions/io.github.artur282.portslayer.policy \
+    /usr/share/dbus-1/system-services/io.github.artur282.PortSlayer1.service \
+    /usr/share/dbus-1/system.d/io.github.artur282.PortSlayer1.conf
 
 # ─── Eliminar autostart ───────────────────────────────
 echo -e "${YELLOW}[3/4]${NC} Eliminando autostart..."
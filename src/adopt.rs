@@ -0,0 +1,291 @@
+/// Módulo de adopción de procesos como servicios de systemd.
+///
+/// `portslayer adopt <PID>` convierte un servidor de desarrollo lanzado
+/// a mano en un servicio de usuario de systemd: a partir de la línea de
+/// comandos, el directorio y el entorno del proceso (capturados como en
+/// `restart`) genera `portslayer-<proceso>-<puerto>.service` en
+/// `~/.config/systemd/user/`. A partir de ahí el tray lo reconoce por su
+/// cgroup y ofrece detenerlo o reiniciarlo con `systemctl --user`.
+///
+/// El entorno se guarda aparte, en `~/.config/portslayer/units/` con
+/// permisos 0600 (`EnvironmentFile=`), porque puede contener secretos y
+/// las unidades de systemd suelen ser legibles por todos.
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::i18n::{trf, Text};
+use crate::paths;
+use crate::port_scanner;
+use crate::restart::{self, ProcessSnapshot};
+
+/// Prefijo de las unidades generadas (así el tray las reconoce)
+pub const UNIT_PREFIX: &str = "portslayer-";
+
+/// Unidad de systemd generada para un proceso
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnitFile {
+    /// Nombre de la unidad (ej: "portslayer-node-3000.service")
+    pub name: String,
+    /// Contenido del archivo `.service`
+    pub content: String,
+    /// Contenido del `EnvironmentFile` (vacío si no se leyó el entorno)
+    pub environment: String,
+}
+
+impl UnitFile {
+    /// Genera la unidad a partir de una captura del proceso.
+    ///
+    /// # Arguments
+    /// * `snapshot` - Proceso capturado (`restart::capture`)
+    /// * `exe` - Ruta absoluta del ejecutable si `cmdline[0]` es relativo
+    /// * `name` - Nombre de la unidad sin prefijo ni sufijo (por defecto
+    ///   `<proceso>-<puerto>`)
+    pub fn generate(snapshot: &ProcessSnapshot, exe: Option<&Path>, name: Option<&str>) -> Self {
+        let base = match name {
+            Some(name) => sanitize(name),
+            None if snapshot.port > 0 => {
+                format!("{}-{}", sanitize(&snapshot.process_name), snapshot.port)
+            }
+            None => sanitize(&snapshot.process_name),
+        };
+        let name = format!("{}{}.service", UNIT_PREFIX, base);
+
+        let mut args = snapshot.cmdline.clone();
+        if let Some(exe) = exe.filter(|_| !args[0].starts_with('/')) {
+            args[0] = exe.display().to_string();
+        }
+        let exec_start: Vec<String> = args.iter().map(|arg| quote_arg(arg)).collect();
+        let port = if snapshot.port > 0 {
+            format!(" (puerto {})", snapshot.port)
+        } else {
+            String::new()
+        };
+
+        let mut content = format!(
+            "# Generado por `portslayer adopt` a partir del PID {}\n\
+             [Unit]\n\
+             Description=PortSlayer: {}{}\n\
+             \n\
+             [Service]\n\
+             Type=simple\n\
+             WorkingDirectory={}\n\
+             ExecStart={}\n",
+            snapshot.pid,
+            snapshot.process_name,
+            port,
+            escape_specifiers(&snapshot.cwd.display().to_string()),
+            exec_start.join(" ")
+        );
+        if snapshot.env.is_some() {
+            content.push_str(&format!(
+                "EnvironmentFile={}\n",
+                escape_specifiers(&env_path(&name).display().to_string())
+            ));
+        }
+        content.push_str("Restart=on-failure\n\n[Install]\nWantedBy=default.target\n");
+
+        let environment = snapshot
+            .env
+            .iter()
+            .flatten()
+            .filter(|(key, value)| !key.is_empty() && !value.contains('\n'))
+            .map(|(key, value)| format!("{}=\"{}\"\n", key, escape(value)))
+            .collect();
+
+        Self {
+            name,
+            content,
+            environment,
+        }
+    }
+
+    /// Escribe la unidad y su entorno, y recarga systemd.
+    ///
+    /// # Returns
+    /// Ruta del archivo `.service` instalado.
+    pub fn install(&self) -> Result<PathBuf, String> {
+        let unit_path = unit_dir().join(&self.name);
+        write_file(&unit_path, &self.content, 0o644)?;
+        if !self.environment.is_empty() {
+            write_file(&env_path(&self.name), &self.environment, 0o600)?;
+        }
+        systemctl(&["daemon-reload"])?;
+        Ok(unit_path)
+    }
+}
+
+/// Captura un proceso en marcha y genera su unidad.
+///
+/// # Returns
+/// `Err(String)` si el proceso no existe o no es legible.
+pub fn unit_for_pid(pid: u32, name: Option<&str>) -> Result<UnitFile, String> {
+    let (port, protocol) = port_scanner::scan_open_ports()
+        .iter()
+        .find(|p| p.pid == pid)
+        .map(|p| (p.port, p.protocol.clone()))
+        .unwrap_or((0, "tcp".to_string()));
+    let snapshot = restart::capture(pid, port, &protocol)?;
+    let exe = fs::read_link(format!("/proc/{}/exe", pid)).ok();
+    Ok(UnitFile::generate(&snapshot, exe.as_deref(), name))
+}
+
+/// Sustituye el proceso manual por el servicio: lo mata, espera a que
+/// el puerto quede libre y arranca la unidad.
+pub fn start_in_place(unit: &UnitFile, pid: u32, port: u16, protocol: &str) -> Result<(), String> {
+    port_scanner::kill_process(pid)?;
+    if port > 0 && !restart::wait_port_free(port, protocol) {
+        return Err(trf(Text::PortStillBusy, &[&port]));
+    }
+    systemctl(&["start", &unit.name])
+}
+
+/// Unidad adoptada que gestiona un proceso, leída de su cgroup.
+///
+/// # Returns
+/// `None` si el proceso no pertenece a una unidad `portslayer-*`.
+pub fn managed_unit(pid: u32) -> Option<String> {
+    if pid == 0 {
+        return None;
+    }
+    let cgroup = fs::read_to_string(format!("/proc/{}/cgroup", pid)).ok()?;
+    unit_from_cgroup(&cgroup)
+}
+
+/// Ejecuta `systemctl --user` con los argumentos dados.
+pub fn systemctl(args: &[&str]) -> Result<(), String> {
+    let output = Command::new("systemctl")
+        .arg("--user")
+        .args(args)
+        .output()
+        .map_err(|e| format!("No se pudo ejecutar systemctl: {}", e))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "systemctl --user {} falló: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// Busca la unidad `portslayer-*.service` en el contenido de `/proc/<pid>/cgroup`.
+fn unit_from_cgroup(cgroup: &str) -> Option<String> {
+    cgroup
+        .lines()
+        .flat_map(|line| line.rsplit(':').next().unwrap_or("").split('/'))
+        .find(|part| part.starts_with(UNIT_PREFIX) && part.ends_with(".service"))
+        .map(str::to_string)
+}
+
+/// Directorio de unidades de usuario de systemd.
+fn unit_dir() -> PathBuf {
+    paths::config_dir()
+        .parent()
+        .map(|dir| dir.join("systemd/user"))
+        .unwrap_or_else(|| PathBuf::from("systemd/user"))
+}
+
+/// Archivo de entorno de una unidad.
+fn env_path(unit_name: &str) -> PathBuf {
+    paths::config_dir()
+        .join("units")
+        .join(unit_name.replace(".service", ".env"))
+}
+
+/// Escribe un archivo creando su directorio, con los permisos indicados.
+fn write_file(path: &Path, content: &str, mode: u32) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(true)
+        .mode(mode)
+        .open(path)
+        .map_err(|e| format!("No se pudo escribir {}: {}", path.display(), e))?;
+    file.write_all(content.as_bytes())
+        .map_err(|e| format!("No se pudo escribir {}: {}", path.display(), e))
+}
+
+/// Deja solo caracteres válidos en nombres de unidad.
+fn sanitize(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect()
+}
+
+/// Escapa `\` y `"` para un valor entre comillas.
+fn escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Escapa los especificadores de systemd (`%`) en rutas.
+fn escape_specifiers(path: &str) -> String {
+    path.replace('%', "%%")
+}
+
+/// Argumento entre comillas para `ExecStart=`, escapando también las
+/// variables de systemd (`$`).
+fn quote_arg(arg: &str) -> String {
+    format!("\"{}\"", escape_specifiers(&escape(arg)).replace('$', "$$"))
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verifica la unidad generada a partir de una captura
+    #[test]
+    fn test_generate_unit() {
+        let snapshot = ProcessSnapshot {
+            pid: 42,
+            process_name: "node".into(),
+            port: 3000,
+            protocol: "tcp".into(),
+            cmdline: vec!["node".into(), "server.js".into(), "--title=50%".into()],
+            cwd: PathBuf::from("/home/dev/app"),
+            env: Some(vec![("API_KEY".into(), "a\"b".into())]),
+            captured_at: 0,
+        };
+        let unit = UnitFile::generate(&snapshot, Some(Path::new("/usr/bin/node")), None);
+        assert_eq!(unit.name, "portslayer-node-3000.service");
+        assert!(unit
+            .content
+            .contains("ExecStart=\"/usr/bin/node\" \"server.js\" \"--title=50%%\"\n"));
+        assert!(unit.content.contains("WorkingDirectory=/home/dev/app\n"));
+        assert!(unit.content.contains("EnvironmentFile="));
+        assert_eq!(unit.environment, "API_KEY=\"a\\\"b\"\n");
+
+        let named = UnitFile::generate(&snapshot, None, Some("mi api"));
+        assert_eq!(named.name, "portslayer-mi-api.service");
+    }
+
+    /// Verifica la detección de la unidad en el cgroup del proceso
+    #[test]
+    fn test_unit_from_cgroup() {
+        let cgroup = "0::/user.slice/user-1000.slice/user@1000.service/app.slice/portslayer-node-3000.service\n";
+        assert_eq!(
+            unit_from_cgroup(cgroup).as_deref(),
+            Some("portslayer-node-3000.service")
+        );
+        assert_eq!(
+            unit_from_cgroup("0::/user.slice/user-1000.slice/session-2.scope\n"),
+            None
+        );
+    }
+}
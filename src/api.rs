@@ -0,0 +1,1535 @@
+/// Módulo de la API HTTP del modo daemon.
+///
+/// Expone un servidor HTTP/1.1 mínimo (sin dependencias externas)
+/// que responde JSON para que scripts y dashboards remotos usen el
+/// motor de escaneo de PortSlayer sin el system tray.
+///
+/// ## Endpoints
+/// - `GET /ports` → lista de puertos abiertos del último escaneo
+/// - `POST /kill/{pid}` → termina el proceso indicado (los críticos,
+///   como `sshd` o PID 1, solo con `?confirm=true`; ver `safety`)
+/// - `POST /kill-parent/{pid}?confirm=true` → mata al padre de un zombi
+///   sin hilos vivos, el único que puede liberarlo (ver `zombie`)
+/// - `POST /kill-range/{inicio}/{fin}?protocol=tcp|udp` → termina los
+///   procesos de un rango de puertos (respetando los "no matar")
+/// - `GET /impact/{pid}` → conexiones que se cortarían al matarlo
+/// - `GET /restartable`, `POST /restart/{índice}` → relanza procesos cerrados
+/// - `GET /history?limit=N` → eventos recientes del historial (y métricas
+///   del escritor: líneas en cola, escritas y descartadas)
+/// - `GET /history/ports?at=<unix>` → puertos abiertos en ese momento,
+///   reconstruidos desde el historial
+/// - `GET /history/changes?since=<unix>` → puertos abiertos, cerrados y
+///   con otro PID desde ese momento (ver `history::diff_scans`)
+/// - `GET /pinned` → puertos fijados y su estado (abiertos o cerrados),
+///   sin aplicar filtros
+/// - `GET /sources` → estado de las fuentes de escaneo (las que no
+///   responden quedan en cuarentena, ver `sources`) y precisión del
+///   último recorrido de `/proc/*/fd` (muestreado en hosts enormes)
+/// - `GET /capabilities` → qué puede hacer la instalación (escaneo con
+///   privilegios, Docker, firewall, eBPF, notificaciones, portapapeles)
+///   para ocultar las acciones no disponibles
+/// - `GET /tunnels` → reenvíos (`ssh -L`, `socat`) entre los puertos
+///   abiertos, con su destino y si quedaron huérfanos (ver `tunnels`)
+/// - `GET /kill-stats` → efectividad de los kills por proceso (cuántos
+///   liberaron el puerto y cuántos se relanzaron, ver `effectiveness`)
+/// - `GET /events` → cambios en vivo como Server-Sent Events (ver `events`)
+/// - `GET /logs` → mensajes del log en vivo (escaneos, kills, errores)
+///   como Server-Sent Events `log`, empezando por los recientes
+/// - `GET /export?format=json|csv` → instantánea del último escaneo
+/// - `GET /graph?format=dot|json` → grafo de servicios locales
+/// - `GET /remote-scan?host=<host>&range=1-1024` → puertos TCP abiertos
+///   de otra máquina (TCP connect, con `concurrency` y `timeout` en ms
+///   opcionales; feature `remote`)
+/// - `GET /free-port?range=3000-9999` → primer puerto TCP libre del rango
+///   (según el escaneo y comprobado abriéndolo)
+/// - `GET /explain/{puerto}` → por qué está ocupado: dueño, `TIME_WAIT`,
+///   Docker y unidades `.socket` de systemd (ver `explain`)
+/// - `GET /blocks` → puertos bloqueados en el firewall por PortSlayer
+/// - `POST /block/{puerto}?protocol=tcp|udp`, `DELETE /block/{puerto}` →
+///   bloquea o desbloquea las conexiones entrantes a un puerto (ver
+///   `firewall`)
+/// - `GET /firewall` → gestor de firewall activo (ufw o firewalld) y su
+///   política por defecto
+/// - `POST /firewall/{puerto}?action=allow|deny&protocol=tcp|udp` →
+///   permite o deniega el puerto en ese gestor (ver `firewall_manager`)
+/// - `GET /watchlist`, `POST /watchlist/{puerto}?on=open,close,owner`,
+///   `DELETE /watchlist/{puerto}` → puertos vigilados (ver `watchlist`)
+/// - `GET /tags`, `POST /tags`, `DELETE /tags/{índice}` → etiquetas
+///   (las de paquetes compartidos aparecen en `shared` y son de solo lectura)
+/// - `GET /settings`, `PATCH /settings` → ajustes de la raíz de
+///   `config.toml`; el cuerpo JSON trae solo las claves que cambian y,
+///   si son válidas, se guardan y se anuncian en `/events` con un evento
+///   `settings` (ver `settings`)
+///
+/// `GET /ports` incluye las etiquetas de cada puerto y acepta
+/// `?tag=<color>` para filtrar por color de etiqueta, `?owner=<usuario>`
+/// (`me`, `root`, un UID o un nombre) para filtrar por dueño del socket,
+/// `?scope=loopback|exposed` para ver solo los locales o solo los
+/// accesibles desde la red y `?state=<estado>` (`time_wait`,
+/// `close_wait`, ... o `all`) para listar los sockets en ese estado en
+/// lugar de los que escuchan. Cada puerto indica si escucha en todas las
+/// interfaces (`exposed`) y, si se pueden leer las reglas del firewall,
+/// si es accesible desde la red (`reachable`, ver `firewall`) y, con ufw
+/// o firewalld activos, si su gestor lo permite (`firewall_manager`), y
+/// con `service_fingerprints` el servicio identificado por su banner
+/// (`service_fingerprint`) y su certificado si habla TLS (`tls`: sujeto,
+/// SAN, caducidad y si está caducado o es autofirmado). Con
+/// `http_health`, los servidores web llevan su último código de estado y
+/// latencia (`health`).
+///
+/// Todas las peticiones requieren autenticación según el backend de
+/// `auth` (por defecto `Authorization: Bearer <token>`), salvo el panel
+/// de invitado (`/` y `/dashboard/events`, ver `dashboard`) cuando se
+/// activa con `--dashboard`. Con el backend `peer` la API se sirve en un
+/// socket Unix en lugar de TCP.
+///
+/// Los errores responden `{"error": "<mensaje>", "code": "<tipo>"}`; el
+/// `code` (`permission_denied`, `process_not_found`, `protected_process`,
+/// `scan_failed`, ...) no depende del idioma, así que los clientes pueden
+/// reaccionar a él (pedir elevación ante `permission_denied`).
+use percent_encoding::percent_decode_str;
+use portslayer_core::{sources, KillError};
+use serde::Serialize;
+use serde_json::json;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{IpAddr, TcpListener};
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::net::UnixListener;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::auth::{self, Authenticator};
+use crate::capabilities;
+use crate::config::Config;
+use crate::connections;
+use crate::dashboard;
+use crate::docker;
+use crate::effectiveness::{self, KillStats, KillTarget};
+#[cfg(feature = "email")]
+use crate::email;
+use crate::events::{self, EventBus};
+use crate::explain;
+use crate::export::{self, ExportFormat};
+use crate::fingerprint;
+use crate::firewall::{self, BlockStore};
+use crate::firewall_manager;
+use crate::health;
+use crate::history::{self, HistoryEvent, HistoryEventKind};
+use crate::i18n::{tr, trf, Text};
+use crate::journal;
+use crate::kubernetes;
+use crate::pins::PinStore;
+#[cfg(feature = "remote")]
+use crate::port_scanner::RemoteOptions;
+use crate::port_scanner::{
+    self, KillFailure, OwnerFilter, PortInfo, ProcessFilter, ProtocolFilter, RangeFilter,
+    ScopeFilter, StateFilter,
+};
+use crate::project;
+use crate::restart;
+use crate::safety;
+use crate::sandbox;
+use crate::settings::{self, Settings, SettingsPatch};
+use crate::tags::{self, Tag, TagColor, TagStore};
+use crate::topology::{GraphFormat, Topology};
+use crate::tunnels;
+use crate::watchlist::{self, WatchEntry, WatchEvent, WatchStore};
+use crate::webhooks;
+use crate::zombie;
+
+/// Dirección por defecto del servidor (solo accesible localmente)
+pub const DEFAULT_BIND: &str = "127.0.0.1:7780";
+
+/// Cantidad de eventos devueltos por `/history` si no se indica `limit`
+const DEFAULT_HISTORY_LIMIT: usize = 100;
+
+/// Tamaño máximo aceptado para el cuerpo de una petición
+const MAX_BODY_SIZE: usize = 64 * 1024;
+
+/// Estado compartido entre las conexiones de la API
+#[derive(Debug, Clone)]
+pub struct ApiState {
+    /// Lista de puertos del último escaneo (compartida con el hilo de escaneo)
+    pub ports: Arc<Mutex<Vec<PortInfo>>>,
+    /// Cambios publicados por el hilo de escaneo para `/events`
+    pub events: Arc<EventBus>,
+    /// Backend de autenticación configurado
+    pub auth: Authenticator,
+    /// Sirve el panel web de invitado en `/` y `/dashboard/events`
+    pub dashboard: bool,
+}
+
+/// Petición HTTP ya parseada
+#[derive(Debug)]
+struct Request {
+    /// Método HTTP (GET, POST...)
+    method: String,
+    /// Ruta sin query string (ej: "/kill/1234")
+    path: String,
+    /// Parámetros de la query string
+    query: HashMap<String, String>,
+    /// Cabeceras con el nombre en minúsculas
+    headers: HashMap<String, String>,
+    /// Cuerpo de la petición (vacío si no hay)
+    body: String,
+    /// UID del cliente (solo en conexiones por socket Unix)
+    peer_uid: Option<u32>,
+    /// IP del cliente (solo en conexiones TCP)
+    peer_ip: Option<IpAddr>,
+}
+
+/// Tipo de un error de la API, en el campo `code` de la respuesta para
+/// que los clientes reaccionen sin interpretar el mensaje (por ejemplo,
+/// pedir elevación ante `permission_denied`)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "code", rename_all = "snake_case")]
+enum ApiError {
+    /// Parámetro, ruta o cuerpo inválidos
+    BadRequest,
+    /// Faltan las credenciales o no son válidas
+    Unauthorized,
+    /// El sistema rechazó el kill y el reintento con privilegios falló
+    PermissionDenied,
+    /// Puerto marcado "no matar"
+    ProtectedProcess,
+    /// Ruta o recurso inexistente
+    NotFound,
+    /// El proceso ya no existe
+    ProcessNotFound,
+    /// Método no soportado en esta ruta
+    MethodNotAllowed,
+    /// Acción peligrosa que hay que repetir con `?confirm=true`
+    ConfirmationRequired,
+    /// La petición choca con el estado actual (ej: un puerto vigilado
+    /// desde `config.toml`)
+    Conflict,
+    /// No se pudo escanear, con el origen del escaneo (ej: el host remoto)
+    #[cfg(feature = "remote")]
+    ScanFailed { source: String },
+    /// Fallo interno (disco, firewall, un kill que el sistema rechazó)
+    Internal,
+}
+
+impl ApiError {
+    /// Código de estado HTTP del error.
+    fn status(&self) -> u16 {
+        match self {
+            ApiError::BadRequest => 400,
+            ApiError::Unauthorized => 401,
+            ApiError::PermissionDenied | ApiError::ProtectedProcess => 403,
+            ApiError::NotFound | ApiError::ProcessNotFound => 404,
+            ApiError::MethodNotAllowed => 405,
+            ApiError::ConfirmationRequired | ApiError::Conflict => 409,
+            #[cfg(feature = "remote")]
+            ApiError::ScanFailed { .. } => 502,
+            ApiError::Internal => 500,
+        }
+    }
+
+    /// Error de un kill fallido según su motivo.
+    fn from_kill(failure: &KillFailure) -> Self {
+        match failure.error {
+            Some(KillError::PermissionDenied(_)) => ApiError::PermissionDenied,
+            Some(KillError::NoSuchProcess) => ApiError::ProcessNotFound,
+            Some(KillError::InvalidPid) => ApiError::BadRequest,
+            Some(KillError::Failed(_)) | None => ApiError::Internal,
+        }
+    }
+}
+
+/// Respuesta HTTP (JSON salvo en exportaciones CSV)
+#[derive(Debug)]
+struct Response {
+    /// Código de estado HTTP
+    status: u16,
+    /// Tipo MIME del cuerpo
+    content_type: &'static str,
+    /// Cuerpo serializado
+    body: String,
+}
+
+impl Response {
+    /// Respuesta exitosa con el valor JSON indicado
+    fn ok(body: serde_json::Value) -> Self {
+        Self {
+            status: 200,
+            content_type: "application/json",
+            body: body.to_string(),
+        }
+    }
+
+    /// Respuesta de error con formato `{"error": "...", "code": "..."}`
+    /// (más los campos del error, como `source` en `scan_failed`)
+    fn error(error: ApiError, message: &str) -> Self {
+        let mut body = json!(error);
+        body["error"] = json!(message);
+        Self {
+            status: error.status(),
+            content_type: "application/json",
+            body: body.to_string(),
+        }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// Servidor
+// ─────────────────────────────────────────────────────────────
+
+/// Inicia el servidor HTTP y atiende peticiones indefinidamente.
+///
+/// Cada conexión se atiende en su propio hilo; la API es de bajo
+/// tráfico, así que no hace falta un pool.
+///
+/// # Arguments
+/// * `bind` - Dirección `host:puerto` donde escuchar
+/// * `state` - Estado compartido con el hilo de escaneo
+///
+/// # Returns
+/// `Err` si no se pudo abrir el socket de escucha.
+pub fn serve(bind: &str, state: ApiState) -> io::Result<()> {
+    let listener = TcpListener::bind(bind)?;
+    log::info!("API HTTP escuchando en http://{}", bind);
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                let state = state.clone();
+                std::thread::spawn(move || {
+                    let _ = stream.set_read_timeout(Some(Duration::from_secs(5)));
+                    let peer_ip = stream.peer_addr().ok().map(|addr| addr.ip());
+                    if let Err(e) = handle_connection(stream, None, peer_ip, &state) {
+                        log::debug!("Error atendiendo conexión de la API: {}", e);
+                    }
+                });
+            }
+            Err(e) => log::warn!("Error aceptando conexión: {}", e),
+        }
+    }
+
+    Ok(())
+}
+
+/// Inicia la API en un socket Unix (backend `peer`).
+///
+/// El UID de cada cliente se obtiene del socket y lo valida el
+/// autenticador. El socket se crea con permisos 0666 solo si hay otros
+/// usuarios autorizados; si no, 0600.
+///
+/// # Arguments
+/// * `path` - Ruta del socket (se reemplaza si ya existe)
+/// * `state` - Estado compartido con el hilo de escaneo
+/// * `shared` - Permitir que otros usuarios se conecten
+pub fn serve_unix(path: &Path, state: ApiState, shared: bool) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    if path.exists() {
+        fs::remove_file(path)?;
+    }
+    let listener = UnixListener::bind(path)?;
+    let mode = if shared { 0o666 } else { 0o600 };
+    fs::set_permissions(path, fs::Permissions::from_mode(mode))?;
+    log::info!("API HTTP escuchando en el socket {}", path.display());
+
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                let state = state.clone();
+                std::thread::spawn(move || {
+                    let _ = stream.set_read_timeout(Some(Duration::from_secs(5)));
+                    let peer_uid = auth::peer_uid(&stream);
+                    if let Err(e) = handle_connection(stream, peer_uid, None, &state) {
+                        log::debug!("Error atendiendo conexión de la API: {}", e);
+                    }
+                });
+            }
+            Err(e) => log::warn!("Error aceptando conexión: {}", e),
+        }
+    }
+
+    Ok(())
+}
+
+/// Atiende una conexión: lee la petición, la autoriza (una sola vez: con
+/// PAM cada comprobación es una llamada a libpam), la enruta y responde.
+fn handle_connection<S: Read + Write>(
+    stream: S,
+    peer_uid: Option<u32>,
+    peer_ip: Option<IpAddr>,
+    state: &ApiState,
+) -> io::Result<()> {
+    let mut reader = BufReader::new(stream);
+    let request = read_request(&mut reader);
+    let mut stream = reader.into_inner();
+
+    let mut request = match request {
+        Some(request) => request,
+        None => {
+            return write_response(
+                &mut stream,
+                &Response::error(ApiError::BadRequest, tr(Text::InvalidRequest)),
+            )
+        }
+    };
+    request.peer_uid = peer_uid;
+    request.peer_ip = peer_ip;
+    log::debug!("API: {} {}", request.method, request.path);
+
+    // Panel de invitado: sin token y de solo lectura
+    if state.dashboard && request.method == "GET" {
+        match request.path.as_str() {
+            "/" => {
+                let page = Response {
+                    status: 200,
+                    content_type: "text/html; charset=utf-8",
+                    body: dashboard::DASHBOARD_HTML.to_string(),
+                };
+                return write_response(&mut stream, &page);
+            }
+            "/dashboard/events" => return dashboard::stream_events(stream, &state.ports),
+            _ => {}
+        }
+    }
+
+    if !is_authorized(&request, &state.auth) {
+        return write_response(
+            &mut stream,
+            &Response::error(ApiError::Unauthorized, tr(Text::InvalidCredentials)),
+        );
+    }
+
+    // Flujo de cambios: la conexión queda abierta hasta que el cliente cierre
+    if request.method == "GET" && request.path == "/events" {
+        let last_event_id = request
+            .headers
+            .get("last-event-id")
+            .and_then(|id| id.trim().parse().ok());
+        return events::stream_events(stream, &state.events, last_event_id);
+    }
+    if request.method == "GET" && request.path == "/logs" {
+        let last_event_id = request
+            .headers
+            .get("last-event-id")
+            .and_then(|id| id.trim().parse().ok());
+        return events::stream_logs(stream, last_event_id);
+    }
+
+    write_response(&mut stream, &dispatch(&request, state))
+}
+
+/// Lee y parsea una petición HTTP/1.1 desde el stream.
+///
+/// # Returns
+/// `Some(Request)` si la línea de petición y las cabeceras son válidas.
+fn read_request<R: BufRead>(reader: &mut R) -> Option<Request> {
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).ok()?;
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next()?.to_string();
+    let target = parts.next()?;
+    let (path, query) = parse_target(target);
+
+    let mut headers = HashMap::new();
+    loop {
+        let mut line = String::new();
+        if reader.read_line(&mut line).ok()? == 0 {
+            break;
+        }
+        let line = line.trim_end();
+        if line.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+        }
+    }
+
+    let body_len: usize = headers
+        .get("content-length")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(0);
+    if body_len > MAX_BODY_SIZE {
+        return None;
+    }
+    let mut body = vec![0u8; body_len];
+    reader.read_exact(&mut body).ok()?;
+
+    Some(Request {
+        method,
+        path,
+        query,
+        headers,
+        body: String::from_utf8(body).ok()?,
+        peer_uid: None,
+        peer_ip: None,
+    })
+}
+
+/// Separa la ruta de la query string y decodifica los parámetros
+/// (`%XX` y `+` como espacio, como en un formulario).
+///
+/// # Arguments
+/// * `target` - Destino de la petición (ej: "/history?limit=10")
+///
+/// # Returns
+/// Tupla `(ruta, parámetros)`.
+fn parse_target(target: &str) -> (String, HashMap<String, String>) {
+    let (path, query_str) = target.split_once('?').unwrap_or((target, ""));
+    let query = query_str
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (decode_component(key), decode_component(value))
+        })
+        .collect();
+    (path.to_string(), query)
+}
+
+/// Decodifica una clave o un valor de la query string.
+fn decode_component(text: &str) -> String {
+    percent_decode_str(&text.replace('+', " "))
+        .decode_utf8_lossy()
+        .into_owned()
+}
+
+/// Escribe la respuesta HTTP y cierra la conexión.
+fn write_response<W: Write>(stream: &mut W, response: &Response) -> io::Result<()> {
+    let reason = match response.status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        502 => "Bad Gateway",
+        _ => "Internal Server Error",
+    };
+
+    write!(
+        stream,
+        "HTTP/1.1 {} {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.status,
+        reason,
+        response.content_type,
+        response.body.len(),
+        response.body
+    )?;
+    stream.flush()
+}
+
+// ─────────────────────────────────────────────────────────────
+// Enrutado y endpoints
+// ─────────────────────────────────────────────────────────────
+
+/// Autoriza una petición y la enruta (lo que hace `handle_connection`
+/// con las que no son flujos).
+#[cfg(test)]
+fn route(request: &Request, state: &ApiState) -> Response {
+    if !is_authorized(request, &state.auth) {
+        return Response::error(ApiError::Unauthorized, tr(Text::InvalidCredentials));
+    }
+    dispatch(request, state)
+}
+
+/// Enruta una petición ya autorizada al endpoint correspondiente.
+fn dispatch(request: &Request, state: &ApiState) -> Response {
+    let segments: Vec<&str> = request
+        .path
+        .trim_matches('/')
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .collect();
+
+    match (request.method.as_str(), segments.as_slice()) {
+        ("GET", ["ports"]) => handle_get_ports(request, state),
+        ("POST", ["kill", pid]) => handle_kill(pid, request, state),
+        ("POST", ["kill-parent", pid]) => handle_kill_parent(pid, request),
+        ("POST", ["kill-range", start, end]) => handle_kill_range(start, end, request, state),
+        ("GET", ["impact", pid]) => handle_get_impact(pid, state),
+        ("GET", ["restartable"]) => handle_get_restartable(),
+        ("POST", ["restart", index]) => handle_restart(index),
+        ("GET", ["history"]) => handle_get_history(request),
+        ("GET", ["pinned"]) => handle_get_pinned(state),
+        ("GET", ["sources"]) => handle_get_sources(),
+        ("GET", ["capabilities"]) => Response::ok(json!(capabilities::get_capabilities())),
+        ("GET", ["tunnels"]) => handle_get_tunnels(state),
+        ("GET", ["kill-stats"]) => handle_get_kill_stats(),
+        ("GET", ["history", "ports"]) => handle_get_ports_at(request),
+        ("GET", ["history", "changes"]) => handle_get_changes(request),
+        ("GET", ["export"]) => handle_export(request, state),
+        ("GET", ["graph"]) => handle_graph(request, state),
+        #[cfg(feature = "remote")]
+        ("GET", ["remote-scan"]) => handle_remote_scan(request),
+        ("GET", ["explain", port]) => handle_explain(port),
+        ("GET", ["free-port"]) => handle_free_port(request),
+        ("GET", ["blocks"]) => Response::ok(json!(BlockStore::load())),
+        ("POST", ["block", port]) => handle_block(port, request, true),
+        ("DELETE", ["block", port]) => handle_block(port, request, false),
+        ("GET", ["firewall"]) => handle_get_firewall(),
+        ("POST", ["firewall", port]) => handle_set_firewall(port, request),
+        ("GET", ["watchlist"]) => handle_get_watchlist(state),
+        ("POST", ["watchlist", port]) => handle_watch(port, request),
+        ("DELETE", ["watchlist", port]) => handle_unwatch(port),
+        ("GET", ["tags"]) => handle_get_tags(),
+        ("POST", ["tags"]) => handle_add_tag(request),
+        ("DELETE", ["tags", index]) => handle_delete_tag(index),
+        ("GET", ["settings"]) => handle_get_settings(),
+        ("PATCH", ["settings"]) => handle_patch_settings(request, state),
+        (_, ["ports"])
+        | (_, ["kill", _])
+        | (_, ["kill-parent", _])
+        | (_, ["kill-range", _, _])
+        | (_, ["impact", _])
+        | (_, ["restartable"])
+        | (_, ["restart", _])
+        | (_, ["history"])
+        | (_, ["pinned"])
+        | (_, ["sources"])
+        | (_, ["capabilities"])
+        | (_, ["tunnels"])
+        | (_, ["kill-stats"])
+        | (_, ["history", "ports"])
+        | (_, ["history", "changes"])
+        | (_, ["events"])
+        | (_, ["logs"])
+        | (_, ["export"])
+        | (_, ["graph"])
+        | (_, ["remote-scan"])
+        | (_, ["explain", _])
+        | (_, ["free-port"])
+        | (_, ["blocks"])
+        | (_, ["block", _])
+        | (_, ["firewall"])
+        | (_, ["firewall", _])
+        | (_, ["watchlist", ..])
+        | (_, ["tags", ..])
+        | (_, ["settings"]) => {
+            Response::error(ApiError::MethodNotAllowed, tr(Text::MethodNotAllowed))
+        }
+        _ => Response::error(ApiError::NotFound, tr(Text::RouteNotFound)),
+    }
+}
+
+/// Comprueba las credenciales de la petición con el backend configurado.
+fn is_authorized(request: &Request, auth: &Authenticator) -> bool {
+    auth.authorize(
+        request.headers.get("authorization").map(String::as_str),
+        request.peer_uid,
+        request.peer_ip,
+    )
+}
+
+/// `GET /ports[?tag=color][&owner=usuario][&scope=alcance][&state=estado]`
+/// `[&process=patrón|&process_regex=regex]`: devuelve el último escaneo
+/// con sus etiquetas; con `state`, un escaneo de los sockets en ese
+/// estado.
+///
+/// Con el backend `peer`, `owner=me` es quien hace la petición y no el
+/// usuario del daemon.
+fn handle_get_ports(request: &Request, state: &ApiState) -> Response {
+    let ports = match request.query.get("state").map(|s| StateFilter::parse(s)) {
+        Some(Some(socket_state)) => port_scanner::filter_ports(
+            &port_scanner::scan_all_sockets(),
+            ProtocolFilter::All,
+            RangeFilter::All,
+            socket_state,
+        ),
+        Some(None) => return Response::error(ApiError::BadRequest, tr(Text::UnknownSocketState)),
+        None => match state.ports.lock() {
+            Ok(ports) => ports.clone(),
+            Err(_) => Vec::new(),
+        },
+    };
+
+    let color = match request.query.get("tag") {
+        Some(name) => match TagColor::parse(name) {
+            Some(color) => Some(color),
+            None => return Response::error(ApiError::BadRequest, tr(Text::UnknownTagColor)),
+        },
+        None => None,
+    };
+
+    let owner = match request.query.get("owner").map(|s| OwnerFilter::parse(s)) {
+        Some(Some(OwnerFilter::Mine)) => match request.peer_uid {
+            Some(uid) => OwnerFilter::User(uid),
+            None => OwnerFilter::Mine,
+        },
+        Some(Some(owner)) => owner,
+        Some(None) => return Response::error(ApiError::BadRequest, tr(Text::UnknownOwner)),
+        None => OwnerFilter::All,
+    };
+    let scope = match request.query.get("scope").map(|s| ScopeFilter::parse(s)) {
+        Some(Some(scope)) => scope,
+        Some(None) => return Response::error(ApiError::BadRequest, tr(Text::UnknownScope)),
+        None => ScopeFilter::All,
+    };
+    let process = match (
+        request.query.get("process_regex"),
+        request.query.get("process"),
+    ) {
+        (Some(pattern), _) => match ProcessFilter::regex(pattern) {
+            Ok(process) => process,
+            Err(e) => {
+                return Response::error(
+                    ApiError::BadRequest,
+                    &trf(Text::InvalidProcessRegex, &[&e]),
+                )
+            }
+        },
+        (None, Some(pattern)) => ProcessFilter::Glob(pattern.clone()),
+        (None, None) => ProcessFilter::All,
+    };
+    let ports: Vec<PortInfo> = ports
+        .into_iter()
+        .filter(|p| owner.matches(p.uid) && scope.matches(p) && process.matches(p))
+        .collect();
+
+    let store = TagStore::load();
+    let firewall = firewall::rules();
+    let manager = firewall_manager::policy();
+    let bindings = docker::bindings();
+    let pods = kubernetes::pods();
+    let annotated: Vec<serde_json::Value> = tags::filter_by_color(&ports, &store, color)
+        .iter()
+        .map(|p| {
+            let mut value = tags::annotate(p, &store);
+            value["exposed"] = json!(p.listens_on_all_interfaces());
+            if let Some(reachable) = firewall::reachable(p, firewall.as_deref()) {
+                value["reachable"] = json!(reachable);
+            }
+            if let Some(binding) = docker::binding_for(p, &bindings) {
+                value["docker"] = json!(binding);
+            }
+            if let Some(pod) = kubernetes::pod_for(p, &pods) {
+                value["pod"] = json!(pod);
+            }
+            if let Some(sandbox) = sandbox::detect(p.pid) {
+                value["sandbox"] = json!(sandbox);
+            }
+            if let Some(defunct) = zombie::detect(p.pid) {
+                value["defunct"] = json!(defunct);
+            }
+            if let Some(project) = project::project_for(p.pid) {
+                value["project"] = json!(project);
+            }
+            if let Some(service) = fingerprint::get(p) {
+                value["service_fingerprint"] = json!(service);
+            }
+            if let Some(health) = health::get(p) {
+                value["health"] = json!(health);
+            }
+            if let Some(certificate) = fingerprint::certificate(p) {
+                value["tls"] = json!(certificate);
+                value["tls"]["expired"] = json!(certificate.expired(history::unix_now()));
+                value["tls"]["self_signed"] = json!(certificate.self_signed());
+            }
+            if let Some(policy) = manager.as_ref().filter(|_| !p.is_loopback()) {
+                value["firewall_manager"] = json!({
+                    "name": policy.manager.name(),
+                    "allowed": policy.allows(&p.protocol, p.port),
+                });
+            }
+            value
+        })
+        .collect();
+    Response::ok(json!({ "count": annotated.len(), "ports": annotated }))
+}
+
+/// `POST /kill/{pid}`: termina el proceso y lo registra en el historial.
+///
+/// Se rechaza con 403 si algún puerto del proceso está marcado "no matar",
+/// y con 409 si es un proceso crítico sin `?confirm=true`.
+fn handle_kill(pid_str: &str, request: &Request, state: &ApiState) -> Response {
+    let pid: u32 = match pid_str.parse() {
+        Ok(pid) if pid > 0 => pid,
+        _ => return Response::error(ApiError::BadRequest, tr(Text::InvalidPid)),
+    };
+
+    let store = TagStore::load();
+    let protected = match state.ports.lock() {
+        Ok(ports) => ports.iter().any(|p| p.pid == pid && store.is_protected(p)),
+        Err(_) => false,
+    };
+    if protected {
+        return Response::error(ApiError::ProtectedProcess, tr(Text::ProtectedProcess));
+    }
+    if request.query.get("confirm").is_some_and(|v| v == "true") {
+        safety::confirm(pid);
+    } else if let Err(e) = safety::check(pid) {
+        return Response::error(ApiError::ConfirmationRequired, &e);
+    }
+
+    log::info!("API: solicitud de cierre del PID {}", pid);
+    let owned_port = match state.ports.lock() {
+        Ok(ports) => ports
+            .iter()
+            .find(|p| p.pid == pid)
+            .map(|p| (p.port, p.protocol.clone(), p.process_name.clone())),
+        Err(_) => None,
+    };
+    let (port, protocol, process_name) =
+        owned_port.unwrap_or((0, "tcp".to_string(), String::new()));
+    restart::remember(pid, port, &protocol);
+    let target = KillTarget::capture(pid, port, &protocol);
+
+    match port_scanner::try_kill_process(pid) {
+        Ok(()) => {
+            if let Some(target) = target {
+                effectiveness::track(target);
+            }
+            let killed = [HistoryEvent {
+                timestamp: history::unix_now(),
+                kind: HistoryEventKind::Killed,
+                protocol: if port > 0 { protocol } else { String::new() },
+                port,
+                pid,
+                process_name,
+            }];
+            history::append_events(&killed);
+            state.events.publish(&killed);
+            let config = Config::load();
+            webhooks::send(&config.webhooks, &killed);
+            #[cfg(feature = "email")]
+            email::queue(config.email.as_ref(), &killed);
+            Response::ok(json!({ "killed": pid }))
+        }
+        Err(e) => Response::error(ApiError::from_kill(&e), &e.message),
+    }
+}
+
+/// `POST /kill-parent/{pid}`: mata al padre de un zombi sin hilos vivos.
+///
+/// Responde 404 si el PID no es un zombi retenido y 409 sin
+/// `?confirm=true`, nombrando al padre que se mataría.
+fn handle_kill_parent(pid_str: &str, request: &Request) -> Response {
+    let pid: u32 = match pid_str.parse() {
+        Ok(pid) if pid > 0 => pid,
+        _ => return Response::error(ApiError::BadRequest, tr(Text::InvalidPid)),
+    };
+    let (parent, name) = match zombie::reaping_parent(pid) {
+        Ok(parent) => parent,
+        Err(e) => return Response::error(ApiError::NotFound, &e),
+    };
+    if request.query.get("confirm").is_none_or(|v| v != "true") {
+        return Response::error(
+            ApiError::ConfirmationRequired,
+            &trf(Text::ZombieParentUnconfirmed, &[&name, &parent]),
+        );
+    }
+    log::info!(
+        "API: solicitud de matar al padre {} del zombi {}",
+        parent,
+        pid
+    );
+    match zombie::kill_parent(pid) {
+        Ok(parent) => Response::ok(json!({ "zombie": pid, "killed": parent })),
+        Err(e) => Response::error(ApiError::from_kill(&e), &e.message),
+    }
+}
+
+/// `POST /kill-range/{inicio}/{fin}`: termina los procesos de un rango.
+fn handle_kill_range(start: &str, end: &str, request: &Request, state: &ApiState) -> Response {
+    let (start, end): (u16, u16) = match (start.parse(), end.parse()) {
+        (Ok(start), Ok(end)) if start > 0 && start <= end => (start, end),
+        _ => return Response::error(ApiError::BadRequest, tr(Text::InvalidPortRange)),
+    };
+    let protocol = match request.query.get("protocol").map(String::as_str) {
+        None | Some("all") => ProtocolFilter::All,
+        Some("tcp") => ProtocolFilter::Tcp,
+        Some("udp") => ProtocolFilter::Udp,
+        Some(_) => return Response::error(ApiError::BadRequest, tr(Text::UnknownProtocol)),
+    };
+
+    let store = TagStore::load();
+    let protected: Vec<u32> = match state.ports.lock() {
+        Ok(ports) => ports
+            .iter()
+            .filter(|p| p.pid > 0 && store.is_protected(p))
+            .map(|p| p.pid)
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+
+    log::info!("API: solicitud de cierre del rango {}-{}", start, end);
+    match port_scanner::kill_port_range(start, end, protocol, &protected) {
+        Ok(count) => Response::ok(json!({ "killed": count, "start": start, "end": end })),
+        Err(e) => Response::error(ApiError::Internal, &e),
+    }
+}
+
+/// `GET /remote-scan`: escanea los puertos TCP de otra máquina.
+#[cfg(feature = "remote")]
+fn handle_remote_scan(request: &Request) -> Response {
+    let Some(host) = request.query.get("host").filter(|h| !h.is_empty()) else {
+        return Response::error(ApiError::BadRequest, tr(Text::MissingHost));
+    };
+    let range = match query_range(request, (1, 1024)) {
+        Ok(range) => range,
+        Err(response) => return response,
+    };
+    let mut options = RemoteOptions::default();
+    if let Some(concurrency) = request
+        .query
+        .get("concurrency")
+        .and_then(|c| c.parse().ok())
+    {
+        options.concurrency = concurrency;
+    }
+    if let Some(timeout) = request.query.get("timeout").and_then(|t| t.parse().ok()) {
+        options.timeout = Duration::from_millis(timeout);
+    }
+
+    log::info!("API: escaneo remoto de {} ({}-{})", host, range.0, range.1);
+    match port_scanner::scan_remote_with(host, range, options) {
+        Ok(scan) => Response::ok(json!(scan)),
+        Err(e) => Response::error(
+            ApiError::ScanFailed {
+                source: host.clone(),
+            },
+            &e,
+        ),
+    }
+}
+
+/// `GET /explain/{puerto}`: explica por qué un puerto está ocupado.
+fn handle_explain(port_str: &str) -> Response {
+    match port_str.parse::<u16>() {
+        Ok(port) if port > 0 => Response::ok(json!(explain::explain_port(port))),
+        _ => Response::error(ApiError::BadRequest, tr(Text::InvalidPort)),
+    }
+}
+
+/// `GET /free-port?range=3000-9999`: primer puerto TCP libre del rango.
+fn handle_free_port(request: &Request) -> Response {
+    let range = match query_range(request, (3000, 9999)) {
+        Ok(range) => range,
+        Err(response) => return response,
+    };
+    match port_scanner::find_free_port(range) {
+        Some(port) => Response::ok(json!({ "start": range.0, "end": range.1, "port": port })),
+        None => Response::error(
+            ApiError::NotFound,
+            &trf(Text::NoFreePort, &[&range.0, &range.1]),
+        ),
+    }
+}
+
+/// Rango `?range=INICIO-FIN` de la consulta, o `default` si no viene.
+fn query_range(request: &Request, default: (u16, u16)) -> Result<(u16, u16), Response> {
+    match request.query.get("range") {
+        None => Ok(default),
+        Some(range) => match range.split_once('-').map(|(s, e)| (s.parse(), e.parse())) {
+            Some((Ok(start), Ok(end))) if start > 0 && start <= end => Ok((start, end)),
+            _ => Err(Response::error(
+                ApiError::BadRequest,
+                tr(Text::InvalidPortRange),
+            )),
+        },
+    }
+}
+
+/// `POST /block/{puerto}` y `DELETE /block/{puerto}`: bloquea o
+/// desbloquea un puerto en el firewall.
+fn handle_block(port_str: &str, request: &Request, block: bool) -> Response {
+    let port: u16 = match port_str.parse() {
+        Ok(port) if port > 0 => port,
+        _ => return Response::error(ApiError::BadRequest, tr(Text::InvalidPort)),
+    };
+    let protocol = match request.query.get("protocol").map(String::as_str) {
+        None | Some("tcp") => "tcp",
+        Some("udp") => "udp",
+        Some(_) => return Response::error(ApiError::BadRequest, tr(Text::UnknownProtocol)),
+    };
+
+    if block {
+        log::info!("API: solicitud de bloqueo de {}/{}", port, protocol);
+        match firewall::block(port, protocol) {
+            Ok(()) => Response::ok(json!(BlockStore::load())),
+            Err(e) => Response::error(ApiError::Internal, &e),
+        }
+    } else {
+        log::info!("API: solicitud de desbloqueo de {}/{}", port, protocol);
+        match firewall::unblock(port, protocol) {
+            Ok(true) => Response::ok(json!(BlockStore::load())),
+            Ok(false) => Response::error(ApiError::NotFound, tr(Text::BlockNotFound)),
+            Err(e) => Response::error(ApiError::Internal, &e),
+        }
+    }
+}
+
+/// `GET /firewall`: gestor de firewall activo.
+fn handle_get_firewall() -> Response {
+    match firewall_manager::policy() {
+        Some(policy) => Response::ok(json!({
+            "manager": policy.manager.name(),
+            "default": if policy.default_allow { "allow" } else { "deny" },
+        })),
+        None => Response::ok(json!({ "manager": null })),
+    }
+}
+
+/// `POST /firewall/{puerto}`: permite o deniega un puerto en ufw o
+/// firewalld.
+fn handle_set_firewall(port_str: &str, request: &Request) -> Response {
+    let port: u16 = match port_str.parse() {
+        Ok(port) if port > 0 => port,
+        _ => return Response::error(ApiError::BadRequest, tr(Text::InvalidPort)),
+    };
+    let protocol = match request.query.get("protocol").map(String::as_str) {
+        None | Some("tcp") => "tcp",
+        Some("udp") => "udp",
+        Some(_) => return Response::error(ApiError::BadRequest, tr(Text::UnknownProtocol)),
+    };
+    let allow = match request.query.get("action").map(String::as_str) {
+        Some("allow") => true,
+        Some("deny") => false,
+        _ => return Response::error(ApiError::BadRequest, tr(Text::UnknownFirewallAction)),
+    };
+
+    log::info!(
+        "API: solicitud de {} {}/{} en el firewall",
+        if allow { "permitir" } else { "denegar" },
+        port,
+        protocol
+    );
+    match firewall_manager::set_allowed(port, protocol, allow) {
+        Ok(()) => Response::ok(json!({ "port": port, "protocol": protocol, "allowed": allow })),
+        Err(e) => Response::error(ApiError::Internal, &e),
+    }
+}
+
+/// `GET /impact/{pid}`: simula el kill y devuelve las conexiones afectadas.
+fn handle_get_impact(pid_str: &str, state: &ApiState) -> Response {
+    let pid: u32 = match pid_str.parse() {
+        Ok(pid) if pid > 0 => pid,
+        _ => return Response::error(ApiError::BadRequest, tr(Text::InvalidPid)),
+    };
+
+    let owned: Vec<PortInfo> = match state.ports.lock() {
+        Ok(ports) => ports.iter().filter(|p| p.pid == pid).cloned().collect(),
+        Err(_) => Vec::new(),
+    };
+    let established = connections::scan_established();
+    let impacts: Vec<serde_json::Value> = owned
+        .iter()
+        .map(|port| {
+            json!({
+                "protocol": port.protocol,
+                "port": port.port,
+                "impact": connections::kill_impact(port, &established),
+            })
+        })
+        .collect();
+    Response::ok(json!({ "pid": pid, "ports": impacts }))
+}
+
+/// `GET /restartable`: procesos cerrados que se pueden relanzar.
+///
+/// No incluye el entorno capturado para no exponer secretos.
+fn handle_get_restartable() -> Response {
+    let snapshots: Vec<serde_json::Value> = restart::load_snapshots()
+        .iter()
+        .enumerate()
+        .map(|(index, s)| {
+            json!({
+                "index": index,
+                "pid": s.pid,
+                "process_name": s.process_name,
+                "port": s.port,
+                "protocol": s.protocol,
+                "cmdline": s.cmdline,
+                "cwd": s.cwd,
+                "captured_at": s.captured_at,
+            })
+        })
+        .collect();
+    Response::ok(json!({ "restartable": snapshots }))
+}
+
+/// `POST /restart/{índice}`: relanza un proceso cerrado.
+fn handle_restart(index_str: &str) -> Response {
+    let index: usize = match index_str.parse() {
+        Ok(index) => index,
+        Err(_) => return Response::error(ApiError::BadRequest, tr(Text::InvalidIndex)),
+    };
+    match restart::restart(index) {
+        Ok(pid) => Response::ok(json!({ "restarted": pid })),
+        Err(e) => Response::error(ApiError::Internal, &e),
+    }
+}
+
+/// `GET /history?limit=N`: devuelve los eventos más recientes.
+fn handle_get_history(request: &Request) -> Response {
+    let limit = request
+        .query
+        .get("limit")
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_HISTORY_LIMIT);
+    let events = history::read_events(limit);
+    Response::ok(json!({
+        "count": events.len(),
+        "events": events,
+        "writer": journal::stats(),
+    }))
+}
+
+/// `GET /pinned`: puertos fijados con su estado en el último escaneo.
+fn handle_get_pinned(state: &ApiState) -> Response {
+    let ports = match state.ports.lock() {
+        Ok(ports) => ports.clone(),
+        Err(_) => Vec::new(),
+    };
+    let pinned = PinStore::load().status(&ports);
+    Response::ok(json!({ "count": pinned.len(), "pinned": pinned }))
+}
+
+/// `GET /watchlist`: puertos vigilados, de qué avisan y quién los ocupa.
+fn handle_get_watchlist(state: &ApiState) -> Response {
+    let ports = match state.ports.lock() {
+        Ok(ports) => ports.clone(),
+        Err(_) => Vec::new(),
+    };
+    let config = Config::load();
+    let watched: Vec<serde_json::Value> = watchlist::entries(&config, &WatchStore::load())
+        .into_iter()
+        .map(|entry| {
+            json!({
+                "port": entry.port,
+                "on": entry.events(),
+                "from_config": config.watch.contains(&entry),
+                "owner": watchlist::owner(entry.port, &ports),
+            })
+        })
+        .collect();
+    Response::ok(json!({ "count": watched.len(), "watched": watched }))
+}
+
+/// `POST /watchlist/{puerto}?on=open,close,owner`: vigila un puerto
+/// (por defecto, avisa de todo).
+fn handle_watch(port_str: &str, request: &Request) -> Response {
+    let port = match watch_target(port_str) {
+        Ok(port) => port,
+        Err(response) => return response,
+    };
+    let mut on = Vec::new();
+    for name in request
+        .query
+        .get("on")
+        .into_iter()
+        .flat_map(|on| on.split(','))
+    {
+        match name {
+            "open" => on.push(WatchEvent::Open),
+            "close" => on.push(WatchEvent::Close),
+            "owner" => on.push(WatchEvent::Owner),
+            _ => {
+                return Response::error(
+                    ApiError::BadRequest,
+                    &trf(Text::UnknownWatchEvent, &[&name]),
+                )
+            }
+        }
+    }
+
+    let mut store = WatchStore::load();
+    store.watch(WatchEntry { port, on });
+    log::info!("API: puerto {} vigilado", port);
+    save_watchlist(&store)
+}
+
+/// `DELETE /watchlist/{puerto}`: deja de vigilar un puerto.
+fn handle_unwatch(port_str: &str) -> Response {
+    let port = match watch_target(port_str) {
+        Ok(port) => port,
+        Err(response) => return response,
+    };
+    let mut store = WatchStore::load();
+    if !store.unwatch(port) {
+        return Response::error(ApiError::NotFound, &trf(Text::WatchNotFound, &[&port]));
+    }
+    log::info!("API: puerto {} ya no se vigila", port);
+    save_watchlist(&store)
+}
+
+/// Puerto de `/watchlist/{puerto}`; los de la configuración no se
+/// cambian desde la API.
+fn watch_target(port_str: &str) -> Result<u16, Response> {
+    let port: u16 = match port_str.parse() {
+        Ok(port) if port > 0 => port,
+        _ => return Err(Response::error(ApiError::BadRequest, tr(Text::InvalidPort))),
+    };
+    if Config::load().watch.iter().any(|e| e.port == port) {
+        return Err(Response::error(
+            ApiError::Conflict,
+            &trf(Text::WatchInConfig, &[&port]),
+        ));
+    }
+    Ok(port)
+}
+
+/// Guarda los puertos vigilados y los devuelve.
+fn save_watchlist(store: &WatchStore) -> Response {
+    match store.save() {
+        Ok(()) => Response::ok(json!(store)),
+        Err(e) => Response::error(ApiError::Internal, &trf(Text::WatchlistNotSaved, &[&e])),
+    }
+}
+
+/// `GET /tunnels`: túneles entre los puertos del último escaneo.
+fn handle_get_tunnels(state: &ApiState) -> Response {
+    let ports = match state.ports.lock() {
+        Ok(ports) => ports.clone(),
+        Err(_) => Vec::new(),
+    };
+    let tunnels = tunnels::detect(&ports);
+    Response::ok(json!({ "count": tunnels.len(), "tunnels": tunnels }))
+}
+
+/// `GET /sources`: estado de las fuentes de escaneo.
+fn handle_get_sources() -> Response {
+    let sources = sources::status();
+    let degraded = sources.iter().any(|s| s.degraded);
+    Response::ok(json!({
+        "degraded": degraded,
+        "sources": sources,
+        "fd_scan": port_scanner::last_fd_scan(),
+    }))
+}
+
+/// `GET /kill-stats`: efectividad de los kills por proceso.
+fn handle_get_kill_stats() -> Response {
+    let stats = KillStats::load();
+    let processes: Vec<serde_json::Value> = stats
+        .processes()
+        .into_iter()
+        .map(|p| {
+            json!({
+                "stats": p,
+                "respawn_rate": p.respawn_rate(),
+                "suggestion": p.suggestion(),
+            })
+        })
+        .collect();
+    Response::ok(json!({ "count": processes.len(), "processes": processes }))
+}
+
+/// `GET /history/ports?at=<unix>`: puertos abiertos en un momento pasado.
+fn handle_get_ports_at(request: &Request) -> Response {
+    let timestamp = match request.query.get("at").map(|v| v.parse::<u64>()) {
+        Some(Ok(timestamp)) => timestamp,
+        _ => return Response::error(ApiError::BadRequest, tr(Text::MissingAt)),
+    };
+    let ports = history::ports_at(timestamp);
+    Response::ok(json!({
+        "timestamp": timestamp,
+        "count": ports.len(),
+        "ports": ports,
+    }))
+}
+
+/// `GET /history/changes?since=<unix>`: qué cambió desde un momento pasado.
+fn handle_get_changes(request: &Request) -> Response {
+    let since = match request.query.get("since").map(|v| v.parse::<u64>()) {
+        Some(Ok(since)) => since,
+        _ => return Response::error(ApiError::BadRequest, tr(Text::MissingSince)),
+    };
+    Response::ok(json!({
+        "since": since,
+        "changes": history::changes_since(since),
+    }))
+}
+
+/// `GET /export?format=json|csv`: exporta el último escaneo.
+fn handle_export(request: &Request, state: &ApiState) -> Response {
+    let format_name = request
+        .query
+        .get("format")
+        .map(String::as_str)
+        .unwrap_or("json");
+    let format = match ExportFormat::parse(format_name) {
+        Some(format) => format,
+        None => return Response::error(ApiError::BadRequest, tr(Text::UnsupportedExportFormat)),
+    };
+
+    let ports = match state.ports.lock() {
+        Ok(ports) => ports.clone(),
+        Err(_) => Vec::new(),
+    };
+    Response {
+        status: 200,
+        content_type: format.mime_type(),
+        body: export::render(&ports, &TagStore::load(), format, history::unix_now()),
+    }
+}
+
+/// `GET /graph?format=dot|json`: topología de servicios locales.
+fn handle_graph(request: &Request, state: &ApiState) -> Response {
+    let format_name = request
+        .query
+        .get("format")
+        .map(String::as_str)
+        .unwrap_or("json");
+    let format = match GraphFormat::parse(format_name) {
+        Some(format) => format,
+        None => return Response::error(ApiError::BadRequest, tr(Text::UnsupportedGraphFormat)),
+    };
+
+    let ports = match state.ports.lock() {
+        Ok(ports) => ports.clone(),
+        Err(_) => Vec::new(),
+    };
+    let graph = Topology::build(&ports, &connections::scan_established());
+    Response {
+        status: 200,
+        content_type: format.mime_type(),
+        body: graph.render(format),
+    }
+}
+
+/// `GET /tags`: etiquetas locales y compartidas (de paquetes importados).
+fn handle_get_tags() -> Response {
+    let store = TagStore::load();
+    Response::ok(json!({ "tags": store.tags, "shared": store.shared }))
+}
+
+/// `POST /tags`: añade una etiqueta (cuerpo JSON con formato `Tag`).
+fn handle_add_tag(request: &Request) -> Response {
+    let tag: Tag = match serde_json::from_str(&request.body) {
+        Ok(tag) => tag,
+        Err(e) => return Response::error(ApiError::BadRequest, &trf(Text::InvalidTag, &[&e])),
+    };
+
+    let mut store = TagStore::load();
+    store.tags.push(tag);
+    match store.save() {
+        Ok(()) => Response::ok(json!(store)),
+        Err(e) => Response::error(ApiError::Internal, &trf(Text::TagsNotSaved, &[&e])),
+    }
+}
+
+/// `DELETE /tags/{índice}`: elimina la etiqueta en esa posición.
+fn handle_delete_tag(index_str: &str) -> Response {
+    let mut store = TagStore::load();
+    let index: usize = match index_str.parse() {
+        Ok(index) if index < store.tags.len() => index,
+        _ => return Response::error(ApiError::NotFound, tr(Text::TagNotFound)),
+    };
+
+    store.tags.remove(index);
+    match store.save() {
+        Ok(()) => Response::ok(json!(store)),
+        Err(e) => Response::error(ApiError::Internal, &trf(Text::TagsNotSaved, &[&e])),
+    }
+}
+
+/// `GET /settings`: ajustes en vigor (ver `settings`).
+fn handle_get_settings() -> Response {
+    Response::ok(json!(Settings::from_config(&Config::load())))
+}
+
+/// `PATCH /settings`: cambia los ajustes presentes en el cuerpo JSON,
+/// los guarda en `config.toml` y los anuncia en `/events`.
+fn handle_patch_settings(request: &Request, state: &ApiState) -> Response {
+    let patch: SettingsPatch = match serde_json::from_str(&request.body) {
+        Ok(patch) => patch,
+        Err(e) => return Response::error(ApiError::BadRequest, &trf(Text::InvalidSettings, &[&e])),
+    };
+    let content = match settings::read_config() {
+        Ok(content) => content,
+        Err(e) => return Response::error(ApiError::Internal, &trf(Text::SettingsNotSaved, &[&e])),
+    };
+    let updated = match patch.apply(&content) {
+        Ok(updated) => updated,
+        Err(e) => return Response::error(ApiError::BadRequest, &trf(Text::InvalidSettings, &[&e])),
+    };
+    if let Err(e) = settings::write_config(&updated) {
+        return Response::error(ApiError::Internal, &trf(Text::SettingsNotSaved, &[&e]));
+    }
+
+    // Aplicar ya el idioma y el tema (el resto, en el próximo escaneo)
+    let settings = Settings::from_config(&Config::load());
+    log::info!("API: ajustes cambiados en {}", Config::path().display());
+    state.events.publish_settings(&settings);
+    Response::ok(json!(settings))
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_state() -> ApiState {
+        ApiState {
+            ports: Arc::new(Mutex::new(vec![PortInfo {
+                protocol: "tcp".into(),
+                port: 8080,
+                local_address: "0.0.0.0".into(),
+                pid: 42,
+                process_name: "node".into(),
+                uid: Some(1000),
+                ..Default::default()
+            }])),
+            events: Arc::new(EventBus::new()),
+            auth: Authenticator::Token("secreto".into()),
+            dashboard: false,
+        }
+    }
+
+    fn request(raw: &str) -> Request {
+        read_request(&mut raw.as_bytes()).expect("petición válida")
+    }
+
+    /// Verifica el parseo de la línea de petición, query y cabeceras
+    #[test]
+    fn test_read_request() {
+        let req = request(
+            "GET /history?limit=5 HTTP/1.1\r\nHost: localhost\r\nAuthorization: Bearer abc\r\n\r\n",
+        );
+        assert_eq!(req.method, "GET");
+        assert_eq!(req.path, "/history");
+        assert_eq!(req.query.get("limit").map(String::as_str), Some("5"));
+        assert_eq!(
+            req.headers.get("authorization").map(String::as_str),
+            Some("Bearer abc")
+        );
+
+        // Parámetros codificados, como los manda un navegador
+        let req = request(
+            "GET /ports?process_regex=%5Enode%28%2Fdev%29%3F%24&process=my+app%20v2 HTTP/1.1\r\n\r\n",
+        );
+        assert_eq!(
+            req.query.get("process_regex").map(String::as_str),
+            Some("^node(/dev)?$")
+        );
+        assert_eq!(
+            req.query.get("process").map(String::as_str),
+            Some("my app v2")
+        );
+    }
+
+    /// Verifica que las peticiones sin token válido se rechazan
+    #[test]
+    fn test_route_requires_token() {
+        let state = test_state();
+        let no_token = request("GET /ports HTTP/1.1\r\n\r\n");
+        assert_eq!(route(&no_token, &state).status, 401);
+
+        let wrong = request("GET /ports HTTP/1.1\r\nAuthorization: Bearer otro\r\n\r\n");
+        assert_eq!(route(&wrong, &state).status, 401);
+    }
+
+    /// Verifica el campo `code` de los errores y su código HTTP
+    #[test]
+    fn test_api_error_codes() {
+        let state = test_state();
+        let unknown = request("GET /nada HTTP/1.1\r\nAuthorization: Bearer secreto\r\n\r\n");
+        let body: serde_json::Value = serde_json::from_str(&route(&unknown, &state).body).unwrap();
+        assert_eq!(body["code"], "not_found");
+        assert!(body["error"].is_string());
+
+        let denied = KillFailure {
+            error: Some(KillError::PermissionDenied("EPERM".into())),
+            message: "sin permisos".into(),
+        };
+        let error = ApiError::from_kill(&denied);
+        assert_eq!(error, ApiError::PermissionDenied);
+        let response = Response::error(error, &denied.message);
+        assert_eq!(response.status, 403);
+        assert!(response.body.contains("\"code\":\"permission_denied\""));
+
+        let gone = KillFailure {
+            error: Some(KillError::NoSuchProcess),
+            message: String::new(),
+        };
+        assert_eq!(ApiError::from_kill(&gone).status(), 404);
+        assert_eq!(
+            ApiError::from_kill(&KillFailure::refused("no".into())),
+            ApiError::Internal
+        );
+
+        #[cfg(feature = "remote")]
+        {
+            let response = Response::error(
+                ApiError::ScanFailed {
+                    source: "10.0.0.5".into(),
+                },
+                "timeout",
+            );
+            assert_eq!(response.status, 502);
+            let mut written = Vec::new();
+            write_response(&mut written, &response).unwrap();
+            assert!(written.starts_with(b"HTTP/1.1 502 Bad Gateway\r\n"));
+            let body: serde_json::Value = serde_json::from_str(&response.body).unwrap();
+            assert_eq!(body["code"], "scan_failed");
+            assert_eq!(body["source"], "10.0.0.5");
+        }
+    }
+
+    /// Verifica el endpoint de puertos y los errores de enrutado
+    #[test]
+    fn test_route_ports() {
+        let state = test_state();
+        let ok = request("GET /ports HTTP/1.1\r\nAuthorization: Bearer secreto\r\n\r\n");
+        let response = route(&ok, &state);
+        assert_eq!(response.status, 200);
+        assert!(response.body.contains("\"port\":8080"));
+
+        let mine =
+            request("GET /ports?owner=1000 HTTP/1.1\r\nAuthorization: Bearer secreto\r\n\r\n");
+        assert!(route(&mine, &state).body.contains("\"count\":1"));
+        let root =
+            request("GET /ports?owner=root HTTP/1.1\r\nAuthorization: Bearer secreto\r\n\r\n");
+        assert!(route(&root, &state).body.contains("\"count\":0"));
+        let local =
+            request("GET /ports?scope=loopback HTTP/1.1\r\nAuthorization: Bearer secreto\r\n\r\n");
+        assert!(route(&local, &state).body.contains("\"count\":0"));
+        let bad_scope =
+            request("GET /ports?scope=lan HTTP/1.1\r\nAuthorization: Bearer secreto\r\n\r\n");
+        assert_eq!(route(&bad_scope, &state).status, 400);
+
+        let bad_method = request("DELETE /ports HTTP/1.1\r\nAuthorization: Bearer secreto\r\n\r\n");
+        assert_eq!(route(&bad_method, &state).status, 405);
+
+        let bad_pid = request("POST /kill/abc HTTP/1.1\r\nAuthorization: Bearer secreto\r\n\r\n");
+        assert_eq!(route(&bad_pid, &state).status, 400);
+
+        let bad_action = request(
+            "POST /firewall/8080?action=open HTTP/1.1\r\nAuthorization: Bearer secreto\r\n\r\n",
+        );
+        assert_eq!(route(&bad_action, &state).status, 400);
+
+        #[cfg(feature = "remote")]
+        {
+            let no_host = request(
+                "GET /remote-scan?range=1-10 HTTP/1.1\r\nAuthorization: Bearer secreto\r\n\r\n",
+            );
+            assert_eq!(route(&no_host, &state).status, 400);
+        }
+
+        let bad_range = request(
+            "GET /free-port?range=9000-8000 HTTP/1.1\r\nAuthorization: Bearer secreto\r\n\r\n",
+        );
+        assert_eq!(route(&bad_range, &state).status, 400);
+
+        let no_since =
+            request("GET /history/changes HTTP/1.1\r\nAuthorization: Bearer secreto\r\n\r\n");
+        assert_eq!(route(&no_since, &state).status, 400);
+
+        let bad_event = request(
+            "POST /watchlist/5432?on=restart HTTP/1.1\r\nAuthorization: Bearer secreto\r\n\r\n",
+        );
+        assert_eq!(route(&bad_event, &state).status, 400);
+
+        let unknown = request("GET /nada HTTP/1.1\r\nAuthorization: Bearer secreto\r\n\r\n");
+        assert_eq!(route(&unknown, &state).status, 404);
+    }
+}
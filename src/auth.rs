@@ -0,0 +1,478 @@
+/// Módulo de autenticación de la API.
+///
+/// El backend se elige en la sección `[api]` de `config.toml`:
+///
+/// - `token` (por defecto): cabecera `Authorization: Bearer <token>`
+///   con el token de `PORTSLAYER_API_TOKEN` o de un archivo.
+/// - `peer`: la API escucha en un socket Unix y se autentica por las
+///   credenciales del proceso que conecta (`SO_PEERCRED`), sin secretos.
+/// - `pam`: HTTP Basic verificado contra PAM (requiere compilar con la
+///   feature `pam`).
+///
+/// En `peer` y `pam` solo se acepta al usuario que ejecuta el daemon y a
+/// los de `allowed_users`, para poder exponer la API en máquinas
+/// compartidas sin que cualquier usuario local mate procesos ajenos.
+///
+/// Con `pam` viajan contraseñas reales del sistema en HTTP Basic sin
+/// cifrar, así que el daemon solo lo acepta escuchando en loopback. Cada
+/// fallo obliga a la IP a esperar el doble antes del siguiente intento
+/// (de [`FAILURE_DELAY`] a [`MAX_FAILURE_DELAY`]) y una IP no puede tener
+/// dos intentos a la vez, para que abrir conexiones en paralelo no
+/// acelere un ataque de contraseñas.
+///
+/// Ejemplo:
+/// ```toml
+/// [api]
+/// auth = "peer"
+/// socket = "/run/portslayer/api.sock"
+/// allowed_users = ["alice", "bob"]
+/// ```
+use nix::sys::socket::{getsockopt, sockopt};
+use nix::unistd::{self, User};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Read, Write};
+use std::net::IpAddr;
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::paths;
+
+/// Variable de entorno que permite fijar el token de la API
+const TOKEN_ENV: &str = "PORTSLAYER_API_TOKEN";
+
+/// Archivo donde se persiste el token generado
+const TOKEN_FILE: &str = "api_token";
+
+/// Socket por defecto del backend `peer` (en el directorio de estado)
+const SOCKET_FILE: &str = "api.sock";
+
+/// Servicio PAM por defecto
+const DEFAULT_PAM_SERVICE: &str = "login";
+
+/// Espera tras el primer fallo de PAM desde una IP (se dobla en cada fallo)
+const FAILURE_DELAY: Duration = Duration::from_secs(1);
+
+/// Espera máxima entre intentos desde una IP
+const MAX_FAILURE_DELAY: Duration = Duration::from_secs(60);
+
+/// Tiempo tras el que se olvidan los fallos de una IP que no vuelve
+const FORGET_FAILURES_AFTER: Duration = Duration::from_secs(15 * 60);
+
+/// Intentos de PAM por IP del cliente
+static THROTTLE: Mutex<Option<Throttle>> = Mutex::new(None);
+
+/// Backend de autenticación de la API
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuthBackend {
+    /// Token estático (`Authorization: Bearer`)
+    #[default]
+    Token,
+    /// Credenciales del proceso en un socket Unix
+    Peer,
+    /// Usuario y contraseña verificados con PAM (HTTP Basic)
+    Pam,
+}
+
+/// Sección `[api]` de la configuración
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ApiConfig {
+    /// Backend de autenticación
+    pub auth: AuthBackend,
+    /// Archivo del token (por defecto `~/.config/portslayer/api_token`)
+    pub token_file: Option<PathBuf>,
+    /// Socket Unix del backend `peer`
+    pub socket: Option<PathBuf>,
+    /// Usuarios aceptados además del que ejecuta el daemon (`peer` y `pam`)
+    pub allowed_users: Vec<String>,
+    /// Servicio PAM con el que se verifican las contraseñas
+    pub pam_service: Option<String>,
+}
+
+impl ApiConfig {
+    /// Ruta del socket Unix del backend `peer`.
+    pub fn socket_path(&self) -> PathBuf {
+        self.socket
+            .clone()
+            .unwrap_or_else(|| paths::state_dir().join(SOCKET_FILE))
+    }
+}
+
+/// Autenticador ya inicializado a partir de la configuración
+#[derive(Debug, Clone)]
+pub enum Authenticator {
+    /// Token que deben presentar los clientes
+    Token(String),
+    /// UIDs aceptados en el socket Unix
+    Peer { allowed_uids: Vec<u32> },
+    /// Servicio PAM y usuarios aceptados
+    Pam {
+        service: String,
+        allowed_users: Vec<String>,
+    },
+}
+
+impl Authenticator {
+    /// Prepara el backend configurado.
+    ///
+    /// # Returns
+    /// `Err(String)` si no se pudo obtener el token, algún usuario de
+    /// `allowed_users` no existe o PAM no está disponible en esta compilación.
+    pub fn from_config(config: &ApiConfig) -> Result<Self, String> {
+        match config.auth {
+            AuthBackend::Token => {
+                let path = config
+                    .token_file
+                    .clone()
+                    .unwrap_or_else(|| paths::config_dir().join(TOKEN_FILE));
+                load_or_create_token(&path)
+                    .map(Self::Token)
+                    .map_err(|e| format!("No se pudo obtener el token de la API: {}", e))
+            }
+            AuthBackend::Peer => {
+                let mut allowed_uids = vec![unistd::getuid().as_raw()];
+                for name in &config.allowed_users {
+                    match User::from_name(name) {
+                        Ok(Some(user)) => allowed_uids.push(user.uid.as_raw()),
+                        _ => return Err(format!("Usuario desconocido en allowed_users: {}", name)),
+                    }
+                }
+                Ok(Self::Peer { allowed_uids })
+            }
+            AuthBackend::Pam => {
+                if !cfg!(feature = "pam") {
+                    return Err("PortSlayer se compiló sin soporte PAM (feature \"pam\")".into());
+                }
+                let mut allowed_users = config.allowed_users.clone();
+                if let Ok(Some(user)) = User::from_uid(unistd::getuid()) {
+                    allowed_users.push(user.name);
+                }
+                Ok(Self::Pam {
+                    service: config
+                        .pam_service
+                        .clone()
+                        .unwrap_or_else(|| DEFAULT_PAM_SERVICE.to_string()),
+                    allowed_users,
+                })
+            }
+        }
+    }
+
+    /// Nombre del backend para los logs.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Token(_) => "token",
+            Self::Peer { .. } => "peer",
+            Self::Pam { .. } => "pam",
+        }
+    }
+
+    /// Indica si este backend sirve la API en un socket Unix.
+    pub fn uses_socket(&self) -> bool {
+        matches!(self, Self::Peer { .. })
+    }
+
+    /// Decide si una petición está autorizada.
+    ///
+    /// # Arguments
+    /// * `authorization` - Valor de la cabecera `Authorization`, si la hay
+    /// * `peer_uid` - UID del cliente (solo en conexiones por socket Unix)
+    /// * `peer_ip` - IP del cliente (solo en conexiones TCP)
+    pub fn authorize(
+        &self,
+        authorization: Option<&str>,
+        peer_uid: Option<u32>,
+        peer_ip: Option<IpAddr>,
+    ) -> bool {
+        match self {
+            Self::Token(token) => authorization
+                .and_then(|value| value.strip_prefix("Bearer "))
+                .map(|provided| constant_time_eq(provided.trim().as_bytes(), token.as_bytes()))
+                .unwrap_or(false),
+            Self::Peer { allowed_uids } => peer_uid.is_some_and(|uid| allowed_uids.contains(&uid)),
+            Self::Pam {
+                service,
+                allowed_users,
+            } => match authorization.and_then(basic_credentials) {
+                Some((user, password)) => throttled(peer_ip, || {
+                    allowed_users.contains(&user) && check_pam(service, &user, &password)
+                }),
+                None => false,
+            },
+        }
+    }
+}
+
+/// Intenta autenticar respetando la espera de la IP tras sus fallos.
+///
+/// # Returns
+/// `false` sin llamar a `check` si la IP aún debe esperar o ya tiene un
+/// intento en curso.
+fn throttled(peer_ip: Option<IpAddr>, check: impl FnOnce() -> bool) -> bool {
+    let allowed = THROTTLE
+        .lock()
+        .map(|mut throttle| {
+            throttle
+                .get_or_insert_with(Throttle::default)
+                .begin(peer_ip, Instant::now())
+        })
+        .unwrap_or(false);
+    if !allowed {
+        log::warn!(
+            "API: demasiados intentos fallidos desde {}; se rechaza sin consultar PAM",
+            peer_ip.map_or_else(|| "?".to_string(), |ip| ip.to_string())
+        );
+        return false;
+    }
+    let ok = check();
+    if let Ok(mut throttle) = THROTTLE.lock() {
+        throttle
+            .get_or_insert_with(Throttle::default)
+            .finish(peer_ip, ok, Instant::now());
+    }
+    ok
+}
+
+/// Fallos de autenticación por IP del cliente
+#[derive(Debug, Default)]
+struct Throttle {
+    clients: HashMap<Option<IpAddr>, Attempts>,
+}
+
+/// Estado de los intentos de una IP
+#[derive(Debug)]
+struct Attempts {
+    /// Fallos seguidos
+    failures: u32,
+    /// Momento a partir del cual puede volver a intentarlo
+    retry_at: Instant,
+    /// Hay un intento en curso
+    in_flight: bool,
+}
+
+impl Throttle {
+    /// Reserva un intento: `false` si la IP está esperando tras un fallo
+    /// o ya tiene otro intento en curso.
+    fn begin(&mut self, client: Option<IpAddr>, now: Instant) -> bool {
+        let attempts = self.clients.entry(client).or_insert(Attempts {
+            failures: 0,
+            retry_at: now,
+            in_flight: false,
+        });
+        if attempts.in_flight || now < attempts.retry_at {
+            return false;
+        }
+        attempts.in_flight = true;
+        true
+    }
+
+    /// Registra el resultado del intento reservado con [`Throttle::begin`].
+    fn finish(&mut self, client: Option<IpAddr>, ok: bool, now: Instant) {
+        if ok {
+            self.clients.remove(&client);
+            return;
+        }
+        if let Some(attempts) = self.clients.get_mut(&client) {
+            attempts.in_flight = false;
+            attempts.failures += 1;
+            attempts.retry_at = now + failure_delay(attempts.failures);
+        }
+        self.clients.retain(|_, attempts| {
+            attempts.in_flight || now < attempts.retry_at + FORGET_FAILURES_AFTER
+        });
+    }
+}
+
+/// Espera tras `failures` fallos seguidos: 1 s, 2 s, 4 s... hasta el máximo.
+fn failure_delay(failures: u32) -> Duration {
+    FAILURE_DELAY
+        .saturating_mul(1 << failures.saturating_sub(1).min(16))
+        .min(MAX_FAILURE_DELAY)
+}
+
+/// UID del proceso al otro lado de un socket Unix.
+pub fn peer_uid(stream: &UnixStream) -> Option<u32> {
+    getsockopt(stream, sockopt::PeerCredentials)
+        .ok()
+        .map(|cred| cred.uid())
+}
+
+/// Verifica usuario y contraseña con PAM.
+#[cfg(feature = "pam")]
+fn check_pam(service: &str, user: &str, password: &str) -> bool {
+    match crate::pam::authenticate(service, user, password) {
+        Ok(()) => true,
+        Err(e) => {
+            log::warn!("API: autenticación PAM fallida para '{}': {}", user, e);
+            false
+        }
+    }
+}
+
+/// Sin la feature `pam` nunca se acepta (`from_config` ya lo impide).
+#[cfg(not(feature = "pam"))]
+fn check_pam(_service: &str, _user: &str, _password: &str) -> bool {
+    false
+}
+
+/// Extrae usuario y contraseña de una cabecera `Basic <base64>`.
+fn basic_credentials(authorization: &str) -> Option<(String, String)> {
+    let encoded = authorization.strip_prefix("Basic ")?.trim();
+    let decoded = String::from_utf8(decode_base64(encoded)?).ok()?;
+    let (user, password) = decoded.split_once(':')?;
+    Some((user.to_string(), password.to_string()))
+}
+
+/// Decodifica base64 estándar (con o sin relleno `=`).
+fn decode_base64(input: &str) -> Option<Vec<u8>> {
+    let mut bits: u32 = 0;
+    let mut bit_count = 0;
+    let mut output = Vec::new();
+    for byte in input.trim_end_matches('=').bytes() {
+        let value = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+        bits = (bits << 6) | u32::from(value);
+        bit_count += 6;
+        if bit_count >= 8 {
+            bit_count -= 8;
+            output.push((bits >> bit_count) as u8);
+            bits &= (1 << bit_count) - 1;
+        }
+    }
+    Some(output)
+}
+
+/// Compara dos secuencias en tiempo constante para no filtrar el token.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+// ─────────────────────────────────────────────────────────────
+// Token estático
+// ─────────────────────────────────────────────────────────────
+
+/// Obtiene el token de la API.
+///
+/// Orden de prioridad:
+/// 1. Variable de entorno `PORTSLAYER_API_TOKEN`
+/// 2. Archivo indicado (por defecto `~/.config/portslayer/api_token`)
+/// 3. Token aleatorio nuevo, guardado en ese archivo con permisos 0600
+///
+/// # Returns
+/// `Err` si no existe token y no se pudo generar ni guardar uno nuevo.
+fn load_or_create_token(path: &Path) -> io::Result<String> {
+    if let Ok(token) = std::env::var(TOKEN_ENV) {
+        if !token.trim().is_empty() {
+            return Ok(token.trim().to_string());
+        }
+    }
+
+    if let Ok(token) = fs::read_to_string(path) {
+        if !token.trim().is_empty() {
+            return Ok(token.trim().to_string());
+        }
+    }
+
+    // Generar 16 bytes aleatorios desde el kernel y codificarlos en hex
+    let mut bytes = [0u8; 16];
+    fs::File::open("/dev/urandom")?.read_exact(&mut bytes)?;
+    let token: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)?;
+    writeln!(file, "{}", token)?;
+
+    log::info!("Token de la API generado en {}", path.display());
+    Ok(token)
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verifica los backends de token y de credenciales del socket
+    #[test]
+    fn test_authorize() {
+        let token = Authenticator::Token("secreto".into());
+        assert!(token.authorize(Some("Bearer secreto"), None, None));
+        assert!(!token.authorize(Some("Bearer otro"), None, None));
+        assert!(!token.authorize(None, Some(0), None));
+
+        let peer = Authenticator::Peer {
+            allowed_uids: vec![1000],
+        };
+        assert!(peer.authorize(None, Some(1000), None));
+        assert!(!peer.authorize(None, Some(1001), None));
+        // Por TCP no hay credenciales del proceso
+        assert!(!peer.authorize(Some("Bearer secreto"), None, None));
+    }
+
+    /// Verifica el parseo de la cabecera HTTP Basic
+    #[test]
+    fn test_basic_credentials() {
+        // "alice:abc:123" (la contraseña puede contener ':')
+        assert_eq!(
+            basic_credentials("Basic YWxpY2U6YWJjOjEyMw=="),
+            Some(("alice".to_string(), "abc:123".to_string()))
+        );
+        assert_eq!(decode_base64("aGk"), Some(b"hi".to_vec()));
+        assert_eq!(basic_credentials("Basic ***"), None);
+        assert_eq!(basic_credentials("Bearer YWxpY2U6eA=="), None);
+    }
+
+    /// Verifica la espera creciente tras los fallos de una IP, el límite
+    /// de un intento a la vez y que un acierto la borra
+    #[test]
+    fn test_throttle_failures() {
+        let mut throttle = Throttle::default();
+        let ip = Some(IpAddr::from([192, 168, 1, 50]));
+        let other = Some(IpAddr::from([192, 168, 1, 51]));
+        let start = Instant::now();
+
+        assert!(throttle.begin(ip, start));
+        // Una conexión en paralelo de la misma IP no puede probar a la vez
+        assert!(!throttle.begin(ip, start));
+        assert!(throttle.begin(other, start));
+        throttle.finish(ip, false, start);
+        assert!(!throttle.begin(ip, start + Duration::from_millis(500)));
+
+        let second = start + FAILURE_DELAY;
+        assert!(throttle.begin(ip, second));
+        throttle.finish(ip, false, second);
+        assert!(!throttle.begin(ip, second + FAILURE_DELAY));
+        let third = second + FAILURE_DELAY * 2;
+        assert!(throttle.begin(ip, third));
+        throttle.finish(ip, true, third);
+        assert!(throttle.begin(ip, third));
+
+        assert_eq!(failure_delay(1), FAILURE_DELAY);
+        assert_eq!(failure_delay(3), FAILURE_DELAY * 4);
+        assert_eq!(failure_delay(40), MAX_FAILURE_DELAY);
+    }
+}
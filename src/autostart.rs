@@ -0,0 +1,135 @@
+/// Módulo del inicio automático con la sesión (XDG autostart).
+///
+/// Los escritorios compatibles con XDG lanzan al iniciar sesión las
+/// entradas de `~/.config/autostart/`. El instalador crea
+/// `portslayer.desktop`; desde el tray ("🚀 Iniciar con la sesión") o con
+/// `portslayer autostart --enable|--disable` se crea o se borra sin
+/// reinstalar.
+///
+/// Una entrada con `Hidden=true` o `X-GNOME-Autostart-enabled=false`
+/// (lo que escriben algunas herramientas de "Aplicaciones al inicio") se
+/// considera desactivada.
+use std::fs;
+use std::path::PathBuf;
+
+use crate::paths;
+
+/// Nombre de la entrada de autostart
+const DESKTOP_FILE: &str = "portslayer.desktop";
+
+/// Segundos de espera tras el inicio de sesión (como el instalador)
+const STARTUP_DELAY_SECS: u32 = 5;
+
+/// Ruta de la entrada (`$XDG_CONFIG_HOME/autostart/portslayer.desktop`).
+pub fn path() -> PathBuf {
+    paths::config_home().join("autostart").join(DESKTOP_FILE)
+}
+
+/// Indica si PortSlayer se inicia con la sesión.
+pub fn is_enabled() -> bool {
+    fs::read_to_string(path()).is_ok_and(|entry| entry_enabled(&entry))
+}
+
+/// Activa o desactiva el inicio con la sesión.
+///
+/// # Returns
+/// `Err(String)` si no se pudo escribir o borrar la entrada.
+pub fn set_enabled(enabled: bool) -> Result<(), String> {
+    let path = path();
+    if !enabled {
+        return match fs::remove_file(&path) {
+            Ok(()) => {
+                log::info!("Inicio automático desactivado");
+                Ok(())
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(format!("No se pudo borrar {}: {}", path.display(), e)),
+        };
+    }
+
+    let exe = std::env::current_exe()
+        .map_err(|e| format!("No se pudo obtener la ruta del ejecutable: {}", e))?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("No se pudo crear {}: {}", dir.display(), e))?;
+    }
+    fs::write(&path, desktop_entry(&exe.display().to_string()))
+        .map_err(|e| format!("No se pudo escribir {}: {}", path.display(), e))?;
+    log::info!("Inicio automático activado ({})", path.display());
+    Ok(())
+}
+
+/// Contenido de la entrada, igual que la que crea `scripts/install.sh`.
+fn desktop_entry(exe: &str) -> String {
+    format!(
+        "[Desktop Entry]\n\
+         Type=Application\n\
+         Name=PortSlayer\n\
+         Comment=Monitor and kill open ports from system tray\n\
+         Exec={}\n\
+         Icon=network-server\n\
+         Terminal=false\n\
+         Categories=System;Network;Monitor;\n\
+         StartupNotify=false\n\
+         X-GNOME-Autostart-enabled=true\n\
+         X-GNOME-Autostart-Delay={}\n",
+        quote_exec(exe),
+        STARTUP_DELAY_SECS
+    )
+}
+
+/// Entrecomilla la ruta del `Exec` si lo necesita (especificación de
+/// Desktop Entry: comillas dobles y `\` ante `"`, `` ` ``, `$` y `\`).
+fn quote_exec(exe: &str) -> String {
+    if !exe.contains(|c: char| c.is_whitespace() || "\"'`$\\".contains(c)) {
+        return exe.to_string();
+    }
+    let mut quoted = String::from("\"");
+    for c in exe.chars() {
+        if "\"`$\\".contains(c) {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Indica si el contenido de una entrada la deja activa.
+fn entry_enabled(entry: &str) -> bool {
+    !entry.lines().any(|line| {
+        let Some((key, value)) = line.split_once('=') else {
+            return false;
+        };
+        match key.trim() {
+            "Hidden" => value.trim().eq_ignore_ascii_case("true"),
+            "X-GNOME-Autostart-enabled" => value.trim().eq_ignore_ascii_case("false"),
+            _ => false,
+        }
+    })
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verifica la entrada generada y la detección de las desactivadas
+    #[test]
+    fn test_desktop_entry() {
+        let entry = desktop_entry("/usr/local/bin/portslayer");
+        assert!(entry.contains("\nExec=/usr/local/bin/portslayer\n"));
+        assert!(entry_enabled(&entry));
+        assert!(!entry_enabled(&format!("{}Hidden=true\n", entry)));
+        assert!(!entry_enabled(
+            &entry.replace("Autostart-enabled=true", "Autostart-enabled=false")
+        ));
+        assert_eq!(
+            quote_exec("/opt/Port Slayer/$bin"),
+            "\"/opt/Port Slayer/\\$bin\""
+        );
+    }
+}
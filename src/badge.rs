@@ -0,0 +1,151 @@
+/// Módulo del contador de puertos sobre el ícono del tray.
+///
+/// El número de puertos escuchando se muestra en el título y el tooltip
+/// del StatusNotifierItem y, en los paneles que dibujan el
+/// `OverlayIconPixmap` (KDE, por ejemplo), como un círculo rojo con el
+/// número encima del ícono. Así se ve que algo nuevo empezó a escuchar
+/// sin abrir el menú. Los colores son los del tema de íconos (ver
+/// `theme`).
+///
+/// El dibujo se hace a mano con una fuente de 3×5 píxeles para no
+/// depender de una biblioteca gráfica.
+use ksni::Icon;
+
+use crate::theme::{self, Palette};
+
+/// Tamaños (en píxeles) en los que se genera el contador
+const SIZES: [i32; 2] = [16, 32];
+
+/// Máximo que se muestra; por encima se dibuja "99+"
+const MAX_SHOWN: usize = 99;
+
+/// Ancho y alto de cada carácter de la fuente
+const GLYPH_WIDTH: i32 = 3;
+const GLYPH_HEIGHT: i32 = 5;
+
+/// Filas de cada carácter (bit 2 = columna izquierda)
+fn glyph(c: char) -> [u8; 5] {
+    match c {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b001, 0b001],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        '+' => [0b000, 0b010, 0b111, 0b010, 0b000],
+        _ => [0; 5],
+    }
+}
+
+/// Texto del contador.
+fn badge_text(count: usize) -> String {
+    if count > MAX_SHOWN {
+        format!("{}+", MAX_SHOWN)
+    } else {
+        count.to_string()
+    }
+}
+
+/// Ícono superpuesto con el número de puertos, en varios tamaños.
+///
+/// # Returns
+/// Vacío si no hay puertos (sin contador).
+pub fn overlay(count: usize) -> Vec<Icon> {
+    if count == 0 {
+        return Vec::new();
+    }
+    let text = badge_text(count);
+    let palette = theme::palette();
+    SIZES
+        .iter()
+        .map(|&size| render(&text, size, &palette))
+        .collect()
+}
+
+/// Dibuja el texto sobre un círculo de `size`×`size` píxeles.
+fn render(text: &str, size: i32, palette: &Palette) -> Icon {
+    let chars: Vec<char> = text.chars().collect();
+    let n = chars.len() as i32;
+    // Mayor escala entera con la que el texto cabe dentro del círculo
+    let scale = ((size - 2) / (n * (GLYPH_WIDTH + 1) - 1))
+        .min((size - 4) / GLYPH_HEIGHT)
+        .max(1);
+    let text_width = scale * (n * (GLYPH_WIDTH + 1) - 1);
+    let left = (size - text_width) / 2;
+    let top = (size - scale * GLYPH_HEIGHT) / 2;
+
+    let radius = size as f32 / 2.0;
+    let mut data = Vec::with_capacity((size * size * 4) as usize);
+    for y in 0..size {
+        for x in 0..size {
+            let dx = x as f32 + 0.5 - radius;
+            let dy = y as f32 + 0.5 - radius;
+            let pixel =
+                if x >= left && y >= top && lit(&chars, (x - left) / scale, (y - top) / scale) {
+                    palette.badge_foreground
+                } else if dx * dx + dy * dy <= radius * radius {
+                    palette.badge_background
+                } else {
+                    [0; 4]
+                };
+            data.extend_from_slice(&pixel);
+        }
+    }
+    Icon {
+        width: size,
+        height: size,
+        data,
+    }
+}
+
+/// Indica si la celda (columna, fila) de la fuente está encendida.
+fn lit(chars: &[char], col: i32, row: i32) -> bool {
+    if !(0..GLYPH_HEIGHT).contains(&row) || col < 0 {
+        return false;
+    }
+    let index = (col / (GLYPH_WIDTH + 1)) as usize;
+    let offset = col % (GLYPH_WIDTH + 1);
+    match chars.get(index) {
+        Some(&c) if offset < GLYPH_WIDTH => {
+            glyph(c)[row as usize] & (1 << (GLYPH_WIDTH - 1 - offset)) != 0
+        }
+        _ => false,
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verifica el texto, los tamaños y el dibujo del contador
+    #[test]
+    fn test_overlay() {
+        assert!(overlay(0).is_empty());
+        assert_eq!(badge_text(7), "7");
+        assert_eq!(badge_text(150), "99+");
+
+        let icons = overlay(42);
+        assert_eq!(icons.len(), SIZES.len());
+        for icon in &icons {
+            assert_eq!(icon.data.len(), (icon.width * icon.height * 4) as usize);
+        }
+        // El centro del círculo de 16 px cae en un dígito o en el fondo,
+        // nunca transparente; la esquina sí lo es.
+        let small = &icons[0];
+        let center = ((8 * 16 + 8) * 4) as usize;
+        assert_eq!(small.data[center], 0xFF);
+        assert_eq!(&small.data[0..4], &[0, 0, 0, 0]);
+        assert!(small
+            .data
+            .chunks(4)
+            .any(|p| p == theme::palette().badge_foreground));
+    }
+}
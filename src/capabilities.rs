@@ -0,0 +1,152 @@
+/// Módulo de capacidades de la instalación.
+///
+/// Informa qué puede hacer PortSlayer en este sistema (escanear con
+/// privilegios, hablar con Docker, tocar el firewall, usar eBPF, mostrar
+/// notificaciones o copiar al portapapeles), para que el tray y los
+/// clientes de la API oculten las acciones no disponibles en lugar de
+/// fallar al hacer clic.
+///
+/// La detección lanza algún comando (`sudo -n`), así que se hace una vez
+/// por proceso y se reutiliza. Se consulta con `portslayer capabilities`
+/// o `GET /capabilities`.
+use portslayer_core::helper;
+use portslayer_core::sources::{self, SOURCE_TIMEOUT};
+use serde::Serialize;
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::process::Command;
+use std::sync::OnceLock;
+
+use crate::clipboard;
+use crate::docker::DOCKER_SOCKET;
+
+/// Herramientas de firewall reconocidas
+const FIREWALL_TOOLS: [&str; 4] = ["ufw", "firewall-cmd", "nft", "iptables"];
+
+/// BTF del kernel, necesario para los programas eBPF portables
+const KERNEL_BTF: &str = "/sys/kernel/btf/vmlinux";
+
+/// Bits de `CapEff` que permiten cargar programas eBPF
+/// (`CAP_SYS_ADMIN` = 21, `CAP_BPF` = 39)
+const BPF_CAPABILITY_BITS: [u32; 2] = [21, 39];
+
+/// Capacidades detectadas, en caché para todo el proceso
+static CAPABILITIES: OnceLock<Capabilities> = OnceLock::new();
+
+/// Qué puede hacer la instalación actual
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Capabilities {
+    /// `ss` ve los PIDs de todos los usuarios (root, helper privilegiado o
+    /// `sudo -n` sin contraseña)
+    pub elevated_scan: bool,
+    /// El socket de Docker acepta conexiones
+    pub docker: bool,
+    /// Hay `ufw`, `nft` o `iptables` instalado
+    pub firewall: bool,
+    /// El kernel expone BTF y el proceso tiene permisos para eBPF
+    pub ebpf: bool,
+    /// `notify-send` y un bus de sesión disponibles
+    pub notifications: bool,
+    /// Alguna herramienta de portapapeles utilizable
+    pub clipboard: bool,
+}
+
+impl Capabilities {
+    /// Detecta las capacidades del sistema.
+    fn detect() -> Self {
+        let root = nix::unistd::geteuid().is_root();
+        let capabilities = Self {
+            elevated_scan: root || helper::running() || sudo_ss_allowed(),
+            docker: UnixStream::connect(DOCKER_SOCKET).is_ok(),
+            firewall: FIREWALL_TOOLS.iter().any(|tool| in_path(tool)),
+            ebpf: Path::new(KERNEL_BTF).exists() && (root || has_bpf_capability(&read_status())),
+            notifications: in_path("notify-send")
+                && std::env::var_os("DBUS_SESSION_BUS_ADDRESS").is_some(),
+            clipboard: clipboard::available(),
+        };
+        log::info!("Capacidades detectadas: {:?}", capabilities);
+        capabilities
+    }
+
+    /// (nombre, descripción, disponible) en el orden en que se muestran.
+    pub fn entries(&self) -> [(&'static str, &'static str, bool); 6] {
+        [
+            (
+                "elevated_scan",
+                "escaneo con privilegios (PIDs de todos los usuarios)",
+                self.elevated_scan,
+            ),
+            ("docker", "contenedores de Docker", self.docker),
+            (
+                "firewall",
+                "reglas de firewall (ufw, firewalld, nft o iptables)",
+                self.firewall,
+            ),
+            ("ebpf", "eBPF (BTF del kernel y permisos)", self.ebpf),
+            (
+                "notifications",
+                "notificaciones de escritorio (notify-send)",
+                self.notifications,
+            ),
+            (
+                "clipboard",
+                "portapapeles (wl-copy, xclip o xsel)",
+                self.clipboard,
+            ),
+        ]
+    }
+}
+
+/// Capacidades de la instalación actual (detectadas en la primera llamada).
+pub fn get_capabilities() -> Capabilities {
+    *CAPABILITIES.get_or_init(Capabilities::detect)
+}
+
+/// Indica si un ejecutable está en el `PATH`.
+pub fn in_path(tool: &str) -> bool {
+    std::env::var_os("PATH")
+        .is_some_and(|path| std::env::split_paths(&path).any(|dir| dir.join(tool).is_file()))
+}
+
+/// Indica si `sudo -n ss` funciona sin pedir contraseña.
+fn sudo_ss_allowed() -> bool {
+    let mut sudo = Command::new("sudo");
+    sudo.args(["-n", "ss", "-V"]);
+    sources::output_with_timeout(&mut sudo, SOURCE_TIMEOUT)
+        .is_ok_and(|output| output.status.success())
+}
+
+/// Contenido de `/proc/self/status` (vacío si no se puede leer).
+fn read_status() -> String {
+    std::fs::read_to_string("/proc/self/status").unwrap_or_default()
+}
+
+/// Indica si la línea `CapEff` de `/proc/<pid>/status` incluye algún
+/// permiso para cargar programas eBPF.
+fn has_bpf_capability(status: &str) -> bool {
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("CapEff:"))
+        .and_then(|mask| u64::from_str_radix(mask.trim(), 16).ok())
+        .is_some_and(|mask| BPF_CAPABILITY_BITS.iter().any(|bit| mask & (1 << bit) != 0))
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verifica la lectura de los permisos efectivos para eBPF
+    #[test]
+    fn test_has_bpf_capability() {
+        assert!(!has_bpf_capability(
+            "Name:\tportslayer\nCapEff:\t0000000000000000\n"
+        ));
+        assert!(has_bpf_capability("CapEff:\t000001ffffffffff\n"));
+        assert!(has_bpf_capability("CapEff:\t0000008000000000\n"));
+        assert!(!has_bpf_capability("Name:\tportslayer\n"));
+    }
+}
@@ -0,0 +1,2840 @@
+/// Módulo de la interfaz de línea de comandos.
+///
+/// Sin subcomando, `portslayer` inicia el system tray. Los subcomandos
+/// permiten usar el mismo motor de escaneo desde scripts y CI:
+///
+/// ```text
+/// portslayer list [--protocol tcp|udp|all] [--state ESTADO|all] [--owner me|root|USUARIO] [--scope loopback|exposed] [--json] [--output json|csv [--file RUTA]]
+/// portslayer kill <PID|:PUERTO> [--dry-run] [--force] [--protocol ...] [--json]
+/// portslayer kill-parent <PID> [--yes] [--json]
+/// portslayer killall [--range INICIO-FIN] [--protocol ...] [--json]
+/// portslayer renice <PID|:PUERTO> [--level -20..19]
+/// portslayer ionice <PID|:PUERTO> [--class idle|best-effort]
+/// portslayer freeze [--force] <PID|:PUERTO>
+/// portslayer thaw <PID|:PUERTO>
+/// portslayer watch [--interval SEGUNDOS] [--json]
+/// portslayer graph [--format dot|json] [--file RUTA]
+/// portslayer restart [ÍNDICE]
+/// portslayer adopt <PID> [--name NOMBRE] [--print | --start]
+/// portslayer workspace [NOMBRE | --clear]
+/// portslayer reserve [PUERTO [--owner PROCESO] [--protocol tcp|udp] [--kill] | PUERTO --remove]
+/// portslayer watchlist [PUERTO [--on open,close,owner] | PUERTO --remove]
+/// portslayer history compact
+/// portslayer history export [--since 7d] [--format json|csv] [--file RUTA]
+/// portslayer history at <14:32 | "2026-10-14 14:32" | 2h | UNIX> [--json]
+/// portslayer history changes <14:32 | "2026-10-14 14:32" | 2h | UNIX> [--json]
+/// portslayer snapshot save <NOMBRE>
+/// portslayer snapshot diff <NOMBRE> [NOMBRE] [--json]
+/// portslayer snapshot list
+/// portslayer kill-stats [--json]
+/// portslayer capabilities [--json]
+/// portslayer helper [--uid UID] [--parent PID | --dbus]  (como root)
+/// portslayer tunnels [--json]
+/// portslayer scan-remote <HOST> [--range INICIO-FIN] [--concurrency N] [--timeout MS] [--json]
+/// portslayer explain <PUERTO> [--json]
+/// portslayer free-port [--range INICIO-FIN] [--json]
+/// portslayer autostart [--enable | --disable]
+/// portslayer replay <ARCHIVO> [--speed N] [--loop] [--at SEGUNDOS [--protocol ...] [--json]]
+/// portslayer daemon [--bind HOST:PUERTO] [--dashboard]
+/// portslayer import-tags <ARCHIVO>
+/// portslayer completions bash|zsh|fish
+/// ```
+///
+/// ## Códigos de salida
+/// - `0` → éxito
+/// - `1` → error (kill fallido, archivo inválido...)
+/// - `2` → uso incorrecto (argumentos inválidos)
+/// - `3` → no hay ningún proceso que coincida con el objetivo
+use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
+use portslayer_core::helper::{self, Helper};
+#[cfg(feature = "remote")]
+use portslayer_core::remote::DEFAULT_CONCURRENCY;
+use serde_json::json;
+use std::io::IsTerminal;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::{Duration, Instant};
+
+use crate::adopt;
+#[cfg(feature = "api")]
+use crate::api;
+use crate::autostart;
+use crate::capabilities;
+use crate::completions::{self, Shell};
+use crate::config::Config;
+use crate::connections::{self, KillImpact};
+use crate::effectiveness::KillStats;
+use crate::explain::{self, FindingKind};
+use crate::export::{self, ExportFormat};
+use crate::history::{self, HistoryEventKind, ScanDiff};
+use crate::kubernetes;
+use crate::maintenance;
+use crate::manifest::{self, Manifest};
+use crate::pins::PinStore;
+use crate::polkit;
+#[cfg(feature = "remote")]
+use crate::port_scanner::RemoteOptions;
+use crate::port_scanner::{
+    self, OwnerFilter, PortInfo, ProcessFilter, ProtocolFilter, RangeFilter, ScopeFilter,
+    SocketState, StateFilter,
+};
+use crate::project;
+use crate::replay::{self, Replay};
+use crate::reservations::{Reservation, ReservationStore};
+use crate::restart;
+use crate::safety;
+use crate::sandbox;
+use crate::snapshot::{self, Snapshot};
+use crate::tags::{self, TagStore};
+use crate::throttle::{self, Action, IoClass};
+use crate::time_wait;
+use crate::topology::{GraphFormat, Topology};
+use crate::tui;
+use crate::tunnels;
+use crate::watchlist::{self, WatchEntry, WatchEvent, WatchStore};
+use crate::workspace;
+use crate::zombie;
+
+/// Código de salida cuando ningún proceso coincide con el objetivo
+const EXIT_NOT_FOUND: u8 = 3;
+
+/// Código de salida de `wait` cuando se agota el tiempo (el de `timeout`)
+const EXIT_TIMEOUT: u8 = 124;
+
+/// Tiempo entre escaneos de `wait`
+const WAIT_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Segundos entre escaneos de `watch` si no se indica otro intervalo
+const DEFAULT_WATCH_INTERVAL_SECS: u64 = 2;
+
+/// Espera por defecto de cada conexión de `scan-remote` (ms)
+#[cfg(feature = "remote")]
+const DEFAULT_REMOTE_TIMEOUT_MS: u64 = 500;
+
+/// Argumentos de la línea de comandos
+#[derive(Debug, Parser)]
+#[command(
+    name = "portslayer",
+    version,
+    about = "⚔️ Monitorea y cierra puertos abiertos en Linux"
+)]
+pub struct Cli {
+    /// Subcomando a ejecutar (sin subcomando se inicia el system tray)
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+/// Subcomandos disponibles
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Inicia el system tray (comportamiento por defecto)
+    Tray,
+    /// Genera el script de autocompletado de una shell
+    Completions {
+        /// Shell destino
+        #[arg(value_enum)]
+        shell: ShellArg,
+    },
+    /// Imprime los puertos abiertos para el autocompletado
+    #[command(name = completions::COMPLETE_PORTS, hide = true)]
+    CompletePorts,
+    /// Lista los puertos abiertos
+    List {
+        #[command(flatten)]
+        output: OutputArgs,
+        /// Exporta el escaneo completo (con marca de tiempo) en este formato
+        #[arg(long = "output", value_enum)]
+        export: Option<FormatArg>,
+        /// Archivo donde escribir la exportación (por defecto, stdout)
+        #[arg(long, requires = "export")]
+        file: Option<PathBuf>,
+        /// Sockets en este estado (time_wait, close_wait, established...
+        /// o all) en lugar de solo los que escuchan
+        #[arg(long, value_name = "ESTADO", value_parser = parse_state, conflicts_with = "export")]
+        state: Option<StateFilter>,
+        /// Solo los sockets de este usuario (me, root, un UID o un nombre)
+        #[arg(long, value_name = "USUARIO", value_parser = parse_owner, conflicts_with = "export")]
+        owner: Option<OwnerFilter>,
+        /// Solo los que escuchan en loopback o solo los accesibles desde la red
+        #[arg(long, value_enum, conflicts_with = "export")]
+        scope: Option<ScopeArg>,
+        /// Solo los procesos cuyo nombre o línea de comandos coincide con
+        /// este patrón ("node*", "*vite*")
+        #[arg(long, value_name = "PATRÓN", conflicts_with = "export")]
+        process: Option<String>,
+        /// Como --process, con una expresión regular ("^(node|vite|webpack)")
+        #[arg(
+            long,
+            value_name = "REGEX",
+            value_parser = parse_process_regex,
+            conflicts_with_all = ["export", "process"]
+        )]
+        process_regex: Option<ProcessFilter>,
+    },
+    /// Termina un proceso por PID o los dueños de un puerto (":3000", "tcp/3000", "3000-3010")
+    Kill {
+        /// PID del proceso, ":PUERTO", "tcp/PUERTO" o un rango ("3000-3010")
+        #[arg(required_unless_present = "profile")]
+        target: Option<String>,
+        /// Perfil de `[profiles]` en config.toml: cierra todos sus puertos
+        #[arg(long, conflicts_with = "target")]
+        profile: Option<String>,
+        /// Solo muestra las conexiones que se cortarían, sin matar nada
+        #[arg(long)]
+        dry_run: bool,
+        /// Confirma el kill de procesos críticos (PID 1, sshd, systemd-*,
+        /// gestor de sesiones, PortSlayer)
+        #[arg(long)]
+        force: bool,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Mata al padre de un zombi sin hilos vivos, el único que puede liberarlo
+    KillParent {
+        /// PID del zombi
+        pid: u32,
+        /// No pide confirmación
+        #[arg(long)]
+        yes: bool,
+        /// Salida en JSON en lugar de texto
+        #[arg(long)]
+        json: bool,
+    },
+    /// Corta conexiones TCP de un puerto sin matar al servidor (sin par, las lista)
+    Disconnect {
+        /// Puerto del servicio (":8080", "8080" o un rango)
+        port: String,
+        /// Cliente a desconectar: "IP:PUERTO" (una conexión) o "IP" (todas las suyas)
+        peer: Option<String>,
+        /// Salida en JSON en lugar de texto
+        #[arg(long)]
+        json: bool,
+    },
+    /// Termina todos los procesos con puertos abiertos
+    Killall {
+        /// Solo los puertos de este rango (ej: "3000-3999")
+        #[arg(long, value_parser = parse_port_range)]
+        range: Option<(u16, u16)>,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Cambia la prioridad de CPU de un proceso sin cerrarlo
+    Renice {
+        /// PID del proceso o ":PUERTO"
+        target: String,
+        /// Prioridad, de -20 (máxima) a 19 (mínima)
+        #[arg(long, default_value_t = throttle::DEFAULT_NICE, allow_negative_numbers = true)]
+        level: i32,
+    },
+    /// Cambia la prioridad de disco de un proceso sin cerrarlo
+    Ionice {
+        /// PID del proceso o ":PUERTO"
+        target: String,
+        /// Clase de E/S
+        #[arg(long, value_enum, default_value_t = IoClassArg::Idle)]
+        class: IoClassArg,
+    },
+    /// Congela un proceso (SIGSTOP) conservando su estado
+    Freeze {
+        /// PID del proceso o ":PUERTO"
+        target: String,
+        /// Confirma que se congele un proceso crítico
+        #[arg(long)]
+        force: bool,
+    },
+    /// Reanuda un proceso congelado (SIGCONT)
+    Thaw {
+        /// PID del proceso o ":PUERTO"
+        target: String,
+    },
+    /// Muestra en vivo los puertos que se abren, se cierran o cambian de dueño
+    Watch {
+        /// Segundos entre escaneos (por defecto `scan_interval_secs` o 2)
+        #[arg(long)]
+        interval: Option<u64>,
+        /// Formato de salida; `jsonl` (igual que --json) emite un objeto
+        /// JSON por línea y evento
+        #[arg(long, value_enum, default_value_t = WatchFormatArg::Text)]
+        format: WatchFormatArg,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Espera a que un puerto quede libre (o, con --until-open, a que se abra)
+    Wait {
+        /// Puerto o rango (":3000", "tcp/3000", "3000-3010")
+        target: String,
+        /// Segundos máximos de espera (por defecto, sin límite); al
+        /// agotarse sale con el código 124
+        #[arg(long)]
+        timeout: Option<u64>,
+        /// Espera a que algún proceso escuche en lugar de a que quede libre
+        #[arg(long)]
+        until_open: bool,
+    },
+    /// Tabla interactiva de puertos en la terminal (servidores sin tray)
+    Tui {
+        /// Segundos entre escaneos (por defecto `scan_interval_secs` o 2)
+        #[arg(long)]
+        interval: Option<u64>,
+    },
+    /// Selector rápido para un atajo de teclado: escribe para filtrar y
+    /// Enter mata el proceso elegido
+    QuickKill,
+    /// Exporta el grafo de servicios locales (quién se conecta a quién)
+    Graph {
+        /// Formato del grafo
+        #[arg(long, value_enum, default_value_t = GraphFormatArg::Dot)]
+        format: GraphFormatArg,
+        /// Archivo donde escribir el grafo (por defecto, stdout)
+        #[arg(long)]
+        file: Option<PathBuf>,
+    },
+    /// Relanza un proceso cerrado con su comando original (sin índice, los lista)
+    Restart {
+        /// Índice mostrado por `portslayer restart`
+        index: Option<usize>,
+    },
+    /// Convierte un proceso en marcha en un servicio de usuario de systemd
+    Adopt {
+        /// PID del proceso a adoptar
+        pid: u32,
+        /// Nombre del servicio (por defecto "<proceso>-<puerto>")
+        #[arg(long)]
+        name: Option<String>,
+        /// Solo muestra la unidad generada, sin instalarla
+        #[arg(long)]
+        print: bool,
+        /// Mata el proceso manual y arranca el servicio en su lugar
+        #[arg(long, conflicts_with = "print")]
+        start: bool,
+    },
+    /// Muestra o cambia el espacio de trabajo activo
+    Workspace {
+        /// Espacio a activar (sin nombre, lista los disponibles)
+        name: Option<String>,
+        /// Desactiva el espacio de trabajo actual
+        #[arg(long, conflicts_with = "name")]
+        clear: bool,
+    },
+    /// Indica si los puertos del `.portslayer.toml` del proyecto están libres
+    Project {
+        /// Cierra los procesos que ocupan los puertos del proyecto
+        #[arg(long)]
+        kill: bool,
+        /// Confirma que se cierre un proceso crítico
+        #[arg(long, requires = "kill")]
+        force: bool,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Reserva un puerto para un proceso y avisa si otro lo ocupa (sin puerto, lista las reservas)
+    Reserve {
+        /// Puerto a reservar
+        port: Option<u16>,
+        /// Proceso dueño del puerto (admite comodines, ej: "node*")
+        #[arg(long)]
+        owner: Option<String>,
+        /// Reservar solo este protocolo
+        #[arg(long, value_parser = ["tcp", "udp"])]
+        protocol: Option<String>,
+        /// Matar al intruso además de notificar (requiere --owner)
+        #[arg(long, requires = "owner")]
+        kill: bool,
+        /// Quita la reserva del puerto
+        #[arg(long, requires = "port", conflicts_with_all = ["owner", "protocol", "kill"])]
+        remove: bool,
+    },
+    /// Fija un puerto al principio del tray (sin puerto, lista los fijados y su estado)
+    Pin {
+        /// Puerto a fijar
+        port: Option<u16>,
+        /// Quita el puerto de los fijados
+        #[arg(long, requires = "port")]
+        remove: bool,
+    },
+    /// Vigila un puerto y avisa de sus cambios (sin puerto, lista los vigilados)
+    Watchlist {
+        /// Puerto a vigilar
+        port: Option<u16>,
+        /// Cambios de los que avisar (por defecto, todos)
+        #[arg(
+            long,
+            value_delimiter = ',',
+            requires = "port",
+            conflicts_with = "remove"
+        )]
+        on: Vec<WatchEventArg>,
+        /// Deja de vigilar el puerto
+        #[arg(long, requires = "port")]
+        remove: bool,
+    },
+    /// Mantenimiento del historial de eventos
+    History {
+        #[command(subcommand)]
+        action: HistoryAction,
+    },
+    /// Guarda escaneos con nombre y los compara (ver `snapshot`)
+    Snapshot {
+        #[command(subcommand)]
+        action: SnapshotAction,
+    },
+    /// Muestra qué procesos se relanzan tras matarlos (efectividad de los kills)
+    KillStats {
+        /// Salida en JSON en lugar de texto
+        #[arg(long)]
+        json: bool,
+    },
+    /// Muestra qué puede hacer esta instalación (privilegios, Docker, firewall...)
+    Capabilities {
+        /// Salida en JSON en lugar de texto
+        #[arg(long)]
+        json: bool,
+    },
+    /// Atiende como root las consultas y kills del usuario, para no pedir
+    /// la contraseña en cada acción (ver `privileged_helper`)
+    Helper {
+        /// Usuario al que se atiende (por defecto, quien lanzó `pkexec`/`sudo`)
+        #[arg(long)]
+        uid: Option<u32>,
+        /// Terminar cuando termine este proceso
+        #[arg(long)]
+        parent: Option<u32>,
+        /// Servicio del bus del sistema que autoriza cada kill con polkit
+        /// (lo lanza D-Bus, ver `assets/dbus/`)
+        #[arg(long, conflicts_with_all = ["uid", "parent"])]
+        dbus: bool,
+    },
+    /// Lista los túneles (`ssh -L`, `socat`) que escuchan y a dónde llevan
+    Tunnels {
+        /// Salida en JSON en lugar de texto
+        #[arg(long)]
+        json: bool,
+    },
+    /// Escanea los puertos TCP abiertos de otra máquina (TCP connect)
+    #[cfg(feature = "remote")]
+    ScanRemote {
+        /// Nombre o IP del host
+        host: String,
+        /// Rango de puertos a probar
+        #[arg(long, default_value = "1-1024", value_parser = parse_port_range)]
+        range: (u16, u16),
+        /// Conexiones simultáneas como máximo
+        #[arg(long, default_value_t = DEFAULT_CONCURRENCY)]
+        concurrency: usize,
+        /// Espera máxima de cada conexión, en milisegundos
+        #[arg(long, value_name = "MS", default_value_t = DEFAULT_REMOTE_TIMEOUT_MS)]
+        timeout: u64,
+        /// Salida en JSON en lugar de texto
+        #[arg(long)]
+        json: bool,
+    },
+    /// Explica por qué un puerto está ocupado ("Address already in use")
+    Explain {
+        /// Puerto a diagnosticar
+        port: u16,
+        /// Salida en JSON en lugar de texto
+        #[arg(long)]
+        json: bool,
+        /// Destruye con `ss -K` los sockets en TIME_WAIT que retienen el puerto
+        #[arg(long)]
+        clear_time_wait: bool,
+        /// No pide confirmación antes de --clear-time-wait
+        #[arg(long, requires = "clear_time_wait")]
+        yes: bool,
+    },
+    /// Busca el primer puerto TCP libre de un rango
+    FreePort {
+        /// Rango en el que buscar
+        #[arg(long, default_value = "3000-9999", value_parser = parse_port_range)]
+        range: (u16, u16),
+        /// Salida en JSON en lugar de texto
+        #[arg(long)]
+        json: bool,
+    },
+    /// Inicia PortSlayer con la sesión (sin opciones, muestra si está activado)
+    Autostart {
+        /// Crea la entrada en ~/.config/autostart
+        #[arg(long, conflicts_with = "disable")]
+        enable: bool,
+        /// Borra la entrada de ~/.config/autostart
+        #[arg(long)]
+        disable: bool,
+    },
+    /// Reproduce una sesión grabada (formato de history.jsonl) en el tray
+    Replay {
+        /// Archivo de la sesión
+        file: PathBuf,
+        /// Velocidad de reproducción (10 = diez veces más rápido)
+        #[arg(long, default_value_t = 1.0)]
+        speed: f64,
+        /// Vuelve a empezar al llegar al final
+        #[arg(long = "loop")]
+        repeat: bool,
+        /// Sin tray: lista los puertos a los N segundos de la sesión
+        #[arg(long, value_name = "SEGUNDOS")]
+        at: Option<u64>,
+        #[command(flatten)]
+        output: OutputArgs,
+    },
+    /// Inicia el modo daemon con la API HTTP
+    #[cfg(feature = "api")]
+    Daemon {
+        /// Dirección donde escuchar la API
+        #[arg(long, default_value = api::DEFAULT_BIND)]
+        bind: String,
+        /// Sirve un panel web de solo lectura (sin token) en `/`
+        #[arg(long)]
+        dashboard: bool,
+    },
+    /// Importa un paquete de etiquetas compartido por el equipo
+    ImportTags {
+        /// Archivo JSON del paquete
+        path: PathBuf,
+    },
+}
+
+/// Acciones de `history`
+#[derive(Debug, Subcommand)]
+pub enum HistoryAction {
+    /// Elimina líneas corruptas, eventos caducados y recorta el archivo al límite
+    Compact,
+    /// Exporta los eventos del historial
+    Export {
+        /// Solo los eventos de este periodo (ej: "7d", "12h", "30m")
+        #[arg(long, value_parser = parse_age)]
+        since: Option<u64>,
+        /// Formato de salida
+        #[arg(long, value_enum, default_value_t = FormatArg::Json)]
+        format: FormatArg,
+        /// Archivo donde escribir la exportación (por defecto, stdout)
+        #[arg(long)]
+        file: Option<PathBuf>,
+    },
+    /// Muestra los puertos que estaban abiertos en un momento pasado
+    At {
+        /// Momento: "14:32" (hoy), "2026-10-14 14:32", "2h" (hace 2 horas) o segundos UNIX
+        #[arg(value_parser = parse_moment)]
+        moment: u64,
+        /// Salida en JSON en lugar de texto
+        #[arg(long)]
+        json: bool,
+    },
+    /// Muestra los puertos abiertos, cerrados y con otro PID desde un momento pasado
+    Changes {
+        /// Momento: "14:32" (hoy), "2026-10-14 14:32", "2h" (hace 2 horas) o segundos UNIX
+        #[arg(value_parser = parse_moment)]
+        since: u64,
+        /// Salida en JSON en lugar de texto
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+/// Acciones de `snapshot`
+#[derive(Debug, Subcommand)]
+pub enum SnapshotAction {
+    /// Guarda el escaneo actual (reemplaza otra con el mismo nombre)
+    Save {
+        /// Nombre de la instantánea (letras, números, -, _ y .)
+        name: String,
+    },
+    /// Compara una instantánea con otra o con el escaneo actual
+    Diff {
+        /// Instantánea de partida
+        before: String,
+        /// Instantánea con la que comparar (por defecto, el escaneo actual)
+        after: Option<String>,
+        /// Salida en JSON en lugar de texto
+        #[arg(long)]
+        json: bool,
+    },
+    /// Lista las instantáneas guardadas
+    List,
+}
+
+/// Opciones de filtrado y formato comunes a varios subcomandos
+#[derive(Debug, clap::Args)]
+pub struct OutputArgs {
+    /// Protocolo a considerar
+    #[arg(long, value_enum, default_value_t = ProtocolArg::All)]
+    protocol: ProtocolArg,
+    /// Salida en JSON en lugar de texto
+    #[arg(long)]
+    json: bool,
+}
+
+/// Valores aceptados por `--protocol`
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum ProtocolArg {
+    All,
+    Tcp,
+    Udp,
+}
+
+impl From<ProtocolArg> for ProtocolFilter {
+    fn from(arg: ProtocolArg) -> Self {
+        match arg {
+            ProtocolArg::All => ProtocolFilter::All,
+            ProtocolArg::Tcp => ProtocolFilter::Tcp,
+            ProtocolArg::Udp => ProtocolFilter::Udp,
+        }
+    }
+}
+
+/// Valores aceptados por `--scope`
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ScopeArg {
+    Loopback,
+    Exposed,
+}
+
+impl From<ScopeArg> for ScopeFilter {
+    fn from(arg: ScopeArg) -> Self {
+        match arg {
+            ScopeArg::Loopback => ScopeFilter::Loopback,
+            ScopeArg::Exposed => ScopeFilter::Exposed,
+        }
+    }
+}
+
+/// Valores aceptados por `--output`
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum FormatArg {
+    Json,
+    Csv,
+}
+
+impl From<FormatArg> for ExportFormat {
+    fn from(arg: FormatArg) -> Self {
+        match arg {
+            FormatArg::Json => ExportFormat::Json,
+            FormatArg::Csv => ExportFormat::Csv,
+        }
+    }
+}
+
+/// Valores aceptados por `graph --format`
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum GraphFormatArg {
+    Dot,
+    Json,
+}
+
+impl From<GraphFormatArg> for GraphFormat {
+    fn from(arg: GraphFormatArg) -> Self {
+        match arg {
+            GraphFormatArg::Dot => GraphFormat::Dot,
+            GraphFormatArg::Json => GraphFormat::Json,
+        }
+    }
+}
+
+/// Valores aceptados por `watch --format`
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum WatchFormatArg {
+    Text,
+    Jsonl,
+}
+
+/// Valores aceptados por `ionice --class`
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum IoClassArg {
+    Idle,
+    BestEffort,
+}
+
+impl From<IoClassArg> for IoClass {
+    fn from(arg: IoClassArg) -> Self {
+        match arg {
+            IoClassArg::Idle => IoClass::Idle,
+            IoClassArg::BestEffort => IoClass::BestEffort,
+        }
+    }
+}
+
+/// Valores aceptados por `watchlist --on`
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum WatchEventArg {
+    Open,
+    Close,
+    Owner,
+}
+
+impl From<WatchEventArg> for WatchEvent {
+    fn from(arg: WatchEventArg) -> Self {
+        match arg {
+            WatchEventArg::Open => WatchEvent::Open,
+            WatchEventArg::Close => WatchEvent::Close,
+            WatchEventArg::Owner => WatchEvent::Owner,
+        }
+    }
+}
+
+/// Valores aceptados por `completions`
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum ShellArg {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl From<ShellArg> for Shell {
+    fn from(arg: ShellArg) -> Self {
+        match arg {
+            ShellArg::Bash => Shell::Bash,
+            ShellArg::Zsh => Shell::Zsh,
+            ShellArg::Fish => Shell::Fish,
+        }
+    }
+}
+
+/// Objetivo de `kill` y de las acciones que frenan procesos
+#[derive(Debug, PartialEq)]
+enum KillTarget {
+    /// Un proceso concreto
+    Pid(u32),
+    /// Todos los procesos que escuchan en un puerto o un rango
+    Ports(PortSpec),
+    /// Todos los procesos que escuchan en los puertos de un perfil
+    Profile { name: String, ports: Vec<u16> },
+}
+
+impl KillTarget {
+    /// Indica si un puerto escaneado es de este objetivo.
+    fn matches(&self, port: &PortInfo) -> bool {
+        match self {
+            KillTarget::Pid(pid) => port.pid == *pid,
+            KillTarget::Ports(spec) => spec.matches(port),
+            KillTarget::Profile { ports, .. } => ports.contains(&port.port),
+        }
+    }
+
+    /// Descripción para los mensajes (ver [`PortSpec::describe`]).
+    fn describe(&self) -> String {
+        match self {
+            KillTarget::Pid(pid) => format!("el PID {}", pid),
+            KillTarget::Ports(spec) => spec.describe(),
+            KillTarget::Profile { name, .. } => format!("los puertos del perfil {}", name),
+        }
+    }
+}
+
+/// Puertos de un objetivo: ":3000", "tcp/3000" o "3000-3010"
+#[derive(Debug, PartialEq)]
+struct PortSpec {
+    /// "tcp" o "udp" (`None`: los dos)
+    protocol: Option<&'static str>,
+    start: u16,
+    end: u16,
+}
+
+impl PortSpec {
+    /// Indica si un puerto escaneado es de este objetivo.
+    fn matches(&self, port: &PortInfo) -> bool {
+        (self.start..=self.end).contains(&port.port)
+            && self
+                .protocol
+                .is_none_or(|protocol| port.protocol == protocol)
+    }
+
+    /// Descripción para los mensajes: "el puerto 3000", "los puertos
+    /// 3000-3010", "el puerto tcp/3000".
+    fn describe(&self) -> String {
+        let protocol = self.protocol.map(|p| format!("{}/", p)).unwrap_or_default();
+        if self.start == self.end {
+            format!("el puerto {}{}", protocol, self.start)
+        } else {
+            format!("los puertos {}{}-{}", protocol, self.start, self.end)
+        }
+    }
+}
+
+/// Interpreta el objetivo de `kill`: "1234" (PID) o unos puertos (ver
+/// [`parse_port_spec`]).
+fn parse_kill_target(target: &str) -> Result<KillTarget, String> {
+    let is_ports = target.starts_with(':') || target.contains(['/', '-']);
+    if is_ports {
+        return parse_port_spec(target).map(KillTarget::Ports);
+    }
+    match target.parse::<u32>() {
+        Ok(pid) if pid > 0 => Ok(KillTarget::Pid(pid)),
+        _ => Err(format!(
+            "PID inválido: {} (usa :PUERTO, tcp/PUERTO o INICIO-FIN para puertos)",
+            target
+        )),
+    }
+}
+
+/// Objetivo de `kill`: el perfil de `--profile` (según la
+/// configuración) o el indicado (ver [`parse_kill_target`]).
+fn resolve_kill_target(target: Option<&str>, profile: Option<&str>) -> Result<KillTarget, String> {
+    let Some(name) = profile else {
+        return parse_kill_target(target.unwrap_or_default());
+    };
+    let config = Config::load();
+    match config.profiles.get(name) {
+        Some(ports) => Ok(KillTarget::Profile {
+            name: name.to_string(),
+            ports: ports.clone(),
+        }),
+        None if config.profiles.is_empty() => Err(format!(
+            "Perfil desconocido: {} (defínelos en [profiles] de {})",
+            name,
+            Config::path().display()
+        )),
+        None => {
+            let names: Vec<&str> = config.profiles.keys().map(String::as_str).collect();
+            Err(format!(
+                "Perfil desconocido: {} (disponibles: {})",
+                name,
+                names.join(", ")
+            ))
+        }
+    }
+}
+
+/// Interpreta unos puertos: ":3000" o "3000", "tcp/3000" o "udp/5353"
+/// (de un protocolo) y "3000-3010" (rango, también con protocolo).
+fn parse_port_spec(text: &str) -> Result<PortSpec, String> {
+    let (protocol, ports) = match text.split_once('/') {
+        Some((protocol, ports)) => match protocol.to_lowercase().as_str() {
+            "tcp" => (Some("tcp"), ports),
+            "udp" => (Some("udp"), ports),
+            _ => {
+                return Err(format!(
+                    "Protocolo desconocido: {} (usa tcp o udp)",
+                    protocol
+                ))
+            }
+        },
+        None => (None, text),
+    };
+    let ports = ports.strip_prefix(':').unwrap_or(ports);
+    let (start, end) = if ports.contains('-') {
+        parse_port_range(ports)?
+    } else {
+        match ports.parse::<u16>() {
+            Ok(port) if port > 0 => (port, port),
+            _ => return Err(format!("Puerto inválido: {}", ports)),
+        }
+    };
+    Ok(PortSpec {
+        protocol,
+        start,
+        end,
+    })
+}
+
+/// Interpreta una antigüedad ("7d", "12h", "30m", "45s") en segundos.
+fn parse_age(text: &str) -> Result<u64, String> {
+    let invalid = || format!("Periodo inválido: {} (usa ej: 7d, 12h, 30m)", text);
+    let split = text.len().saturating_sub(1);
+    let (amount, unit) = text.split_at(split);
+    let multiplier = match unit {
+        "d" => 86_400,
+        "h" => 3_600,
+        "m" => 60,
+        "s" => 1,
+        _ => return Err(invalid()),
+    };
+    match amount.parse::<u64>() {
+        Ok(amount) if amount > 0 => Ok(amount * multiplier),
+        _ => Err(invalid()),
+    }
+}
+
+/// Interpreta un momento pasado y lo devuelve en segundos UNIX.
+///
+/// Acepta segundos UNIX, una hora de hoy ("14:32"), una fecha y hora
+/// locales ("2026-10-14 14:32") o una antigüedad ("2h", ver `parse_age`).
+fn parse_moment(text: &str) -> Result<u64, String> {
+    if !text.is_empty() && text.bytes().all(|b| b.is_ascii_digit()) {
+        return text
+            .parse()
+            .map_err(|_| format!("Momento inválido: {}", text));
+    }
+    if text.contains(':') {
+        let datetime = match text.parse::<jiff::civil::DateTime>() {
+            Ok(datetime) => datetime,
+            Err(_) => {
+                let time = text
+                    .parse::<jiff::civil::Time>()
+                    .map_err(|_| format!("Momento inválido: {} (usa ej: 14:32)", text))?;
+                maintenance::local_now().date().to_datetime(time)
+            }
+        };
+        return datetime
+            .to_zoned(jiff::tz::TimeZone::system())
+            .map(|zoned| zoned.timestamp().as_second().max(0) as u64)
+            .map_err(|e| format!("Momento inválido: {} ({})", text, e));
+    }
+    parse_age(text).map(|age| history::unix_now().saturating_sub(age))
+}
+
+// ─────────────────────────────────────────────────────────────
+// Ejecución de subcomandos
+// ─────────────────────────────────────────────────────────────
+
+/// Ejecuta un subcomando de una sola vez (todos salvo tray y daemon).
+///
+/// # Returns
+/// Código de salida del proceso.
+pub fn run(command: Command) -> ExitCode {
+    // Aplica el idioma y el escaneo de namespaces de la configuración
+    Config::load();
+    match command {
+        Command::Completions { shell } => {
+            print!("{}", completions::generate(shell.into(), &Cli::command()));
+            ExitCode::SUCCESS
+        }
+        Command::CompletePorts => {
+            for line in completions::port_candidates(&port_scanner::scan_open_ports()) {
+                println!("{}", line);
+            }
+            ExitCode::SUCCESS
+        }
+        Command::List {
+            output,
+            export: Some(format),
+            file,
+            ..
+        } => run_export(&output, format.into(), file.as_deref()),
+        Command::List {
+            output,
+            state,
+            owner,
+            scope,
+            process,
+            process_regex,
+            ..
+        } => run_list(
+            &output,
+            state,
+            owner.unwrap_or(OwnerFilter::All),
+            scope.map_or(ScopeFilter::All, Into::into),
+            process_regex
+                .or(process.map(ProcessFilter::Glob))
+                .unwrap_or_default(),
+        ),
+        Command::Kill {
+            target,
+            profile,
+            dry_run,
+            force,
+            output,
+        } => run_kill(
+            target.as_deref(),
+            profile.as_deref(),
+            dry_run,
+            force,
+            &output,
+        ),
+        Command::KillParent { pid, yes, json } => run_kill_parent(pid, yes, json),
+        Command::Disconnect { port, peer, json } => run_disconnect(&port, peer.as_deref(), json),
+        Command::Killall { range, output } => run_killall(range, &output),
+        Command::Renice { target, level } => run_throttle(&target, Action::Renice(level), false),
+        Command::Ionice { target, class } => {
+            run_throttle(&target, Action::Ionice(class.into()), false)
+        }
+        Command::Freeze { target, force } => run_throttle(&target, Action::Freeze, force),
+        Command::Thaw { target } => run_throttle(&target, Action::Thaw, false),
+        Command::Watch {
+            interval,
+            format,
+            output,
+        } => run_watch(interval, format, &output),
+        Command::Wait {
+            target,
+            timeout,
+            until_open,
+        } => run_wait(&target, timeout, until_open),
+        Command::Tui { interval } => run_tui(interval, false),
+        Command::QuickKill => run_tui(None, true),
+        Command::Graph { format, file } => run_graph(format.into(), file.as_deref()),
+        Command::Restart { index } => run_restart(index),
+        Command::Adopt {
+            pid,
+            name,
+            print,
+            start,
+        } => run_adopt(pid, name.as_deref(), print, start),
+        Command::Workspace { name, clear } => run_workspace(name, clear),
+        Command::Project {
+            kill,
+            force,
+            output,
+        } => run_project(kill, force, &output),
+        Command::Reserve {
+            port,
+            owner,
+            protocol,
+            kill,
+            remove,
+        } => run_reserve(port, owner, protocol, kill, remove),
+        Command::Pin { port, remove } => run_pin(port, remove),
+        Command::Watchlist { port, on, remove } => run_watchlist(port, on, remove),
+        Command::History { action } => run_history(action),
+        Command::Snapshot { action } => run_snapshot(action),
+        Command::KillStats { json } => run_kill_stats(json),
+        Command::Capabilities { json } => run_capabilities(json),
+        Command::Helper { dbus: true, .. } => run_polkit_service(),
+        Command::Helper { uid, parent, .. } => run_helper(uid, parent),
+        Command::Tunnels { json } => run_tunnels(json),
+        #[cfg(feature = "remote")]
+        Command::ScanRemote {
+            host,
+            range,
+            concurrency,
+            timeout,
+            json,
+        } => {
+            let options = RemoteOptions {
+                concurrency,
+                timeout: Duration::from_millis(timeout),
+            };
+            run_scan_remote(&host, range, options, json)
+        }
+        Command::Explain {
+            port,
+            json,
+            clear_time_wait,
+            yes,
+        } => match clear_time_wait {
+            true => run_clear_time_wait(port, yes, json),
+            false => run_explain(port, json),
+        },
+        Command::FreePort { range, json } => run_free_port(range, json),
+        Command::Autostart { enable, disable } => run_autostart(enable, disable),
+        Command::ImportTags { path } => run_import_tags(&path),
+        Command::Replay {
+            file,
+            speed,
+            repeat,
+            at: Some(offset),
+            output,
+        } => run_replay_at(&file, speed, repeat, offset, &output),
+        Command::Replay { .. } => unreachable!("la reproducción en el tray se inicia desde main"),
+        Command::Tray => unreachable!("el tray se inicia desde main"),
+        #[cfg(feature = "api")]
+        Command::Daemon { .. } => unreachable!("el daemon se inicia desde main"),
+    }
+}
+
+/// Escanea y aplica el filtro de protocolo solicitado.
+fn scan_filtered(output: &OutputArgs) -> Vec<PortInfo> {
+    port_scanner::filter_ports(
+        &port_scanner::scan_open_ports(),
+        output.protocol.into(),
+        RangeFilter::All,
+        StateFilter::All,
+    )
+}
+
+/// Interpreta el estado de `--state` ("time_wait", "TIME-WAIT", "all"...).
+fn parse_state(text: &str) -> Result<StateFilter, String> {
+    StateFilter::parse(text).ok_or_else(|| {
+        let names: Vec<&str> = SocketState::ALL.iter().map(|s| s.label()).collect();
+        format!(
+            "Estado desconocido '{}': usa all o {}",
+            text,
+            names.join(", ")
+        )
+    })
+}
+
+/// Interpreta el usuario de `--owner` ("me", "root", un UID o un nombre).
+fn parse_owner(text: &str) -> Result<OwnerFilter, String> {
+    OwnerFilter::parse(text).ok_or_else(|| format!("Usuario desconocido '{}'", text))
+}
+
+/// Interpreta la expresión regular de `--process-regex`.
+fn parse_process_regex(text: &str) -> Result<ProcessFilter, String> {
+    ProcessFilter::regex(text).map_err(|e| format!("Expresión regular inválida: {}", e))
+}
+
+/// `list`: imprime los puertos abiertos en formato tabla o JSON.
+///
+/// Con `--state` lista los sockets en ese estado, escuchen o no; con
+/// `--owner`, solo los de ese usuario; con `--scope`, solo los locales
+/// o los expuestos, y con `--process` o `--process-regex`, solo los de
+/// los procesos que coinciden.
+fn run_list(
+    output: &OutputArgs,
+    state: Option<StateFilter>,
+    owner: OwnerFilter,
+    scope: ScopeFilter,
+    process: ProcessFilter,
+) -> ExitCode {
+    let mut ports = match state {
+        Some(state) => port_scanner::filter_ports(
+            &port_scanner::scan_all_sockets(),
+            output.protocol.into(),
+            RangeFilter::All,
+            state,
+        ),
+        None => scan_filtered(output),
+    };
+    ports.retain(|p| owner.matches(p.uid) && scope.matches(p) && process.matches(p));
+    let store = TagStore::load();
+
+    if output.json {
+        let annotated: Vec<serde_json::Value> =
+            ports.iter().map(|p| tags::annotate(p, &store)).collect();
+        println!("{}", json!(annotated));
+        return ExitCode::SUCCESS;
+    }
+
+    println!(
+        "{:<6} {:<7} {:<40} {:<11} {:<8} {:<8} PROCESO",
+        "PROTO", "PUERTO", "DIRECCIÓN", "ESTADO", "PID", "ACTIVO"
+    );
+    let now = history::unix_now();
+    let pods = kubernetes::pods();
+    for port in &ports {
+        let pid = if port.pid > 0 {
+            port.pid.to_string()
+        } else {
+            "-".to_string()
+        };
+        // Sin PID, al menos el dueño del socket ("desconocido [👤 postgres]")
+        let mut process = match port.uid {
+            Some(uid) if port.pid == 0 => format!(
+                "{} [👤 {}]",
+                port.process_name,
+                port_scanner::user_name(uid)
+            ),
+            _ => port.process_name.clone(),
+        };
+        if let Some(pod) = kubernetes::pod_for(port, &pods) {
+            process = format!("{} ☸ {}", process, pod.full_name());
+        }
+        if let Some(sandbox) = sandbox::detect(port.pid) {
+            process = format!("{} {}", process, sandbox.label());
+        }
+        if let Some(defunct) = zombie::detect(port.pid) {
+            process = format!("{} {}", process, defunct.label());
+        }
+        if let Some(project) = project::project_for(port.pid) {
+            process = format!("{} 📁 {}", process, project);
+        }
+        let uptime = port
+            .uptime(now)
+            .map(port_scanner::format_uptime)
+            .unwrap_or_else(|| "-".to_string());
+        let notes: Vec<&str> = store
+            .tags_for(port)
+            .iter()
+            .map(|t| t.note.as_str())
+            .collect();
+        let notes = if notes.is_empty() {
+            String::new()
+        } else {
+            format!("  # {}", notes.join(", "))
+        };
+        let address = match port.netns {
+            Some(netns) => format!("{} (netns {})", port.local_address, netns),
+            None => port.local_address.clone(),
+        };
+        println!(
+            "{:<6} {:<7} {:<40} {:<11} {:<8} {:<8} {}{}",
+            port.protocol,
+            port.port,
+            address,
+            port.state.label(),
+            pid,
+            uptime,
+            process,
+            notes
+        );
+    }
+    ExitCode::SUCCESS
+}
+
+/// `list --output`: exporta el escaneo a stdout o a un archivo.
+fn run_export(
+    output: &OutputArgs,
+    format: ExportFormat,
+    file: Option<&std::path::Path>,
+) -> ExitCode {
+    let ports = scan_filtered(output);
+    match file {
+        Some(path) => match export::export_ports(&ports, format, path) {
+            Ok(count) => {
+                println!("{} puertos exportados a {}", count, path.display());
+                ExitCode::SUCCESS
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                ExitCode::FAILURE
+            }
+        },
+        None => {
+            print!(
+                "{}",
+                export::render(&ports, &TagStore::load(), format, history::unix_now())
+            );
+            ExitCode::SUCCESS
+        }
+    }
+}
+
+/// `kill`: termina un PID o los dueños de un puerto.
+///
+/// Antes de matar avisa de las conexiones establecidas que se
+/// cortarán; con `--dry-run` solo muestra ese impacto.
+fn run_kill(
+    target: Option<&str>,
+    profile: Option<&str>,
+    dry_run: bool,
+    force: bool,
+    output: &OutputArgs,
+) -> ExitCode {
+    let target = match resolve_kill_target(target, profile) {
+        Ok(target) => target,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::from(2);
+        }
+    };
+
+    let ports = scan_filtered(output);
+    let matching: Vec<&PortInfo> = ports.iter().filter(|p| target.matches(p)).collect();
+
+    if dry_run {
+        if let Err(code) = refuse_protected(&matching) {
+            return code;
+        }
+        let established = connections::scan_established();
+        let impacts: Vec<(&PortInfo, KillImpact)> = matching
+            .iter()
+            .map(|p| (*p, connections::kill_impact(p, &established)))
+            .collect();
+        report_impact(&impacts, output);
+        return if impacts.is_empty() {
+            ExitCode::from(EXIT_NOT_FOUND)
+        } else {
+            ExitCode::SUCCESS
+        };
+    }
+    if let Err(code) = prepare_kill(&matching, force) {
+        return code;
+    }
+    // Un PID sin puertos abiertos no pasa por `prepare_kill`, pero
+    // `safety::check` lo vigila igualmente al matarlo
+    if let KillTarget::Pid(pid) = target {
+        if force {
+            safety::confirm(pid);
+        }
+        if matching.is_empty() {
+            restart::remember(pid, 0, "tcp");
+        }
+    }
+
+    let result = match target {
+        // Un PID sin puertos abiertos también se puede matar
+        KillTarget::Pid(pid) => port_scanner::kill_process(pid).map(|()| 1),
+        _ => {
+            if matching.is_empty() {
+                eprintln!("Ningún proceso escucha en {}", target.describe());
+                return ExitCode::from(EXIT_NOT_FOUND);
+            }
+            kill_matching(&matching)
+        }
+    };
+    if result.is_ok() && !output.json {
+        let mut pids: Vec<u32> = matching.iter().map(|p| p.pid).collect();
+        if let KillTarget::Pid(pid) = target {
+            pids.push(pid);
+        }
+        pids.sort_unstable();
+        pids.dedup();
+        for pid in pids {
+            if let Ok((parent, name)) = zombie::reaping_parent(pid) {
+                eprintln!(
+                    "🧟 El PID {} sigue como zombi sin hilos; solo lo libera matar a su padre {} (PID {}): portslayer kill-parent {}",
+                    pid, name, parent, pid
+                );
+            }
+        }
+    }
+
+    report_kill(result, output)
+}
+
+/// `kill-parent`: mata al padre de un zombi sin hilos vivos, tras
+/// confirmarlo (el padre suele ser una shell o un IDE).
+fn run_kill_parent(pid: u32, yes: bool, json: bool) -> ExitCode {
+    let (parent, name) = match zombie::reaping_parent(pid) {
+        Ok(parent) => parent,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::from(EXIT_NOT_FOUND);
+        }
+    };
+    let question = format!(
+        "El PID {} es un zombi que retiene {} (PID {}). ¿Matar a {}?",
+        pid, name, parent, name
+    );
+    if !yes && !confirm(&question) {
+        eprintln!("Cancelado (usa --yes para no preguntar)");
+        return ExitCode::FAILURE;
+    }
+    match zombie::kill_parent(pid) {
+        Ok(parent) => {
+            if json {
+                println!("{}", json!({ "ok": true, "zombie": pid, "killed": parent }));
+            } else {
+                println!(
+                    "{} (PID {}) terminado; init recogerá al zombi",
+                    name, parent
+                );
+            }
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            if json {
+                println!("{}", json!({ "ok": false, "error": e.message }));
+            } else {
+                eprintln!("{}", e);
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// `disconnect`: lista las conexiones de los clientes de un puerto o
+/// corta las de un par con `ss -K`, sin matar al servidor.
+fn run_disconnect(port: &str, peer: Option<&str>, json: bool) -> ExitCode {
+    let spec = match parse_port_spec(port) {
+        Ok(spec) if spec.protocol != Some("udp") => spec,
+        Ok(_) => {
+            eprintln!("Solo se pueden cortar conexiones TCP");
+            return ExitCode::from(2);
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::from(2);
+        }
+    };
+    let established = connections::scan_established();
+    let mut clients: Vec<&connections::Connection> = Vec::new();
+    for listener in port_scanner::scan_open_ports()
+        .iter()
+        .filter(|p| spec.matches(p))
+    {
+        for conn in connections::clients_of(listener, &established) {
+            if !clients.contains(&conn) {
+                clients.push(conn);
+            }
+        }
+    }
+
+    let Some(peer) = peer else {
+        if json {
+            println!("{}", json!(clients));
+        } else if clients.is_empty() {
+            println!("Ningún cliente conectado a {}", spec.describe());
+        } else {
+            for conn in &clients {
+                println!(
+                    "{} {:<24} → {}:{}  {} [PID {}]",
+                    if conn.is_local_peer() { "🖥" } else { "🌐" },
+                    conn.peer(),
+                    conn.local_address,
+                    conn.local_port,
+                    conn.process_name,
+                    conn.pid
+                );
+            }
+        }
+        return if clients.is_empty() {
+            ExitCode::from(EXIT_NOT_FOUND)
+        } else {
+            ExitCode::SUCCESS
+        };
+    };
+
+    let selected: Vec<&connections::Connection> = clients
+        .into_iter()
+        .filter(|c| c.matches_peer(peer))
+        .collect();
+    if selected.is_empty() {
+        eprintln!("Ninguna conexión de {} en {}", peer, spec.describe());
+        return ExitCode::from(EXIT_NOT_FOUND);
+    }
+    let mut dropped = 0;
+    let mut errors: Vec<String> = Vec::new();
+    for conn in &selected {
+        match connections::drop_connection(conn) {
+            Ok(()) => dropped += 1,
+            Err(e) => errors.push(e),
+        }
+    }
+    if json {
+        println!(
+            "{}",
+            json!({ "ok": errors.is_empty(), "dropped": dropped, "errors": errors })
+        );
+    } else {
+        if dropped > 0 {
+            println!("✂️ {} conexiones cortadas", dropped);
+        }
+        for e in &errors {
+            eprintln!("{}", e);
+        }
+    }
+    if errors.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// `kill --dry-run`: imprime el impacto estimado por puerto.
+fn report_impact(impacts: &[(&PortInfo, KillImpact)], output: &OutputArgs) {
+    if output.json {
+        let ports: Vec<serde_json::Value> = impacts
+            .iter()
+            .map(|(port, impact)| {
+                json!({ "protocol": port.protocol, "port": port.port, "pid": port.pid, "impact": impact })
+            })
+            .collect();
+        println!("{}", json!({ "dry_run": true, "ports": ports }));
+        return;
+    }
+
+    if impacts.is_empty() {
+        println!("El objetivo no tiene puertos abiertos");
+    }
+    for (port, impact) in impacts {
+        println!("{}: {}", port, impact.summary());
+        if let Some(note) = impact.dependency_note(&port.process_name) {
+            println!("  ⛓ {}", note);
+        }
+        for peer in &impact.local_peers {
+            println!("  🖥 {} ({} conexiones)", peer.address, peer.connections);
+        }
+        for peer in &impact.remote_peers {
+            println!("  🌐 {} ({} conexiones)", peer.address, peer.connections);
+        }
+    }
+}
+
+/// Comprobaciones comunes antes de cerrar los dueños de unos puertos
+/// (`kill`, también con perfil, y `project --kill`): rechaza los marcados
+/// como "no matar", avisa de las conexiones establecidas que se cortarán,
+/// exige `--force` para los procesos críticos y captura cada proceso
+/// para poder relanzarlo con `portslayer restart`.
+fn prepare_kill(matching: &[&PortInfo], force: bool) -> Result<(), ExitCode> {
+    refuse_protected(matching)?;
+
+    // Avisar antes de cortar conexiones (en stderr para no romper --json)
+    let established = connections::scan_established();
+    for port in matching {
+        let impact = connections::kill_impact(port, &established);
+        if impact.established == 0 {
+            continue;
+        }
+        eprintln!("⚠️  Puerto {}: {}", port.port, impact.summary());
+        if let Some(note) = impact.dependency_note(&port.process_name) {
+            eprintln!("⛓  {}", note);
+        }
+    }
+
+    // Los procesos críticos (ver `safety`) solo se cierran con --force
+    if force {
+        matching.iter().for_each(|p| safety::confirm(p.pid));
+    } else if let Some(critical) = matching
+        .iter()
+        .find(|p| p.pid > 0 && safety::assess(p.pid, &p.process_name).is_some())
+    {
+        eprintln!(
+            "{} (PID {}) es un proceso crítico; usa --force para cerrarlo",
+            critical.process_name, critical.pid
+        );
+        return Err(ExitCode::FAILURE);
+    }
+
+    let mut seen: Vec<u32> = Vec::new();
+    for port in matching.iter().filter(|p| p.pid > 0) {
+        if !seen.contains(&port.pid) {
+            seen.push(port.pid);
+            restart::remember(port.pid, port.port, &port.protocol);
+        }
+    }
+    Ok(())
+}
+
+/// Rechaza el cierre si alguno de los puertos está marcado como
+/// "no matar".
+fn refuse_protected(matching: &[&PortInfo]) -> Result<(), ExitCode> {
+    let store = TagStore::load();
+    match matching.iter().find(|p| store.is_protected(p)) {
+        Some(protected) => {
+            eprintln!(
+                "Puerto {} marcado como \"no matar\"; no se cerrará",
+                protected.port
+            );
+            Err(ExitCode::FAILURE)
+        }
+        None => Ok(()),
+    }
+}
+
+/// Termina los procesos de los puertos indicados.
+///
+/// Los puertos sin PID conocido se cierran con `fuser` vía pkexec; los
+/// de Windows (en WSL2) no se tocan.
+fn kill_matching(matching: &[&PortInfo]) -> Result<usize, String> {
+    let with_pid: Vec<PortInfo> = matching
+        .iter()
+        .filter(|p| p.pid > 0)
+        .map(|p| (*p).clone())
+        .collect();
+    let mut killed = 0;
+
+    if !with_pid.is_empty() {
+        killed += port_scanner::kill_port_processes(&with_pid, &[])?;
+    }
+    let without_pid = matching
+        .iter()
+        .filter(|p| p.pid == 0 && p.origin.is_linux());
+    for port in without_pid {
+        port_scanner::kill_port_by_number(port.port, &port.protocol)?;
+        killed += 1;
+    }
+    Ok(killed)
+}
+
+/// `renice`, `ionice`, `freeze` y `thaw`: aplican la acción al proceso o
+/// a los dueños de un puerto (`force` confirma congelar uno crítico).
+fn run_throttle(target: &str, action: Action, force: bool) -> ExitCode {
+    let pids = match parse_kill_target(target) {
+        Ok(KillTarget::Pid(pid)) => vec![pid],
+        Ok(target) => {
+            let mut pids: Vec<u32> = port_scanner::scan_open_ports()
+                .iter()
+                .filter(|p| target.matches(p) && p.pid > 0)
+                .map(|p| p.pid)
+                .collect();
+            pids.sort_unstable();
+            pids.dedup();
+            if pids.is_empty() {
+                eprintln!(
+                    "Ningún proceso con PID conocido escucha en {}",
+                    target.describe()
+                );
+                return ExitCode::from(EXIT_NOT_FOUND);
+            }
+            pids
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::from(2);
+        }
+    };
+
+    let mut failed = false;
+    for pid in pids {
+        if force {
+            safety::confirm(pid);
+        }
+        match throttle::apply(pid, action) {
+            Ok(()) => println!("{} aplicado al PID {}", action.describe(), pid),
+            Err(e) => {
+                eprintln!("{}", e);
+                failed = true;
+            }
+        }
+    }
+    if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// Interpreta un rango de puertos "INICIO-FIN" (extremos incluidos).
+fn parse_port_range(text: &str) -> Result<(u16, u16), String> {
+    let (start, end) = text
+        .split_once('-')
+        .ok_or_else(|| format!("Rango inválido '{}': usa INICIO-FIN", text))?;
+    let start: u16 = start
+        .trim()
+        .parse()
+        .map_err(|_| format!("Puerto inválido: {}", start))?;
+    let end: u16 = end
+        .trim()
+        .parse()
+        .map_err(|_| format!("Puerto inválido: {}", end))?;
+    if start == 0 || start > end {
+        return Err(format!("Rango inválido: {}-{}", start, end));
+    }
+    Ok((start, end))
+}
+
+/// `killall`: termina todos los procesos con puertos, salvo los protegidos.
+///
+/// Con `--range` solo se consideran los puertos de ese rango.
+fn run_killall(range: Option<(u16, u16)>, output: &OutputArgs) -> ExitCode {
+    let mut ports = scan_filtered(output);
+    if let Some((start, end)) = range {
+        ports = port_scanner::ports_in_range(&ports, start, end);
+    }
+    if ports.is_empty() {
+        if !output.json {
+            println!("No hay puertos abiertos");
+        }
+        return ExitCode::from(EXIT_NOT_FOUND);
+    }
+
+    let store = TagStore::load();
+    let protected: Vec<u32> = ports
+        .iter()
+        .filter(|p| p.pid > 0 && store.is_protected(p))
+        .map(|p| p.pid)
+        .collect();
+
+    report_kill(
+        port_scanner::kill_port_processes(&ports, &protected),
+        output,
+    )
+}
+
+/// Imprime el resultado de un kill y devuelve el código de salida.
+fn report_kill(result: Result<usize, String>, output: &OutputArgs) -> ExitCode {
+    match result {
+        Ok(count) => {
+            if output.json {
+                println!("{}", json!({ "ok": true, "killed": count }));
+            } else {
+                println!("{} procesos terminados", count);
+            }
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            if output.json {
+                println!("{}", json!({ "ok": false, "error": e }));
+            } else {
+                eprintln!("{}", e);
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// `watch`: imprime los cambios entre escaneos hasta que se interrumpa.
+///
+/// Con `--format jsonl` (o `--json`) cada cambio es una línea JSON
+/// independiente: las aperturas y cierres con el formato de los eventos
+/// del historial y los cambios de dueño como `owner_changed`.
+fn run_watch(interval: Option<u64>, format: WatchFormatArg, output: &OutputArgs) -> ExitCode {
+    let interval = interval
+        .or(Config::load().scan_interval_secs)
+        .unwrap_or(DEFAULT_WATCH_INTERVAL_SECS);
+    let interval = Duration::from_secs(interval.max(1));
+    let jsonl = output.json || format == WatchFormatArg::Jsonl;
+    let mut previous = scan_filtered(output);
+
+    if !jsonl {
+        println!(
+            "Vigilando {} puertos (Ctrl+C para salir)...",
+            previous.len()
+        );
+    }
+
+    loop {
+        std::thread::sleep(interval);
+        let current = scan_filtered(output);
+
+        for event in history::scan_changes(&previous, &current) {
+            if jsonl {
+                println!("{}", json!(event));
+            } else {
+                let sign = match event.kind {
+                    HistoryEventKind::Opened => "+",
+                    _ => "-",
+                };
+                println!(
+                    "{} {} {} → {} [PID {}]",
+                    sign,
+                    event.protocol.to_uppercase(),
+                    event.port,
+                    event.process_name,
+                    event.pid
+                );
+            }
+        }
+        for change in history::diff_scans(&previous, &current).pid_changes {
+            if jsonl {
+                println!("{}", owner_change_line(&change, history::unix_now()));
+            } else {
+                println!(
+                    "~ {} {} → {} [PID {}] (antes {} [PID {}])",
+                    change.protocol.to_uppercase(),
+                    change.port,
+                    change.after_process,
+                    change.after_pid,
+                    change.before_process,
+                    change.before_pid
+                );
+            }
+        }
+        previous = current;
+    }
+}
+
+/// Línea de `watch --format jsonl` para un puerto que cambió de dueño.
+///
+/// Lleva los mismos campos que las de apertura y cierre (con el proceso
+/// nuevo) más `previous_pid` y `previous_process`.
+fn owner_change_line(change: &history::PidChange, timestamp: u64) -> serde_json::Value {
+    json!({
+        "timestamp": timestamp,
+        "kind": "owner_changed",
+        "protocol": change.protocol,
+        "port": change.port,
+        "pid": change.after_pid,
+        "process_name": change.after_process,
+        "previous_pid": change.before_pid,
+        "previous_process": change.before_process,
+    })
+}
+
+/// `wait`: espera a que los puertos queden libres o, con `until_open`, a
+/// que alguien escuche en ellos.
+fn run_wait(target: &str, timeout: Option<u64>, until_open: bool) -> ExitCode {
+    let spec = match parse_port_spec(target) {
+        Ok(spec) => spec,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::from(2);
+        }
+    };
+    let deadline = timeout.map(|secs| Instant::now() + Duration::from_secs(secs));
+    loop {
+        let open = port_scanner::scan_open_ports()
+            .iter()
+            .any(|p| spec.matches(p));
+        if open == until_open {
+            return ExitCode::SUCCESS;
+        }
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            match until_open {
+                true => eprintln!("Tiempo agotado: nadie escucha aún en {}", spec.describe()),
+                false => eprintln!(
+                    "Tiempo agotado: sigue habiendo procesos escuchando en {}",
+                    spec.describe()
+                ),
+            }
+            return ExitCode::from(EXIT_TIMEOUT);
+        }
+        std::thread::sleep(WAIT_POLL_INTERVAL);
+    }
+}
+
+/// `tui` y `quick-kill`: abre la tabla interactiva de puertos.
+fn run_tui(interval: Option<u64>, quick: bool) -> ExitCode {
+    let interval = interval
+        .or(Config::load().scan_interval_secs)
+        .unwrap_or(DEFAULT_WATCH_INTERVAL_SECS);
+    match tui::run(Duration::from_secs(interval.max(1)), quick) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// `graph`: exporta la topología de servicios locales.
+fn run_graph(format: GraphFormat, file: Option<&std::path::Path>) -> ExitCode {
+    let graph = Topology::build(
+        &port_scanner::scan_open_ports(),
+        &connections::scan_established(),
+    );
+    let content = graph.render(format);
+
+    match file {
+        Some(path) => match std::fs::write(path, content) {
+            Ok(()) => {
+                println!(
+                    "Grafo con {} servicios exportado a {}",
+                    graph.nodes.len(),
+                    path.display()
+                );
+                ExitCode::SUCCESS
+            }
+            Err(e) => {
+                eprintln!("No se pudo escribir {}: {}", path.display(), e);
+                ExitCode::FAILURE
+            }
+        },
+        None => {
+            print!("{}", content);
+            ExitCode::SUCCESS
+        }
+    }
+}
+
+/// `restart`: lista las capturas o relanza una de ellas.
+fn run_restart(index: Option<usize>) -> ExitCode {
+    let Some(index) = index else {
+        let snapshots = restart::load_snapshots();
+        if snapshots.is_empty() {
+            println!("No hay procesos para reiniciar");
+            return ExitCode::from(EXIT_NOT_FOUND);
+        }
+        for (i, snapshot) in snapshots.iter().enumerate() {
+            println!(
+                "{:>2}  :{:<5}  {}  ({})",
+                i,
+                snapshot.port,
+                snapshot.command_line(),
+                snapshot.cwd.display()
+            );
+        }
+        return ExitCode::SUCCESS;
+    };
+
+    match restart::restart(index) {
+        Ok(pid) => {
+            println!("Proceso relanzado con PID {}", pid);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// `adopt`: genera e instala un servicio de systemd para un proceso.
+fn run_adopt(pid: u32, name: Option<&str>, print: bool, start: bool) -> ExitCode {
+    let unit = match adopt::unit_for_pid(pid, name) {
+        Ok(unit) => unit,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::from(EXIT_NOT_FOUND);
+        }
+    };
+    if print {
+        print!("{}", unit.content);
+        return ExitCode::SUCCESS;
+    }
+
+    match unit.install() {
+        Ok(path) => println!("Servicio instalado en {}", path.display()),
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    }
+
+    if start {
+        let owned = port_scanner::scan_open_ports()
+            .into_iter()
+            .find(|p| p.pid == pid);
+        let (port, protocol) = owned
+            .map(|p| (p.port, p.protocol))
+            .unwrap_or((0, "tcp".to_string()));
+        if let Err(e) = adopt::start_in_place(&unit, pid, port, &protocol) {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+        println!("▶ {} en marcha (el proceso {} se detuvo)", unit.name, pid);
+    } else {
+        println!(
+            "Detén el proceso actual y ejecuta: systemctl --user start {}",
+            unit.name
+        );
+    }
+    println!(
+        "Para arrancarlo al iniciar sesión: systemctl --user enable {}",
+        unit.name
+    );
+    ExitCode::SUCCESS
+}
+
+/// `project`: estado de los puertos del manifiesto del directorio actual
+/// o, con `kill`, cierre de los procesos que los ocupan.
+///
+/// Sale con 0 si todos están libres (o se liberaron), 1 si alguno está
+/// ocupado y 3 si no hay manifiesto.
+fn run_project(kill: bool, force: bool, output: &OutputArgs) -> ExitCode {
+    let (path, manifest) = match Manifest::discover() {
+        Ok(Some(found)) => found,
+        Ok(None) => {
+            eprintln!(
+                "No hay ningún {} en este directorio ni en sus padres",
+                manifest::FILE_NAME
+            );
+            return ExitCode::from(EXIT_NOT_FOUND);
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let name = manifest.display_name(&path);
+    let ports = scan_filtered(output);
+    let status = manifest.status(&ports);
+    let busy: Vec<&PortInfo> = status.iter().flat_map(|s| s.owners.clone()).collect();
+
+    if kill {
+        if busy.is_empty() {
+            println!("Los puertos de {} ya están libres", name);
+            return ExitCode::SUCCESS;
+        }
+        if let Err(code) = prepare_kill(&busy, force) {
+            return code;
+        }
+        return report_kill(kill_matching(&busy), output);
+    }
+
+    if output.json {
+        let ports: Vec<serde_json::Value> = status
+            .iter()
+            .map(|s| {
+                let owners: Vec<serde_json::Value> = s
+                    .owners
+                    .iter()
+                    .map(|p| json!({ "protocol": p.protocol, "pid": p.pid, "process_name": p.process_name }))
+                    .collect();
+                json!({ "service": s.service, "port": s.port, "free": s.owners.is_empty(), "owners": owners })
+            })
+            .collect();
+        println!(
+            "{}",
+            json!({ "project": name, "manifest": path, "free": busy.is_empty(), "ports": ports })
+        );
+    } else {
+        println!("📋 {} ({})", name, path.display());
+        for s in &status {
+            match s.owners.first() {
+                None => println!("  ✅ {:<6} {:<12} libre", s.port, s.service),
+                Some(owner) => println!(
+                    "  🔴 {:<6} {:<12} ocupado por {} [PID {}]",
+                    s.port, s.service, owner.process_name, owner.pid
+                ),
+            }
+        }
+    }
+    if busy.is_empty() {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// `workspace`: lista los espacios de trabajo o cambia el activo.
+fn run_workspace(name: Option<String>, clear: bool) -> ExitCode {
+    let config = Config::load();
+
+    if clear {
+        return match workspace::set_active(None) {
+            Ok(()) => ExitCode::SUCCESS,
+            Err(e) => {
+                eprintln!("{}", e);
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    let Some(name) = name else {
+        if config.workspaces.is_empty() {
+            println!("No hay espacios de trabajo en {}", Config::path().display());
+            return ExitCode::from(EXIT_NOT_FOUND);
+        }
+        let active = config.active_workspace().map(|(name, _)| name);
+        for name in config.workspaces.keys() {
+            let marker = if active.as_ref() == Some(name) {
+                "●"
+            } else {
+                "○"
+            };
+            println!("{} {}", marker, name);
+        }
+        return ExitCode::SUCCESS;
+    };
+
+    if !config.workspaces.contains_key(&name) {
+        eprintln!("El espacio de trabajo '{}' no existe", name);
+        return ExitCode::from(EXIT_NOT_FOUND);
+    }
+    match workspace::set_active(Some(&name)) {
+        Ok(()) => {
+            println!("Espacio de trabajo activo: {}", name);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// `reserve`: lista, añade o quita reservas de puertos.
+fn run_reserve(
+    port: Option<u16>,
+    owner: Option<String>,
+    protocol: Option<String>,
+    kill: bool,
+    remove: bool,
+) -> ExitCode {
+    let mut store = ReservationStore::load();
+
+    let Some(port) = port else {
+        if store.reservations.is_empty() {
+            println!("No hay puertos reservados");
+            return ExitCode::from(EXIT_NOT_FOUND);
+        }
+        let ports = port_scanner::scan_open_ports();
+        for reservation in &store.reservations {
+            let intruders: Vec<String> = ports
+                .iter()
+                .filter(|p| reservation.is_intruder(p))
+                .map(|p| format!("{} [PID {}]", p.process_name, p.pid))
+                .collect();
+            if intruders.is_empty() {
+                println!("🛡 {}", reservation.describe());
+            } else {
+                println!(
+                    "🚨 {}  ocupado por {}",
+                    reservation.describe(),
+                    intruders.join(", ")
+                );
+            }
+        }
+        return ExitCode::SUCCESS;
+    };
+
+    if remove {
+        if store.release(port) == 0 {
+            eprintln!("El puerto {} no está reservado", port);
+            return ExitCode::from(EXIT_NOT_FOUND);
+        }
+    } else {
+        let reservation = Reservation {
+            port,
+            protocol,
+            owner,
+            kill,
+        };
+        let description = reservation.describe();
+        if let Err(e) = store.reserve(reservation) {
+            eprintln!("{}", e);
+            return ExitCode::from(2);
+        }
+        println!("🛡 Reservado {}", description);
+    }
+
+    match store.save() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("No se pudieron guardar las reservas: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// `watchlist`: lista, vigila o deja de vigilar puertos.
+fn run_watchlist(port: Option<u16>, on: Vec<WatchEventArg>, remove: bool) -> ExitCode {
+    let config = Config::load();
+    let mut store = WatchStore::load();
+
+    let Some(port) = port else {
+        let entries = watchlist::entries(&config, &store);
+        if entries.is_empty() {
+            println!("No hay puertos vigilados");
+            return ExitCode::from(EXIT_NOT_FOUND);
+        }
+        let ports = port_scanner::scan_open_ports();
+        for entry in entries {
+            let events: Vec<&str> = entry.events().iter().map(|e| e.name()).collect();
+            let source = if config.watch.contains(&entry) {
+                " (config.toml)"
+            } else {
+                ""
+            };
+            let state = match watchlist::owner(entry.port, &ports) {
+                Some(owner) => format!("🔴 abierto por {} [PID {}]", owner.process_name, owner.pid),
+                None => "⚪ cerrado".to_string(),
+            };
+            println!(
+                "👁 {:<6} {}  avisa de: {}{}",
+                entry.port,
+                state,
+                events.join(", "),
+                source
+            );
+        }
+        return ExitCode::SUCCESS;
+    };
+
+    if config.watch.iter().any(|e| e.port == port) {
+        eprintln!(
+            "El puerto {} se vigila desde {}: cámbialo ahí",
+            port,
+            Config::path().display()
+        );
+        return ExitCode::FAILURE;
+    }
+    if remove {
+        if !store.unwatch(port) {
+            eprintln!("El puerto {} no se vigila", port);
+            return ExitCode::from(EXIT_NOT_FOUND);
+        }
+        println!("Puerto {} ya no se vigila", port);
+    } else {
+        let entry = WatchEntry {
+            port,
+            on: on.into_iter().map(WatchEvent::from).collect(),
+        };
+        if !store.watch(entry) {
+            println!("El puerto {} ya se vigilaba", port);
+            return ExitCode::SUCCESS;
+        }
+        println!("👁 Puerto {} vigilado", port);
+    }
+
+    match store.save() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("No se pudieron guardar los puertos vigilados: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// `pin`: lista, fija o suelta puertos fijados.
+fn run_pin(port: Option<u16>, remove: bool) -> ExitCode {
+    let mut store = PinStore::load();
+
+    let Some(port) = port else {
+        if store.ports.is_empty() {
+            println!("No hay puertos fijados");
+            return ExitCode::from(EXIT_NOT_FOUND);
+        }
+        for status in store.status(&port_scanner::scan_open_ports()) {
+            if status.open {
+                let owners: Vec<String> = status
+                    .listeners
+                    .iter()
+                    .map(|p| {
+                        format!(
+                            "{} {} [PID {}]",
+                            p.protocol.to_uppercase(),
+                            p.process_name,
+                            p.pid
+                        )
+                    })
+                    .collect();
+                println!("📌 🔴 {}  abierto por {}", status.port, owners.join(", "));
+            } else {
+                println!("📌 ⚪ {}  cerrado", status.port);
+            }
+        }
+        return ExitCode::SUCCESS;
+    };
+
+    if remove {
+        if !store.unpin(port) {
+            eprintln!("El puerto {} no está fijado", port);
+            return ExitCode::from(EXIT_NOT_FOUND);
+        }
+        println!("Puerto {} soltado", port);
+    } else if store.pin(port) {
+        println!("📌 Puerto {} fijado", port);
+    } else {
+        println!("El puerto {} ya estaba fijado", port);
+        return ExitCode::SUCCESS;
+    }
+
+    match store.save() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("No se pudieron guardar los puertos fijados: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// `history`: compacta o exporta el historial de eventos.
+fn run_history(action: HistoryAction) -> ExitCode {
+    match action {
+        HistoryAction::Compact => match history::compact(&Config::load().history) {
+            Ok(stats) => {
+                println!(
+                    "Historial compactado: {} eventos conservados ({} corruptos, {} caducados, {} recortados)",
+                    stats.kept, stats.corrupt, stats.expired, stats.pruned
+                );
+                ExitCode::SUCCESS
+            }
+            Err(e) => {
+                eprintln!("No se pudo compactar el historial: {}", e);
+                ExitCode::FAILURE
+            }
+        },
+        HistoryAction::Export {
+            since,
+            format,
+            file,
+        } => {
+            let since = since.map_or(0, |age| history::unix_now().saturating_sub(age));
+            let events = history::read_since(since);
+            let content = export::render_history(&events, format.into());
+            match file {
+                Some(path) => match std::fs::write(&path, content) {
+                    Ok(()) => {
+                        println!("{} eventos exportados a {}", events.len(), path.display());
+                        ExitCode::SUCCESS
+                    }
+                    Err(e) => {
+                        eprintln!("No se pudo escribir {}: {}", path.display(), e);
+                        ExitCode::FAILURE
+                    }
+                },
+                None => {
+                    print!("{}", content);
+                    ExitCode::SUCCESS
+                }
+            }
+        }
+        HistoryAction::At { moment, json } => run_history_at(moment, json),
+        HistoryAction::Changes { since, json } => run_history_changes(since, json),
+    }
+}
+
+/// `history at`: puertos abiertos en un momento pasado.
+fn run_history_at(moment: u64, json: bool) -> ExitCode {
+    let ports = history::ports_at(moment);
+    if json {
+        println!(
+            "{}",
+            json!({ "timestamp": moment, "count": ports.len(), "ports": ports })
+        );
+    } else if ports.is_empty() {
+        println!("No hay puertos registrados en ese momento");
+    } else {
+        println!("{:<6} {:<7} {:<8} PROCESO", "PROTO", "PUERTO", "PID");
+        for port in &ports {
+            println!(
+                "{:<6} {:<7} {:<8} {}",
+                port.protocol, port.port, port.pid, port.process_name
+            );
+        }
+    }
+    if ports.is_empty() {
+        ExitCode::from(EXIT_NOT_FOUND)
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// `history changes`: qué cambió desde un momento pasado.
+fn run_history_changes(since: u64, json: bool) -> ExitCode {
+    let diff = history::changes_since(since);
+    if json {
+        println!("{}", json!({ "since": since, "changes": diff }));
+    } else if diff.is_empty() {
+        println!("Sin cambios registrados desde ese momento");
+    } else {
+        print_diff(&diff, use_color());
+    }
+    if diff.is_empty() {
+        ExitCode::from(EXIT_NOT_FOUND)
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+/// `snapshot`: guarda escaneos con nombre y los compara.
+fn run_snapshot(action: SnapshotAction) -> ExitCode {
+    match action {
+        SnapshotAction::Save { name } => {
+            let snapshot = Snapshot::new(&name, port_scanner::scan_open_ports());
+            match snapshot.save() {
+                Ok(path) => {
+                    println!(
+                        "Instantánea \"{}\" guardada: {} puertos en {}",
+                        name,
+                        snapshot.ports.len(),
+                        path.display()
+                    );
+                    ExitCode::SUCCESS
+                }
+                Err(e) => {
+                    eprintln!("{}", e);
+                    ExitCode::FAILURE
+                }
+            }
+        }
+        SnapshotAction::Diff {
+            before,
+            after,
+            json,
+        } => {
+            let load = |name: &str| {
+                Snapshot::load(name)
+                    .inspect_err(|e| eprintln!("{}", e))
+                    .ok()
+            };
+            let Some(before) = load(&before) else {
+                return ExitCode::FAILURE;
+            };
+            let after = match after {
+                Some(name) => match load(&name) {
+                    Some(snapshot) => snapshot,
+                    None => return ExitCode::FAILURE,
+                },
+                None => Snapshot::new("actual", port_scanner::scan_open_ports()),
+            };
+            let diff = history::diff_scans(&before.ports, &after.ports);
+            if json {
+                println!(
+                    "{}",
+                    json!({ "before": before.name, "after": after.name, "changes": diff })
+                );
+            } else if diff.is_empty() {
+                println!("Sin cambios entre \"{}\" y \"{}\"", before.name, after.name);
+            } else {
+                print_diff(&diff, use_color());
+            }
+            if diff.is_empty() {
+                ExitCode::from(EXIT_NOT_FOUND)
+            } else {
+                ExitCode::SUCCESS
+            }
+        }
+        SnapshotAction::List => {
+            let names = snapshot::list();
+            if names.is_empty() {
+                println!("No hay instantáneas guardadas");
+                return ExitCode::from(EXIT_NOT_FOUND);
+            }
+            for name in names {
+                println!("{}", name);
+            }
+            ExitCode::SUCCESS
+        }
+    }
+}
+
+/// Colorear la salida: solo en una terminal y sin `NO_COLOR`.
+fn use_color() -> bool {
+    std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none()
+}
+
+/// Imprime una diferencia entre escaneos: `+` abiertos (verde), `-`
+/// cerrados (rojo) y `~` con otro PID (amarillo).
+fn print_diff(diff: &ScanDiff, color: bool) {
+    let paint = |code: &str, line: String| {
+        if color {
+            println!("\x1b[{}m{}\x1b[0m", code, line);
+        } else {
+            println!("{}", line);
+        }
+    };
+    for (sign, code, ports) in [("+", "32", &diff.opened), ("-", "31", &diff.closed)] {
+        for port in ports {
+            paint(
+                code,
+                format!(
+                    "{} {:<4} {:<6} {} [PID {}]",
+                    sign,
+                    port.protocol.to_uppercase(),
+                    port.port,
+                    port.process_name,
+                    port.pid
+                ),
+            );
+        }
+    }
+    for change in &diff.pid_changes {
+        paint(
+            "33",
+            format!(
+                "~ {:<4} {:<6} {} [PID {}] → {} [PID {}]",
+                change.protocol.to_uppercase(),
+                change.port,
+                change.before_process,
+                change.before_pid,
+                change.after_process,
+                change.after_pid
+            ),
+        );
+    }
+}
+
+/// `kill-stats`: efectividad de los kills por proceso.
+fn run_kill_stats(json: bool) -> ExitCode {
+    let stats = KillStats::load();
+    let processes = stats.processes();
+    if json {
+        println!("{}", json!(processes));
+        return ExitCode::SUCCESS;
+    }
+    if processes.is_empty() {
+        println!("Todavía no hay kills comprobados");
+        return ExitCode::from(EXIT_NOT_FOUND);
+    }
+
+    println!(
+        "{:<20} {:>6} {:>10} {:>11} {:>8}",
+        "PROCESO", "KILLS", "LIBERADOS", "RELANZADOS", "OTROS"
+    );
+    for p in &processes {
+        println!(
+            "{:<20} {:>6} {:>10} {:>11} {:>8}",
+            p.process_name,
+            p.kills,
+            p.freed,
+            p.respawned,
+            p.occupied + p.still_open
+        );
+    }
+    for suggestion in processes.iter().filter_map(|p| p.suggestion()) {
+        println!("💡 {}", suggestion);
+    }
+    ExitCode::SUCCESS
+}
+
+/// `capabilities`: qué acciones están disponibles en esta instalación.
+fn run_capabilities(json: bool) -> ExitCode {
+    let capabilities = capabilities::get_capabilities();
+    if json {
+        println!("{}", json!(capabilities));
+        return ExitCode::SUCCESS;
+    }
+    for (name, description, available) in capabilities.entries() {
+        let mark = if available { "✅" } else { "❌" };
+        println!("{} {:<14} {}", mark, name, description);
+    }
+    ExitCode::SUCCESS
+}
+
+/// `helper`: atiende las peticiones privilegiadas hasta que termine `parent`.
+fn run_helper(uid: Option<u32>, parent: Option<u32>) -> ExitCode {
+    // pkexec y sudo dejan en el entorno quién los lanzó
+    let uid = uid
+        .or_else(|| {
+            ["PKEXEC_UID", "SUDO_UID"]
+                .iter()
+                .find_map(|var| std::env::var(var).ok()?.parse().ok())
+        })
+        .unwrap_or_else(|| nix::unistd::getuid().as_raw());
+    match Helper::bind(&helper::socket_path(uid), uid) {
+        Ok(helper) => {
+            helper.serve(parent);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// `helper --dbus`: servicio de kills autorizado por polkit.
+fn run_polkit_service() -> ExitCode {
+    match polkit::serve() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// `tunnels`: reenvíos de puertos abiertos y su destino.
+fn run_tunnels(json: bool) -> ExitCode {
+    let tunnels = tunnels::detect(&port_scanner::scan_open_ports());
+    if json {
+        println!("{}", json!(tunnels));
+        return ExitCode::SUCCESS;
+    }
+    if tunnels.is_empty() {
+        println!("No hay túneles abiertos");
+        return ExitCode::from(EXIT_NOT_FOUND);
+    }
+    for tunnel in &tunnels {
+        let via = tunnel
+            .via
+            .as_deref()
+            .map(|host| format!(" vía {}", host))
+            .unwrap_or_default();
+        let orphaned = if tunnel.orphaned {
+            " 🧟 huérfano"
+        } else {
+            ""
+        };
+        println!(
+            "{} {} → {}{} ({}, PID {}){}",
+            tunnel.port.protocol.to_uppercase(),
+            tunnel.port.port,
+            tunnel.target,
+            via,
+            tunnel.kind.label(),
+            tunnel.port.pid,
+            orphaned
+        );
+    }
+    println!("Ciérralos con `portslayer kill :PUERTO`");
+    ExitCode::SUCCESS
+}
+
+/// `scan-remote`: puertos TCP abiertos de otra máquina.
+#[cfg(feature = "remote")]
+fn run_scan_remote(host: &str, range: (u16, u16), options: RemoteOptions, json: bool) -> ExitCode {
+    let scan = match port_scanner::scan_remote_with(host, range, options) {
+        Ok(scan) => scan,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    if json {
+        println!("{}", json!(scan));
+        return ExitCode::SUCCESS;
+    }
+    if scan.open.is_empty() {
+        println!(
+            "Ningún puerto abierto en {} ({}) entre {} y {}",
+            scan.host, scan.address, scan.start, scan.end
+        );
+        return ExitCode::from(EXIT_NOT_FOUND);
+    }
+    println!("{:<7} LATENCIA", "PUERTO");
+    for port in &scan.open {
+        println!("{:<7} {} ms", port.port, port.latency_ms);
+    }
+    println!(
+        "{} abiertos en {} ({}), puertos {}-{}, en {} ms",
+        scan.open.len(),
+        scan.host,
+        scan.address,
+        scan.start,
+        scan.end,
+        scan.elapsed_ms
+    );
+    ExitCode::SUCCESS
+}
+
+/// `explain`: diagnostica por qué un puerto está ocupado.
+fn run_explain(port: u16, json: bool) -> ExitCode {
+    let explanation = explain::explain_port(port);
+    if json {
+        println!("{}", json!(explanation));
+    } else {
+        for finding in &explanation.findings {
+            let icon = match finding.kind {
+                FindingKind::Owner => "●",
+                FindingKind::Docker => "🐳",
+                FindingKind::SocketActivation => "⚙",
+                FindingKind::TimeWait => "⏳",
+                FindingKind::Hint => "💡",
+                FindingKind::Free => "✓",
+            };
+            println!("{} {}", icon, finding.text);
+        }
+        let listening = explanation
+            .findings
+            .iter()
+            .any(|f| f.kind == FindingKind::Owner);
+        if explanation.time_wait.is_some() && !listening {
+            println!(
+                "💡 Para liberarlo ya: portslayer explain {} --clear-time-wait",
+                port
+            );
+        }
+    }
+    if explanation.busy {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::from(EXIT_NOT_FOUND)
+    }
+}
+
+/// `explain --clear-time-wait`: destruye los sockets en TIME_WAIT del
+/// puerto, tras confirmarlo (o con `yes`).
+fn run_clear_time_wait(port: u16, yes: bool, json: bool) -> ExitCode {
+    let Some(lingering) = time_wait::for_port(port) else {
+        eprintln!("No hay sockets en TIME_WAIT en el puerto {}", port);
+        return ExitCode::from(EXIT_NOT_FOUND);
+    };
+    let question = format!(
+        "¿Destruir los {} sockets en TIME_WAIT del puerto {} con ss -K?",
+        lingering.count, port
+    );
+    if !yes && !confirm(&question) {
+        eprintln!("Cancelado (usa --yes para no preguntar)");
+        return ExitCode::FAILURE;
+    }
+    match time_wait::destroy(port) {
+        Ok(destroyed) => {
+            if json {
+                println!("{}", json!({ "ok": true, "destroyed": destroyed }));
+            } else {
+                println!("🧹 {} sockets en TIME_WAIT destruidos", destroyed);
+            }
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            if json {
+                println!("{}", json!({ "ok": false, "error": e }));
+            } else {
+                eprintln!("{}", e);
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Pregunta "sí o no" en la terminal (en stderr, para no romper --json).
+///
+/// Sin terminal no se puede confirmar, así que la respuesta es no.
+fn confirm(question: &str) -> bool {
+    if !std::io::stdin().is_terminal() {
+        return false;
+    }
+    eprint!("{} [s/N] ", question);
+    let mut answer = String::new();
+    if std::io::stdin().read_line(&mut answer).is_err() {
+        return false;
+    }
+    matches!(
+        answer.trim().to_lowercase().as_str(),
+        "s" | "si" | "sí" | "y" | "yes"
+    )
+}
+
+/// `free-port`: imprime el primer puerto TCP libre del rango.
+fn run_free_port(range: (u16, u16), json: bool) -> ExitCode {
+    let port = port_scanner::find_free_port(range);
+    if json {
+        println!(
+            "{}",
+            json!({ "start": range.0, "end": range.1, "port": port })
+        );
+    } else if let Some(port) = port {
+        println!("{}", port);
+    } else {
+        eprintln!("Ningún puerto libre entre {} y {}", range.0, range.1);
+    }
+    match port {
+        Some(_) => ExitCode::SUCCESS,
+        None => ExitCode::from(EXIT_NOT_FOUND),
+    }
+}
+
+/// `replay --at`: lista los puertos de una sesión grabada en una posición.
+fn run_replay_at(
+    file: &std::path::Path,
+    speed: f64,
+    repeat: bool,
+    offset: u64,
+    output: &OutputArgs,
+) -> ExitCode {
+    match Replay::load(file, speed, repeat) {
+        Ok(replay) => replay::start(replay.freeze_at(offset)),
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    }
+    run_list(
+        output,
+        None,
+        OwnerFilter::All,
+        ScopeFilter::All,
+        ProcessFilter::All,
+    )
+}
+
+/// `autostart`: activa, desactiva o consulta el inicio con la sesión.
+fn run_autostart(enable: bool, disable: bool) -> ExitCode {
+    if enable || disable {
+        if let Err(e) = autostart::set_enabled(enable) {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    }
+    let state = if autostart::is_enabled() {
+        "activado"
+    } else {
+        "desactivado"
+    };
+    println!(
+        "Inicio con la sesión {} ({})",
+        state,
+        autostart::path().display()
+    );
+    ExitCode::SUCCESS
+}
+
+/// `import-tags`: instala un paquete de etiquetas compartido.
+fn run_import_tags(path: &std::path::Path) -> ExitCode {
+    match tags::import_bundle(path) {
+        Ok(bundle) => {
+            println!(
+                "Paquete '{}' importado ({} etiquetas)",
+                bundle.name,
+                bundle.tags.len()
+            );
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verifica la interpretación de PIDs y puertos en `kill`
+    #[test]
+    fn test_parse_kill_target() {
+        assert_eq!(parse_kill_target("1234"), Ok(KillTarget::Pid(1234)));
+        let ports = |protocol, start, end| {
+            Ok(KillTarget::Ports(PortSpec {
+                protocol,
+                start,
+                end,
+            }))
+        };
+        assert_eq!(parse_kill_target(":3000"), ports(None, 3000, 3000));
+        assert_eq!(
+            parse_kill_target("tcp/3000"),
+            ports(Some("tcp"), 3000, 3000)
+        );
+        assert_eq!(
+            parse_kill_target("UDP/5353"),
+            ports(Some("udp"), 5353, 5353)
+        );
+        assert_eq!(parse_kill_target("3000-3010"), ports(None, 3000, 3010));
+        assert_eq!(
+            parse_kill_target("tcp/:3000-3010"),
+            ports(Some("tcp"), 3000, 3010)
+        );
+        assert!(parse_kill_target("sctp/3000").is_err());
+        assert_eq!(
+            parse_port_spec("3000").map(KillTarget::Ports),
+            ports(None, 3000, 3000)
+        );
+        assert!(parse_kill_target("3010-3000").is_err());
+        assert!(parse_kill_target(":0").is_err());
+        assert!(parse_kill_target(":99999").is_err());
+        assert!(parse_kill_target("abc").is_err());
+    }
+
+    /// Verifica los perfiles de `kill --profile` de la configuración
+    #[test]
+    fn test_kill_profile() {
+        let config = Config::parse("[profiles]\nfrontend = [3000, 5173]\n").unwrap();
+        let target = KillTarget::Profile {
+            name: "frontend".into(),
+            ports: config.profiles["frontend"].clone(),
+        };
+        assert_eq!(target.describe(), "los puertos del perfil frontend");
+        assert!(matches!(&target, KillTarget::Profile { ports, .. } if ports == &[3000, 5173]));
+        assert!(Config::parse("[profiles]\nvacio = []\n").is_err());
+        assert!(Config::parse("[profiles]\nmalo = [0]\n").is_err());
+    }
+
+    /// Verifica la interpretación de rangos de `killall --range`
+    #[test]
+    fn test_parse_port_range() {
+        assert_eq!(parse_port_range("3000-3999"), Ok((3000, 3999)));
+        assert_eq!(parse_port_range("8080-8080"), Ok((8080, 8080)));
+        assert!(parse_port_range("3999-3000").is_err());
+        assert!(parse_port_range("0-10").is_err());
+        assert!(parse_port_range("3000").is_err());
+    }
+
+    /// Verifica que las líneas de cambio de dueño de `watch --format jsonl`
+    /// siguen el esquema de las de apertura y cierre
+    #[test]
+    fn test_owner_change_line() {
+        let change = history::PidChange {
+            protocol: "tcp".into(),
+            port: 3000,
+            before_pid: 100,
+            before_process: "node".into(),
+            after_pid: 200,
+            after_process: "vite".into(),
+        };
+        let line = owner_change_line(&change, 1_700_000_000);
+        assert_eq!(line["kind"], "owner_changed");
+        assert_eq!(line["timestamp"], 1_700_000_000);
+        assert_eq!(line["port"], 3000);
+        assert_eq!(line["pid"], 200);
+        assert_eq!(line["process_name"], "vite");
+        assert_eq!(line["previous_pid"], 100);
+        assert_eq!(line["previous_process"], "node");
+        assert!(!line.to_string().contains('\n'));
+    }
+
+    /// Verifica la interpretación de periodos de `history export --since`
+    #[test]
+    fn test_parse_age() {
+        assert_eq!(parse_age("7d"), Ok(7 * 86_400));
+        assert_eq!(parse_age("12h"), Ok(12 * 3_600));
+        assert_eq!(parse_age("45s"), Ok(45));
+        assert!(parse_age("0d").is_err());
+        assert!(parse_age("7").is_err());
+        assert!(parse_age("d").is_err());
+        assert!(parse_age("").is_err());
+    }
+
+    /// Verifica la interpretación de momentos de `history at`
+    #[test]
+    fn test_parse_moment() {
+        assert_eq!(parse_moment("1700000000"), Ok(1_700_000_000));
+        let now = history::unix_now();
+        let two_hours_ago = parse_moment("2h").unwrap();
+        assert!(now - two_hours_ago >= 7_200 && now - two_hours_ago < 7_300);
+        assert!(parse_moment("14:32").is_ok());
+        assert!(parse_moment("2026-10-14 14:32").is_ok());
+        assert!(parse_moment("25:99").is_err());
+        assert!(parse_moment("ayer").is_err());
+    }
+
+    /// Verifica que la definición de clap es consistente
+    #[test]
+    fn test_cli_definition() {
+        Cli::command().debug_assert();
+
+        let cli =
+            Cli::try_parse_from(["portslayer", "list", "--protocol", "tcp", "--json"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::List { output, export: None, .. }) if output.json
+        ));
+
+        let cli = Cli::try_parse_from(["portslayer", "list", "--output", "csv"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Command::List {
+                export: Some(FormatArg::Csv),
+                file: None,
+                ..
+            })
+        ));
+        assert!(Cli::try_parse_from(["portslayer", "list", "--file", "x.csv"]).is_err());
+        assert!(Cli::try_parse_from(["portslayer", "list", "--process-regex", "^(node"]).is_err());
+        assert!(Cli::try_parse_from(["portslayer", "reserve", "3000", "--kill"]).is_err());
+        assert!(Cli::try_parse_from(["portslayer", "reserve", "--remove"]).is_err());
+        assert!(Cli::try_parse_from(["portslayer"])
+            .unwrap()
+            .command
+            .is_none());
+    }
+}
@@ -0,0 +1,136 @@
+/// Módulo del portapapeles.
+///
+/// Las etiquetas del menú del tray no se pueden seleccionar, así que cada
+/// puerto ofrece "📋 Copiar" con tres formatos: la línea completa, el PID
+/// o el comando `kill -9 <pid>`.
+///
+/// No hay un portapapeles común a X11 y Wayland: se usa la primera
+/// herramienta disponible entre `wl-copy` (Wayland), `xclip` y `xsel`.
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use crate::capabilities;
+use crate::i18n::{tr, Text};
+use crate::port_scanner::PortInfo;
+
+/// Herramientas de portapapeles en orden de preferencia
+const TOOLS: [(&str, &[&str]); 3] = [
+    ("wl-copy", &[]),
+    ("xclip", &["-selection", "clipboard"]),
+    ("xsel", &["--clipboard", "--input"]),
+];
+
+/// Qué se copia de un puerto
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CopyFormat {
+    /// Línea completa: "TCP 8080 (0.0.0.0) → node [PID 1234]"
+    Line,
+    /// Solo el PID
+    Pid,
+    /// Comando para matarlo a mano: "kill -9 1234"
+    KillCommand,
+}
+
+impl CopyFormat {
+    /// Todos los formatos, en el orden del submenu.
+    pub const ALL: [CopyFormat; 3] = [Self::Line, Self::Pid, Self::KillCommand];
+
+    /// Texto del item del menú.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Line => tr(Text::CopyLine),
+            Self::Pid => tr(Text::CopyPid),
+            Self::KillCommand => tr(Text::CopyKillCommand),
+        }
+    }
+
+    /// Indica si el formato necesita un PID conocido.
+    pub fn needs_pid(&self) -> bool {
+        !matches!(self, Self::Line)
+    }
+
+    /// Texto a copiar para un puerto.
+    pub fn render(&self, port_info: &PortInfo) -> String {
+        match self {
+            Self::Line => port_info.to_string(),
+            Self::Pid => port_info.pid.to_string(),
+            Self::KillCommand => format!("kill -9 {}", port_info.pid),
+        }
+    }
+}
+
+/// Indica si hay alguna herramienta de portapapeles utilizable.
+pub fn available() -> bool {
+    let wayland = std::env::var_os("WAYLAND_DISPLAY").is_some();
+    TOOLS
+        .iter()
+        .any(|(tool, _)| (wayland || *tool != "wl-copy") && capabilities::in_path(tool))
+}
+
+/// Copia un texto al portapapeles del escritorio.
+///
+/// # Returns
+/// `Err(String)` si no hay ninguna herramienta de portapapeles instalada
+/// o si la que se encontró falla.
+pub fn copy(text: &str) -> Result<(), String> {
+    let wayland = std::env::var_os("WAYLAND_DISPLAY").is_some();
+    for (tool, args) in TOOLS {
+        // wl-copy solo sirve en una sesión Wayland
+        if tool == "wl-copy" && !wayland {
+            continue;
+        }
+        let child = Command::new(tool)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+        let Ok(mut child) = child else {
+            continue;
+        };
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(text.as_bytes())
+                .map_err(|e| format!("No se pudo escribir en {}: {}", tool, e))?;
+        }
+        // La herramienta queda en segundo plano sirviendo la selección;
+        // aquí solo se espera al proceso inicial.
+        let status = child.wait().map_err(|e| format!("{} falló: {}", tool, e))?;
+        return if status.success() {
+            Ok(())
+        } else {
+            Err(format!("{} terminó con {}", tool, status))
+        };
+    }
+    Err("No se encontró wl-copy, xclip ni xsel para copiar al portapapeles".into())
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verifica el texto copiado en cada formato
+    #[test]
+    fn test_render_formats() {
+        let port = PortInfo {
+            protocol: "tcp".into(),
+            port: 8080,
+            local_address: "0.0.0.0".into(),
+            pid: 1234,
+            process_name: "node".into(),
+            ..Default::default()
+        };
+        assert_eq!(
+            CopyFormat::Line.render(&port),
+            "TCP 8080 (0.0.0.0) → node [PID 1234]"
+        );
+        assert_eq!(CopyFormat::Pid.render(&port), "1234");
+        assert_eq!(CopyFormat::KillCommand.render(&port), "kill -9 1234");
+        assert!(!CopyFormat::Line.needs_pid());
+        assert!(CopyFormat::KillCommand.needs_pid());
+    }
+}
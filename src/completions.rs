@@ -0,0 +1,573 @@
+/// Módulo de autocompletado para bash, zsh y fish.
+///
+/// Los scripts se generan a partir de la definición de clap del CLI, así
+/// que cualquier subcomando u opción nueva se completa sin tocar este
+/// módulo:
+///
+/// ```text
+/// portslayer completions bash > ~/.local/share/bash-completion/completions/portslayer
+/// portslayer completions zsh > ~/.zfunc/_portslayer
+/// portslayer completions fish > ~/.config/fish/completions/portslayer.fish
+/// ```
+///
+/// El objetivo de `kill` se completa con los puertos abiertos en ese
+/// momento: el script llama al subcomando oculto `complete-ports`, que
+/// imprime una línea ":PUERTO\tPROCESO" por puerto.
+use clap::builder::{Arg, Command, ValueHint};
+use std::fmt::Write;
+
+use crate::port_scanner::PortInfo;
+
+/// Subcomandos cuyo argumento posicional se completa con puertos abiertos
+const PORT_COMMANDS: &[&str] = &["kill"];
+
+/// Subcomando oculto que consultan los scripts para completar puertos
+pub const COMPLETE_PORTS: &str = "complete-ports";
+
+/// Shells soportadas
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+/// Genera el script de autocompletado de una shell.
+///
+/// # Arguments
+/// * `shell` - Shell destino
+/// * `cli` - Definición del CLI (`Cli::command()`)
+pub fn generate(shell: Shell, cli: &Command) -> String {
+    match shell {
+        Shell::Bash => bash(cli),
+        Shell::Zsh => zsh(cli),
+        Shell::Fish => fish(cli),
+    }
+}
+
+/// Líneas de `complete-ports`: ":PUERTO\tPROCESO", sin repetir puertos.
+pub fn port_candidates(ports: &[PortInfo]) -> Vec<String> {
+    let mut ports: Vec<&PortInfo> = ports.iter().collect();
+    ports.sort_by_key(|p| p.port);
+    ports.dedup_by_key(|p| p.port);
+    ports
+        .iter()
+        .map(|p| format!(":{}\t{}", p.port, p.process_name))
+        .collect()
+}
+
+// ─────────────────────────────────────────────────────────────
+// Introspección de la definición de clap
+// ─────────────────────────────────────────────────────────────
+
+/// Un subcomando visible junto con su ruta desde la raíz
+struct Node<'a> {
+    /// Nombres desde la raíz (vacío para la raíz)
+    path: Vec<&'a str>,
+    command: &'a Command,
+}
+
+impl Node<'_> {
+    /// Clave de la ruta: "" para la raíz, "history:export" para anidados.
+    fn key(&self) -> String {
+        self.path.join(":")
+    }
+
+    /// Si su argumento posicional se completa con puertos abiertos.
+    fn completes_ports(&self) -> bool {
+        self.path.len() == 1 && PORT_COMMANDS.contains(&self.path[0])
+    }
+}
+
+/// Recorre la raíz y todos sus subcomandos visibles en profundidad.
+fn nodes(cli: &Command) -> Vec<Node<'_>> {
+    let mut nodes = vec![Node {
+        path: Vec::new(),
+        command: cli,
+    }];
+    let mut index = 0;
+    while index < nodes.len() {
+        let children: Vec<Node> = subcommands(nodes[index].command)
+            .map(|child| {
+                let mut path = nodes[index].path.clone();
+                path.push(child.get_name());
+                Node {
+                    path,
+                    command: child,
+                }
+            })
+            .collect();
+        nodes.extend(children);
+        index += 1;
+    }
+    nodes
+}
+
+/// Subcomandos que no están ocultos.
+fn subcommands(command: &Command) -> impl Iterator<Item = &Command> {
+    command.get_subcommands().filter(|c| !c.is_hide_set())
+}
+
+/// Opciones largas visibles, más `--help` (y `--version` en la raíz),
+/// que clap solo añade al construir el comando.
+fn options(command: &Command) -> Vec<Opt> {
+    let mut options: Vec<Opt> = command
+        .get_arguments()
+        .filter(|a| !a.is_positional() && !a.is_hide_set())
+        .filter_map(|a| {
+            Some(Opt {
+                long: a.get_long()?.to_string(),
+                help: help(a.get_help().map(|h| h.to_string())),
+                value: a.get_action().takes_values().then(|| value_kind(a)),
+            })
+        })
+        .collect();
+    options.push(Opt {
+        long: "help".into(),
+        help: "Muestra la ayuda".into(),
+        value: None,
+    });
+    if command.get_version().is_some() {
+        options.push(Opt {
+            long: "version".into(),
+            help: "Muestra la versión".into(),
+            value: None,
+        });
+    }
+    options
+}
+
+/// Argumentos posicionales visibles.
+fn positionals(command: &Command) -> impl Iterator<Item = &Arg> {
+    command
+        .get_arguments()
+        .filter(|a| a.is_positional() && !a.is_hide_set())
+}
+
+/// Una opción larga
+struct Opt {
+    long: String,
+    help: String,
+    /// Qué se completa como valor (`None` si es un flag)
+    value: Option<ValueKind>,
+}
+
+/// Cómo se completa un valor
+#[derive(Debug, PartialEq)]
+enum ValueKind {
+    /// Uno de una lista cerrada (`--protocol tcp|udp|all`)
+    Choices(Vec<String>),
+    /// Una ruta
+    Path,
+    /// Texto libre
+    Any,
+}
+
+/// Cómo se completa el valor de un argumento.
+fn value_kind(arg: &Arg) -> ValueKind {
+    let choices: Vec<String> = arg
+        .get_possible_values()
+        .iter()
+        .filter(|v| !v.is_hide_set())
+        .map(|v| v.get_name().to_string())
+        .collect();
+    if !choices.is_empty() {
+        return ValueKind::Choices(choices);
+    }
+    match arg.get_value_hint() {
+        ValueHint::AnyPath | ValueHint::FilePath | ValueHint::DirPath => ValueKind::Path,
+        _ => ValueKind::Any,
+    }
+}
+
+/// Primera línea de la ayuda de un argumento o subcomando.
+fn help(text: Option<String>) -> String {
+    text.unwrap_or_default()
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Ayuda de un subcomando.
+fn about(command: &Command) -> String {
+    help(command.get_about().map(|a| a.to_string()))
+}
+
+// ─────────────────────────────────────────────────────────────
+// bash
+// ─────────────────────────────────────────────────────────────
+
+fn bash(cli: &Command) -> String {
+    let name = cli.get_name();
+    let nodes = nodes(cli);
+    let mut script = format!(
+        "# Autocompletado de {name} para bash\n\
+         _{name}() {{\n    \
+             # La palabra actual sale de COMP_LINE: COMP_WORDS parte \":3000\" en dos\n    \
+             local line=\"${{COMP_LINE:0:$COMP_POINT}}\"\n    \
+             local cur=\"${{line##*[[:space:]]}}\"\n    \
+             local -a words\n    \
+             read -ra words <<< \"$line\"\n    \
+             [[ -n $cur ]] && unset 'words[${{#words[@]}}-1]'\n    \
+             local prev=\"${{words[${{#words[@]}}-1]}}\"\n\n    \
+             local path=\"\" word\n    \
+             for word in \"${{words[@]:1}}\"; do\n        \
+                 case \"$path:$word\" in\n"
+    );
+    let children: Vec<String> = nodes
+        .iter()
+        .skip(1)
+        .map(|node| {
+            let (parent, name) = node.path.split_at(node.path.len() - 1);
+            format!("\"{}:{}\"", parent.join(":"), name[0])
+        })
+        .collect();
+    if !children.is_empty() {
+        let _ = writeln!(
+            script,
+            "            {}) path=\"${{path:+$path:}}$word\" ;;",
+            children.join("|")
+        );
+    }
+    script.push_str("        esac\n    done\n\n    case \"$path/$prev\" in\n");
+    for node in &nodes {
+        for option in options(node.command) {
+            let Some(value) = &option.value else {
+                continue;
+            };
+            let reply = match value {
+                ValueKind::Choices(choices) => {
+                    format!(
+                        "COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))",
+                        choices.join(" ")
+                    )
+                }
+                ValueKind::Path => "COMPREPLY=($(compgen -f -- \"$cur\"))".to_string(),
+                ValueKind::Any => "COMPREPLY=()".to_string(),
+            };
+            let _ = writeln!(
+                script,
+                "        \"{}/--{}\") {}; return ;;",
+                node.key(),
+                option.long,
+                reply
+            );
+        }
+    }
+    script.push_str("    esac\n\n    local opts=\"\" files=\"\"\n    case \"$path\" in\n");
+    for node in &nodes {
+        let mut words: Vec<String> = subcommands(node.command)
+            .map(|c| c.get_name().to_string())
+            .collect();
+        let mut files = false;
+        for arg in positionals(node.command) {
+            match value_kind(arg) {
+                ValueKind::Choices(choices) => words.extend(choices),
+                ValueKind::Path => files = true,
+                ValueKind::Any => {}
+            }
+        }
+        words.extend(
+            options(node.command)
+                .iter()
+                .map(|o| format!("--{}", o.long)),
+        );
+        let _ = write!(
+            script,
+            "        \"{}\")\n            opts=\"{}\"",
+            node.key(),
+            words.join(" ")
+        );
+        if node.completes_ports() {
+            let _ = write!(
+                script,
+                "\n            [[ $cur != -* ]] && opts=\"$opts $({name} {COMPLETE_PORTS} 2>/dev/null | cut -f1)\""
+            );
+        }
+        if files {
+            script.push_str("\n            files=1");
+        }
+        script.push_str(" ;;\n");
+    }
+    let _ = write!(
+        script,
+        "    esac\n\n    \
+             COMPREPLY=($(compgen -W \"$opts\" -- \"$cur\"))\n    \
+             [[ -n $files && $cur != -* ]] && COMPREPLY+=($(compgen -f -- \"$cur\"))\n    \
+             # bash ya escribió lo que precede al último ':' de la palabra\n    \
+             if [[ $cur == *:* && $COMP_WORDBREAKS == *:* ]]; then\n        \
+                 local colon=\"${{cur%\"${{cur##*:}}\"}}\"\n        \
+                 COMPREPLY=(\"${{COMPREPLY[@]#\"$colon\"}}\")\n    \
+             fi\n\
+         }}\n\
+         complete -F _{name} {name}\n"
+    );
+    script
+}
+
+// ─────────────────────────────────────────────────────────────
+// zsh
+// ─────────────────────────────────────────────────────────────
+
+fn zsh(cli: &Command) -> String {
+    let name = cli.get_name();
+    let mut script = format!("#compdef {name}\n\n# Autocompletado de {name} para zsh\n");
+    for node in nodes(cli) {
+        let function = zsh_function(name, &node.path);
+        let mut specs: Vec<String> = options(node.command)
+            .iter()
+            .map(|option| {
+                let help = zsh_escape(&option.help);
+                match &option.value {
+                    None => format!("'--{}[{}]'", option.long, help),
+                    Some(value) => format!(
+                        "'--{}=[{}]:{}:{}'",
+                        option.long,
+                        help,
+                        option.long,
+                        zsh_action(value)
+                    ),
+                }
+            })
+            .collect();
+        let has_subcommands = subcommands(node.command).next().is_some();
+        if has_subcommands {
+            specs.push(format!("'1: :{}_commands'", function));
+            specs.push("'*::arg:->args'".to_string());
+        } else {
+            for (index, arg) in positionals(node.command).enumerate() {
+                let label = arg.get_id().as_str();
+                let action = if node.completes_ports() {
+                    format!("_{}_ports", name)
+                } else {
+                    zsh_action(&value_kind(arg))
+                };
+                let position = if arg.get_num_args().is_some_and(|n| n.max_values() > 1) {
+                    "*".to_string()
+                } else {
+                    (index + 1).to_string()
+                };
+                specs.push(format!("'{}:{}:{}'", position, label, action));
+            }
+        }
+
+        let _ = write!(script, "\n{}() {{\n", function);
+        if has_subcommands {
+            script.push_str("    local state line\n    _arguments -C \\\n        ");
+        } else {
+            script.push_str("    _arguments \\\n        ");
+        }
+        script.push_str(&specs.join(" \\\n        "));
+        if has_subcommands {
+            script.push_str("\n    case $state in\n        args)\n            case $words[1] in\n");
+            for child in subcommands(node.command) {
+                let mut path = node.path.clone();
+                path.push(child.get_name());
+                let _ = writeln!(
+                    script,
+                    "                {}) {} ;;",
+                    child.get_name(),
+                    zsh_function(name, &path)
+                );
+            }
+            script.push_str("            esac\n            ;;\n    esac");
+        }
+        script.push_str("\n}\n");
+
+        if has_subcommands {
+            let entries: Vec<String> = subcommands(node.command)
+                .map(|c| format!("'{}:{}'", c.get_name(), zsh_escape(&about(c))))
+                .collect();
+            let _ = write!(
+                script,
+                "\n{}_commands() {{\n    local -a commands\n    commands=(\n        {}\n    )\n    _describe 'command' commands\n}}\n",
+                function,
+                entries.join("\n        ")
+            );
+        }
+    }
+    let _ = write!(
+        script,
+        "\n_{name}_ports() {{\n    \
+             local -a ports\n    \
+             ports=(${{(f)\"$({name} {COMPLETE_PORTS} 2>/dev/null | sed 's/:/\\\\:/; s/\\t/:/')\"}})\n    \
+             _describe 'puerto' ports\n\
+         }}\n\n\
+         _{name} \"$@\"\n"
+    );
+    script
+}
+
+/// Nombre de la función de zsh de un subcomando: `_portslayer_history_export`.
+fn zsh_function(name: &str, path: &[&str]) -> String {
+    std::iter::once(name)
+        .chain(path.iter().copied())
+        .map(|part| part.replace('-', "_"))
+        .fold(String::new(), |function, part| function + "_" + &part)
+}
+
+/// Acción de `_arguments` para completar un valor.
+fn zsh_action(value: &ValueKind) -> String {
+    match value {
+        ValueKind::Choices(choices) => format!("({})", choices.join(" ")),
+        ValueKind::Path => "_files".to_string(),
+        ValueKind::Any => " ".to_string(),
+    }
+}
+
+/// Escapa un texto para ir entre comillas simples en una especificación
+/// de `_arguments` o `_describe`.
+fn zsh_escape(text: &str) -> String {
+    text.replace('\'', "'\\''")
+        .replace('[', "\\[")
+        .replace(']', "\\]")
+        .replace(':', "\\:")
+}
+
+// ─────────────────────────────────────────────────────────────
+// fish
+// ─────────────────────────────────────────────────────────────
+
+fn fish(cli: &Command) -> String {
+    let name = cli.get_name();
+    let mut script = format!("# Autocompletado de {name} para fish\ncomplete -c {name} -f\n");
+    for node in nodes(cli) {
+        // Condición: estar en este subcomando y no en uno de sus hijos
+        let mut conditions: Vec<String> = node
+            .path
+            .iter()
+            .map(|part| format!("__fish_seen_subcommand_from {}", part))
+            .collect();
+        let children: Vec<&str> = subcommands(node.command).map(|c| c.get_name()).collect();
+        if node.path.is_empty() {
+            conditions.push("__fish_use_subcommand".to_string());
+        } else if !children.is_empty() {
+            conditions.push(format!(
+                "not __fish_seen_subcommand_from {}",
+                children.join(" ")
+            ));
+        }
+        let condition = conditions.join("; and ");
+
+        for child in subcommands(node.command) {
+            let _ = writeln!(
+                script,
+                "complete -c {} -n '{}' -a {} -d '{}'",
+                name,
+                condition,
+                child.get_name(),
+                fish_escape(&about(child))
+            );
+        }
+        for option in options(node.command) {
+            let value = match &option.value {
+                None => String::new(),
+                Some(ValueKind::Choices(choices)) => {
+                    format!(" -r -a '{}'", choices.join(" "))
+                }
+                Some(ValueKind::Path) => " -r -F".to_string(),
+                Some(ValueKind::Any) => " -r".to_string(),
+            };
+            let _ = writeln!(
+                script,
+                "complete -c {} -n '{}' -l {}{} -d '{}'",
+                name,
+                condition,
+                option.long,
+                value,
+                fish_escape(&option.help)
+            );
+        }
+        for arg in positionals(node.command) {
+            let candidates = if node.completes_ports() {
+                format!(" -a '({} {})'", name, COMPLETE_PORTS)
+            } else {
+                match value_kind(arg) {
+                    ValueKind::Choices(choices) => format!(" -a '{}'", choices.join(" ")),
+                    ValueKind::Path => " -F".to_string(),
+                    ValueKind::Any => continue,
+                }
+            };
+            let _ = writeln!(
+                script,
+                "complete -c {} -n '{}'{}",
+                name, condition, candidates
+            );
+        }
+    }
+    script
+}
+
+/// Escapa un texto para ir entre comillas simples en fish.
+fn fish_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('\'', "\\'")
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cli() -> Command {
+        Command::new("portslayer")
+            .version("1.0")
+            .subcommand(
+                Command::new("kill")
+                    .about("Termina un proceso")
+                    .arg(Arg::new("target").required(true))
+                    .arg(
+                        Arg::new("protocol")
+                            .long("protocol")
+                            .help("Protocolo [tcp|udp]")
+                            .value_parser(["tcp", "udp"]),
+                    ),
+            )
+            .subcommand(
+                Command::new("history")
+                    .about("Historial")
+                    .subcommand(Command::new("compact").about("Compacta el historial")),
+            )
+            .subcommand(Command::new(COMPLETE_PORTS).hide(true))
+    }
+
+    /// Verifica que los tres scripts incluyen subcomandos, opciones y
+    /// la consulta de puertos de `kill`, y omiten los ocultos
+    #[test]
+    fn test_generate_scripts() {
+        let bash = generate(Shell::Bash, &cli());
+        assert!(bash.contains("\":kill\"|\":history\"|\"history:compact\")"));
+        assert!(bash.contains(
+            "\"kill/--protocol\") COMPREPLY=($(compgen -W \"tcp udp\" -- \"$cur\")); return ;;"
+        ));
+        assert!(bash.contains("opts=\"kill history --help --version\""));
+        assert!(bash.contains("$(portslayer complete-ports 2>/dev/null | cut -f1)"));
+        assert!(bash.ends_with("complete -F _portslayer portslayer\n"));
+
+        let zsh = generate(Shell::Zsh, &cli());
+        assert!(zsh.starts_with("#compdef portslayer\n"));
+        assert!(zsh.contains("'--protocol=[Protocolo \\[tcp|udp\\]]:protocol:(tcp udp)'"));
+        assert!(zsh.contains("'1:target:_portslayer_ports'"));
+        assert!(zsh.contains("_portslayer_history_commands() {"));
+        assert!(zsh.contains("'compact:Compacta el historial'"));
+
+        let fish = generate(Shell::Fish, &cli());
+        assert!(fish.contains(
+            "complete -c portslayer -n '__fish_use_subcommand' -a kill -d 'Termina un proceso'"
+        ));
+        assert!(fish.contains(
+            "complete -c portslayer -n '__fish_seen_subcommand_from kill' -a '(portslayer complete-ports)'"
+        ));
+        assert!(fish.contains(
+            "-n '__fish_seen_subcommand_from history; and not __fish_seen_subcommand_from compact' -a compact"
+        ));
+
+        for script in [bash, zsh, fish] {
+            assert!(!script.contains("-a complete-ports"));
+            assert!(!script.contains(":complete-ports\""));
+        }
+    }
+}
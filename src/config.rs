@@ -0,0 +1,274 @@
+/// Módulo de configuración de PortSlayer.
+///
+/// Lee el archivo `~/.config/portslayer/config.toml`. Todas las
+/// secciones son opcionales: si el archivo no existe o una clave
+/// falta, se usan los valores por defecto.
+///
+/// Ejemplo:
+/// ```toml
+/// scan_interval_secs = 5       # 0 = solo manual en el tray
+/// language = "en"              # "es" | "en" (por defecto, el del locale)
+/// custom_range = "8000-8999"   # filtro "Personalizado" del tray
+/// scan_namespaces = true       # puertos internos de los contenedores
+/// scan_windows = false         # WSL2: puertos de Windows (por defecto, sí)
+/// icon_theme = "colorblind"    # ver `theme.rs`
+/// traffic_stats = true         # ver `traffic.rs`
+/// service_fingerprints = true  # ver `fingerprint.rs`
+/// http_health = true           # ver `health.rs`
+/// privileged_helper = true     # ver `portslayer_core::helper`
+///
+/// [[maintenance]]
+/// name = "backups nocturnos"
+/// schedule = "0 2 * * *"      # minuto hora día mes día_semana
+/// duration_minutes = 90
+///
+/// [[rules]]                    # ver `rules.rs`
+/// name = "privilegiados"
+/// ports = "1-1024"
+/// process_not_in = ["sshd"]
+/// action = "notify"
+///
+/// [[watch]]                    # ver `watchlist.rs`
+/// port = 5432
+/// on = ["close", "owner"]
+///
+/// [workspaces.work]            # ver `workspace.rs`
+/// favorites = [3000, 5432]
+/// ignore_ports = [22]
+///
+/// [profiles]                   # `portslayer kill --profile frontend`
+/// frontend = [3000, 5173]
+/// backend = [8080, 5432, 6379]
+///
+/// [[webhooks]]                 # ver `webhooks.rs`
+/// url = "https://ntfy.example.com/portslayer"
+///
+/// [email]                      # ver `email.rs` (feature `email`)
+/// smtp_host = "smtp.example.com"
+/// from = "alertas@example.com"
+/// to = ["ops@example.com"]
+/// digest_minutes = 30
+///
+/// [api]                        # ver `auth.rs`
+/// auth = "peer"
+///
+/// [cpu_alerts]                 # ver `cpu.rs`
+/// threshold_percent = 90
+/// minutes = 5
+///
+/// [history]                    # ver `history.rs`
+/// max_size_kb = 5120
+/// retention_days = 90
+///
+/// [log]                        # ver `logging.rs`
+/// level = "debug"
+/// max_size_kb = 1024
+/// format = "json"
+/// ```
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+#[cfg(feature = "api")]
+use crate::auth::ApiConfig;
+use crate::cpu::CpuAlertConfig;
+#[cfg(feature = "email")]
+use crate::email::EmailConfig;
+use crate::history::HistoryConfig;
+use crate::i18n::{self, Lang};
+use crate::logging::LogConfig;
+use crate::maintenance::MaintenanceWindow;
+use crate::paths;
+use crate::port_scanner;
+use crate::rules::{self, Rule};
+use crate::theme::{self, IconTheme};
+use crate::watchlist::WatchEntry;
+#[cfg(feature = "api")]
+use crate::webhooks::Webhook;
+use crate::workspace::{self, Workspace};
+
+/// Nombre del archivo de configuración
+const CONFIG_FILE: &str = "config.toml";
+
+/// Intervalo entre escaneos automáticos si no se configura otro
+pub const DEFAULT_SCAN_INTERVAL_SECS: u64 = 10;
+
+/// Configuración completa de la aplicación
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Segundos entre escaneos automáticos (tray, daemon y `watch`);
+    /// 0 desactiva la actualización automática del tray
+    pub scan_interval_secs: Option<u64>,
+    /// Rango del filtro "Personalizado" del tray (ej: "8000-8999")
+    pub custom_range: Option<String>,
+    /// Idioma de la interfaz ("es" o "en"); por defecto, el del locale
+    pub language: Option<String>,
+    /// Escanear también los namespaces de red de los contenedores (ver
+    /// `port_scanner::set_namespace_scan`)
+    pub scan_namespaces: bool,
+    /// Escanear también los puertos del Windows anfitrión (ver
+    /// `portslayer_core::wsl`); por defecto, solo dentro de WSL
+    pub scan_windows: Option<bool>,
+    /// Juego de íconos y marcas del tray (ver `theme`)
+    pub icon_theme: IconTheme,
+    /// Mostrar el tráfico de cada puerto en el tray (ver `traffic`)
+    pub traffic_stats: bool,
+    /// Identificar el servicio de cada puerto TCP por su banner (ver
+    /// `fingerprint`)
+    pub service_fingerprints: bool,
+    /// Comprobar el estado HTTP de los servidores web locales (ver
+    /// `health`)
+    pub http_health: bool,
+    /// Lanzar el helper privilegiado al iniciar el tray (una contraseña
+    /// para toda la sesión, ver `port_scanner::start_helper`)
+    pub privileged_helper: bool,
+    /// Ventanas de mantenimiento durante las que se suprimen
+    /// reglas automáticas y alertas
+    pub maintenance: Vec<MaintenanceWindow>,
+    /// Reglas de acciones automáticas evaluadas tras cada escaneo
+    pub rules: Vec<Rule>,
+    /// Puertos vigilados (además de los de `watchlist.json`, ver
+    /// `watchlist`)
+    pub watch: Vec<WatchEntry>,
+    /// Espacios de trabajo por nombre
+    pub workspaces: BTreeMap<String, Workspace>,
+    /// Perfiles de kill por nombre: puertos que se cierran juntos
+    pub profiles: BTreeMap<String, Vec<u16>>,
+    /// Autenticación de la API del daemon
+    #[cfg(feature = "api")]
+    pub api: ApiConfig,
+    /// URLs a las que el daemon envía los eventos (ver `webhooks`)
+    #[cfg(feature = "api")]
+    pub webhooks: Vec<Webhook>,
+    /// Alertas por correo del daemon (ver `email`)
+    #[cfg(feature = "email")]
+    pub email: Option<EmailConfig>,
+    /// Límites del archivo de historial
+    pub history: HistoryConfig,
+    /// Nivel y rotación del archivo de log
+    pub log: LogConfig,
+    /// Avisos de procesos con puertos que consumen mucha CPU
+    pub cpu_alerts: CpuAlertConfig,
+}
+
+impl Config {
+    /// Ruta del archivo de configuración.
+    pub fn path() -> PathBuf {
+        paths::config_dir().join(CONFIG_FILE)
+    }
+
+    /// Carga la configuración desde disco.
+    ///
+    /// Un archivo inexistente equivale a la configuración por defecto;
+    /// un archivo inválido se registra como error y también se ignora.
+    /// Aplica además el idioma configurado (ver `i18n`), el escaneo de
+    /// namespaces de red y de Windows y el tema de íconos.
+    pub fn load() -> Self {
+        let path = Self::path();
+        let config = match fs::read_to_string(&path) {
+            Ok(content) => Self::parse(&content).unwrap_or_else(|e| {
+                log::error!("Configuración inválida en {}: {}", path.display(), e);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        };
+        i18n::set_language(config.language());
+        port_scanner::set_namespace_scan(config.scan_namespaces);
+        port_scanner::set_windows_scan(config.scan_windows.unwrap_or_else(port_scanner::is_wsl));
+        theme::set_theme(config.icon_theme);
+        config
+    }
+
+    /// Parsea y valida la configuración desde texto TOML.
+    ///
+    /// # Returns
+    /// `Err(String)` con la descripción del primer problema encontrado.
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let config: Config = toml::from_str(content).map_err(|e| e.to_string())?;
+        if let Some(code) = &config.language {
+            if Lang::parse(code).is_none() {
+                return Err(format!(
+                    "Idioma desconocido: {} (usa \"es\" o \"en\")",
+                    code
+                ));
+            }
+        }
+        for window in &config.maintenance {
+            window.validate()?;
+        }
+        config.cpu_alerts.validate()?;
+        config.log.validate()?;
+        if config.watch.iter().any(|entry| entry.port == 0) {
+            return Err("Puerto vigilado inválido: 0".to_string());
+        }
+        for (name, ports) in &config.profiles {
+            if ports.is_empty() || ports.contains(&0) {
+                return Err(format!(
+                    "Perfil {} inválido: necesita puertos entre 1 y 65535",
+                    name
+                ));
+            }
+        }
+        #[cfg(feature = "api")]
+        for webhook in &config.webhooks {
+            webhook.validate()?;
+        }
+        #[cfg(feature = "email")]
+        if let Some(email) = &config.email {
+            email.validate()?;
+        }
+        let workspace_rules = config.workspaces.values().flat_map(|ws| ws.rules.iter());
+        for rule in config.rules.iter().chain(workspace_rules) {
+            rule.validate()?;
+        }
+        Ok(config)
+    }
+
+    /// Idioma de la interfaz: el configurado, el del locale o español.
+    pub fn language(&self) -> Lang {
+        self.language
+            .as_deref()
+            .and_then(Lang::parse)
+            .or_else(Lang::from_env)
+            .unwrap_or(Lang::Es)
+    }
+
+    /// Intervalo de escaneo configurado (por defecto 10 s; 0 = manual).
+    pub fn scan_interval(&self) -> u64 {
+        self.scan_interval_secs
+            .unwrap_or(DEFAULT_SCAN_INTERVAL_SECS)
+    }
+
+    /// Rango personalizado del filtro del tray, si está definido y es válido.
+    pub fn custom_range(&self) -> Option<(u16, u16)> {
+        let text = self.custom_range.as_deref()?;
+        match rules::parse_range(text) {
+            Ok(range) => Some(range),
+            Err(e) => {
+                log::error!("custom_range inválido en {}: {}", Self::path().display(), e);
+                None
+            }
+        }
+    }
+
+    /// Espacio de trabajo activo y su nombre.
+    ///
+    /// # Returns
+    /// `None` si no hay ninguno activo o si el nombre guardado ya no
+    /// existe en la configuración.
+    pub fn active_workspace(&self) -> Option<(String, &Workspace)> {
+        let name = workspace::active_name()?;
+        match self.workspaces.get(&name) {
+            Some(ws) => Some((name, ws)),
+            None => {
+                log::debug!(
+                    "El espacio de trabajo '{}' no está en la configuración",
+                    name
+                );
+                None
+            }
+        }
+    }
+}
@@ -0,0 +1,473 @@
+/// Módulo de conexiones TCP establecidas.
+///
+/// Complementa el escaneo de puertos en escucha con la vista de
+/// conexiones activas (`ss -tanpH`, estado ESTAB). Permite estimar el
+/// impacto de matar un servicio: cuántas conexiones se cortarían y
+/// desde qué pares (aplicaciones locales o IPs remotas), y qué
+/// servicios locales dependen de él (proceso A conectado al puerto
+/// en escucha del proceso B).
+///
+/// También corta conexiones sueltas sin matar al servidor (un cliente
+/// colgado, por ejemplo) con `ss -K`, que destruye el socket por su
+/// cuádrupla de direcciones y puertos. Hace falta root y un kernel con
+/// `CONFIG_INET_DIAG_DESTROY`; como `ss` no avisa si el kernel se niega,
+/// se comprueba después que la conexión ya no está.
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+
+use crate::firewall;
+use crate::i18n::{tr, trf, Text};
+use crate::port_scanner::{self, PortInfo};
+use crate::replay;
+
+/// Máximo de direcciones remotas listadas en el resumen de impacto
+const MAX_REMOTE_IN_SUMMARY: usize = 3;
+
+/// Conexión TCP establecida entre un socket local y un par
+#[derive(Debug, Clone, PartialEq, Hash, Serialize)]
+pub struct Connection {
+    /// Dirección local del socket
+    pub local_address: String,
+    /// Puerto local del socket
+    pub local_port: u16,
+    /// Dirección del otro extremo
+    pub peer_address: String,
+    /// Puerto del otro extremo
+    pub peer_port: u16,
+    /// PID dueño del socket local (0 si no se pudo determinar)
+    pub pid: u32,
+    /// Nombre del proceso dueño ("desconocido" si no se pudo determinar)
+    pub process_name: String,
+}
+
+impl Connection {
+    /// Indica si el par está en esta misma máquina.
+    ///
+    /// Se considera local si es una dirección de loopback o si coincide
+    /// con la dirección local del socket (conexión a la IP propia).
+    pub fn is_local_peer(&self) -> bool {
+        self.peer_address == self.local_address || is_loopback(&self.peer_address)
+    }
+
+    /// Par como "10.0.0.7:40000" o "[::1]:40000".
+    pub fn peer(&self) -> String {
+        join_socket(&self.peer_address, self.peer_port)
+    }
+
+    /// Indica si es del par indicado: "10.0.0.7" (cualquier puerto) o
+    /// "10.0.0.7:40000" ("[::1]:40000" en IPv6).
+    pub fn matches_peer(&self, peer: &str) -> bool {
+        let address = peer.trim_start_matches('[').trim_end_matches(']');
+        address == self.peer_address || peer == self.peer()
+    }
+
+    /// Indica si es el mismo socket (misma cuádrupla) que otra conexión.
+    fn same_socket(&self, other: &Connection) -> bool {
+        self.local_address == other.local_address
+            && self.local_port == other.local_port
+            && self.peer_address == other.peer_address
+            && self.peer_port == other.peer_port
+    }
+}
+
+/// Conexiones que se agrupan bajo la misma dirección de par
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PeerCount {
+    /// Dirección del par
+    pub address: String,
+    /// Conexiones establecidas desde ese par
+    pub connections: usize,
+}
+
+/// Proceso local que tiene conexiones abiertas hacia un servicio
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Dependent {
+    /// PID del proceso cliente (0 si no se pudo determinar)
+    pub pid: u32,
+    /// Nombre del proceso cliente
+    pub process_name: String,
+    /// Conexiones que mantiene con el servicio
+    pub connections: usize,
+}
+
+/// Impacto estimado de matar el proceso de un puerto
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct KillImpact {
+    /// Total de conexiones establecidas que se cortarían
+    pub established: usize,
+    /// Pares en esta máquina (aplicaciones locales)
+    pub local_peers: Vec<PeerCount>,
+    /// Pares remotos por IP
+    pub remote_peers: Vec<PeerCount>,
+    /// Procesos locales que dependen del servicio
+    pub dependents: Vec<Dependent>,
+}
+
+impl KillImpact {
+    /// Resumen legible del impacto.
+    ///
+    /// Ejemplo: "3 conexiones activas: 2 locales, 1 remotas (10.0.0.5)"
+    pub fn summary(&self) -> String {
+        if self.established == 0 {
+            return tr(Text::NoActiveConnections).to_string();
+        }
+
+        let local: usize = self.local_peers.iter().map(|p| p.connections).sum();
+        let remote: usize = self.remote_peers.iter().map(|p| p.connections).sum();
+        let mut text = trf(
+            Text::ConnectionsSummary,
+            &[&self.established, &local, &remote],
+        );
+
+        if !self.remote_peers.is_empty() {
+            let mut addresses: Vec<&str> = self
+                .remote_peers
+                .iter()
+                .take(MAX_REMOTE_IN_SUMMARY)
+                .map(|p| p.address.as_str())
+                .collect();
+            if self.remote_peers.len() > MAX_REMOTE_IN_SUMMARY {
+                addresses.push("…");
+            }
+            text.push_str(&format!(" ({})", addresses.join(", ")));
+        }
+        text
+    }
+
+    /// Nota de dependencias para el flujo de confirmación.
+    ///
+    /// Ejemplo: "Matar postgres romperá node (3 conexiones activas)"
+    ///
+    /// # Arguments
+    /// * `service` - Nombre del proceso que se va a matar
+    ///
+    /// # Returns
+    /// `None` si ningún proceso local identificado depende del servicio.
+    pub fn dependency_note(&self, service: &str) -> Option<String> {
+        let known: Vec<String> = self
+            .dependents
+            .iter()
+            .filter(|d| d.pid > 0)
+            .map(|d| {
+                let count = if d.connections == 1 {
+                    Text::ActiveConnection
+                } else {
+                    Text::ActiveConnections
+                };
+                format!("{} ({})", d.process_name, trf(count, &[&d.connections]))
+            })
+            .collect();
+        if known.is_empty() {
+            return None;
+        }
+        Some(trf(Text::DependencyNote, &[&service, &known.join(", ")]))
+    }
+}
+
+/// Escanea las conexiones TCP establecidas del sistema.
+///
+/// # Returns
+/// Vector con las conexiones en estado ESTAB (vacío si `ss` falla).
+pub fn scan_established() -> Vec<Connection> {
+    // La sesión reproducida no grabó conexiones
+    if replay::is_active() {
+        return Vec::new();
+    }
+    match port_scanner::execute_ss_command("-tanpH") {
+        Some(output) => parse_ss_connections(&output),
+        None => Vec::new(),
+    }
+}
+
+/// Calcula qué conexiones se cortarían al matar el dueño de un puerto.
+///
+/// Solo cuentan las conexiones cuyo extremo local es el puerto en
+/// escucha (clientes conectados al servicio), agrupadas por par y
+/// ordenadas de mayor a menor número de conexiones.
+///
+/// # Arguments
+/// * `port` - Puerto en escucha que se quiere cerrar
+/// * `connections` - Conexiones establecidas (ver [`scan_established`])
+pub fn kill_impact(port: &PortInfo, connections: &[Connection]) -> KillImpact {
+    if port.protocol != "tcp" {
+        return KillImpact::default();
+    }
+
+    let mut local: BTreeMap<String, usize> = BTreeMap::new();
+    let mut remote: BTreeMap<String, usize> = BTreeMap::new();
+    let mut established = 0;
+
+    for conn in clients_of(port, connections) {
+        established += 1;
+        let group = if conn.is_local_peer() {
+            &mut local
+        } else {
+            &mut remote
+        };
+        *group.entry(conn.peer_address.clone()).or_insert(0) += 1;
+    }
+
+    KillImpact {
+        established,
+        local_peers: sorted_peers(local),
+        remote_peers: sorted_peers(remote),
+        dependents: local_dependents(port, connections),
+    }
+}
+
+/// Conexiones de los clientes del puerto en escucha de un servicio (su
+/// extremo local es el puerto).
+pub fn clients_of<'a>(port: &PortInfo, connections: &'a [Connection]) -> Vec<&'a Connection> {
+    if port.protocol != "tcp" {
+        return Vec::new();
+    }
+    connections
+        .iter()
+        .filter(|c| c.local_port == port.port && (port.pid == 0 || c.pid == 0 || c.pid == port.pid))
+        .collect()
+}
+
+/// Corta una conexión establecida con `ss -K` sin tocar el proceso.
+///
+/// # Returns
+/// `Err(String)` si `ss` falla o la conexión sigue abierta después.
+pub fn drop_connection(conn: &Connection) -> Result<(), String> {
+    let filter = format!(
+        "src {} sport = :{} dst {} dport = :{}",
+        filter_host(&conn.local_address),
+        conn.local_port,
+        filter_host(&conn.peer_address),
+        conn.peer_port
+    );
+    firewall::run_as_root("ss", &["-K", &filter], None)
+        .map_err(|e| format!("ss -K falló: {}", e))?;
+    if scan_established().iter().any(|c| c.same_socket(conn)) {
+        return Err(trf(Text::ConnectionNotDropped, &[&conn.peer()]));
+    }
+    log::info!(
+        "Conexión {} → {}:{} cortada con ss -K",
+        conn.peer(),
+        conn.local_address,
+        conn.local_port
+    );
+    Ok(())
+}
+
+/// Dirección para un filtro de `ss`: IPv6 entre corchetes y sin zona.
+fn filter_host(address: &str) -> String {
+    let host = address.split('%').next().unwrap_or(address);
+    if host.contains(':') {
+        format!("[{}]", host)
+    } else {
+        host.to_string()
+    }
+}
+
+/// Une dirección y puerto ("[::1]:80" en IPv6).
+fn join_socket(address: &str, port: u16) -> String {
+    if address.contains(':') {
+        format!("[{}]:{}", address, port)
+    } else {
+        format!("{}:{}", address, port)
+    }
+}
+
+/// Busca los procesos locales conectados al puerto en escucha.
+///
+/// Un proceso depende del servicio si tiene una conexión cuyo extremo
+/// remoto es el puerto del servicio y cuyo extremo local es el par de
+/// una de las conexiones del servicio (el otro lado del mismo socket).
+/// Las conexiones del servicio consigo mismo no cuentan.
+fn local_dependents(port: &PortInfo, connections: &[Connection]) -> Vec<Dependent> {
+    let mut counts: BTreeMap<(u32, String), usize> = BTreeMap::new();
+
+    for server_side in connections.iter().filter(|c| c.local_port == port.port) {
+        let client_side = connections.iter().find(|c| {
+            c.local_port == server_side.peer_port
+                && c.peer_port == server_side.local_port
+                && c.local_address == server_side.peer_address
+                && c.peer_address == server_side.local_address
+        });
+        if let Some(client) = client_side {
+            if port.pid > 0 && client.pid == port.pid {
+                continue;
+            }
+            *counts
+                .entry((client.pid, client.process_name.clone()))
+                .or_insert(0) += 1;
+        }
+    }
+
+    let mut dependents: Vec<Dependent> = counts
+        .into_iter()
+        .map(|((pid, process_name), connections)| Dependent {
+            pid,
+            process_name,
+            connections,
+        })
+        .collect();
+    dependents.sort_by_key(|d| std::cmp::Reverse(d.connections));
+    dependents
+}
+
+/// Convierte el conteo por dirección en una lista ordenada por conexiones.
+fn sorted_peers(counts: BTreeMap<String, usize>) -> Vec<PeerCount> {
+    let mut peers: Vec<PeerCount> = counts
+        .into_iter()
+        .map(|(address, connections)| PeerCount {
+            address,
+            connections,
+        })
+        .collect();
+    // Orden estable: a igual conteo se mantiene el orden alfabético
+    peers.sort_by_key(|p| std::cmp::Reverse(p.connections));
+    peers
+}
+
+/// Parsea la salida de `ss -tanpH` quedándose con las conexiones ESTAB.
+///
+/// Formato esperado:
+/// ```text
+/// ESTAB  0  0  127.0.0.1:5432  127.0.0.1:51234  users:(("postgres",pid=880,fd=9))
+/// ```
+fn parse_ss_connections(output: &str) -> Vec<Connection> {
+    output
+        .lines()
+        .filter_map(parse_ss_connection_line)
+        .collect()
+}
+
+/// Parsea una línea de `ss -tanpH`; `None` si no es una conexión establecida.
+fn parse_ss_connection_line(line: &str) -> Option<Connection> {
+    let parts: Vec<&str> = line.split_whitespace().collect();
+    if parts.len() < 5 || parts[0] != "ESTAB" {
+        return None;
+    }
+
+    let (local_address, local_port) = split_socket(parts[3])?;
+    let (peer_address, peer_port) = split_socket(parts[4])?;
+    let (pid, process_name) = port_scanner::extract_process_info(line)
+        .unwrap_or_else(|| (0, tr(Text::UnknownProcess).to_string()));
+
+    Some(Connection {
+        local_address,
+        local_port,
+        peer_address,
+        peer_port,
+        pid,
+        process_name,
+    })
+}
+
+/// Separa "dirección:puerto" (IPv4 o IPv6 entre corchetes).
+fn split_socket(socket: &str) -> Option<(String, u16)> {
+    let colon = socket.rfind(':')?;
+    let port = socket[colon + 1..].parse().ok()?;
+    Some((port_scanner::clean_address(&socket[..colon]), port))
+}
+
+/// Indica si una dirección (ya limpia) es de loopback, incluidas
+/// las IPv4 mapeadas en IPv6 (`::ffff:127.0.0.1`).
+fn is_loopback(address: &str) -> bool {
+    match address.parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) => ip.is_loopback(),
+        Ok(IpAddr::V6(ip)) => {
+            ip.is_loopback() || ip.to_ipv4_mapped().is_some_and(|v4| v4.is_loopback())
+        }
+        Err(_) => false,
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SS_OUTPUT: &str = "\
+LISTEN 0 128 0.0.0.0:5432 0.0.0.0:* users:((\"postgres\",pid=880,fd=5))
+ESTAB 0 0 127.0.0.1:5432 127.0.0.1:51234 users:((\"postgres\",pid=880,fd=9))
+ESTAB 0 0 127.0.0.1:51234 127.0.0.1:5432 users:((\"node\",pid=1200,fd=21))
+ESTAB 0 0 10.0.0.2:5432 10.0.0.7:40000 users:((\"postgres\",pid=880,fd=10))
+ESTAB 0 0 10.0.0.2:5432 10.0.0.7:40001 users:((\"postgres\",pid=880,fd=11))
+ESTAB 0 0 [::ffff:10.0.0.2]:5432 [::ffff:10.0.0.2]:39000
+";
+
+    fn postgres() -> PortInfo {
+        PortInfo {
+            protocol: "tcp".into(),
+            port: 5432,
+            local_address: "0.0.0.0".into(),
+            pid: 880,
+            process_name: "postgres".into(),
+            ..Default::default()
+        }
+    }
+
+    /// Verifica el parseo de conexiones establecidas e IPv6
+    #[test]
+    fn test_parse_ss_connections() {
+        let conns = parse_ss_connections(SS_OUTPUT);
+        assert_eq!(conns.len(), 5);
+        assert_eq!(conns[1].process_name, "node");
+        assert_eq!(conns[1].local_port, 51234);
+        assert_eq!(conns[4].peer_address, "::ffff:10.0.0.2");
+        assert_eq!(conns[4].pid, 0);
+        assert!(conns[4].is_local_peer());
+        assert!(!conns[2].is_local_peer());
+    }
+
+    /// Verifica qué conexiones se pueden cortar sueltas y cómo se
+    /// eligen por su par
+    #[test]
+    fn test_clients_and_peers() {
+        let conns = parse_ss_connections(SS_OUTPUT);
+        let clients = clients_of(&postgres(), &conns);
+        assert_eq!(clients.len(), 4);
+        assert_eq!(clients[1].peer(), "10.0.0.7:40000");
+        assert_eq!(conns[4].peer(), "[::ffff:10.0.0.2]:39000");
+
+        assert!(clients[1].matches_peer("10.0.0.7"));
+        assert!(clients[1].matches_peer("10.0.0.7:40000"));
+        assert!(!clients[1].matches_peer("10.0.0.7:40001"));
+        assert!(conns[4].matches_peer("[::ffff:10.0.0.2]:39000"));
+        assert!(conns[4].matches_peer("::ffff:10.0.0.2"));
+        assert_eq!(filter_host("fe80::1%eth0"), "[fe80::1]");
+    }
+
+    /// Verifica el cálculo y el resumen del impacto de un kill
+    #[test]
+    fn test_kill_impact() {
+        let conns = parse_ss_connections(SS_OUTPUT);
+        let impact = kill_impact(&postgres(), &conns);
+
+        assert_eq!(impact.established, 4);
+        assert_eq!(impact.local_peers.len(), 2);
+        assert_eq!(
+            impact.remote_peers,
+            vec![PeerCount {
+                address: "10.0.0.7".into(),
+                connections: 2
+            }]
+        );
+        assert_eq!(
+            impact.summary(),
+            "4 conexiones activas: 2 locales, 2 remotas (10.0.0.7)"
+        );
+
+        assert_eq!(
+            impact.dependency_note("postgres").as_deref(),
+            Some("Matar postgres romperá node (1 conexión activa)")
+        );
+
+        let mut udp = postgres();
+        udp.protocol = "udp".into();
+        assert_eq!(
+            kill_impact(&udp, &conns).summary(),
+            "sin conexiones activas"
+        );
+    }
+}
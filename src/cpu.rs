@@ -0,0 +1,348 @@
+/// Módulo de picos de CPU de los procesos con puertos abiertos.
+///
+/// Tras cada intervalo de escaneo se muestrea el tiempo de CPU de cada
+/// proceso con puertos (`/proc/<pid>/stat`). Si uno se mantiene por
+/// encima del umbral durante los minutos configurados ("tu watcher de
+/// webpack olvidado está quemando un núcleo"), se notifica una vez; el
+/// aviso se rearma cuando el uso baja del umbral.
+///
+/// El porcentaje es de un núcleo, como en `top`: un proceso con varios
+/// hilos puede superar el 100%. Los umbrales se configuran en la sección
+/// `[cpu_alerts]`, con excepciones por proceso (admiten comodines):
+///
+/// ```toml
+/// [cpu_alerts]
+/// threshold_percent = 90
+/// minutes = 5
+///
+/// [cpu_alerts.processes]
+/// "webpack*" = { threshold_percent = 50, minutes = 2 }
+/// postgres = { enabled = false }
+/// ```
+///
+/// Las ventanas de mantenimiento suprimen los avisos, como con las reglas.
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::time::{Duration, Instant};
+
+use crate::config::Config;
+use crate::i18n::{trf, Text};
+use crate::maintenance;
+use crate::port_scanner::{self, PortInfo};
+use crate::rules;
+
+/// Ticks de CPU por segundo en `/proc` (USER_HZ, fijo en Linux)
+const TICKS_PER_SEC: f64 = 100.0;
+
+/// Umbral por defecto (% de un núcleo)
+const DEFAULT_THRESHOLD_PERCENT: f64 = 90.0;
+
+/// Minutos por defecto por encima del umbral antes de avisar
+const DEFAULT_MINUTES: u64 = 5;
+
+/// Sección `[cpu_alerts]` de la configuración
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct CpuAlertConfig {
+    /// Avisar de los picos de CPU
+    pub enabled: bool,
+    /// Porcentaje de un núcleo a partir del cual se considera un pico
+    pub threshold_percent: f64,
+    /// Minutos seguidos por encima del umbral antes de avisar
+    pub minutes: u64,
+    /// Umbrales por nombre de proceso (admite comodines, ej: "node*")
+    pub processes: BTreeMap<String, ProcessThreshold>,
+}
+
+impl Default for CpuAlertConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold_percent: DEFAULT_THRESHOLD_PERCENT,
+            minutes: DEFAULT_MINUTES,
+            processes: BTreeMap::new(),
+        }
+    }
+}
+
+/// Umbral de un proceso; lo que falte se toma de la sección general
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ProcessThreshold {
+    /// `false` para no vigilar el proceso
+    pub enabled: Option<bool>,
+    /// Porcentaje de un núcleo
+    pub threshold_percent: Option<f64>,
+    /// Minutos seguidos por encima del umbral
+    pub minutes: Option<u64>,
+}
+
+/// Umbral efectivo de un proceso
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Threshold {
+    percent: f64,
+    sustained: Duration,
+}
+
+impl CpuAlertConfig {
+    /// Verifica que los umbrales tengan sentido.
+    pub fn validate(&self) -> Result<(), String> {
+        let overrides = self.processes.iter().map(|(name, t)| {
+            (
+                name.as_str(),
+                t.threshold_percent.unwrap_or(self.threshold_percent),
+                t.minutes.unwrap_or(self.minutes),
+            )
+        });
+        for (name, percent, minutes) in
+            std::iter::once(("cpu_alerts", self.threshold_percent, self.minutes)).chain(overrides)
+        {
+            if percent <= 0.0 || minutes == 0 {
+                return Err(format!(
+                    "Umbral de CPU inválido en {}: el porcentaje y los minutos deben ser mayores que 0",
+                    name
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Umbral aplicable a un proceso (`None` si no se vigila).
+    fn threshold_for(&self, process_name: &str) -> Option<Threshold> {
+        let custom = self
+            .processes
+            .iter()
+            .find(|(pattern, _)| port_scanner::glob_match(pattern, process_name))
+            .map(|(_, threshold)| threshold);
+        if !custom.and_then(|t| t.enabled).unwrap_or(self.enabled) {
+            return None;
+        }
+        Some(Threshold {
+            percent: custom
+                .and_then(|t| t.threshold_percent)
+                .unwrap_or(self.threshold_percent),
+            sustained: Duration::from_secs(
+                custom.and_then(|t| t.minutes).unwrap_or(self.minutes) * 60,
+            ),
+        })
+    }
+}
+
+/// Pico de CPU sostenido de un proceso con puertos
+#[derive(Debug, Clone, PartialEq)]
+pub struct CpuAlert {
+    /// PID del proceso
+    pub pid: u32,
+    /// Nombre del proceso
+    pub process_name: String,
+    /// Puerto más bajo que ocupa
+    pub port: u16,
+    /// Uso de CPU en el último muestreo (% de un núcleo)
+    pub percent: f64,
+    /// Minutos seguidos por encima del umbral
+    pub minutes: u64,
+}
+
+/// Último muestreo de un proceso
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    /// Ticks de CPU acumulados (usuario + sistema)
+    ticks: u64,
+    /// Momento del muestreo
+    at: Instant,
+    /// Desde cuándo está por encima del umbral
+    over_since: Option<Instant>,
+    /// Ya se avisó en este pico
+    alerted: bool,
+}
+
+/// Estado del detector entre escaneos
+#[derive(Debug, Default)]
+pub struct CpuMonitor {
+    samples: HashMap<u32, Sample>,
+}
+
+impl CpuMonitor {
+    /// Crea un detector sin muestreos previos.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Muestrea los procesos con puertos y devuelve los picos nuevos.
+    pub fn check(&mut self, config: &CpuAlertConfig, ports: &[PortInfo]) -> Vec<CpuAlert> {
+        let now = Instant::now();
+        let mut alerts = Vec::new();
+        let mut seen = Vec::new();
+        for port_info in ports.iter().filter(|p| p.pid > 0) {
+            if seen.contains(&port_info.pid) {
+                continue;
+            }
+            seen.push(port_info.pid);
+            let Some(threshold) = config.threshold_for(&port_info.process_name) else {
+                continue;
+            };
+            let Some(ticks) = process_ticks(port_info.pid) else {
+                continue;
+            };
+            if let Some((percent, over_for)) = self.sample(port_info.pid, ticks, now, threshold) {
+                alerts.push(CpuAlert {
+                    pid: port_info.pid,
+                    process_name: port_info.process_name.clone(),
+                    port: port_info.port,
+                    percent,
+                    minutes: over_for.as_secs() / 60,
+                });
+            }
+        }
+        // Olvidar los procesos que ya no tienen puertos
+        self.samples.retain(|pid, _| seen.contains(pid));
+        alerts
+    }
+
+    /// Registra un muestreo.
+    ///
+    /// # Returns
+    /// Uso actual y tiempo por encima del umbral si hay que avisar.
+    fn sample(
+        &mut self,
+        pid: u32,
+        ticks: u64,
+        now: Instant,
+        threshold: Threshold,
+    ) -> Option<(f64, Duration)> {
+        let previous = self.samples.insert(
+            pid,
+            Sample {
+                ticks,
+                at: now,
+                over_since: None,
+                alerted: false,
+            },
+        )?;
+        let elapsed = now.saturating_duration_since(previous.at).as_secs_f64();
+        // Un PID reutilizado empieza de cero
+        if ticks < previous.ticks || elapsed <= 0.0 {
+            return None;
+        }
+
+        let percent = (ticks - previous.ticks) as f64 / TICKS_PER_SEC / elapsed * 100.0;
+        let sample = self.samples.get_mut(&pid)?;
+        if percent < threshold.percent {
+            return None;
+        }
+        let over_since = previous.over_since.unwrap_or(previous.at);
+        sample.over_since = Some(over_since);
+        sample.alerted = previous.alerted;
+        let over_for = now.saturating_duration_since(over_since);
+        if previous.alerted || over_for < threshold.sustained {
+            return None;
+        }
+        sample.alerted = true;
+        Some((percent, over_for))
+    }
+}
+
+/// Muestrea y notifica los picos de CPU (salvo en mantenimiento).
+///
+/// # Arguments
+/// * `monitor` - Estado del detector entre escaneos
+/// * `config` - Configuración con los umbrales y las ventanas
+/// * `ports` - Puertos del último escaneo
+pub fn run_after_scan(monitor: &mut CpuMonitor, config: &Config, ports: &[PortInfo]) {
+    let alerts = monitor.check(&config.cpu_alerts, ports);
+    if alerts.is_empty() {
+        return;
+    }
+    if let Some((window, _)) =
+        maintenance::active_window(&config.maintenance, maintenance::local_now())
+    {
+        log::debug!("Avisos de CPU suprimidos por la ventana '{}'", window.name);
+        return;
+    }
+    for alert in alerts {
+        let message = trf(
+            Text::CpuSpike,
+            &[
+                &alert.process_name,
+                &alert.pid,
+                &alert.port,
+                &alert.minutes,
+                &format!("{:.0}", alert.percent),
+            ],
+        );
+        log::warn!("{}", message);
+        rules::notify(&message);
+    }
+}
+
+/// Ticks de CPU acumulados de un proceso (usuario + sistema).
+fn process_ticks(pid: u32) -> Option<u64> {
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    parse_stat_ticks(&stat)
+}
+
+/// Extrae `utime + stime` de una línea de `/proc/<pid>/stat`.
+///
+/// El nombre del proceso va entre paréntesis y puede contener espacios,
+/// así que los campos se cuentan desde el último `)`.
+fn parse_stat_ticks(stat: &str) -> Option<u64> {
+    let fields: Vec<&str> = stat
+        .get(stat.rfind(')')? + 1..)?
+        .split_whitespace()
+        .collect();
+    // Tras el nombre: estado (3), ..., utime (14), stime (15)
+    let utime: u64 = fields.get(11)?.parse().ok()?;
+    let stime: u64 = fields.get(12)?.parse().ok()?;
+    Some(utime + stime)
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verifica la lectura de los ticks aunque el nombre tenga espacios
+    #[test]
+    fn test_parse_stat_ticks() {
+        let stat = "4242 (node dev) S 1 4242 4242 0 -1 4194560 1234 0 0 0 250 30 0 0 20 0 11 0";
+        assert_eq!(parse_stat_ticks(stat), Some(280));
+        assert_eq!(parse_stat_ticks("4242 (node"), None);
+    }
+
+    /// Verifica que solo se avisa tras el tiempo sostenido, y una vez por pico
+    #[test]
+    fn test_sustained_spike() {
+        let threshold = Threshold {
+            percent: 80.0,
+            sustained: Duration::from_secs(120),
+        };
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let mut monitor = CpuMonitor::new();
+
+        // Un núcleo entero: 100 ticks por segundo
+        assert_eq!(monitor.sample(7, 0, at(0), threshold), None);
+        assert_eq!(monitor.sample(7, 6_000, at(60), threshold), None);
+        let (percent, over_for) = monitor.sample(7, 12_000, at(120), threshold).unwrap();
+        assert_eq!(percent, 100.0);
+        assert_eq!(over_for, Duration::from_secs(120));
+        assert_eq!(monitor.sample(7, 18_000, at(180), threshold), None);
+
+        // Al bajar del umbral se rearma
+        assert_eq!(monitor.sample(7, 18_100, at(240), threshold), None);
+        assert_eq!(monitor.sample(7, 24_100, at(300), threshold), None);
+        assert!(monitor.sample(7, 30_100, at(360), threshold).is_some());
+
+        // Con la configuración por defecto se vigila cualquier proceso
+        let config = CpuAlertConfig::default();
+        assert!(config.validate().is_ok());
+        assert_eq!(
+            config.threshold_for("webpack").map(|t| t.sustained),
+            Some(Duration::from_secs(300))
+        );
+    }
+}
@@ -0,0 +1,169 @@
+/// Módulo del modo daemon (sin interfaz gráfica).
+///
+/// Ejecuta el mismo motor de escaneo que el system tray, pero en lugar
+/// de mostrar un menú expone los resultados a través de la API HTTP
+/// (ver `api`). Cada escaneo se compara con el anterior y los cambios
+/// se registran en el historial y se publican en `/events`; después se
+/// evalúan las reglas. Los picos de CPU se vigilan en cada intervalo.
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::api::{self, ApiState};
+use crate::auth::Authenticator;
+use crate::config::{self, Config};
+use crate::connections;
+use crate::cpu::{self, CpuMonitor};
+#[cfg(feature = "email")]
+use crate::email;
+use crate::events::EventBus;
+use crate::fingerprint;
+use crate::health;
+use crate::history;
+use crate::port_scanner::{self, ChangeDetector};
+use crate::rules::{self, RuleEngine};
+use crate::watchlist::{self, WatchMonitor};
+use crate::webhooks;
+
+/// Inicia el daemon y bloquea el hilo principal sirviendo la API.
+///
+/// # Arguments
+/// * `bind` - Dirección `host:puerto` donde escuchará la API
+/// * `dashboard` - Servir también el panel web de invitado
+///
+/// # Returns
+/// `Err(String)` si la autenticación no se pudo preparar o no admite
+/// esa dirección, o si no se pudo abrir la API.
+pub fn run_daemon(bind: &str, dashboard: bool) -> Result<(), String> {
+    log::info!("Iniciando PortSlayer en modo daemon...");
+
+    if !is_loopback_bind(bind) {
+        log::warn!(
+            "La API escuchará en {}, accesible desde otras máquinas de la red",
+            bind
+        );
+    }
+
+    let api_config = Config::load().api;
+    let auth = Authenticator::from_config(&api_config)?;
+    // PAM recibe contraseñas del sistema en HTTP Basic sin cifrar
+    if matches!(auth, Authenticator::Pam { .. }) && !is_loopback_bind(bind) {
+        return Err(format!(
+            "La autenticación pam solo se admite escuchando en loopback (no en {}): las contraseñas viajarían sin cifrar",
+            bind
+        ));
+    }
+    log::info!("Autenticación de la API: {}", auth.name());
+
+    let initial = port_scanner::scan_open_ports();
+    log::info!("Escaneo inicial: {} puertos detectados", initial.len());
+
+    // Registrar lo que cambió con el daemon detenido, para que el
+    // historial siga siendo reproducible (ver `history::ports_at`)
+    let known = history::ports_at(history::unix_now());
+    history::append_events(&history::scan_changes(&known, &initial));
+
+    let ports = Arc::new(Mutex::new(initial));
+    let events = Arc::new(EventBus::new());
+
+    // Hilo de escaneo periódico con registro de cambios en el historial
+    let scan_handle = Arc::clone(&ports);
+    let scan_events = Arc::clone(&events);
+    std::thread::spawn(move || {
+        let mut engine = RuleEngine::new();
+        let mut cpu_monitor = CpuMonitor::new();
+        let mut watch_monitor = WatchMonitor::new();
+        let mut interval = scan_interval(&Config::load());
+        let mut detector = ChangeDetector::default();
+        loop {
+            std::thread::sleep(Duration::from_secs(interval));
+
+            let config = Config::load();
+            interval = scan_interval(&config);
+            if let Ok(current) = scan_handle.lock() {
+                cpu::run_after_scan(&mut cpu_monitor, &config, &current);
+            }
+            #[cfg(feature = "email")]
+            email::flush_due(config.email.as_ref());
+            // Antes de los atajos sin cambios: en reposo también hay que podar
+            history::prune_if_needed(&config.history);
+            // Sin cambios en /proc/net no hace falta el escaneo completo
+            if !detector.should_scan() {
+                continue;
+            }
+
+            let new_ports = port_scanner::scan_open_ports();
+            if config.service_fingerprints {
+                fingerprint::request(&new_ports);
+            }
+            if config.http_health {
+                health::request(&new_ports);
+            }
+            if !rules::active_rules(&config).is_empty() {
+                let established = connections::scan_established();
+                rules::run_after_scan(&mut engine, &config, &new_ports, &established);
+            }
+            watchlist::run_after_scan(&mut watch_monitor, &config, &new_ports);
+
+            if let Ok(mut current) = scan_handle.lock() {
+                let checksum = port_scanner::scan_checksum(&new_ports);
+                if port_scanner::scan_checksum(&current) == checksum {
+                    log::debug!("Escaneo sin cambios: no se emiten eventos ni historial");
+                    continue;
+                }
+                let changes = history::scan_changes(&current, &new_ports);
+                history::append_events(&changes);
+                scan_events.publish(&changes);
+                webhooks::send(&config.webhooks, &changes);
+                #[cfg(feature = "email")]
+                email::queue(config.email.as_ref(), &changes);
+                *current = new_ports;
+            }
+        }
+    });
+
+    if dashboard {
+        log::info!("Panel web de solo lectura en http://{}/", bind);
+    }
+
+    let uses_socket = auth.uses_socket();
+    let state = ApiState {
+        ports,
+        events,
+        auth,
+        dashboard,
+    };
+
+    // Con `peer` la API va por socket Unix; por TCP solo queda el panel
+    if uses_socket {
+        if dashboard {
+            let tcp_state = state.clone();
+            let tcp_bind = bind.to_string();
+            std::thread::spawn(move || {
+                if let Err(e) = api::serve(&tcp_bind, tcp_state) {
+                    log::error!("Error iniciando el panel en {}: {}", tcp_bind, e);
+                }
+            });
+        }
+        let socket = api_config.socket_path();
+        let shared = !api_config.allowed_users.is_empty();
+        api::serve_unix(&socket, state, shared)
+            .map_err(|e| format!("Error iniciando la API en {}: {}", socket.display(), e))
+    } else {
+        api::serve(bind, state).map_err(|e| format!("Error iniciando la API en {}: {}", bind, e))
+    }
+}
+
+/// Intervalo de escaneo del daemon.
+///
+/// El daemon no tiene modo manual: con 0 se usa el intervalo por defecto.
+fn scan_interval(config: &Config) -> u64 {
+    match config.scan_interval() {
+        0 => config::DEFAULT_SCAN_INTERVAL_SECS,
+        secs => secs,
+    }
+}
+
+/// Indica si la dirección de escucha es solo local (loopback).
+fn is_loopback_bind(bind: &str) -> bool {
+    bind.starts_with("127.") || bind.starts_with("localhost:") || bind.starts_with("[::1]:")
+}
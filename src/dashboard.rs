@@ -0,0 +1,114 @@
+/// Módulo del panel web de solo lectura del modo daemon.
+///
+/// Con `portslayer daemon --dashboard` la API sirve además una página
+/// HTML estática (sin build de frontend) en `/` que muestra los puertos
+/// en vivo mediante Server-Sent Events en `/dashboard/events`. Pensado para
+/// consultar los puertos de la máquina desde el móvil en la red local.
+///
+/// Ambas rutas son de invitado: no requieren token y solo exponen
+/// protocolo, puerto, dirección y nombre de proceso (sin PIDs, notas ni
+/// acciones).
+use serde_json::json;
+use std::io::{self, Write};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::history;
+use crate::port_scanner::PortInfo;
+
+/// Página del panel, embebida en el binario
+pub const DASHBOARD_HTML: &str = include_str!("../assets/dashboard.html");
+
+/// Intervalo entre eventos enviados a cada cliente
+const EVENT_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Envía la lista de puertos como Server-Sent Events hasta que el cliente se desconecte.
+///
+/// Solo se emite un evento cuando la lista cambia, más un comentario
+/// de keep-alive en cada intervalo para detectar clientes caídos.
+///
+/// # Arguments
+/// * `stream` - Conexión del cliente (ya leída la petición)
+/// * `ports` - Lista de puertos compartida con el hilo de escaneo
+pub fn stream_events<W: Write>(mut stream: W, ports: &Arc<Mutex<Vec<PortInfo>>>) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: keep-alive\r\n\r\n"
+    )?;
+
+    let mut last_sent = String::new();
+    loop {
+        let current = match ports.lock() {
+            Ok(ports) => guest_ports(&ports),
+            Err(_) => Vec::new(),
+        };
+        let ports_json = serde_json::Value::Array(current).to_string();
+
+        if ports_json != last_sent {
+            write!(
+                stream,
+                "{}",
+                event_message(&ports_json, history::unix_now())
+            )?;
+            last_sent = ports_json;
+        } else {
+            write!(stream, ": keep-alive\n\n")?;
+        }
+        stream.flush()?;
+        std::thread::sleep(EVENT_INTERVAL);
+    }
+}
+
+/// Vista de invitado de los puertos: sin PIDs ni datos sensibles.
+fn guest_ports(ports: &[PortInfo]) -> Vec<serde_json::Value> {
+    ports
+        .iter()
+        .map(|p| {
+            json!({
+                "protocol": p.protocol,
+                "port": p.port,
+                "local_address": p.local_address,
+                "process_name": p.process_name,
+            })
+        })
+        .collect()
+}
+
+/// Formatea un mensaje SSE con la lista de puertos ya serializada.
+fn event_message(ports_json: &str, timestamp: u64) -> String {
+    format!(
+        "data: {{\"timestamp\":{},\"ports\":{}}}\n\n",
+        timestamp, ports_json
+    )
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verifica que el evento es JSON válido y no expone el PID
+    #[test]
+    fn test_event_message() {
+        let ports = vec![PortInfo {
+            protocol: "tcp".into(),
+            port: 3000,
+            local_address: "0.0.0.0".into(),
+            pid: 1234,
+            process_name: "node".into(),
+            ..Default::default()
+        }];
+        let ports_json = serde_json::Value::Array(guest_ports(&ports)).to_string();
+        let message = event_message(&ports_json, 42);
+
+        assert!(message.starts_with("data: ") && message.ends_with("\n\n"));
+        let value: serde_json::Value =
+            serde_json::from_str(message["data: ".len()..].trim()).unwrap();
+        assert_eq!(value["timestamp"], 42);
+        assert_eq!(value["ports"][0]["port"], 3000);
+        assert!(value["ports"][0].get("pid").is_none());
+    }
+}
@@ -0,0 +1,317 @@
+/// Módulo de los puertos publicados por Docker.
+///
+/// Un puerto publicado (`docker run -p 8080:80`) aparece en el host como
+/// `docker-proxy` (o sin PID si el proxy es de root y no hay permisos),
+/// que no dice a qué contenedor lleva. Se le pregunta a la API de Docker
+/// por su socket (`GET /containers/json`) y cada puerto del host se
+/// muestra con su contenedor, su imagen y el puerto dentro del contenedor:
+///
+/// ```text
+/// TCP 8080 (0.0.0.0) → docker-proxy [PID 2210]
+///   🐳 → contenedor myapp-web (nginx:1.25), puerto 80 del contenedor
+/// ```
+///
+/// Los contenedores de un proyecto de Docker Compose (etiqueta
+/// `com.docker.compose.project`) se agrupan por proyecto (ver
+/// [`compose_projects`]), para bajarlos todos con `docker compose down`.
+///
+/// La consulta se reutiliza unos segundos, como las reglas del firewall.
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{Read, Write};
+use std::os::unix::net::UnixStream;
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::i18n::{trf, Text};
+use crate::port_scanner::PortInfo;
+
+/// Socket del daemon de Docker
+pub const DOCKER_SOCKET: &str = "/var/run/docker.sock";
+
+/// Tiempo durante el que se reutilizan los puertos publicados
+const BINDINGS_TTL: Duration = Duration::from_secs(10);
+
+/// Espera máxima de la respuesta de la API
+const API_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Etiqueta con el proyecto de Docker Compose de un contenedor
+const COMPOSE_PROJECT_LABEL: &str = "com.docker.compose.project";
+
+/// Puertos publicados y momento de la consulta
+type CachedBindings = (Instant, Arc<Vec<Binding>>);
+
+/// Última consulta a la API
+static BINDINGS: Mutex<Option<CachedBindings>> = Mutex::new(None);
+
+/// Puerto del host publicado por un contenedor
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Binding {
+    pub host_port: u16,
+    /// Protocolo ("tcp" o "udp")
+    pub protocol: String,
+    /// Nombre del contenedor (sin la "/" inicial de la API)
+    pub container: String,
+    /// ID corto del contenedor (12 caracteres)
+    pub container_id: String,
+    pub image: String,
+    pub container_port: u16,
+    /// Proyecto de Docker Compose del contenedor, si es de uno
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compose_project: Option<String>,
+}
+
+impl Binding {
+    /// Texto del detalle del puerto: "→ contenedor myapp-web (nginx:1.25),
+    /// puerto 80 del contenedor".
+    pub fn describe(&self) -> String {
+        trf(
+            Text::DockerBinding,
+            &[&self.container, &self.image, &self.container_port],
+        )
+    }
+
+    /// Comando para ver los logs del contenedor.
+    pub fn logs_command(&self) -> String {
+        format!("docker logs {}", self.container)
+    }
+}
+
+/// Contenedor tal y como lo devuelve `GET /containers/json`
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ApiContainer {
+    id: String,
+    #[serde(default)]
+    names: Vec<String>,
+    image: String,
+    #[serde(default)]
+    ports: Vec<ApiPort>,
+    #[serde(default)]
+    labels: HashMap<String, String>,
+}
+
+/// Puerto de un contenedor en la API (`PublicPort` solo si se publica)
+#[derive(Debug, Deserialize)]
+struct ApiPort {
+    #[serde(rename = "PrivatePort")]
+    private_port: u16,
+    #[serde(rename = "PublicPort")]
+    public_port: Option<u16>,
+    #[serde(rename = "Type")]
+    protocol: String,
+}
+
+/// Puertos publicados por los contenedores en marcha.
+///
+/// Reutiliza la última consulta durante unos segundos; sin Docker (o sin
+/// permiso para su socket) la lista queda vacía.
+pub fn bindings() -> Arc<Vec<Binding>> {
+    let mut cached = BINDINGS.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((at, bindings)) = cached.as_ref() {
+        if at.elapsed() < BINDINGS_TTL {
+            return bindings.clone();
+        }
+    }
+    let bindings = match query("/containers/json").and_then(|body| parse_containers(&body)) {
+        Ok(bindings) => bindings,
+        Err(e) => {
+            log::debug!("Sin puertos de Docker: {}", e);
+            Vec::new()
+        }
+    };
+    let bindings = Arc::new(bindings);
+    *cached = Some((Instant::now(), bindings.clone()));
+    bindings
+}
+
+/// Últimos puertos publicados, sin volver a consultar aunque hayan
+/// caducado (para construir el menú sin esperar a Docker).
+pub fn cached() -> Arc<Vec<Binding>> {
+    let cached = BINDINGS.lock().unwrap_or_else(|e| e.into_inner());
+    cached
+        .as_ref()
+        .map(|(_, bindings)| bindings.clone())
+        .unwrap_or_default()
+}
+
+/// Contenedor que publica un puerto del host, si lo hay.
+///
+/// Solo cuentan los puertos del host de `docker-proxy` o sin PID visible:
+/// un proceso normal en el mismo puerto no es del contenedor.
+pub fn binding_for<'a>(port: &PortInfo, bindings: &'a [Binding]) -> Option<&'a Binding> {
+    let proxied = port.process_name == "docker-proxy" || port.pid == 0;
+    if !proxied || port.netns.is_some() {
+        return None;
+    }
+    bindings
+        .iter()
+        .find(|b| b.host_port == port.port && b.protocol == port.protocol)
+}
+
+/// Puertos del host de cada proyecto de Docker Compose, por nombre del
+/// proyecto y de menor a mayor.
+pub fn compose_projects(bindings: &[Binding]) -> BTreeMap<String, Vec<u16>> {
+    let mut projects: BTreeMap<String, Vec<u16>> = BTreeMap::new();
+    for binding in bindings {
+        if let Some(project) = &binding.compose_project {
+            projects
+                .entry(project.clone())
+                .or_default()
+                .push(binding.host_port);
+        }
+    }
+    for ports in projects.values_mut() {
+        ports.sort_unstable();
+        ports.dedup();
+    }
+    projects
+}
+
+/// Baja un proyecto de Docker Compose: `docker compose -p <proyecto> down`.
+///
+/// Con `-p` no hace falta el `docker-compose.yml`: Compose encuentra los
+/// contenedores por su etiqueta.
+///
+/// # Returns
+/// `Err(String)` con la salida de error si `docker compose` falló.
+pub fn compose_down(project: &str) -> Result<(), String> {
+    let output = Command::new("docker")
+        .args(["compose", "-p", project, "down"])
+        .output()
+        .map_err(|e| format!("No se pudo ejecutar docker compose: {}", e))?;
+    if output.status.success() {
+        log::info!("Proyecto de Docker Compose {} detenido", project);
+        Ok(())
+    } else {
+        Err(format!(
+            "docker compose -p {} down falló: {}",
+            project,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+/// Hace un `GET` a la API de Docker por su socket Unix.
+///
+/// Con HTTP/1.0 la respuesta no viene troceada y Docker cierra la
+/// conexión al terminar, así que basta con leer hasta el final.
+///
+/// # Returns
+/// El cuerpo de la respuesta, o `Err(String)` si no se pudo conectar o
+/// la respuesta no es un 200.
+fn query(path: &str) -> Result<String, String> {
+    let mut stream = UnixStream::connect(DOCKER_SOCKET)
+        .map_err(|e| format!("No se pudo conectar a {}: {}", DOCKER_SOCKET, e))?;
+    stream
+        .set_read_timeout(Some(API_TIMEOUT))
+        .and_then(|_| stream.set_write_timeout(Some(API_TIMEOUT)))
+        .map_err(|e| e.to_string())?;
+    write!(stream, "GET {} HTTP/1.0\r\nHost: docker\r\n\r\n", path)
+        .map_err(|e| format!("No se pudo consultar la API de Docker: {}", e))?;
+    let mut response = String::new();
+    stream
+        .read_to_string(&mut response)
+        .map_err(|e| format!("Respuesta incompleta de la API de Docker: {}", e))?;
+    response_body(&response).map(str::to_string)
+}
+
+/// Cuerpo de una respuesta HTTP con estado 200.
+fn response_body(response: &str) -> Result<&str, String> {
+    let (head, body) = response
+        .split_once("\r\n\r\n")
+        .ok_or("Respuesta HTTP inválida de la API de Docker")?;
+    let status = head.lines().next().unwrap_or_default();
+    match status.split_whitespace().nth(1) {
+        Some("200") => Ok(body),
+        _ => Err(format!("La API de Docker respondió \"{}\"", status)),
+    }
+}
+
+/// Interpreta la lista de contenedores de la API.
+///
+/// Docker repite cada puerto publicado para IPv4 y IPv6 ("0.0.0.0" y
+/// "::"): se deja uno por puerto del host y protocolo.
+fn parse_containers(body: &str) -> Result<Vec<Binding>, String> {
+    let containers: Vec<ApiContainer> =
+        serde_json::from_str(body).map_err(|e| format!("Lista de contenedores inválida: {}", e))?;
+    let mut bindings: Vec<Binding> = Vec::new();
+    for container in containers {
+        let name = container
+            .names
+            .first()
+            .map(|name| name.trim_start_matches('/').to_string())
+            .unwrap_or_else(|| container.id.chars().take(12).collect());
+        for port in container.ports {
+            let Some(host_port) = port.public_port else {
+                continue;
+            };
+            let duplicate = bindings
+                .iter()
+                .any(|b| b.host_port == host_port && b.protocol == port.protocol);
+            if !duplicate {
+                bindings.push(Binding {
+                    host_port,
+                    protocol: port.protocol,
+                    container: name.clone(),
+                    container_id: container.id.chars().take(12).collect(),
+                    image: container.image.clone(),
+                    container_port: port.private_port,
+                    compose_project: container.labels.get(COMPOSE_PROJECT_LABEL).cloned(),
+                });
+            }
+        }
+    }
+    Ok(bindings)
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verifica la lectura de los puertos publicados y a qué puerto del
+    /// host corresponden
+    #[test]
+    fn test_parse_containers() {
+        let response = "HTTP/1.0 200 OK\r\nContent-Type: application/json\r\n\r\n[\
+            {\"Id\":\"4f1c2a9b8e7d6c5b4a39\",\"Names\":[\"/myapp-web\"],\"Image\":\"nginx:1.25\",\
+             \"Ports\":[{\"IP\":\"0.0.0.0\",\"PrivatePort\":80,\"PublicPort\":8080,\"Type\":\"tcp\"},\
+                        {\"IP\":\"::\",\"PrivatePort\":80,\"PublicPort\":8080,\"Type\":\"tcp\"},\
+                        {\"PrivatePort\":443,\"Type\":\"tcp\"}]},\
+            {\"Id\":\"9a8b7c6d5e4f\",\"Names\":[\"/db\"],\"Image\":\"postgres:16\",\"Ports\":[]},\
+            {\"Id\":\"1b2c3d4e5f6a\",\"Names\":[\"/shop-db-1\"],\"Image\":\"postgres:16\",\
+             \"Labels\":{\"com.docker.compose.project\":\"shop\"},\
+             \"Ports\":[{\"PrivatePort\":5432,\"PublicPort\":5432,\"Type\":\"tcp\"}]},\
+            {\"Id\":\"6a5f4e3d2c1b\",\"Names\":[\"/shop-web-1\"],\"Image\":\"nginx:1.25\",\
+             \"Labels\":{\"com.docker.compose.project\":\"shop\"},\
+             \"Ports\":[{\"PrivatePort\":80,\"PublicPort\":8081,\"Type\":\"tcp\"}]}]";
+        let bindings = parse_containers(response_body(response).unwrap()).unwrap();
+        assert_eq!(bindings.len(), 3);
+        assert_eq!(bindings[0].compose_project, None);
+        let projects = compose_projects(&bindings);
+        assert_eq!(projects.len(), 1);
+        assert_eq!(projects["shop"], vec![5432, 8081]);
+        assert_eq!(bindings[0].container, "myapp-web");
+        assert_eq!(bindings[0].container_id, "4f1c2a9b8e7d");
+        assert_eq!(bindings[0].logs_command(), "docker logs myapp-web");
+        assert!(response_body("HTTP/1.0 404 Not Found\r\n\r\n{}").is_err());
+
+        let mut port = PortInfo {
+            protocol: "tcp".into(),
+            port: 8080,
+            local_address: "0.0.0.0".into(),
+            pid: 2210,
+            process_name: "docker-proxy".into(),
+            uid: Some(0),
+            ..Default::default()
+        };
+        assert_eq!(binding_for(&port, &bindings), Some(&bindings[0]));
+        port.process_name = "node".into();
+        assert_eq!(binding_for(&port, &bindings), None);
+    }
+}
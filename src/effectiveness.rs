@@ -0,0 +1,409 @@
+/// Módulo de efectividad de los kills.
+///
+/// Matar un proceso no siempre libera su puerto: supervisores como
+/// `nodemon`, `systemd` o `docker` lo relanzan al instante. Tras cada
+/// kill de un puerto concreto se vuelve a escanear a los
+/// [`VERIFY_SECS`] segundos y se anota el resultado en `kills.jsonl`
+/// (directorio de estado):
+///
+/// - `freed` → el puerto quedó libre
+/// - `respawned` → lo volvió a abrir un proceso con el mismo nombre
+/// - `occupied` → lo abrió otro proceso distinto
+/// - `still_open` → el mismo PID sigue escuchando
+///
+/// Agregando los resultados por nombre de proceso se obtiene su
+/// efectividad ("nodemon relanza node el 90% de las veces") y, si se
+/// relanza a menudo, el tray propone detener el proceso padre.
+///
+/// Se verifican los kills del tray, de la API (`POST /kill/{pid}`) y de
+/// las reglas; los del CLI no, porque el comando termina antes.
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::history;
+use crate::i18n::{trf, Text};
+use crate::journal;
+use crate::paths;
+use crate::port_scanner::{self, PortInfo};
+use crate::shutdown;
+
+/// Nombre del registro de resultados dentro del directorio de estado
+const KILLS_FILE: &str = "kills.jsonl";
+
+/// Segundos tras el kill antes de comprobar el puerto
+pub const VERIFY_SECS: u64 = 5;
+
+/// Kills mínimos de un proceso antes de sugerir nada
+const MIN_SAMPLES: usize = 3;
+
+/// Resultado de un kill comprobado tras [`VERIFY_SECS`]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KillResult {
+    /// El puerto quedó libre
+    Freed,
+    /// Un proceso con el mismo nombre volvió a abrir el puerto
+    Respawned,
+    /// Otro proceso ocupó el puerto
+    Occupied,
+    /// El proceso sigue vivo escuchando
+    StillOpen,
+}
+
+/// Proceso a punto de matarse, capturado antes del kill
+#[derive(Debug, Clone, PartialEq)]
+pub struct KillTarget {
+    /// PID que se va a matar
+    pub pid: u32,
+    /// Puerto que ocupa
+    pub port: u16,
+    /// Protocolo del puerto ("tcp" o "udp")
+    pub protocol: String,
+    /// Nombre del proceso
+    pub process_name: String,
+    /// Proceso padre (PID y nombre), si no es init
+    pub parent: Option<(u32, String)>,
+}
+
+impl KillTarget {
+    /// Captura nombre y padre desde `/proc` (tiene que ser antes del kill).
+    ///
+    /// # Returns
+    /// `None` si no hay puerto concreto o el proceso ya no existe.
+    pub fn capture(pid: u32, port: u16, protocol: &str) -> Option<Self> {
+        if pid == 0 || port == 0 {
+            return None;
+        }
+        Some(Self {
+            pid,
+            port,
+            protocol: protocol.to_string(),
+            process_name: process_name(pid)?,
+            parent: parent_of(pid),
+        })
+    }
+}
+
+/// Resultado registrado en `kills.jsonl`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KillOutcome {
+    /// Momento del kill (segundos desde UNIX epoch)
+    pub timestamp: u64,
+    /// PID matado
+    pub pid: u32,
+    /// Puerto que ocupaba
+    pub port: u16,
+    /// Protocolo del puerto
+    pub protocol: String,
+    /// Nombre del proceso matado
+    pub process_name: String,
+    /// Nombre del proceso padre en el momento del kill
+    #[serde(default)]
+    pub parent_name: Option<String>,
+    /// Estado del puerto tras el kill
+    pub result: KillResult,
+    /// PID que ocupa el puerto tras el kill (si lo hay)
+    #[serde(default)]
+    pub new_pid: Option<u32>,
+}
+
+/// Efectividad agregada de los kills de un proceso
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ProcessStats {
+    /// Nombre del proceso
+    pub process_name: String,
+    /// Kills comprobados
+    pub kills: usize,
+    /// Kills que liberaron el puerto
+    pub freed: usize,
+    /// Kills tras los que el proceso se relanzó
+    pub respawned: usize,
+    /// Kills tras los que otro proceso ocupó el puerto
+    pub occupied: usize,
+    /// Kills que no terminaron el proceso
+    pub still_open: usize,
+    /// Padre más frecuente en los kills que acabaron relanzados
+    pub parent_name: Option<String>,
+}
+
+impl ProcessStats {
+    /// Porcentaje de kills que terminaron relanzados.
+    pub fn respawn_rate(&self) -> u8 {
+        (self.respawned * 100 / self.kills.max(1)) as u8
+    }
+
+    /// Indica si conviene detener el padre en lugar del proceso.
+    pub fn respawns_often(&self) -> bool {
+        self.kills >= MIN_SAMPLES && self.respawned * 2 >= self.kills
+    }
+
+    /// Sugerencia legible para el CLI y los logs.
+    pub fn suggestion(&self) -> Option<String> {
+        if !self.respawns_often() {
+            return None;
+        }
+        let parent = self.parent_name.as_deref().unwrap_or("su supervisor");
+        Some(format!(
+            "{} se relanza el {}% de las veces: detén el proceso padre ({})",
+            self.process_name,
+            self.respawn_rate(),
+            parent
+        ))
+    }
+}
+
+/// Propuesta de detener el padre de un proceso que se relanza
+#[derive(Debug, Clone, PartialEq)]
+pub struct RespawnAdvice {
+    /// Porcentaje de kills relanzados
+    pub rate: u8,
+    /// PID actual del padre
+    pub parent_pid: u32,
+    /// Nombre del padre
+    pub parent_name: String,
+}
+
+/// Estadísticas por nombre de proceso
+#[derive(Debug, Clone, Default)]
+pub struct KillStats {
+    by_process: BTreeMap<String, ProcessStats>,
+}
+
+impl KillStats {
+    /// Carga y agrega `kills.jsonl`; vacío si no existe.
+    pub fn load() -> Self {
+        let outcomes: Vec<KillOutcome> = fs::read_to_string(kills_path())
+            .map(|content| {
+                content
+                    .lines()
+                    .filter_map(|line| serde_json::from_str(line).ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self::aggregate(&outcomes)
+    }
+
+    /// Agrega resultados por nombre de proceso.
+    pub fn aggregate(outcomes: &[KillOutcome]) -> Self {
+        let mut by_process: BTreeMap<String, ProcessStats> = BTreeMap::new();
+        let mut parents: BTreeMap<(&str, &str), usize> = BTreeMap::new();
+        for outcome in outcomes {
+            let stats = by_process
+                .entry(outcome.process_name.clone())
+                .or_insert_with(|| ProcessStats {
+                    process_name: outcome.process_name.clone(),
+                    ..Default::default()
+                });
+            stats.kills += 1;
+            match outcome.result {
+                KillResult::Freed => stats.freed += 1,
+                KillResult::Respawned => stats.respawned += 1,
+                KillResult::Occupied => stats.occupied += 1,
+                KillResult::StillOpen => stats.still_open += 1,
+            }
+            if let (KillResult::Respawned, Some(parent)) = (outcome.result, &outcome.parent_name) {
+                *parents
+                    .entry((outcome.process_name.as_str(), parent.as_str()))
+                    .or_default() += 1;
+            }
+        }
+        for stats in by_process.values_mut() {
+            stats.parent_name = parents
+                .iter()
+                .filter(|((name, _), _)| *name == stats.process_name)
+                .max_by_key(|(_, count)| **count)
+                .map(|((_, parent), _)| parent.to_string());
+        }
+        Self { by_process }
+    }
+
+    /// Estadísticas ordenadas por cantidad de kills.
+    pub fn processes(&self) -> Vec<&ProcessStats> {
+        let mut all: Vec<&ProcessStats> = self.by_process.values().collect();
+        all.sort_by_key(|p| std::cmp::Reverse(p.kills));
+        all
+    }
+
+    /// Propone detener el padre actual del dueño del puerto, si suele relanzarse.
+    pub fn advice(&self, port_info: &PortInfo) -> Option<RespawnAdvice> {
+        let stats = self.by_process.get(&port_info.process_name)?;
+        if port_info.pid == 0 || !stats.respawns_often() {
+            return None;
+        }
+        let (parent_pid, parent_name) = parent_of(port_info.pid)?;
+        Some(RespawnAdvice {
+            rate: stats.respawn_rate(),
+            parent_pid,
+            parent_name,
+        })
+    }
+}
+
+/// Ruta del registro de resultados.
+pub fn kills_path() -> PathBuf {
+    paths::state_dir().join(KILLS_FILE)
+}
+
+/// Comprueba en segundo plano si el kill liberó el puerto y lo registra.
+///
+/// La comprobación cuenta como operación en curso hasta que termina (ver
+/// `shutdown`); si el puerto sigue ocupado, como fallida.
+pub fn track(target: KillTarget) {
+    let operation = shutdown::begin(trf(
+        Text::OperationKillCheck,
+        &[&target.process_name, &target.port],
+    ));
+    let spawned = std::thread::Builder::new()
+        .name("kill-check".into())
+        .spawn(move || {
+            let timestamp = history::unix_now();
+            std::thread::sleep(Duration::from_secs(VERIFY_SECS));
+            let (result, new_pid) = classify(&target, &port_scanner::scan_open_ports());
+            if result != KillResult::Freed {
+                log::warn!(
+                    "El kill de {} (PID {}) no liberó el puerto {}: {:?}",
+                    target.process_name,
+                    target.pid,
+                    target.port,
+                    result
+                );
+                operation.fail(trf(Text::PortNotFreed, &[&target.port]));
+            } else {
+                drop(operation);
+            }
+            let outcome = KillOutcome {
+                timestamp,
+                pid: target.pid,
+                port: target.port,
+                protocol: target.protocol,
+                process_name: target.process_name,
+                parent_name: target.parent.map(|(_, name)| name),
+                result,
+                new_pid,
+            };
+            if let Ok(line) = serde_json::to_string(&outcome) {
+                journal::append(&kills_path(), vec![line]);
+            }
+        });
+    if let Err(e) = spawned {
+        log::error!("No se pudo comprobar el kill: {}", e);
+    }
+}
+
+/// Clasifica el estado del puerto tras el kill.
+fn classify(target: &KillTarget, ports: &[PortInfo]) -> (KillResult, Option<u32>) {
+    let owner = ports
+        .iter()
+        .find(|p| p.port == target.port && p.protocol == target.protocol);
+    match owner {
+        None => (KillResult::Freed, None),
+        Some(p) if p.pid == target.pid => (KillResult::StillOpen, Some(p.pid)),
+        Some(p) if p.process_name == target.process_name => (KillResult::Respawned, Some(p.pid)),
+        Some(p) => (KillResult::Occupied, Some(p.pid).filter(|pid| *pid > 0)),
+    }
+}
+
+/// Nombre de un proceso (`/proc/<pid>/comm`).
+fn process_name(pid: u32) -> Option<String> {
+    fs::read_to_string(format!("/proc/{}/comm", pid))
+        .ok()
+        .map(|name| name.trim().to_string())
+}
+
+/// Padre de un proceso, salvo que sea init (PID 1) o el kernel.
+fn parent_of(pid: u32) -> Option<(u32, String)> {
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    let ppid = parse_ppid(&stat)?;
+    if ppid <= 1 {
+        return None;
+    }
+    Some((ppid, process_name(ppid)?))
+}
+
+/// Extrae el PPID de `/proc/<pid>/stat` ("pid (comm) estado ppid ...").
+///
+/// El nombre puede contener espacios y paréntesis, por eso se busca el
+/// último ')'.
+fn parse_ppid(stat: &str) -> Option<u32> {
+    let (_, rest) = stat.rsplit_once(')')?;
+    rest.split_whitespace().nth(1)?.parse().ok()
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outcome(process_name: &str, result: KillResult) -> KillOutcome {
+        KillOutcome {
+            timestamp: 1,
+            pid: 10,
+            port: 3000,
+            protocol: "tcp".into(),
+            process_name: process_name.into(),
+            parent_name: Some("nodemon".into()),
+            result,
+            new_pid: None,
+        }
+    }
+
+    /// Verifica la clasificación del puerto tras el kill
+    #[test]
+    fn test_classify() {
+        let target = KillTarget {
+            pid: 10,
+            port: 3000,
+            protocol: "tcp".into(),
+            process_name: "node".into(),
+            parent: None,
+        };
+        let port = |pid, process_name: &str| PortInfo {
+            protocol: "tcp".into(),
+            port: 3000,
+            local_address: "0.0.0.0".into(),
+            pid,
+            process_name: process_name.into(),
+            ..Default::default()
+        };
+        assert_eq!(classify(&target, &[]), (KillResult::Freed, None));
+        assert_eq!(
+            classify(&target, &[port(10, "node")]).0,
+            KillResult::StillOpen
+        );
+        assert_eq!(
+            classify(&target, &[port(11, "node")]),
+            (KillResult::Respawned, Some(11))
+        );
+        assert_eq!(
+            classify(&target, &[port(12, "python3")]).0,
+            KillResult::Occupied
+        );
+        assert_eq!(parse_ppid("42 (my (weird) app) S 7 42 42"), Some(7));
+    }
+
+    /// Verifica la agregación y la sugerencia de detener el padre
+    #[test]
+    fn test_aggregate() {
+        let mut outcomes: Vec<KillOutcome> = (0..9)
+            .map(|_| outcome("node", KillResult::Respawned))
+            .collect();
+        outcomes.push(outcome("node", KillResult::Freed));
+        outcomes.push(outcome("python3", KillResult::Freed));
+
+        let stats = KillStats::aggregate(&outcomes);
+        let processes = stats.processes();
+        assert_eq!(processes[0].process_name, "node");
+        assert_eq!(processes[0].respawn_rate(), 90);
+        assert_eq!(
+            processes[0].suggestion().as_deref(),
+            Some("node se relanza el 90% de las veces: detén el proceso padre (nodemon)")
+        );
+        assert!(processes[1].suggestion().is_none());
+    }
+}
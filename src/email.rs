@@ -0,0 +1,383 @@
+/// Módulo de alertas por correo.
+///
+/// En servidores donde el correo es la única vía de aviso, el daemon
+/// puede enviar los eventos (puerto abierto o cerrado, proceso terminado
+/// desde la API) por SMTP. Se configura en la sección `[email]` y
+/// requiere compilar con `--features email`:
+///
+/// ```toml
+/// [email]
+/// smtp_host = "smtp.example.com"
+/// smtp_port = 587                  # por defecto, según `security`
+/// security = "starttls"            # starttls (por defecto) | tls | plain
+/// username = "alertas@example.com"
+/// password_file = "/etc/portslayer/smtp-password"
+/// from = "PortSlayer <alertas@example.com>"
+/// to = ["ops@example.com"]
+/// events = ["opened", "killed"]    # por defecto, todos
+/// digest_minutes = 30              # 0 = un correo por escaneo
+/// ```
+///
+/// La contraseña se toma de la variable `PORTSLAYER_SMTP_PASSWORD`, de
+/// `password_file` o de `password`, en ese orden. Con `digest_minutes`
+/// los eventos se acumulan y se envían juntos en un resumen, como mucho
+/// uno por intervalo; si no, cada escaneo con cambios manda un correo. El
+/// texto de cada evento es el de los webhooks (ver `webhooks::message`),
+/// o `template` con los mismos marcadores.
+///
+/// Los envíos se hacen en un hilo en segundo plano, para que un servidor
+/// SMTP lento no retrase los escaneos ni las respuestas de la API.
+use lettre::message::header::ContentType;
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, SmtpTransport, Transport};
+use serde::Deserialize;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::history::{HistoryEvent, HistoryEventKind};
+use crate::webhooks;
+
+/// Variable de entorno con la contraseña SMTP (tiene prioridad)
+const PASSWORD_ENV: &str = "PORTSLAYER_SMTP_PASSWORD";
+
+/// Espera máxima de la conexión y de cada orden SMTP
+const SEND_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Cifrado de la conexión con el servidor SMTP
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpSecurity {
+    /// Conexión en claro que pasa a TLS con `STARTTLS` (puerto 587)
+    #[default]
+    Starttls,
+    /// TLS desde el inicio (puerto 465)
+    Tls,
+    /// Sin cifrar (puerto 25): solo para relays locales
+    Plain,
+}
+
+impl SmtpSecurity {
+    /// Puerto habitual para cada cifrado.
+    fn default_port(self) -> u16 {
+        match self {
+            Self::Starttls => 587,
+            Self::Tls => 465,
+            Self::Plain => 25,
+        }
+    }
+}
+
+/// Sección `[email]` de la configuración
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct EmailConfig {
+    /// Servidor SMTP
+    pub smtp_host: String,
+    /// Puerto del servidor (por defecto, el de `security`)
+    #[serde(default)]
+    pub smtp_port: Option<u16>,
+    /// Cifrado de la conexión
+    #[serde(default)]
+    pub security: SmtpSecurity,
+    /// Usuario SMTP (sin usuario no se autentica)
+    #[serde(default)]
+    pub username: Option<String>,
+    /// Contraseña SMTP en claro (mejor `password_file`)
+    #[serde(default)]
+    pub password: Option<String>,
+    /// Archivo con la contraseña SMTP
+    #[serde(default)]
+    pub password_file: Option<PathBuf>,
+    /// Remitente ("PortSlayer <alertas@example.com>")
+    pub from: String,
+    /// Destinatarios
+    pub to: Vec<String>,
+    /// Eventos que se envían (vacío = todos)
+    #[serde(default)]
+    pub events: Vec<HistoryEventKind>,
+    /// Minutos entre resúmenes (0 = un correo por escaneo)
+    #[serde(default)]
+    pub digest_minutes: u64,
+    /// Texto de cada evento (con los marcadores de los webhooks)
+    #[serde(default)]
+    pub template: Option<String>,
+}
+
+impl EmailConfig {
+    /// Verifica el servidor y las direcciones.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.smtp_host.trim().is_empty() {
+            return Err("Falta el servidor SMTP (smtp_host)".to_string());
+        }
+        if self.to.is_empty() {
+            return Err("El correo no tiene destinatarios (to)".to_string());
+        }
+        for address in std::iter::once(&self.from).chain(&self.to) {
+            address
+                .parse::<Mailbox>()
+                .map_err(|e| format!("Dirección de correo inválida: {} ({})", address, e))?;
+        }
+        Ok(())
+    }
+
+    /// Indica si se envía un tipo de evento.
+    pub fn accepts(&self, kind: HistoryEventKind) -> bool {
+        self.events.is_empty() || self.events.contains(&kind)
+    }
+
+    /// Contraseña SMTP: la de la variable de entorno, la del archivo o la
+    /// de la configuración.
+    fn password(&self) -> Result<String, String> {
+        if let Ok(password) = std::env::var(PASSWORD_ENV) {
+            return Ok(password);
+        }
+        if let Some(path) = &self.password_file {
+            return fs::read_to_string(path)
+                .map(|password| password.trim_end().to_string())
+                .map_err(|e| format!("No se pudo leer {}: {}", path.display(), e));
+        }
+        self.password.clone().ok_or_else(|| {
+            format!(
+                "Falta la contraseña SMTP (password, password_file o {})",
+                PASSWORD_ENV
+            )
+        })
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// Resumen de eventos
+// ─────────────────────────────────────────────────────────────
+
+/// Eventos acumulados para el próximo resumen
+#[derive(Debug, Default)]
+struct Digest {
+    events: Vec<HistoryEvent>,
+    /// Cuándo se acumuló el primero
+    since: Option<Instant>,
+}
+
+impl Digest {
+    /// Acumula eventos.
+    fn push(&mut self, events: impl IntoIterator<Item = HistoryEvent>, now: Instant) {
+        let before = self.events.len();
+        self.events.extend(events);
+        if self.events.len() > before && self.since.is_none() {
+            self.since = Some(now);
+        }
+    }
+
+    /// Saca los eventos acumulados si ya pasó el intervalo desde el primero.
+    fn take_due(&mut self, interval: Duration, now: Instant) -> Option<Vec<HistoryEvent>> {
+        let since = self.since?;
+        if now.duration_since(since) < interval {
+            return None;
+        }
+        self.since = None;
+        Some(std::mem::take(&mut self.events))
+    }
+}
+
+/// Resumen en curso del daemon
+static DIGEST: Mutex<Digest> = Mutex::new(Digest {
+    events: Vec::new(),
+    since: None,
+});
+
+/// Envía por correo los eventos que recibe o, en modo resumen, los
+/// acumula para el próximo.
+///
+/// # Arguments
+/// * `config` - Sección `[email]`, si está configurada
+/// * `events` - Eventos recién ocurridos
+pub fn queue(config: Option<&EmailConfig>, events: &[HistoryEvent]) {
+    let Some(config) = config else {
+        return;
+    };
+    let accepted: Vec<HistoryEvent> = events
+        .iter()
+        .filter(|event| config.accepts(event.kind))
+        .cloned()
+        .collect();
+    if accepted.is_empty() {
+        return;
+    }
+    if config.digest_minutes == 0 {
+        send(config, accepted);
+    } else {
+        let mut digest = DIGEST.lock().unwrap_or_else(|e| e.into_inner());
+        digest.push(accepted, Instant::now());
+    }
+}
+
+/// Envía el resumen acumulado si ya toca (el daemon lo llama en cada
+/// vuelta de su bucle).
+pub fn flush_due(config: Option<&EmailConfig>) {
+    let Some(config) = config.filter(|config| config.digest_minutes > 0) else {
+        return;
+    };
+    let interval = Duration::from_secs(config.digest_minutes * 60);
+    let due = DIGEST
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .take_due(interval, Instant::now());
+    if let Some(events) = due {
+        send(config, events);
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// Envío
+// ─────────────────────────────────────────────────────────────
+
+/// Asunto y cuerpo del correo de unos eventos: un evento por línea, y de
+/// asunto el primero y cuántos más hay ("... (+3)").
+fn compose(config: &EmailConfig, events: &[HistoryEvent], host: &str) -> (String, String) {
+    let lines: Vec<String> = events
+        .iter()
+        .map(|event| webhooks::message(config.template.as_deref(), event, host))
+        .collect();
+    let subject = match lines.as_slice() {
+        [first, rest @ ..] if !rest.is_empty() => format!("{} (+{})", first, rest.len()),
+        [first, ..] => first.clone(),
+        [] => String::new(),
+    };
+    (subject, lines.join("\n") + "\n")
+}
+
+/// Envía en segundo plano un correo con los eventos.
+fn send(config: &EmailConfig, events: Vec<HistoryEvent>) {
+    let config = config.clone();
+    let spawned = std::thread::Builder::new()
+        .name("email".into())
+        .spawn(move || {
+            let (subject, body) = compose(&config, &events, &webhooks::host_name());
+            match deliver(&config, &subject, body) {
+                Ok(()) => log::debug!("Correo enviado: {}", subject),
+                Err(e) => log::error!("No se pudo enviar el correo de alerta: {}", e),
+            }
+        });
+    if let Err(e) = spawned {
+        log::error!("No se pudo lanzar el envío de correo: {}", e);
+    }
+}
+
+/// Arma el correo y lo entrega al servidor SMTP.
+fn deliver(config: &EmailConfig, subject: &str, body: String) -> Result<(), String> {
+    let parse = |address: &str| {
+        address
+            .parse::<Mailbox>()
+            .map_err(|e| format!("Dirección de correo inválida: {} ({})", address, e))
+    };
+    let mut builder = Message::builder()
+        .from(parse(&config.from)?)
+        .subject(subject)
+        .header(ContentType::TEXT_PLAIN);
+    for address in &config.to {
+        builder = builder.to(parse(address)?);
+    }
+    let message = builder
+        .body(body)
+        .map_err(|e| format!("No se pudo armar el correo: {}", e))?;
+
+    let relay = match config.security {
+        SmtpSecurity::Starttls => SmtpTransport::starttls_relay(&config.smtp_host),
+        SmtpSecurity::Tls => SmtpTransport::relay(&config.smtp_host),
+        SmtpSecurity::Plain => Ok(SmtpTransport::builder_dangerous(&config.smtp_host)),
+    };
+    let mut relay = relay
+        .map_err(|e| format!("Servidor SMTP inválido {}: {}", config.smtp_host, e))?
+        .port(
+            config
+                .smtp_port
+                .unwrap_or_else(|| config.security.default_port()),
+        )
+        .timeout(Some(SEND_TIMEOUT));
+    if let Some(username) = &config.username {
+        relay = relay.credentials(Credentials::new(username.clone(), config.password()?));
+    }
+    relay
+        .build()
+        .send(&message)
+        .map(|_| ())
+        .map_err(|e| format!("{}: {}", config.smtp_host, e))
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(kind: HistoryEventKind, port: u16) -> HistoryEvent {
+        HistoryEvent {
+            timestamp: 1791990000,
+            kind,
+            protocol: "tcp".into(),
+            port,
+            pid: 4242,
+            process_name: "node".into(),
+        }
+    }
+
+    fn config() -> EmailConfig {
+        toml::from_str(
+            "smtp_host = \"smtp.example.com\"\nfrom = \"PortSlayer <alertas@example.com>\"\n\
+             to = [\"ops@example.com\"]\nevents = [\"opened\"]\n\
+             template = \"{host}: {port}/{protocol}\"\n",
+        )
+        .unwrap()
+    }
+
+    /// Verifica la validación, el filtro y el texto de los correos
+    #[test]
+    fn test_compose() {
+        let config = config();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.security, SmtpSecurity::Starttls);
+        assert_eq!(config.security.default_port(), 587);
+        assert!(!config.accepts(HistoryEventKind::Killed));
+        let bad = EmailConfig {
+            to: vec!["no-es-un-correo".into()],
+            ..config.clone()
+        };
+        assert!(bad.validate().is_err());
+
+        let (subject, body) = compose(&config, &[event(HistoryEventKind::Opened, 8080)], "prod-1");
+        assert_eq!(subject, "prod-1: 8080/TCP");
+        assert_eq!(body, "prod-1: 8080/TCP\n");
+
+        let events = [
+            event(HistoryEventKind::Opened, 8080),
+            event(HistoryEventKind::Opened, 9090),
+        ];
+        let (subject, body) = compose(&config, &events, "prod-1");
+        assert_eq!(subject, "prod-1: 8080/TCP (+1)");
+        assert_eq!(body.lines().count(), 2);
+    }
+
+    /// Verifica que el resumen se envía solo al cumplirse el intervalo
+    #[test]
+    fn test_digest() {
+        let start = Instant::now();
+        let interval = Duration::from_secs(600);
+        let mut digest = Digest::default();
+        assert!(digest.take_due(interval, start).is_none());
+
+        digest.push([event(HistoryEventKind::Opened, 8080)], start);
+        digest.push(
+            [event(HistoryEventKind::Closed, 8080)],
+            start + Duration::from_secs(300),
+        );
+        assert!(digest
+            .take_due(interval, start + Duration::from_secs(599))
+            .is_none());
+        let events = digest.take_due(interval, start + interval).unwrap();
+        assert_eq!(events.len(), 2);
+        assert!(digest.take_due(interval, start + interval * 2).is_none());
+    }
+}
@@ -0,0 +1,342 @@
+/// Módulo del flujo de eventos en vivo de la API.
+///
+/// El hilo de escaneo del daemon publica aquí los cambios detectados
+/// (puertos abiertos y cerrados, procesos terminados) y cada cliente de
+/// `GET /events` los recibe como Server-Sent Events en JSON, sin tener
+/// que sondear `/ports`. `GET /logs` emite del mismo modo los mensajes
+/// del log (ver `logging`).
+///
+/// Cada evento lleva un `id` creciente. Al reconectar, el navegador (o
+/// cualquier cliente SSE) envía la cabecera `Last-Event-ID` y se
+/// reenvían los eventos posteriores que sigan en el búfer. Si ya no
+/// están (búfer desbordado o daemon reiniciado) se envía un evento
+/// `resync` para que el cliente vuelva a pedir `/ports`.
+///
+/// Los cambios de ajustes (`PATCH /settings`) viajan por el mismo flujo
+/// como eventos `settings`, con los ajustes nuevos como datos.
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::io::{self, Write};
+use std::sync::{Condvar, Mutex};
+use std::time::Duration;
+
+use crate::history::HistoryEvent;
+use crate::logging::{self, LogLine};
+use crate::settings::Settings;
+
+/// Eventos que se conservan para reenviar a clientes que reconectan
+const BUFFER_SIZE: usize = 1000;
+
+/// Intervalo máximo sin enviar nada antes de mandar un heartbeat
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Milisegundos que el cliente debe esperar antes de reconectar
+const RETRY_MS: u64 = 3000;
+
+/// Evento publicado con su identificador de secuencia
+#[derive(Debug, Clone, Serialize)]
+pub struct StreamEvent {
+    /// Identificador creciente (empieza en 1 con cada arranque del daemon)
+    pub id: u64,
+    /// Cambio detectado
+    #[serde(flatten)]
+    pub event: BusEvent,
+}
+
+/// Contenido de un evento del flujo
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum BusEvent {
+    /// Puerto abierto, cerrado o proceso terminado
+    Port(HistoryEvent),
+    /// Ajustes nuevos tras un `PATCH /settings` (evento SSE `settings`)
+    Settings(Settings),
+}
+
+/// Eventos pendientes de leer desde un identificador dado
+#[derive(Debug, Default)]
+pub struct Replay {
+    /// Eventos posteriores al identificador pedido
+    pub events: Vec<StreamEvent>,
+    /// Se perdieron eventos: el cliente debe resincronizarse
+    pub gap: bool,
+}
+
+/// Búfer circular de eventos con su siguiente identificador
+#[derive(Debug)]
+struct Buffer {
+    next_id: u64,
+    events: VecDeque<StreamEvent>,
+}
+
+/// Canal de eventos compartido entre el hilo de escaneo y la API
+#[derive(Debug)]
+pub struct EventBus {
+    buffer: Mutex<Buffer>,
+    changed: Condvar,
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EventBus {
+    /// Crea un canal vacío.
+    pub fn new() -> Self {
+        Self {
+            buffer: Mutex::new(Buffer {
+                next_id: 1,
+                events: VecDeque::new(),
+            }),
+            changed: Condvar::new(),
+        }
+    }
+
+    /// Publica eventos y despierta a los clientes conectados.
+    pub fn publish(&self, events: &[HistoryEvent]) {
+        self.push(events.iter().cloned().map(BusEvent::Port).collect());
+    }
+
+    /// Anuncia los ajustes nuevos a los clientes conectados.
+    pub fn publish_settings(&self, settings: &Settings) {
+        self.push(vec![BusEvent::Settings(settings.clone())]);
+    }
+
+    /// Añade eventos al búfer con identificadores consecutivos.
+    fn push(&self, events: Vec<BusEvent>) {
+        if events.is_empty() {
+            return;
+        }
+        if let Ok(mut buffer) = self.buffer.lock() {
+            for event in events {
+                let id = buffer.next_id;
+                buffer.next_id += 1;
+                buffer.events.push_back(StreamEvent { id, event });
+                if buffer.events.len() > BUFFER_SIZE {
+                    buffer.events.pop_front();
+                }
+            }
+        }
+        self.changed.notify_all();
+    }
+
+    /// Eventos con identificador mayor que `last_id` (0 = solo los nuevos).
+    pub fn since(&self, last_id: u64) -> Replay {
+        match self.buffer.lock() {
+            Ok(buffer) => replay(&buffer, last_id),
+            Err(_) => Replay::default(),
+        }
+    }
+
+    /// Como [`EventBus::since`], pero espera hasta `timeout` si no hay nada nuevo.
+    fn wait_since(&self, last_id: u64, timeout: Duration) -> Replay {
+        let buffer = match self.buffer.lock() {
+            Ok(buffer) => buffer,
+            Err(_) => return Replay::default(),
+        };
+        match self
+            .changed
+            .wait_timeout_while(buffer, timeout, |b| b.next_id <= last_id + 1)
+        {
+            Ok((buffer, _)) => replay(&buffer, last_id),
+            Err(_) => Replay::default(),
+        }
+    }
+
+    /// Identificador del último evento publicado (0 si no hay ninguno).
+    fn last_id(&self) -> u64 {
+        self.buffer.lock().map(|b| b.next_id - 1).unwrap_or(0)
+    }
+}
+
+/// Calcula los eventos a reenviar y si hay un hueco irrecuperable.
+fn replay(buffer: &Buffer, last_id: u64) -> Replay {
+    let oldest = buffer
+        .events
+        .front()
+        .map(|e| e.id)
+        .unwrap_or(buffer.next_id);
+    let gap = last_id > 0 && (last_id + 1 < oldest || last_id >= buffer.next_id);
+    Replay {
+        events: buffer
+            .events
+            .iter()
+            .filter(|e| e.id > last_id)
+            .cloned()
+            .collect(),
+        gap,
+    }
+}
+
+/// Envía los eventos como Server-Sent Events hasta que el cliente se desconecte.
+///
+/// # Arguments
+/// * `stream` - Conexión del cliente (ya leída y autenticada la petición)
+/// * `bus` - Canal de eventos del daemon
+/// * `last_event_id` - Valor de `Last-Event-ID` (`None` en la primera conexión)
+pub fn stream_events<W: Write>(
+    mut stream: W,
+    bus: &EventBus,
+    last_event_id: Option<u64>,
+) -> io::Result<()> {
+    write_header(&mut stream)?;
+
+    // Sin Last-Event-ID solo interesan los eventos a partir de ahora
+    let mut last_id = match last_event_id {
+        Some(id) => {
+            let replay = bus.since(id);
+            if replay.gap {
+                write!(stream, "{}", resync_message(bus.last_id()))?;
+                bus.last_id()
+            } else {
+                write_events(&mut stream, &replay.events)?.unwrap_or(id)
+            }
+        }
+        None => bus.last_id(),
+    };
+    stream.flush()?;
+
+    loop {
+        let replay = bus.wait_since(last_id, HEARTBEAT_INTERVAL);
+        if replay.events.is_empty() {
+            write!(stream, ": heartbeat\n\n")?;
+        } else if let Some(id) = write_events(&mut stream, &replay.events)? {
+            last_id = id;
+        }
+        stream.flush()?;
+    }
+}
+
+/// Envía los mensajes del log como Server-Sent Events hasta que el
+/// cliente se desconecte: primero los que se conservan (o los posteriores
+/// a `Last-Event-ID`) y luego los nuevos.
+pub fn stream_logs<W: Write>(mut stream: W, last_event_id: Option<u64>) -> io::Result<()> {
+    write_header(&mut stream)?;
+    stream.flush()?;
+
+    let mut last_id = last_event_id.unwrap_or(0);
+    loop {
+        let lines = logging::lines_since(last_id, HEARTBEAT_INTERVAL);
+        if lines.is_empty() {
+            write!(stream, ": heartbeat\n\n")?;
+        }
+        for line in &lines {
+            write!(stream, "{}", log_message(line))?;
+            last_id = line.id;
+        }
+        stream.flush()?;
+    }
+}
+
+/// Cabecera de la respuesta SSE y el tiempo de reconexión.
+fn write_header<W: Write>(stream: &mut W) -> io::Result<()> {
+    write!(
+        stream,
+        "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nConnection: keep-alive\r\n\r\nretry: {}\n\n",
+        RETRY_MS
+    )
+}
+
+/// Escribe una tanda de eventos y devuelve el identificador del último.
+fn write_events<W: Write>(stream: &mut W, events: &[StreamEvent]) -> io::Result<Option<u64>> {
+    for event in events {
+        write!(stream, "{}", event_message(event))?;
+    }
+    Ok(events.last().map(|e| e.id))
+}
+
+/// Formatea un evento SSE con su identificador (los de ajustes, con
+/// nombre `settings`).
+fn event_message(event: &StreamEvent) -> String {
+    let data = serde_json::to_string(event).unwrap_or_else(|_| "{}".to_string());
+    match event.event {
+        BusEvent::Port(_) => format!("id: {}\ndata: {}\n\n", event.id, data),
+        BusEvent::Settings(_) => format!("id: {}\nevent: settings\ndata: {}\n\n", event.id, data),
+    }
+}
+
+/// Formatea un mensaje del log como evento SSE `log`.
+fn log_message(line: &LogLine) -> String {
+    let data = serde_json::to_string(line).unwrap_or_else(|_| "{}".to_string());
+    format!("id: {}\nevent: log\ndata: {}\n\n", line.id, data)
+}
+
+/// Evento que pide al cliente volver a cargar la lista completa.
+fn resync_message(last_id: u64) -> String {
+    format!(
+        "id: {}\nevent: resync\ndata: {{\"reason\":\"eventos perdidos, vuelve a pedir /ports\"}}\n\n",
+        last_id
+    )
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::history::HistoryEventKind;
+
+    fn event(port: u16) -> HistoryEvent {
+        HistoryEvent {
+            timestamp: 1,
+            kind: HistoryEventKind::Opened,
+            protocol: "tcp".into(),
+            port,
+            pid: 10,
+            process_name: "node".into(),
+        }
+    }
+
+    /// Verifica el reenvío desde Last-Event-ID y la detección de huecos
+    #[test]
+    fn test_replay_since() {
+        let bus = EventBus::new();
+        let ports: Vec<HistoryEvent> = (0..BUFFER_SIZE as u16 + 5).map(event).collect();
+        bus.publish(&ports);
+
+        let recent = bus.since(BUFFER_SIZE as u64 + 2);
+        assert!(!recent.gap);
+        assert_eq!(
+            recent.events.iter().map(|e| e.id).collect::<Vec<_>>(),
+            vec![
+                BUFFER_SIZE as u64 + 3,
+                BUFFER_SIZE as u64 + 4,
+                BUFFER_SIZE as u64 + 5
+            ]
+        );
+
+        // Los primeros eventos ya salieron del búfer
+        assert!(bus.since(1).gap);
+        // Un id del futuro indica que el daemon se reinició
+        assert!(bus.since(5000).gap);
+        assert!(bus.since(BUFFER_SIZE as u64 + 5).events.is_empty());
+    }
+
+    /// Verifica el formato SSE de un evento
+    #[test]
+    fn test_event_message() {
+        let message = event_message(&StreamEvent {
+            id: 7,
+            event: BusEvent::Port(event(3000)),
+        });
+        assert!(message.starts_with("id: 7\ndata: "));
+        let data = message.lines().nth(1).unwrap().trim_start_matches("data: ");
+        let value: serde_json::Value = serde_json::from_str(data).unwrap();
+        assert_eq!(value["id"], 7);
+        assert_eq!(value["kind"], "opened");
+        assert_eq!(value["port"], 3000);
+
+        let settings = Settings::from_config(&Config::default());
+        let message = event_message(&StreamEvent {
+            id: 8,
+            event: BusEvent::Settings(settings),
+        });
+        assert!(message.starts_with("id: 8\nevent: settings\ndata: {"));
+        assert!(message.contains("\"scan_interval_secs\":10"));
+    }
+}
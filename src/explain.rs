@@ -0,0 +1,355 @@
+/// Módulo de diagnóstico de puertos ocupados.
+///
+/// "Address already in use" (`EADDRINUSE`) no dice quién tiene el
+/// puerto. [`explain_port`] junta todo lo que PortSlayer sabe de él y lo
+/// explica en frases:
+///
+/// - quién escucha (proceso, PID, usuario y dirección) y, si es solo en
+///   una dirección concreta, que otra dirección también choca,
+/// - los sockets en `TIME_WAIT`, que retienen el puerto unos segundos
+///   tras cerrar si el servidor no usa `SO_REUSEADDR` (cuántos y cuándo
+///   expira el último, ver `time_wait`),
+/// - los contenedores de Docker que lo publican (`docker-proxy`, con la
+///   IP y el puerto del contenedor),
+/// - las unidades `.socket` de systemd que lo reservan para activar un
+///   servicio bajo demanda (`systemctl list-sockets`).
+///
+/// Lo usan `portslayer explain <PUERTO>` y `GET /explain/{puerto}`.
+use serde::Serialize;
+use std::fs;
+use std::process::Command;
+
+use portslayer_core::sources::{self, SOURCE_TIMEOUT};
+
+use crate::i18n::{tr, trf, Text};
+use crate::port_scanner::{self, PortInfo, SocketState};
+use crate::time_wait::{self, Lingering};
+
+/// Tipo de hallazgo
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FindingKind {
+    /// Un proceso escucha en el puerto
+    Owner,
+    /// Conexiones cerradas que aún retienen el puerto
+    TimeWait,
+    /// Lo publica un contenedor de Docker
+    Docker,
+    /// Lo reserva una unidad `.socket` de systemd
+    SocketActivation,
+    /// Consejo para evitar el error
+    Hint,
+    /// Nadie lo usa
+    Free,
+}
+
+/// Una causa (o consejo) de que el puerto esté ocupado
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Finding {
+    pub kind: FindingKind,
+    /// Explicación legible
+    pub text: String,
+    /// PID implicado, si lo hay
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
+}
+
+/// Diagnóstico de un puerto
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Explanation {
+    pub port: u16,
+    /// Hay algo que impide escuchar en él
+    pub busy: bool,
+    pub findings: Vec<Finding>,
+    /// Sockets en `TIME_WAIT` del puerto, si los hay
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_wait: Option<Lingering>,
+}
+
+/// Unidad `.socket` de systemd que escucha en un puerto
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct SocketUnit {
+    /// Dirección ("[::]:22", "0.0.0.0:631")
+    listen: String,
+    unit: String,
+    /// Servicio que arranca al recibir una conexión
+    activates: Option<String>,
+}
+
+/// Explica por qué un puerto está ocupado.
+pub fn explain_port(port: u16) -> Explanation {
+    let sockets: Vec<PortInfo> = port_scanner::scan_all_sockets()
+        .into_iter()
+        .filter(|p| p.port == port)
+        .collect();
+    let mut units = list_socket_units(&["list-sockets", "--all", "--no-legend"]);
+    units.extend(list_socket_units(&[
+        "--user",
+        "list-sockets",
+        "--all",
+        "--no-legend",
+    ]));
+    let units: Vec<SocketUnit> = units
+        .into_iter()
+        .filter(|unit| listen_port(&unit.listen) == Some(port))
+        .collect();
+    let lingering = time_wait::for_port(port);
+    explain(port, &sockets, &units, lingering, docker_target)
+}
+
+/// Arma el diagnóstico a partir de los sockets del puerto y las
+/// unidades `.socket` que lo escuchan.
+///
+/// # Arguments
+/// * `lingering` - Sockets en `TIME_WAIT` contados por `ss`
+/// * `docker_target` - Destino ("172.17.0.2:80") de un `docker-proxy` por PID
+fn explain(
+    port: u16,
+    sockets: &[PortInfo],
+    units: &[SocketUnit],
+    lingering: Option<Lingering>,
+    docker_target: impl Fn(u32) -> Option<String>,
+) -> Explanation {
+    let mut findings = Vec::new();
+    let listeners: Vec<&PortInfo> = sockets
+        .iter()
+        .filter(|p| matches!(p.state, SocketState::Listen | SocketState::Unconn))
+        .collect();
+
+    for listener in &listeners {
+        let pid = Some(listener.pid).filter(|pid| *pid > 0);
+        let owner = listener
+            .uid
+            .map(port_scanner::user_name)
+            .unwrap_or_else(|| "?".to_string());
+        let process = match pid {
+            Some(_) => listener.process_name.as_str(),
+            None => tr(Text::UnknownProcess),
+        };
+        let pid_text = pid.map_or_else(|| "?".to_string(), |pid| pid.to_string());
+        let address = if listener.local_address.contains(':') {
+            format!("[{}]:{}", listener.local_address, port)
+        } else {
+            format!("{}:{}", listener.local_address, port)
+        };
+        findings.push(Finding {
+            kind: FindingKind::Owner,
+            text: trf(
+                Text::ExplainOwner,
+                &[
+                    &process,
+                    &pid_text,
+                    &owner,
+                    &listener.protocol.to_uppercase(),
+                    &address,
+                ],
+            ),
+            pid,
+        });
+
+        if listener.process_name == "docker-proxy" {
+            if let Some(target) = pid.and_then(&docker_target) {
+                findings.push(Finding {
+                    kind: FindingKind::Docker,
+                    text: trf(Text::ExplainDocker, &[&target, &port]),
+                    pid,
+                });
+            }
+        } else if listener.process_name == "systemd" && units.is_empty() {
+            findings.push(Finding {
+                kind: FindingKind::SocketActivation,
+                text: tr(Text::ExplainSystemdUnknownUnit).to_string(),
+                pid,
+            });
+        }
+    }
+
+    for unit in units {
+        let activates = unit.activates.as_deref().unwrap_or("—");
+        findings.push(Finding {
+            kind: FindingKind::SocketActivation,
+            text: trf(
+                Text::ExplainSocketUnit,
+                &[&unit.unit, &unit.listen, &activates, &unit.unit],
+            ),
+            pid: None,
+        });
+    }
+
+    let time_wait = lingering.is_some() || sockets.iter().any(|p| p.state == SocketState::TimeWait);
+    if time_wait {
+        let text = match &lingering {
+            Some(Lingering {
+                count,
+                clears_in_secs: Some(secs),
+                ..
+            }) => trf(Text::ExplainTimeWaitCount, &[count, secs]),
+            _ => tr(Text::ExplainTimeWait).to_string(),
+        };
+        findings.push(Finding {
+            kind: FindingKind::TimeWait,
+            text,
+            pid: None,
+        });
+    }
+
+    // Una dirección concreta ocupada también impide escuchar en 0.0.0.0
+    let specific = listeners
+        .iter()
+        .find(|p| !p.listens_on_all_interfaces() && p.netns.is_none());
+    if let Some(listener) = specific {
+        findings.push(Finding {
+            kind: FindingKind::Hint,
+            text: trf(Text::ExplainSpecificAddress, &[&listener.local_address]),
+            pid: None,
+        });
+    }
+
+    let busy = !listeners.is_empty() || !units.is_empty() || time_wait;
+    if !busy {
+        findings.push(Finding {
+            kind: FindingKind::Free,
+            text: trf(Text::ExplainFree, &[&port]),
+            pid: None,
+        });
+    }
+    Explanation {
+        port,
+        busy,
+        findings,
+        time_wait: lingering,
+    }
+}
+
+/// Unidades `.socket` de systemd (`systemctl [--user] list-sockets`).
+fn list_socket_units(args: &[&str]) -> Vec<SocketUnit> {
+    let mut command = Command::new("systemctl");
+    command.args(args);
+    sources::output_with_timeout(&mut command, SOURCE_TIMEOUT)
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| parse_list_sockets(&String::from_utf8_lossy(&output.stdout)))
+        .unwrap_or_default()
+}
+
+/// Interpreta la salida de `systemctl list-sockets --no-legend`:
+/// `[::]:22  ssh.socket  ssh.service`.
+fn parse_list_sockets(text: &str) -> Vec<SocketUnit> {
+    text.lines()
+        .filter_map(|line| {
+            let mut tokens = line.split_whitespace();
+            let listen = tokens.next()?.to_string();
+            let unit = tokens.next()?.to_string();
+            let activates = tokens.next().map(str::to_string);
+            Some(SocketUnit {
+                listen,
+                unit,
+                activates,
+            })
+        })
+        .collect()
+}
+
+/// Puerto de una dirección de escucha ("[::]:22" → 22); `None` para los
+/// sockets Unix.
+fn listen_port(listen: &str) -> Option<u16> {
+    listen.rsplit_once(':')?.1.parse().ok()
+}
+
+/// IP y puerto del contenedor al que reenvía un `docker-proxy`.
+fn docker_target(pid: u32) -> Option<String> {
+    let cmdline = fs::read(format!("/proc/{}/cmdline", pid)).ok()?;
+    let args: Vec<String> = cmdline
+        .split(|b| *b == 0)
+        .map(|arg| String::from_utf8_lossy(arg).into_owned())
+        .collect();
+    parse_docker_proxy(&args)
+}
+
+/// Destino de los argumentos de `docker-proxy` (`-container-ip
+/// 172.17.0.2 -container-port 80`).
+fn parse_docker_proxy(args: &[String]) -> Option<String> {
+    let value = |flag: &str| {
+        let index = args.iter().position(|arg| arg == flag)?;
+        args.get(index + 1).cloned()
+    };
+    Some(format!(
+        "{}:{}",
+        value("-container-ip")?,
+        value("-container-port")?
+    ))
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn socket(address: &str, pid: u32, name: &str, state: SocketState) -> PortInfo {
+        PortInfo {
+            protocol: "tcp".into(),
+            local_address: address.into(),
+            port: 8080,
+            pid,
+            process_name: name.into(),
+            state,
+            ..Default::default()
+        }
+    }
+
+    /// Verifica el diagnóstico con dueño, Docker, TIME_WAIT y systemd
+    #[test]
+    fn test_explain() {
+        let sockets = [
+            socket("127.0.0.1", 4242, "docker-proxy", SocketState::Listen),
+            socket("127.0.0.1", 0, "", SocketState::TimeWait),
+        ];
+        let args: Vec<String> =
+            "docker-proxy -proto tcp -host-port 8080 -container-ip 172.17.0.2 -container-port 80"
+                .split(' ')
+                .map(String::from)
+                .collect();
+        let explanation = explain(8080, &sockets, &[], None, |_| parse_docker_proxy(&args));
+        assert!(explanation.busy);
+        let kinds: Vec<FindingKind> = explanation.findings.iter().map(|f| f.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                FindingKind::Owner,
+                FindingKind::Docker,
+                FindingKind::TimeWait,
+                FindingKind::Hint
+            ]
+        );
+        assert!(explanation.findings[1].text.contains("172.17.0.2:80"));
+
+        let units = parse_list_sockets(
+            "/run/dbus/system_bus_socket dbus.socket dbus.service\n[::]:8080 app.socket app.service\n",
+        );
+        assert_eq!(listen_port(&units[0].listen), None);
+        assert_eq!(listen_port(&units[1].listen), Some(8080));
+        let explanation = explain(8080, &[], &units[1..], None, |_| None);
+        assert!(explanation.busy);
+        assert_eq!(explanation.findings[0].kind, FindingKind::SocketActivation);
+
+        let free = explain(8080, &[], &[], None, |_| None);
+        assert!(!free.busy);
+        assert_eq!(free.findings[0].kind, FindingKind::Free);
+
+        // Solo TIME_WAIT: ocupado, con cuántos y cuándo se libera
+        let lingering = Lingering {
+            port: 8080,
+            count: 23,
+            clears_in_secs: Some(41),
+        };
+        let held = explain(8080, &[], &[], Some(lingering), |_| None);
+        assert!(held.busy);
+        assert_eq!(held.findings.len(), 1);
+        assert_eq!(held.findings[0].kind, FindingKind::TimeWait);
+        assert!(held.findings[0].text.contains("23"));
+        assert!(held.findings[0].text.contains("41"));
+    }
+}
@@ -0,0 +1,202 @@
+/// Módulo de exportación de resultados de escaneo.
+///
+/// Serializa la lista completa de puertos (con sus etiquetas y la
+/// marca de tiempo del escaneo) a JSON o CSV, para adjuntar
+/// instantáneas a tickets de incidentes o procesarlas con otras
+/// herramientas. También exporta eventos del historial.
+use serde_json::json;
+use std::fs;
+use std::path::Path;
+
+use crate::history::{self, HistoryEvent};
+use crate::port_scanner::PortInfo;
+use crate::tags::{self, TagStore};
+
+/// Formatos de exportación soportados
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    /// Documento JSON con `timestamp` y `ports`
+    Json,
+    /// CSV con cabecera, una fila por puerto
+    Csv,
+}
+
+impl ExportFormat {
+    /// Interpreta el nombre de un formato ("json" o "csv").
+    #[cfg(feature = "api")]
+    pub fn parse(name: &str) -> Option<ExportFormat> {
+        match name.to_lowercase().as_str() {
+            "json" => Some(ExportFormat::Json),
+            "csv" => Some(ExportFormat::Csv),
+            _ => None,
+        }
+    }
+
+    /// Tipo MIME del formato, para respuestas HTTP
+    #[cfg(feature = "api")]
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Json => "application/json",
+            ExportFormat::Csv => "text/csv",
+        }
+    }
+}
+
+/// Genera el contenido exportado para una lista de puertos.
+///
+/// # Arguments
+/// * `ports` - Puertos a exportar
+/// * `store` - Etiquetas a incluir junto a cada puerto
+/// * `format` - Formato de salida
+/// * `timestamp` - Momento del escaneo (segundos desde UNIX epoch)
+///
+/// # Returns
+/// Texto listo para escribir en un archivo.
+pub fn render(
+    ports: &[PortInfo],
+    store: &TagStore,
+    format: ExportFormat,
+    timestamp: u64,
+) -> String {
+    match format {
+        ExportFormat::Json => {
+            let annotated: Vec<serde_json::Value> =
+                ports.iter().map(|p| tags::annotate(p, store)).collect();
+            let document = json!({
+                "timestamp": timestamp,
+                "count": annotated.len(),
+                "ports": annotated,
+            });
+            serde_json::to_string_pretty(&document).unwrap_or_default()
+        }
+        ExportFormat::Csv => {
+            let mut out =
+                String::from("timestamp,protocol,port,local_address,pid,process_name,tags\n");
+            for port in ports {
+                let notes: Vec<String> = store
+                    .tags_for(port)
+                    .iter()
+                    .map(|t| t.note.clone())
+                    .collect();
+                out.push_str(&format!(
+                    "{},{},{},{},{},{},{}\n",
+                    timestamp,
+                    csv_field(&port.protocol),
+                    port.port,
+                    csv_field(&port.local_address),
+                    port.pid,
+                    csv_field(&port.process_name),
+                    csv_field(&notes.join("; "))
+                ));
+            }
+            out
+        }
+    }
+}
+
+/// Genera el contenido exportado para eventos del historial.
+///
+/// # Arguments
+/// * `events` - Eventos a exportar (en orden cronológico)
+/// * `format` - Formato de salida
+pub fn render_history(events: &[HistoryEvent], format: ExportFormat) -> String {
+    match format {
+        ExportFormat::Json => {
+            let document = json!({ "count": events.len(), "events": events });
+            serde_json::to_string_pretty(&document).unwrap_or_default()
+        }
+        ExportFormat::Csv => {
+            let mut out = String::from("timestamp,kind,protocol,port,pid,process_name\n");
+            for event in events {
+                out.push_str(&format!(
+                    "{},{},{},{},{},{}\n",
+                    event.timestamp,
+                    event.kind.as_str(),
+                    csv_field(&event.protocol),
+                    event.port,
+                    event.pid,
+                    csv_field(&event.process_name)
+                ));
+            }
+            out
+        }
+    }
+}
+
+/// Escribe una lista de puertos en un archivo con la hora actual.
+///
+/// # Arguments
+/// * `ports` - Puertos a exportar (normalmente un escaneo recién hecho)
+/// * `format` - Formato de salida
+/// * `path` - Archivo destino (se sobrescribe si existe)
+///
+/// # Returns
+/// `Ok(cantidad)` de puertos exportados, o `Err(String)` si falla la escritura.
+pub fn export_ports(
+    ports: &[PortInfo],
+    format: ExportFormat,
+    path: &Path,
+) -> Result<usize, String> {
+    let content = render(ports, &TagStore::load(), format, history::unix_now());
+    fs::write(path, content)
+        .map_err(|e| format!("No se pudo escribir {}: {}", path.display(), e))?;
+    log::info!("{} puertos exportados a {}", ports.len(), path.display());
+    Ok(ports.len())
+}
+
+/// Escapa un campo CSV según RFC 4180 (comillas si contiene `,`, `"` o saltos).
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ports() -> Vec<PortInfo> {
+        vec![PortInfo {
+            protocol: "tcp".into(),
+            port: 8080,
+            local_address: "0.0.0.0".into(),
+            pid: 1234,
+            process_name: "my,app".into(),
+            ..Default::default()
+        }]
+    }
+
+    /// Verifica el formato CSV y el escapado de campos
+    #[test]
+    fn test_render_csv() {
+        let csv = render(
+            &ports(),
+            &TagStore::default(),
+            ExportFormat::Csv,
+            1700000000,
+        );
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            lines[0],
+            "timestamp,protocol,port,local_address,pid,process_name,tags"
+        );
+        assert_eq!(lines[1], "1700000000,tcp,8080,0.0.0.0,1234,\"my,app\",");
+        assert_eq!(csv_field("a\"b"), "\"a\"\"b\"");
+    }
+
+    /// Verifica el documento JSON exportado
+    #[test]
+    fn test_render_json() {
+        let json = render(&ports(), &TagStore::default(), ExportFormat::Json, 42);
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["timestamp"], 42);
+        assert_eq!(value["count"], 1);
+        assert_eq!(value["ports"][0]["pid"], 1234);
+    }
+}
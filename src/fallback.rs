@@ -0,0 +1,174 @@
+/// Módulo del tray alternativo para escritorios sin StatusNotifierItem.
+///
+/// `ksni` necesita un host de StatusNotifierItem; en GNOME sin la
+/// extensión AppIndicator no aparece nada. Cuando ksni avisa de que no
+/// hay `org.kde.StatusNotifierWatcher`, el tray arranca este respaldo:
+/// un ícono XEmbed dibujado por `yad --notification`, con un menú
+/// reducido (cerrar cada puerto, cerrar todos y salir) cuyas acciones
+/// ejecutan el propio CLI (`portslayer kill :PUERTO`). Si el watcher
+/// aparece más tarde, el respaldo se cierra y vuelve el tray normal.
+///
+/// `yad` recibe el ícono, el tooltip y el menú por su entrada estándar
+/// (`--listen`), así que el menú se actualiza tras cada escaneo.
+use std::io::Write;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::shutdown;
+
+/// Programa que dibuja el ícono XEmbed
+const FALLBACK_TOOL: &str = "yad";
+
+/// Acción de `yad` que lo cierra (y con él, PortSlayer)
+const QUIT_ACTION: &str = "quit";
+
+/// Cada cuánto se comprueba si `yad` terminó
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Entrada del menú del tray alternativo
+#[derive(Debug, Clone, PartialEq)]
+pub struct FallbackItem {
+    /// Texto visible
+    pub label: String,
+    /// Programa y argumentos a ejecutar; vacío = salir
+    pub command: Vec<String>,
+}
+
+/// Ícono XEmbed en marcha
+#[derive(Debug)]
+pub struct FallbackTray {
+    /// Proceso de `yad` (compartido con el hilo que vigila su salida)
+    child: Arc<Mutex<Child>>,
+    /// Entrada estándar de `yad` para enviarle cambios
+    stdin: ChildStdin,
+    /// Se detuvo a propósito (watcher de nuevo disponible), no con "Salir"
+    stopped: Arc<AtomicBool>,
+}
+
+impl FallbackTray {
+    /// Lanza el ícono XEmbed.
+    ///
+    /// Si el usuario elige "Salir", `yad` termina y PortSlayer también.
+    ///
+    /// # Returns
+    /// `Err(String)` si `yad` no está instalado.
+    pub fn start(icon: &str) -> Result<Self, String> {
+        let mut child = Command::new(FALLBACK_TOOL)
+            .args(["--notification", "--listen", "--command=menu"])
+            .arg(format!("--image={}", icon))
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| format!("No se pudo lanzar {}: {}", FALLBACK_TOOL, e))?;
+        let stdin = child
+            .stdin
+            .take()
+            .ok_or_else(|| format!("{} sin entrada estándar", FALLBACK_TOOL))?;
+
+        let child = Arc::new(Mutex::new(child));
+        let stopped = Arc::new(AtomicBool::new(false));
+        let (watched, quit) = (Arc::clone(&child), Arc::clone(&stopped));
+        std::thread::spawn(move || loop {
+            std::thread::sleep(POLL_INTERVAL);
+            let exited = watched
+                .lock()
+                .map(|mut child| !matches!(child.try_wait(), Ok(None)))
+                .unwrap_or(true);
+            if exited {
+                if !quit.load(Ordering::Relaxed) {
+                    // Sin menú donde preguntar: se espera a lo pendiente
+                    shutdown::exit(Some(shutdown::WAIT_TIMEOUT));
+                }
+                break;
+            }
+        });
+
+        log::info!("Tray alternativo (XEmbed con {}) iniciado", FALLBACK_TOOL);
+        Ok(Self {
+            child,
+            stdin,
+            stopped,
+        })
+    }
+
+    /// Envía el ícono, el tooltip y el menú actuales.
+    pub fn update(&mut self, icon: &str, tooltip: &str, items: &[FallbackItem]) {
+        let commands = format!(
+            "icon:{}\ntooltip:{}\nmenu:{}\n",
+            icon,
+            tooltip.replace('\n', " · "),
+            render_menu(items)
+        );
+        if let Err(e) = self.stdin.write_all(commands.as_bytes()) {
+            log::error!("No se pudo actualizar el tray alternativo: {}", e);
+        }
+    }
+
+    /// Cierra el ícono sin cerrar PortSlayer.
+    pub fn stop(self) {
+        self.stopped.store(true, Ordering::Relaxed);
+        if let Ok(mut child) = self.child.lock() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+        log::info!("Tray alternativo detenido");
+    }
+}
+
+/// Menú en el formato de `yad`: `etiqueta!acción|etiqueta!acción`.
+///
+/// `yad` separa los argumentos de la acción como una shell, así que se
+/// entrecomillan; de las etiquetas se quitan los separadores.
+fn render_menu(items: &[FallbackItem]) -> String {
+    items
+        .iter()
+        .map(|item| {
+            let label = item.label.replace(['!', '|', '\n'], " ");
+            let action = if item.command.is_empty() {
+                QUIT_ACTION.to_string()
+            } else {
+                let args: Vec<String> = item.command.iter().map(|arg| quote(arg)).collect();
+                args.join(" ")
+            };
+            format!("{}!{}", label, action)
+        })
+        .collect::<Vec<_>>()
+        .join("|")
+}
+
+/// Entrecomilla un argumento para una línea de comandos tipo shell.
+fn quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', "'\\''"))
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verifica el formato del menú de yad
+    #[test]
+    fn test_render_menu() {
+        let items = vec![
+            FallbackItem {
+                label: "⚔️ Cerrar TCP 3000 → node|dev!".into(),
+                command: vec!["/opt/port slayer".into(), "kill".into(), ":3000".into()],
+            },
+            FallbackItem {
+                label: "❌ Salir".into(),
+                command: Vec::new(),
+            },
+        ];
+        assert_eq!(
+            render_menu(&items),
+            "⚔️ Cerrar TCP 3000 → node dev !'/opt/port slayer' 'kill' ':3000'|❌ Salir!quit"
+        );
+        assert_eq!(quote("it's"), "'it'\\''s'");
+    }
+}
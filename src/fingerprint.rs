@@ -0,0 +1,305 @@
+/// Módulo de identificación de servicios por su banner.
+///
+/// El nombre del proceso no siempre dice qué sirve un puerto (`java`,
+/// `python3`, `docker-proxy`...). Con `service_fingerprints = true` en la
+/// configuración, tras cada escaneo se conecta a los puertos TCP que
+/// escuchan y se lee lo que responden:
+///
+/// - los que hablan primero (SSH, SMTP, FTP...) mandan su banner
+///   al conectar: `SSH-2.0-OpenSSH_9.6`,
+/// - a los demás se les envía `PING`: Redis responde `+PONG` (o pide
+///   autenticación) y los servidores HTTP un error con su cabecera
+///   `Server`; si cierran sin responder, se prueba con `HEAD /`.
+///
+/// Los que no saludan al conectar pueden estar esperando un handshake
+/// TLS: antes del `PING` se pide su certificado (ver `tls`) y, si lo
+/// presentan, la huella es `TLS`.
+///
+/// Las sondas corren en un pool de pocos hilos en segundo plano, así que
+/// el escaneo no espera por ellas: el resultado aparece en el siguiente
+/// menú o en la siguiente consulta a la API (`service_fingerprint`). Se
+/// guarda por puerto y PID: un puerto se vuelve a sondear solo si lo abre
+/// otro proceso.
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::{Read, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, TcpStream};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::port_scanner::{PortInfo, SocketState};
+use crate::tls::{self, Certificate};
+
+/// Espera máxima de cada conexión y de cada respuesta
+const PROBE_TIMEOUT: Duration = Duration::from_millis(800);
+
+/// Hilos de sondeo simultáneos como máximo
+const MAX_WORKERS: usize = 4;
+
+/// Bytes leídos de cada respuesta como máximo
+const MAX_BANNER: usize = 512;
+
+/// Longitud máxima de la huella mostrada
+const MAX_FINGERPRINT_LEN: usize = 60;
+
+/// Puerto sondeado: (puerto, PID)
+type ProbeKey = (u16, u32);
+
+/// Resultado del sondeo de un puerto
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Probe {
+    /// Huella del servicio (`None` = no se identificó)
+    service: Option<String>,
+    /// Certificado, si habla TLS
+    certificate: Option<Certificate>,
+}
+
+/// Estado del pool de sondeo
+#[derive(Debug, Default)]
+struct Probes {
+    /// Resultado de cada puerto sondeado
+    results: HashMap<ProbeKey, Probe>,
+    /// Puertos pendientes y la dirección a la que conectar
+    queue: VecDeque<(ProbeKey, SocketAddr)>,
+    /// Hilos en marcha
+    workers: usize,
+}
+
+/// Resultados y cola de sondeo
+static PROBES: Mutex<Option<Probes>> = Mutex::new(None);
+
+/// Encola el sondeo de los puertos TCP que escuchan y aún no se
+/// sondearon, y olvida los que ya no están abiertos.
+pub fn request(ports: &[PortInfo]) {
+    let mut guard = PROBES.lock().unwrap_or_else(|e| e.into_inner());
+    let probes = guard.get_or_insert_with(Probes::default);
+
+    let current: HashSet<ProbeKey> = ports.iter().map(|p| (p.port, p.pid)).collect();
+    probes.results.retain(|key, _| current.contains(key));
+
+    for port in ports {
+        let key = (port.port, port.pid);
+        let pending = probes.queue.iter().any(|(queued, _)| *queued == key);
+        if probes.results.contains_key(&key) || pending {
+            continue;
+        }
+        if let Some(address) = probe_address(port) {
+            probes.queue.push_back((key, address));
+        }
+    }
+
+    while probes.workers < MAX_WORKERS && probes.workers < probes.queue.len() {
+        let spawned = std::thread::Builder::new()
+            .name("fingerprint".into())
+            .spawn(work);
+        match spawned {
+            Ok(_) => probes.workers += 1,
+            Err(e) => {
+                log::error!("No se pudo lanzar el sondeo de servicios: {}", e);
+                break;
+            }
+        }
+    }
+}
+
+/// Resultado del sondeo de un puerto, si ya se sondeó.
+fn result(port: &PortInfo) -> Option<Probe> {
+    let guard = PROBES.lock().unwrap_or_else(|e| e.into_inner());
+    guard
+        .as_ref()
+        .and_then(|probes| probes.results.get(&(port.port, port.pid)).cloned())
+}
+
+/// Huella del servicio de un puerto, si ya se sondeó y se identificó.
+pub fn get(port: &PortInfo) -> Option<String> {
+    result(port).and_then(|probe| probe.service)
+}
+
+/// Certificado TLS de un puerto, si ya se sondeó y habla TLS.
+pub fn certificate(port: &PortInfo) -> Option<Certificate> {
+    result(port).and_then(|probe| probe.certificate)
+}
+
+/// Puertos identificados hasta ahora (para saber si hay que reconstruir
+/// el menú).
+pub fn identified() -> usize {
+    let guard = PROBES.lock().unwrap_or_else(|e| e.into_inner());
+    guard.as_ref().map_or(0, |probes| {
+        probes
+            .results
+            .values()
+            .filter(|probe| probe.service.is_some())
+            .count()
+    })
+}
+
+/// Hilo del pool: sondea puertos de la cola hasta vaciarla.
+fn work() {
+    loop {
+        let next = {
+            let mut guard = PROBES.lock().unwrap_or_else(|e| e.into_inner());
+            let probes = guard.get_or_insert_with(Probes::default);
+            match probes.queue.pop_front() {
+                Some(next) => next,
+                None => {
+                    probes.workers -= 1;
+                    return;
+                }
+            }
+        };
+        let (key, address) = next;
+        let result = probe(address);
+        log::debug!("Servicio en el puerto {}: {:?}", key.0, result.service);
+        let mut guard = PROBES.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(probes) = guard.as_mut() {
+            probes.results.insert(key, result);
+        }
+    }
+}
+
+/// Dirección a la que conectar para sondear un puerto: loopback si
+/// escucha en todas las interfaces.
+///
+/// # Returns
+/// `None` si no es TCP en escucha, es de un contenedor o la dirección
+/// no es utilizable (enlace local con zona).
+pub fn probe_address(port: &PortInfo) -> Option<SocketAddr> {
+    if port.protocol != "tcp" || port.state != SocketState::Listen || port.netns.is_some() {
+        return None;
+    }
+    let ip = match port.local_address.as_str() {
+        "0.0.0.0" | "*" => IpAddr::V4(Ipv4Addr::LOCALHOST),
+        "::" => IpAddr::V6(Ipv6Addr::LOCALHOST),
+        address => address.parse().ok()?,
+    };
+    Some(SocketAddr::new(ip, port.port))
+}
+
+/// Sondea un puerto: banner al conectar, certificado TLS si no saluda,
+/// `PING` y, como último recurso, `HEAD /`.
+fn probe(address: SocketAddr) -> Probe {
+    let Some(greeting) = exchange(address, None) else {
+        return Probe::default();
+    };
+    if let Some(service) = identify(&greeting) {
+        return Probe {
+            service: Some(service),
+            certificate: None,
+        };
+    }
+    if greeting.is_empty() {
+        if let Some(certificate) = tls::inspect(address) {
+            return Probe {
+                service: Some("TLS".to_string()),
+                certificate: Some(certificate),
+            };
+        }
+    }
+    let service = exchange(address, Some(b"PING\r\n"))
+        .and_then(|r| identify(&r))
+        .or_else(|| exchange(address, Some(b"HEAD / HTTP/1.0\r\n\r\n")).and_then(|r| identify(&r)));
+    Probe {
+        service,
+        certificate: None,
+    }
+}
+
+/// Conecta, envía `request` si hay y lee la respuesta.
+///
+/// # Returns
+/// `None` si no se pudo conectar; si no, lo leído (vacío si no respondió).
+fn exchange(address: SocketAddr, request: Option<&[u8]>) -> Option<String> {
+    let mut stream = TcpStream::connect_timeout(&address, PROBE_TIMEOUT).ok()?;
+    stream.set_read_timeout(Some(PROBE_TIMEOUT)).ok()?;
+    stream.set_write_timeout(Some(PROBE_TIMEOUT)).ok()?;
+    if let Some(request) = request {
+        stream.write_all(request).ok()?;
+    }
+    let mut buffer = [0u8; MAX_BANNER];
+    let read = stream.read(&mut buffer).unwrap_or(0);
+    Some(String::from_utf8_lossy(&buffer[..read]).into_owned())
+}
+
+/// Identifica el servicio por su respuesta.
+///
+/// # Returns
+/// `None` si la respuesta está vacía o no es texto reconocible.
+fn identify(response: &str) -> Option<String> {
+    if response.starts_with("+PONG") || response.starts_with("-NOAUTH") {
+        return Some("Redis".to_string());
+    }
+    if response.starts_with("HTTP/") {
+        let server = response
+            .lines()
+            .filter_map(|line| line.split_once(':'))
+            .find(|(name, _)| name.eq_ignore_ascii_case("server"))
+            .map(|(_, value)| value.trim());
+        return Some(match server {
+            Some(server) if !server.is_empty() => format!("HTTP ({})", truncate(server)),
+            _ => "HTTP".to_string(),
+        });
+    }
+    // Banner de texto (SSH, SMTP, FTP...): su primera línea
+    let line = response.lines().next()?.trim();
+    let printable = line.chars().all(|c| !c.is_control());
+    if line.is_empty() || !printable || line.contains('\u{FFFD}') {
+        return None;
+    }
+    Some(truncate(line))
+}
+
+/// Recorta una huella a `MAX_FINGERPRINT_LEN` caracteres.
+fn truncate(text: &str) -> String {
+    if text.chars().count() <= MAX_FINGERPRINT_LEN {
+        text.to_string()
+    } else {
+        let cut: String = text.chars().take(MAX_FINGERPRINT_LEN - 1).collect();
+        format!("{}…", cut)
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    /// Verifica la identificación de banners, Redis y HTTP
+    #[test]
+    fn test_identify() {
+        assert_eq!(
+            identify("SSH-2.0-OpenSSH_9.6p1 Ubuntu-3ubuntu13\r\n").as_deref(),
+            Some("SSH-2.0-OpenSSH_9.6p1 Ubuntu-3ubuntu13")
+        );
+        assert_eq!(identify("+PONG\r\n").as_deref(), Some("Redis"));
+        assert_eq!(
+            identify("-NOAUTH Authentication required.\r\n").as_deref(),
+            Some("Redis")
+        );
+        assert_eq!(
+            identify("HTTP/1.1 400 Bad Request\r\nServer: nginx/1.24.0\r\n\r\n").as_deref(),
+            Some("HTTP (nginx/1.24.0)")
+        );
+        assert_eq!(identify("HTTP/1.0 200 OK\r\n\r\n").as_deref(), Some("HTTP"));
+        assert_eq!(identify(""), None);
+        // Protocolos binarios (MySQL, PostgreSQL): sin huella
+        assert_eq!(identify("J\0\0\0\n8.0.36\0"), None);
+    }
+
+    /// Verifica el sondeo de un servicio que saluda al conectar
+    #[test]
+    fn test_probe_banner() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            stream.write_all(b"220 mail ESMTP Postfix\r\n").unwrap();
+        });
+        assert_eq!(
+            probe(address).service.as_deref(),
+            Some("220 mail ESMTP Postfix")
+        );
+    }
+}
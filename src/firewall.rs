@@ -0,0 +1,760 @@
+/// Módulo de lectura de las reglas de entrada del firewall.
+///
+/// Un puerto en `0.0.0.0` o `::` escucha en todas las interfaces, pero
+/// si el firewall descarta las conexiones nuevas a ese puerto no es
+/// accesible desde fuera. Para distinguirlos se leen las reglas con
+/// `nft list ruleset` (o `iptables -S` si nftables está vacío) y se
+/// evalúan las cadenas base de entrada (`hook input` / `INPUT`) para una
+/// conexión nueva desde cualquier origen:
+///
+/// - las reglas con condiciones que no dependen solo del puerto (estado
+///   de la conexión, interfaz, origen...) no cuentan,
+/// - los saltos (`jump`, `-j cadena`) se siguen, como los de ufw y
+///   firewalld,
+/// - sin regla que decida, manda la política de la cadena.
+///
+/// Leer las reglas necesita privilegios: se intenta sin ellos y con
+/// `sudo -n`. Si no se pueden leer, la accesibilidad queda desconocida.
+/// El resultado se guarda unos segundos para no lanzar comandos en cada
+/// escaneo.
+///
+/// También bloquea puertos sin matar a su dueño ([`block`]): las reglas
+/// van en una tabla propia de nftables (`inet portslayer`) o, sin `nft`,
+/// en una cadena propia de iptables/ip6tables (`PORTSLAYER`), que se
+/// reescriben enteras en cada cambio con `pkexec`. Los bloqueos se
+/// registran en `~/.local/state/portslayer/blocks.json` con el arranque
+/// en que se crearon: tras reiniciar, las reglas ya no existen y el
+/// registro los descarta.
+use nix::unistd::Uid;
+use portslayer_core::sources::{self, SOURCE_TIMEOUT};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::capabilities;
+use crate::history;
+use crate::i18n::{trf, Text};
+use crate::paths;
+use crate::port_scanner::PortInfo;
+
+/// Tiempo durante el que se reutilizan las reglas leídas
+const RULES_TTL: Duration = Duration::from_secs(30);
+
+/// Saltos entre cadenas como máximo (evita bucles)
+const MAX_JUMP_DEPTH: usize = 16;
+
+/// Reglas leídas y momento de la lectura
+type CachedRules = (Instant, Option<Arc<Firewall>>);
+
+/// Última lectura de las reglas
+static RULES: Mutex<Option<CachedRules>> = Mutex::new(None);
+
+/// Qué hace una regla con los paquetes que coinciden
+#[derive(Debug, Clone, PartialEq)]
+enum Verdict {
+    Accept,
+    /// `drop` o `reject`
+    Drop,
+    /// Salto a otra cadena: si no decide, se sigue en esta
+    Jump(String),
+    /// Vuelve a la cadena que saltó
+    Return,
+}
+
+/// Regla de una cadena
+#[derive(Debug, Clone)]
+struct Rule {
+    /// Protocolo ("tcp", "udp"); `None` = cualquiera
+    protocol: Option<String>,
+    /// Rangos de puertos de destino (extremos incluidos); `None` = cualquiera
+    ports: Option<Vec<(u16, u16)>>,
+    /// Tiene condiciones que no dependen solo del puerto
+    conditional: bool,
+    verdict: Verdict,
+}
+
+impl Rule {
+    /// Indica si la regla decide sobre una conexión nueva al puerto.
+    fn applies(&self, protocol: &str, port: u16) -> bool {
+        !self.conditional
+            && self.protocol.as_deref().is_none_or(|p| p == protocol)
+            && self.ports.as_ref().is_none_or(|ranges| {
+                ranges
+                    .iter()
+                    .any(|(start, end)| port >= *start && port <= *end)
+            })
+    }
+}
+
+/// Cadena de reglas
+#[derive(Debug, Clone, Default)]
+struct Chain {
+    /// Política si ninguna regla decide (solo en las cadenas base)
+    accept_by_default: bool,
+    rules: Vec<Rule>,
+}
+
+/// Reglas de entrada del firewall
+#[derive(Debug, Clone, Default)]
+pub struct Firewall {
+    /// Herramienta de la que se leyeron ("nftables" o "iptables")
+    pub backend: &'static str,
+    chains: HashMap<String, Chain>,
+    /// Cadenas base de entrada: un paquete tiene que pasar todas
+    base: Vec<String>,
+}
+
+impl Firewall {
+    /// Indica si el firewall deja pasar conexiones nuevas al puerto.
+    pub fn allows(&self, protocol: &str, port: u16) -> bool {
+        self.base.iter().all(|name| {
+            self.verdict(name, protocol, port, 0).unwrap_or_else(|| {
+                self.chains
+                    .get(name)
+                    .is_none_or(|chain| chain.accept_by_default)
+            })
+        })
+    }
+
+    /// Decisión de una cadena (`None` si ninguna regla decide).
+    fn verdict(&self, chain: &str, protocol: &str, port: u16, depth: usize) -> Option<bool> {
+        let chain = self.chains.get(chain)?;
+        for rule in chain.rules.iter().filter(|r| r.applies(protocol, port)) {
+            match &rule.verdict {
+                Verdict::Accept => return Some(true),
+                Verdict::Drop => return Some(false),
+                Verdict::Return => return None,
+                Verdict::Jump(target) if depth < MAX_JUMP_DEPTH => {
+                    if let Some(decision) = self.verdict(target, protocol, port, depth + 1) {
+                        return Some(decision);
+                    }
+                }
+                Verdict::Jump(_) => {}
+            }
+        }
+        None
+    }
+}
+
+/// Reglas actuales del firewall (en caché durante `RULES_TTL`).
+///
+/// # Returns
+/// `None` si no se pudieron leer (sin privilegios).
+pub fn rules() -> Option<Arc<Firewall>> {
+    let mut cached = RULES.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((at, rules)) = cached.as_ref() {
+        if at.elapsed() < RULES_TTL {
+            return rules.clone();
+        }
+    }
+    let rules = load().map(Arc::new);
+    match &rules {
+        Some(firewall) => log::debug!("Reglas del firewall leídas de {}", firewall.backend),
+        None => log::debug!("No se pudieron leer las reglas del firewall"),
+    }
+    *cached = Some((Instant::now(), rules.clone()));
+    rules
+}
+
+/// Últimas reglas leídas, sin volver a leerlas aunque hayan caducado
+/// (para construir el menú sin lanzar comandos).
+pub fn cached() -> Option<Arc<Firewall>> {
+    let cached = RULES.lock().unwrap_or_else(|e| e.into_inner());
+    cached.as_ref().and_then(|(_, rules)| rules.clone())
+}
+
+/// Indica si un puerto es accesible desde la red: no escucha solo en
+/// loopback ni dentro de un contenedor y el firewall no lo bloquea.
+///
+/// # Returns
+/// `None` si podría serlo pero no se conocen las reglas del firewall.
+pub fn reachable(port: &PortInfo, firewall: Option<&Firewall>) -> Option<bool> {
+    if port.netns.is_some() || port.is_loopback() {
+        return Some(false);
+    }
+    firewall.map(|firewall| firewall.allows(&port.protocol, port.port))
+}
+
+/// Lee las reglas: nftables si tiene cadenas de entrada, si no iptables.
+fn load() -> Option<Firewall> {
+    let nft = read_rules("nft", &["list", "ruleset"]).map(|text| parse_nft(&text));
+    if let Some(firewall) = nft.as_ref().filter(|f| !f.base.is_empty()) {
+        return Some(firewall.clone());
+    }
+    read_rules("iptables", &["-S"])
+        .map(|text| parse_iptables(&text))
+        .or(nft)
+}
+
+/// Salida de una herramienta del firewall, sin privilegios o con `sudo -n`.
+pub fn read_rules(tool: &str, args: &[&str]) -> Option<String> {
+    let mut direct = Command::new(tool);
+    direct.args(args);
+    let mut sudo = Command::new("sudo");
+    sudo.arg("-n").arg(tool).args(args);
+    [direct, sudo].into_iter().find_map(|mut command| {
+        sources::output_with_timeout(&mut command, SOURCE_TIMEOUT)
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| String::from_utf8(output.stdout).ok())
+    })
+}
+
+/// Reemplaza las cadenas entre comillas (comentarios, prefijos de log)
+/// por `_`, para poder separar la regla por espacios.
+fn strip_quoted(line: &str) -> String {
+    let mut result = String::new();
+    let mut quoted = false;
+    for c in line.chars() {
+        match c {
+            '"' if quoted => quoted = false,
+            '"' => {
+                quoted = true;
+                result.push('_');
+            }
+            _ if quoted => {}
+            _ => result.push(c),
+        }
+    }
+    result
+}
+
+/// Interpreta una lista de puertos ("22", "80,443", "6000-6010",
+/// "6000:6010").
+pub fn parse_port_list(text: &str) -> Option<Vec<(u16, u16)>> {
+    text.split(',')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .map(|part| match part.split_once(['-', ':']) {
+            Some((start, end)) => Some((start.parse().ok()?, end.parse().ok()?)),
+            None => part.parse().ok().map(|port| (port, port)),
+        })
+        .collect()
+}
+
+// ─────────────────────────────────────────────────────────────
+// nftables (`nft list ruleset`)
+// ─────────────────────────────────────────────────────────────
+
+/// Interpreta la salida de `nft list ruleset`.
+///
+/// Las cadenas se nombran con su tabla ("inet filter input"), que es
+/// donde se resuelven los saltos.
+fn parse_nft(text: &str) -> Firewall {
+    let mut firewall = Firewall {
+        backend: "nftables",
+        ..Default::default()
+    };
+    let mut table = String::new();
+    let mut current: Option<(String, Chain)> = None;
+
+    for line in text.lines().map(str::trim) {
+        if let Some(name) = line.strip_prefix("table ") {
+            table = name.trim_end_matches('{').trim().to_string();
+        } else if let Some(name) = line.strip_prefix("chain ") {
+            let name = name.trim_end_matches('{').trim();
+            current = Some((format!("{} {}", table, name), Chain::default()));
+        } else if line == "}" {
+            if let Some((name, chain)) = current.take() {
+                firewall.chains.insert(name, chain);
+            }
+        } else if let Some((name, chain)) = current.as_mut() {
+            if line.starts_with("type filter hook input") {
+                chain.accept_by_default = !line.contains("policy drop");
+                firewall.base.push(name.clone());
+            } else if let Some(rule) = parse_nft_rule(line, &table) {
+                chain.rules.push(rule);
+            }
+        }
+    }
+    firewall
+}
+
+/// Interpreta una regla de nftables ("tcp dport { 80, 443 } accept").
+fn parse_nft_rule(line: &str, table: &str) -> Option<Rule> {
+    let line = strip_quoted(line);
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    let mut rule = Rule {
+        protocol: None,
+        ports: None,
+        conditional: false,
+        verdict: Verdict::Return,
+    };
+    let mut verdict = None;
+    let mut i = 0;
+    while i < tokens.len() && verdict.is_none() {
+        match tokens[i] {
+            "tcp" | "udp" | "th" if tokens.get(i + 1) == Some(&"dport") => {
+                if tokens[i] != "th" {
+                    rule.protocol = Some(tokens[i].to_string());
+                }
+                let (ports, used) = parse_nft_ports(&tokens[i + 2..]);
+                rule.conditional |= ports.is_none();
+                rule.ports = ports;
+                i += 2 + used;
+            }
+            "meta" if tokens.get(i + 1) == Some(&"l4proto") => {
+                match tokens.get(i + 2) {
+                    Some(&protocol) if protocol == "tcp" || protocol == "udp" => {
+                        rule.protocol = Some(protocol.to_string());
+                    }
+                    _ => rule.conditional = true,
+                }
+                i += 3;
+            }
+            "counter" => i += 1,
+            "packets" | "bytes" | "comment" => i += 2,
+            "accept" => verdict = Some(Verdict::Accept),
+            "drop" | "reject" => verdict = Some(Verdict::Drop),
+            "return" => verdict = Some(Verdict::Return),
+            "jump" | "goto" => {
+                let target = tokens.get(i + 1)?;
+                verdict = Some(Verdict::Jump(format!("{} {}", table, target)));
+            }
+            _ => {
+                rule.conditional = true;
+                i += 1;
+            }
+        }
+    }
+    rule.verdict = verdict?;
+    Some(rule)
+}
+
+/// Puertos tras `dport`: uno, un rango o un conjunto `{ 80, 443 }`.
+///
+/// # Returns
+/// Los rangos (`None` si no se entienden, ej: `!= 22` o un nombre) y
+/// cuántos tokens se consumieron.
+fn parse_nft_ports(tokens: &[&str]) -> (Option<Vec<(u16, u16)>>, usize) {
+    match tokens.first() {
+        Some(&"{") => {
+            let end = tokens
+                .iter()
+                .position(|t| *t == "}")
+                .unwrap_or(tokens.len());
+            let list = tokens[1..end].concat();
+            (parse_port_list(&list), end + 1)
+        }
+        Some(token) => (parse_port_list(token), 1),
+        None => (None, 0),
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// iptables (`iptables -S`)
+// ─────────────────────────────────────────────────────────────
+
+/// Interpreta la salida de `iptables -S` (solo IPv4).
+fn parse_iptables(text: &str) -> Firewall {
+    let mut firewall = Firewall {
+        backend: "iptables",
+        base: vec!["INPUT".to_string()],
+        ..Default::default()
+    };
+    for line in text.lines() {
+        let line = strip_quoted(line);
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        match tokens.as_slice() {
+            ["-P", "INPUT", policy] => {
+                firewall
+                    .chains
+                    .entry("INPUT".into())
+                    .or_default()
+                    .accept_by_default = *policy == "ACCEPT";
+            }
+            ["-N", name] => {
+                firewall.chains.entry(name.to_string()).or_default();
+            }
+            ["-A", name, rest @ ..] => {
+                if let Some(rule) = parse_iptables_rule(rest) {
+                    firewall
+                        .chains
+                        .entry(name.to_string())
+                        .or_default()
+                        .rules
+                        .push(rule);
+                }
+            }
+            _ => {}
+        }
+    }
+    firewall
+}
+
+/// Interpreta los argumentos de una regla (`-p tcp -m tcp --dport 22 -j ACCEPT`).
+fn parse_iptables_rule(tokens: &[&str]) -> Option<Rule> {
+    let mut rule = Rule {
+        protocol: None,
+        ports: None,
+        conditional: false,
+        verdict: Verdict::Return,
+    };
+    let mut verdict = None;
+    let mut i = 0;
+    while i < tokens.len() {
+        let value = tokens.get(i + 1).copied();
+        match tokens[i] {
+            "-p" => match value {
+                Some("all") => {}
+                Some(protocol) => rule.protocol = Some(protocol.to_string()),
+                None => return None,
+            },
+            "-m" if matches!(value, Some("tcp" | "udp" | "multiport" | "comment")) => {}
+            "--comment" => {}
+            "--dport" | "--dports" => {
+                rule.ports = value.and_then(parse_port_list);
+                rule.conditional |= rule.ports.is_none();
+            }
+            "-j" | "-g" => {
+                verdict = match value? {
+                    "ACCEPT" => Some(Verdict::Accept),
+                    "DROP" | "REJECT" => Some(Verdict::Drop),
+                    "RETURN" => Some(Verdict::Return),
+                    // LOG y demás extensiones no deciden nada
+                    target if target.chars().all(|c| c.is_ascii_uppercase()) => None,
+                    target => Some(Verdict::Jump(target.to_string())),
+                };
+            }
+            _ => {
+                rule.conditional = true;
+                i += 1;
+                continue;
+            }
+        }
+        i += 2;
+    }
+    rule.verdict = verdict?;
+    Some(rule)
+}
+
+// ─────────────────────────────────────────────────────────────
+// Bloqueo de puertos
+// ─────────────────────────────────────────────────────────────
+
+/// Tabla propia de nftables
+const NFT_TABLE: &str = "inet portslayer";
+
+/// Cadena propia de iptables e ip6tables
+const IPTABLES_CHAIN: &str = "PORTSLAYER";
+
+/// Nombre del registro de bloqueos dentro del directorio de estado
+const BLOCKS_FILE: &str = "blocks.json";
+
+/// Identificador del arranque actual del kernel
+const BOOT_ID_PATH: &str = "/proc/sys/kernel/random/boot_id";
+
+/// Puerto bloqueado por PortSlayer
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Block {
+    pub port: u16,
+    /// "tcp" o "udp"
+    pub protocol: String,
+    /// Momento del bloqueo (UNIX)
+    pub since: u64,
+    /// Arranque en el que se creó la regla
+    boot_id: String,
+}
+
+/// Registro de los puertos bloqueados
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct BlockStore {
+    pub blocks: Vec<Block>,
+}
+
+impl BlockStore {
+    /// Ruta del registro de bloqueos.
+    pub fn path() -> PathBuf {
+        paths::state_dir().join(BLOCKS_FILE)
+    }
+
+    /// Carga los bloqueos de este arranque; vacío si no hay registro.
+    pub fn load() -> Self {
+        let store: Self = fs::read_to_string(Self::path())
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        store.of_boot(&boot_id())
+    }
+
+    /// Solo los bloqueos creados en un arranque.
+    fn of_boot(mut self, boot_id: &str) -> Self {
+        self.blocks.retain(|b| b.boot_id == boot_id);
+        self
+    }
+
+    /// Guarda el registro en disco.
+    fn save(&self) -> io::Result<()> {
+        let path = Self::path();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json)
+    }
+
+    /// Indica si un puerto está bloqueado.
+    pub fn is_blocked(&self, port: u16, protocol: &str) -> bool {
+        self.blocks
+            .iter()
+            .any(|b| b.port == port && b.protocol == protocol)
+    }
+}
+
+/// Arranque actual (vacío si no se puede leer).
+fn boot_id() -> String {
+    fs::read_to_string(BOOT_ID_PATH)
+        .map(|id| id.trim().to_string())
+        .unwrap_or_default()
+}
+
+/// Bloquea las conexiones entrantes a un puerto, sin matar a su dueño.
+///
+/// # Returns
+/// `Err(String)` si el protocolo no es válido, no hay `nft` ni
+/// `iptables` o no se pudieron aplicar las reglas.
+pub fn block(port: u16, protocol: &str) -> Result<(), String> {
+    if protocol != "tcp" && protocol != "udp" {
+        return Err(trf(Text::BlockFailed, &[&port, &protocol, &"tcp/udp"]));
+    }
+    let mut store = BlockStore::load();
+    if store.is_blocked(port, protocol) {
+        return Ok(());
+    }
+    store.blocks.push(Block {
+        port,
+        protocol: protocol.to_string(),
+        since: history::unix_now(),
+        boot_id: boot_id(),
+    });
+    apply(&store.blocks).map_err(|e| trf(Text::BlockFailed, &[&port, &protocol, &e]))?;
+    log::info!("Puerto {}/{} bloqueado en el firewall", port, protocol);
+    save_blocks(&store);
+    Ok(())
+}
+
+/// Quita el bloqueo de un puerto.
+///
+/// # Returns
+/// `Ok(false)` si no estaba bloqueado, `Err(String)` si no se pudieron
+/// aplicar las reglas.
+pub fn unblock(port: u16, protocol: &str) -> Result<bool, String> {
+    let mut store = BlockStore::load();
+    if !store.is_blocked(port, protocol) {
+        return Ok(false);
+    }
+    store
+        .blocks
+        .retain(|b| b.port != port || b.protocol != protocol);
+    apply(&store.blocks).map_err(|e| trf(Text::UnblockFailed, &[&port, &protocol, &e]))?;
+    log::info!("Puerto {}/{} desbloqueado", port, protocol);
+    save_blocks(&store);
+    Ok(true)
+}
+
+/// Guarda el registro tras aplicar las reglas y descarta las reglas leídas.
+fn save_blocks(store: &BlockStore) {
+    if let Err(e) = store.save() {
+        log::error!("No se pudo guardar el registro de bloqueos: {}", e);
+    }
+    invalidate();
+}
+
+/// Descarta las reglas leídas para que la próxima consulta las relea
+/// (tras cambiarlas).
+pub fn invalidate() {
+    *RULES.lock().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// Reescribe las reglas propias con los bloqueos indicados.
+fn apply(blocks: &[Block]) -> Result<(), String> {
+    if capabilities::in_path("nft") {
+        run_as_root("nft", &["-f", "-"], Some(&nft_script(blocks)))
+    } else if capabilities::in_path("iptables") {
+        run_as_root("sh", &["-c", &iptables_script(blocks)], None)
+    } else {
+        Err("no hay nft ni iptables".to_string())
+    }
+}
+
+/// Script de `nft -f` que recrea la tabla propia (o la elimina si no
+/// quedan bloqueos). Su cadena corre antes que las del sistema y deja
+/// pasar todo lo demás.
+fn nft_script(blocks: &[Block]) -> String {
+    // "add" no falla si la tabla ya existe: así se puede borrar siempre
+    let mut script = format!("add table {0}\ndelete table {0}\n", NFT_TABLE);
+    if blocks.is_empty() {
+        return script;
+    }
+    script.push_str(&format!(
+        "add table {0}\nadd chain {0} input {{ type filter hook input priority -10; policy accept; }}\n",
+        NFT_TABLE
+    ));
+    for block in blocks {
+        script.push_str(&format!(
+            "add rule {} input {} dport {} drop\n",
+            NFT_TABLE, block.protocol, block.port
+        ));
+    }
+    script
+}
+
+/// Script de shell que recrea la cadena propia en iptables e ip6tables
+/// (o la elimina si no quedan bloqueos).
+fn iptables_script(blocks: &[Block]) -> String {
+    let mut script =
+        String::from("for t in iptables ip6tables; do command -v $t >/dev/null || continue; ");
+    if blocks.is_empty() {
+        script.push_str(&format!(
+            "$t -D INPUT -j {0} 2>/dev/null; $t -F {0} 2>/dev/null; $t -X {0} 2>/dev/null; ",
+            IPTABLES_CHAIN
+        ));
+    } else {
+        script.push_str(&format!(
+            "$t -N {0} 2>/dev/null; $t -F {0} || exit 1; $t -C INPUT -j {0} 2>/dev/null || $t -I INPUT -j {0} || exit 1; ",
+            IPTABLES_CHAIN
+        ));
+        for block in blocks {
+            script.push_str(&format!(
+                "$t -A {} -p {} --dport {} -j DROP || exit 1; ",
+                IPTABLES_CHAIN, block.protocol, block.port
+            ));
+        }
+    }
+    script.push_str("done");
+    script
+}
+
+/// Ejecuta un comando como root: directamente si ya lo somos y, si no,
+/// con `pkexec` (prompt gráfico).
+pub fn run_as_root(program: &str, args: &[&str], stdin: Option<&str>) -> Result<(), String> {
+    let mut command = if Uid::current().is_root() {
+        Command::new(program)
+    } else {
+        let mut pkexec = Command::new("pkexec");
+        pkexec.arg(program);
+        pkexec
+    };
+    let mut child = command
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("{}: {}", program, e))?;
+    if let (Some(mut input), Some(text)) = (child.stdin.take(), stdin) {
+        input
+            .write_all(text.as_bytes())
+            .map_err(|e| e.to_string())?;
+    }
+    let output = child.wait_with_output().map_err(|e| e.to_string())?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verifica la evaluación de un ruleset de nftables con saltos
+    #[test]
+    fn test_parse_nft() {
+        let ruleset = r#"table inet filter {
+	chain input {
+		type filter hook input priority filter; policy drop;
+		ct state established,related accept
+		iif "lo" accept
+		ip saddr 10.0.0.0/8 tcp dport 5432 accept
+		tcp dport 22 counter packets 10 bytes 600 accept comment "ssh"
+		tcp dport { 80, 443, 8000-8010 } accept
+		jump user_input
+	}
+	chain user_input {
+		udp dport 51820 accept
+		tcp dport 3000 drop
+	}
+}
+"#;
+        let firewall = parse_nft(ruleset);
+        assert_eq!(firewall.base, vec!["inet filter input".to_string()]);
+        assert!(firewall.allows("tcp", 22));
+        assert!(firewall.allows("tcp", 443));
+        assert!(firewall.allows("tcp", 8005));
+        assert!(firewall.allows("udp", 51820));
+        assert!(!firewall.allows("udp", 22));
+        assert!(!firewall.allows("tcp", 5432), "solo desde 10.0.0.0/8");
+        assert!(!firewall.allows("tcp", 3000));
+
+        // Sin cadenas de entrada no se filtra nada
+        assert!(parse_nft("table ip nat {\n}\n").allows("tcp", 3000));
+    }
+
+    /// Verifica la evaluación de las reglas de iptables (como las de ufw)
+    #[test]
+    fn test_parse_iptables() {
+        let rules = r#"-P INPUT DROP
+-P FORWARD DROP
+-P OUTPUT ACCEPT
+-N ufw-user-input
+-A INPUT -i lo -j ACCEPT
+-A INPUT -m conntrack --ctstate RELATED,ESTABLISHED -j ACCEPT
+-A INPUT -j LOG --log-prefix "[entrada] "
+-A INPUT -j ufw-user-input
+-A ufw-user-input -p tcp -m tcp --dport 22 -m comment --comment "ssh abierto" -j ACCEPT
+-A ufw-user-input -p udp -m multiport --dports 60000:61000,5353 -j ACCEPT
+"#;
+        let firewall = parse_iptables(rules);
+        assert!(firewall.allows("tcp", 22));
+        assert!(firewall.allows("udp", 60500));
+        assert!(firewall.allows("udp", 5353));
+        assert!(!firewall.allows("tcp", 8080));
+
+        let open = parse_iptables("-P INPUT ACCEPT\n-A INPUT -p tcp --dport 25 -j REJECT\n");
+        assert!(open.allows("tcp", 8080));
+        assert!(!open.allows("tcp", 25));
+    }
+
+    /// Verifica que las reglas de bloqueo se leen como bloqueos y que el
+    /// registro descarta los de otro arranque
+    #[test]
+    fn test_block_rules() {
+        let block = |port: u16, boot_id: &str| Block {
+            port,
+            protocol: "tcp".into(),
+            since: 0,
+            boot_id: boot_id.into(),
+        };
+        let blocks = [block(3000, "")];
+
+        // La tabla tal como la lista `nft list ruleset`
+        let script = nft_script(&blocks);
+        assert!(script.starts_with("add table inet portslayer\ndelete table inet portslayer\n"));
+        let listed = "table inet portslayer {\n\tchain input {\n\t\ttype filter hook input priority -10; policy accept;\n\t\ttcp dport 3000 drop\n\t}\n}\n";
+        assert!(script.contains("tcp dport 3000 drop"));
+        let firewall = parse_nft(listed);
+        assert!(!firewall.allows("tcp", 3000));
+        assert!(firewall.allows("tcp", 3001));
+
+        assert!(iptables_script(&blocks).contains("-A PORTSLAYER -p tcp --dport 3000 -j DROP"));
+        assert!(iptables_script(&[]).contains("-X PORTSLAYER"));
+
+        let store = BlockStore {
+            blocks: vec![block(22, "anterior"), block(80, "actual")],
+        }
+        .of_boot("actual");
+        assert!(store.is_blocked(80, "tcp"));
+        assert!(!store.is_blocked(22, "tcp"));
+    }
+}
@@ -0,0 +1,417 @@
+/// Módulo de integración con ufw y firewalld.
+///
+/// En los escritorios y servidores con ufw (Ubuntu) o firewalld (Fedora,
+/// RHEL) las reglas se gestionan con su herramienta y no a mano con nft:
+/// lo útil es saber si el gestor permite un puerto y poder cambiarlo sin
+/// salir de PortSlayer. Se detecta el gestor activo
+/// (`ufw status verbose`, `firewall-cmd --state`), se leen sus reglas de
+/// entrada y se evalúan para una conexión nueva desde cualquier origen:
+///
+/// - en ufw gana la primera regla que coincide (las que solo valen para
+///   un origen o una interfaz no cuentan) y, sin regla, la política por
+///   defecto de entrada,
+/// - en firewalld cuentan los puertos y servicios de la zona por defecto
+///   y, sin coincidencia, su `target` (normalmente rechazar).
+///
+/// Los cambios van por su CLI con `pkexec`: `ufw allow|deny` (que
+/// actualiza la regla del puerto si ya existía) o `firewall-cmd
+/// --add-port|--remove-port`, en la configuración activa y en la
+/// permanente. Igual que al leer, el resultado se guarda unos segundos.
+use std::fs;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::firewall;
+use crate::i18n::{tr, trf, Text};
+
+/// Tiempo durante el que se reutiliza la configuración leída
+const POLICY_TTL: Duration = Duration::from_secs(30);
+
+/// Directorios con las definiciones de servicios de firewalld (las de
+/// `/etc` tienen prioridad)
+const FIREWALLD_SERVICE_DIRS: [&str; 2] =
+    ["/etc/firewalld/services", "/usr/lib/firewalld/services"];
+
+/// Configuración leída y momento de la lectura
+type CachedPolicy = (Instant, Option<Arc<Policy>>);
+
+/// Última lectura del gestor
+static POLICY: Mutex<Option<CachedPolicy>> = Mutex::new(None);
+
+/// Gestor de firewall reconocido
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Manager {
+    Ufw,
+    Firewalld,
+}
+
+impl Manager {
+    /// Nombre de la herramienta.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Manager::Ufw => "ufw",
+            Manager::Firewalld => "firewalld",
+        }
+    }
+}
+
+/// Regla de entrada del gestor
+#[derive(Debug, Clone, PartialEq)]
+struct ManagerRule {
+    /// Protocolo ("tcp", "udp"); `None` = cualquiera
+    protocol: Option<String>,
+    /// Rangos de puertos (extremos incluidos); `None` = cualquiera
+    ports: Option<Vec<(u16, u16)>>,
+    allow: bool,
+    /// Servicio de firewalld que la define (`None` = puerto suelto)
+    service: Option<String>,
+}
+
+impl ManagerRule {
+    /// Indica si la regla decide sobre el puerto.
+    fn applies(&self, protocol: &str, port: u16) -> bool {
+        self.protocol.as_deref().is_none_or(|p| p == protocol)
+            && self.ports.as_ref().is_none_or(|ranges| {
+                ranges
+                    .iter()
+                    .any(|(start, end)| port >= *start && port <= *end)
+            })
+    }
+}
+
+/// Reglas de entrada del gestor activo
+#[derive(Debug, Clone, PartialEq)]
+pub struct Policy {
+    pub manager: Manager,
+    /// Política si ninguna regla decide
+    pub default_allow: bool,
+    rules: Vec<ManagerRule>,
+}
+
+impl Policy {
+    /// Indica si el gestor permite conexiones nuevas al puerto.
+    pub fn allows(&self, protocol: &str, port: u16) -> bool {
+        self.rule_for(protocol, port)
+            .map_or(self.default_allow, |rule| rule.allow)
+    }
+
+    /// Primera regla que decide sobre el puerto.
+    fn rule_for(&self, protocol: &str, port: u16) -> Option<&ManagerRule> {
+        self.rules.iter().find(|rule| rule.applies(protocol, port))
+    }
+}
+
+/// Configuración del gestor activo (en caché durante `POLICY_TTL`).
+///
+/// # Returns
+/// `None` si no hay ufw ni firewalld activos o no se pudieron leer.
+pub fn policy() -> Option<Arc<Policy>> {
+    let mut cached = POLICY.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((at, policy)) = cached.as_ref() {
+        if at.elapsed() < POLICY_TTL {
+            return policy.clone();
+        }
+    }
+    let policy = load().map(Arc::new);
+    if let Some(policy) = &policy {
+        log::debug!("Gestor de firewall activo: {}", policy.manager.name());
+    }
+    *cached = Some((Instant::now(), policy.clone()));
+    policy
+}
+
+/// Última configuración leída, sin volver a leerla aunque haya caducado
+/// (para construir el menú sin lanzar comandos).
+pub fn cached() -> Option<Arc<Policy>> {
+    let cached = POLICY.lock().unwrap_or_else(|e| e.into_inner());
+    cached.as_ref().and_then(|(_, policy)| policy.clone())
+}
+
+/// Lee la configuración: ufw si está activo, si no firewalld.
+fn load() -> Option<Policy> {
+    if let Some(policy) = firewall::read_rules("ufw", &["status", "verbose"])
+        .as_deref()
+        .and_then(parse_ufw)
+    {
+        return Some(policy);
+    }
+    let running = firewall::read_rules("firewall-cmd", &["--state"])
+        .is_some_and(|state| state.trim() == "running");
+    if !running {
+        return None;
+    }
+    firewall::read_rules("firewall-cmd", &["--list-all"])
+        .map(|text| parse_firewalld(&text, service_ports))
+}
+
+/// Permite o deniega un puerto en el gestor activo.
+///
+/// # Returns
+/// `Err(String)` si el protocolo no es válido, no hay gestor activo, el
+/// puerto lo permite un servicio de firewalld (hay que quitar el
+/// servicio) o no se pudo cambiar la regla.
+pub fn set_allowed(port: u16, protocol: &str, allow: bool) -> Result<(), String> {
+    if protocol != "tcp" && protocol != "udp" {
+        return Err(tr(Text::UnknownProtocol).to_string());
+    }
+    let Some(policy) = policy() else {
+        return Err(tr(Text::NoFirewallManager).to_string());
+    };
+    let manager = policy.manager.name();
+    let spec = format!("{}/{}", port, protocol);
+    let result = match policy.manager {
+        Manager::Ufw => {
+            let action = if allow { "allow" } else { "deny" };
+            firewall::run_as_root("ufw", &[action, &spec], None)
+        }
+        Manager::Firewalld => {
+            if let Some(service) = policy
+                .rule_for(protocol, port)
+                .filter(|rule| rule.allow && !allow)
+                .and_then(|rule| rule.service.as_deref())
+            {
+                return Err(trf(Text::ManagerService, &[&spec, &service]));
+            }
+            let option = format!("--{}-port={}", if allow { "add" } else { "remove" }, spec);
+            let script = format!("firewall-cmd {0} && firewall-cmd --permanent {0}", option);
+            firewall::run_as_root("sh", &["-c", &script], None)
+        }
+    };
+    result.map_err(|e| trf(Text::ManagerFailed, &[&manager, &port, &protocol, &e]))?;
+    log::info!(
+        "Puerto {} {} en {}",
+        spec,
+        if allow { "permitido" } else { "denegado" },
+        manager
+    );
+    *POLICY.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    firewall::invalidate();
+    Ok(())
+}
+
+// ─────────────────────────────────────────────────────────────
+// ufw
+// ─────────────────────────────────────────────────────────────
+
+/// Interpreta la salida de `ufw status verbose`.
+///
+/// # Returns
+/// `None` si ufw está inactivo.
+fn parse_ufw(text: &str) -> Option<Policy> {
+    let mut lines = text.lines();
+    let active = lines
+        .by_ref()
+        .find_map(|line| line.strip_prefix("Status:"))
+        .is_some_and(|status| status.trim() == "active");
+    if !active {
+        return None;
+    }
+
+    let mut policy = Policy {
+        manager: Manager::Ufw,
+        default_allow: false,
+        rules: Vec::new(),
+    };
+    for line in lines {
+        if let Some(defaults) = line.strip_prefix("Default:") {
+            // "deny (incoming), allow (outgoing), disabled (routed)"
+            policy.default_allow = defaults
+                .split(',')
+                .find(|part| part.contains("(incoming)"))
+                .is_some_and(|part| part.trim().starts_with("allow"));
+        } else if let Some(rule) = parse_ufw_rule(line) {
+            policy.rules.push(rule);
+        }
+    }
+    Some(policy)
+}
+
+/// Interpreta una regla: `22/tcp  ALLOW IN  Anywhere`,
+/// `80,443/tcp (v6)  DENY  Anywhere (v6)`...
+///
+/// Las de salida, las de un origen o interfaz concretos y los perfiles
+/// de aplicación (`OpenSSH`) se descartan.
+fn parse_ufw_rule(line: &str) -> Option<ManagerRule> {
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    let mut index = 1;
+    if tokens.get(index) == Some(&"(v6)") {
+        index += 1;
+    }
+    let allow = match *tokens.get(index)? {
+        "ALLOW" | "LIMIT" => true,
+        "DENY" | "REJECT" => false,
+        _ => return None,
+    };
+    index += 1;
+    match tokens.get(index) {
+        Some(&"IN") => index += 1,
+        Some(&"OUT") | Some(&"FWD") => return None,
+        _ => {}
+    }
+    if tokens.get(index) != Some(&"Anywhere") {
+        return None;
+    }
+
+    let (ports, protocol) = match tokens[0].split_once('/') {
+        Some((ports, protocol)) => (ports, Some(protocol.to_string())),
+        None => (tokens[0], None),
+    };
+    let ports = if ports == "Anywhere" {
+        None
+    } else {
+        Some(firewall::parse_port_list(ports)?)
+    };
+    Some(ManagerRule {
+        protocol,
+        ports,
+        allow,
+        service: None,
+    })
+}
+
+// ─────────────────────────────────────────────────────────────
+// firewalld
+// ─────────────────────────────────────────────────────────────
+
+/// Interpreta la salida de `firewall-cmd --list-all` (zona por defecto).
+///
+/// # Arguments
+/// * `service_ports` - Puertos de un servicio por su nombre
+fn parse_firewalld(text: &str, service_ports: impl Fn(&str) -> Vec<(u16, String)>) -> Policy {
+    let mut policy = Policy {
+        manager: Manager::Firewalld,
+        default_allow: false,
+        rules: Vec::new(),
+    };
+    for line in text.lines() {
+        let Some((key, value)) = line.trim().split_once(':') else {
+            continue;
+        };
+        match key {
+            "target" => policy.default_allow = value.trim() == "ACCEPT",
+            "services" => {
+                for service in value.split_whitespace() {
+                    policy.rules.extend(service_ports(service).into_iter().map(
+                        |(port, protocol)| ManagerRule {
+                            protocol: Some(protocol),
+                            ports: Some(vec![(port, port)]),
+                            allow: true,
+                            service: Some(service.to_string()),
+                        },
+                    ));
+                }
+            }
+            "ports" => {
+                for spec in value.split_whitespace() {
+                    let Some((ports, protocol)) = spec.split_once('/') else {
+                        continue;
+                    };
+                    if let Some(ports) = firewall::parse_port_list(ports) {
+                        policy.rules.push(ManagerRule {
+                            protocol: Some(protocol.to_string()),
+                            ports: Some(ports),
+                            allow: true,
+                            service: None,
+                        });
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    policy
+}
+
+/// Puertos de un servicio de firewalld, de su definición XML
+/// (`<port protocol="tcp" port="22"/>`).
+fn service_ports(service: &str) -> Vec<(u16, String)> {
+    FIREWALLD_SERVICE_DIRS
+        .iter()
+        .find_map(|dir| fs::read_to_string(format!("{}/{}.xml", dir, service)).ok())
+        .map(|xml| parse_service_xml(&xml))
+        .unwrap_or_default()
+}
+
+/// Extrae los `<port>` de una definición de servicio.
+fn parse_service_xml(xml: &str) -> Vec<(u16, String)> {
+    let attribute = |tag: &str, name: &str| -> Option<String> {
+        let start = tag.find(&format!("{}=\"", name))? + name.len() + 2;
+        let end = tag[start..].find('"')? + start;
+        Some(tag[start..end].to_string())
+    };
+    xml.split("<port ")
+        .skip(1)
+        .filter_map(|tag| {
+            let tag = &tag[..tag.find('>')?];
+            let protocol = attribute(tag, "protocol")?;
+            let port = attribute(tag, "port")?.parse().ok()?;
+            Some((port, protocol))
+        })
+        .collect()
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verifica la lectura de ufw: orden, origen, v6 y política por defecto
+    #[test]
+    fn test_parse_ufw() {
+        let text = "Status: active\n\
+            Logging: on (low)\n\
+            Default: deny (incoming), allow (outgoing), disabled (routed)\n\
+            New profiles: skip\n\
+            \n\
+            To                         Action      From\n\
+            --                         ------      ----\n\
+            22/tcp                     ALLOW IN    Anywhere\n\
+            8080                       DENY IN     Anywhere\n\
+            6000:6010/tcp              ALLOW IN    Anywhere\n\
+            5432/tcp                   ALLOW IN    192.168.1.0/24\n\
+            OpenSSH                    ALLOW IN    Anywhere\n\
+            8080/tcp                   ALLOW IN    Anywhere\n\
+            22/tcp (v6)                ALLOW IN    Anywhere (v6)\n";
+        let policy = parse_ufw(text).unwrap();
+        assert_eq!(policy.manager, Manager::Ufw);
+        assert!(!policy.default_allow);
+        assert!(policy.allows("tcp", 22));
+        assert!(policy.allows("tcp", 6005));
+        // Gana la primera regla que coincide
+        assert!(!policy.allows("tcp", 8080));
+        // Solo desde una red: no cuenta
+        assert!(!policy.allows("tcp", 5432));
+        assert!(!policy.allows("udp", 22));
+
+        assert_eq!(parse_ufw("Status: inactive\n"), None);
+    }
+
+    /// Verifica la lectura de firewalld con puertos y servicios
+    #[test]
+    fn test_parse_firewalld() {
+        let text = "public (active)\n  target: default\n  interfaces: eth0\n  \
+            services: dhcpv6-client ssh\n  ports: 8080/tcp 6000-6010/udp\n  protocols: \n";
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<service>
+  <short>SSH</short>
+  <port protocol="tcp" port="22"/>
+</service>"#;
+        let policy = parse_firewalld(text, |service| match service {
+            "ssh" => parse_service_xml(xml),
+            _ => Vec::new(),
+        });
+        assert!(!policy.default_allow);
+        assert!(policy.allows("tcp", 22));
+        assert_eq!(
+            policy.rule_for("tcp", 22).unwrap().service.as_deref(),
+            Some("ssh")
+        );
+        assert!(policy.allows("tcp", 8080));
+        assert!(policy.allows("udp", 6001));
+        assert!(!policy.allows("tcp", 6001));
+        assert!(!policy.allows("tcp", 3000));
+    }
+}
@@ -0,0 +1,254 @@
+/// Módulo de salud de los servidores web locales.
+///
+/// Un servidor de desarrollo colgado sigue escuchando: el puerto está
+/// abierto pero cada petición tarda o devuelve 500. Con `http_health =
+/// true` en la configuración, tras cada escaneo se pide `GET /` a los
+/// puertos TCP locales y se anota el código de estado y la latencia,
+/// que el tray muestra junto al puerto ("✅ 200 12ms") y la API en
+/// `health`.
+///
+/// La primera comprobación de cada puerto (y PID) decide si es web: si
+/// no responde con HTTP no se le vuelve a preguntar. Tampoco se pregunta
+/// a los que el sondeo de servicios identificó como otra cosa (ver
+/// `fingerprint`). Los web se comprueban como mucho cada
+/// [`CHECK_INTERVAL`], en un hilo en segundo plano para no retrasar el
+/// escaneo.
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::io::{Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::fingerprint;
+use crate::i18n::{tr, Text};
+use crate::port_scanner::PortInfo;
+
+/// Tiempo mínimo entre comprobaciones de un mismo puerto
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Espera máxima de la conexión y de la respuesta
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Puerto comprobado: (puerto, PID)
+type HealthKey = (u16, u32);
+
+/// Resultado de la última comprobación de un puerto web
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Health {
+    /// Código de estado HTTP (`None` = no respondió a tiempo)
+    pub status: Option<u16>,
+    /// Tiempo hasta la línea de estado (ms)
+    pub latency_ms: u64,
+}
+
+impl Health {
+    /// Marca para el menú: "✅ 200 12ms", "🟡 404 3ms", "❌ 500 8ms" o
+    /// "⏳ sin respuesta".
+    pub fn badge(&self) -> String {
+        match self.status {
+            Some(status) => {
+                let icon = match status {
+                    0..=399 => "✅",
+                    400..=499 => "🟡",
+                    _ => "❌",
+                };
+                format!("{} {} {}ms", icon, status, self.latency_ms)
+            }
+            None => format!("⏳ {}", tr(Text::HealthTimeout)),
+        }
+    }
+}
+
+/// Estado de un puerto comprobado
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Entry {
+    /// No habla HTTP: no se vuelve a comprobar
+    NotWeb,
+    /// Servidor web y su última comprobación
+    Web(Health, Instant),
+}
+
+/// Estado de cada puerto comprobado
+static ENTRIES: Mutex<Option<HashMap<HealthKey, Entry>>> = Mutex::new(None);
+
+/// Hay un hilo de comprobación en marcha
+static CHECKING: AtomicBool = AtomicBool::new(false);
+
+/// Comprueba en segundo plano los puertos web que toca y olvida los que
+/// ya no están abiertos.
+pub fn request(ports: &[PortInfo]) {
+    let due: Vec<(HealthKey, SocketAddr)> = {
+        let mut guard = ENTRIES.lock().unwrap_or_else(|e| e.into_inner());
+        let entries = guard.get_or_insert_with(HashMap::new);
+        let current: HashSet<HealthKey> = ports.iter().map(|p| (p.port, p.pid)).collect();
+        entries.retain(|key, _| current.contains(key));
+
+        ports
+            .iter()
+            .filter(|port| fingerprint::get(port).is_none_or(|service| service.starts_with("HTTP")))
+            .filter(|port| match entries.get(&(port.port, port.pid)) {
+                None => true,
+                Some(Entry::NotWeb) => false,
+                Some(Entry::Web(_, at)) => at.elapsed() >= CHECK_INTERVAL,
+            })
+            .filter_map(|port| Some(((port.port, port.pid), fingerprint::probe_address(port)?)))
+            .collect()
+    };
+    if due.is_empty() || CHECKING.swap(true, Ordering::AcqRel) {
+        return;
+    }
+
+    let spawned = std::thread::Builder::new()
+        .name("http-health".into())
+        .spawn(move || {
+            for (key, address) in due {
+                let was_web = get_entry(key).is_some_and(|e| matches!(e, Entry::Web(..)));
+                let began = Instant::now();
+                // Solo es web si alguna vez respondió con HTTP: uno que
+                // deja de responder sigue siéndolo
+                let entry = match check(address) {
+                    Some(health) if health.status.is_some() || was_web => {
+                        Entry::Web(health, Instant::now())
+                    }
+                    None if was_web => Entry::Web(
+                        Health {
+                            status: None,
+                            latency_ms: began.elapsed().as_millis() as u64,
+                        },
+                        Instant::now(),
+                    ),
+                    _ => Entry::NotWeb,
+                };
+                log::debug!("Salud HTTP del puerto {}: {:?}", key.0, entry);
+                let mut guard = ENTRIES.lock().unwrap_or_else(|e| e.into_inner());
+                guard.get_or_insert_with(HashMap::new).insert(key, entry);
+            }
+            CHECKING.store(false, Ordering::Release);
+        });
+    if let Err(e) = spawned {
+        log::error!("No se pudo lanzar la comprobación HTTP: {}", e);
+        CHECKING.store(false, Ordering::Release);
+    }
+}
+
+/// Estado de un puerto comprobado.
+fn get_entry(key: HealthKey) -> Option<Entry> {
+    let guard = ENTRIES.lock().unwrap_or_else(|e| e.into_inner());
+    guard.as_ref()?.get(&key).copied()
+}
+
+/// Última comprobación de un puerto web.
+pub fn get(port: &PortInfo) -> Option<Health> {
+    match get_entry((port.port, port.pid))? {
+        Entry::Web(health, _) => Some(health),
+        Entry::NotWeb => None,
+    }
+}
+
+/// Resultados de todos los puertos web (para saber si hay que
+/// reconstruir el menú).
+pub fn snapshot() -> Vec<(u16, u32, Option<u16>)> {
+    let guard = ENTRIES.lock().unwrap_or_else(|e| e.into_inner());
+    let mut results: Vec<(u16, u32, Option<u16>)> = guard
+        .iter()
+        .flatten()
+        .filter_map(|(key, entry)| match entry {
+            Entry::Web(health, _) => Some((key.0, key.1, health.status)),
+            Entry::NotWeb => None,
+        })
+        .collect();
+    results.sort();
+    results
+}
+
+/// Pide `GET /` a un puerto.
+///
+/// # Returns
+/// `None` si no se pudo conectar o respondió algo que no es HTTP; con
+/// `status: None` si aceptó pero no respondió a tiempo.
+fn check(address: SocketAddr) -> Option<Health> {
+    let began = Instant::now();
+    let mut stream = TcpStream::connect_timeout(&address, REQUEST_TIMEOUT).ok()?;
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT)).ok()?;
+    stream.set_write_timeout(Some(REQUEST_TIMEOUT)).ok()?;
+    let host = match address {
+        SocketAddr::V4(_) => "127.0.0.1".to_string(),
+        SocketAddr::V6(_) => "[::1]".to_string(),
+    };
+    let request = format!(
+        "GET / HTTP/1.1\r\nHost: {}:{}\r\nUser-Agent: portslayer\r\nConnection: close\r\n\r\n",
+        host,
+        address.port()
+    );
+    stream.write_all(request.as_bytes()).ok()?;
+
+    let mut buffer = [0u8; 64];
+    let read = match stream.read(&mut buffer) {
+        Ok(read) => read,
+        Err(e)
+            if matches!(
+                e.kind(),
+                std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+            ) =>
+        {
+            return Some(Health {
+                status: None,
+                latency_ms: began.elapsed().as_millis() as u64,
+            });
+        }
+        Err(_) => return None,
+    };
+    let status = parse_status_line(&String::from_utf8_lossy(&buffer[..read]))?;
+    Some(Health {
+        status: Some(status),
+        latency_ms: began.elapsed().as_millis() as u64,
+    })
+}
+
+/// Código de estado de una respuesta HTTP ("HTTP/1.1 200 OK" → 200).
+fn parse_status_line(response: &str) -> Option<u16> {
+    let line = response.strip_prefix("HTTP/")?.lines().next()?;
+    line.split_whitespace().nth(1)?.parse().ok()
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+
+    /// Verifica la comprobación de un servidor web y de uno que no lo es
+    #[test]
+    fn test_check() {
+        assert_eq!(
+            parse_status_line("HTTP/1.1 503 Service Unavailable\r\n"),
+            Some(503)
+        );
+        assert_eq!(parse_status_line("SSH-2.0-OpenSSH_9.6\r\n"), None);
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        std::thread::spawn(move || {
+            for (index, stream) in listener.incoming().take(2).enumerate() {
+                let mut stream = stream.unwrap();
+                let mut request = [0u8; 256];
+                let _ = stream.read(&mut request);
+                let reply: &[u8] = if index == 0 {
+                    b"HTTP/1.1 500 Internal Server Error\r\nContent-Length: 0\r\n\r\n"
+                } else {
+                    b"+PONG\r\n"
+                };
+                stream.write_all(reply).unwrap();
+            }
+        });
+        let health = check(address).unwrap();
+        assert_eq!(health.status, Some(500));
+        assert!(health.badge().starts_with("❌ 500 "));
+        assert_eq!(check(address), None);
+    }
+}
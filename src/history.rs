@@ -0,0 +1,591 @@
+/// Módulo de historial de eventos de puertos.
+///
+/// Registra en un archivo JSON Lines (`history.jsonl` dentro del
+/// directorio de estado) los puertos que se abren, los que se cierran
+/// y los procesos terminados desde PortSlayer. Cada línea es un
+/// `HistoryEvent` independiente, lo que permite añadir eventos sin
+/// reescribir el archivo. Las escrituras pasan por el escritor en
+/// segundo plano de `journal` para no frenar los escaneos.
+///
+/// Para que el archivo no crezca sin límite en máquinas que no se
+/// reinician, se poda automáticamente al superar `max_size_kb` (sección
+/// `[history]` de `config.toml`) y se puede compactar a mano con
+/// `portslayer history compact`.
+///
+/// Reproduciendo los eventos hasta un momento dado se reconstruye la
+/// lista de puertos de ese instante ("¿qué escuchaba cuando falló el
+/// test a las 14:32?"), ver [`ports_at`]. Comparando dos de esas listas
+/// (o dos escaneos) se obtiene qué cambió entre ellas, ver [`diff_scans`]
+/// y [`changes_since`].
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(feature = "api")]
+use crate::journal;
+use crate::paths;
+use crate::port_scanner::{PortInfo, PortOrigin, SocketState};
+
+/// Nombre del archivo de historial dentro del directorio de estado
+const HISTORY_FILE: &str = "history.jsonl";
+
+/// Tamaño máximo por defecto del historial antes de podarlo (KiB)
+const DEFAULT_MAX_SIZE_KB: u64 = 5 * 1024;
+
+/// Sección `[history]` de la configuración
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct HistoryConfig {
+    /// Tamaño a partir del cual se poda el archivo (KiB)
+    pub max_size_kb: u64,
+    /// Días de eventos que se conservan al compactar (`None` = todos)
+    pub retention_days: Option<u64>,
+}
+
+impl Default for HistoryConfig {
+    fn default() -> Self {
+        Self {
+            max_size_kb: DEFAULT_MAX_SIZE_KB,
+            retention_days: None,
+        }
+    }
+}
+
+/// Resultado de una compactación
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CompactStats {
+    /// Eventos conservados
+    pub kept: usize,
+    /// Líneas corruptas eliminadas
+    pub corrupt: usize,
+    /// Eventos más antiguos que `retention_days`
+    pub expired: usize,
+    /// Eventos antiguos eliminados para respetar `max_size_kb`
+    pub pruned: usize,
+}
+
+/// Tipo de evento registrado en el historial
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HistoryEventKind {
+    /// Un puerto nuevo empezó a escuchar
+    Opened,
+    /// Un puerto dejó de escuchar
+    Closed,
+    /// Se terminó un proceso desde PortSlayer
+    Killed,
+}
+
+impl HistoryEventKind {
+    /// Nombre del tipo tal como se guarda en el historial.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HistoryEventKind::Opened => "opened",
+            HistoryEventKind::Closed => "closed",
+            HistoryEventKind::Killed => "killed",
+        }
+    }
+}
+
+/// Evento individual del historial
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEvent {
+    /// Momento del evento (segundos desde UNIX epoch)
+    pub timestamp: u64,
+    /// Tipo de evento
+    pub kind: HistoryEventKind,
+    /// Protocolo del puerto (tcp, udp; vacío en kills por PID)
+    pub protocol: String,
+    /// Número del puerto (0 si no aplica)
+    pub port: u16,
+    /// PID del proceso involucrado (0 si no se conoce)
+    pub pid: u32,
+    /// Nombre del proceso involucrado
+    pub process_name: String,
+}
+
+impl HistoryEvent {
+    /// Crea un evento a partir de la información de un puerto.
+    ///
+    /// # Arguments
+    /// * `kind` - Tipo de evento
+    /// * `port_info` - Puerto al que se refiere el evento
+    pub fn from_port(kind: HistoryEventKind, port_info: &PortInfo) -> Self {
+        Self {
+            timestamp: unix_now(),
+            kind,
+            protocol: port_info.protocol.clone(),
+            port: port_info.port,
+            pid: port_info.pid,
+            process_name: port_info.process_name.clone(),
+        }
+    }
+}
+
+/// Segundos transcurridos desde UNIX epoch.
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Ruta del archivo de historial.
+pub fn history_path() -> PathBuf {
+    paths::state_dir().join(HISTORY_FILE)
+}
+
+/// Añade eventos al final del archivo de historial.
+///
+/// La escritura se hace en segundo plano (ver `journal`): la llamada no
+/// espera al disco. Los errores de escritura se registran en el log pero
+/// no llegan al llamador: perder una entrada del historial no debe
+/// impedir escanear ni matar procesos.
+///
+/// # Arguments
+/// * `events` - Eventos a persistir
+#[cfg(feature = "api")]
+pub fn append_events(events: &[HistoryEvent]) {
+    let lines = events
+        .iter()
+        .filter_map(|event| serde_json::to_string(event).ok())
+        .collect();
+    journal::append(&history_path(), lines);
+}
+
+/// Lee los eventos más recientes del historial.
+///
+/// Las líneas corruptas se ignoran silenciosamente.
+///
+/// # Arguments
+/// * `limit` - Cantidad máxima de eventos a devolver (los más recientes)
+///
+/// # Returns
+/// Vector de eventos en orden cronológico.
+pub fn read_events(limit: usize) -> Vec<HistoryEvent> {
+    let content = match fs::read_to_string(history_path()) {
+        Ok(content) => content,
+        Err(_) => return Vec::new(),
+    };
+
+    let events: Vec<HistoryEvent> = content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+
+    let skip = events.len().saturating_sub(limit);
+    events.into_iter().skip(skip).collect()
+}
+
+/// Lee los eventos ocurridos desde un momento dado.
+///
+/// # Arguments
+/// * `since` - Momento mínimo (segundos desde UNIX epoch; 0 = todos)
+pub fn read_since(since: u64) -> Vec<HistoryEvent> {
+    read_events(usize::MAX)
+        .into_iter()
+        .filter(|event| event.timestamp >= since)
+        .collect()
+}
+
+/// Reconstruye los puertos abiertos en un momento pasado.
+///
+/// Solo conoce lo que registró el daemon: lo podado del historial o lo
+/// ocurrido con el daemon detenido no aparece (al arrancar, el daemon
+/// registra las diferencias con el último estado conocido).
+///
+/// # Arguments
+/// * `timestamp` - Momento a reconstruir (segundos desde UNIX epoch)
+///
+/// # Returns
+/// Puertos ordenados por número, sin dirección local (no se registra).
+pub fn ports_at(timestamp: u64) -> Vec<PortInfo> {
+    replay_ports(&read_events(usize::MAX), timestamp)
+}
+
+/// Aplica en orden los eventos hasta `timestamp` (incluido).
+///
+/// Un puerto se identifica por `(protocolo, puerto)` como en
+/// [`scan_changes`]; un kill cierra todos los puertos de ese PID.
+pub fn replay_ports(events: &[HistoryEvent], timestamp: u64) -> Vec<PortInfo> {
+    let mut open: BTreeMap<(u16, String), PortInfo> = BTreeMap::new();
+    for event in events.iter().take_while(|e| e.timestamp <= timestamp) {
+        match event.kind {
+            HistoryEventKind::Opened => {
+                open.insert(
+                    (event.port, event.protocol.clone()),
+                    PortInfo {
+                        protocol: event.protocol.clone(),
+                        port: event.port,
+                        local_address: String::new(),
+                        pid: event.pid,
+                        process_name: event.process_name.clone(),
+                        netns: None,
+                        uid: None,
+                        approximate: false,
+                        started_at: None,
+                        queue: None,
+                        origin: PortOrigin::Linux,
+                        state: SocketState::open_for(&event.protocol),
+                    },
+                );
+            }
+            HistoryEventKind::Closed => {
+                open.remove(&(event.port, event.protocol.clone()));
+            }
+            HistoryEventKind::Killed => open.retain(|_, p| p.pid != event.pid),
+        }
+    }
+    open.into_values().collect()
+}
+
+/// Compacta el archivo de historial.
+///
+/// Elimina las líneas corruptas y los eventos más antiguos que
+/// `retention_days`; si aun así supera `max_size_kb`, descarta los más
+/// antiguos hasta dejarlo en 3/4 del límite (para no podar en cada
+/// escaneo). El archivo se reescribe de forma atómica.
+///
+/// Si el daemon o el tray están en marcha, las líneas que escriban
+/// mientras se compacta desde otro proceso pueden perderse.
+pub fn compact(config: &HistoryConfig) -> io::Result<CompactStats> {
+    compact_file(&history_path(), config, unix_now())
+}
+
+/// Poda el historial en segundo plano si supera el tamaño máximo.
+///
+/// Se llama en cada vuelta del daemon, haya escaneo o no; la compactación
+/// se encola en el escritor de `journal` para no competir con las
+/// escrituras pendientes.
+#[cfg(feature = "api")]
+pub fn prune_if_needed(config: &HistoryConfig) {
+    let path = history_path();
+    let size = fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+    if size <= config.max_size_kb * 1024 {
+        return;
+    }
+
+    let config = *config;
+    journal::run(move || match compact_file(&path, &config, unix_now()) {
+        Ok(stats) => log::info!(
+            "Historial podado: {} eventos conservados, {} eliminados",
+            stats.kept,
+            stats.corrupt + stats.expired + stats.pruned
+        ),
+        Err(e) => log::error!("No se pudo podar el historial: {}", e),
+    });
+}
+
+/// Compacta un archivo de historial concreto.
+fn compact_file(path: &Path, config: &HistoryConfig, now: u64) -> io::Result<CompactStats> {
+    let content = match fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(CompactStats::default()),
+        Err(e) => return Err(e),
+    };
+
+    let (lines, stats) = compact_lines(&content, config, now);
+    let tmp = path.with_extension("jsonl.tmp");
+    let mut output = lines.join("\n");
+    if !output.is_empty() {
+        output.push('\n');
+    }
+    fs::write(&tmp, output)?;
+    fs::rename(&tmp, path)?;
+    Ok(stats)
+}
+
+/// Selecciona las líneas que sobreviven a la compactación (en orden).
+fn compact_lines<'a>(
+    content: &'a str,
+    config: &HistoryConfig,
+    now: u64,
+) -> (Vec<&'a str>, CompactStats) {
+    let mut stats = CompactStats::default();
+    let cutoff = config
+        .retention_days
+        .map(|days| now.saturating_sub(days * 24 * 3600));
+
+    let mut lines: Vec<&str> = Vec::new();
+    for line in content.lines() {
+        match serde_json::from_str::<HistoryEvent>(line) {
+            Ok(event) if cutoff.is_some_and(|cutoff| event.timestamp < cutoff) => {
+                stats.expired += 1
+            }
+            Ok(_) => lines.push(line),
+            Err(_) => stats.corrupt += 1,
+        }
+    }
+
+    // Conservar los más recientes que quepan en 3/4 del tamaño máximo
+    let max_bytes = config.max_size_kb * 1024;
+    let total: u64 = lines.iter().map(|l| l.len() as u64 + 1).sum();
+    if total > max_bytes {
+        let target = max_bytes * 3 / 4;
+        let mut size = 0;
+        let keep = lines
+            .iter()
+            .rev()
+            .take_while(|l| {
+                size += l.len() as u64 + 1;
+                size <= target
+            })
+            .count();
+        stats.pruned = lines.len() - keep;
+        lines.drain(..stats.pruned);
+    }
+
+    stats.kept = lines.len();
+    (lines, stats)
+}
+
+// ─────────────────────────────────────────────────────────────
+// Diferencias entre escaneos
+// ─────────────────────────────────────────────────────────────
+
+/// Puerto que siguió abierto pero cambió de proceso
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PidChange {
+    pub protocol: String,
+    pub port: u16,
+    pub before_pid: u32,
+    pub before_process: String,
+    pub after_pid: u32,
+    pub after_process: String,
+}
+
+/// Qué cambió entre dos escaneos
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ScanDiff {
+    /// Puertos que no estaban antes
+    pub opened: Vec<PortInfo>,
+    /// Puertos que ya no están
+    pub closed: Vec<PortInfo>,
+    /// Puertos que siguen abiertos con otro PID
+    pub pid_changes: Vec<PidChange>,
+}
+
+impl ScanDiff {
+    /// Indica si no cambió nada.
+    pub fn is_empty(&self) -> bool {
+        self.opened.is_empty() && self.closed.is_empty() && self.pid_changes.is_empty()
+    }
+}
+
+/// Compara dos escaneos.
+///
+/// Un puerto se identifica por `(protocolo, puerto)`, igual que en
+/// la deduplicación de `port_scanner::scan_open_ports`.
+///
+/// # Arguments
+/// * `before` - Escaneo anterior
+/// * `after` - Escaneo más reciente
+pub fn diff_scans(before: &[PortInfo], after: &[PortInfo]) -> ScanDiff {
+    let index = |ports: &[PortInfo]| -> HashMap<(String, u16), PortInfo> {
+        ports
+            .iter()
+            .map(|p| ((p.protocol.clone(), p.port), p.clone()))
+            .collect()
+    };
+    let previous = index(before);
+    let current = index(after);
+
+    let mut diff = ScanDiff::default();
+    for port in after {
+        match previous.get(&(port.protocol.clone(), port.port)) {
+            None => diff.opened.push(port.clone()),
+            Some(old) if old.pid != port.pid => diff.pid_changes.push(PidChange {
+                protocol: port.protocol.clone(),
+                port: port.port,
+                before_pid: old.pid,
+                before_process: old.process_name.clone(),
+                after_pid: port.pid,
+                after_process: port.process_name.clone(),
+            }),
+            Some(_) => {}
+        }
+    }
+    diff.closed = before
+        .iter()
+        .filter(|p| !current.contains_key(&(p.protocol.clone(), p.port)))
+        .cloned()
+        .collect();
+    diff
+}
+
+/// Qué cambió desde un momento pasado hasta ahora, según el historial
+/// (ver [`ports_at`]).
+///
+/// # Arguments
+/// * `since` - Momento de partida (segundos desde UNIX epoch)
+pub fn changes_since(since: u64) -> ScanDiff {
+    let events = read_events(usize::MAX);
+    diff_scans(
+        &replay_ports(&events, since),
+        &replay_ports(&events, u64::MAX),
+    )
+}
+
+/// Calcula los eventos de apertura y cierre entre dos escaneos (ver
+/// [`diff_scans`]; un cambio de PID no genera eventos).
+///
+/// # Arguments
+/// * `previous` - Escaneo anterior
+/// * `current` - Escaneo más reciente
+///
+/// # Returns
+/// Eventos `Opened` para puertos nuevos y `Closed` para los que desaparecieron.
+pub fn scan_changes(previous: &[PortInfo], current: &[PortInfo]) -> Vec<HistoryEvent> {
+    let diff = diff_scans(previous, current);
+    let opened = diff
+        .opened
+        .iter()
+        .map(|p| HistoryEvent::from_port(HistoryEventKind::Opened, p));
+    let closed = diff
+        .closed
+        .iter()
+        .map(|p| HistoryEvent::from_port(HistoryEventKind::Closed, p));
+    opened.chain(closed).collect()
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn port(protocol: &str, port: u16) -> PortInfo {
+        PortInfo {
+            protocol: protocol.into(),
+            port,
+            local_address: "0.0.0.0".into(),
+            pid: 1,
+            process_name: "proc".into(),
+            state: SocketState::open_for(protocol),
+            ..Default::default()
+        }
+    }
+
+    /// Verifica la detección de puertos abiertos y cerrados entre escaneos
+    #[test]
+    fn test_scan_changes() {
+        let previous = vec![port("tcp", 80), port("udp", 53)];
+        let current = vec![port("tcp", 80), port("tcp", 3000)];
+
+        let events = scan_changes(&previous, &current);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events[0].kind, HistoryEventKind::Opened);
+        assert_eq!(events[0].port, 3000);
+        assert_eq!(events[1].kind, HistoryEventKind::Closed);
+        assert_eq!(events[1].port, 53);
+    }
+
+    /// Verifica los puertos abiertos, cerrados y con otro PID entre escaneos
+    #[test]
+    fn test_diff_scans() {
+        let mut moved = port("tcp", 5432);
+        let before = vec![port("tcp", 80), moved.clone(), port("udp", 53)];
+        moved.pid = 2;
+        moved.process_name = "postgres".into();
+        let after = vec![port("tcp", 80), moved, port("tcp", 3000)];
+
+        let diff = diff_scans(&before, &after);
+        assert_eq!(diff.opened.len(), 1);
+        assert_eq!(diff.opened[0].port, 3000);
+        assert_eq!(diff.closed.len(), 1);
+        assert_eq!(diff.closed[0].port, 53);
+        assert_eq!(
+            diff.pid_changes,
+            [PidChange {
+                protocol: "tcp".into(),
+                port: 5432,
+                before_pid: 1,
+                before_process: "proc".into(),
+                after_pid: 2,
+                after_process: "postgres".into(),
+            }]
+        );
+        assert!(diff_scans(&after, &after).is_empty());
+    }
+
+    /// Verifica la compactación: corruptos, retención y poda por tamaño
+    #[test]
+    fn test_compact_lines() {
+        let line = |timestamp: u64| {
+            let mut event = HistoryEvent::from_port(HistoryEventKind::Opened, &port("tcp", 80));
+            event.timestamp = timestamp;
+            serde_json::to_string(&event).unwrap()
+        };
+        let content = [line(100), "{roto".to_string(), line(5000), line(9000)].join("\n");
+
+        let config = HistoryConfig {
+            max_size_kb: 1024,
+            retention_days: None,
+        };
+        let (kept, stats) = compact_lines(&content, &config, 10_000);
+        assert_eq!((stats.kept, stats.corrupt), (3, 1));
+        assert_eq!(kept[0], line(100));
+
+        // Retención de un día: el evento de t=100 queda fuera
+        let daily = HistoryConfig {
+            max_size_kb: 1024,
+            retention_days: Some(1),
+        };
+        let (_, stats) = compact_lines(&content, &daily, 86_400 + 5000);
+        assert_eq!((stats.expired, stats.kept), (1, 2));
+
+        // Tamaño: se conservan los más recientes dentro de 3/4 de 1 KiB
+        let many: Vec<String> = (0..20).map(line).collect();
+        let small = HistoryConfig {
+            max_size_kb: 1,
+            retention_days: None,
+        };
+        let joined = many.join("\n");
+        let (kept, stats) = compact_lines(&joined, &small, 0);
+        assert!(stats.pruned > 0 && stats.kept + stats.pruned == 20);
+        assert_eq!(kept.last().copied(), Some(many[19].as_str()));
+        assert!(kept.iter().map(|l| l.len() + 1).sum::<usize>() <= 768);
+    }
+
+    /// Verifica la reconstrucción de los puertos de un momento pasado
+    #[test]
+    fn test_replay_ports() {
+        let at = |kind, timestamp, number: u16, pid| {
+            let info = PortInfo {
+                pid,
+                ..port("tcp", number)
+            };
+            let mut event = HistoryEvent::from_port(kind, &info);
+            event.timestamp = timestamp;
+            event
+        };
+        let events = vec![
+            at(HistoryEventKind::Opened, 10, 3000, 7),
+            at(HistoryEventKind::Opened, 20, 5432, 8),
+            at(HistoryEventKind::Closed, 30, 3000, 7),
+            at(HistoryEventKind::Killed, 40, 0, 8),
+        ];
+        let ports = |timestamp| -> Vec<u16> {
+            replay_ports(&events, timestamp)
+                .iter()
+                .map(|p| p.port)
+                .collect()
+        };
+        assert!(ports(5).is_empty());
+        assert_eq!(ports(20), vec![3000, 5432]);
+        assert_eq!(ports(30), vec![5432]);
+        assert!(ports(40).is_empty());
+    }
+
+    /// Verifica que un escaneo idéntico no genera eventos
+    #[test]
+    fn test_scan_changes_no_changes() {
+        let ports = vec![port("tcp", 80)];
+        assert!(scan_changes(&ports, &ports).is_empty());
+    }
+}
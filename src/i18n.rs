@@ -0,0 +1,388 @@
+/// Módulo de internacionalización (español e inglés).
+///
+/// Los textos visibles para el usuario (menú del tray, notificaciones y
+/// errores que devuelve la API) se buscan en una tabla por idioma. Los
+/// mensajes del log siguen en español: son para depurar, no para el
+/// usuario final.
+///
+/// El idioma se elige con `language = "es" | "en"` en `config.toml`; si
+/// no se indica, se toma de `LC_ALL`, `LC_MESSAGES` o `LANG`, y por
+/// defecto es español. `Config::load` aplica el idioma en cada recarga,
+/// así que un cambio en la configuración se ve en la siguiente
+/// actualización del menú.
+///
+/// Los textos con `{}` se completan con [`trf`], en orden.
+use std::fmt::Display;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Idioma activo (índice de [`Lang`])
+static LANGUAGE: AtomicU8 = AtomicU8::new(Lang::Es as u8);
+
+/// Idiomas disponibles
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lang {
+    /// Español (por defecto)
+    Es = 0,
+    /// Inglés
+    En = 1,
+}
+
+impl Lang {
+    /// Interpreta un código de idioma o de locale ("en", "es_AR.UTF-8").
+    pub fn parse(code: &str) -> Option<Lang> {
+        let code = code.trim().to_lowercase();
+        if code.starts_with("es") {
+            Some(Lang::Es)
+        } else if code.starts_with("en") {
+            Some(Lang::En)
+        } else {
+            None
+        }
+    }
+
+    /// Idioma de las variables de entorno de locale, si es uno conocido.
+    pub fn from_env() -> Option<Lang> {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| Lang::parse(&value))
+    }
+
+    /// Código del idioma, como en `config.toml`.
+    #[cfg_attr(not(feature = "api"), allow(dead_code))]
+    pub fn code(self) -> &'static str {
+        match self {
+            Lang::Es => "es",
+            Lang::En => "en",
+        }
+    }
+
+    /// Texto en este idioma.
+    pub fn text(self, text: Text) -> &'static str {
+        let (es, en) = text.translations();
+        match self {
+            Lang::Es => es,
+            Lang::En => en,
+        }
+    }
+}
+
+/// Cambia el idioma activo.
+pub fn set_language(lang: Lang) {
+    LANGUAGE.store(lang as u8, Ordering::Relaxed);
+    // El motor de escaneo nombra los procesos sin dueño visible
+    portslayer_core::set_unknown_process_name(lang.text(Text::UnknownProcess));
+}
+
+/// Idioma activo.
+pub fn language() -> Lang {
+    match LANGUAGE.load(Ordering::Relaxed) {
+        1 => Lang::En,
+        _ => Lang::Es,
+    }
+}
+
+/// Texto en el idioma activo.
+pub fn tr(text: Text) -> &'static str {
+    language().text(text)
+}
+
+/// Texto en el idioma activo con sus `{}` reemplazados por `args`.
+pub fn trf(text: Text, args: &[&dyn Display]) -> String {
+    fill(tr(text), args)
+}
+
+/// Reemplaza cada `{}` de la plantilla por el argumento correspondiente.
+fn fill(template: &str, args: &[&dyn Display]) -> String {
+    let mut result = String::with_capacity(template.len());
+    let mut args = args.iter();
+    let mut parts = template.split("{}");
+    if let Some(first) = parts.next() {
+        result.push_str(first);
+    }
+    for part in parts {
+        if let Some(arg) = args.next() {
+            result.push_str(&arg.to_string());
+        }
+        result.push_str(part);
+    }
+    result
+}
+
+/// Define [`Text`] y su tabla de traducciones (español, inglés).
+macro_rules! catalog {
+    ($($key:ident => $es:literal, $en:literal;)*) => {
+        /// Textos traducibles (los errores de la API solo se usan con la
+        /// feature `api`, y el del escaneo remoto además con `remote`)
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        #[cfg_attr(not(all(feature = "api", feature = "remote")), allow(dead_code))]
+        pub enum Text {
+            $($key,)*
+        }
+
+        impl Text {
+            /// Todos los textos (para verificar las traducciones)
+            #[cfg(test)]
+            const ALL: &'static [Text] = &[$(Text::$key,)*];
+
+            /// Traducciones del texto: (español, inglés).
+            fn translations(self) -> (&'static str, &'static str) {
+                match self {
+                    $(Text::$key => ($es, $en),)*
+                }
+            }
+        }
+    };
+}
+
+catalog! {
+    // ── Menú del tray ──
+    Refresh => "🔄 Actualizar", "🔄 Refresh";
+    PauseRefresh => "⏸ Pausar actualización", "⏸ Pause auto-refresh";
+    MaintenanceActive => "🛠 Mantenimiento: {} (hasta {})", "🛠 Maintenance: {} (until {})";
+    MaintenanceInactive => "🛠 Mantenimiento: inactivo", "🛠 Maintenance: inactive";
+    AutomationEnabled => "🤖 Automatización: activada ({} reglas)", "🤖 Automation: on ({} rules)";
+    AutomationDisabled => "🤖 Automatización: desactivada ({} reglas)", "🤖 Automation: off ({} rules)";
+    RestartMenu => "♻️ Reiniciar", "♻️ Restart";
+    WorkspaceMenu => "🗂 Espacio: {}", "🗂 Workspace: {}";
+    WorkspaceNone => "Ninguno", "None";
+    FilterMenu => "📊 Filtro: {}", "📊 Filter: {}";
+    FilterAll => "Todos", "All";
+    RangeMenu => "🔢 Rango: {}", "🔢 Range: {}";
+    RangeWellKnown => "Conocidos (<1024)", "Well-known (<1024)";
+    RangeDev => "Desarrollo (3000–9999)", "Development (3000–9999)";
+    RangeEphemeral => "Efímeros ({}–{})", "Ephemeral ({}–{})";
+    RangeCustom => "Personalizado ({}–{})", "Custom ({}–{})";
+    RangeCustomUnset => "○ Personalizado (define custom_range en config.toml)", "○ Custom (set custom_range in config.toml)";
+    TagMenu => "🏷 Etiqueta: {}", "🏷 Tag: {}";
+    OwnerMenu => "👤 Usuario: {}", "👤 User: {}";
+    OwnerMine => "Mis procesos ({})", "My processes ({})";
+    ScopeMenu => "🌐 Alcance: {}", "🌐 Scope: {}";
+    ScopeLoopback => "🔒 Solo local (loopback)", "🔒 Local only (loopback)";
+    ScopeExposed => "🌍 Expuestos a la red", "🌍 Exposed to the network";
+    TagAll => "Todas", "All";
+    ColorRed => "Rojo", "Red";
+    ColorOrange => "Naranja", "Orange";
+    ColorYellow => "Amarillo", "Yellow";
+    ColorGreen => "Verde", "Green";
+    ColorBlue => "Azul", "Blue";
+    ColorPurple => "Morado", "Purple";
+    ColorGray => "Gris", "Gray";
+    PageSizeMenu => "📋 Por página: {}", "📋 Per page: {}";
+    PageSizeOption => "{} puertos", "{} ports";
+    IntervalMenu => "⏱ Actualización: {}", "⏱ Refresh: {}";
+    IntervalManual => "manual", "manual";
+    NoPorts => "✅ No hay puertos abiertos", "✅ No open ports";
+    PinnedClosed => "📌 ⚪ Puerto {}: cerrado", "📌 ⚪ Port {}: closed";
+    TunnelItem => "{} {} → {} ({}, PID {})", "{} {} → {} ({}, PID {})";
+    TunnelVia => "{} vía {}", "{} via {}";
+    TunnelOrphaned => "🧟 Túnel huérfano: quien lo lanzó ya no existe", "🧟 Orphaned tunnel: whoever started it is gone";
+    CloseTunnel => "⚔️ Cerrar túnel", "⚔️ Close tunnel";
+    PortsFound => "📡 {} puertos encontrados{}", "📡 {} ports found{}";
+    KillAll => "⚔️ Cerrar Todos ({} puertos)", "⚔️ Kill All ({} ports)";
+    Kill => "⚔️ Cerrar", "⚔️ Kill";
+    KillAnyway => "⚔️ Cerrar igualmente", "⚔️ Kill anyway";
+    CriticalWarning => "⚠️ Proceso crítico: {}", "⚠️ Critical process: {}";
+    BlockPort => "🚫 Bloquear puerto", "🚫 Block port";
+    UnblockPort => "✅ Desbloquear puerto", "✅ Unblock port";
+    BlocksMenu => "🚫 Puertos bloqueados ({})", "🚫 Blocked ports ({})";
+    UnblockItem => "✅ Desbloquear {}/{}", "✅ Unblock {}/{}";
+    HealthTimeout => "sin respuesta", "no response";
+    CertificateItem => "🔒 {} (caduca el {})", "🔒 {} (expires {})";
+    CertificateExpired => "⚠️ Certificado caducado el {}", "⚠️ Certificate expired on {}";
+    CertificateSelfSigned => "⚠️ Certificado autofirmado en un puerto expuesto", "⚠️ Self-signed certificate on an exposed port";
+    ManagerAllowed => "🛡 {}: permitido (clic para denegar)", "🛡 {}: allowed (click to deny)";
+    ManagerDenied => "🛡 {}: denegado (clic para permitir)", "🛡 {}: denied (click to allow)";
+    DockerBinding => "🐳 → contenedor {} ({}), puerto {} del contenedor", "🐳 → container {} ({}), container port {}";
+    DockerLogs => "🐳 Ver en Docker (copia `docker logs {}`)", "🐳 View in Docker (copies `docker logs {}`)";
+    ComposeProject => "📦 {}: {}", "📦 {}: {}";
+    ComposeDown => "⏹ Bajar el proyecto (docker compose down)", "⏹ Take the project down (docker compose down)";
+    ManifestProject => "📋 {}: {}/{} puertos libres", "📋 {}: {}/{} ports free";
+    ManifestPortFree => "✅ {} {}: libre", "✅ {} {}: free";
+    ManifestPortBusy => "🔴 {} {} → {} [PID {}]", "🔴 {} {} → {} [PID {}]";
+    ManifestKill => "⚔️ Cerrar los puertos del proyecto", "⚔️ Kill the project's ports";
+    KillProfiles => "🎯 Perfiles", "🎯 Profiles";
+    TimeWaitHeld => "⏳ {}: {} conexiones en TIME_WAIT (~{} s)", "⏳ {}: {} connections in TIME_WAIT (~{} s)";
+    ConnectionNotDropped => "El kernel no cortó la conexión con {} (ss -K necesita CONFIG_INET_DIAG_DESTROY)", "The kernel did not drop the connection to {} (ss -K needs CONFIG_INET_DIAG_DESTROY)";
+    DropConnections => "✂️ Cortar una conexión ({})", "✂️ Drop a connection ({})";
+    TimeWaitDestroy => "🧹 Destruir los sockets (ss -K)", "🧹 Destroy the sockets (ss -K)";
+    ProcessZombie => "🧟 zombi", "🧟 zombie";
+    ProcessZombieHeld => "🧟 zombi (lo retiene {} [PID {}])", "🧟 zombie (held by {} [PID {}])";
+    ProcessOrphan => "👻 huérfano", "👻 orphaned";
+    ZombieKillParent => "🧟 Matar al padre {} (PID {})", "🧟 Kill parent {} (PID {})";
+    ZombieParentWarning => "⚠️ El zombi ya no tiene hilos: solo lo libera matar a su padre", "⚠️ The zombie has no threads left: only killing its parent frees it";
+    ZombieKillParentConfirm => "⚔️ Matar a {} (PID {})", "⚔️ Kill {} (PID {})";
+    KillProfile => "⚔️ {}: {} ({} abiertos)", "⚔️ {}: {} ({} open)";
+    ProcessUptime => "⏱ En marcha desde hace {}", "⏱ Running for {}";
+    ListenQueue => "📥 Cola de conexiones: {}/{}", "📥 Connection queue: {}/{}";
+    ListenQueueFull => "⚠️ Cola llena: {}/{} (no acepta las conexiones a tiempo)", "⚠️ Queue full: {}/{} (not accepting connections in time)";
+    ThrottleMenu => "🐢 Frenar sin cerrar", "🐢 Slow down without killing";
+    CurrentPriority => "Prioridad actual: nice {}", "Current priority: nice {}";
+    LowerPriority => "Bajar prioridad (nice {})", "Lower priority (nice {})";
+    NormalPriority => "Prioridad normal (nice 0)", "Normal priority (nice 0)";
+    IoIdle => "💽 Disco solo en reposo (ionice idle)", "💽 Disk only when idle (ionice idle)";
+    IoNormal => "💽 Disco normal (ionice best-effort)", "💽 Normal disk (ionice best-effort)";
+    Freeze => "⏸ Congelar (SIGSTOP)", "⏸ Freeze (SIGSTOP)";
+    Thaw => "▶ Reanudar (SIGCONT)", "▶ Resume (SIGCONT)";
+    CopyMenu => "📋 Copiar", "📋 Copy";
+    CopyLine => "Línea completa", "Full line";
+    CopyPid => "PID", "PID";
+    CopyKillCommand => "Comando kill -9", "kill -9 command";
+    PeerConnections => "{} {} ({} conexiones)", "{} {} ({} connections)";
+    RestartService => "🔄 Reiniciar servicio", "🔄 Restart service";
+    StopService => "⏹ Detener servicio", "⏹ Stop service";
+    RespawnSummary => "🔁 Se relanza el {}% de las veces ({} lo vuelve a iniciar)", "🔁 Respawns {}% of the time ({} starts it again)";
+    StopParent => "⏹ Detener el padre: {} [PID {}]", "⏹ Stop the parent: {} [PID {}]";
+    PreviousPage => "◀ Anterior", "◀ Previous";
+    PageOf => "📄 Página {}/{}", "📄 Page {}/{}";
+    NextPage => "▶ Siguiente", "▶ Next";
+    Autostart => "🚀 Iniciar con la sesión", "🚀 Start on login";
+    OpenLog => "📜 Abrir log", "📜 Open log";
+    Exit => "❌ Salir", "❌ Quit";
+    ExitPending => "❌ Salir (⏳ {} pendientes)", "❌ Quit (⏳ {} pending)";
+    ExitSummary => "✅ {} completadas · ⏳ {} pendientes · ❌ {} fallidas", "✅ {} complete · ⏳ {} pending · ❌ {} failed";
+    ExitWait => "⏳ Esperar y salir", "⏳ Wait, then quit";
+    ExitNow => "❌ Salir ahora", "❌ Quit now";
+    OperationKill => "Cerrar PID {}", "Kill PID {}";
+    OperationKillCheck => "Comprobar el kill de {} (puerto {})", "Check the kill of {} (port {})";
+    PortNotFreed => "el puerto {} sigue ocupado", "port {} is still taken";
+    UnknownProcess => "desconocido", "unknown";
+    IntrudersAlert => "🚨 {} puertos reservados ocupados por otro proceso", "🚨 {} reserved ports taken by another process";
+    ExposedAlert => "🌐 {} puertos TCP expuestos en todas las interfaces", "🌐 {} TCP ports exposed on all interfaces";
+    TrayTooltip => "{} puertos escuchando ({} TCP, {} UDP)", "{} listening ports ({} TCP, {} UDP)";
+    ReplayActive => "⏪ Reproducción: {}", "⏪ Replay: {}";
+    ReplayKill => "Reproduciendo una sesión grabada: no se terminan procesos reales", "Replaying a recorded session: real processes are not killed";
+    ApproximateScan => "≈ Escaneo aproximado: {} de {} procesos sin leer del todo", "≈ Approximate scan: {} of {} processes not fully read";
+    SourceDegraded => "⚠️ {} no responde: se escanea sin esta fuente", "⚠️ {} is not responding: scanning without it";
+
+    // ── Impacto de un kill ──
+    NoActiveConnections => "sin conexiones activas", "no active connections";
+    ConnectionsSummary => "{} conexiones activas: {} locales, {} remotas", "{} active connections: {} local, {} remote";
+    ActiveConnection => "{} conexión activa", "{} active connection";
+    ActiveConnections => "{} conexiones activas", "{} active connections";
+    DependencyNote => "Matar {} romperá {}", "Killing {} will break {}";
+
+    // ── Notificaciones ──
+    RuleHit => "Regla '{}': {} {} → {} [PID {}]", "Rule '{}': {} {} → {} [PID {}]";
+    WatchOpened => "👁 Puerto {} abierto por {} [PID {}]", "👁 Port {} opened by {} [PID {}]";
+    WatchClosed => "👁 Puerto {} cerrado ({} [PID {}] ya no escucha)", "👁 Port {} closed ({} [PID {}] stopped listening)";
+    WatchOwnerChanged => "👁 Puerto {}: {} [PID {}] → {} [PID {}]", "👁 Port {}: {} [PID {}] → {} [PID {}]";
+    Watch => "👁 Vigilar (avisar si se abre, cierra o cambia de proceso)", "👁 Watch (notify when it opens, closes or changes process)";
+    Unwatch => "👁 Dejar de vigilar", "👁 Stop watching";
+    WatchedInConfig => "👁 Vigilado desde config.toml", "👁 Watched from config.toml";
+    CpuSpike => "🔥 {} [PID {}] (puerto {}): {} min al {}% de CPU", "🔥 {} [PID {}] (port {}): {} min at {}% CPU";
+
+    // ── Errores devueltos por la API ──
+    InvalidRequest => "Petición HTTP inválida", "Invalid HTTP request";
+    InvalidCredentials => "Credenciales inválidas o ausentes", "Invalid or missing credentials";
+    MethodNotAllowed => "Método no permitido", "Method not allowed";
+    RouteNotFound => "Ruta no encontrada", "Route not found";
+    UnknownTagColor => "Color de etiqueta desconocido", "Unknown tag color";
+    BlockFailed => "No se pudo bloquear el puerto {}/{}: {}", "Could not block port {}/{}: {}";
+    UnblockFailed => "No se pudo desbloquear el puerto {}/{}: {}", "Could not unblock port {}/{}: {}";
+    InvalidPort => "Puerto inválido", "Invalid port";
+    BlockNotFound => "El puerto no está bloqueado", "Port is not blocked";
+    MissingHost => "Falta el host (?host=)", "Missing host (?host=)";
+    ExplainOwner => "Lo ocupa {} (PID {}, usuario {}), escuchando en {} {}", "Held by {} (PID {}, user {}), listening on {} {}";
+    ExplainDocker => "Es un puerto publicado por Docker: reenvía al contenedor {} (docker ps --filter publish={})", "It is a port published by Docker: forwards to container {} (docker ps --filter publish={})";
+    ExplainSystemdUnknownUnit => "Lo tiene systemd: probablemente una unidad .socket (systemctl list-sockets)", "Held by systemd: probably a .socket unit (systemctl list-sockets)";
+    ExplainSocketUnit => "La unidad {} de systemd lo reserva en {} para activar {} bajo demanda (systemctl stop {})", "The systemd unit {} reserves it on {} to start {} on demand (systemctl stop {})";
+    ExplainTimeWaitCount => "{} conexiones cerradas siguen en TIME_WAIT: retienen el puerto unos {} s más salvo que el servidor use SO_REUSEADDR", "{} closed connections are still in TIME_WAIT: they hold the port for about {} s more unless the server uses SO_REUSEADDR";
+    TimeWaitNotDestroyed => "El kernel no destruyó los sockets en TIME_WAIT (ss -K necesita CONFIG_INET_DIAG_DESTROY); expiran solos en unos {} s", "The kernel did not destroy the TIME_WAIT sockets (ss -K needs CONFIG_INET_DIAG_DESTROY); they expire on their own in about {} s";
+    ExplainTimeWait => "Hay conexiones cerradas en TIME_WAIT: el puerto queda retenido hasta 60 s salvo que el servidor use SO_REUSEADDR", "There are closed connections in TIME_WAIT: the port stays held for up to 60 s unless the server uses SO_REUSEADDR";
+    ExplainSpecificAddress => "Escucha solo en {}: otro servidor tampoco puede escuchar en 0.0.0.0 (todas las interfaces) en el mismo puerto", "Listens only on {}: another server can't listen on 0.0.0.0 (all interfaces) on the same port either";
+    NoFreePort => "Ningún puerto libre entre {} y {}", "No free port between {} and {}";
+    ExplainFree => "El puerto {} está libre: nada lo ocupa ni lo reserva", "Port {} is free: nothing holds or reserves it";
+    NoFirewallManager => "No hay ufw ni firewalld activos", "Neither ufw nor firewalld is active";
+    ManagerFailed => "No se pudo cambiar la regla de {} para {}/{}: {}", "Could not change the {} rule for {}/{}: {}";
+    ManagerService => "{} lo permite el servicio {} de firewalld: quítalo con firewall-cmd --remove-service", "{} is allowed by the firewalld service {}: remove it with firewall-cmd --remove-service";
+    UnknownFirewallAction => "Acción desconocida (usa allow o deny)", "Unknown action (use allow or deny)";
+    UnknownScope => "Alcance desconocido (usa all, loopback o exposed)", "Unknown scope (use all, loopback or exposed)";
+    InvalidProcessRegex => "Expresión regular inválida: {}", "Invalid regular expression: {}";
+    UnknownOwner => "Usuario desconocido (usa all, me, root, un UID o un nombre de usuario)", "Unknown user (use all, me, root, a UID or a user name)";
+    UnknownSocketState => "Estado de socket desconocido (usa all, listen, time_wait, close_wait...)", "Unknown socket state (use all, listen, time_wait, close_wait...)";
+    InvalidPid => "PID inválido", "Invalid PID";
+    ProtectedProcess => "El proceso está marcado como \"no matar\"", "The process is marked as \"do not kill\"";
+    InvalidPortRange => "Rango de puertos inválido", "Invalid port range";
+    UnknownProtocol => "Protocolo desconocido", "Unknown protocol";
+    InvalidIndex => "Índice inválido", "Invalid index";
+    MissingAt => "Falta el parámetro at (segundos UNIX)", "Missing at parameter (UNIX seconds)";
+    MissingSince => "Falta el parámetro since (segundos UNIX)", "Missing since parameter (UNIX seconds)";
+    UnsupportedExportFormat => "Formato no soportado (json o csv)", "Unsupported format (json or csv)";
+    UnsupportedGraphFormat => "Formato no soportado (dot o json)", "Unsupported format (dot or json)";
+    InvalidTag => "Etiqueta inválida: {}", "Invalid tag: {}";
+    WebhookOpened => "🟢 {host}: {process} [PID {pid}] escucha en {protocol} {port}", "🟢 {host}: {process} [PID {pid}] is listening on {protocol} {port}";
+    WebhookClosed => "⚪ {host}: {protocol} {port} cerrado ({process} [PID {pid}])", "⚪ {host}: {protocol} {port} closed ({process} [PID {pid}])";
+    WebhookKilled => "⚔️ {host}: {process} [PID {pid}] terminado desde PortSlayer", "⚔️ {host}: {process} [PID {pid}] killed from PortSlayer";
+    UnknownWatchEvent => "Cambio desconocido: {} (usa open, close u owner)", "Unknown change: {} (use open, close or owner)";
+    WatchNotFound => "El puerto {} no se vigila", "Port {} is not watched";
+    WatchInConfig => "El puerto {} se vigila desde config.toml", "Port {} is watched from config.toml";
+    WatchlistNotSaved => "No se pudieron guardar los puertos vigilados: {}", "Could not save the watchlist: {}";
+    TagsNotSaved => "No se pudieron guardar las etiquetas: {}", "Could not save tags: {}";
+    TagNotFound => "Etiqueta no encontrada", "Tag not found";
+    InvalidSettings => "Ajustes inválidos: {}", "Invalid settings: {}";
+    SettingsNotSaved => "No se pudieron guardar los ajustes: {}", "Could not save the settings: {}";
+
+    // ── Errores al matar o relanzar procesos ──
+    ZombieNoParent => "El PID {} es un zombi sin padre que matar: lo recogerá init", "PID {} is a zombie with no parent to kill: init will reap it";
+    ZombieParentUnconfirmed => "Matar al padre {} (PID {}) necesita ?confirm=true", "Killing parent {} (PID {}) requires ?confirm=true";
+    ZombieNotHeld => "El PID {} no es un zombi sin hilos retenido por su padre", "PID {} is not a threadless zombie held by its parent";
+    UnknownPidKill => "No se puede matar un proceso con PID desconocido (0)", "Cannot kill a process with unknown PID (0)";
+    KillFailed => "No se pudo matar el proceso {}: {}", "Could not kill process {}: {}";
+    NoSuchProcess => "el proceso ya no existe", "the process no longer exists";
+    UnknownPidAction => "No se puede frenar un proceso con PID desconocido (0)", "Cannot slow down a process with unknown PID (0)";
+    ReplayAction => "Reproduciendo una sesión grabada: no se modifican procesos reales", "Replaying a recorded session: real processes are not modified";
+    InvalidNice => "Prioridad inválida: {} (de -20 a 19)", "Invalid priority: {} (from -20 to 19)";
+    ActionFailed => "No se pudo aplicar {} al proceso {}: {}", "Could not apply {} to process {}: {}";
+    ClosePortFailed => "No se pudo cerrar el puerto {}/{}: {}", "Could not close port {}/{}: {}";
+    InvalidRange => "Rango de puertos inválido: {}-{}", "Invalid port range: {}-{}";
+    NothingToKill => "No hay procesos con PID conocido que cerrar", "No processes with a known PID to kill";
+    NoCmdline => "El PID {} no tiene línea de comandos", "PID {} has no command line";
+    PortStillBusy => "El puerto {} sigue ocupado", "Port {} is still in use";
+    CriticalProcess => "{} (PID {}) es un proceso crítico ({}): confirma antes de cerrarlo", "{} (PID {}) is a critical process ({}): confirm before killing it";
+    DangerInit => "el init del sistema", "the system init";
+    DangerRemoteAccess => "corta las sesiones SSH", "drops SSH sessions";
+    DangerSystemService => "servicio del sistema", "system service";
+    DangerDisplayManager => "cierra la sesión gráfica", "ends the graphical session";
+    DangerPortSlayer => "es el propio PortSlayer", "it is PortSlayer itself";
+    HelperFailed => "No se pudo iniciar el helper privilegiado: {}", "Could not start the privileged helper: {}";
+    HelperCancelled => "se canceló la autenticación", "authentication was cancelled";
+    HelperTimeout => "no respondió a tiempo", "it did not respond in time";
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verifica que cada traducción tiene los mismos `{}` en ambos idiomas
+    #[test]
+    fn test_translations_have_same_placeholders() {
+        for text in Text::ALL {
+            let (es, en) = text.translations();
+            assert_eq!(
+                es.matches("{}").count(),
+                en.matches("{}").count(),
+                "{:?}",
+                text
+            );
+        }
+        assert_eq!(Lang::En.text(Text::Refresh), "🔄 Refresh");
+        assert_eq!(
+            fill("Puerto {}: {}", &[&3000, &"cerrado"]),
+            "Puerto 3000: cerrado"
+        );
+    }
+
+    /// Verifica la detección del idioma a partir del locale
+    #[test]
+    fn test_parse_lang() {
+        assert_eq!(Lang::parse("en_US.UTF-8"), Some(Lang::En));
+        assert_eq!(Lang::parse("ES"), Some(Lang::Es));
+        assert_eq!(Lang::parse("C.UTF-8"), None);
+    }
+}
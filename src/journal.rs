@@ -0,0 +1,377 @@
+/// Módulo del escritor en segundo plano de los registros JSON Lines.
+///
+/// El historial (`history.jsonl`) y el registro de reglas (`rules.jsonl`)
+/// no se escriben desde el hilo de escaneo: las líneas se encolan y un
+/// hilo dedicado las vuelca a disco. Así un disco lento o bloqueado no
+/// retrasa los escaneos ni las respuestas de la API.
+///
+/// La cola está acotada a [`QUEUE_CAPACITY`] líneas. Si se llena, se
+/// descartan las más antiguas (conviene más conservar los cambios
+/// recientes) y se contabilizan en [`JournalStats::dropped`]; las tareas
+/// encoladas nunca se descartan.
+///
+/// Las tareas que reescriben un archivo (como la poda del historial) se
+/// encolan con [`run`] para ejecutarse en el mismo hilo, en orden con las
+/// líneas pendientes, sin perder escrituras.
+use serde::Serialize;
+use std::collections::{BTreeMap, VecDeque};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+use std::time::Duration;
+
+/// Líneas pendientes máximas antes de descartar las más antiguas
+pub const QUEUE_CAPACITY: usize = 1024;
+
+/// Tiempo máximo de espera al vaciar la cola antes de salir
+pub const FLUSH_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Escritor global, creado en el primer uso
+static JOURNAL: OnceLock<Arc<Journal>> = OnceLock::new();
+
+/// Métricas del escritor
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize)]
+pub struct JournalStats {
+    /// Líneas en cola pendientes de escribir
+    pub queued: usize,
+    /// Líneas escritas desde el arranque
+    pub written: u64,
+    /// Líneas descartadas por cola llena
+    pub dropped: u64,
+}
+
+/// Línea pendiente junto con el archivo al que va
+#[derive(Debug)]
+struct Record {
+    path: PathBuf,
+    line: String,
+}
+
+/// Trabajo pendiente del hilo escritor
+enum Entry {
+    /// Línea a añadir a un archivo
+    Line(Record),
+    /// Tarea a ejecutar tras escribir las líneas anteriores
+    #[cfg_attr(not(feature = "api"), allow(dead_code))]
+    Task(Box<dyn FnOnce() + Send>),
+}
+
+/// Cola compartida con el hilo escritor
+#[derive(Default)]
+struct Queue {
+    records: VecDeque<Entry>,
+    /// El escritor tiene un lote en curso (aún no está en disco)
+    writing: bool,
+}
+
+/// Escritor con su cola acotada y sus contadores
+struct Journal {
+    capacity: usize,
+    queue: Mutex<Queue>,
+    /// Avisa al escritor de que hay líneas nuevas
+    pending: Condvar,
+    /// Avisa a `flush` de que la cola quedó vacía
+    idle: Condvar,
+    written: AtomicU64,
+    dropped: AtomicU64,
+}
+
+impl Journal {
+    /// Crea un escritor y lanza su hilo.
+    fn start(capacity: usize) -> Arc<Self> {
+        let journal = Arc::new(Self {
+            capacity,
+            queue: Mutex::new(Queue::default()),
+            pending: Condvar::new(),
+            idle: Condvar::new(),
+            written: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+        });
+        let worker = Arc::clone(&journal);
+        std::thread::Builder::new()
+            .name("journal".into())
+            .spawn(move || worker.run())
+            .expect("no se pudo lanzar el hilo del historial");
+        journal
+    }
+
+    /// Encola líneas sin bloquear más que el tiempo de tomar el mutex.
+    fn append(&self, path: &Path, lines: Vec<String>) {
+        let entries = lines.into_iter().map(|line| {
+            Entry::Line(Record {
+                path: path.to_path_buf(),
+                line,
+            })
+        });
+        self.enqueue(entries);
+    }
+
+    /// Añade trabajos a la cola, descartando las líneas más antiguas si
+    /// está llena.
+    fn enqueue(&self, entries: impl IntoIterator<Item = Entry>) {
+        let mut dropped = 0;
+        if let Ok(mut queue) = self.queue.lock() {
+            for entry in entries {
+                if push_bounded(&mut queue.records, entry, self.capacity) {
+                    dropped += 1;
+                }
+            }
+        }
+        self.pending.notify_one();
+
+        if dropped > 0 {
+            let total = self.dropped.fetch_add(dropped, Ordering::Relaxed) + dropped;
+            log::warn!(
+                "Historial saturado: {} líneas antiguas descartadas ({} en total)",
+                dropped,
+                total
+            );
+        }
+    }
+
+    /// Bucle del hilo escritor: vuelca lotes completos a disco.
+    fn run(&self) {
+        loop {
+            let batch: Vec<Entry> = {
+                let Ok(queue) = self.queue.lock() else {
+                    return;
+                };
+                let Ok(mut queue) = self.pending.wait_while(queue, |q| q.records.is_empty()) else {
+                    return;
+                };
+                queue.writing = true;
+                queue.records.drain(..).collect()
+            };
+
+            let written = write_batch(batch);
+            self.written.fetch_add(written, Ordering::Relaxed);
+
+            if let Ok(mut queue) = self.queue.lock() {
+                queue.writing = false;
+            }
+            self.idle.notify_all();
+        }
+    }
+
+    /// Espera a que todo lo encolado esté en disco.
+    fn flush(&self, timeout: Duration) -> bool {
+        let Ok(queue) = self.queue.lock() else {
+            return false;
+        };
+        match self
+            .idle
+            .wait_timeout_while(queue, timeout, |q| !q.records.is_empty() || q.writing)
+        {
+            Ok((_, result)) => !result.timed_out(),
+            Err(_) => false,
+        }
+    }
+
+    /// Métricas actuales.
+    fn stats(&self) -> JournalStats {
+        JournalStats {
+            queued: self.queue.lock().map(|q| q.records.len()).unwrap_or(0),
+            written: self.written.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Añade un trabajo a la cola descartando la línea más antigua si está
+/// llena.
+///
+/// Las tareas nunca se descartan (una poda perdida dejaría crecer el
+/// archivo sin límite): siempre se encolan y, si la cola llena solo
+/// tiene tareas, se descarta la línea que llega.
+///
+/// # Returns
+/// `true` si se descartó una línea.
+fn push_bounded(records: &mut VecDeque<Entry>, entry: Entry, capacity: usize) -> bool {
+    let full = matches!(entry, Entry::Line(_)) && records.len() >= capacity;
+    if full {
+        match records.iter().position(|e| matches!(e, Entry::Line(_))) {
+            Some(oldest) => {
+                records.remove(oldest);
+            }
+            None => return true,
+        }
+    }
+    records.push_back(entry);
+    full
+}
+
+/// Procesa un lote en orden y devuelve las líneas escritas.
+///
+/// Las líneas consecutivas se escriben juntas; cada tarea se ejecuta
+/// después de las líneas que se encolaron antes que ella.
+fn write_batch(batch: Vec<Entry>) -> u64 {
+    let mut written = 0;
+    let mut lines = Vec::new();
+    for entry in batch {
+        match entry {
+            Entry::Line(record) => lines.push(record),
+            Entry::Task(task) => {
+                written += write_lines(std::mem::take(&mut lines));
+                task();
+            }
+        }
+    }
+    written + write_lines(lines)
+}
+
+/// Escribe líneas agrupadas por archivo y devuelve cuántas se escribieron.
+///
+/// Los errores se registran en el log: perder una entrada no debe
+/// detener el escritor.
+fn write_lines(batch: Vec<Record>) -> u64 {
+    if batch.is_empty() {
+        return 0;
+    }
+    let mut by_path: BTreeMap<PathBuf, Vec<String>> = BTreeMap::new();
+    for record in batch {
+        by_path.entry(record.path).or_default().push(record.line);
+    }
+
+    let mut written = 0;
+    for (path, lines) in by_path {
+        if let Some(dir) = path.parent() {
+            if let Err(e) = fs::create_dir_all(dir) {
+                log::error!("No se pudo crear el directorio {}: {}", dir.display(), e);
+                continue;
+            }
+        }
+        let mut file = match OpenOptions::new().create(true).append(true).open(&path) {
+            Ok(file) => file,
+            Err(e) => {
+                log::error!("No se pudo abrir {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        for line in lines {
+            if let Err(e) = writeln!(file, "{}", line) {
+                log::error!("Error escribiendo en {}: {}", path.display(), e);
+                break;
+            }
+            written += 1;
+        }
+    }
+    written
+}
+
+/// Escritor global.
+fn journal() -> &'static Arc<Journal> {
+    JOURNAL.get_or_init(|| Journal::start(QUEUE_CAPACITY))
+}
+
+/// Encola líneas para añadirlas al final de un archivo JSON Lines.
+///
+/// # Arguments
+/// * `path` - Archivo de destino (se crea junto con su directorio)
+/// * `lines` - Líneas ya serializadas, sin salto de línea final
+pub fn append(path: &Path, lines: Vec<String>) {
+    if !lines.is_empty() {
+        journal().append(path, lines);
+    }
+}
+
+/// Ejecuta una tarea en el hilo escritor, después de las líneas ya encoladas.
+#[cfg_attr(not(feature = "api"), allow(dead_code))]
+pub fn run(task: impl FnOnce() + Send + 'static) {
+    journal().enqueue([Entry::Task(Box::new(task))]);
+}
+
+/// Espera a que se escriba todo lo pendiente (hasta `timeout`).
+///
+/// Debe llamarse antes de terminar el proceso para no perder las
+/// últimas líneas. No hace nada si nunca se encoló nada.
+///
+/// # Returns
+/// `false` si se agotó el tiempo con líneas aún en cola.
+pub fn flush(timeout: Duration) -> bool {
+    match JOURNAL.get() {
+        Some(journal) => journal.flush(timeout),
+        None => true,
+    }
+}
+
+/// Métricas del escritor global.
+#[cfg_attr(not(feature = "api"), allow(dead_code))]
+pub fn stats() -> JournalStats {
+    JOURNAL.get().map(|j| j.stats()).unwrap_or_default()
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(text: &str) -> Entry {
+        Entry::Line(Record {
+            path: PathBuf::from("/tmp/x"),
+            line: text.into(),
+        })
+    }
+
+    fn lines(records: &VecDeque<Entry>) -> Vec<&str> {
+        records
+            .iter()
+            .filter_map(|e| match e {
+                Entry::Line(r) => Some(r.line.as_str()),
+                Entry::Task(_) => None,
+            })
+            .collect()
+    }
+
+    /// Verifica la política de descartar los registros más antiguos
+    #[test]
+    fn test_push_bounded_drops_oldest() {
+        let mut records = VecDeque::new();
+        assert!(!push_bounded(&mut records, line("a"), 2));
+        assert!(!push_bounded(&mut records, line("b"), 2));
+        assert!(push_bounded(&mut records, line("c"), 2));
+        assert_eq!(lines(&records), vec!["b", "c"]);
+    }
+
+    /// Verifica que una tarea encolada no se descarta al llenarse la cola
+    #[test]
+    fn test_push_bounded_keeps_tasks() {
+        let mut records = VecDeque::new();
+        assert!(!push_bounded(&mut records, Entry::Task(Box::new(|| ())), 3));
+        for text in ["a", "b", "c", "d"] {
+            push_bounded(&mut records, line(text), 3);
+        }
+        assert!(matches!(records.front(), Some(Entry::Task(_))));
+        assert_eq!(lines(&records), vec!["c", "d"]);
+
+        // Una cola llena de tareas acepta más tareas pero no líneas
+        let mut records = VecDeque::new();
+        for _ in 0..3 {
+            assert!(!push_bounded(&mut records, Entry::Task(Box::new(|| ())), 2));
+        }
+        assert!(push_bounded(&mut records, line("a"), 2));
+        assert_eq!(records.len(), 3);
+        assert!(lines(&records).is_empty());
+    }
+
+    /// Verifica que `flush` espera a que las líneas estén en disco
+    #[test]
+    fn test_append_and_flush() {
+        let path = std::env::temp_dir().join(format!(
+            "portslayer-journal-{}/log.jsonl",
+            std::process::id()
+        ));
+        let journal = Journal::start(QUEUE_CAPACITY);
+        journal.append(&path, vec!["{\"a\":1}".into(), "{\"b\":2}".into()]);
+        assert!(journal.flush(Duration::from_secs(5)));
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert_eq!(content, "{\"a\":1}\n{\"b\":2}\n");
+        assert_eq!(journal.stats().written, 2);
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+}
@@ -0,0 +1,258 @@
+/// Módulo de los pods de Kubernetes (kubelet, k3s).
+///
+/// En un nodo de Kubernetes los puertos de los pods aparecen como
+/// procesos sueltos o, en su namespace de red, como `containerd-shim`,
+/// que no dicen de qué pod son. Se le piden los pods al runtime por el
+/// socket CRI (`crictl pods -o json`, o `k3s crictl` en k3s) y cada
+/// puerto se atribuye a su pod:
+///
+/// - un proceso de un contenedor, por su cgroup (`.../kubepods/.../pod<UID>/...`),
+/// - un `containerd-shim`, por el sandbox que gestiona (`-id <ID>`).
+///
+/// `crictl` suele necesitar root; sin él, los pods se leen de los
+/// directorios de logs del kubelet (`/var/log/pods/<namespace>_<pod>_<UID>`),
+/// que solo permiten la atribución por cgroup.
+///
+/// La consulta se reutiliza unos segundos, como los puertos de Docker.
+use portslayer_core::sources::{self, SOURCE_TIMEOUT};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::process::Command;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::port_scanner::PortInfo;
+
+/// Tiempo durante el que se reutiliza la lista de pods
+const PODS_TTL: Duration = Duration::from_secs(30);
+
+/// Directorio de logs del kubelet: un subdirectorio por pod
+const POD_LOGS_DIR: &str = "/var/log/pods";
+
+/// Comandos que hablan con el socket CRI, en orden de preferencia
+const CRICTL_COMMANDS: [&[&str]; 2] = [&["crictl"], &["k3s", "crictl"]];
+
+/// Pods leídos y momento de la lectura
+type CachedPods = (Instant, Arc<Vec<Pod>>);
+
+/// Última lectura de los pods
+static PODS: Mutex<Option<CachedPods>> = Mutex::new(None);
+
+/// Pod del nodo
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Pod {
+    pub namespace: String,
+    pub name: String,
+    pub uid: String,
+    /// ID del sandbox en el runtime (`None` si se leyó de los logs)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sandbox_id: Option<String>,
+}
+
+impl Pod {
+    /// Nombre completo: "kube-system/traefik-7cd4fcff68-x2v9p".
+    pub fn full_name(&self) -> String {
+        format!("{}/{}", self.namespace, self.name)
+    }
+}
+
+/// Respuesta de `crictl pods -o json`
+#[derive(Debug, Deserialize)]
+struct CriPods {
+    #[serde(default)]
+    items: Vec<CriPod>,
+}
+
+/// Sandbox de un pod en la respuesta de `crictl`
+#[derive(Debug, Deserialize)]
+struct CriPod {
+    id: String,
+    metadata: CriMetadata,
+}
+
+/// Metadatos de un sandbox
+#[derive(Debug, Deserialize)]
+struct CriMetadata {
+    name: String,
+    namespace: String,
+    uid: String,
+}
+
+/// Pods del nodo.
+///
+/// Reutiliza la última lectura durante unos segundos; fuera de un nodo
+/// de Kubernetes la lista queda vacía.
+pub fn pods() -> Arc<Vec<Pod>> {
+    let mut cached = PODS.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((at, pods)) = cached.as_ref() {
+        if at.elapsed() < PODS_TTL {
+            return pods.clone();
+        }
+    }
+    let pods = Arc::new(load());
+    if !pods.is_empty() {
+        log::debug!("{} pods de Kubernetes en el nodo", pods.len());
+    }
+    *cached = Some((Instant::now(), pods.clone()));
+    pods
+}
+
+/// Últimos pods leídos, sin volver a leerlos aunque hayan caducado (para
+/// construir el menú sin lanzar comandos).
+pub fn cached() -> Arc<Vec<Pod>> {
+    let cached = PODS.lock().unwrap_or_else(|e| e.into_inner());
+    cached
+        .as_ref()
+        .map(|(_, pods)| pods.clone())
+        .unwrap_or_default()
+}
+
+/// Pod al que pertenece el dueño de un puerto, si lo hay.
+pub fn pod_for<'a>(port: &PortInfo, pods: &'a [Pod]) -> Option<&'a Pod> {
+    if port.pid == 0 || pods.is_empty() {
+        return None;
+    }
+    if port.process_name.starts_with("containerd-shim") {
+        let cmdline = fs::read(format!("/proc/{}/cmdline", port.pid)).ok()?;
+        let sandbox = shim_sandbox_id(&cmdline)?;
+        return pods
+            .iter()
+            .find(|pod| pod.sandbox_id.as_deref() == Some(sandbox.as_str()));
+    }
+    let cgroup = fs::read_to_string(format!("/proc/{}/cgroup", port.pid)).ok()?;
+    let uid = pod_uid_from_cgroup(&cgroup)?;
+    pods.iter().find(|pod| pod.uid == uid)
+}
+
+/// Lee los pods del runtime o, si `crictl` no responde, de los logs del
+/// kubelet.
+fn load() -> Vec<Pod> {
+    for args in CRICTL_COMMANDS {
+        let mut command = Command::new(args[0]);
+        command.args(&args[1..]).args(["pods", "-o", "json"]);
+        let output = sources::output_with_timeout(&mut command, SOURCE_TIMEOUT);
+        let Ok(output) = output.inspect_err(|e| log::debug!("{} no disponible: {}", args[0], e))
+        else {
+            continue;
+        };
+        if !output.status.success() {
+            continue;
+        }
+        match parse_crictl_pods(&String::from_utf8_lossy(&output.stdout)) {
+            Ok(pods) => return pods,
+            Err(e) => log::warn!("{}", e),
+        }
+    }
+    fs::read_dir(POD_LOGS_DIR)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| parse_pod_log_dir(&entry.file_name().to_string_lossy()))
+        .collect()
+}
+
+/// Interpreta la salida de `crictl pods -o json`.
+fn parse_crictl_pods(json: &str) -> Result<Vec<Pod>, String> {
+    let pods: CriPods =
+        serde_json::from_str(json).map_err(|e| format!("Salida de crictl inválida: {}", e))?;
+    Ok(pods
+        .items
+        .into_iter()
+        .map(|pod| Pod {
+            namespace: pod.metadata.namespace,
+            name: pod.metadata.name,
+            uid: pod.metadata.uid,
+            sandbox_id: Some(pod.id),
+        })
+        .collect())
+}
+
+/// Pod de un directorio de `/var/log/pods` ("kube-system_coredns-ccb96694c-4xkz2_7f1d...").
+///
+/// Ni el namespace ni el nombre pueden llevar "_", así que se parte por
+/// los dos primeros.
+fn parse_pod_log_dir(name: &str) -> Option<Pod> {
+    let mut parts = name.splitn(3, '_');
+    let (namespace, pod, uid) = (parts.next()?, parts.next()?, parts.next()?);
+    is_pod_uid(uid).then(|| Pod {
+        namespace: namespace.to_string(),
+        name: pod.to_string(),
+        uid: uid.to_string(),
+        sandbox_id: None,
+    })
+}
+
+/// UID del pod en el cgroup de un proceso.
+///
+/// Con el driver cgroupfs el segmento es `pod<UID>`; con el de systemd,
+/// `kubepods-besteffort-pod<UID con "_">.slice`.
+fn pod_uid_from_cgroup(cgroup: &str) -> Option<String> {
+    cgroup
+        .lines()
+        .flat_map(|line| line.split('/'))
+        .find_map(|segment| {
+            let segment = segment.trim_end_matches(".slice");
+            let start = segment.rfind("pod")? + "pod".len();
+            let uid = segment[start..].replace('_', "-");
+            is_pod_uid(&uid).then_some(uid)
+        })
+}
+
+/// Indica si un texto tiene forma de UID de pod (UUID).
+fn is_pod_uid(text: &str) -> bool {
+    text.len() == 36 && text.chars().all(|c| c.is_ascii_hexdigit() || c == '-')
+}
+
+/// Sandbox que gestiona un `containerd-shim` (argumento `-id`).
+fn shim_sandbox_id(cmdline: &[u8]) -> Option<String> {
+    let args: Vec<String> = cmdline
+        .split(|b| *b == 0)
+        .map(|arg| String::from_utf8_lossy(arg).into_owned())
+        .collect();
+    let index = args.iter().position(|arg| arg == "-id")?;
+    args.get(index + 1).cloned()
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verifica las tres formas de atribuir un puerto a su pod: por la
+    /// salida de crictl, por los logs del kubelet y por el cgroup o el
+    /// sandbox del dueño
+    #[test]
+    fn test_pod_attribution() {
+        let uid = "7f1d2c3b-4a59-4e6f-8a7b-9c0d1e2f3a4b";
+        let json = format!(
+            r#"{{"items":[{{"id":"e3b0c44298fc","metadata":{{"name":"traefik-7cd4fcff68-x2v9p",
+                "uid":"{}","namespace":"kube-system","attempt":0}},"state":"SANDBOX_READY"}}]}}"#,
+            uid
+        );
+        let pods = parse_crictl_pods(&json).unwrap();
+        assert_eq!(pods[0].full_name(), "kube-system/traefik-7cd4fcff68-x2v9p");
+        assert_eq!(pods[0].sandbox_id.as_deref(), Some("e3b0c44298fc"));
+
+        let from_logs = parse_pod_log_dir(&format!("default_web-0_{}", uid)).unwrap();
+        assert_eq!(
+            (from_logs.namespace.as_str(), from_logs.name.as_str()),
+            ("default", "web-0")
+        );
+        assert_eq!(parse_pod_log_dir("no-es-un-pod"), None);
+
+        let cgroupfs = format!("0::/kubepods/besteffort/pod{}/0123abcd", uid);
+        assert_eq!(pod_uid_from_cgroup(&cgroupfs).as_deref(), Some(uid));
+        let systemd = format!(
+            "0::/kubepods.slice/kubepods-besteffort.slice/kubepods-besteffort-pod{}.slice/cri-containerd-0123.scope",
+            uid.replace('-', "_")
+        );
+        assert_eq!(pod_uid_from_cgroup(&systemd).as_deref(), Some(uid));
+        assert_eq!(pod_uid_from_cgroup("0::/user.slice/user-1000.slice"), None);
+
+        let shim = b"/usr/bin/containerd-shim-runc-v2\0-namespace\0k8s.io\0-id\0e3b0c44298fc\0-address\0/run/containerd/containerd.sock\0";
+        assert_eq!(shim_sandbox_id(shim).as_deref(), Some("e3b0c44298fc"));
+    }
+}
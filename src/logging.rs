@@ -0,0 +1,480 @@
+/// Módulo de logging de PortSlayer.
+///
+/// El tray y el daemon escriben su log, además de en stderr, en
+/// `~/.local/state/portslayer/portslayer.log`, para poder consultarlo
+/// aunque se hayan lanzado sin terminal (inicio de sesión, systemd). Se
+/// configura en la sección `[log]`:
+///
+/// ```toml
+/// [log]
+/// level = "debug"      # error | warn | info (por defecto) | debug | trace
+/// max_size_kb = 1024   # tamaño a partir del cual se rota
+/// keep_files = 3       # archivos rotados que se conservan
+/// format = "json"      # text (por defecto) | json
+/// ```
+///
+/// Al pasar de `max_size_kb`, el archivo se renombra a `portslayer.log.1`
+/// (el `.1` anterior pasa a `.2`, y así hasta `keep_files`) y se empieza
+/// uno nuevo. `RUST_LOG` tiene prioridad sobre `level`. El CLI solo
+/// muestra los avisos por stderr y no escribe en el archivo.
+///
+/// Algunos mensajes llevan campos (`duration_ms`, `ports`, `pid`,
+/// `outcome`...): en texto se añaden al final como `clave=valor` y con
+/// `format = "json"` cada mensaje es una línea JSON con ellos, para
+/// llevar el log a Loki o Elastic:
+///
+/// ```json
+/// {"timestamp":"2026-10-14T17:07:09.123Z","level":"INFO","target":"portslayer_core::scan",
+///  "message":"Escaneo completado: 23 puertos encontrados","duration_ms":14,"ports":23}
+/// ```
+///
+/// Los últimos [`FEED_SIZE`] mensajes del tray y del daemon quedan además
+/// en memoria, numerados, para seguirlos en vivo sin abrir una terminal
+/// (`GET /logs` del daemon, ver `events::stream_logs`).
+use log::kv::{self, Key, Value, VisitSource};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map};
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::str::FromStr;
+use std::sync::{Condvar, Mutex};
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::history;
+use crate::paths;
+
+/// Nombre del archivo de log dentro del directorio de estado
+const LOG_FILE: &str = "portslayer.log";
+
+/// Tamaño por defecto a partir del cual se rota el log (KiB)
+const DEFAULT_MAX_SIZE_KB: u64 = 1024;
+
+/// Archivos rotados que se conservan por defecto
+const DEFAULT_KEEP_FILES: u32 = 3;
+
+/// Mensajes recientes que se conservan en memoria
+const FEED_SIZE: usize = 500;
+
+/// Formato de cada mensaje
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    /// Una línea legible: `[fecha NIVEL módulo] mensaje clave=valor`
+    #[default]
+    Text,
+    /// Una línea JSON por mensaje
+    Json,
+}
+
+/// Sección `[log]` de la configuración
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct LogConfig {
+    /// Nivel mínimo de los mensajes (por defecto, `info`)
+    pub level: Option<String>,
+    /// Tamaño a partir del cual se rota el archivo (KiB)
+    pub max_size_kb: u64,
+    /// Archivos rotados que se conservan
+    pub keep_files: u32,
+    /// Formato de los mensajes
+    pub format: LogFormat,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            level: None,
+            max_size_kb: DEFAULT_MAX_SIZE_KB,
+            keep_files: DEFAULT_KEEP_FILES,
+            format: LogFormat::Text,
+        }
+    }
+}
+
+impl LogConfig {
+    /// Verifica el nivel y el tamaño.
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(level) = &self.level {
+            if log::LevelFilter::from_str(level).is_err() {
+                return Err(format!(
+                    "Nivel de log desconocido: {} (usa error, warn, info, debug o trace)",
+                    level
+                ));
+            }
+        }
+        if self.max_size_kb == 0 {
+            return Err("max_size_kb del log debe ser mayor que 0".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Ruta del archivo de log.
+pub fn log_path() -> PathBuf {
+    paths::state_dir().join(LOG_FILE)
+}
+
+/// Inicializa el logging.
+///
+/// # Arguments
+/// * `long_running` - Tray o daemon: nivel y archivo de `[log]`; si no
+///   (CLI), solo avisos por stderr para no mezclarlos con la salida
+pub fn init(long_running: bool) {
+    if !long_running {
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn"))
+            .format_timestamp_secs()
+            .init();
+        return;
+    }
+
+    let config = Config::load().log;
+    let level = config.level.as_deref().unwrap_or("info");
+    let mut builder =
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or(level));
+    match config.format {
+        LogFormat::Text => builder.format_timestamp_secs(),
+        LogFormat::Json => builder.format(|buf, record| {
+            let timestamp = buf.timestamp_millis().to_string();
+            writeln!(buf, "{}", json_line(&timestamp, record))
+        }),
+    };
+    let path = log_path();
+    let file = RotatingFile::open(&path, config.max_size_kb * 1024, config.keep_files, true);
+    let error = match file {
+        Ok(file) => {
+            builder.target(env_logger::Target::Pipe(Box::new(file)));
+            None
+        }
+        Err(e) => Some(e),
+    };
+    let logger = builder.build();
+    log::set_max_level(logger.filter());
+    if log::set_boxed_logger(Box::new(FeedLogger { inner: logger })).is_err() {
+        eprintln!("El logging ya estaba inicializado");
+    }
+    if let Some(e) = error {
+        log::warn!("No se pudo abrir el log {}: {}", path.display(), e);
+    }
+}
+
+/// Línea JSON de un mensaje, con sus campos al mismo nivel que el texto.
+fn json_line(timestamp: &str, record: &log::Record) -> String {
+    let mut line = Map::new();
+    line.insert("timestamp".into(), json!(timestamp));
+    line.insert("level".into(), json!(record.level().as_str()));
+    line.insert("target".into(), json!(record.target()));
+    line.insert("message".into(), json!(record.args().to_string()));
+    line.extend(fields(record));
+    serde_json::Value::Object(line).to_string()
+}
+
+/// Campos (`clave=valor`) de un mensaje.
+fn fields(record: &log::Record) -> Map<String, serde_json::Value> {
+    let mut fields = Map::new();
+    let _ = record.key_values().visit(&mut Fields(&mut fields));
+    fields
+}
+
+/// Copia los campos de un mensaje al objeto JSON, como números o
+/// booleanos si lo son.
+struct Fields<'a>(&'a mut Map<String, serde_json::Value>);
+
+impl<'kvs> VisitSource<'kvs> for Fields<'_> {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), kv::Error> {
+        let value = if let Some(number) = value.to_u64() {
+            json!(number)
+        } else if let Some(number) = value.to_i64() {
+            json!(number)
+        } else if let Some(number) = value.to_f64() {
+            json!(number)
+        } else if let Some(flag) = value.to_bool() {
+            json!(flag)
+        } else {
+            json!(value.to_string())
+        };
+        self.0.insert(key.to_string(), value);
+        Ok(())
+    }
+}
+
+/// Abre el archivo de log con la aplicación predeterminada (`xdg-open`).
+pub fn open_log() -> Result<(), String> {
+    let path = log_path();
+    if !path.exists() {
+        return Err(format!("Todavía no hay log en {}", path.display()));
+    }
+    let mut child = Command::new("xdg-open")
+        .arg(&path)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("No se pudo ejecutar xdg-open: {}", e))?;
+    // Recoger el proceso al terminar para no dejar un zombi
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
+
+// ─────────────────────────────────────────────────────────────
+// Mensajes recientes en memoria
+// ─────────────────────────────────────────────────────────────
+
+/// Mensaje del log para seguirlo en vivo
+#[derive(Debug, Clone, Serialize)]
+pub struct LogLine {
+    /// Identificador creciente (empieza en 1 con cada arranque)
+    pub id: u64,
+    /// Segundos desde la época Unix
+    pub timestamp: u64,
+    pub level: &'static str,
+    /// Módulo que lo emitió
+    pub target: String,
+    pub message: String,
+    /// Campos del mensaje (`duration_ms`, `pid`, `outcome`...)
+    #[serde(skip_serializing_if = "Map::is_empty")]
+    pub fields: Map<String, serde_json::Value>,
+}
+
+/// Búfer circular de mensajes con su siguiente identificador
+struct Feed {
+    next_id: u64,
+    lines: VecDeque<LogLine>,
+}
+
+/// Mensajes recientes
+static FEED: Mutex<Feed> = Mutex::new(Feed {
+    next_id: 1,
+    lines: VecDeque::new(),
+});
+
+/// Avisa a quien espera mensajes nuevos
+static FEED_CHANGED: Condvar = Condvar::new();
+
+/// Logger de `env_logger` que además guarda cada mensaje en [`FEED`]
+struct FeedLogger {
+    inner: env_logger::Logger,
+}
+
+impl log::Log for FeedLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.inner.matches(record) {
+            self.inner.log(record);
+            publish(record);
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+/// Guarda un mensaje y despierta a quien lo espera.
+fn publish(record: &log::Record) {
+    let mut feed = FEED.lock().unwrap_or_else(|e| e.into_inner());
+    let id = feed.next_id;
+    feed.next_id += 1;
+    feed.lines.push_back(LogLine {
+        id,
+        timestamp: history::unix_now(),
+        level: record.level().as_str(),
+        target: record.target().to_string(),
+        message: record.args().to_string(),
+        fields: fields(record),
+    });
+    if feed.lines.len() > FEED_SIZE {
+        feed.lines.pop_front();
+    }
+    FEED_CHANGED.notify_all();
+}
+
+/// Mensajes posteriores a `last_id` (0 = todos los que se conservan),
+/// esperando hasta `timeout` si aún no hay ninguno.
+///
+/// Un `last_id` de un arranque anterior (mayor que el último) cuenta como 0.
+#[cfg_attr(not(feature = "api"), allow(dead_code))]
+pub fn lines_since(last_id: u64, timeout: Duration) -> Vec<LogLine> {
+    let feed = FEED.lock().unwrap_or_else(|e| e.into_inner());
+    let last_id = if last_id >= feed.next_id { 0 } else { last_id };
+    let (feed, _) = FEED_CHANGED
+        .wait_timeout_while(feed, timeout, |feed| feed.next_id <= last_id + 1)
+        .unwrap_or_else(|e| e.into_inner());
+    feed.lines
+        .iter()
+        .filter(|line| line.id > last_id)
+        .cloned()
+        .collect()
+}
+
+// ─────────────────────────────────────────────────────────────
+// Archivo con rotación por tamaño
+// ─────────────────────────────────────────────────────────────
+
+/// Archivo de log que se rota al llegar a `max_size`
+struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    /// Bytes escritos en el archivo actual
+    size: u64,
+    max_size: u64,
+    /// Archivos rotados que se conservan
+    keep: u32,
+    /// Copiar además cada mensaje a stderr
+    echo: bool,
+}
+
+impl RotatingFile {
+    /// Abre (o crea) el archivo para añadir al final.
+    fn open(path: &Path, max_size: u64, keep: u32, echo: bool) -> io::Result<Self> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+            size,
+            max_size,
+            keep,
+            echo,
+        })
+    }
+
+    /// Ruta del archivo rotado número `index` (`portslayer.log.1`).
+    fn rotated(&self, index: u32) -> PathBuf {
+        PathBuf::from(format!("{}.{}", self.path.display(), index))
+    }
+
+    /// Desplaza los archivos rotados y empieza uno nuevo.
+    fn rotate(&mut self) -> io::Result<()> {
+        if self.keep == 0 {
+            self.file = File::create(&self.path)?;
+        } else {
+            let _ = fs::remove_file(self.rotated(self.keep));
+            for index in (1..self.keep).rev() {
+                let _ = fs::rename(self.rotated(index), self.rotated(index + 1));
+            }
+            fs::rename(&self.path, self.rotated(1))?;
+            self.file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?;
+        }
+        self.size = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.echo {
+            let _ = io::stderr().write_all(buf);
+        }
+        if self.size > 0 && self.size + buf.len() as u64 > self.max_size {
+            if let Err(e) = self.rotate() {
+                eprintln!("No se pudo rotar el log {}: {}", self.path.display(), e);
+            }
+        }
+        self.file.write_all(buf)?;
+        self.size += buf.len() as u64;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verifica la rotación por tamaño y los archivos conservados
+    #[test]
+    fn test_rotation() {
+        let dir = std::env::temp_dir().join(format!("portslayer-log-{}", std::process::id()));
+        let path = dir.join(LOG_FILE);
+        let mut file = RotatingFile::open(&path, 10, 2, false).unwrap();
+        for line in ["uno\n", "dos\n", "tres\n", "cuatro\n", "cinco\n"] {
+            file.write_all(line.as_bytes()).unwrap();
+        }
+        file.flush().unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "cinco\n");
+        assert_eq!(
+            fs::read_to_string(dir.join("portslayer.log.1")).unwrap(),
+            "cuatro\n"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.join("portslayer.log.2")).unwrap(),
+            "tres\n"
+        );
+        assert!(!dir.join("portslayer.log.3").exists());
+        let _ = fs::remove_dir_all(&dir);
+
+        let config: LogConfig = toml::from_str("level = \"debug\"").unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.max_size_kb, DEFAULT_MAX_SIZE_KB);
+        let config: LogConfig = toml::from_str("level = \"ruidoso\"").unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    /// Verifica que los mensajes guardados se leen a partir de un id
+    #[test]
+    fn test_lines_since() {
+        let before = FEED.lock().unwrap().next_id - 1;
+        for message in [format_args!("uno"), format_args!("dos")] {
+            publish(
+                &log::Record::builder()
+                    .args(message)
+                    .level(log::Level::Warn)
+                    .target("portslayer::tray")
+                    .build(),
+            );
+        }
+        let lines = lines_since(before, Duration::ZERO);
+        let messages: Vec<&str> = lines.iter().map(|l| l.message.as_str()).collect();
+        assert_eq!(messages, ["uno", "dos"]);
+        assert_eq!(lines[0].level, "WARN");
+        assert!(lines_since(lines[1].id, Duration::ZERO).is_empty());
+        // Un id de un arranque anterior devuelve todo lo guardado
+        assert_eq!(lines_since(u64::MAX, Duration::ZERO).len(), lines.len());
+    }
+
+    /// Verifica la línea JSON de un mensaje con campos
+    #[test]
+    fn test_json_line() {
+        let fields: [(&str, Value); 3] = [
+            ("ports", Value::from(23u64)),
+            ("outcome", Value::from("killed")),
+            ("elevated", Value::from(false)),
+        ];
+        let line = json_line(
+            "2026-10-14T17:07:09.123Z",
+            &log::Record::builder()
+                .args(format_args!("Escaneo completado"))
+                .level(log::Level::Info)
+                .target("portslayer::daemon")
+                .key_values(&fields)
+                .build(),
+        );
+        let value: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value["level"], "INFO");
+        assert_eq!(value["message"], "Escaneo completado");
+        assert_eq!(value["ports"], 23);
+        assert_eq!(value["outcome"], "killed");
+        assert_eq!(value["elevated"], false);
+        assert!(!line.contains('\n'));
+    }
+}
@@ -7,15 +7,96 @@
 //! - Ícono en la bandeja del sistema con menú contextual
 //! - Lista dinámica de puertos TCP/UDP abiertos
 //! - Cierre individual o masivo de puertos
-//! - Actualización automática cada 10 segundos
+//! - Actualización automática configurable (10 segundos por defecto)
 //! - Soporte para solicitar permisos elevados vía pkexec
+//! - Modo daemon sin interfaz con API HTTP JSON
+//! - CLI para scripts y CI (`list`, `kill`, `killall`, `watch`)
 //!
 //! ## Uso
 //! Ejecutar el binario para que aparezca en la bandeja del sistema.
 //! Clic derecho sobre el ícono para ver el menú con los puertos.
+//!
+//! `portslayer <subcomando>` usa el CLI (ver `portslayer --help`);
+//! `portslayer daemon [--bind 127.0.0.1:7780]` inicia el modo
+//! headless con la API HTTP en lugar del tray (feature `api`, incluida
+//! por defecto). `portslayer replay sesion.jsonl` abre el tray con una
+//! sesión grabada en lugar de los puertos del sistema.
 
+mod adopt;
+#[cfg(feature = "api")]
+mod api;
+#[cfg(feature = "api")]
+mod auth;
+mod autostart;
+mod badge;
+mod capabilities;
+mod cli;
+mod clipboard;
+mod completions;
+mod config;
+mod connections;
+mod cpu;
+#[cfg(feature = "api")]
+mod daemon;
+#[cfg(feature = "api")]
+mod dashboard;
+mod docker;
+mod effectiveness;
+#[cfg(feature = "email")]
+mod email;
+#[cfg(feature = "api")]
+mod events;
+mod explain;
+mod export;
+mod fallback;
+mod fingerprint;
+mod firewall;
+mod firewall_manager;
+mod health;
+mod history;
+mod i18n;
+mod journal;
+mod kubernetes;
+mod logging;
+mod maintenance;
+mod manifest;
+#[cfg(feature = "pam")]
+mod pam;
+mod paths;
+mod pins;
+mod polkit;
 mod port_scanner;
+mod project;
+mod replay;
+mod reservations;
+mod restart;
+mod rules;
+mod safety;
+mod sandbox;
+#[cfg(feature = "api")]
+mod settings;
+mod shutdown;
+mod snapshot;
+mod tags;
+mod theme;
+mod throttle;
+mod time_wait;
+mod tls;
+mod topology;
+mod traffic;
 mod tray;
+mod tui;
+mod tunnels;
+mod watchlist;
+#[cfg(feature = "api")]
+mod webhooks;
+mod workspace;
+mod zombie;
+
+use clap::Parser;
+use std::process::ExitCode;
+
+use cli::{Cli, Command};
 
 /// Desvincula el proceso de la terminal que lo inició.
 ///
@@ -36,22 +117,67 @@ fn daemonize() {
 
 /// Punto de entrada principal de PortSlayer.
 ///
-/// Inicializa el sistema de logging, se desvincula de la terminal
-/// y lanza el system tray. La aplicación se ejecuta indefinidamente
-/// hasta que el usuario seleccione "Salir" del menú contextual.
-fn main() {
-    // Inicializar logging (nivel INFO por defecto, configurable con RUST_LOG)
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info"))
-        .format_timestamp_secs()
-        .init();
+/// Sin subcomando (o con `tray`/`daemon`) inicializa el logging, se
+/// desvincula de la terminal y se ejecuta indefinidamente hasta que el
+/// usuario seleccione "Salir" del menú contextual. El resto de
+/// subcomandos del CLI se ejecutan una vez y devuelven su código de salida.
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+    let long_running = match cli.command {
+        None | Some(Command::Tray) => true,
+        Some(Command::Replay { at: None, .. }) => true,
+        #[cfg(feature = "api")]
+        Some(Command::Daemon { .. }) => true,
+        Some(_) => false,
+    };
+
+    // Inicializar logging (archivo con rotación en el tray y el daemon; en
+    // el CLI solo avisos, para no mezclar logs con la salida)
+    logging::init(long_running);
+
+    if !long_running {
+        let code = cli::run(cli.command.expect("subcomando del CLI"));
+        // Esperar a que el historial pendiente llegue a disco antes de salir
+        journal::flush(journal::FLUSH_TIMEOUT);
+        return code;
+    }
 
     log::info!("⚔️  PortSlayer v{} iniciando...", env!("CARGO_PKG_VERSION"));
     log::info!("Sistema de monitoreo de puertos para Linux");
 
+    // Cargar la sesión a reproducir antes de soltar la terminal, para
+    // poder informar de un archivo inválido
+    if let Some(Command::Replay {
+        file,
+        speed,
+        repeat,
+        ..
+    }) = &cli.command
+    {
+        match replay::Replay::load(file, *speed, *repeat) {
+            Ok(session) => replay::start(session),
+            Err(e) => {
+                log::error!("{}", e);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
     // Desengancharse de la terminal para sobrevivir al cierre de la sesión.
     // Esto permite ejecutar `portslayer &` sin necesitar `nohup`.
     daemonize();
 
-    // Lanzar el system tray (bloquea el hilo principal)
-    tray::run_tray();
+    // Lanzar el modo seleccionado (ambos bloquean el hilo principal)
+    match cli.command {
+        #[cfg(feature = "api")]
+        Some(Command::Daemon { bind, dashboard }) => {
+            if let Err(e) = daemon::run_daemon(&bind, dashboard) {
+                log::error!("{}", e);
+                return ExitCode::FAILURE;
+            }
+        }
+        _ => tray::run_tray(),
+    }
+
+    ExitCode::SUCCESS
 }
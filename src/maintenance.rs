@@ -0,0 +1,279 @@
+/// Módulo de ventanas de mantenimiento.
+///
+/// Una ventana de mantenimiento es un periodo recurrente, definido con
+/// una expresión estilo cron en la configuración, durante el cual las
+/// reglas de cierre automático y las alertas se suprimen. Pensado para
+/// usuarios con tareas programadas que abren muchos puertos (backups,
+/// builds nocturnos, etc.).
+use jiff::civil::DateTime;
+use jiff::ToSpan;
+use serde::Deserialize;
+
+/// Duración máxima admitida para una ventana (7 días, en minutos)
+const MAX_DURATION_MINUTES: u32 = 7 * 24 * 60;
+
+/// Ventana de mantenimiento configurada por el usuario
+#[derive(Debug, Clone, Deserialize)]
+pub struct MaintenanceWindow {
+    /// Nombre descriptivo mostrado en el tray
+    pub name: String,
+    /// Expresión cron de inicio: "minuto hora día mes día_semana"
+    pub schedule: String,
+    /// Duración de la ventana desde cada inicio, en minutos
+    pub duration_minutes: u32,
+}
+
+impl MaintenanceWindow {
+    /// Comprueba que la expresión y la duración son válidas.
+    pub fn validate(&self) -> Result<(), String> {
+        CronSchedule::parse(&self.schedule)
+            .map_err(|e| format!("Ventana '{}': {}", self.name, e))?;
+        if self.duration_minutes == 0 || self.duration_minutes > MAX_DURATION_MINUTES {
+            return Err(format!(
+                "Ventana '{}': la duración debe estar entre 1 y {} minutos",
+                self.name, MAX_DURATION_MINUTES
+            ));
+        }
+        Ok(())
+    }
+
+    /// Devuelve el fin de la ventana si está activa en el momento indicado.
+    ///
+    /// Busca hacia atrás, minuto a minuto y hasta la duración de la
+    /// ventana, un inicio que coincida con la expresión cron.
+    ///
+    /// # Arguments
+    /// * `now` - Fecha y hora local a evaluar
+    ///
+    /// # Returns
+    /// `Some(fin)` si la ventana cubre `now`, `None` en caso contrario.
+    pub fn active_until(&self, now: DateTime) -> Option<DateTime> {
+        let schedule = CronSchedule::parse(&self.schedule).ok()?;
+        // Truncar a minutos: los inicios de cron siempre son en el segundo 0
+        let mut start = now.with().second(0).subsec_nanosecond(0).build().ok()?;
+
+        for _ in 0..self.duration_minutes {
+            if schedule.matches(start) {
+                return start
+                    .checked_add((self.duration_minutes as i64).minutes())
+                    .ok();
+            }
+            start = start.checked_sub(1.minute()).ok()?;
+        }
+        None
+    }
+}
+
+/// Devuelve la primera ventana activa y su hora de fin.
+///
+/// # Arguments
+/// * `windows` - Ventanas configuradas
+/// * `now` - Fecha y hora local a evaluar
+pub fn active_window(
+    windows: &[MaintenanceWindow],
+    now: DateTime,
+) -> Option<(&MaintenanceWindow, DateTime)> {
+    windows
+        .iter()
+        .find_map(|w| w.active_until(now).map(|end| (w, end)))
+}
+
+/// Fecha y hora local actual según la zona horaria del sistema.
+pub fn local_now() -> DateTime {
+    jiff::Zoned::now().datetime()
+}
+
+// ─────────────────────────────────────────────────────────────
+// Expresiones cron
+// ─────────────────────────────────────────────────────────────
+
+/// Expresión cron de cinco campos ya interpretada.
+///
+/// Cada campo se guarda como un vector de booleanos indexado por el
+/// valor permitido (ej: `hours[2]` es `true` si la hora 2 coincide).
+#[derive(Debug, Clone, PartialEq)]
+struct CronSchedule {
+    minutes: Vec<bool>,
+    hours: Vec<bool>,
+    days: Vec<bool>,
+    months: Vec<bool>,
+    weekdays: Vec<bool>,
+    /// El campo día del mes era `*`
+    any_day: bool,
+    /// El campo día de la semana era `*`
+    any_weekday: bool,
+}
+
+impl CronSchedule {
+    /// Parsea una expresión "minuto hora día mes día_semana".
+    ///
+    /// Cada campo admite `*`, valores (`5`), rangos (`1-5`), listas
+    /// (`1,15`) y pasos (`*/10`, `0-30/5`). En el día de la semana,
+    /// 0 y 7 son domingo.
+    fn parse(expr: &str) -> Result<Self, String> {
+        let fields: Vec<&str> = expr.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(format!("la expresión cron '{}' debe tener 5 campos", expr));
+        }
+
+        let mut weekdays = parse_field(fields[4], 0, 7)?;
+        // El 7 es un alias del domingo (0)
+        if weekdays[7] {
+            weekdays[0] = true;
+        }
+        weekdays.truncate(7);
+
+        Ok(Self {
+            minutes: parse_field(fields[0], 0, 59)?,
+            hours: parse_field(fields[1], 0, 23)?,
+            days: parse_field(fields[2], 1, 31)?,
+            months: parse_field(fields[3], 1, 12)?,
+            weekdays,
+            any_day: fields[2] == "*",
+            any_weekday: fields[4] == "*",
+        })
+    }
+
+    /// Comprueba si un instante (truncado al minuto) coincide.
+    ///
+    /// Igual que en cron, si día del mes y día de la semana están
+    /// restringidos basta con que coincida uno de los dos.
+    fn matches(&self, dt: DateTime) -> bool {
+        let day_ok = self.days[dt.day() as usize];
+        let weekday_ok = self.weekdays[dt.weekday().to_sunday_zero_offset() as usize];
+        let calendar_ok = match (self.any_day, self.any_weekday) {
+            (true, true) => true,
+            (false, true) => day_ok,
+            (true, false) => weekday_ok,
+            (false, false) => day_ok || weekday_ok,
+        };
+
+        self.minutes[dt.minute() as usize]
+            && self.hours[dt.hour() as usize]
+            && self.months[dt.month() as usize]
+            && calendar_ok
+    }
+}
+
+/// Parsea un campo cron a un vector de coincidencias indexado por valor.
+///
+/// # Arguments
+/// * `field` - Texto del campo (ej: "1-5", "*/15", "0,30")
+/// * `min` - Valor mínimo permitido
+/// * `max` - Valor máximo permitido
+fn parse_field(field: &str, min: u32, max: u32) -> Result<Vec<bool>, String> {
+    let mut allowed = vec![false; max as usize + 1];
+
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step: u32 = step
+                    .parse()
+                    .map_err(|_| format!("paso inválido en '{}'", part))?;
+                if step == 0 {
+                    return Err(format!("paso cero en '{}'", part));
+                }
+                (range, step)
+            }
+            None => (part, 1),
+        };
+
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((a, b)) = range.split_once('-') {
+            (parse_value(a, min, max)?, parse_value(b, min, max)?)
+        } else {
+            let value = parse_value(range, min, max)?;
+            // "5/10" significa "desde 5 hasta el máximo cada 10"
+            (value, if step > 1 { max } else { value })
+        };
+
+        if start > end {
+            return Err(format!("rango invertido en '{}'", part));
+        }
+        for value in (start..=end).step_by(step as usize) {
+            allowed[value as usize] = true;
+        }
+    }
+
+    Ok(allowed)
+}
+
+/// Parsea un valor numérico de un campo cron validando sus límites.
+fn parse_value(text: &str, min: u32, max: u32) -> Result<u32, String> {
+    let value: u32 = text
+        .parse()
+        .map_err(|_| format!("valor inválido '{}'", text))?;
+    if value < min || value > max {
+        return Err(format!("valor {} fuera de rango ({}-{})", value, min, max));
+    }
+    Ok(value)
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jiff::civil::date;
+
+    fn window(schedule: &str, duration_minutes: u32) -> MaintenanceWindow {
+        MaintenanceWindow {
+            name: "test".into(),
+            schedule: schedule.into(),
+            duration_minutes,
+        }
+    }
+
+    /// Verifica el parseo de campos con rangos, listas y pasos
+    #[test]
+    fn test_parse_field() {
+        let minutes = parse_field("*/15", 0, 59).unwrap();
+        assert!(minutes[0] && minutes[15] && minutes[45]);
+        assert!(!minutes[10]);
+
+        let hours = parse_field("1-3,22", 0, 23).unwrap();
+        assert!(hours[1] && hours[2] && hours[3] && hours[22]);
+        assert!(!hours[4]);
+
+        assert!(parse_field("60", 0, 59).is_err());
+        assert!(parse_field("5-1", 0, 59).is_err());
+        assert!(parse_field("*/0", 0, 59).is_err());
+    }
+
+    /// Verifica que una expresión debe tener cinco campos
+    #[test]
+    fn test_parse_schedule_invalid() {
+        assert!(CronSchedule::parse("0 2 * *").is_err());
+        assert!(window("0 2 * * *", 0).validate().is_err());
+        assert!(window("0 2 * * *", 60).validate().is_ok());
+    }
+
+    /// Verifica la detección de ventana activa y su hora de fin
+    #[test]
+    fn test_active_until() {
+        let nightly = window("0 2 * * *", 90);
+        let inside = date(2024, 5, 10).at(3, 15, 30, 0);
+        let end = nightly.active_until(inside).unwrap();
+        assert_eq!(end, date(2024, 5, 10).at(3, 30, 0, 0));
+
+        let after = date(2024, 5, 10).at(3, 30, 0, 0);
+        assert!(nightly.active_until(after).is_none());
+        let before = date(2024, 5, 10).at(1, 59, 0, 0);
+        assert!(nightly.active_until(before).is_none());
+    }
+
+    /// Verifica el filtrado por día de la semana (2024-05-11 es sábado)
+    #[test]
+    fn test_weekday_schedule() {
+        let weekdays = window("0 9 * * 1-5", 60);
+        assert!(weekdays
+            .active_until(date(2024, 5, 10).at(9, 30, 0, 0))
+            .is_some());
+        assert!(weekdays
+            .active_until(date(2024, 5, 11).at(9, 30, 0, 0))
+            .is_none());
+    }
+}
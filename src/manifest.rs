@@ -0,0 +1,173 @@
+/// Módulo de los manifiestos de proyecto (`.portslayer.toml`).
+///
+/// Un equipo que fija los puertos de desarrollo de un repositorio los
+/// declara en un `.portslayer.toml` en su raíz:
+///
+/// ```toml
+/// name = "tienda-web"
+///
+/// [ports]
+/// web = 3000
+/// api = 8080
+/// db = 5432
+/// ```
+///
+/// Desde cualquier directorio del proyecto, `portslayer project` dice si
+/// esos puertos están libres (y quién ocupa los que no) y `portslayer
+/// project --kill` los libera. El tray hace lo mismo con el manifiesto del
+/// directorio desde el que se lanzó.
+///
+/// El manifiesto se busca en el directorio actual y en sus ancestros,
+/// como `.git`. Un puerto declarado cuenta como ocupado en TCP y en UDP.
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::port_scanner::PortInfo;
+
+/// Nombre del archivo de manifiesto
+pub const FILE_NAME: &str = ".portslayer.toml";
+
+/// Puertos declarados por un proyecto
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Manifest {
+    /// Nombre del proyecto (por defecto, el de su directorio)
+    pub name: Option<String>,
+    /// Puertos por servicio ("web" → 3000)
+    pub ports: BTreeMap<String, u16>,
+}
+
+/// Estado de un puerto declarado
+#[derive(Debug)]
+pub struct PortStatus<'a> {
+    /// Servicio que lo declara
+    pub service: &'a str,
+    /// Número del puerto
+    pub port: u16,
+    /// Sockets que escuchan en él (vacío si está libre)
+    pub owners: Vec<&'a PortInfo>,
+}
+
+impl Manifest {
+    /// Interpreta el contenido de un `.portslayer.toml`.
+    pub fn parse(content: &str) -> Result<Self, String> {
+        let manifest: Manifest = toml::from_str(content).map_err(|e| e.to_string())?;
+        if let Some((service, _)) = manifest.ports.iter().find(|(_, port)| **port == 0) {
+            return Err(format!("Puerto inválido para {}: 0", service));
+        }
+        Ok(manifest)
+    }
+
+    /// Busca el manifiesto en `dir` y en sus ancestros.
+    ///
+    /// # Returns
+    /// La ruta del manifiesto y su contenido, `None` si no hay ninguno o
+    /// un error si el más cercano no se puede leer o es inválido.
+    pub fn find(dir: &Path) -> Result<Option<(PathBuf, Self)>, String> {
+        let Some(path) = dir
+            .ancestors()
+            .map(|dir| dir.join(FILE_NAME))
+            .find(|path| path.is_file())
+        else {
+            return Ok(None);
+        };
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("No se pudo leer {}: {}", path.display(), e))?;
+        let manifest = Self::parse(&content).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Ok(Some((path, manifest)))
+    }
+
+    /// Busca el manifiesto desde el directorio actual (ver [`Manifest::find`]).
+    pub fn discover() -> Result<Option<(PathBuf, Self)>, String> {
+        match std::env::current_dir() {
+            Ok(dir) => Self::find(&dir),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Nombre del proyecto: el declarado o el del directorio del manifiesto.
+    pub fn display_name(&self, path: &Path) -> String {
+        self.name.clone().unwrap_or_else(|| {
+            path.parent()
+                .and_then(Path::file_name)
+                .map_or_else(|| "proyecto".into(), |n| n.to_string_lossy().into_owned())
+        })
+    }
+
+    /// Estado de cada puerto declarado según un escaneo, por número.
+    pub fn status<'a>(&'a self, ports: &'a [PortInfo]) -> Vec<PortStatus<'a>> {
+        let mut status: Vec<PortStatus> = self
+            .ports
+            .iter()
+            .map(|(service, port)| PortStatus {
+                service,
+                port: *port,
+                owners: ports.iter().filter(|p| p.port == *port).collect(),
+            })
+            .collect();
+        status.sort_by_key(|s| s.port);
+        status
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn port(port: u16, pid: u32) -> PortInfo {
+        PortInfo {
+            protocol: "tcp".into(),
+            port,
+            local_address: "127.0.0.1".into(),
+            pid,
+            process_name: "node".into(),
+            ..Default::default()
+        }
+    }
+
+    /// Verifica la lectura del manifiesto y el estado de sus puertos
+    #[test]
+    fn test_manifest_status() {
+        let manifest =
+            Manifest::parse("name = \"tienda-web\"\n[ports]\nweb = 3000\ndb = 5432\n").unwrap();
+        assert_eq!(
+            manifest.display_name(Path::new("/src/x/.portslayer.toml")),
+            "tienda-web"
+        );
+        assert!(Manifest::parse("[ports]\nweb = 0\n").is_err());
+        assert!(Manifest::parse("[puertos]\nweb = 3000\n").is_err());
+
+        let ports = [port(3000, 42), port(8080, 7)];
+        let status = manifest.status(&ports);
+        assert_eq!(status.len(), 2);
+        assert_eq!((status[0].service, status[0].port), ("web", 3000));
+        assert_eq!(status[0].owners.len(), 1);
+        assert_eq!((status[1].service, status[1].owners.len()), ("db", 0));
+    }
+
+    /// Verifica que el manifiesto se encuentra desde un subdirectorio
+    #[test]
+    fn test_find_manifest() {
+        let root = std::env::temp_dir().join(format!("portslayer-manifest-{}", std::process::id()));
+        let nested = root.join("packages/api");
+        fs::create_dir_all(&nested).unwrap();
+        assert!(Manifest::find(&nested)
+            .unwrap()
+            .is_none_or(|(path, _)| !path.starts_with(&root)));
+
+        fs::write(root.join(FILE_NAME), "[ports]\napi = 8080\n").unwrap();
+        let (path, manifest) = Manifest::find(&nested).unwrap().unwrap();
+        assert_eq!(path, root.join(FILE_NAME));
+        assert_eq!(manifest.ports.get("api"), Some(&8080));
+        assert!(manifest
+            .display_name(&path)
+            .starts_with("portslayer-manifest-"));
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
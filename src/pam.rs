@@ -0,0 +1,137 @@
+/// Módulo de verificación de contraseñas con PAM (feature `pam`).
+///
+/// Enlaza directamente con `libpam` para no añadir dependencias: se
+/// inicia una transacción con el servicio configurado, se responde a
+/// los mensajes de la conversación con la contraseña recibida y se
+/// comprueban la autenticación y el estado de la cuenta.
+use std::ffi::{c_char, c_int, c_void, CString};
+use std::ptr;
+
+/// El módulo pide un dato sin eco (contraseña)
+const PAM_PROMPT_ECHO_OFF: c_int = 1;
+/// Mensaje de error del módulo (no espera respuesta)
+const PAM_ERROR_MSG: c_int = 3;
+/// Mensaje informativo del módulo (no espera respuesta)
+const PAM_TEXT_INFO: c_int = 4;
+/// Código de éxito de las funciones de PAM
+const PAM_SUCCESS: c_int = 0;
+/// Error de conversación
+const PAM_CONV_ERR: c_int = 19;
+
+#[repr(C)]
+struct PamMessage {
+    msg_style: c_int,
+    msg: *const c_char,
+}
+
+#[repr(C)]
+struct PamResponse {
+    resp: *mut c_char,
+    resp_retcode: c_int,
+}
+
+#[repr(C)]
+struct PamConv {
+    conv: extern "C" fn(c_int, *mut *const PamMessage, *mut *mut PamResponse, *mut c_void) -> c_int,
+    appdata_ptr: *mut c_void,
+}
+
+#[link(name = "pam")]
+extern "C" {
+    fn pam_start(
+        service_name: *const c_char,
+        user: *const c_char,
+        pam_conversation: *const PamConv,
+        pamh: *mut *mut c_void,
+    ) -> c_int;
+    fn pam_authenticate(pamh: *mut c_void, flags: c_int) -> c_int;
+    fn pam_acct_mgmt(pamh: *mut c_void, flags: c_int) -> c_int;
+    fn pam_end(pamh: *mut c_void, pam_status: c_int) -> c_int;
+}
+
+extern "C" {
+    fn calloc(count: usize, size: usize) -> *mut c_void;
+    fn strdup(s: *const c_char) -> *mut c_char;
+    fn free(ptr: *mut c_void);
+}
+
+/// Responde con la contraseña (`appdata_ptr`) a las peticiones sin eco.
+///
+/// Cualquier otra petición (con eco, como un nombre de usuario o un
+/// código) hace fallar la conversación: la contraseña solo se entrega a
+/// quien la pide como contraseña. Los mensajes informativos y de error
+/// no llevan respuesta.
+///
+/// PAM libera las respuestas con `free()`, por eso se reservan con
+/// `calloc`/`strdup`.
+extern "C" fn conversation(
+    num_msg: c_int,
+    msg: *mut *const PamMessage,
+    resp: *mut *mut PamResponse,
+    appdata_ptr: *mut c_void,
+) -> c_int {
+    if num_msg <= 0 || msg.is_null() || resp.is_null() || appdata_ptr.is_null() {
+        return PAM_CONV_ERR;
+    }
+    let count = num_msg as usize;
+    // SAFETY: PAM entrega `num_msg` mensajes válidos y espera un arreglo
+    // de respuestas del mismo tamaño reservado con malloc.
+    unsafe {
+        let responses = calloc(count, std::mem::size_of::<PamResponse>()) as *mut PamResponse;
+        if responses.is_null() {
+            return PAM_CONV_ERR;
+        }
+        let password = appdata_ptr as *const c_char;
+        for i in 0..count {
+            let message = *msg.add(i);
+            match (*message).msg_style {
+                PAM_PROMPT_ECHO_OFF => (*responses.add(i)).resp = strdup(password),
+                PAM_ERROR_MSG | PAM_TEXT_INFO => {}
+                _ => {
+                    for j in 0..i {
+                        free((*responses.add(j)).resp as *mut c_void);
+                    }
+                    free(responses as *mut c_void);
+                    return PAM_CONV_ERR;
+                }
+            }
+        }
+        *resp = responses;
+    }
+    PAM_SUCCESS
+}
+
+/// Verifica usuario y contraseña contra el servicio PAM indicado.
+///
+/// # Returns
+/// `Err(String)` con el código de PAM si la autenticación falla o la
+/// cuenta no está habilitada.
+pub fn authenticate(service: &str, user: &str, password: &str) -> Result<(), String> {
+    let service = CString::new(service).map_err(|e| e.to_string())?;
+    let user = CString::new(user).map_err(|e| e.to_string())?;
+    let password = CString::new(password).map_err(|e| e.to_string())?;
+
+    let conv = PamConv {
+        conv: conversation,
+        appdata_ptr: password.as_ptr() as *mut c_void,
+    };
+    let mut handle: *mut c_void = ptr::null_mut();
+
+    // SAFETY: todas las cadenas y la conversación viven hasta `pam_end`.
+    unsafe {
+        let mut status = pam_start(service.as_ptr(), user.as_ptr(), &conv, &mut handle);
+        if status != PAM_SUCCESS {
+            return Err(format!("pam_start devolvió {}", status));
+        }
+        status = pam_authenticate(handle, 0);
+        if status == PAM_SUCCESS {
+            status = pam_acct_mgmt(handle, 0);
+        }
+        pam_end(handle, status);
+        if status == PAM_SUCCESS {
+            Ok(())
+        } else {
+            Err(format!("código PAM {}", status))
+        }
+    }
+}
@@ -0,0 +1,53 @@
+/// Módulo de rutas de archivos de PortSlayer.
+///
+/// Resuelve los directorios estándar XDG donde la aplicación guarda
+/// su configuración y su estado persistente:
+/// - Configuración: `$XDG_CONFIG_HOME/portslayer` (o `~/.config/portslayer`)
+/// - Estado: `$XDG_STATE_HOME/portslayer` (o `~/.local/state/portslayer`)
+use std::env;
+use std::path::PathBuf;
+
+/// Nombre del subdirectorio propio de la aplicación
+const APP_DIR: &str = "portslayer";
+
+/// Directorio de configuración de PortSlayer.
+///
+/// # Returns
+/// Ruta al directorio (puede no existir todavía).
+pub fn config_dir() -> PathBuf {
+    xdg_dir("XDG_CONFIG_HOME", ".config")
+}
+
+/// Directorio de estado persistente (historial, logs).
+///
+/// # Returns
+/// Ruta al directorio (puede no existir todavía).
+pub fn state_dir() -> PathBuf {
+    xdg_dir("XDG_STATE_HOME", ".local/state")
+}
+
+/// Directorio base de configuración (`$XDG_CONFIG_HOME` o `~/.config`),
+/// compartido con otras aplicaciones (ej: `autostart/`).
+pub fn config_home() -> PathBuf {
+    xdg_base("XDG_CONFIG_HOME", ".config")
+}
+
+/// Resuelve un directorio XDG con su valor por defecto relativo a `$HOME`.
+///
+/// # Arguments
+/// * `var` - Variable de entorno XDG a consultar
+/// * `fallback` - Ruta relativa a `$HOME` si la variable no está definida
+fn xdg_dir(var: &str, fallback: &str) -> PathBuf {
+    xdg_base(var, fallback).join(APP_DIR)
+}
+
+/// Directorio XDG base, sin el subdirectorio de la aplicación.
+fn xdg_base(var: &str, fallback: &str) -> PathBuf {
+    match env::var(var) {
+        Ok(value) if !value.is_empty() => PathBuf::from(value),
+        _ => {
+            let home = env::var("HOME").unwrap_or_else(|_| "/tmp".to_string());
+            PathBuf::from(home).join(fallback)
+        }
+    }
+}
@@ -0,0 +1,149 @@
+/// Módulo de puertos fijados.
+///
+/// Los puertos fijados (ej: 3000, 5432, 6379) aparecen siempre al
+/// principio del menú del tray, sin importar la página ni los filtros,
+/// y muestran su estado aunque no haya nada escuchando en ellos. A
+/// diferencia de los favoritos de un espacio de trabajo, son globales.
+///
+/// Se guardan en `~/.config/portslayer/pins.json` y se gestionan con
+/// `portslayer pin`; la API los expone en `GET /pinned`.
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::paths;
+use crate::port_scanner::PortInfo;
+
+/// Nombre del archivo de puertos fijados dentro del directorio de configuración
+const PINS_FILE: &str = "pins.json";
+
+/// Estado de un puerto fijado en el último escaneo
+#[derive(Debug, Clone, Serialize)]
+pub struct PinnedStatus {
+    /// Puerto fijado
+    pub port: u16,
+    /// Hay algún proceso escuchando
+    pub open: bool,
+    /// Procesos que lo ocupan (TCP y UDP)
+    pub listeners: Vec<PortInfo>,
+}
+
+/// Puertos fijados persistidos en disco
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct PinStore {
+    /// Puertos en el orden en que se fijaron
+    pub ports: Vec<u16>,
+}
+
+impl PinStore {
+    /// Ruta del archivo de puertos fijados.
+    pub fn path() -> PathBuf {
+        paths::config_dir().join(PINS_FILE)
+    }
+
+    /// Carga los puertos fijados; vacío si el archivo no existe o no es válido.
+    pub fn load() -> Self {
+        let path = Self::path();
+        match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                log::error!(
+                    "Archivo de puertos fijados inválido {}: {}",
+                    path.display(),
+                    e
+                );
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Guarda los puertos fijados en disco.
+    pub fn save(&self) -> io::Result<()> {
+        let path = Self::path();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json)
+    }
+
+    /// Fija un puerto.
+    ///
+    /// # Returns
+    /// `false` si ya estaba fijado.
+    pub fn pin(&mut self, port: u16) -> bool {
+        if self.is_pinned(port) {
+            return false;
+        }
+        self.ports.push(port);
+        true
+    }
+
+    /// Quita un puerto de los fijados.
+    ///
+    /// # Returns
+    /// `false` si no estaba fijado.
+    pub fn unpin(&mut self, port: u16) -> bool {
+        let before = self.ports.len();
+        self.ports.retain(|p| *p != port);
+        self.ports.len() != before
+    }
+
+    /// Indica si un puerto está fijado.
+    pub fn is_pinned(&self, port: u16) -> bool {
+        self.ports.contains(&port)
+    }
+
+    /// Estado de cada puerto fijado según un escaneo.
+    pub fn status(&self, ports: &[PortInfo]) -> Vec<PinnedStatus> {
+        self.ports
+            .iter()
+            .map(|&port| {
+                let listeners: Vec<PortInfo> =
+                    ports.iter().filter(|p| p.port == port).cloned().collect();
+                PinnedStatus {
+                    port,
+                    open: !listeners.is_empty(),
+                    listeners,
+                }
+            })
+            .collect()
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verifica el estado de los puertos fijados, abiertos o no
+    #[test]
+    fn test_pinned_status() {
+        let mut store = PinStore::default();
+        assert!(store.pin(5432));
+        assert!(store.pin(3000));
+        assert!(!store.pin(3000));
+
+        let ports = vec![PortInfo {
+            protocol: "tcp".into(),
+            port: 3000,
+            local_address: "0.0.0.0".into(),
+            pid: 7,
+            process_name: "node".into(),
+            ..Default::default()
+        }];
+        let status = store.status(&ports);
+        assert_eq!(status[0].port, 5432);
+        assert!(!status[0].open);
+        assert!(status[1].open);
+        assert_eq!(status[1].listeners[0].pid, 7);
+
+        assert!(store.unpin(5432));
+        assert!(!store.unpin(5432));
+        assert_eq!(store.ports, vec![3000]);
+    }
+}
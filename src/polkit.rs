@@ -0,0 +1,260 @@
+/// Módulo del servicio de kills autorizado por polkit.
+///
+/// Cuando el sistema rechaza un kill por permisos, en lugar del diálogo
+/// genérico de `pkexec kill` se le pide al servicio
+/// `io.github.artur282.PortSlayer1` del bus del sistema. D-Bus lo arranca
+/// como root la primera vez que se usa (ver `assets/dbus/`) y, antes de
+/// enviar la señal, pregunta a polkit por la acción
+/// `io.github.artur282.portslayer.kill` (ver `assets/polkit/`) con el
+/// proceso y sus puertos como detalles. El diálogo dice así "PortSlayer
+/// quiere terminar el proceso node (PID 4242, puertos: 3000/tcp)".
+///
+/// Los detalles los calcula el servicio, no quien pide el kill: el
+/// mensaje no puede mentir sobre qué se va a cerrar. El servicio termina
+/// solo tras un minuto sin peticiones.
+///
+/// Si el servicio no está instalado, [`kill`] devuelve `None` y se sigue
+/// con `pkexec`.
+use dbus::arg::{PropMap, Variant};
+use dbus::blocking::Connection;
+use dbus::message::MessageType;
+use dbus::strings::ErrorName;
+use dbus::Message;
+use portslayer_core::kill::{self as core_kill, KillError};
+use std::collections::HashMap;
+use std::ffi::CString;
+use std::fs;
+use std::time::Duration;
+
+use crate::port_scanner::{self, PortInfo};
+
+/// Nombre del servicio en el bus del sistema
+pub const BUS_NAME: &str = "io.github.artur282.PortSlayer1";
+
+/// Objeto que atiende las peticiones
+const OBJECT_PATH: &str = "/io/github/artur282/PortSlayer1";
+
+/// Interfaz del servicio
+const INTERFACE: &str = "io.github.artur282.PortSlayer1";
+
+/// Acción de polkit que autoriza cada kill
+pub const KILL_ACTION: &str = "io.github.artur282.portslayer.kill";
+
+/// Tiempo máximo de una petición (incluye escribir la contraseña)
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Tiempo sin peticiones tras el que el servicio termina
+const IDLE_EXIT: Duration = Duration::from_secs(60);
+
+/// `CheckAuthorization`: permitir que polkit muestre el diálogo
+const ALLOW_USER_INTERACTION: u32 = 1;
+
+/// Errores que devuelve el servicio
+const ERROR_NOT_AUTHORIZED: &str = "io.github.artur282.PortSlayer1.Error.NotAuthorized";
+const ERROR_NO_SUCH_PROCESS: &str = "io.github.artur282.PortSlayer1.Error.NoSuchProcess";
+const ERROR_FAILED: &str = "io.github.artur282.PortSlayer1.Error.Failed";
+
+/// Prefijos de los errores del bus cuando el servicio no está instalado
+/// o no se pudo arrancar
+const UNAVAILABLE_ERRORS: [&str; 2] = [
+    "org.freedesktop.DBus.Error.ServiceUnknown",
+    "org.freedesktop.DBus.Error.Spawn",
+];
+
+/// Pide al servicio que mate un proceso, previa autorización de polkit.
+///
+/// # Returns
+/// `None` si el servicio no está instalado; si no, su resultado.
+pub fn kill(pid: u32) -> Option<Result<(), KillError>> {
+    let connection = Connection::new_system().ok()?;
+    let proxy = connection.with_proxy(BUS_NAME, OBJECT_PATH, REQUEST_TIMEOUT);
+    let result: Result<(), dbus::Error> = proxy.method_call(INTERFACE, "Kill", (pid,));
+    match result {
+        Ok(()) => {
+            log::info!("Proceso {} terminado vía polkit", pid);
+            Some(Ok(()))
+        }
+        Err(e) => {
+            let name = e.name().unwrap_or_default();
+            if UNAVAILABLE_ERRORS
+                .iter()
+                .any(|prefix| name.starts_with(prefix))
+            {
+                log::debug!("Servicio de polkit no disponible: {}", name);
+                return None;
+            }
+            Some(Err(match name {
+                ERROR_NO_SUCH_PROCESS => KillError::NoSuchProcess,
+                _ => KillError::Failed(e.message().unwrap_or(name).to_string()),
+            }))
+        }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// Servicio (activado por D-Bus como root)
+// ─────────────────────────────────────────────────────────────
+
+/// Atiende las peticiones del bus del sistema hasta quedar inactivo.
+///
+/// # Returns
+/// `Err(String)` si no se pudo conectar al bus o reservar el nombre.
+pub fn serve() -> Result<(), String> {
+    let connection = Connection::new_system().map_err(|e| e.to_string())?;
+    connection
+        .request_name(BUS_NAME, false, true, true)
+        .map_err(|e| format!("No se pudo reservar {}: {}", BUS_NAME, e))?;
+    log::info!("Servicio {} en marcha", BUS_NAME);
+
+    loop {
+        let message = connection
+            .channel()
+            .blocking_pop_message(IDLE_EXIT)
+            .map_err(|e| e.to_string())?;
+        let Some(call) = message else {
+            log::info!(
+                "Sin peticiones en {} s, cerrando el servicio",
+                IDLE_EXIT.as_secs()
+            );
+            return Ok(());
+        };
+        if call.msg_type() != MessageType::MethodCall {
+            continue;
+        }
+        let reply = match (call.interface().as_deref(), call.member().as_deref()) {
+            (Some(INTERFACE), Some("Kill")) => handle_kill(&connection, &call),
+            _ => error_reply(
+                &call,
+                "org.freedesktop.DBus.Error.UnknownMethod",
+                "Método desconocido",
+            ),
+        };
+        let _ = connection.channel().send(reply);
+    }
+}
+
+/// Atiende `Kill(u pid)`: autoriza con polkit y envía `SIGKILL`.
+fn handle_kill(connection: &Connection, call: &Message) -> Message {
+    let Ok(pid) = call.read1::<u32>() else {
+        return error_reply(call, ERROR_FAILED, "Se esperaba un PID");
+    };
+    let Some(sender) = call.sender() else {
+        return error_reply(call, ERROR_FAILED, "Petición sin remitente");
+    };
+    let Ok(comm) = fs::read_to_string(format!("/proc/{}/comm", pid)) else {
+        return error_reply(call, ERROR_NO_SUCH_PROCESS, "El proceso ya no existe");
+    };
+
+    let details = kill_details(pid, comm.trim(), &port_scanner::scan_open_ports());
+    match authorize(connection, &sender, &details) {
+        Ok(true) => {}
+        Ok(false) => {
+            log::warn!("polkit no autorizó a {} a matar el PID {}", &*sender, pid);
+            return error_reply(call, ERROR_NOT_AUTHORIZED, "No autorizado");
+        }
+        Err(e) => return error_reply(call, ERROR_FAILED, &e),
+    }
+
+    log::info!(
+        "{} autorizado a matar el PID {} ({})",
+        &*sender,
+        pid,
+        comm.trim()
+    );
+    // Como root no hay nada más con qué reintentar
+    let no_retry = |_| Err(KillError::Failed("Permiso denegado".into()));
+    match core_kill::kill_with(pid, no_retry) {
+        Ok(()) => call.method_return(),
+        Err(KillError::NoSuchProcess) => {
+            error_reply(call, ERROR_NO_SUCH_PROCESS, "El proceso ya no existe")
+        }
+        Err(e) => error_reply(call, ERROR_FAILED, &e.to_string()),
+    }
+}
+
+/// Detalles de la autorización, que el mensaje de la política muestra
+/// como `$(process)`, `$(pid)` y `$(ports)`.
+fn kill_details(pid: u32, process: &str, ports: &[PortInfo]) -> HashMap<&'static str, String> {
+    let mut owned: Vec<String> = ports
+        .iter()
+        .filter(|port| port.pid == pid)
+        .map(|port| format!("{}/{}", port.port, port.protocol))
+        .collect();
+    owned.dedup();
+    let ports = if owned.is_empty() {
+        "—".to_string()
+    } else {
+        owned.join(", ")
+    };
+    HashMap::from([
+        ("process", process.to_string()),
+        ("pid", pid.to_string()),
+        ("ports", ports),
+    ])
+}
+
+/// Pregunta a polkit si quien envió la petición puede matar el proceso.
+///
+/// # Returns
+/// `Err(String)` si polkit no respondió.
+fn authorize(
+    connection: &Connection,
+    sender: &str,
+    details: &HashMap<&'static str, String>,
+) -> Result<bool, String> {
+    let mut subject_details = PropMap::new();
+    subject_details.insert("name".into(), Variant(Box::new(sender.to_string())));
+    let subject = ("system-bus-name", subject_details);
+
+    let authority = connection.with_proxy(
+        "org.freedesktop.PolicyKit1",
+        "/org/freedesktop/PolicyKit1/Authority",
+        REQUEST_TIMEOUT,
+    );
+    let ((authorized, _challenge, _),): ((bool, bool, HashMap<String, String>),) = authority
+        .method_call(
+            "org.freedesktop.PolicyKit1.Authority",
+            "CheckAuthorization",
+            (subject, KILL_ACTION, details, ALLOW_USER_INTERACTION, ""),
+        )
+        .map_err(|e| format!("polkit no respondió: {}", e))?;
+    Ok(authorized)
+}
+
+/// Respuesta de error a una petición.
+fn error_reply(call: &Message, name: &'static str, text: &str) -> Message {
+    let text = CString::new(text.replace('\0', "")).unwrap_or_default();
+    call.error(&ErrorName::from(name), &text)
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verifica los detalles del mensaje y que la política los usa
+    #[test]
+    fn test_kill_details_match_policy() {
+        let port = |port: u16, pid: u32| PortInfo {
+            protocol: "tcp".into(),
+            local_address: "0.0.0.0".into(),
+            port,
+            pid,
+            process_name: "node".into(),
+            ..Default::default()
+        };
+        let ports = [port(3000, 4242), port(3001, 4242), port(5432, 99)];
+        let details = kill_details(4242, "node", &ports);
+        assert_eq!(details["ports"], "3000/tcp, 3001/tcp");
+        assert_eq!(kill_details(7, "sleep", &ports)["ports"], "—");
+
+        let policy = include_str!("../assets/polkit/io.github.artur282.portslayer.policy");
+        assert!(policy.contains(&format!("<action id=\"{}\">", KILL_ACTION)));
+        for key in details.keys() {
+            assert!(policy.contains(&format!("$({})", key)), "{}", key);
+        }
+    }
+}
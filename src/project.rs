@@ -0,0 +1,82 @@
+/// Módulo del proyecto de cada proceso, según su directorio de trabajo.
+///
+/// Con tres `node` escuchando no se sabe cuál es de qué proyecto. El
+/// directorio de trabajo del proceso (`/proc/[pid]/cwd`) suele decirlo:
+/// se muestra el nombre del repositorio git que lo contiene o, fuera de
+/// uno, el del propio directorio.
+///
+/// ```text
+/// TCP 3000 (127.0.0.1) → node [PID 4242] 📁 tienda-web
+/// ```
+///
+/// La búsqueda del repositorio se detiene en el directorio personal: un
+/// `~` versionado (dotfiles) no es un proyecto. Tampoco lo son `/` ni el
+/// propio directorio personal, donde arrancan los servicios del sistema
+/// y las sesiones.
+use std::fs;
+use std::path::Path;
+
+/// Proyecto de un proceso, si su directorio de trabajo lo indica.
+///
+/// # Returns
+/// `None` sin PID, sin permiso para leer el directorio (procesos de
+/// otros usuarios) o si el directorio no es de un proyecto.
+pub fn project_for(pid: u32) -> Option<String> {
+    if pid == 0 {
+        return None;
+    }
+    let cwd = fs::read_link(format!("/proc/{}/cwd", pid)).ok()?;
+    project_name(&cwd, |dir| dir.join(".git").exists())
+}
+
+/// Nombre del proyecto de un directorio: el del repositorio que lo
+/// contiene (según `is_repo`) o el suyo.
+fn project_name(cwd: &Path, is_repo: impl Fn(&Path) -> bool) -> Option<String> {
+    if is_home(cwd) {
+        return None;
+    }
+    let repo = cwd
+        .ancestors()
+        .take_while(|dir| !is_home(dir))
+        .find(|dir| is_repo(dir));
+    let dir = repo.unwrap_or(cwd);
+    Some(dir.file_name()?.to_string_lossy().into_owned())
+}
+
+/// Indica si un directorio es `/`, `/root` o uno de `/home`.
+fn is_home(dir: &Path) -> bool {
+    dir.parent()
+        .is_none_or(|parent| parent == Path::new("/home"))
+        || dir == Path::new("/root")
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verifica que se elige el repositorio más cercano y que `/` y los
+    /// directorios personales no cuentan como proyectos
+    #[test]
+    fn test_project_name() {
+        let repos = [
+            Path::new("/home/ana/src/tienda-web"),
+            Path::new("/home/ana"),
+        ];
+        let is_repo = |dir: &Path| repos.contains(&dir);
+
+        let name = |cwd: &str| project_name(Path::new(cwd), is_repo);
+        assert_eq!(
+            name("/home/ana/src/tienda-web/packages/api").as_deref(),
+            Some("tienda-web")
+        );
+        assert_eq!(name("/home/ana/src/notas").as_deref(), Some("notas"));
+        assert_eq!(name("/srv/blog").as_deref(), Some("blog"));
+        assert_eq!(name("/home/ana"), None);
+        assert_eq!(name("/root"), None);
+        assert_eq!(name("/"), None);
+    }
+}
@@ -0,0 +1,215 @@
+/// Módulo de reproducción de sesiones grabadas.
+///
+/// El historial (`history.jsonl`, o una copia adjunta a un reporte de
+/// error) es la grabación de una sesión: qué puertos se abrieron, se
+/// cerraron o se mataron y cuándo. `portslayer replay sesion.jsonl` abre
+/// el tray con esos puertos en lugar de los del sistema, avanzando al
+/// ritmo en que ocurrieron ("así se veía exactamente mi escaneo"):
+///
+/// ```sh
+/// portslayer replay sesion.jsonl --speed 10 --loop
+/// portslayer replay sesion.jsonl --at 90 --json   # sin tray, para scripts
+/// ```
+///
+/// Durante la reproducción no se toca el sistema: los escaneos devuelven
+/// el estado grabado, no hay conexiones, las reglas automáticas quedan
+/// desactivadas y los kills se rechazan. Con `--at` la posición queda
+/// fija, lo que hace la salida reproducible en tests y documentación.
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Instant;
+
+use crate::history::{self, HistoryEvent};
+use crate::port_scanner::PortInfo;
+
+/// Reproducción en curso de este proceso
+static ACTIVE: OnceLock<Replay> = OnceLock::new();
+
+/// Sesión grabada y su reloj de reproducción
+#[derive(Debug)]
+pub struct Replay {
+    /// Archivo de la sesión
+    path: PathBuf,
+    /// Eventos en orden cronológico
+    events: Vec<HistoryEvent>,
+    /// Momento del primer evento (segundos desde UNIX epoch)
+    origin: u64,
+    /// Duración de la sesión en segundos
+    duration: u64,
+    /// Segundos de sesión por segundo real
+    speed: f64,
+    /// Volver a empezar al llegar al final
+    repeat: bool,
+    /// Inicio de la reproducción
+    started: Instant,
+    /// Posición fija (segundos desde el inicio de la sesión)
+    frozen_at: Option<u64>,
+}
+
+impl Replay {
+    /// Carga una sesión grabada (formato de `history.jsonl`).
+    ///
+    /// # Returns
+    /// `Err(String)` si no se pudo leer, la velocidad no es positiva o
+    /// el archivo no tiene eventos válidos.
+    pub fn load(path: &Path, speed: f64, repeat: bool) -> Result<Replay, String> {
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("No se pudo leer {}: {}", path.display(), e))?;
+        let events: Vec<HistoryEvent> = content
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect();
+        Replay::from_events(path, events, speed, repeat)
+    }
+
+    /// Crea una reproducción a partir de eventos ya leídos.
+    fn from_events(
+        path: &Path,
+        mut events: Vec<HistoryEvent>,
+        speed: f64,
+        repeat: bool,
+    ) -> Result<Replay, String> {
+        if !(speed > 0.0 && speed.is_finite()) {
+            return Err(format!(
+                "Velocidad inválida: {} (debe ser mayor que 0)",
+                speed
+            ));
+        }
+        // Estable: los eventos de un mismo segundo conservan su orden
+        events.sort_by_key(|e| e.timestamp);
+        let (Some(first), Some(last)) = (events.first(), events.last()) else {
+            return Err(format!(
+                "{} no contiene eventos de historial",
+                path.display()
+            ));
+        };
+        let (origin, duration) = (first.timestamp, last.timestamp - first.timestamp);
+        Ok(Replay {
+            path: path.to_path_buf(),
+            events,
+            origin,
+            duration,
+            speed,
+            repeat,
+            started: Instant::now(),
+            frozen_at: None,
+        })
+    }
+
+    /// Fija la posición en `offset` segundos desde el inicio de la sesión.
+    pub fn freeze_at(mut self, offset: u64) -> Replay {
+        self.frozen_at = Some(offset);
+        self
+    }
+
+    /// Posición actual en segundos desde el inicio de la sesión.
+    fn position(&self) -> u64 {
+        let offset = self
+            .frozen_at
+            .unwrap_or_else(|| (self.started.elapsed().as_secs_f64() * self.speed) as u64);
+        if self.repeat {
+            offset % (self.duration + 1)
+        } else {
+            offset.min(self.duration)
+        }
+    }
+
+    /// Puertos abiertos en una posición de la sesión.
+    fn ports_at(&self, offset: u64) -> Vec<PortInfo> {
+        history::replay_ports(&self.events, self.origin + offset)
+    }
+}
+
+/// Activa la reproducción para el resto del proceso.
+pub fn start(replay: Replay) {
+    log::info!(
+        "Reproduciendo {} ({} eventos, {}s, velocidad x{})",
+        replay.path.display(),
+        replay.events.len(),
+        replay.duration,
+        replay.speed
+    );
+    if ACTIVE.set(replay).is_err() {
+        log::warn!("Ya hay una reproducción en curso");
+    }
+}
+
+/// Indica si se está reproduciendo una sesión.
+pub fn is_active() -> bool {
+    ACTIVE.get().is_some()
+}
+
+/// Puertos de la sesión en la posición actual (`None` sin reproducción).
+pub fn current_ports() -> Option<Vec<PortInfo>> {
+    ACTIVE
+        .get()
+        .map(|replay| replay.ports_at(replay.position()))
+}
+
+/// Archivo y posición de la reproducción (ej: "sesion.jsonl · 01:30 / 05:00").
+pub fn status() -> Option<String> {
+    let replay = ACTIVE.get()?;
+    let name = replay
+        .path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    Some(format!(
+        "{} · {} / {}",
+        name,
+        clock(replay.position()),
+        clock(replay.duration)
+    ))
+}
+
+/// Formatea segundos como `mm:ss`.
+fn clock(secs: u64) -> String {
+    format!("{:02}:{:02}", secs / 60, secs % 60)
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::history::HistoryEventKind;
+
+    fn event(timestamp: u64, kind: HistoryEventKind, port: u16, pid: u32) -> HistoryEvent {
+        HistoryEvent {
+            timestamp,
+            kind,
+            protocol: "tcp".into(),
+            port,
+            pid,
+            process_name: "node".into(),
+        }
+    }
+
+    /// Verifica que la sesión se reproduce igual en cada posición fija
+    #[test]
+    fn test_replay_positions() {
+        let events = vec![
+            event(1_000, HistoryEventKind::Opened, 3000, 10),
+            event(1_030, HistoryEventKind::Opened, 5173, 11),
+            event(1_090, HistoryEventKind::Killed, 0, 10),
+        ];
+        let path = Path::new("sesion.jsonl");
+        let replay = Replay::from_events(path, events.clone(), 1.0, false).unwrap();
+        let ports =
+            |offset| -> Vec<u16> { replay.ports_at(offset).iter().map(|p| p.port).collect() };
+        assert_eq!(ports(0), vec![3000]);
+        assert_eq!(ports(45), vec![3000, 5173]);
+        assert_eq!(ports(90), vec![5173]);
+
+        // Con --loop la posición vuelve a empezar tras el último evento
+        let looping = Replay::from_events(path, events, 1.0, true)
+            .unwrap()
+            .freeze_at(121);
+        assert_eq!(looping.position(), 30);
+        assert_eq!(clock(looping.duration), "01:30");
+        assert!(Replay::from_events(path, Vec::new(), 1.0, false).is_err());
+    }
+}
@@ -0,0 +1,291 @@
+/// Módulo de puertos reservados ("guardia de puertos").
+///
+/// Un puerto reservado (ej: 3000 para el dev server, 5432 para postgres)
+/// solo debería estar ocupado por su dueño. Si otro proceso lo ocupa, se
+/// notifica en el siguiente escaneo y, opcionalmente, se mata al intruso
+/// para recuperar el puerto sin buscarlo a mano.
+///
+/// Las reservas se guardan en `~/.config/portslayer/reservations.json` y
+/// se gestionan con `portslayer reserve`. Internamente cada reserva se
+/// evalúa como una regla más del motor de reglas, así que comparte su
+/// registro (`rules.jsonl`), las protecciones "no matar" y la supresión
+/// durante las ventanas de mantenimiento.
+///
+/// Las reservas también sirven de lista de puertos esperados para el
+/// ícono de aviso del tray (ver [`SecurityAlerts`]): un puerto TCP
+/// expuesto en todas las interfaces no avisa si lo ocupa el dueño de su
+/// reserva (ej: reservar el 22 para `sshd`).
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::paths;
+use crate::port_scanner::PortInfo;
+use crate::rules::{Rule, RuleAction};
+
+/// Nombre del archivo de reservas dentro del directorio de configuración
+const RESERVATIONS_FILE: &str = "reservations.json";
+
+/// Motivos para mostrar el ícono de aviso del tray
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SecurityAlerts {
+    /// Puertos reservados ocupados por otro proceso
+    pub intruders: usize,
+    /// Puertos TCP en todas las interfaces que no son de su dueño reservado
+    pub exposed: usize,
+}
+
+impl SecurityAlerts {
+    /// Indica si hay algún motivo de aviso.
+    pub fn any(&self) -> bool {
+        self.intruders > 0 || self.exposed > 0
+    }
+}
+
+/// Reserva de un puerto
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Reservation {
+    /// Puerto reservado
+    pub port: u16,
+    /// Protocolo ("tcp" o "udp"); `None` reserva ambos
+    #[serde(default)]
+    pub protocol: Option<String>,
+    /// Proceso dueño (admite comodines); `None` avisa de cualquier proceso
+    #[serde(default)]
+    pub owner: Option<String>,
+    /// Matar al intruso además de notificar
+    #[serde(default)]
+    pub kill: bool,
+}
+
+impl Reservation {
+    /// Comprueba que la reserva tiene sentido.
+    ///
+    /// Matar intrusos requiere un dueño: si no, se mataría también al
+    /// proceso legítimo.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.port == 0 {
+            return Err("El puerto 0 no se puede reservar".into());
+        }
+        if self.kill && self.owner.is_none() {
+            return Err(format!(
+                "La reserva del puerto {} necesita un dueño para matar intrusos",
+                self.port
+            ));
+        }
+        self.to_rule().validate()
+    }
+
+    /// Indica si el puerto está ocupado por un proceso que no es el dueño.
+    pub fn is_intruder(&self, port_info: &PortInfo) -> bool {
+        self.to_rule().matches(port_info, 0)
+    }
+
+    /// Regla equivalente para el motor de reglas.
+    pub fn to_rule(&self) -> Rule {
+        Rule {
+            name: format!("puerto {} reservado", self.port),
+            action: if self.kill {
+                RuleAction::Kill
+            } else {
+                RuleAction::Notify
+            },
+            process: None,
+            process_not_in: self.owner.iter().cloned().collect(),
+            ports: Some(self.port.to_string()),
+            protocol: self.protocol.clone(),
+            idle_minutes: None,
+        }
+    }
+
+    /// Descripción legible para el CLI.
+    pub fn describe(&self) -> String {
+        let protocol = self.protocol.as_deref().unwrap_or("tcp/udp");
+        let owner = self.owner.as_deref().unwrap_or("sin dueño");
+        let action = if self.kill {
+            "matar intrusos"
+        } else {
+            "notificar"
+        };
+        format!("{} {} → {} ({})", protocol, self.port, owner, action)
+    }
+}
+
+/// Reservas persistidas en disco
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReservationStore {
+    /// Reservas definidas por el usuario
+    pub reservations: Vec<Reservation>,
+}
+
+impl ReservationStore {
+    /// Ruta del archivo de reservas.
+    pub fn path() -> PathBuf {
+        paths::config_dir().join(RESERVATIONS_FILE)
+    }
+
+    /// Carga las reservas; vacío si el archivo no existe o no es válido.
+    pub fn load() -> Self {
+        let path = Self::path();
+        match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                log::error!("Archivo de reservas inválido {}: {}", path.display(), e);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Guarda las reservas en disco.
+    pub fn save(&self) -> io::Result<()> {
+        let path = Self::path();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json)
+    }
+
+    /// Añade o reemplaza la reserva de un puerto (y protocolo).
+    pub fn reserve(&mut self, reservation: Reservation) -> Result<(), String> {
+        reservation.validate()?;
+        self.reservations
+            .retain(|r| !(r.port == reservation.port && r.protocol == reservation.protocol));
+        self.reservations.push(reservation);
+        self.reservations.sort_by_key(|r| r.port);
+        Ok(())
+    }
+
+    /// Quita las reservas de un puerto.
+    ///
+    /// # Returns
+    /// Cantidad de reservas eliminadas.
+    pub fn release(&mut self, port: u16) -> usize {
+        let before = self.reservations.len();
+        self.reservations.retain(|r| r.port != port);
+        before - self.reservations.len()
+    }
+
+    /// Reserva que cubre un puerto abierto, si la hay.
+    pub fn reservation_for(&self, port_info: &PortInfo) -> Option<&Reservation> {
+        self.reservations.iter().find(|r| {
+            r.port == port_info.port
+                && r.protocol
+                    .as_ref()
+                    .is_none_or(|protocol| *protocol == port_info.protocol)
+        })
+    }
+
+    /// Reglas equivalentes a todas las reservas.
+    pub fn rules(&self) -> Vec<Rule> {
+        self.reservations.iter().map(Reservation::to_rule).collect()
+    }
+
+    /// Avisos de seguridad de un escaneo: reservas ocupadas por intrusos
+    /// y puertos TCP expuestos que no ocupa el dueño de su reserva.
+    pub fn security_alerts(&self, ports: &[PortInfo]) -> SecurityAlerts {
+        let mut alerts = SecurityAlerts::default();
+        for port_info in ports {
+            let reservation = self.reservation_for(port_info);
+            if reservation.is_some_and(|r| r.is_intruder(port_info)) {
+                alerts.intruders += 1;
+            } else if reservation.is_none()
+                && port_info.protocol == "tcp"
+                && port_info.listens_on_all_interfaces()
+            {
+                alerts.exposed += 1;
+            }
+        }
+        alerts
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn port(port: u16, process_name: &str) -> PortInfo {
+        PortInfo {
+            protocol: "tcp".into(),
+            port,
+            local_address: "0.0.0.0".into(),
+            pid: 1,
+            process_name: process_name.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Verifica la detección de intrusos en un puerto reservado
+    #[test]
+    fn test_intruder_detection() {
+        let reservation = Reservation {
+            port: 3000,
+            protocol: Some("tcp".into()),
+            owner: Some("node*".into()),
+            kill: true,
+        };
+        assert!(reservation.validate().is_ok());
+        assert!(!reservation.is_intruder(&port(3000, "node")));
+        assert!(reservation.is_intruder(&port(3000, "python3")));
+        assert!(!reservation.is_intruder(&port(3001, "python3")));
+        assert_eq!(reservation.to_rule().action, RuleAction::Kill);
+
+        // Sin dueño no se puede matar a ciegas
+        let blind = Reservation {
+            owner: None,
+            ..reservation
+        };
+        assert!(blind.validate().is_err());
+    }
+
+    /// Verifica que reservar de nuevo un puerto reemplaza la reserva
+    #[test]
+    fn test_reserve_and_release() {
+        let mut store = ReservationStore::default();
+        let reservation = |owner: &str| Reservation {
+            port: 5432,
+            protocol: None,
+            owner: Some(owner.into()),
+            kill: false,
+        };
+        store.reserve(reservation("postgres")).unwrap();
+        store.reserve(reservation("postgres*")).unwrap();
+        assert_eq!(store.reservations.len(), 1);
+        assert!(store.reservation_for(&port(5432, "x")).is_some());
+        assert_eq!(store.release(5432), 1);
+        assert!(store.reservations.is_empty());
+    }
+
+    /// Verifica los avisos de intrusos y de puertos expuestos
+    #[test]
+    fn test_security_alerts() {
+        let mut store = ReservationStore::default();
+        store
+            .reserve(Reservation {
+                port: 22,
+                protocol: Some("tcp".into()),
+                owner: Some("sshd".into()),
+                kill: false,
+            })
+            .unwrap();
+        let local = PortInfo {
+            local_address: "127.0.0.1".into(),
+            ..port(5432, "postgres")
+        };
+        assert!(!store.security_alerts(&[port(22, "sshd"), local]).any());
+
+        let alerts = store.security_alerts(&[port(22, "nc"), port(8080, "python3")]);
+        assert_eq!(
+            alerts,
+            SecurityAlerts {
+                intruders: 1,
+                exposed: 1
+            }
+        );
+    }
+}
@@ -0,0 +1,300 @@
+/// Módulo de reinicio tras un kill.
+///
+/// Antes de matar un proceso se captura su línea de comandos, su
+/// directorio de trabajo y su entorno desde `/proc/<pid>`. Así se
+/// puede relanzar el mismo comando en cuanto el puerto queda libre,
+/// útil para desatascar un servidor de desarrollo sin buscar la
+/// terminal donde se inició.
+///
+/// Las capturas se guardan en `restartable.json` dentro del directorio
+/// de estado con permisos 0600, porque el entorno puede contener secretos.
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::os::unix::fs::OpenOptionsExt;
+use std::os::unix::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use crate::history;
+use crate::i18n::{tr, trf, Text};
+use crate::paths;
+use crate::port_scanner;
+
+/// Nombre del archivo de capturas dentro del directorio de estado
+const RESTART_FILE: &str = "restartable.json";
+
+/// Capturas que se conservan (las más recientes primero)
+const MAX_SNAPSHOTS: usize = 10;
+
+/// Tiempo máximo de espera a que el puerto quede libre
+const PORT_FREE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Estado de un proceso capturado justo antes de matarlo
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProcessSnapshot {
+    /// PID que tenía el proceso
+    pub pid: u32,
+    /// Nombre del proceso
+    pub process_name: String,
+    /// Puerto que ocupaba (0 si se mató por PID sin puerto conocido)
+    pub port: u16,
+    /// Protocolo del puerto ("tcp" o "udp")
+    pub protocol: String,
+    /// Programa y argumentos (`/proc/<pid>/cmdline`)
+    pub cmdline: Vec<String>,
+    /// Directorio de trabajo (`/proc/<pid>/cwd`)
+    pub cwd: PathBuf,
+    /// Entorno del proceso; `None` si no se pudo leer (se hereda el actual)
+    pub env: Option<Vec<(String, String)>>,
+    /// Momento de la captura (segundos desde UNIX epoch)
+    pub captured_at: u64,
+}
+
+impl ProcessSnapshot {
+    /// Comando legible para mostrar en menús y en el CLI.
+    pub fn command_line(&self) -> String {
+        self.cmdline.join(" ")
+    }
+}
+
+/// Captura el estado de un proceso desde `/proc`.
+///
+/// # Arguments
+/// * `pid` - Proceso a capturar
+/// * `port` - Puerto que ocupa (0 si no aplica)
+/// * `protocol` - Protocolo del puerto
+///
+/// # Returns
+/// `Err(String)` si el proceso no existe o su cmdline no es legible
+/// (procesos de otro usuario o hilos del kernel).
+pub fn capture(pid: u32, port: u16, protocol: &str) -> Result<ProcessSnapshot, String> {
+    let proc_dir = PathBuf::from(format!("/proc/{}", pid));
+
+    let raw_cmdline = fs::read(proc_dir.join("cmdline")).map_err(|e| {
+        format!(
+            "No se pudo leer la línea de comandos del PID {}: {}",
+            pid, e
+        )
+    })?;
+    let cmdline = split_nul(&raw_cmdline);
+    if cmdline.is_empty() {
+        return Err(trf(Text::NoCmdline, &[&pid]));
+    }
+
+    let cwd = fs::read_link(proc_dir.join("cwd"))
+        .map_err(|e| format!("No se pudo leer el directorio del PID {}: {}", pid, e))?;
+    let env = fs::read(proc_dir.join("environ"))
+        .ok()
+        .map(|raw| parse_environ(&raw));
+    let process_name = fs::read_to_string(proc_dir.join("comm"))
+        .map(|name| name.trim().to_string())
+        .unwrap_or_else(|_| tr(Text::UnknownProcess).to_string());
+
+    Ok(ProcessSnapshot {
+        pid,
+        process_name,
+        port,
+        protocol: protocol.to_string(),
+        cmdline,
+        cwd,
+        env,
+        captured_at: history::unix_now(),
+    })
+}
+
+/// Captura un proceso y lo guarda como reiniciable.
+///
+/// Los errores se registran en el log: no poder capturar no debe
+/// impedir el kill.
+pub fn remember(pid: u32, port: u16, protocol: &str) {
+    match capture(pid, port, protocol) {
+        Ok(snapshot) => {
+            if let Err(e) = save_snapshot(snapshot) {
+                log::error!("No se pudo guardar la captura del PID {}: {}", pid, e);
+            }
+        }
+        Err(e) => log::warn!("Reinicio no disponible: {}", e),
+    }
+}
+
+/// Ruta del archivo de capturas.
+pub fn snapshots_path() -> PathBuf {
+    paths::state_dir().join(RESTART_FILE)
+}
+
+/// Lee las capturas guardadas (las más recientes primero).
+pub fn load_snapshots() -> Vec<ProcessSnapshot> {
+    read_snapshots(&snapshots_path())
+}
+
+/// Añade una captura al principio de la lista guardada.
+fn save_snapshot(snapshot: ProcessSnapshot) -> Result<(), String> {
+    let path = snapshots_path();
+    let mut snapshots = read_snapshots(&path);
+    // Un mismo comando en el mismo puerto solo se guarda una vez
+    snapshots.retain(|s| !(s.cmdline == snapshot.cmdline && s.port == snapshot.port));
+    snapshots.insert(0, snapshot);
+    snapshots.truncate(MAX_SNAPSHOTS);
+    write_snapshots(&path, &snapshots)
+}
+
+/// Elimina una captura de la lista guardada.
+fn forget_snapshot(index: usize) -> Result<(), String> {
+    let path = snapshots_path();
+    let mut snapshots = read_snapshots(&path);
+    if index < snapshots.len() {
+        snapshots.remove(index);
+    }
+    write_snapshots(&path, &snapshots)
+}
+
+/// Lee un archivo de capturas; vacío si no existe o no es válido.
+fn read_snapshots(path: &Path) -> Vec<ProcessSnapshot> {
+    fs::read_to_string(path)
+        .ok()
+        .and_then(|content| serde_json::from_str(&content).ok())
+        .unwrap_or_default()
+}
+
+/// Escribe el archivo de capturas con permisos 0600.
+fn write_snapshots(path: &Path, snapshots: &[ProcessSnapshot]) -> Result<(), String> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+    }
+    let content = serde_json::to_string_pretty(snapshots).map_err(|e| e.to_string())?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)
+        .map_err(|e| format!("No se pudo escribir {}: {}", path.display(), e))?;
+    file.write_all(content.as_bytes())
+        .map_err(|e| format!("No se pudo escribir {}: {}", path.display(), e))
+}
+
+/// Relanza la captura indicada y la quita de la lista.
+///
+/// Espera hasta [`PORT_FREE_TIMEOUT`] a que el puerto quede libre y
+/// lanza el comando en su propio grupo de procesos, con su directorio
+/// y entorno originales, desvinculado de la entrada/salida actual.
+///
+/// # Arguments
+/// * `index` - Posición en [`load_snapshots`]
+///
+/// # Returns
+/// `Ok(pid)` del nuevo proceso, o `Err(String)` si falla.
+pub fn restart(index: usize) -> Result<u32, String> {
+    let snapshots = load_snapshots();
+    let snapshot = snapshots
+        .get(index)
+        .ok_or_else(|| format!("No hay ninguna captura con índice {}", index))?;
+
+    if snapshot.port > 0 && !wait_port_free(snapshot.port, &snapshot.protocol) {
+        return Err(trf(Text::PortStillBusy, &[&snapshot.port]));
+    }
+
+    let mut command = Command::new(&snapshot.cmdline[0]);
+    command
+        .args(&snapshot.cmdline[1..])
+        .current_dir(&snapshot.cwd)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .process_group(0);
+    if let Some(env) = &snapshot.env {
+        command.env_clear().envs(env.iter().map(|(k, v)| (k, v)));
+    }
+
+    let child = command
+        .spawn()
+        .map_err(|e| format!("No se pudo relanzar '{}': {}", snapshot.command_line(), e))?;
+    log::info!(
+        "♻️ Relanzado '{}' (PID {})",
+        snapshot.command_line(),
+        child.id()
+    );
+
+    let pid = child.id();
+    // Recoger el proceso al terminar para no dejar zombis en el tray/daemon
+    std::thread::spawn(move || {
+        let mut child = child;
+        let _ = child.wait();
+    });
+
+    forget_snapshot(index)?;
+    Ok(pid)
+}
+
+/// Espera hasta [`PORT_FREE_TIMEOUT`] a que ningún proceso escuche en el puerto.
+pub fn wait_port_free(port: u16, protocol: &str) -> bool {
+    let start = Instant::now();
+    loop {
+        let busy = port_scanner::scan_open_ports()
+            .iter()
+            .any(|p| p.port == port && p.protocol == protocol);
+        if !busy {
+            return true;
+        }
+        if start.elapsed() >= PORT_FREE_TIMEOUT {
+            return false;
+        }
+        std::thread::sleep(Duration::from_millis(500));
+    }
+}
+
+/// Separa un bloque de cadenas terminadas en NUL (formato de `/proc`).
+fn split_nul(raw: &[u8]) -> Vec<String> {
+    raw.split(|&b| b == 0)
+        .filter(|part| !part.is_empty())
+        .map(|part| String::from_utf8_lossy(part).into_owned())
+        .collect()
+}
+
+/// Interpreta `/proc/<pid>/environ` como pares clave/valor.
+fn parse_environ(raw: &[u8]) -> Vec<(String, String)> {
+    split_nul(raw)
+        .into_iter()
+        .filter_map(|entry| {
+            let (key, value) = entry.split_once('=')?;
+            Some((key.to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verifica el parseo de cmdline y environ separados por NUL
+    #[test]
+    fn test_parse_proc_blocks() {
+        assert_eq!(
+            split_nul(b"npm\0run\0dev\0"),
+            vec!["npm".to_string(), "run".into(), "dev".into()]
+        );
+        assert_eq!(
+            parse_environ(b"PORT=3000\0EMPTY=\0A=b=c\0BROKEN\0"),
+            vec![
+                ("PORT".to_string(), "3000".to_string()),
+                ("EMPTY".into(), "".into()),
+                ("A".into(), "b=c".into()),
+            ]
+        );
+    }
+
+    /// Verifica la captura del propio proceso de test
+    #[test]
+    fn test_capture_self() {
+        let snapshot = capture(std::process::id(), 0, "tcp").unwrap();
+        assert!(!snapshot.cmdline.is_empty());
+        assert_eq!(snapshot.cwd, std::env::current_dir().unwrap());
+        assert!(snapshot.env.is_some());
+    }
+}
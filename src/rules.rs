@@ -0,0 +1,437 @@
+/// Módulo del motor de reglas para acciones automáticas.
+///
+/// Las reglas se definen en `config.toml` (globales o dentro de un
+/// espacio de trabajo) y se evalúan después de cada escaneo. Todas las
+/// condiciones de una regla deben cumplirse; las que se omiten no filtran.
+///
+/// Ejemplo:
+/// ```toml
+/// [[rules]]
+/// name = "privilegiados no permitidos"
+/// ports = "1-1024"
+/// process_not_in = ["sshd", "cupsd", "systemd-*"]
+/// action = "notify"
+///
+/// [[rules]]
+/// name = "dev server olvidado"
+/// process = "old-dev-server"
+/// idle_minutes = 60
+/// action = "kill"
+/// ```
+///
+/// Cada regla se dispara una sola vez por puerto mientras siga
+/// coincidiendo; los disparos se registran en `rules.jsonl` dentro del
+/// directorio de estado. Durante una ventana de mantenimiento no se
+/// evalúa ninguna regla.
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::config::Config;
+use crate::connections::{self, Connection};
+use crate::effectiveness::{self, KillTarget};
+use crate::history;
+use crate::i18n::{trf, Text};
+use crate::journal;
+use crate::maintenance;
+use crate::paths;
+use crate::port_scanner::{self, PortInfo};
+use crate::reservations::ReservationStore;
+use crate::restart;
+use crate::tags::TagStore;
+
+/// Archivo (en el directorio de estado) donde se registran los disparos
+const RULES_LOG_FILE: &str = "rules.jsonl";
+
+/// Acción a ejecutar cuando una regla coincide
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleAction {
+    /// Notificación de escritorio (`notify-send`)
+    Notify,
+    /// Matar el proceso y notificarlo (se respetan las etiquetas "no matar")
+    Kill,
+    /// Solo registrar el disparo
+    Log,
+}
+
+/// Regla configurada por el usuario
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct Rule {
+    /// Nombre descriptivo usado en logs y notificaciones
+    pub name: String,
+    /// Acción a ejecutar
+    pub action: RuleAction,
+    /// El proceso debe coincidir con este patrón (admite comodines)
+    #[serde(default)]
+    pub process: Option<String>,
+    /// El proceso NO debe coincidir con ninguno de estos patrones
+    #[serde(default)]
+    pub process_not_in: Vec<String>,
+    /// Puerto o rango de puertos ("8080" o "1-1024")
+    #[serde(default)]
+    pub ports: Option<String>,
+    /// Protocolo ("tcp" o "udp")
+    #[serde(default)]
+    pub protocol: Option<String>,
+    /// Minutos mínimos sin conexiones establecidas
+    #[serde(default)]
+    pub idle_minutes: Option<u64>,
+}
+
+impl Rule {
+    /// Comprueba que el rango de puertos y el protocolo son válidos.
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(ports) = &self.ports {
+            parse_range(ports).map_err(|e| format!("Regla '{}': {}", self.name, e))?;
+        }
+        if let Some(protocol) = &self.protocol {
+            if protocol != "tcp" && protocol != "udp" {
+                return Err(format!(
+                    "Regla '{}': protocolo desconocido '{}'",
+                    self.name, protocol
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Evalúa las condiciones de la regla sobre un puerto.
+    ///
+    /// # Arguments
+    /// * `port_info` - Puerto a evaluar
+    /// * `idle_secs` - Segundos que lleva el puerto sin conexiones
+    pub fn matches(&self, port_info: &PortInfo, idle_secs: u64) -> bool {
+        if let Some(pattern) = &self.process {
+            if !port_scanner::glob_match(pattern, &port_info.process_name) {
+                return false;
+            }
+        }
+        if self
+            .process_not_in
+            .iter()
+            .any(|pattern| port_scanner::glob_match(pattern, &port_info.process_name))
+        {
+            return false;
+        }
+        if let Some(Ok((start, end))) = self.ports.as_deref().map(parse_range) {
+            if port_info.port < start || port_info.port > end {
+                return false;
+            }
+        }
+        if let Some(protocol) = &self.protocol {
+            if *protocol != port_info.protocol {
+                return false;
+            }
+        }
+        if let Some(minutes) = self.idle_minutes {
+            if idle_secs < minutes * 60 {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Disparo de una regla sobre un puerto
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RuleHit {
+    /// Momento del disparo (segundos desde UNIX epoch)
+    pub timestamp: u64,
+    /// Nombre de la regla
+    pub rule: String,
+    /// Acción ejecutada
+    pub action: RuleAction,
+    /// Protocolo del puerto
+    pub protocol: String,
+    /// Número de puerto
+    pub port: u16,
+    /// PID del proceso
+    pub pid: u32,
+    /// Nombre del proceso
+    pub process_name: String,
+}
+
+/// Estado del motor de reglas entre escaneos.
+///
+/// Recuerda desde cuándo está inactivo cada puerto y qué reglas ya se
+/// dispararon para no repetir la acción en cada escaneo.
+#[derive(Debug, Default)]
+pub struct RuleEngine {
+    /// Última vez que cada (protocolo, puerto, PID) tuvo conexiones
+    last_active: HashMap<(String, u16, u32), u64>,
+    /// Disparos vigentes: (regla, protocolo, puerto, PID)
+    fired: HashSet<(String, String, u16, u32)>,
+}
+
+impl RuleEngine {
+    /// Crea un motor sin historial.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Evalúa las reglas sobre un escaneo y devuelve los disparos nuevos.
+    ///
+    /// # Arguments
+    /// * `rules` - Reglas activas
+    /// * `ports` - Puertos del escaneo actual
+    /// * `connections` - Conexiones establecidas (para la inactividad)
+    /// * `now` - Momento del escaneo (segundos desde UNIX epoch)
+    pub fn evaluate(
+        &mut self,
+        rules: &[Rule],
+        ports: &[PortInfo],
+        connections: &[Connection],
+        now: u64,
+    ) -> Vec<RuleHit> {
+        // Actualizar la inactividad y olvidar los puertos que ya no existen
+        let mut seen: HashSet<(String, u16, u32)> = HashSet::new();
+        for port in ports {
+            let key = (port.protocol.clone(), port.port, port.pid);
+            let busy = connections::kill_impact(port, connections).established > 0;
+            let last = self.last_active.entry(key.clone()).or_insert(now);
+            if busy {
+                *last = now;
+            }
+            seen.insert(key);
+        }
+        self.last_active.retain(|key, _| seen.contains(key));
+
+        let mut hits = Vec::new();
+        let mut still_matching: HashSet<(String, String, u16, u32)> = HashSet::new();
+        for rule in rules {
+            for port in ports {
+                let key = (port.protocol.clone(), port.port, port.pid);
+                let idle = now.saturating_sub(self.last_active.get(&key).copied().unwrap_or(now));
+                if !rule.matches(port, idle) {
+                    continue;
+                }
+
+                let fired_key = (rule.name.clone(), key.0, key.1, key.2);
+                if !self.fired.contains(&fired_key) {
+                    hits.push(RuleHit {
+                        timestamp: now,
+                        rule: rule.name.clone(),
+                        action: rule.action,
+                        protocol: port.protocol.clone(),
+                        port: port.port,
+                        pid: port.pid,
+                        process_name: port.process_name.clone(),
+                    });
+                }
+                still_matching.insert(fired_key);
+            }
+        }
+        // Una regla puede volver a dispararse cuando deja de coincidir
+        self.fired = still_matching;
+        hits
+    }
+}
+
+/// Reglas vigentes: las globales, las del espacio de trabajo activo y
+/// las de los puertos reservados.
+pub fn active_rules(config: &Config) -> Vec<Rule> {
+    let mut rules = config.rules.clone();
+    if let Some((_, ws)) = config.active_workspace() {
+        rules.extend(ws.rules.iter().cloned());
+    }
+    rules.extend(ReservationStore::load().rules());
+    rules
+}
+
+/// Evalúa las reglas tras un escaneo y ejecuta sus acciones.
+///
+/// No hace nada si no hay reglas o si hay una ventana de mantenimiento
+/// activa.
+///
+/// # Arguments
+/// * `engine` - Estado del motor entre escaneos
+/// * `config` - Configuración con las reglas y ventanas
+/// * `ports` - Puertos del escaneo actual
+/// * `connections` - Conexiones establecidas del escaneo actual
+pub fn run_after_scan(
+    engine: &mut RuleEngine,
+    config: &Config,
+    ports: &[PortInfo],
+    connections: &[Connection],
+) {
+    let rules = active_rules(config);
+    if rules.is_empty() {
+        return;
+    }
+    if let Some((window, _)) =
+        maintenance::active_window(&config.maintenance, maintenance::local_now())
+    {
+        log::debug!("Reglas suprimidas por la ventana '{}'", window.name);
+        return;
+    }
+
+    let hits = engine.evaluate(&rules, ports, connections, history::unix_now());
+    if hits.is_empty() {
+        return;
+    }
+
+    let store = TagStore::load();
+    for hit in &hits {
+        execute(hit, ports, &store);
+    }
+    append_hits(&hits);
+}
+
+/// Ejecuta la acción de un disparo.
+fn execute(hit: &RuleHit, ports: &[PortInfo], store: &TagStore) {
+    let message = trf(
+        Text::RuleHit,
+        &[
+            &hit.rule,
+            &hit.protocol.to_uppercase(),
+            &hit.port,
+            &hit.process_name,
+            &hit.pid,
+        ],
+    );
+    log::info!("🤖 {}", message);
+
+    match hit.action {
+        RuleAction::Log => {}
+        RuleAction::Notify => notify(&message),
+        RuleAction::Kill => {
+            let protected = ports
+                .iter()
+                .any(|p| p.pid == hit.pid && store.is_protected(p));
+            if hit.pid == 0 || protected {
+                // Sin PID haría falta pkexec: no se piden permisos desde una regla
+                log::warn!("Regla '{}': PID {} no se matará", hit.rule, hit.pid);
+                return;
+            }
+            restart::remember(hit.pid, hit.port, &hit.protocol);
+            let target = KillTarget::capture(hit.pid, hit.port, &hit.protocol);
+            match port_scanner::kill_process(hit.pid) {
+                Ok(()) => {
+                    notify(&format!("⚔️ {}", message));
+                    if let Some(target) = target {
+                        effectiveness::track(target);
+                    }
+                }
+                Err(e) => log::error!("Regla '{}': {}", hit.rule, e),
+            }
+        }
+    }
+}
+
+/// Muestra una notificación de escritorio con `notify-send`.
+pub fn notify(message: &str) {
+    let result = Command::new("notify-send")
+        .args(["--app-name=PortSlayer", "PortSlayer ⚔️", message])
+        .output();
+    if let Err(e) = result {
+        log::debug!("notify-send no disponible: {}", e);
+    }
+}
+
+/// Ruta del registro de disparos.
+pub fn rules_log_path() -> PathBuf {
+    paths::state_dir().join(RULES_LOG_FILE)
+}
+
+/// Encola los disparos en el registro (los errores solo se registran en el log).
+fn append_hits(hits: &[RuleHit]) {
+    let lines = hits
+        .iter()
+        .filter_map(|hit| serde_json::to_string(hit).ok())
+        .collect();
+    journal::append(&rules_log_path(), lines);
+}
+
+/// Interpreta un puerto ("8080") o un rango ("1-1024").
+pub fn parse_range(text: &str) -> Result<(u16, u16), String> {
+    let (start, end) = text.split_once('-').unwrap_or((text, text));
+    let start: u16 = start
+        .trim()
+        .parse()
+        .map_err(|_| format!("puerto inválido '{}'", start))?;
+    let end: u16 = end
+        .trim()
+        .parse()
+        .map_err(|_| format!("puerto inválido '{}'", end))?;
+    if start > end {
+        return Err(format!("rango invertido '{}'", text));
+    }
+    Ok((start, end))
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn port(port: u16, process_name: &str) -> PortInfo {
+        PortInfo {
+            protocol: "tcp".into(),
+            port,
+            local_address: "0.0.0.0".into(),
+            pid: port as u32,
+            process_name: process_name.into(),
+            ..Default::default()
+        }
+    }
+
+    fn rule(toml_src: &str) -> Rule {
+        toml::from_str(toml_src).unwrap()
+    }
+
+    /// Verifica las condiciones de proceso, rango e inactividad
+    #[test]
+    fn test_rule_matches() {
+        let privileged = rule(
+            r#"
+            name = "privilegiados"
+            action = "notify"
+            ports = "1-1024"
+            process_not_in = ["sshd", "cups*"]
+            "#,
+        );
+        assert!(privileged.matches(&port(80, "nginx"), 0));
+        assert!(!privileged.matches(&port(22, "sshd"), 0));
+        assert!(!privileged.matches(&port(631, "cupsd"), 0));
+        assert!(!privileged.matches(&port(8080, "nginx"), 0));
+
+        let idle = rule(
+            r#"
+            name = "olvidado"
+            action = "kill"
+            process = "old-dev-server"
+            idle_minutes = 60
+            "#,
+        );
+        assert!(!idle.matches(&port(3000, "old-dev-server"), 59 * 60));
+        assert!(idle.matches(&port(3000, "old-dev-server"), 3600));
+        assert!(!idle.matches(&port(3000, "node"), 3600));
+
+        assert!(rule("name = \"x\"\naction = \"log\"\nports = \"9-1\"")
+            .validate()
+            .is_err());
+    }
+
+    /// Verifica que cada regla se dispara una vez por puerto y la inactividad
+    #[test]
+    fn test_engine_fires_once() {
+        let rules = [rule("name = \"idle\"\naction = \"log\"\nidle_minutes = 1")];
+        let ports = vec![port(3000, "node")];
+        let mut engine = RuleEngine::new();
+
+        assert!(engine.evaluate(&rules, &ports, &[], 1000).is_empty());
+        let hits = engine.evaluate(&rules, &ports, &[], 1060);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].port, 3000);
+        assert!(engine.evaluate(&rules, &ports, &[], 1120).is_empty());
+
+        // Al desaparecer y volver, la inactividad empieza de cero
+        assert!(engine.evaluate(&rules, &[], &[], 1180).is_empty());
+        assert!(engine.evaluate(&rules, &ports, &[], 1200).is_empty());
+    }
+}
@@ -0,0 +1,153 @@
+/// Módulo de protección de procesos críticos.
+///
+/// Algunos procesos se pueden matar pero casi nunca se quiere: init (PID
+/// 1), `sshd` (corta las sesiones remotas, incluida la propia), los
+/// servicios `systemd-*`, el gestor de sesiones gráficas (cierra el
+/// escritorio) y el propio PortSlayer. Antes de enviarles una señal se
+/// exige una confirmación explícita:
+///
+/// - en el tray, un segundo paso "⚔️ Cerrar igualmente" en su submenu,
+/// - en el CLI, `--force`,
+/// - en la API, `?confirm=true`.
+///
+/// La confirmación vale para un solo kill de ese proceso y caduca a los
+/// pocos segundos. Las reglas automáticas y "Cerrar todos" nunca
+/// confirman, así que no los tocan.
+use portslayer_core::helper::DISPLAY_MANAGERS;
+use std::fs;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::i18n::{tr, trf, Text};
+
+/// Tiempo durante el que vale una confirmación
+const CONFIRMATION_TTL: Duration = Duration::from_secs(30);
+
+/// Confirmaciones pendientes: (PID, nombre del proceso, momento)
+static CONFIRMED: Mutex<Vec<(u32, String, Instant)>> = Mutex::new(Vec::new());
+
+/// Motivo por el que un proceso es crítico
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Danger {
+    /// PID 1: matarlo provoca un kernel panic o reinicia el contenedor
+    Init,
+    /// `sshd`: corta las sesiones remotas
+    RemoteAccess,
+    /// Un servicio de systemd (`systemd-*`)
+    SystemService,
+    /// El gestor de sesiones o el servidor gráfico: cierra el escritorio
+    DisplayManager,
+    /// El propio PortSlayer (tray, daemon o helper)
+    PortSlayer,
+}
+
+impl Danger {
+    /// Descripción legible del riesgo.
+    pub fn describe(&self) -> &'static str {
+        tr(match self {
+            Danger::Init => Text::DangerInit,
+            Danger::RemoteAccess => Text::DangerRemoteAccess,
+            Danger::SystemService => Text::DangerSystemService,
+            Danger::DisplayManager => Text::DangerDisplayManager,
+            Danger::PortSlayer => Text::DangerPortSlayer,
+        })
+    }
+}
+
+/// Indica si un proceso es crítico según su PID y nombre.
+pub fn assess(pid: u32, process_name: &str) -> Option<Danger> {
+    if pid == 1 {
+        Some(Danger::Init)
+    } else if pid == std::process::id() || process_name == "portslayer" {
+        Some(Danger::PortSlayer)
+    } else if process_name == "sshd" || process_name.starts_with("sshd-") {
+        Some(Danger::RemoteAccess)
+    } else if process_name == "systemd" || process_name.starts_with("systemd-") {
+        Some(Danger::SystemService)
+    } else if DISPLAY_MANAGERS
+        .iter()
+        .any(|dm| process_name == *dm || process_name.starts_with(&format!("{}-", dm)))
+    {
+        Some(Danger::DisplayManager)
+    } else {
+        None
+    }
+}
+
+/// Nombre actual de un proceso (`/proc/<pid>/comm`).
+fn process_name(pid: u32) -> Option<String> {
+    let comm = fs::read_to_string(format!("/proc/{}/comm", pid)).ok()?;
+    Some(comm.trim().to_string())
+}
+
+/// Confirma el próximo kill de un proceso crítico.
+pub fn confirm(pid: u32) {
+    let name = process_name(pid).unwrap_or_default();
+    if let Ok(mut confirmed) = CONFIRMED.lock() {
+        confirmed.retain(|(p, _, _)| *p != pid);
+        confirmed.push((pid, name, Instant::now()));
+    }
+}
+
+/// Comprueba que se puede enviar una señal a un proceso, consumiendo su
+/// confirmación si es crítico.
+///
+/// # Returns
+/// `Err(String)` si es crítico y no se confirmó (o la confirmación
+/// caducó o era de otro proceso con el mismo PID).
+pub fn check(pid: u32) -> Result<(), String> {
+    let Some(name) = process_name(pid) else {
+        // Ya no existe: el kill informará del error
+        return Ok(());
+    };
+    let Some(danger) = assess(pid, &name) else {
+        return Ok(());
+    };
+    let confirmed = CONFIRMED.lock().ok().is_some_and(|mut confirmed| {
+        confirmed.retain(|(_, _, at)| at.elapsed() < CONFIRMATION_TTL);
+        let found = confirmed
+            .iter()
+            .position(|(p, n, _)| *p == pid && *n == name);
+        found.map(|index| confirmed.remove(index)).is_some()
+    });
+    if confirmed {
+        log::warn!("Kill confirmado del proceso crítico {} ({})", pid, name);
+        Ok(())
+    } else {
+        Err(trf(
+            Text::CriticalProcess,
+            &[&name, &pid, &danger.describe()],
+        ))
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verifica la detección de procesos críticos y la confirmación
+    #[test]
+    fn test_critical_processes() {
+        assert_eq!(assess(1, "systemd"), Some(Danger::Init));
+        assert_eq!(assess(880, "sshd"), Some(Danger::RemoteAccess));
+        assert_eq!(assess(420, "systemd-resolve"), Some(Danger::SystemService));
+        assert_eq!(assess(777, "gdm-session-wor"), Some(Danger::DisplayManager));
+        assert_eq!(
+            assess(std::process::id(), "cargo"),
+            Some(Danger::PortSlayer)
+        );
+        assert_eq!(assess(4242, "node"), None);
+        assert_eq!(assess(4242, "sshfs"), None);
+
+        // El propio proceso de test es crítico: hace falta confirmar, una vez
+        let own = std::process::id();
+        assert!(check(own).is_err());
+        confirm(own);
+        assert!(check(own).is_ok());
+        assert!(check(own).is_err());
+    }
+}
@@ -0,0 +1,182 @@
+/// Módulo de las aplicaciones en sandbox (Flatpak y Snap).
+///
+/// Los procesos de una app de Flatpak o de Snap tienen nombres que no
+/// dicen de qué app son (`bwrap`, `electron`, `.firefox-wrapped`) y
+/// rutas de un sistema de archivos aparte. La app se reconoce por el
+/// cgroup del proceso, que systemd nombra según la app:
+///
+/// - Flatpak: `app-flatpak-org.mozilla.firefox-12345.scope` (o, si el
+///   cgroup no lo dice, el `/.flatpak-info` de su raíz),
+/// - Snap: `snap.firefox.firefox-<uuid>.scope` para las apps y
+///   `snap.lxd.daemon.service` para los servicios.
+///
+/// Matar un proceso suelto de un sandbox suele dejar vivo al resto (o
+/// que lo relance snapd), así que el kill se delega: `flatpak kill
+/// <app>` cierra la instancia entera y `snap stop <snap>.<servicio>`
+/// detiene el servicio. Las apps de Snap que no son servicios se matan
+/// como cualquier proceso.
+use serde::Serialize;
+use std::fs;
+use std::process::Command;
+
+/// Aplicación en sandbox a la que pertenece un proceso
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Sandbox {
+    /// App de Flatpak por su ID ("org.mozilla.firefox")
+    Flatpak { app: String },
+    /// App o servicio de un snap ("lxd" y "daemon")
+    Snap {
+        snap: String,
+        app: String,
+        service: bool,
+    },
+}
+
+impl Sandbox {
+    /// Marca del puerto: "📦 org.mozilla.firefox" o "📦 snap lxd.daemon".
+    pub fn label(&self) -> String {
+        match self {
+            Sandbox::Flatpak { app } => format!("📦 {}", app),
+            Sandbox::Snap { snap, app, .. } if snap == app => format!("📦 snap {}", snap),
+            Sandbox::Snap { snap, app, .. } => format!("📦 snap {}.{}", snap, app),
+        }
+    }
+
+    /// Detiene la app por su gestor, si es el modo de cerrarla.
+    ///
+    /// # Returns
+    /// `None` si el proceso se mata como cualquier otro (apps de Snap que
+    /// no son servicios).
+    pub fn stop(&self) -> Option<Result<(), String>> {
+        let args: Vec<String> = match self {
+            Sandbox::Flatpak { app } => vec!["flatpak".into(), "kill".into(), app.clone()],
+            Sandbox::Snap {
+                snap,
+                app,
+                service: true,
+            } => vec!["snap".into(), "stop".into(), format!("{}.{}", snap, app)],
+            Sandbox::Snap { .. } => return None,
+        };
+        let output = match Command::new(&args[0]).args(&args[1..]).output() {
+            Ok(output) => output,
+            Err(e) => return Some(Err(format!("No se pudo ejecutar {}: {}", args[0], e))),
+        };
+        Some(if output.status.success() {
+            log::info!("{} detenido con {}", self.label(), args.join(" "));
+            Ok(())
+        } else {
+            Err(format!(
+                "{} falló: {}",
+                args.join(" "),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ))
+        })
+    }
+}
+
+/// App en sandbox de un proceso, si la hay.
+pub fn detect(pid: u32) -> Option<Sandbox> {
+    if pid == 0 {
+        return None;
+    }
+    let cgroup = fs::read_to_string(format!("/proc/{}/cgroup", pid)).unwrap_or_default();
+    from_cgroup(&cgroup).or_else(|| {
+        let info = fs::read_to_string(format!("/proc/{}/root/.flatpak-info", pid)).ok()?;
+        flatpak_app(&info).map(|app| Sandbox::Flatpak { app })
+    })
+}
+
+/// App en sandbox según el contenido de `/proc/<pid>/cgroup`.
+fn from_cgroup(cgroup: &str) -> Option<Sandbox> {
+    cgroup
+        .lines()
+        .flat_map(|line| line.rsplit(':').next().unwrap_or("").split('/'))
+        .find_map(|part| {
+            let flatpak = part
+                .strip_prefix("app-flatpak-")
+                .or_else(|| part.strip_prefix("flatpak-"))
+                .and_then(|rest| rest.strip_suffix(".scope"));
+            if let Some(rest) = flatpak {
+                // Sin el número de instancia: "org.mozilla.firefox-12345"
+                let app = rest.rsplit_once('-').map_or(rest, |(app, _)| app);
+                return Some(Sandbox::Flatpak {
+                    app: app.to_string(),
+                });
+            }
+            let rest = part.strip_prefix("snap.")?;
+            let (rest, service) = match rest.strip_suffix(".service") {
+                Some(rest) => (rest, true),
+                None => (rest.strip_suffix(".scope")?, false),
+            };
+            let (snap, app) = rest.split_once('.')?;
+            // Las apps llevan un UUID por instancia: "firefox-<uuid>"
+            let app = match service {
+                true => app,
+                false => app.split(['.', '-']).next().unwrap_or(app),
+            };
+            Some(Sandbox::Snap {
+                snap: snap.to_string(),
+                app: app.to_string(),
+                service,
+            })
+        })
+}
+
+/// ID de la app en un `.flatpak-info` (`name=` de `[Application]`).
+fn flatpak_app(info: &str) -> Option<String> {
+    let mut in_application = false;
+    for line in info.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_application = line == "[Application]";
+        } else if let Some(name) = line.strip_prefix("name=").filter(|_| in_application) {
+            return Some(name.to_string());
+        }
+    }
+    None
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verifica el reconocimiento de las apps por su cgroup y por su
+    /// `.flatpak-info`
+    #[test]
+    fn test_detect_sandbox() {
+        let flatpak = "0::/user.slice/user-1000.slice/user@1000.service/app.slice/\
+                       app-flatpak-org.mozilla.firefox-20871.scope";
+        assert_eq!(
+            from_cgroup(flatpak),
+            Some(Sandbox::Flatpak {
+                app: "org.mozilla.firefox".into()
+            })
+        );
+        let snap_app = "0::/user.slice/user-1000.slice/user@1000.service/app.slice/\
+                        snap.firefox.firefox-4b1c6f0e-8a52-4e4a-9d0b-3c2f1e0d9a8b.scope";
+        let snap_app = from_cgroup(snap_app).unwrap();
+        assert_eq!(snap_app.label(), "📦 snap firefox");
+        assert_eq!(snap_app.stop(), None);
+        let service = from_cgroup("0::/system.slice/snap.lxd.daemon.service").unwrap();
+        assert_eq!(
+            service,
+            Sandbox::Snap {
+                snap: "lxd".into(),
+                app: "daemon".into(),
+                service: true
+            }
+        );
+        assert_eq!(service.label(), "📦 snap lxd.daemon");
+        assert_eq!(
+            from_cgroup("0::/user.slice/user-1000.slice/session-2.scope"),
+            None
+        );
+
+        let info = "[Application]\nname=com.visualstudio.code\nruntime=runtime/org.freedesktop.Sdk/x86_64/23.08\n\n[Instance]\nname=otro\n";
+        assert_eq!(flatpak_app(info).as_deref(), Some("com.visualstudio.code"));
+    }
+}
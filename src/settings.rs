@@ -0,0 +1,236 @@
+/// Módulo de los ajustes editables desde la API (`GET`/`PATCH /settings`).
+///
+/// Son las claves sueltas de la raíz de `config.toml`: intervalo de
+/// escaneo, idioma, rango personalizado, tema de íconos y los
+/// interruptores de escaneo y del tray. Las secciones (reglas, webhooks,
+/// `[api]`...) se siguen editando a mano. Los puertos protegidos son
+/// etiquetas "no matar" y se gestionan con `/tags`.
+///
+/// Un `PATCH` solo toca las claves que trae, sobre el propio archivo (con
+/// `toml_edit`, así que se conservan los comentarios y el orden del
+/// usuario), y no guarda nada si el resultado no pasa [`Config::parse`].
+/// El daemon relee la configuración en cada escaneo, así que el cambio se
+/// aplica sin reiniciarlo; además se anuncia a los clientes de
+/// `GET /events` con un evento `settings`.
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::Path;
+use toml_edit::{value, DocumentMut};
+
+use crate::config::Config;
+use crate::port_scanner;
+use crate::rules;
+use crate::theme::IconTheme;
+
+/// Intervalo de escaneo máximo aceptado (una hora)
+const MAX_SCAN_INTERVAL_SECS: u64 = 3600;
+
+/// Ajustes en vigor
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Settings {
+    /// Segundos entre escaneos automáticos (0 = solo manual en el tray)
+    pub scan_interval_secs: u64,
+    /// Idioma de la interfaz: el configurado o, si no hay, el del locale
+    pub language: &'static str,
+    /// Rango del filtro "Personalizado" del tray
+    pub custom_range: Option<String>,
+    /// Juego de íconos y marcas del tray
+    pub icon_theme: IconTheme,
+    /// Escanear los namespaces de red de los contenedores
+    pub scan_namespaces: bool,
+    /// Escanear los puertos del Windows anfitrión (WSL2)
+    pub scan_windows: bool,
+    /// Tráfico de cada puerto en el tray
+    pub traffic_stats: bool,
+    /// Identificar el servicio de cada puerto TCP por su banner
+    pub service_fingerprints: bool,
+    /// Comprobar el estado HTTP de los servidores web locales
+    pub http_health: bool,
+    /// Lanzar el helper privilegiado al iniciar el tray
+    pub privileged_helper: bool,
+}
+
+impl Settings {
+    /// Ajustes de una configuración, con los valores por defecto de las
+    /// claves que no tiene.
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            scan_interval_secs: config.scan_interval(),
+            language: config.language().code(),
+            custom_range: config.custom_range.clone(),
+            icon_theme: config.icon_theme,
+            scan_namespaces: config.scan_namespaces,
+            scan_windows: config.scan_windows.unwrap_or_else(port_scanner::is_wsl),
+            traffic_stats: config.traffic_stats,
+            service_fingerprints: config.service_fingerprints,
+            http_health: config.http_health,
+            privileged_helper: config.privileged_helper,
+        }
+    }
+}
+
+/// Cuerpo de `PATCH /settings`: las claves de [`Settings`] que cambian
+/// (las ausentes se dejan como están; una clave desconocida es un error)
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SettingsPatch {
+    /// Segundos entre escaneos (hasta [`MAX_SCAN_INTERVAL_SECS`])
+    pub scan_interval_secs: Option<u64>,
+    /// "es" o "en"
+    pub language: Option<String>,
+    /// Rango "INICIO-FIN"
+    pub custom_range: Option<String>,
+    /// Tema de íconos ("default", "monochrome"...)
+    pub icon_theme: Option<IconTheme>,
+    /// Namespaces de red de los contenedores
+    pub scan_namespaces: Option<bool>,
+    /// Puertos del Windows anfitrión
+    pub scan_windows: Option<bool>,
+    /// Tráfico en el tray
+    pub traffic_stats: Option<bool>,
+    /// Servicio por banner
+    pub service_fingerprints: Option<bool>,
+    /// Estado HTTP
+    pub http_health: Option<bool>,
+    /// Helper privilegiado
+    pub privileged_helper: Option<bool>,
+}
+
+impl SettingsPatch {
+    /// Aplica los cambios al texto de `config.toml` y valida el resultado.
+    ///
+    /// # Returns
+    /// El nuevo contenido del archivo, o `Err` con el primer problema (un
+    /// valor inválido o un `config.toml` que ya no era TOML válido).
+    pub fn apply(&self, content: &str) -> Result<String, String> {
+        if self
+            .scan_interval_secs
+            .is_some_and(|secs| secs > MAX_SCAN_INTERVAL_SECS)
+        {
+            return Err(format!(
+                "scan_interval_secs debe estar entre 0 y {}",
+                MAX_SCAN_INTERVAL_SECS
+            ));
+        }
+        if let Some(range) = &self.custom_range {
+            rules::parse_range(range).map_err(|e| format!("custom_range inválido: {}", e))?;
+        }
+
+        let mut doc: DocumentMut = content.parse().map_err(|e| format!("{}", e))?;
+        if let Some(secs) = self.scan_interval_secs {
+            doc["scan_interval_secs"] = value(secs as i64);
+        }
+        if let Some(language) = &self.language {
+            doc["language"] = value(language.as_str());
+        }
+        if let Some(range) = &self.custom_range {
+            doc["custom_range"] = value(range.as_str());
+        }
+        if let Some(theme) = self.icon_theme {
+            let name = serde_json::to_value(theme).map_err(|e| e.to_string())?;
+            doc["icon_theme"] = value(name.as_str().unwrap_or_default());
+        }
+        let toggles = [
+            ("scan_namespaces", self.scan_namespaces),
+            ("scan_windows", self.scan_windows),
+            ("traffic_stats", self.traffic_stats),
+            ("service_fingerprints", self.service_fingerprints),
+            ("http_health", self.http_health),
+            ("privileged_helper", self.privileged_helper),
+        ];
+        for (key, enabled) in toggles {
+            if let Some(enabled) = enabled {
+                doc[key] = value(enabled);
+            }
+        }
+
+        let updated = doc.to_string();
+        Config::parse(&updated)?;
+        Ok(updated)
+    }
+}
+
+/// Contenido actual de `config.toml` (vacío si no existe).
+pub fn read_config() -> io::Result<String> {
+    match fs::read_to_string(Config::path()) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(String::new()),
+        result => result,
+    }
+}
+
+/// Reemplaza `config.toml` de una vez (archivo temporal y `rename`).
+///
+/// La configuración puede llevar secretos (cabeceras de webhooks, la
+/// contraseña SMTP), así que el temporal se crea con permisos 0600 y
+/// después recibe los del archivo original.
+pub fn write_config(content: &str) -> io::Result<()> {
+    let path = Config::path();
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let tmp = path.with_extension("toml.tmp");
+    write_private(&tmp, content)?;
+    if let Ok(metadata) = fs::metadata(&path) {
+        fs::set_permissions(&tmp, metadata.permissions())?;
+    }
+    fs::rename(&tmp, &path)
+}
+
+/// Escribe un archivo nuevo legible solo por su dueño.
+fn write_private(path: &Path, content: &str) -> io::Result<()> {
+    let mut file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .truncate(true)
+        .mode(0o600)
+        .open(path)?;
+    file.write_all(content.as_bytes())
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verifica que un cambio conserva el resto del archivo
+    #[test]
+    fn test_apply_keeps_comments() {
+        let content =
+            "# mi configuración\nscan_interval_secs = 5 # rápido\n\n[[watch]]\nport = 5432\n";
+        let patch: SettingsPatch = serde_json::from_str(
+            r#"{"language":"en","icon_theme":"high-contrast","http_health":true}"#,
+        )
+        .unwrap();
+        let updated = patch.apply(content).unwrap();
+        assert!(updated.starts_with("# mi configuración\nscan_interval_secs = 5 # rápido\n"));
+        assert!(updated.contains("[[watch]]\nport = 5432\n"));
+
+        let settings = Settings::from_config(&Config::parse(&updated).unwrap());
+        assert_eq!(settings.scan_interval_secs, 5);
+        assert_eq!(settings.language, "en");
+        assert_eq!(settings.icon_theme, IconTheme::HighContrast);
+        assert!(settings.http_health);
+    }
+
+    /// Verifica que los cambios inválidos se rechazan
+    #[test]
+    fn test_apply_rejects_invalid() {
+        let patch = |json: &str| serde_json::from_str::<SettingsPatch>(json);
+        assert!(patch(r#"{"language":"fr"}"#).unwrap().apply("").is_err());
+        assert!(patch(r#"{"custom_range":"9000-8000"}"#)
+            .unwrap()
+            .apply("")
+            .is_err());
+        assert!(patch(r#"{"scan_interval_secs":86400}"#)
+            .unwrap()
+            .apply("")
+            .is_err());
+        assert!(patch(r#"{"icon_theme":"neon"}"#).is_err());
+        assert!(patch(r#"{"rules":[]}"#).is_err());
+    }
+}
@@ -0,0 +1,184 @@
+/// Módulo de salida ordenada con operaciones en curso.
+///
+/// Los kills (incluidos los de las reglas, desde el hilo de escaneo) y
+/// sus comprobaciones en segundo plano (ver `effectiveness`) se registran
+/// aquí mientras duran. Si el usuario sale con alguna pendiente, el tray
+/// muestra un resumen (completadas / pendientes / fallidas) y ofrece
+/// esperar a que terminen en lugar de cortarlas sin avisar.
+///
+/// Toda salida pasa por [`exit`], que además vacía la cola del historial
+/// (ver `journal`) antes de terminar el proceso.
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::journal;
+
+/// Tiempo máximo de espera a las operaciones pendientes al salir
+pub const WAIT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Fallos que se recuerdan para el resumen
+const MAX_FAILURES: usize = 10;
+
+/// Cada cuánto se comprueba si terminaron las operaciones pendientes
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Operaciones de este proceso
+static OPERATIONS: Mutex<Registry> = Mutex::new(Registry {
+    next_id: 0,
+    pending: BTreeMap::new(),
+    completed: 0,
+    failed: Vec::new(),
+});
+
+/// Registro de operaciones
+#[derive(Debug)]
+struct Registry {
+    /// Identificador de la próxima operación
+    next_id: u64,
+    /// Operaciones en curso por identificador
+    pending: BTreeMap<u64, String>,
+    /// Operaciones terminadas correctamente
+    completed: usize,
+    /// Últimas operaciones fallidas con su error
+    failed: Vec<String>,
+}
+
+/// Operación en curso; al soltarse cuenta como completada salvo que se
+/// marque con [`Operation::fail`]
+#[derive(Debug)]
+#[must_use = "la operación termina al soltarse"]
+pub struct Operation {
+    id: u64,
+    error: Option<String>,
+}
+
+impl Operation {
+    /// Termina la operación como fallida.
+    pub fn fail(mut self, error: impl Into<String>) {
+        self.error = Some(error.into());
+    }
+}
+
+impl Drop for Operation {
+    fn drop(&mut self) {
+        let Ok(mut registry) = OPERATIONS.lock() else {
+            return;
+        };
+        let label = registry.pending.remove(&self.id).unwrap_or_default();
+        match self.error.take() {
+            Some(error) => {
+                if registry.failed.len() == MAX_FAILURES {
+                    registry.failed.remove(0);
+                }
+                registry.failed.push(format!("{}: {}", label, error));
+            }
+            None => registry.completed += 1,
+        }
+    }
+}
+
+/// Registra una operación en curso (ej: "Cerrar PID 1234").
+pub fn begin(label: impl Into<String>) -> Operation {
+    let mut registry = OPERATIONS.lock().unwrap_or_else(|e| e.into_inner());
+    let id = registry.next_id;
+    registry.next_id += 1;
+    registry.pending.insert(id, label.into());
+    Operation { id, error: None }
+}
+
+/// Resumen de las operaciones de la sesión
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Summary {
+    /// Operaciones terminadas correctamente
+    pub completed: usize,
+    /// Operaciones en curso, en orden de inicio
+    pub pending: Vec<String>,
+    /// Últimas operaciones fallidas con su error
+    pub failed: Vec<String>,
+}
+
+/// Estado actual de las operaciones.
+pub fn summary() -> Summary {
+    match OPERATIONS.lock() {
+        Ok(registry) => Summary {
+            completed: registry.completed,
+            pending: registry.pending.values().cloned().collect(),
+            failed: registry.failed.clone(),
+        },
+        Err(_) => Summary::default(),
+    }
+}
+
+/// Espera hasta `timeout` a que terminen las operaciones pendientes.
+///
+/// # Returns
+/// `false` si se agotó el tiempo con operaciones aún en curso.
+pub fn wait_pending(timeout: Duration) -> bool {
+    let start = Instant::now();
+    while !summary().pending.is_empty() {
+        if start.elapsed() >= timeout {
+            return false;
+        }
+        std::thread::sleep(POLL_INTERVAL);
+    }
+    true
+}
+
+/// Termina PortSlayer de forma ordenada.
+///
+/// Con `wait`, espera antes a las operaciones pendientes (hasta ese
+/// tiempo). Registra el resumen y vacía la cola del historial.
+pub fn exit(wait: Option<Duration>) -> ! {
+    if let Some(timeout) = wait {
+        let pending = summary().pending.len();
+        if pending > 0 {
+            log::info!(
+                "Esperando {} operaciones pendientes antes de salir...",
+                pending
+            );
+            wait_pending(timeout);
+        }
+    }
+
+    let summary = summary();
+    log::info!(
+        "Resumen de la sesión: {} completadas, {} pendientes, {} fallidas",
+        summary.completed,
+        summary.pending.len(),
+        summary.failed.len()
+    );
+    for label in &summary.pending {
+        log::warn!("Se interrumpe al salir: {}", label);
+    }
+    log::info!("PortSlayer cerrándose...");
+    journal::flush(journal::FLUSH_TIMEOUT);
+    std::process::exit(0)
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verifica el recuento de operaciones completadas, pendientes y fallidas
+    #[test]
+    fn test_operations_summary() {
+        let before = summary();
+        let kill = begin("Cerrar PID 4242");
+        let check = begin("Comprobar el kill de node (puerto 3000)");
+        assert!(summary().pending.contains(&"Cerrar PID 4242".to_string()));
+
+        kill.fail("permiso denegado");
+        drop(check);
+        let after = summary();
+        assert!(!after.pending.iter().any(|p| p.contains("4242")));
+        assert!(after.completed > before.completed);
+        assert!(after
+            .failed
+            .contains(&"Cerrar PID 4242: permiso denegado".to_string()));
+    }
+}
@@ -0,0 +1,148 @@
+/// Módulo de instantáneas de escaneos.
+///
+/// Una instantánea es un escaneo guardado con nombre, para compararlo
+/// más adelante: se toma la línea base de un servidor recién instalado
+/// y después se comprueba qué puertos aparecieron desde entonces.
+///
+/// ```sh
+/// portslayer snapshot save base
+/// portslayer snapshot diff base          # contra el escaneo actual
+/// portslayer snapshot diff base despues  # entre dos instantáneas
+/// ```
+///
+/// Se guardan en `~/.local/state/portslayer/snapshots/<nombre>.json`, y
+/// la comparación es la de `history::diff_scans`.
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::history;
+use crate::paths;
+use crate::port_scanner::PortInfo;
+
+/// Subdirectorio de las instantáneas dentro del directorio de estado
+const SNAPSHOTS_DIR: &str = "snapshots";
+
+/// Escaneo guardado con nombre
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub name: String,
+    /// Momento del escaneo (segundos desde UNIX epoch)
+    pub timestamp: u64,
+    pub ports: Vec<PortInfo>,
+}
+
+impl Snapshot {
+    /// Crea una instantánea de un escaneo, con la hora actual.
+    pub fn new(name: &str, ports: Vec<PortInfo>) -> Self {
+        Self {
+            name: name.to_string(),
+            timestamp: history::unix_now(),
+            ports,
+        }
+    }
+
+    /// Guarda la instantánea (reemplaza otra con el mismo nombre).
+    ///
+    /// # Returns
+    /// La ruta del archivo, o `Err(String)` si el nombre no es válido o
+    /// no se pudo escribir.
+    pub fn save(&self) -> Result<PathBuf, String> {
+        let path = path(&self.name)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .map_err(|e| format!("No se pudo crear {}: {}", dir.display(), e))?;
+        }
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(&path, json)
+            .map_err(|e| format!("No se pudo escribir {}: {}", path.display(), e))?;
+        Ok(path)
+    }
+
+    /// Carga una instantánea por su nombre.
+    pub fn load(name: &str) -> Result<Self, String> {
+        let path = path(name)?;
+        let content = fs::read_to_string(&path)
+            .map_err(|_| format!("No existe la instantánea \"{}\"", name))?;
+        serde_json::from_str(&content)
+            .map_err(|e| format!("Instantánea inválida {}: {}", path.display(), e))
+    }
+}
+
+/// Directorio de las instantáneas.
+pub fn dir() -> PathBuf {
+    paths::state_dir().join(SNAPSHOTS_DIR)
+}
+
+/// Nombres de las instantáneas guardadas, en orden alfabético.
+pub fn list() -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(dir())
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            name.strip_suffix(".json").map(str::to_string)
+        })
+        .collect();
+    names.sort();
+    names
+}
+
+/// Archivo de una instantánea.
+///
+/// # Returns
+/// `Err(String)` si el nombre está vacío o no es solo letras, números,
+/// `-`, `_` y `.` (para no escribir fuera del directorio).
+fn path(name: &str) -> Result<PathBuf, String> {
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+    if !valid {
+        return Err(format!(
+            "Nombre de instantánea inválido: \"{}\" (usa letras, números, -, _ y .)",
+            name
+        ));
+    }
+    Ok(dir().join(format!("{}.json", name)))
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::port_scanner::SocketState;
+
+    /// Verifica los nombres válidos y que una instantánea se lee de vuelta
+    #[test]
+    fn test_snapshot_roundtrip() {
+        assert!(path("base-2026.10").is_ok());
+        for name in ["", "../config", "a/b", ".oculta", "con espacio"] {
+            assert!(path(name).is_err(), "{}", name);
+        }
+
+        let snapshot = Snapshot::new(
+            "base",
+            vec![PortInfo {
+                protocol: "tcp".into(),
+                port: 22,
+                local_address: "0.0.0.0".into(),
+                pid: 880,
+                process_name: "sshd".into(),
+                uid: Some(0),
+                ..Default::default()
+            }],
+        );
+        let json = serde_json::to_string(&snapshot).unwrap();
+        let loaded: Snapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.name, "base");
+        assert_eq!(loaded.ports[0].process_name, "sshd");
+        assert_eq!(loaded.ports[0].state, SocketState::Listen);
+        assert!(history::diff_scans(&snapshot.ports, &loaded.ports).is_empty());
+    }
+}
@@ -0,0 +1,415 @@
+/// Módulo de etiquetas y notas sobre puertos.
+///
+/// Permite asociar notas de texto libre y una etiqueta de color a un
+/// puerto concreto o a un nombre de proceso (ej: "staging DB — no
+/// tocar"). Las etiquetas se guardan en `~/.config/portslayer/tags.json`
+/// y se muestran tanto en el menú del tray como en la API HTTP.
+///
+/// Además se pueden importar paquetes de etiquetas compartidos por un
+/// equipo (`~/.config/portslayer/bundles/*.json`). Sus etiquetas se
+/// combinan con las locales en modo solo lectura y pueden marcar
+/// servicios como "no matar".
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::i18n::{tr, Text};
+use crate::paths;
+use crate::port_scanner::{self, PortInfo};
+
+/// Nombre del archivo de etiquetas dentro del directorio de configuración
+const TAGS_FILE: &str = "tags.json";
+
+/// Subdirectorio donde se guardan los paquetes de etiquetas importados
+const BUNDLES_DIR: &str = "bundles";
+
+/// Colores disponibles para las etiquetas
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TagColor {
+    Red,
+    Orange,
+    Yellow,
+    Green,
+    Blue,
+    Purple,
+    Gray,
+}
+
+impl TagColor {
+    /// Todos los colores, en el orden en que se muestran en el menú
+    pub const ALL: [TagColor; 7] = [
+        TagColor::Red,
+        TagColor::Orange,
+        TagColor::Yellow,
+        TagColor::Green,
+        TagColor::Blue,
+        TagColor::Purple,
+        TagColor::Gray,
+    ];
+
+    /// Emoji usado para representar el color en el menú del tray
+    pub fn emoji(&self) -> &'static str {
+        match self {
+            TagColor::Red => "🟥",
+            TagColor::Orange => "🟧",
+            TagColor::Yellow => "🟨",
+            TagColor::Green => "🟩",
+            TagColor::Blue => "🟦",
+            TagColor::Purple => "🟪",
+            TagColor::Gray => "⬜",
+        }
+    }
+
+    /// Etiqueta legible para el menú del tray
+    pub fn label(&self) -> &'static str {
+        match self {
+            TagColor::Red => tr(Text::ColorRed),
+            TagColor::Orange => tr(Text::ColorOrange),
+            TagColor::Yellow => tr(Text::ColorYellow),
+            TagColor::Green => tr(Text::ColorGreen),
+            TagColor::Blue => tr(Text::ColorBlue),
+            TagColor::Purple => tr(Text::ColorPurple),
+            TagColor::Gray => tr(Text::ColorGray),
+        }
+    }
+
+    /// Interpreta un nombre de color (ej: "red") como en el JSON.
+    #[cfg_attr(not(feature = "api"), allow(dead_code))]
+    pub fn parse(name: &str) -> Option<TagColor> {
+        serde_json::from_value(serde_json::Value::String(name.to_lowercase())).ok()
+    }
+}
+
+/// Elemento al que se aplica una etiqueta
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TagTarget {
+    /// Un número de puerto (opcionalmente restringido a un protocolo)
+    Port {
+        port: u16,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        protocol: Option<String>,
+    },
+    /// Cualquier puerto cuyo proceso coincida con este nombre
+    /// (admite comodines `*` y `?`, ej: "postgres*")
+    Process { name: String },
+}
+
+/// Etiqueta con nota y color asociada a un puerto o proceso
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Tag {
+    /// Puerto o proceso al que se aplica
+    pub target: TagTarget,
+    /// Nota de texto libre
+    pub note: String,
+    /// Color de la etiqueta
+    pub color: TagColor,
+    /// Si es `true`, PortSlayer se niega a matar los procesos etiquetados
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub do_not_kill: bool,
+    /// Paquete del que proviene la etiqueta (`None` = etiqueta local)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+}
+
+/// Paquete de etiquetas compartido por un equipo
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagBundle {
+    /// Nombre identificativo del paquete (ej: "acme-backend")
+    pub name: String,
+    /// Etiquetas incluidas en el paquete
+    pub tags: Vec<Tag>,
+}
+
+impl Tag {
+    /// Indica si la etiqueta se aplica al puerto indicado.
+    pub fn matches(&self, port_info: &PortInfo) -> bool {
+        match &self.target {
+            TagTarget::Port { port, protocol } => {
+                *port == port_info.port
+                    && protocol
+                        .as_ref()
+                        .map(|p| p.eq_ignore_ascii_case(&port_info.protocol))
+                        .unwrap_or(true)
+            }
+            TagTarget::Process { name } => port_scanner::glob_match(name, &port_info.process_name),
+        }
+    }
+}
+
+/// Conjunto de etiquetas persistidas en disco
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TagStore {
+    /// Etiquetas definidas por el usuario
+    pub tags: Vec<Tag>,
+    /// Etiquetas de paquetes importados (solo lectura, no se guardan)
+    #[serde(skip)]
+    pub shared: Vec<Tag>,
+}
+
+impl TagStore {
+    /// Ruta del archivo de etiquetas.
+    pub fn path() -> PathBuf {
+        paths::config_dir().join(TAGS_FILE)
+    }
+
+    /// Directorio de paquetes de etiquetas importados.
+    pub fn bundles_dir() -> PathBuf {
+        paths::config_dir().join(BUNDLES_DIR)
+    }
+
+    /// Carga las etiquetas locales y las de los paquetes importados.
+    ///
+    /// Si el archivo local no existe o no es válido se usa un conjunto
+    /// vacío (registrando el error en el segundo caso).
+    pub fn load() -> Self {
+        let path = Self::path();
+        let mut store: TagStore = match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                log::error!("Archivo de etiquetas inválido {}: {}", path.display(), e);
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        };
+        store.shared = load_bundles(&Self::bundles_dir());
+        store
+    }
+
+    /// Guarda las etiquetas locales en disco (las compartidas no se tocan).
+    #[cfg_attr(not(feature = "api"), allow(dead_code))]
+    pub fn save(&self) -> io::Result<()> {
+        let path = Self::path();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json)
+    }
+
+    /// Devuelve las etiquetas (locales y compartidas) que se aplican a un puerto.
+    pub fn tags_for(&self, port_info: &PortInfo) -> Vec<&Tag> {
+        self.tags
+            .iter()
+            .chain(&self.shared)
+            .filter(|t| t.matches(port_info))
+            .collect()
+    }
+
+    /// Indica si alguna etiqueta marca el puerto como "no matar".
+    pub fn is_protected(&self, port_info: &PortInfo) -> bool {
+        self.tags_for(port_info).iter().any(|t| t.do_not_kill)
+    }
+}
+
+/// Serializa un puerto a JSON añadiendo el campo `tags` con sus etiquetas
+/// y, si se conoce el UID del socket, `user` con el nombre de su dueño.
+///
+/// Es el formato común de la API HTTP y de la salida `--json` del CLI.
+pub fn annotate(port_info: &PortInfo, store: &TagStore) -> serde_json::Value {
+    let mut value = serde_json::json!(port_info);
+    value["tags"] = serde_json::json!(store.tags_for(port_info));
+    if let Some(uid) = port_info.uid {
+        value["user"] = serde_json::json!(port_scanner::user_name(uid));
+    }
+    value
+}
+
+/// Valida e instala un paquete de etiquetas en el directorio de paquetes.
+///
+/// El paquete se copia como `<nombre>.json`, reemplazando una versión
+/// anterior con el mismo nombre.
+///
+/// # Arguments
+/// * `source` - Archivo JSON del paquete a importar
+///
+/// # Returns
+/// `Ok(paquete)` si se importó, `Err(String)` si el archivo no es válido.
+pub fn import_bundle(source: &Path) -> Result<TagBundle, String> {
+    let content = fs::read_to_string(source)
+        .map_err(|e| format!("No se pudo leer {}: {}", source.display(), e))?;
+    let bundle: TagBundle =
+        serde_json::from_str(&content).map_err(|e| format!("Paquete inválido: {}", e))?;
+
+    let valid_name = !bundle.name.is_empty()
+        && bundle
+            .name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if !valid_name {
+        return Err("El nombre del paquete solo admite letras, números, '-' y '_'".to_string());
+    }
+
+    let dir = TagStore::bundles_dir();
+    fs::create_dir_all(&dir).map_err(|e| format!("No se pudo crear {}: {}", dir.display(), e))?;
+    let dest = dir.join(format!("{}.json", bundle.name));
+    fs::write(&dest, content).map_err(|e| format!("No se pudo guardar el paquete: {}", e))?;
+
+    log::info!(
+        "Paquete de etiquetas '{}' importado ({} etiquetas)",
+        bundle.name,
+        bundle.tags.len()
+    );
+    Ok(bundle)
+}
+
+/// Lee todos los paquetes del directorio y devuelve sus etiquetas.
+///
+/// Cada etiqueta queda marcada con el nombre de su paquete en `source`.
+fn load_bundles(dir: &Path) -> Vec<Tag> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut tags = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let bundle: TagBundle = match fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|c| serde_json::from_str(&c).map_err(|e| e.to_string()))
+        {
+            Ok(bundle) => bundle,
+            Err(e) => {
+                log::error!("Paquete de etiquetas inválido {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        tags.extend(bundle.tags.into_iter().map(|mut tag| {
+            tag.source = Some(bundle.name.clone());
+            tag
+        }));
+    }
+    tags
+}
+
+/// Filtra los puertos que tengan al menos una etiqueta del color indicado.
+///
+/// # Arguments
+/// * `ports` - Puertos a filtrar
+/// * `store` - Etiquetas definidas
+/// * `color` - Color buscado (`None` no filtra)
+///
+/// # Returns
+/// Vector con los puertos que coinciden.
+pub fn filter_by_color(
+    ports: &[PortInfo],
+    store: &TagStore,
+    color: Option<TagColor>,
+) -> Vec<PortInfo> {
+    match color {
+        None => ports.to_vec(),
+        Some(color) => ports
+            .iter()
+            .filter(|p| store.tags_for(p).iter().any(|t| t.color == color))
+            .cloned()
+            .collect(),
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::port_scanner::SocketState;
+
+    fn port(protocol: &str, port: u16, process: &str) -> PortInfo {
+        PortInfo {
+            protocol: protocol.into(),
+            port,
+            local_address: "0.0.0.0".into(),
+            pid: 1,
+            process_name: process.into(),
+            state: SocketState::open_for(protocol),
+            ..Default::default()
+        }
+    }
+
+    fn store() -> TagStore {
+        TagStore {
+            tags: vec![
+                Tag {
+                    target: TagTarget::Port {
+                        port: 5432,
+                        protocol: Some("tcp".into()),
+                    },
+                    note: "staging DB — no tocar".into(),
+                    color: TagColor::Red,
+                    do_not_kill: false,
+                    source: None,
+                },
+                Tag {
+                    target: TagTarget::Process {
+                        name: "node".into(),
+                    },
+                    note: "dev server".into(),
+                    color: TagColor::Blue,
+                    do_not_kill: false,
+                    source: None,
+                },
+            ],
+            shared: vec![Tag {
+                target: TagTarget::Process {
+                    name: "redis*".into(),
+                },
+                note: "caché compartida del equipo".into(),
+                color: TagColor::Purple,
+                do_not_kill: true,
+                source: Some("equipo".into()),
+            }],
+        }
+    }
+
+    /// Verifica la coincidencia por puerto, protocolo y proceso
+    #[test]
+    fn test_tag_matching() {
+        let store = store();
+        assert_eq!(store.tags_for(&port("tcp", 5432, "postgres")).len(), 1);
+        assert!(store.tags_for(&port("udp", 5432, "postgres")).is_empty());
+        assert_eq!(
+            store.tags_for(&port("tcp", 3000, "node"))[0].note,
+            "dev server"
+        );
+    }
+
+    /// Verifica el filtrado por color
+    #[test]
+    fn test_filter_by_color() {
+        let ports = vec![port("tcp", 5432, "postgres"), port("tcp", 3000, "node")];
+        let store = store();
+        let red = filter_by_color(&ports, &store, Some(TagColor::Red));
+        assert_eq!(red.len(), 1);
+        assert_eq!(red[0].port, 5432);
+        assert_eq!(filter_by_color(&ports, &store, None).len(), 2);
+    }
+
+    /// Verifica el formato JSON del archivo de etiquetas
+    #[test]
+    fn test_tag_json_format() {
+        let json = r#"{"tags":[{"target":{"port":{"port":3000}},"note":"web","color":"green"}]}"#;
+        let store: TagStore = serde_json::from_str(json).unwrap();
+        assert_eq!(store.tags[0].color, TagColor::Green);
+        assert_eq!(TagColor::parse("GREEN"), Some(TagColor::Green));
+    }
+
+    /// Verifica que las etiquetas compartidas se combinan y protegen procesos
+    #[test]
+    fn test_shared_tags_protection() {
+        let store = store();
+        let redis = port("tcp", 6379, "redis-server");
+        assert_eq!(store.tags_for(&redis).len(), 1);
+        assert!(store.is_protected(&redis));
+        assert!(!store.is_protected(&port("tcp", 5432, "postgres")));
+
+        // Las etiquetas compartidas nunca se serializan al archivo local
+        let json = serde_json::to_string(&store).unwrap();
+        assert!(!json.contains("redis"));
+    }
+}
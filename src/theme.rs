@@ -0,0 +1,145 @@
+/// Módulo de los temas de íconos del tray.
+///
+/// Por defecto los puertos se distinguen por un punto rojo (PID conocido)
+/// o amarillo (sin PID) y el contador del ícono es un círculo rojo: una
+/// diferencia que no ven algunas personas con daltonismo ni se aprecia
+/// en paneles monocromos. Con `icon_theme` en la configuración se elige
+/// otro juego, aplicado al ícono, al contador y a las marcas del menú:
+///
+/// ```toml
+/// icon_theme = "colorblind"   # "default" | "monochrome" | "high-contrast" | "colorblind"
+/// ```
+///
+/// - `monochrome`: íconos simbólicos del tema del sistema y marcas que se
+///   distinguen por la forma (● / ○), no por el color.
+/// - `high-contrast`: contador negro sobre amarillo y marcas ■ / □.
+/// - `colorblind`: azul y naranja (paleta de Okabe-Ito), distinguibles
+///   con cualquier tipo de daltonismo.
+///
+/// Como el idioma, el tema es global: lo aplica `Config::load`.
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Tema activo (índice de `IconTheme`)
+static THEME: AtomicU8 = AtomicU8::new(IconTheme::Default as u8);
+
+/// Tema de íconos configurado
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum IconTheme {
+    /// Colores originales (rojo y amarillo)
+    #[default]
+    Default = 0,
+    /// Sin color: formas y los íconos simbólicos del sistema
+    Monochrome = 1,
+    /// Máximo contraste
+    HighContrast = 2,
+    /// Colores seguros para daltonismo
+    Colorblind = 3,
+}
+
+/// Íconos, marcas y colores de un tema
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Palette {
+    /// Ícono del tray sin avisos
+    pub normal_icon: &'static str,
+    /// Ícono del tray con avisos de seguridad
+    pub warning_icon: &'static str,
+    /// Marca de un puerto con PID conocido
+    pub known_pid: &'static str,
+    /// Marca de un puerto sin PID
+    pub unknown_pid: &'static str,
+    /// Fondo del contador (ARGB)
+    pub badge_background: [u8; 4],
+    /// Dígitos del contador (ARGB)
+    pub badge_foreground: [u8; 4],
+}
+
+/// Blanco opaco (ARGB)
+const WHITE: [u8; 4] = [0xFF, 0xFF, 0xFF, 0xFF];
+
+impl IconTheme {
+    /// Íconos, marcas y colores del tema.
+    pub fn palette(&self) -> Palette {
+        match self {
+            IconTheme::Default => Palette {
+                normal_icon: "network-server",
+                warning_icon: "dialog-warning",
+                known_pid: "🔴",
+                unknown_pid: "🟡",
+                badge_background: [0xFF, 0xD3, 0x2F, 0x2F],
+                badge_foreground: WHITE,
+            },
+            IconTheme::Monochrome => Palette {
+                normal_icon: "network-server-symbolic",
+                warning_icon: "dialog-warning-symbolic",
+                known_pid: "●",
+                unknown_pid: "○",
+                badge_background: [0xFF, 0x21, 0x21, 0x21],
+                badge_foreground: WHITE,
+            },
+            IconTheme::HighContrast => Palette {
+                normal_icon: "network-server-symbolic",
+                warning_icon: "dialog-error-symbolic",
+                known_pid: "■",
+                unknown_pid: "□",
+                badge_background: [0xFF, 0xFF, 0xFF, 0x00],
+                badge_foreground: [0xFF, 0x00, 0x00, 0x00],
+            },
+            IconTheme::Colorblind => Palette {
+                normal_icon: "network-server",
+                warning_icon: "dialog-warning",
+                known_pid: "🔵",
+                unknown_pid: "🟠",
+                badge_background: [0xFF, 0x00, 0x72, 0xB2],
+                badge_foreground: WHITE,
+            },
+        }
+    }
+}
+
+/// Cambia el tema activo.
+pub fn set_theme(theme: IconTheme) {
+    THEME.store(theme as u8, Ordering::Relaxed);
+}
+
+/// Tema activo.
+pub fn current() -> IconTheme {
+    match THEME.load(Ordering::Relaxed) {
+        1 => IconTheme::Monochrome,
+        2 => IconTheme::HighContrast,
+        3 => IconTheme::Colorblind,
+        _ => IconTheme::Default,
+    }
+}
+
+/// Íconos, marcas y colores del tema activo.
+pub fn palette() -> Palette {
+    current().palette()
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verifica que cada tema distingue los estados y el contador se lee
+    #[test]
+    fn test_palettes() {
+        for theme in [
+            IconTheme::Default,
+            IconTheme::Monochrome,
+            IconTheme::HighContrast,
+            IconTheme::Colorblind,
+        ] {
+            let palette = theme.palette();
+            assert_ne!(palette.known_pid, palette.unknown_pid);
+            assert_ne!(palette.normal_icon, palette.warning_icon);
+            assert_ne!(palette.badge_background, palette.badge_foreground);
+        }
+        assert_eq!(IconTheme::default().palette().known_pid, "🔴");
+    }
+}
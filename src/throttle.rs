@@ -0,0 +1,196 @@
+/// Módulo de acciones que frenan un proceso sin matarlo.
+///
+/// Cuando un proceso con puertos satura el sistema pero no se quiere
+/// perder su estado (una compilación a medias, una base de datos con
+/// datos en memoria), en lugar de cerrarlo se puede:
+///
+/// - bajarle la prioridad de CPU (`renice`),
+/// - dejar su E/S de disco para cuando el disco esté libre (`ionice`),
+/// - congelarlo con `SIGSTOP` y reanudarlo luego con `SIGCONT`.
+///
+/// Las acciones se ofrecen en el submenu de cada puerto y en el CLI
+/// (`portslayer renice|ionice|freeze|thaw`); congelar un proceso crítico
+/// exige confirmación (ver `safety`). Como los kills, se intentan
+/// primero sin privilegios y, si el sistema lo rechaza (ej: devolver un
+/// proceso a nice 0, o uno de otro usuario), con `pkexec`.
+use std::fs;
+use std::process::Command;
+
+use crate::i18n::{tr, trf, Text};
+use crate::replay;
+use crate::safety;
+
+/// Prioridad de CPU más baja (la que menos tiempo recibe)
+pub const LOWEST_NICE: i32 = 19;
+
+/// Prioridad de "Bajar prioridad" del tray y por defecto de `renice`
+pub const DEFAULT_NICE: i32 = 10;
+
+/// Acción sobre un proceso que no lo termina
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    /// Cambiar la prioridad de CPU (-20 a 19)
+    Renice(i32),
+    /// Cambiar la clase de E/S de disco
+    Ionice(IoClass),
+    /// Detener el proceso (`SIGSTOP`)
+    Freeze,
+    /// Reanudar un proceso detenido (`SIGCONT`)
+    Thaw,
+}
+
+/// Clase de planificación de E/S (`ionice -c`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IoClass {
+    /// La clase normal de los procesos
+    BestEffort,
+    /// Solo usa el disco cuando nadie más lo pide
+    Idle,
+}
+
+impl Action {
+    /// Programa y argumentos que aplican la acción.
+    fn command(&self, pid: u32) -> (&'static str, Vec<String>) {
+        let pid = pid.to_string();
+        match self {
+            Action::Renice(nice) => (
+                "renice",
+                vec!["-n".into(), nice.to_string(), "-p".into(), pid],
+            ),
+            Action::Ionice(class) => {
+                let class = match class {
+                    IoClass::BestEffort => "2",
+                    IoClass::Idle => "3",
+                };
+                ("ionice", vec!["-c".into(), class.into(), "-p".into(), pid])
+            }
+            Action::Freeze => ("kill", vec!["-STOP".into(), pid]),
+            Action::Thaw => ("kill", vec!["-CONT".into(), pid]),
+        }
+    }
+
+    /// Descripción para los logs (ej: "nice 10").
+    pub fn describe(&self) -> String {
+        match self {
+            Action::Renice(nice) => format!("nice {}", nice),
+            Action::Ionice(IoClass::BestEffort) => "ionice best-effort".into(),
+            Action::Ionice(IoClass::Idle) => "ionice idle".into(),
+            Action::Freeze => "SIGSTOP".into(),
+            Action::Thaw => "SIGCONT".into(),
+        }
+    }
+}
+
+/// Aplica una acción a un proceso, con `pkexec` si hace falta.
+///
+/// # Returns
+/// `Err(String)` si el PID es desconocido, se está reproduciendo una
+/// sesión, la prioridad está fuera de rango o el sistema la rechazó.
+pub fn apply(pid: u32, action: Action) -> Result<(), String> {
+    if pid == 0 {
+        return Err(tr(Text::UnknownPidAction).to_string());
+    }
+    if replay::is_active() {
+        return Err(tr(Text::ReplayAction).to_string());
+    }
+    // Congelar sshd o el propio PortSlayer es tan grave como matarlo
+    if action == Action::Freeze {
+        safety::check(pid)?;
+    }
+    if let Action::Renice(nice) = action {
+        if !(-20..=LOWEST_NICE).contains(&nice) {
+            return Err(trf(Text::InvalidNice, &[&nice]));
+        }
+    }
+
+    let description = action.describe();
+    log::info!("Aplicando {} al proceso {}", description, pid);
+    let (program, args) = action.command(pid);
+    let result = Command::new(program)
+        .args(&args)
+        .output()
+        .map_err(|e| format!("Error ejecutando {}: {}", program, e))?;
+    if result.status.success() {
+        log::info!("{} aplicado al proceso {}", description, pid);
+        return Ok(());
+    }
+
+    log::warn!("{} sin permisos falló, intentando con pkexec...", program);
+    let elevated = Command::new("pkexec")
+        .arg(program)
+        .args(&args)
+        .output()
+        .map_err(|e| format!("Error ejecutando pkexec: {}", e))?;
+    if elevated.status.success() {
+        log::info!(
+            "{} aplicado al proceso {} con permisos elevados",
+            description,
+            pid
+        );
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&elevated.stderr);
+        Err(trf(Text::ActionFailed, &[&description, &pid, &stderr]))
+    }
+}
+
+/// Estado de planificación de un proceso
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Scheduling {
+    /// Prioridad de CPU actual
+    pub nice: i32,
+    /// Detenido por una señal (`SIGSTOP`)
+    pub frozen: bool,
+}
+
+/// Prioridad y estado de un proceso (`None` si ya no existe).
+pub fn scheduling(pid: u32) -> Option<Scheduling> {
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    parse_scheduling(&stat)
+}
+
+/// Extrae el estado y la prioridad de una línea de `/proc/<pid>/stat`.
+///
+/// Como en `cpu`, los campos se cuentan desde el último `)` porque el
+/// nombre del proceso puede contener espacios.
+fn parse_scheduling(stat: &str) -> Option<Scheduling> {
+    let fields: Vec<&str> = stat
+        .get(stat.rfind(')')? + 1..)?
+        .split_whitespace()
+        .collect();
+    // Tras el nombre: estado (3), ..., nice (19)
+    let frozen = *fields.first()? == "T";
+    let nice = fields.get(16)?.parse().ok()?;
+    Some(Scheduling { nice, frozen })
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verifica la lectura del estado y las órdenes de cada acción
+    #[test]
+    fn test_scheduling_and_commands() {
+        let stat = "4242 (node dev) T 1 4242 4242 0 -1 4194560 1234 0 0 0 250 30 0 0 30 10 11 0";
+        assert_eq!(
+            parse_scheduling(stat),
+            Some(Scheduling {
+                nice: 10,
+                frozen: true,
+            })
+        );
+        let running = stat.replace(") T ", ") S ");
+        assert!(!parse_scheduling(&running).unwrap().frozen);
+
+        let (program, args) = Action::Ionice(IoClass::Idle).command(4242);
+        assert_eq!((program, args.join(" ")), ("ionice", "-c 3 -p 4242".into()));
+        let (program, args) = Action::Freeze.command(4242);
+        assert_eq!((program, args.join(" ")), ("kill", "-STOP 4242".into()));
+        assert!(apply(4242, Action::Renice(25)).is_err());
+        assert!(apply(0, Action::Thaw).is_err());
+    }
+}
@@ -0,0 +1,219 @@
+/// Módulo de los puertos retenidos en `TIME_WAIT`.
+///
+/// Tras cerrar un servidor, cada conexión que cerró él queda unos 60 s
+/// en `TIME_WAIT` con su puerto local. Mientras tanto nadie escucha,
+/// pero volver a abrir el puerto falla con "Address already in use" si el
+/// servidor no usa `SO_REUSEADDR`. La lista de puertos no muestra nada y
+/// el error parece no tener explicación.
+///
+/// [`scan`] cuenta esos sockets por puerto local y estima cuándo
+/// expirará el último con el temporizador de `ss -tano`:
+///
+/// ```text
+/// 0  0  127.0.0.1:8080  127.0.0.1:51234  timer:(timewait,52sec,0)
+/// ```
+///
+/// [`destroy`] los elimina con `ss -K` (con `pkexec` si no se es root).
+/// Necesita un kernel con `CONFIG_INET_DIAG_DESTROY` y `ss` no informa
+/// si el kernel se niega, así que se vuelven a contar después.
+///
+/// Los del rango efímero no cuentan: son de conexiones salientes y no
+/// impiden escuchar en ningún puerto.
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::process::Command;
+
+use portslayer_core::sources::{self, SOURCE_TIMEOUT};
+
+use crate::firewall;
+use crate::i18n::{trf, Text};
+use crate::port_scanner::{PortInfo, SocketState};
+
+/// Rango efímero si no se puede leer `ip_local_port_range`
+const DEFAULT_EPHEMERAL: (u16, u16) = (32768, 60999);
+
+/// Sockets en `TIME_WAIT` de un puerto local
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Lingering {
+    pub port: u16,
+    /// Cantidad de sockets
+    pub count: usize,
+    /// Segundos hasta que expire el último (si `ss` lo informa)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub clears_in_secs: Option<u64>,
+}
+
+/// Sockets TCP en `TIME_WAIT` por puerto local, solo de los puertos
+/// que no son efímeros.
+pub fn scan() -> Vec<Lingering> {
+    let ephemeral = ephemeral_range();
+    run_ss(None)
+        .into_iter()
+        .filter(|l| !(ephemeral.0..=ephemeral.1).contains(&l.port))
+        .collect()
+}
+
+/// Sockets en `TIME_WAIT` de un puerto concreto (efímero o no).
+pub fn for_port(port: u16) -> Option<Lingering> {
+    run_ss(Some(port)).into_iter().next()
+}
+
+/// Puertos retenidos: los de [`scan`] en los que nadie escucha.
+pub fn held_ports(listening: &[PortInfo]) -> Vec<Lingering> {
+    scan()
+        .into_iter()
+        .filter(|l| {
+            !listening
+                .iter()
+                .any(|p| p.port == l.port && p.protocol == "tcp" && p.state == SocketState::Listen)
+        })
+        .collect()
+}
+
+/// Destruye los sockets en `TIME_WAIT` de un puerto con `ss -K`.
+///
+/// # Returns
+/// Cuántos se destruyeron, o un error si no se pudo destruir ninguno.
+pub fn destroy(port: u16) -> Result<usize, String> {
+    let Some(before) = for_port(port) else {
+        return Ok(0);
+    };
+    let filter = format!("( sport = :{} )", port);
+    firewall::run_as_root("ss", &["-K", "state", "time-wait", &filter], None)
+        .map_err(|e| format!("ss -K falló: {}", e))?;
+    let remaining = for_port(port).map_or(0, |l| l.count);
+    let destroyed = before.count.saturating_sub(remaining);
+    if destroyed == 0 {
+        return Err(trf(
+            Text::TimeWaitNotDestroyed,
+            &[&before.clears_in_secs.unwrap_or(60)],
+        ));
+    }
+    log::info!(
+        "{} sockets en TIME_WAIT del puerto {} destruidos con ss -K",
+        destroyed,
+        port
+    );
+    Ok(destroyed)
+}
+
+/// Ejecuta `ss -tanoH state time-wait` (filtrado por puerto local).
+fn run_ss(port: Option<u16>) -> Vec<Lingering> {
+    let mut command = Command::new("ss");
+    command.args(["-tanoH", "state", "time-wait"]);
+    if let Some(port) = port {
+        command.arg(format!("( sport = :{} )", port));
+    }
+    sources::output_with_timeout(&mut command, SOURCE_TIMEOUT)
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| parse_ss(&String::from_utf8_lossy(&output.stdout)))
+        .unwrap_or_default()
+}
+
+/// Agrupa por puerto local la salida de `ss -tanoH state time-wait`
+/// ("Recv-Q Send-Q local peer timer:(timewait,52sec,0)").
+fn parse_ss(text: &str) -> Vec<Lingering> {
+    let mut ports: BTreeMap<u16, Lingering> = BTreeMap::new();
+    for line in text.lines() {
+        let mut tokens = line.split_whitespace();
+        let Some(port) = tokens
+            .nth(2)
+            .and_then(|local| local.rsplit_once(':'))
+            .and_then(|(_, port)| port.parse::<u16>().ok())
+        else {
+            continue;
+        };
+        let remaining = tokens
+            .find_map(|token| token.strip_prefix("timer:(timewait,"))
+            .and_then(|timer| parse_timer(timer.split(',').next()?));
+        let entry = ports.entry(port).or_insert(Lingering {
+            port,
+            count: 0,
+            clears_in_secs: None,
+        });
+        entry.count += 1;
+        entry.clears_in_secs = entry.clears_in_secs.max(remaining);
+    }
+    ports.into_values().collect()
+}
+
+/// Segundos (redondeados hacia arriba) de un temporizador de `ss`:
+/// "52sec", "1min", "1min2sec", "950ms".
+fn parse_timer(timer: &str) -> Option<u64> {
+    let mut millis = 0u64;
+    let mut rest = timer;
+    while !rest.is_empty() {
+        let digits = rest.find(|c: char| !c.is_ascii_digit())?;
+        let value: u64 = rest[..digits].parse().ok()?;
+        rest = &rest[digits..];
+        let unit = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        millis += match &rest[..unit] {
+            "min" => value * 60_000,
+            "sec" => value * 1000,
+            "ms" => value,
+            _ => return None,
+        };
+        rest = &rest[unit..];
+    }
+    Some(millis.div_ceil(1000))
+}
+
+/// Rango de puertos efímeros del kernel (`ip_local_port_range`).
+fn ephemeral_range() -> (u16, u16) {
+    fs::read_to_string("/proc/sys/net/ipv4/ip_local_port_range")
+        .ok()
+        .and_then(|text| {
+            let mut bounds = text.split_whitespace().map(|n| n.parse::<u16>().ok());
+            Some((bounds.next()??, bounds.next()??))
+        })
+        .unwrap_or(DEFAULT_EPHEMERAL)
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verifica el recuento por puerto local y la estimación del último
+    /// en expirar
+    #[test]
+    fn test_parse_time_wait() {
+        let output = "\
+0      0      127.0.0.1:8080 127.0.0.1:56138 timer:(timewait,12sec,0)
+0      0      127.0.0.1:8080 127.0.0.1:56154 timer:(timewait,52sec,0)
+0      0      [::1]:3000     [::1]:40100     timer:(timewait,950ms,0)
+0      0      127.0.0.1:56170 127.0.0.1:8080 timer:(timewait,59sec,0)
+";
+        let lingering = parse_ss(output);
+        assert_eq!(
+            lingering,
+            vec![
+                Lingering {
+                    port: 3000,
+                    count: 1,
+                    clears_in_secs: Some(1)
+                },
+                Lingering {
+                    port: 8080,
+                    count: 2,
+                    clears_in_secs: Some(52)
+                },
+                Lingering {
+                    port: 56170,
+                    count: 1,
+                    clears_in_secs: Some(59)
+                },
+            ]
+        );
+        assert_eq!(parse_timer("1min2sec"), Some(62));
+        assert_eq!(parse_timer("1min"), Some(60));
+        assert_eq!(parse_timer("soon"), None);
+    }
+}
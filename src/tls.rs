@@ -0,0 +1,192 @@
+/// Módulo de inspección de certificados TLS.
+///
+/// Cuando un puerto habla TLS (HTTPS, bases de datos con certificado,
+/// proxies...), su certificado dice para qué nombres vale y hasta cuándo.
+/// Se obtiene con `openssl s_client` contra el puerto local y se lee con
+/// `openssl x509`: sujeto, emisor, nombres alternativos (SAN) y fecha de
+/// caducidad. Lo lanza el sondeo de servicios (ver `fingerprint`) con los
+/// puertos que no saludan al conectar, que es lo que hace un servidor TLS
+/// mientras espera el `ClientHello`.
+///
+/// En los puertos accesibles desde la red, un certificado caducado o
+/// autofirmado (sujeto igual al emisor) se marca como aviso.
+use serde::Serialize;
+use std::io::Write;
+use std::net::SocketAddr;
+use std::process::{Command, Stdio};
+use std::time::Duration;
+
+use portslayer_core::sources;
+
+/// Espera máxima del handshake con `openssl s_client`
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Meses en el formato de fechas de OpenSSL ("Oct 14 12:00:00 2027 GMT")
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Certificado presentado por un puerto
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Certificate {
+    /// Sujeto en formato RFC 2253 ("CN=example.com,O=Acme")
+    pub subject: String,
+    /// Emisor, en el mismo formato
+    pub issuer: String,
+    /// Nombres alternativos ("DNS:example.com", "IP Address:127.0.0.1")
+    pub sans: Vec<String>,
+    /// Fin de la validez (UNIX)
+    pub not_after: u64,
+}
+
+impl Certificate {
+    /// Nombre común (CN) del sujeto o, si no tiene, el sujeto entero.
+    pub fn common_name(&self) -> &str {
+        self.subject
+            .split(',')
+            .find_map(|part| part.trim().strip_prefix("CN="))
+            .unwrap_or(&self.subject)
+    }
+
+    /// Indica si ya caducó.
+    pub fn expired(&self, now: u64) -> bool {
+        self.not_after <= now
+    }
+
+    /// Indica si es autofirmado (el emisor es el propio sujeto).
+    pub fn self_signed(&self) -> bool {
+        self.subject == self.issuer
+    }
+
+    /// Fecha de caducidad legible ("2027-10-14").
+    pub fn expiry_date(&self) -> String {
+        i64::try_from(self.not_after)
+            .ok()
+            .and_then(|secs| jiff::Timestamp::from_second(secs).ok())
+            .map(|ts| ts.to_zoned(jiff::tz::TimeZone::UTC).date().to_string())
+            .unwrap_or_default()
+    }
+}
+
+/// Obtiene el certificado que presenta un puerto.
+///
+/// # Returns
+/// `None` si no habla TLS, no respondió a tiempo o no hay `openssl`.
+pub fn inspect(address: SocketAddr) -> Option<Certificate> {
+    let mut handshake = Command::new("openssl");
+    handshake.args(["s_client", "-connect", &address.to_string(), "-showcerts"]);
+    // Con un servidor que no habla TLS, s_client falla y no imprime nada
+    let output = sources::output_with_timeout(&mut handshake, HANDSHAKE_TIMEOUT).ok()?;
+    let text = String::from_utf8_lossy(&output.stdout);
+    let pem = first_pem(&text)?;
+
+    let mut child = Command::new("openssl")
+        .args([
+            "x509",
+            "-noout",
+            "-nameopt",
+            "RFC2253",
+            "-subject",
+            "-issuer",
+            "-enddate",
+            "-ext",
+            "subjectAltName",
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+    child.stdin.take()?.write_all(pem.as_bytes()).ok()?;
+    let output = child.wait_with_output().ok()?;
+    parse_x509_text(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Primer certificado en PEM de una salida de `s_client` (el del servidor).
+fn first_pem(text: &str) -> Option<String> {
+    const BEGIN: &str = "-----BEGIN CERTIFICATE-----";
+    const END: &str = "-----END CERTIFICATE-----";
+    let start = text.find(BEGIN)?;
+    let end = text[start..].find(END)? + start + END.len();
+    Some(format!("{}\n", &text[start..end]))
+}
+
+/// Interpreta la salida de `openssl x509 -noout -subject -issuer
+/// -enddate -ext subjectAltName`.
+fn parse_x509_text(text: &str) -> Option<Certificate> {
+    let mut subject = None;
+    let mut issuer = None;
+    let mut not_after = None;
+    let mut sans = Vec::new();
+    let mut in_sans = false;
+    for line in text.lines() {
+        if let Some(value) = line.strip_prefix("subject=") {
+            subject = Some(value.trim().to_string());
+        } else if let Some(value) = line.strip_prefix("issuer=") {
+            issuer = Some(value.trim().to_string());
+        } else if let Some(value) = line.strip_prefix("notAfter=") {
+            not_after = parse_openssl_date(value);
+        } else if line.starts_with("X509v3 Subject Alternative Name") {
+            in_sans = true;
+            continue;
+        } else if in_sans && line.starts_with(' ') {
+            sans.extend(line.split(',').map(|san| san.trim().to_string()));
+        }
+        in_sans = false;
+    }
+    Some(Certificate {
+        subject: subject?,
+        issuer: issuer?,
+        sans,
+        not_after: not_after?,
+    })
+}
+
+/// Interpreta una fecha de OpenSSL ("Oct 14 12:00:00 2027 GMT") en
+/// segundos UNIX.
+fn parse_openssl_date(text: &str) -> Option<u64> {
+    let parts: Vec<&str> = text.split_whitespace().collect();
+    let [month, day, time, year, ..] = parts.as_slice() else {
+        return None;
+    };
+    let month = MONTHS.iter().position(|m| m == month)? as i8 + 1;
+    let mut clock = time.split(':').map(|n| n.parse::<i8>().ok());
+    let (hour, minute, second) = (clock.next()??, clock.next()??, clock.next()??);
+    let datetime =
+        jiff::civil::date(year.parse().ok()?, month, day.parse().ok()?).at(hour, minute, second, 0);
+    let zoned = datetime.to_zoned(jiff::tz::TimeZone::UTC).ok()?;
+    u64::try_from(zoned.timestamp().as_second()).ok()
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verifica la lectura del certificado y sus avisos
+    #[test]
+    fn test_parse_x509_text() {
+        let text = "subject=CN=dev.local,O=Acme\n\
+            issuer=CN=dev.local,O=Acme\n\
+            notAfter=Oct  4 12:00:00 2027 GMT\n\
+            X509v3 Subject Alternative Name: \n    \
+            DNS:dev.local, DNS:*.dev.local, IP Address:127.0.0.1\n";
+        let certificate = parse_x509_text(text).unwrap();
+        assert_eq!(certificate.common_name(), "dev.local");
+        assert_eq!(
+            certificate.sans,
+            ["DNS:dev.local", "DNS:*.dev.local", "IP Address:127.0.0.1"]
+        );
+        assert_eq!(certificate.not_after, 1822651200);
+        assert_eq!(certificate.expiry_date(), "2027-10-04");
+        assert!(certificate.self_signed());
+        assert!(!certificate.expired(1791990000));
+        assert!(certificate.expired(1822651200));
+
+        assert_eq!(parse_x509_text("subject=CN=x\n"), None);
+        assert_eq!(parse_openssl_date("Foo 4 12:00:00 2027 GMT"), None);
+    }
+}
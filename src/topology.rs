@@ -0,0 +1,226 @@
+/// Módulo de topología de servicios locales.
+///
+/// Construye el grafo de la pila local: cada proceso con puertos en
+/// escucha es un nodo, y cada proceso local conectado a uno de esos
+/// puertos aporta una arista cliente → servicio. El grafo se exporta
+/// en DOT (Graphviz) o JSON.
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::connections::{self, Connection};
+use crate::port_scanner::PortInfo;
+
+/// Formatos de exportación del grafo
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GraphFormat {
+    /// Lenguaje DOT de Graphviz
+    Dot,
+    /// Documento JSON con `nodes` y `edges`
+    Json,
+}
+
+impl GraphFormat {
+    /// Interpreta el nombre de un formato ("dot" o "json").
+    #[cfg(feature = "api")]
+    pub fn parse(name: &str) -> Option<GraphFormat> {
+        match name.to_lowercase().as_str() {
+            "dot" => Some(GraphFormat::Dot),
+            "json" => Some(GraphFormat::Json),
+            _ => None,
+        }
+    }
+
+    /// Tipo MIME del formato, para respuestas HTTP
+    #[cfg(feature = "api")]
+    pub fn mime_type(&self) -> &'static str {
+        match self {
+            GraphFormat::Dot => "text/vnd.graphviz",
+            GraphFormat::Json => "application/json",
+        }
+    }
+}
+
+/// Proceso del grafo (servicio en escucha o cliente local)
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ServiceNode {
+    /// Identificador estable del nodo dentro del grafo
+    pub id: String,
+    /// PID del proceso (0 si no se pudo determinar)
+    pub pid: u32,
+    /// Nombre del proceso
+    pub process_name: String,
+    /// Puertos en escucha del proceso (vacío para clientes puros)
+    pub ports: Vec<u16>,
+}
+
+/// Conexiones de un cliente local hacia un puerto en escucha
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ServiceEdge {
+    /// Nodo cliente
+    pub from: String,
+    /// Nodo servicio
+    pub to: String,
+    /// Puerto del servicio
+    pub port: u16,
+    /// Conexiones establecidas entre ambos
+    pub connections: usize,
+}
+
+/// Grafo de servicios locales
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Topology {
+    pub nodes: Vec<ServiceNode>,
+    pub edges: Vec<ServiceEdge>,
+}
+
+impl Topology {
+    /// Construye el grafo a partir de un escaneo de puertos y conexiones.
+    ///
+    /// # Arguments
+    /// * `ports` - Puertos en escucha
+    /// * `connections` - Conexiones establecidas
+    pub fn build(ports: &[PortInfo], connections: &[Connection]) -> Self {
+        let mut nodes: BTreeMap<String, ServiceNode> = BTreeMap::new();
+        let mut edges: Vec<ServiceEdge> = Vec::new();
+
+        for port in ports {
+            // Los puertos sin PID no se pueden agrupar por proceso
+            let id = if port.pid > 0 {
+                format!("p{}", port.pid)
+            } else {
+                format!("port{}", port.port)
+            };
+            let node = nodes.entry(id.clone()).or_insert_with(|| ServiceNode {
+                id: id.clone(),
+                pid: port.pid,
+                process_name: port.process_name.clone(),
+                ports: Vec::new(),
+            });
+            if !node.ports.contains(&port.port) {
+                node.ports.push(port.port);
+            }
+
+            for dependent in connections::kill_impact(port, connections).dependents {
+                let from = if dependent.pid > 0 {
+                    format!("p{}", dependent.pid)
+                } else {
+                    "unknown".to_string()
+                };
+                nodes.entry(from.clone()).or_insert_with(|| ServiceNode {
+                    id: from.clone(),
+                    pid: dependent.pid,
+                    process_name: dependent.process_name.clone(),
+                    ports: Vec::new(),
+                });
+                edges.push(ServiceEdge {
+                    from,
+                    to: id.clone(),
+                    port: port.port,
+                    connections: dependent.connections,
+                });
+            }
+        }
+
+        Self {
+            nodes: nodes.into_values().collect(),
+            edges,
+        }
+    }
+
+    /// Serializa el grafo en el formato indicado.
+    pub fn render(&self, format: GraphFormat) -> String {
+        match format {
+            GraphFormat::Dot => self.to_dot(),
+            GraphFormat::Json => serde_json::to_string_pretty(self).unwrap_or_default(),
+        }
+    }
+
+    /// Genera el grafo en lenguaje DOT.
+    ///
+    /// ```text
+    /// digraph portslayer {
+    ///   "p880" [label="postgres\n:5432"];
+    ///   "p1200" -> "p880" [label=":5432 (3)"];
+    /// }
+    /// ```
+    fn to_dot(&self) -> String {
+        let mut out = String::from("digraph portslayer {\n  rankdir=LR;\n  node [shape=box];\n");
+        for node in &self.nodes {
+            let mut label = dot_escape(&node.process_name);
+            if node.pid > 0 {
+                label.push_str(&format!(" [{}]", node.pid));
+            }
+            for port in &node.ports {
+                label.push_str(&format!("\\n:{}", port));
+            }
+            out.push_str(&format!("  \"{}\" [label=\"{}\"];\n", node.id, label));
+        }
+        for edge in &self.edges {
+            out.push_str(&format!(
+                "  \"{}\" -> \"{}\" [label=\":{} ({})\"];\n",
+                edge.from, edge.to, edge.port, edge.connections
+            ));
+        }
+        out.push_str("}\n");
+        out
+    }
+}
+
+/// Escapa una cadena para usarla entre comillas en DOT.
+fn dot_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn connection(local: (&str, u16), peer: (&str, u16), pid: u32, name: &str) -> Connection {
+        Connection {
+            local_address: local.0.into(),
+            local_port: local.1,
+            peer_address: peer.0.into(),
+            peer_port: peer.1,
+            pid,
+            process_name: name.into(),
+        }
+    }
+
+    /// Verifica la construcción del grafo y su salida DOT
+    #[test]
+    fn test_build_topology() {
+        let ports = vec![PortInfo {
+            protocol: "tcp".into(),
+            port: 5432,
+            local_address: "127.0.0.1".into(),
+            pid: 880,
+            process_name: "postgres".into(),
+            ..Default::default()
+        }];
+        let conns = vec![
+            connection(("127.0.0.1", 5432), ("127.0.0.1", 51000), 880, "postgres"),
+            connection(("127.0.0.1", 51000), ("127.0.0.1", 5432), 1200, "node"),
+        ];
+
+        let graph = Topology::build(&ports, &conns);
+        assert_eq!(graph.nodes.len(), 2);
+        assert_eq!(
+            graph.edges,
+            vec![ServiceEdge {
+                from: "p1200".into(),
+                to: "p880".into(),
+                port: 5432,
+                connections: 1,
+            }]
+        );
+
+        let dot = graph.render(GraphFormat::Dot);
+        assert!(dot.starts_with("digraph portslayer {"));
+        assert!(dot.contains("\"p880\" [label=\"postgres [880]\\n:5432\"];"));
+        assert!(dot.contains("\"p1200\" -> \"p880\" [label=\":5432 (1)\"];"));
+    }
+}
@@ -0,0 +1,260 @@
+/// Módulo de tráfico por puerto.
+///
+/// Con `traffic_stats = true` en la configuración, el tray muestra junto a
+/// cada puerto cuánto se está moviendo por él ("⇅ 1.2 MB/s"), para ver qué
+/// servidor trabaja de verdad y cuál solo está abierto.
+///
+/// En cada intervalo se leen los contadores de bytes de las conexiones
+/// TCP establecidas (`ss -tinH`, que los obtiene del kernel con
+/// `INET_DIAG_INFO`) y se comparan con el muestreo anterior. El tráfico
+/// de cada conexión se atribuye a su puerto local, que para las
+/// conexiones aceptadas es el del servidor. UDP no lleva contadores por
+/// socket, así que solo se mide TCP.
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use crate::port_scanner;
+
+/// Tráfico mínimo (bytes/s) a partir del cual se muestra en el menú
+pub const MIN_SHOWN_BYTES_PER_SEC: f64 = 1024.0;
+
+/// Tráfico por puerto del último muestreo
+static LAST_RATES: Mutex<Option<HashMap<u16, Rate>>> = Mutex::new(None);
+
+/// Tráfico de un puerto
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Rate {
+    /// Bytes enviados por segundo
+    pub tx_per_sec: f64,
+    /// Bytes recibidos por segundo
+    pub rx_per_sec: f64,
+}
+
+impl Rate {
+    /// Bytes por segundo en ambos sentidos.
+    pub fn total(&self) -> f64 {
+        self.tx_per_sec + self.rx_per_sec
+    }
+}
+
+/// Contadores de una conexión TCP
+#[derive(Debug, Clone, PartialEq)]
+struct SocketBytes {
+    /// Puerto local
+    port: u16,
+    /// Dirección remota (identifica la conexión junto al puerto)
+    peer: String,
+    /// Bytes enviados desde que se abrió
+    sent: u64,
+    /// Bytes recibidos desde que se abrió
+    received: u64,
+}
+
+/// Estado del muestreo entre intervalos
+#[derive(Debug, Default)]
+pub struct TrafficMonitor {
+    /// Contadores del muestreo anterior por (puerto, dirección remota)
+    previous: HashMap<(u16, String), (u64, u64)>,
+    /// Momento del muestreo anterior
+    at: Option<Instant>,
+}
+
+impl TrafficMonitor {
+    /// Crea un monitor sin muestreos previos.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Lee los contadores actuales y publica el tráfico por puerto.
+    pub fn sample(&mut self) {
+        let Some(output) = port_scanner::execute_ss_command("-tinH") else {
+            return;
+        };
+        let rates = self.update(parse_ss_info(&output), Instant::now());
+        if let Ok(mut last) = LAST_RATES.lock() {
+            *last = rates;
+        }
+    }
+
+    /// Registra un muestreo.
+    ///
+    /// # Returns
+    /// Tráfico por puerto desde el muestreo anterior (`None` en el primero).
+    fn update(&mut self, sockets: Vec<SocketBytes>, now: Instant) -> Option<HashMap<u16, Rate>> {
+        let elapsed = self
+            .at
+            .map(|at| now.saturating_duration_since(at).as_secs_f64());
+        let mut bytes: HashMap<u16, (u64, u64)> = HashMap::new();
+        let mut current = HashMap::new();
+        for socket in sockets {
+            let key = (socket.port, socket.peer);
+            // Una conexión nueva se abrió durante el intervalo: cuenta entera
+            let (sent_before, received_before) = self.previous.get(&key).copied().unwrap_or((0, 0));
+            let entry = bytes.entry(socket.port).or_default();
+            entry.0 += socket.sent.saturating_sub(sent_before);
+            entry.1 += socket.received.saturating_sub(received_before);
+            current.insert(key, (socket.sent, socket.received));
+        }
+        self.previous = current;
+        self.at = Some(now);
+
+        let elapsed = elapsed.filter(|secs| *secs > 0.0)?;
+        Some(
+            bytes
+                .into_iter()
+                .map(|(port, (sent, received))| {
+                    let rate = Rate {
+                        tx_per_sec: sent as f64 / elapsed,
+                        rx_per_sec: received as f64 / elapsed,
+                    };
+                    (port, rate)
+                })
+                .collect(),
+        )
+    }
+}
+
+/// Tráfico de un puerto en el último muestreo (`None` si no se mide o
+/// no tiene conexiones).
+pub fn rate(port: u16) -> Option<Rate> {
+    LAST_RATES.lock().ok()?.as_ref()?.get(&port).copied()
+}
+
+/// Texto del tráfico que el menú muestra por puerto, ordenado por
+/// puerto; solo los que superan [`MIN_SHOWN_BYTES_PER_SEC`].
+pub fn shown() -> Vec<(u16, String)> {
+    let Ok(last) = LAST_RATES.lock() else {
+        return Vec::new();
+    };
+    let mut shown: Vec<(u16, String)> = last
+        .iter()
+        .flatten()
+        .filter(|(_, rate)| rate.total() >= MIN_SHOWN_BYTES_PER_SEC)
+        .map(|(port, rate)| (*port, format_rate(rate.total())))
+        .collect();
+    shown.sort();
+    shown
+}
+
+/// Puerto con más tráfico del último muestreo, si alguno supera
+/// [`MIN_SHOWN_BYTES_PER_SEC`].
+pub fn busiest() -> Option<u16> {
+    let last = LAST_RATES.lock().ok()?;
+    last.as_ref()?
+        .iter()
+        .filter(|(_, rate)| rate.total() >= MIN_SHOWN_BYTES_PER_SEC)
+        .max_by(|a, b| a.1.total().total_cmp(&b.1.total()))
+        .map(|(port, _)| *port)
+}
+
+/// Formatea un tráfico en bytes por segundo ("820 B/s", "1.2 MB/s").
+pub fn format_rate(bytes_per_sec: f64) -> String {
+    const UNITS: [&str; 4] = ["B/s", "KB/s", "MB/s", "GB/s"];
+    let mut value = bytes_per_sec;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{:.0} {}", value, UNITS[unit])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// Parsea la salida de `ss -tinH`: cada conexión ocupa una línea y la
+/// siguiente, con sangría, trae sus contadores.
+///
+/// ```text
+/// ESTAB 0 0 127.0.0.1:5432 127.0.0.1:41234
+///      cubic wscale:7,7 rto:204 ... bytes_sent:1234 bytes_acked:1235 bytes_received:999 ...
+/// ```
+fn parse_ss_info(output: &str) -> Vec<SocketBytes> {
+    let mut sockets = Vec::new();
+    let mut connection: Option<(u16, String)> = None;
+    for line in output.lines() {
+        if !line.starts_with(char::is_whitespace) {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            connection = match parts.as_slice() {
+                [_, _, _, local, peer, ..] => local
+                    .rsplit_once(':')
+                    .and_then(|(_, port)| port.parse().ok())
+                    .map(|port| (port, peer.to_string())),
+                _ => None,
+            };
+            continue;
+        }
+        let Some((port, peer)) = connection.take() else {
+            continue;
+        };
+        let counter = |name: &str| {
+            line.split_whitespace()
+                .find_map(|field| field.strip_prefix(name)?.parse::<u64>().ok())
+                .unwrap_or(0)
+        };
+        sockets.push(SocketBytes {
+            port,
+            peer,
+            sent: counter("bytes_sent:"),
+            received: counter("bytes_received:"),
+        });
+    }
+    sockets
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// Verifica la lectura de los contadores y el cálculo por intervalo
+    #[test]
+    fn test_traffic_rates() {
+        let sample = |sent: u64, received: u64| {
+            format!(
+                "ESTAB 0 0 127.0.0.1:5432 127.0.0.1:41234\n\
+                 \t cubic wscale:7,7 rto:204 bytes_sent:{} bytes_acked:{} bytes_received:{} segs_out:9\n\
+                 ESTAB 0 0 [::1]:3000 [::1]:50000\n",
+                sent,
+                sent + 1,
+                received
+            )
+        };
+        let sockets = parse_ss_info(&sample(1_000, 200));
+        assert_eq!(
+            sockets,
+            vec![SocketBytes {
+                port: 5432,
+                peer: "127.0.0.1:41234".into(),
+                sent: 1_000,
+                received: 200,
+            }]
+        );
+
+        let start = Instant::now();
+        let mut monitor = TrafficMonitor::new();
+        assert_eq!(monitor.update(sockets, start), None);
+        let rates = monitor
+            .update(
+                parse_ss_info(&sample(21_480, 2_248)),
+                start + Duration::from_secs(10),
+            )
+            .unwrap();
+        assert_eq!(
+            rates.get(&5432),
+            Some(&Rate {
+                tx_per_sec: 2_048.0,
+                rx_per_sec: 204.8,
+            })
+        );
+
+        assert_eq!(format_rate(820.0), "820 B/s");
+        assert_eq!(format_rate(1_258_291.2), "1.2 MB/s");
+    }
+}
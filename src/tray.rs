@@ -5,15 +5,73 @@
 ///
 /// ## Características del menú:
 /// - Filtro por protocolo (TCP / UDP / Todos)
+/// - Filtro por color de etiqueta y notas junto a cada puerto
+/// - Estado de las ventanas de mantenimiento configuradas
+/// - Espacios de trabajo con favoritos (⭐) y puertos ignorados
 /// - Paginación configurable (5 o 10 puertos por página)
 /// - Navegación entre páginas con indicador visual
 /// - Cierre individual y masivo de puertos
-/// - Actualización automática cada 10 segundos
-use ksni::{self, menu::StandardItem, menu::SubMenu, Tray};
-use std::process;
+/// - Confirmación con las conexiones activas que se cortarían
+/// - Reinicio de los procesos cerrados con el mismo comando
+/// - Activación/desactivación de las reglas automáticas
+/// - Puertos reservados (🛡) y aviso de intrusos (🚨)
+/// - Actualización automática configurable (2/5/10/30 s o manual)
+/// - Número de puertos en el título, el tooltip y sobre el ícono
+/// - Ícono de aviso si hay intrusos o puertos expuestos en la red
+/// - Ícono XEmbed alternativo (`yad`) si no hay host de StatusNotifierItem
+use ksni::{self, menu::CheckmarkItem, menu::StandardItem, menu::SubMenu, Tray};
+use portslayer_core::sources;
+use std::collections::hash_map::DefaultHasher;
+use std::fs;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
 
-use crate::port_scanner::{self, ProtocolFilter};
+use crate::adopt;
+use crate::autostart;
+use crate::badge;
+use crate::capabilities;
+use crate::clipboard::{self, CopyFormat};
+use crate::config::Config;
+use crate::connections::{self, Connection, KillImpact};
+use crate::cpu::{self, CpuMonitor};
+use crate::docker::{self, Binding};
+use crate::effectiveness::{self, KillStats, KillTarget, RespawnAdvice};
+use crate::fallback::{FallbackItem, FallbackTray};
+use crate::fingerprint;
+use crate::firewall::{self, BlockStore};
+use crate::firewall_manager;
+use crate::health;
+use crate::history;
+use crate::i18n::{tr, trf, Text};
+use crate::kubernetes;
+use crate::logging;
+use crate::maintenance;
+use crate::manifest::Manifest;
+use crate::pins::PinStore;
+use crate::port_scanner::{
+    self, format_uptime, owner_label, protocol_label, range_label, scope_label, ChangeDetector,
+    FdScanReport, OwnerFilter, ProtocolFilter, RangeFilter, ScopeFilter, StateFilter,
+};
+use crate::project;
+use crate::replay;
+use crate::reservations::{Reservation, ReservationStore, SecurityAlerts};
+use crate::restart::{self, ProcessSnapshot};
+use crate::rules::{self, RuleEngine};
+use crate::safety::{self, Danger};
+use crate::sandbox;
+use crate::shutdown::{self, Summary};
+use crate::tags::{self, TagColor, TagStore};
+use crate::theme;
+use crate::throttle::{self, Action, IoClass, Scheduling};
+use crate::time_wait::{self, Lingering};
+use crate::traffic::{self, TrafficMonitor};
+use crate::tunnels::{self, Tunnel};
+use crate::watchlist::{self, WatchEntry, WatchMonitor, WatchStore};
+use crate::workspace::{self, Workspace};
+use crate::zombie;
 
 // ─────────────────────────────────────────────────────────────
 // Estado del tray con filtros y paginación
@@ -28,15 +86,60 @@ pub struct PortSlayerTray {
     ports: Arc<Mutex<Vec<port_scanner::PortInfo>>>,
     /// Filtro de protocolo activo (Todos, TCP, UDP)
     protocol_filter: ProtocolFilter,
+    /// Filtro de rango de puertos activo
+    range_filter: RangeFilter,
+    /// Filtro por usuario dueño de los sockets
+    owner_filter: OwnerFilter,
+    /// Filtro por alcance (solo loopback o expuestos a la red)
+    scope_filter: ScopeFilter,
     /// Página actual (base 0) de la vista paginada
     current_page: usize,
     /// Cantidad de puertos a mostrar por página
     page_size: usize,
+    /// Etiquetas del usuario (recargadas en cada actualización)
+    tags: TagStore,
+    /// Filtro por color de etiqueta (`None` = sin filtro)
+    tag_filter: Option<TagColor>,
+    /// Configuración del usuario (recargada en cada actualización)
+    config: Config,
+    /// Puertos reservados (recargados en cada actualización)
+    reservations: ReservationStore,
+    /// Puertos fijados al principio del menú (recargados en cada actualización)
+    pins: PinStore,
+    /// Efectividad de kills anteriores, para proponer detener el padre
+    kill_stats: KillStats,
+    /// Conexiones TCP establecidas, para estimar el impacto de un kill
+    connections: Vec<Connection>,
+    /// Procesos cerrados que se pueden relanzar
+    restartable: Vec<ProcessSnapshot>,
+    /// Puertos bloqueados en el firewall (recargados en cada actualización)
+    blocks: BlockStore,
+    /// Puertos vigilados desde el tray (recargados en cada actualización)
+    watches: WatchStore,
+    /// Puertos sin nadie escuchando retenidos en TIME_WAIT (recargados en
+    /// cada actualización)
+    lingering: Vec<Lingering>,
+    /// Manifiesto `.portslayer.toml` del directorio desde el que se lanzó
+    /// el tray (recargado en cada actualización)
+    manifest: Option<(PathBuf, Manifest)>,
+    /// Reglas automáticas habilitadas (compartido con el hilo de escaneo)
+    automation: Arc<AtomicBool>,
+    /// Segundos entre actualizaciones automáticas; 0 = solo manual
+    /// (compartido con el hilo de escaneo)
+    scan_interval: Arc<AtomicU64>,
+    /// Actualización automática en pausa, para que el menú no cambie
+    /// mientras se navega (compartido con el hilo de escaneo)
+    paused: Arc<AtomicBool>,
+    /// Ícono XEmbed en uso mientras no haya host de StatusNotifierItem
+    fallback: Arc<Mutex<Option<FallbackTray>>>,
 }
 
 /// Tamaño de página por defecto al iniciar la aplicación
 const DEFAULT_PAGE_SIZE: usize = 10;
 
+/// Conexiones listadas como máximo en "✂️ Cortar una conexión"
+const MAX_DROP_ITEMS: usize = 20;
+
 impl PortSlayerTray {
     /// Crea una nueva instancia del tray con escaneo inicial.
     ///
@@ -45,18 +148,40 @@ impl PortSlayerTray {
     pub fn new() -> Self {
         let ports = port_scanner::scan_open_ports();
         log::info!("Escaneo inicial: {} puertos detectados", ports.len());
+        let config = Config::load();
+        let lingering = time_wait::held_ports(&ports);
         Self {
             ports: Arc::new(Mutex::new(ports)),
             protocol_filter: ProtocolFilter::All,
+            range_filter: RangeFilter::All,
+            owner_filter: OwnerFilter::All,
+            scope_filter: ScopeFilter::All,
             current_page: 0,
             page_size: DEFAULT_PAGE_SIZE,
+            tags: TagStore::load(),
+            tag_filter: None,
+            scan_interval: Arc::new(AtomicU64::new(config.scan_interval())),
+            config,
+            reservations: ReservationStore::load(),
+            pins: PinStore::load(),
+            kill_stats: KillStats::load(),
+            connections: connections::scan_established(),
+            restartable: restart::load_snapshots(),
+            blocks: BlockStore::load(),
+            watches: WatchStore::load(),
+            lingering,
+            manifest: discover_manifest(),
+            // Las reglas no actúan sobre una sesión reproducida
+            automation: Arc::new(AtomicBool::new(!replay::is_active())),
+            paused: Arc::new(AtomicBool::new(false)),
+            fallback: Arc::new(Mutex::new(None)),
         }
     }
 
     /// Obtiene una referencia compartida a la lista de puertos.
     ///
     /// Se usa para compartir el estado con el hilo de actualización
-    /// automática que refresca los puertos cada 10 segundos.
+    /// automática que refresca los puertos periódicamente.
     pub fn ports_handle(&self) -> Arc<Mutex<Vec<port_scanner::PortInfo>>> {
         Arc::clone(&self.ports)
     }
@@ -71,20 +196,484 @@ impl PortSlayerTray {
         if let Ok(mut ports) = self.ports.lock() {
             *ports = new_ports;
         }
+        self.tags = TagStore::load();
+        self.config = Config::load();
+        self.reservations = ReservationStore::load();
+        self.pins = PinStore::load();
+        self.kill_stats = KillStats::load();
+        self.connections = connections::scan_established();
+        self.restartable = restart::load_snapshots();
+        self.blocks = BlockStore::load();
+        self.manifest = discover_manifest();
+        self.lingering = match self.ports.lock() {
+            Ok(ports) => time_wait::held_ports(&ports),
+            Err(_) => Vec::new(),
+        };
+        self.watches = WatchStore::load();
+        firewall::rules();
+        firewall_manager::policy();
+        docker::bindings();
+        kubernetes::pods();
+        if !replay::is_active() {
+            if let Ok(ports) = self.ports.lock() {
+                if self.config.service_fingerprints {
+                    fingerprint::request(&ports);
+                }
+                if self.config.http_health {
+                    health::request(&ports);
+                }
+            }
+        }
         // Resetear a la primera página tras actualizar
         self.current_page = 0;
     }
 
-    /// Obtiene los puertos filtrados según el filtro de protocolo activo.
+    /// Obtiene los puertos filtrados según los filtros de protocolo, rango,
+    /// usuario, alcance y etiqueta.
+    ///
+    /// Los puertos fijados y los túneles se excluyen: ya aparecen en su
+    /// propia sección.
     ///
     /// # Returns
-    /// Vector con los puertos que coinciden con el filtro actual.
+    /// Vector con los puertos que coinciden con los filtros actuales.
     fn get_filtered_ports(&self) -> Vec<port_scanner::PortInfo> {
+        let tunnels = self.tunnels();
+        let is_tunnel = |p: &port_scanner::PortInfo| {
+            tunnels
+                .iter()
+                .any(|t| t.port.pid == p.pid && t.port.port == p.port)
+        };
+        let current_ports: Vec<port_scanner::PortInfo> = match self.ports.lock() {
+            Ok(ports) => ports
+                .iter()
+                .filter(|p| !self.pins.is_pinned(p.port) && !is_tunnel(p))
+                .cloned()
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        let by_protocol = port_scanner::filter_ports(
+            &current_ports,
+            self.protocol_filter,
+            self.range_filter,
+            StateFilter::All,
+        );
+        let by_owner: Vec<port_scanner::PortInfo> = by_protocol
+            .into_iter()
+            .filter(|p| self.owner_filter.matches(p.uid) && self.scope_filter.matches(p))
+            .collect();
+        let by_color = tags::filter_by_color(&by_owner, &self.tags, self.tag_filter);
+        match self.config.active_workspace() {
+            Some((_, ws)) => ws.apply(&by_color),
+            None => by_color,
+        }
+    }
+
+    /// Construye el item de un puerto con sus marcas (favorito, reserva,
+    /// impacto y propuesta de detener el padre) y, si hay firewall, la
+    /// acción de bloquearlo o desbloquearlo (🚫 si está bloqueado), su
+    /// estado en ufw o firewalld, el servicio identificado por su banner y
+    /// su certificado TLS (con aviso si está caducado o es autofirmado y
+    /// el puerto es accesible desde la red). Los puertos publicados por
+    /// Docker muestran su contenedor y ofrecen copiar `docker logs` (ver
+    /// `docker`), y los de un pod de Kubernetes llevan su nombre (ver
+    /// `kubernetes`). Al final, la acción de vigilarlo (👁 si se vigila, ver
+    /// `watchlist`).
+    fn port_item(
+        &self,
+        port_info: &port_scanner::PortInfo,
+        workspace: Option<&Workspace>,
+        pinned: bool,
+    ) -> ksni::MenuItem<Self> {
+        let impact = connections::kill_impact(port_info, &self.connections);
+        let favorite = workspace.is_some_and(|ws| ws.is_favorite(port_info));
+        let item = build_port_item(
+            port_info,
+            &self.tags,
+            &impact,
+            favorite,
+            pinned,
+            self.reservations.reservation_for(port_info),
+            self.kill_stats.advice(port_info),
+        );
+
+        let ksni::MenuItem::SubMenu(mut submenu) = item else {
+            return item;
+        };
+        let bindings = docker::cached();
+        let binding = docker::binding_for(port_info, &bindings);
+        let mut details = Vec::new();
+        if let Some(binding) = binding {
+            details.push(binding.describe());
+        }
+        if let Some(service) = fingerprint::get(port_info) {
+            details.push(format!("🔎 {}", service));
+        }
+        if let Some(certificate) = fingerprint::certificate(port_info) {
+            let expiry = certificate.expiry_date();
+            details.push(trf(
+                Text::CertificateItem,
+                &[&certificate.common_name(), &expiry],
+            ));
+            if !port_info.is_loopback() {
+                if certificate.expired(history::unix_now()) {
+                    details.push(trf(Text::CertificateExpired, &[&expiry]));
+                }
+                if certificate.self_signed() {
+                    details.push(tr(Text::CertificateSelfSigned).to_string());
+                }
+            }
+        }
+        for (index, label) in details.into_iter().enumerate() {
+            let item = StandardItem {
+                label,
+                enabled: false,
+                ..Default::default()
+            };
+            submenu.submenu.insert(index, item.into());
+        }
+        if let Some(pod) = kubernetes::pod_for(port_info, &kubernetes::cached()) {
+            submenu.label = format!("{} ☸ {}", submenu.label, pod.full_name());
+        }
+        if let Some(sandbox) = sandbox::detect(port_info.pid) {
+            submenu.label = format!("{} {}", submenu.label, sandbox.label());
+        }
+        if let Some(defunct) = zombie::detect(port_info.pid) {
+            submenu.label = format!("{} {}", submenu.label, defunct.label());
+        }
+        if let Ok((parent, name)) = zombie::reaping_parent(port_info.pid) {
+            submenu.submenu.push(ksni::MenuItem::Separator);
+            submenu
+                .submenu
+                .push(build_kill_parent_submenu(port_info.pid, parent, name));
+        }
+        if let Some(project) = project::project_for(port_info.pid) {
+            submenu.label = format!("{} 📁 {}", submenu.label, project);
+        }
+        if let Some(health) = health::get(port_info) {
+            submenu.label = format!("{} {}", submenu.label, health.badge());
+        }
+        let blocked = self.blocks.is_blocked(port_info.port, &port_info.protocol);
+        if blocked {
+            submenu.label = format!("🚫 {}", submenu.label);
+        }
+        if capabilities::get_capabilities().firewall {
+            submenu.submenu.push(ksni::MenuItem::Separator);
+            submenu.submenu.push(build_block_item(
+                port_info.port,
+                port_info.protocol.clone(),
+                blocked,
+            ));
+        }
+        let exposed = port_info.netns.is_none() && !port_info.is_loopback();
+        if let Some(policy) = firewall_manager::cached().filter(|_| exposed) {
+            submenu.submenu.push(build_manager_item(
+                port_info.port,
+                port_info.protocol.clone(),
+                policy.manager.name(),
+                policy.allows(&port_info.protocol, port_info.port),
+            ));
+        }
+        if let Some(binding) = binding.filter(|_| capabilities::get_capabilities().clipboard) {
+            submenu.submenu.push(ksni::MenuItem::Separator);
+            submenu.submenu.push(build_docker_logs_item(binding));
+        }
+        let in_config = self.config.watch.iter().any(|e| e.port == port_info.port);
+        let watched = in_config || self.watches.is_watched(port_info.port);
+        if watched {
+            submenu.label = format!("👁 {}", submenu.label);
+        }
+        let clients = connections::clients_of(port_info, &self.connections);
+        if !clients.is_empty() {
+            submenu.submenu.push(build_drop_submenu(&clients));
+        }
+        submenu.submenu.push(ksni::MenuItem::Separator);
+        submenu
+            .submenu
+            .push(build_watch_item(port_info.port, in_config, watched));
+        submenu.into()
+    }
+
+    /// Items de la sección de puertos fijados: cada proceso que escucha en
+    /// ellos o, si no hay ninguno, una línea indicando que está cerrado.
+    fn pinned_items(&self, workspace: Option<&Workspace>) -> Vec<ksni::MenuItem<Self>> {
+        let current_ports = match self.ports.lock() {
+            Ok(ports) => ports.clone(),
+            Err(_) => Vec::new(),
+        };
+        let mut items = Vec::new();
+        for status in self.pins.status(&current_ports) {
+            if status.open {
+                for port_info in &status.listeners {
+                    items.push(self.port_item(port_info, workspace, true));
+                }
+            } else {
+                items.push(build_closed_pin_item(status.port));
+            }
+        }
+        items
+    }
+
+    /// Túneles (`ssh -L`, `socat`) entre los puertos abiertos; los fijados
+    /// se quedan en su sección.
+    fn tunnels(&self) -> Vec<Tunnel> {
+        let current_ports = match self.ports.lock() {
+            Ok(ports) => ports.clone(),
+            Err(_) => Vec::new(),
+        };
+        tunnels::detect(&current_ports)
+            .into_iter()
+            .filter(|t| !self.pins.is_pinned(t.port.port))
+            .collect()
+    }
+
+    /// UIDs de los usuarios (salvo el actual y root) con puertos abiertos,
+    /// para el filtro por usuario.
+    fn owners(&self) -> Vec<u32> {
+        let me = nix::unistd::Uid::current().as_raw();
+        let mut uids: Vec<u32> = match self.ports.lock() {
+            Ok(ports) => ports.iter().filter_map(|p| p.uid).collect(),
+            Err(_) => Vec::new(),
+        };
+        uids.retain(|uid| *uid != me && *uid != 0);
+        uids.sort();
+        uids.dedup();
+        uids
+    }
+
+    /// Número de puertos abiertos: (total, TCP, UDP), sin filtros.
+    fn port_count(&self) -> (usize, usize, usize) {
+        match self.ports.lock() {
+            Ok(ports) => {
+                let tcp = ports.iter().filter(|p| p.protocol == "tcp").count();
+                (ports.len(), tcp, ports.len() - tcp)
+            }
+            Err(_) => (0, 0, 0),
+        }
+    }
+
+    /// Texto del tooltip: puertos abiertos y avisos de seguridad.
+    fn tooltip_description(&self) -> String {
+        let (total, tcp, udp) = self.port_count();
+        let mut description = trf(Text::TrayTooltip, &[&total, &tcp, &udp]);
+        let alerts = self.security_alerts();
+        if alerts.intruders > 0 {
+            description.push('\n');
+            description.push_str(&trf(Text::IntrudersAlert, &[&alerts.intruders]));
+        }
+        if alerts.exposed > 0 {
+            description.push('\n');
+            description.push_str(&trf(Text::ExposedAlert, &[&alerts.exposed]));
+        }
+        description
+    }
+
+    /// Lanza el ícono XEmbed alternativo si no está ya en marcha.
+    fn start_fallback(&self) {
+        let Ok(mut fallback) = self.fallback.lock() else {
+            return;
+        };
+        if fallback.is_some() {
+            return;
+        }
+        match FallbackTray::start(&self.icon_name()) {
+            Ok(started) => *fallback = Some(started),
+            Err(e) => {
+                log::error!(
+                    "{}. Instala yad o la extensión AppIndicator de GNOME para ver el tray",
+                    e
+                );
+                return;
+            }
+        }
+        drop(fallback);
+        self.sync_fallback();
+    }
+
+    /// Envía el estado actual al ícono XEmbed, si está en uso.
+    fn sync_fallback(&self) {
+        let Ok(mut fallback) = self.fallback.lock() else {
+            return;
+        };
+        if let Some(fallback) = fallback.as_mut() {
+            fallback.update(
+                &self.icon_name(),
+                &self.tooltip_description(),
+                &self.fallback_items(),
+            );
+        }
+    }
+
+    /// Menú reducido del ícono XEmbed: cerrar cada puerto visible (los
+    /// fijados primero), cerrar todos y salir. Las acciones ejecutan el CLI.
+    fn fallback_items(&self) -> Vec<FallbackItem> {
+        let exe = std::env::current_exe()
+            .map(|path| path.display().to_string())
+            .unwrap_or_else(|_| "portslayer".into());
         let current_ports = match self.ports.lock() {
             Ok(ports) => ports.clone(),
             Err(_) => Vec::new(),
         };
-        port_scanner::filter_ports(&current_ports, self.protocol_filter)
+        let pinned = self
+            .pins
+            .status(&current_ports)
+            .into_iter()
+            .flat_map(|status| status.listeners);
+        let mut items: Vec<FallbackItem> = pinned
+            .chain(self.get_filtered_ports())
+            .filter(|p| !self.tags.is_protected(p))
+            .take(self.page_size)
+            .map(|p| FallbackItem {
+                label: format!("{} {}", tr(Text::Kill), p),
+                command: vec![
+                    exe.clone(),
+                    "kill".into(),
+                    format!(":{}", p.port),
+                    "--protocol".into(),
+                    p.protocol.clone(),
+                ],
+            })
+            .collect();
+        if !current_ports.is_empty() {
+            items.push(FallbackItem {
+                label: trf(Text::KillAll, &[&current_ports.len()]),
+                command: vec![exe, "killall".into()],
+            });
+        }
+        items.push(FallbackItem {
+            label: tr(Text::Exit).into(),
+            command: Vec::new(),
+        });
+        items
+    }
+
+    /// Avisos de seguridad del último escaneo (ver `reservations`).
+    fn security_alerts(&self) -> SecurityAlerts {
+        match self.ports.lock() {
+            Ok(ports) => self.reservations.security_alerts(&ports),
+            Err(_) => SecurityAlerts::default(),
+        }
+    }
+
+    /// Construye el submenu "🎯 Perfiles" con un elemento por perfil de
+    /// kill: "⚔️ frontend: 3000, 5173 (1 abiertos)" cierra los procesos
+    /// de todos sus puertos (salvo los marcados "no matar").
+    fn build_profiles_submenu(&self) -> ksni::MenuItem<PortSlayerTray> {
+        let ports = self.ports.lock().map(|p| p.clone()).unwrap_or_default();
+        let protected = self.protected_pids();
+        let submenu = self
+            .config
+            .profiles
+            .iter()
+            .map(|(name, profile_ports)| {
+                let open: Vec<port_scanner::PortInfo> = ports
+                    .iter()
+                    .filter(|p| profile_ports.contains(&p.port))
+                    .cloned()
+                    .collect();
+                let port_list: Vec<String> = profile_ports.iter().map(u16::to_string).collect();
+                let label = trf(
+                    Text::KillProfile,
+                    &[name, &port_list.join(", "), &open.len()],
+                );
+                let name = name.clone();
+                let protected = protected.clone();
+                StandardItem {
+                    label,
+                    enabled: !open.is_empty(),
+                    activate: Box::new(move |tray: &mut PortSlayerTray| {
+                        match port_scanner::kill_port_processes(&open, &protected) {
+                            Ok(count) => {
+                                log::info!("Perfil {}: {} procesos terminados", name, count)
+                            }
+                            Err(e) => log::error!("Error al cerrar el perfil {}: {}", name, e),
+                        }
+                        tray.refresh_ports();
+                    }),
+                    ..Default::default()
+                }
+                .into()
+            })
+            .collect();
+        SubMenu {
+            label: tr(Text::KillProfiles).into(),
+            submenu,
+            ..Default::default()
+        }
+        .into()
+    }
+
+    /// Construye el submenu del proyecto del manifiesto: "📋 tienda-web:
+    /// 2/3 puertos libres", con quién ocupa cada puerto y la opción de
+    /// liberarlos todos (salvo los marcados "no matar").
+    fn build_manifest_submenu(
+        &self,
+        path: &Path,
+        manifest: &Manifest,
+    ) -> ksni::MenuItem<PortSlayerTray> {
+        let ports = self.ports.lock().map(|p| p.clone()).unwrap_or_default();
+        let status = manifest.status(&ports);
+        let free = status.iter().filter(|s| s.owners.is_empty()).count();
+        let busy: Vec<port_scanner::PortInfo> = status
+            .iter()
+            .flat_map(|s| s.owners.iter().map(|p| (*p).clone()))
+            .collect();
+
+        let mut submenu: Vec<ksni::MenuItem<PortSlayerTray>> = status
+            .iter()
+            .map(|s| {
+                let label = match s.owners.first() {
+                    None => trf(Text::ManifestPortFree, &[&s.service, &s.port]),
+                    Some(owner) => trf(
+                        Text::ManifestPortBusy,
+                        &[&s.service, &s.port, &owner.process_name, &owner.pid],
+                    ),
+                };
+                StandardItem {
+                    label,
+                    enabled: false,
+                    ..Default::default()
+                }
+                .into()
+            })
+            .collect();
+        let protected = self.protected_pids();
+        submenu.push(
+            StandardItem {
+                label: tr(Text::ManifestKill).into(),
+                enabled: !busy.is_empty(),
+                activate: Box::new(move |tray: &mut PortSlayerTray| {
+                    match port_scanner::kill_port_processes(&busy, &protected) {
+                        Ok(count) => log::info!("{} procesos del proyecto terminados", count),
+                        Err(e) => log::error!("Error al liberar los puertos del proyecto: {}", e),
+                    }
+                    tray.refresh_ports();
+                }),
+                ..Default::default()
+            }
+            .into(),
+        );
+
+        SubMenu {
+            label: trf(
+                Text::ManifestProject,
+                &[&manifest.display_name(path), &free, &status.len()],
+            ),
+            submenu,
+            ..Default::default()
+        }
+        .into()
+    }
+
+    /// PIDs de los puertos marcados como "no matar" por alguna etiqueta.
+    fn protected_pids(&self) -> Vec<u32> {
+        match self.ports.lock() {
+            Ok(ports) => ports
+                .iter()
+                .filter(|p| p.pid > 0 && self.tags.is_protected(p))
+                .map(|p| p.pid)
+                .collect(),
+            Err(_) => Vec::new(),
+        }
     }
 }
 
@@ -93,14 +682,51 @@ impl PortSlayerTray {
 // ─────────────────────────────────────────────────────────────
 
 impl Tray for PortSlayerTray {
-    /// Ícono del system tray (usa tema de íconos del sistema).
+    /// Ícono del system tray (usa tema de íconos del sistema): el de
+    /// aviso si un puerto reservado está ocupado por otro proceso o hay
+    /// puertos TCP inesperados escuchando en todas las interfaces. Los
+    /// nombres dependen del tema de íconos (ver `theme`).
     fn icon_name(&self) -> String {
-        "network-server".into()
+        let palette = theme::palette();
+        if self.security_alerts().any() {
+            palette.warning_icon.into()
+        } else {
+            palette.normal_icon.into()
+        }
     }
 
-    /// Tooltip que aparece al pasar el ratón sobre el ícono.
+    /// Contador de puertos superpuesto al ícono (si el panel lo dibuja).
+    fn overlay_icon_pixmap(&self) -> Vec<ksni::Icon> {
+        badge::overlay(self.port_count().0)
+    }
+
+    /// Título del ícono con el número de puertos abiertos.
     fn title(&self) -> String {
-        "PortSlayer ⚔️".into()
+        format!("PortSlayer ⚔️ ({})", self.port_count().0)
+    }
+
+    /// Tooltip que aparece al pasar el ratón sobre el ícono.
+    fn tool_tip(&self) -> ksni::ToolTip {
+        ksni::ToolTip {
+            title: "PortSlayer ⚔️".into(),
+            description: self.tooltip_description(),
+            ..Default::default()
+        }
+    }
+
+    /// Sin host de StatusNotifierItem: se lanza el ícono XEmbed. El
+    /// servicio sigue vivo para registrarse si el host aparece después.
+    fn watcher_offine(&self) -> bool {
+        log::warn!("No hay host de StatusNotifierItem; se usa el tray alternativo");
+        self.start_fallback();
+        true
+    }
+
+    /// El host de StatusNotifierItem está disponible: sobra el respaldo.
+    fn watcher_online(&self) {
+        if let Some(fallback) = self.fallback.lock().ok().and_then(|mut f| f.take()) {
+            fallback.stop();
+        }
     }
 
     /// ID único para el protocolo StatusNotifierItem.
@@ -113,31 +739,151 @@ impl Tray for PortSlayerTray {
     /// Estructura del menú:
     /// ```text
     /// 🔄 Actualizar
+    /// ⏪ Reproducción: sesion.jsonl · 01:30 / 05:00 (solo con `replay`)
+    /// ≈ Escaneo aproximado: ... (solo en modo seguro con datos incompletos)
+    /// 🛠 Mantenimiento: ... (solo si hay ventanas configuradas)
+    /// ♻️ Reiniciar ▸ submenu (solo si hay procesos cerrados)
+    /// 🤖 Automatización: activada (solo si hay reglas)
     /// ──────────
+    /// 🗂 Espacio: [Ninguno|work|...] ▸ submenu (si hay espacios definidos)
     /// 📊 Filtro: [Todos|TCP|UDP] ▸ submenu
+    /// 🏷 Etiqueta: [Todas|🟥 Rojo|...] ▸ submenu
     /// 📋 Por página: [5|10] ▸ submenu
     /// ──────────
+    /// 📌 🔴 TCP 3000 (0.0.0.0) → node [PID 4321] (puertos fijados)
+    /// 📌 ⚪ Puerto 6379: cerrado
+    /// ──────────
+    /// 🚇 TCP 5432 → db:5432 vía bastion (ssh, PID 2211) ▸ Cerrar túnel
+    /// 🚇 🧟 TCP 8080 → TCP:10.0.0.5:80 (socat, PID 3020) (túnel huérfano)
+    /// ──────────
     /// ⚔️ Cerrar Todos (N puertos)
     /// ──────────
-    /// 🔴 TCP 8080 (0.0.0.0) → node [PID 1234]
+    /// 🔴 TCP 8080 (0.0.0.0) → node [PID 1234] 🟦 dev server
     /// 🟡 TCP 5434 (0.0.0.0) → desconocido
+    /// 🔴 TCP 5432 (0.0.0.0) → postgres [PID 880] 🔗 3 ▸ confirmación
     /// ...
     /// ──────────
     /// ◀ Anterior | Página X/Y | ▶ Siguiente
     /// ──────────
+    /// 📜 Abrir log
+    /// 🚀 Iniciar con la sesión ☑
     /// ❌ Salir
     /// ```
     fn menu(&self) -> Vec<ksni::MenuItem<Self>> {
         let mut items: Vec<ksni::MenuItem<Self>> = vec![
             // ── Botón de actualizar ──
             build_refresh_item(),
-            ksni::MenuItem::Separator,
+            // ── Pausa de la actualización automática ──
+            build_pause_item(self.paused.load(Ordering::Relaxed)),
+        ];
+
+        // ── Fuentes de escaneo en cuarentena ──
+        for source in sources::degraded() {
+            items.push(build_degraded_source_item(&source));
+        }
+
+        // ── Sesión grabada en reproducción ──
+        if let Some(status) = replay::status() {
+            items.push(build_replay_item(&status));
+        }
+
+        // ── Escaneo aproximado (modo seguro en hosts enormes) ──
+        if let Some(report) = port_scanner::last_fd_scan().filter(|r| !r.complete()) {
+            items.push(build_approximate_scan_item(&report));
+        }
+
+        // ── Estado de mantenimiento ──
+        if !self.config.maintenance.is_empty() {
+            items.push(build_maintenance_item(&self.config));
+        }
+
+        // ── Procesos cerrados que se pueden relanzar ──
+        if !self.restartable.is_empty() {
+            items.push(build_restart_submenu(&self.restartable));
+        }
+
+        // ── Puertos bloqueados (aunque ya nadie escuche en ellos) ──
+        if !self.blocks.blocks.is_empty() {
+            items.push(build_blocks_submenu(&self.blocks));
+        }
+
+        // ── Interruptor de reglas automáticas ──
+        let rule_count = rules::active_rules(&self.config).len();
+        if rule_count > 0 {
+            let enabled = self.automation.load(Ordering::Relaxed);
+            items.push(build_automation_item(enabled, rule_count));
+        }
+
+        items.push(ksni::MenuItem::Separator);
+
+        // ── Espacio de trabajo (submenu) ──
+        let active_workspace = self.config.active_workspace();
+        if !self.config.workspaces.is_empty() {
+            let names: Vec<String> = self.config.workspaces.keys().cloned().collect();
+            let active_name = active_workspace.as_ref().map(|(name, _)| name.as_str());
+            items.push(build_workspace_submenu(&names, active_name));
+        }
+
+        items.extend([
             // ── Filtro de protocolo (submenu) ──
             build_filter_submenu(self.protocol_filter),
+            // ── Filtro por rango de puertos (submenu) ──
+            build_range_filter_submenu(self.range_filter, self.config.custom_range()),
+            // ── Filtro por usuario (submenu) ──
+            build_owner_filter_submenu(self.owner_filter, &self.owners()),
+            // ── Filtro por alcance (submenu) ──
+            build_scope_filter_submenu(self.scope_filter),
+            // ── Filtro por etiqueta (submenu) ──
+            build_tag_filter_submenu(self.tag_filter),
             // ── Tamaño de página (submenu) ──
             build_page_size_submenu(self.page_size),
+            // ── Intervalo de actualización (submenu) ──
+            build_interval_submenu(self.scan_interval.load(Ordering::Relaxed)),
             ksni::MenuItem::Separator,
-        ];
+        ]);
+
+        // ── Puertos fijados (sin filtros ni paginación) ──
+        let workspace = active_workspace.as_ref().map(|(_, ws)| *ws);
+        if !self.pins.ports.is_empty() {
+            items.extend(self.pinned_items(workspace));
+            items.push(ksni::MenuItem::Separator);
+        }
+
+        // ── Túneles (ssh -L, socat) ──
+        let tunnels = self.tunnels();
+        if !tunnels.is_empty() {
+            items.extend(tunnels.iter().map(build_tunnel_item));
+            items.push(ksni::MenuItem::Separator);
+        }
+
+        // ── Proyectos de Docker Compose ──
+        let projects = docker::compose_projects(&docker::cached());
+        if !projects.is_empty() {
+            items.extend(
+                projects
+                    .iter()
+                    .map(|(project, ports)| build_compose_submenu(project, ports)),
+            );
+            items.push(ksni::MenuItem::Separator);
+        }
+
+        // ── Puertos retenidos en TIME_WAIT ──
+        if !self.lingering.is_empty() {
+            items.extend(self.lingering.iter().map(build_time_wait_submenu));
+            items.push(ksni::MenuItem::Separator);
+        }
+
+        // ── Perfiles de kill ([profiles] de config.toml) ──
+        if !self.config.profiles.is_empty() {
+            items.push(self.build_profiles_submenu());
+            items.push(ksni::MenuItem::Separator);
+        }
+
+        // ── Puertos del proyecto (.portslayer.toml) ──
+        if let Some((path, manifest)) = &self.manifest {
+            items.push(self.build_manifest_submenu(path, manifest));
+            items.push(ksni::MenuItem::Separator);
+        }
 
         // ── Obtener puertos filtrados y paginados ──
         let filtered_ports = self.get_filtered_ports();
@@ -153,7 +899,7 @@ impl Tray for PortSlayerTray {
             items.push(build_empty_message());
         } else {
             // ── Botón cerrar todos ──
-            items.push(build_kill_all_item(total));
+            items.push(build_kill_all_item(total, self.protected_pids()));
             items.push(ksni::MenuItem::Separator);
 
             // ── Encabezado con conteo ──
@@ -161,7 +907,7 @@ impl Tray for PortSlayerTray {
 
             // ── Lista de puertos de la página actual ──
             for port_info in &page_ports {
-                items.push(build_port_item(port_info));
+                items.push(self.port_item(port_info, workspace, false));
             }
         }
 
@@ -172,9 +918,11 @@ impl Tray for PortSlayerTray {
             items.extend(nav_items);
         }
 
-        // ── Botón salir ──
+        // ── Log, inicio con la sesión y botón salir ──
         items.push(ksni::MenuItem::Separator);
-        items.push(build_exit_item());
+        items.push(build_open_log_item());
+        items.push(build_autostart_item(autostart::is_enabled()));
+        items.push(build_exit_item(&shutdown::summary()));
 
         items
     }
@@ -187,7 +935,7 @@ impl Tray for PortSlayerTray {
 /// Construye el item "🔄 Actualizar" del menú.
 fn build_refresh_item() -> ksni::MenuItem<PortSlayerTray> {
     StandardItem {
-        label: "🔄 Actualizar".into(),
+        label: tr(Text::Refresh).into(),
         activate: Box::new(|tray: &mut PortSlayerTray| {
             tray.refresh_ports();
         }),
@@ -196,39 +944,143 @@ fn build_refresh_item() -> ksni::MenuItem<PortSlayerTray> {
     .into()
 }
 
-/// Construye el submenu de filtro de protocolo.
+/// Construye la casilla "⏸ Pausar actualización".
 ///
-/// Muestra el filtro activo con un indicador ● y permite cambiar
-/// entre Todos, TCP y UDP.
+/// Mientras está marcada, el hilo de escaneo no toca el estado ni el
+/// menú; "🔄 Actualizar" sigue funcionando a mano.
 ///
 /// # Arguments
-/// * `current_filter` - Filtro actualmente activo
-fn build_filter_submenu(current_filter: ProtocolFilter) -> ksni::MenuItem<PortSlayerTray> {
-    // Construir las opciones del filtro con indicador visual
-    let filters = [
-        ProtocolFilter::All,
-        ProtocolFilter::Tcp,
-        ProtocolFilter::Udp,
-    ];
+/// * `paused` - Si la actualización automática está en pausa
+fn build_pause_item(paused: bool) -> ksni::MenuItem<PortSlayerTray> {
+    CheckmarkItem {
+        label: tr(Text::PauseRefresh).into(),
+        checked: paused,
+        activate: Box::new(move |tray: &mut PortSlayerTray| {
+            tray.paused.store(!paused, Ordering::Relaxed);
+            log::info!(
+                "Actualización automática {}",
+                if paused { "reanudada" } else { "en pausa" }
+            );
+        }),
+        ..Default::default()
+    }
+    .into()
+}
 
-    let submenu_items: Vec<ksni::MenuItem<PortSlayerTray>> = filters
+/// Construye el aviso (no clickeable) de la reproducción de una sesión.
+fn build_replay_item(status: &str) -> ksni::MenuItem<PortSlayerTray> {
+    StandardItem {
+        label: trf(Text::ReplayActive, &[&status]),
+        enabled: false,
+        ..Default::default()
+    }
+    .into()
+}
+
+/// Construye el aviso (no clickeable) de un escaneo en modo seguro que no
+/// leyó todos los descriptores: los puertos sin PID llevan "≈".
+fn build_approximate_scan_item(report: &FdScanReport) -> ksni::MenuItem<PortSlayerTray> {
+    StandardItem {
+        label: trf(
+            Text::ApproximateScan,
+            &[
+                &(report.sampled + report.skipped),
+                &(report.processes + report.skipped),
+            ],
+        ),
+        enabled: false,
+        ..Default::default()
+    }
+    .into()
+}
+
+/// Construye el aviso (no clickeable) de una fuente de escaneo en cuarentena.
+fn build_degraded_source_item(source: &str) -> ksni::MenuItem<PortSlayerTray> {
+    StandardItem {
+        label: trf(Text::SourceDegraded, &[&source]),
+        enabled: false,
+        ..Default::default()
+    }
+    .into()
+}
+
+/// Construye el indicador (no clickeable) de ventana de mantenimiento.
+///
+/// Muestra la ventana activa y su hora de fin, o "inactivo".
+///
+/// # Arguments
+/// * `config` - Configuración con las ventanas definidas
+fn build_maintenance_item(config: &Config) -> ksni::MenuItem<PortSlayerTray> {
+    let label = match maintenance::active_window(&config.maintenance, maintenance::local_now()) {
+        Some((window, end)) => trf(
+            Text::MaintenanceActive,
+            &[
+                &window.name,
+                &format!("{:02}:{:02}", end.hour(), end.minute()),
+            ],
+        ),
+        None => tr(Text::MaintenanceInactive).to_string(),
+    };
+
+    StandardItem {
+        label,
+        enabled: false,
+        ..Default::default()
+    }
+    .into()
+}
+
+/// Construye el interruptor de las reglas automáticas.
+///
+/// # Arguments
+/// * `enabled` - Si las reglas se están evaluando
+/// * `rule_count` - Reglas vigentes (globales, espacio activo y reservas)
+fn build_automation_item(enabled: bool, rule_count: usize) -> ksni::MenuItem<PortSlayerTray> {
+    let text = if enabled {
+        Text::AutomationEnabled
+    } else {
+        Text::AutomationDisabled
+    };
+    StandardItem {
+        label: trf(text, &[&rule_count]),
+        activate: Box::new(move |tray: &mut PortSlayerTray| {
+            tray.automation.store(!enabled, Ordering::Relaxed);
+            log::info!(
+                "Automatización {}",
+                if enabled { "desactivada" } else { "activada" }
+            );
+        }),
+        ..Default::default()
+    }
+    .into()
+}
+
+/// Construye el submenu "♻️ Reiniciar" con los procesos cerrados.
+///
+/// Cada opción relanza el comando capturado antes del kill, con su
+/// directorio y entorno, en cuanto el puerto queda libre.
+///
+/// # Arguments
+/// * `snapshots` - Capturas guardadas (las más recientes primero)
+fn build_restart_submenu(snapshots: &[ProcessSnapshot]) -> ksni::MenuItem<PortSlayerTray> {
+    let submenu_items: Vec<ksni::MenuItem<PortSlayerTray>> = snapshots
         .iter()
-        .map(|&filter| {
-            // Indicador visual: ● para el filtro activo, ○ para los demás
-            let indicator = if filter == current_filter {
-                "●"
+        .enumerate()
+        .map(|(index, snapshot)| {
+            let label = if snapshot.port > 0 {
+                format!("▶ {} :{}", snapshot.process_name, snapshot.port)
             } else {
-                "○"
+                format!("▶ {}", snapshot.process_name)
             };
-            let label = format!("{} {}", indicator, filter.label());
 
             StandardItem {
                 label,
                 activate: Box::new(move |tray: &mut PortSlayerTray| {
-                    log::info!("Filtro cambiado a: {}", filter.label());
-                    tray.protocol_filter = filter;
-                    // Resetear a página 0 al cambiar filtro
-                    tray.current_page = 0;
+                    match restart::restart(index) {
+                        Ok(pid) => log::info!("Proceso relanzado con PID {}", pid),
+                        Err(e) => log::error!("Error relanzando proceso: {}", e),
+                    }
+                    tray.refresh_ports();
                 }),
                 ..Default::default()
             }
@@ -237,34 +1089,25 @@ fn build_filter_submenu(current_filter: ProtocolFilter) -> ksni::MenuItem<PortSl
         .collect();
 
     SubMenu {
-        label: format!("📊 Filtro: {}", current_filter.label()),
+        label: tr(Text::RestartMenu).into(),
         submenu: submenu_items,
         ..Default::default()
     }
     .into()
 }
 
-/// Construye el submenu de tamaño de página.
-///
-/// Permite seleccionar entre 5 y 10 puertos por página.
-///
-/// # Arguments
-/// * `current_size` - Tamaño de página actual
-fn build_page_size_submenu(current_size: usize) -> ksni::MenuItem<PortSlayerTray> {
-    let sizes: Vec<usize> = vec![5, 10];
-
-    let submenu_items: Vec<ksni::MenuItem<PortSlayerTray>> = sizes
+/// Construye el submenu de puertos bloqueados en el firewall, con la
+/// opción de desbloquear cada uno.
+fn build_blocks_submenu(store: &BlockStore) -> ksni::MenuItem<PortSlayerTray> {
+    let submenu_items: Vec<ksni::MenuItem<PortSlayerTray>> = store
+        .blocks
         .iter()
-        .map(|&size| {
-            let indicator = if size == current_size { "●" } else { "○" };
-            let label = format!("{} {} puertos", indicator, size);
-
+        .map(|block| {
+            let (port, protocol) = (block.port, block.protocol.clone());
             StandardItem {
-                label,
+                label: trf(Text::UnblockItem, &[&port, &protocol.to_uppercase()]),
                 activate: Box::new(move |tray: &mut PortSlayerTray| {
-                    log::info!("Tamaño de página cambiado a: {}", size);
-                    tray.page_size = size;
-                    tray.current_page = 0;
+                    toggle_block(tray, port, &protocol, true);
                 }),
                 ..Default::default()
             }
@@ -273,116 +1116,1339 @@ fn build_page_size_submenu(current_size: usize) -> ksni::MenuItem<PortSlayerTray
         .collect();
 
     SubMenu {
-        label: format!("📋 Por página: {}", current_size),
+        label: trf(Text::BlocksMenu, &[&store.blocks.len()]),
         submenu: submenu_items,
         ..Default::default()
     }
     .into()
 }
 
+/// Construye la acción de bloquear (o desbloquear) un puerto en el
+/// firewall sin cerrar su proceso.
+fn build_block_item(port: u16, protocol: String, blocked: bool) -> ksni::MenuItem<PortSlayerTray> {
+    StandardItem {
+        label: tr(if blocked {
+            Text::UnblockPort
+        } else {
+            Text::BlockPort
+        })
+        .into(),
+        activate: Box::new(move |tray: &mut PortSlayerTray| {
+            toggle_block(tray, port, &protocol, blocked);
+        }),
+        ..Default::default()
+    }
+    .into()
+}
+
+/// Construye la acción de vigilar o dejar de vigilar un puerto; los
+/// vigilados desde la configuración solo lo indican.
+fn build_watch_item(port: u16, in_config: bool, watched: bool) -> ksni::MenuItem<PortSlayerTray> {
+    let label = if in_config {
+        Text::WatchedInConfig
+    } else if watched {
+        Text::Unwatch
+    } else {
+        Text::Watch
+    };
+    StandardItem {
+        label: tr(label).into(),
+        enabled: !in_config,
+        activate: Box::new(move |tray: &mut PortSlayerTray| {
+            toggle_watch(tray, port, watched);
+        }),
+        ..Default::default()
+    }
+    .into()
+}
+
+/// Construye el estado de un puerto en ufw o firewalld, que al hacer
+/// clic cambia su regla (permitir ↔ denegar).
+fn build_manager_item(
+    port: u16,
+    protocol: String,
+    manager: &str,
+    allowed: bool,
+) -> ksni::MenuItem<PortSlayerTray> {
+    StandardItem {
+        label: trf(
+            if allowed {
+                Text::ManagerAllowed
+            } else {
+                Text::ManagerDenied
+            },
+            &[&manager],
+        ),
+        activate: Box::new(move |tray: &mut PortSlayerTray| {
+            if let Err(e) = firewall_manager::set_allowed(port, &protocol, !allowed) {
+                log::error!("{}", e);
+            }
+            tray.refresh_ports();
+        }),
+        ..Default::default()
+    }
+    .into()
+}
+
+/// Construye el submenu de espacios de trabajo.
+///
+/// Incluye la opción "Ninguno" que desactiva el espacio actual.
+///
+/// # Arguments
+/// * `names` - Espacios definidos en la configuración
+/// * `active` - Espacio activo (`None` = ninguno)
+fn build_workspace_submenu(
+    names: &[String],
+    active: Option<&str>,
+) -> ksni::MenuItem<PortSlayerTray> {
+    let options: Vec<Option<String>> = std::iter::once(None)
+        .chain(names.iter().cloned().map(Some))
+        .collect();
+
+    let submenu_items: Vec<ksni::MenuItem<PortSlayerTray>> = options
+        .into_iter()
+        .map(|option| {
+            let indicator = if option.as_deref() == active {
+                "●"
+            } else {
+                "○"
+            };
+            let name = option.as_deref().unwrap_or(tr(Text::WorkspaceNone));
+            let label = format!("{} {}", indicator, name);
+
+            StandardItem {
+                label,
+                activate: Box::new(move |tray: &mut PortSlayerTray| {
+                    if let Err(e) = workspace::set_active(option.as_deref()) {
+                        log::error!("Error cambiando de espacio de trabajo: {}", e);
+                    }
+                    tray.current_page = 0;
+                }),
+                ..Default::default()
+            }
+            .into()
+        })
+        .collect();
+
+    SubMenu {
+        label: trf(
+            Text::WorkspaceMenu,
+            &[&active.unwrap_or(tr(Text::WorkspaceNone))],
+        ),
+        submenu: submenu_items,
+        ..Default::default()
+    }
+    .into()
+}
+
+/// Construye el submenu de filtro de protocolo.
+///
+/// Muestra el filtro activo con un indicador ● y permite cambiar
+/// entre Todos, TCP y UDP.
+///
+/// # Arguments
+/// * `current_filter` - Filtro actualmente activo
+fn build_filter_submenu(current_filter: ProtocolFilter) -> ksni::MenuItem<PortSlayerTray> {
+    // Construir las opciones del filtro con indicador visual
+    let filters = [
+        ProtocolFilter::All,
+        ProtocolFilter::Tcp,
+        ProtocolFilter::Udp,
+    ];
+
+    let submenu_items: Vec<ksni::MenuItem<PortSlayerTray>> = filters
+        .iter()
+        .map(|&filter| {
+            // Indicador visual: ● para el filtro activo, ○ para los demás
+            let indicator = if filter == current_filter {
+                "●"
+            } else {
+                "○"
+            };
+            let label = format!("{} {}", indicator, protocol_label(filter));
+
+            StandardItem {
+                label,
+                activate: Box::new(move |tray: &mut PortSlayerTray| {
+                    log::info!("Filtro cambiado a: {}", protocol_label(filter));
+                    tray.protocol_filter = filter;
+                    // Resetear a página 0 al cambiar filtro
+                    tray.current_page = 0;
+                }),
+                ..Default::default()
+            }
+            .into()
+        })
+        .collect();
+
+    SubMenu {
+        label: trf(Text::FilterMenu, &[&protocol_label(current_filter)]),
+        submenu: submenu_items,
+        ..Default::default()
+    }
+    .into()
+}
+
+/// Construye el submenu de filtro por rango de puertos.
+///
+/// La opción "Personalizado" usa `custom_range` de `config.toml`; si no
+/// está definido se muestra deshabilitada con una pista.
+///
+/// # Arguments
+/// * `current_filter` - Rango actualmente filtrado
+/// * `custom` - Rango personalizado de la configuración
+fn build_range_filter_submenu(
+    current_filter: RangeFilter,
+    custom: Option<(u16, u16)>,
+) -> ksni::MenuItem<PortSlayerTray> {
+    let mut filters = vec![
+        RangeFilter::All,
+        RangeFilter::WellKnown,
+        RangeFilter::Dev,
+        RangeFilter::Ephemeral,
+    ];
+    if let Some((start, end)) = custom {
+        filters.push(RangeFilter::Custom(start, end));
+    }
+
+    let mut submenu_items: Vec<ksni::MenuItem<PortSlayerTray>> = filters
+        .into_iter()
+        .map(|filter| {
+            let indicator = if filter == current_filter {
+                "●"
+            } else {
+                "○"
+            };
+            StandardItem {
+                label: format!("{} {}", indicator, range_label(filter)),
+                activate: Box::new(move |tray: &mut PortSlayerTray| {
+                    log::info!("Filtro de rango cambiado a: {}", range_label(filter));
+                    tray.range_filter = filter;
+                    tray.current_page = 0;
+                }),
+                ..Default::default()
+            }
+            .into()
+        })
+        .collect();
+
+    if custom.is_none() {
+        submenu_items.push(
+            StandardItem {
+                label: tr(Text::RangeCustomUnset).into(),
+                enabled: false,
+                ..Default::default()
+            }
+            .into(),
+        );
+    }
+
+    SubMenu {
+        label: trf(Text::RangeMenu, &[&range_label(current_filter)]),
+        submenu: submenu_items,
+        ..Default::default()
+    }
+    .into()
+}
+
+/// Construye el submenu de filtro por usuario dueño de los sockets.
+///
+/// Además de "Todos", "Mis procesos" y root ofrece los demás usuarios
+/// con puertos abiertos en el último escaneo.
+///
+/// # Arguments
+/// * `current_filter` - Filtro actualmente activo
+/// * `others` - UIDs de los demás usuarios con puertos abiertos
+fn build_owner_filter_submenu(
+    current_filter: OwnerFilter,
+    others: &[u32],
+) -> ksni::MenuItem<PortSlayerTray> {
+    let filters = [OwnerFilter::All, OwnerFilter::Mine, OwnerFilter::Root]
+        .into_iter()
+        .chain(others.iter().map(|uid| OwnerFilter::User(*uid)));
+
+    let submenu_items: Vec<ksni::MenuItem<PortSlayerTray>> = filters
+        .map(|filter| {
+            let indicator = if filter == current_filter {
+                "●"
+            } else {
+                "○"
+            };
+            StandardItem {
+                label: format!("{} {}", indicator, owner_label(filter)),
+                activate: Box::new(move |tray: &mut PortSlayerTray| {
+                    log::info!("Filtro de usuario cambiado a: {}", owner_label(filter));
+                    tray.owner_filter = filter;
+                    tray.current_page = 0;
+                }),
+                ..Default::default()
+            }
+            .into()
+        })
+        .collect();
+
+    SubMenu {
+        label: trf(Text::OwnerMenu, &[&owner_label(current_filter)]),
+        submenu: submenu_items,
+        ..Default::default()
+    }
+    .into()
+}
+
+/// Construye el submenu de filtro por alcance: los que solo escuchan en
+/// loopback o los accesibles desde la red, para auditar qué se expone.
+///
+/// # Arguments
+/// * `current_filter` - Filtro actualmente activo
+fn build_scope_filter_submenu(current_filter: ScopeFilter) -> ksni::MenuItem<PortSlayerTray> {
+    let filters = [
+        ScopeFilter::All,
+        ScopeFilter::Loopback,
+        ScopeFilter::Exposed,
+    ];
+
+    let submenu_items: Vec<ksni::MenuItem<PortSlayerTray>> = filters
+        .into_iter()
+        .map(|filter| {
+            let indicator = if filter == current_filter {
+                "●"
+            } else {
+                "○"
+            };
+            StandardItem {
+                label: format!("{} {}", indicator, scope_label(filter)),
+                activate: Box::new(move |tray: &mut PortSlayerTray| {
+                    log::info!("Filtro de alcance cambiado a: {}", scope_label(filter));
+                    tray.scope_filter = filter;
+                    tray.current_page = 0;
+                }),
+                ..Default::default()
+            }
+            .into()
+        })
+        .collect();
+
+    SubMenu {
+        label: trf(Text::ScopeMenu, &[&scope_label(current_filter)]),
+        submenu: submenu_items,
+        ..Default::default()
+    }
+    .into()
+}
+
+/// Construye el submenu de filtro por color de etiqueta.
+///
+/// Incluye la opción "Todas" que desactiva el filtro.
+///
+/// # Arguments
+/// * `current_filter` - Color actualmente filtrado (`None` = todas)
+fn build_tag_filter_submenu(current_filter: Option<TagColor>) -> ksni::MenuItem<PortSlayerTray> {
+    let options: Vec<Option<TagColor>> = std::iter::once(None)
+        .chain(TagColor::ALL.into_iter().map(Some))
+        .collect();
+
+    let submenu_items: Vec<ksni::MenuItem<PortSlayerTray>> = options
+        .into_iter()
+        .map(|option| {
+            let indicator = if option == current_filter {
+                "●"
+            } else {
+                "○"
+            };
+            let label = match option {
+                None => format!("{} {}", indicator, tr(Text::TagAll)),
+                Some(color) => format!("{} {} {}", indicator, color.emoji(), color.label()),
+            };
+
+            StandardItem {
+                label,
+                activate: Box::new(move |tray: &mut PortSlayerTray| {
+                    log::info!("Filtro de etiqueta cambiado a: {:?}", option);
+                    tray.tag_filter = option;
+                    tray.current_page = 0;
+                }),
+                ..Default::default()
+            }
+            .into()
+        })
+        .collect();
+
+    let current_label = match current_filter {
+        None => tr(Text::TagAll).to_string(),
+        Some(color) => format!("{} {}", color.emoji(), color.label()),
+    };
+
+    SubMenu {
+        label: trf(Text::TagMenu, &[&current_label]),
+        submenu: submenu_items,
+        ..Default::default()
+    }
+    .into()
+}
+
+/// Construye el submenu de tamaño de página.
+///
+/// Permite seleccionar entre 5 y 10 puertos por página.
+///
+/// # Arguments
+/// * `current_size` - Tamaño de página actual
+fn build_page_size_submenu(current_size: usize) -> ksni::MenuItem<PortSlayerTray> {
+    let sizes: Vec<usize> = vec![5, 10];
+
+    let submenu_items: Vec<ksni::MenuItem<PortSlayerTray>> = sizes
+        .iter()
+        .map(|&size| {
+            let indicator = if size == current_size { "●" } else { "○" };
+            let label = format!("{} {}", indicator, trf(Text::PageSizeOption, &[&size]));
+
+            StandardItem {
+                label,
+                activate: Box::new(move |tray: &mut PortSlayerTray| {
+                    log::info!("Tamaño de página cambiado a: {}", size);
+                    tray.page_size = size;
+                    tray.current_page = 0;
+                }),
+                ..Default::default()
+            }
+            .into()
+        })
+        .collect();
+
+    SubMenu {
+        label: trf(Text::PageSizeMenu, &[&current_size]),
+        submenu: submenu_items,
+        ..Default::default()
+    }
+    .into()
+}
+
+/// Construye el submenu del intervalo de actualización automática.
+///
+/// # Arguments
+/// * `current_secs` - Intervalo activo en segundos (0 = manual)
+fn build_interval_submenu(current_secs: u64) -> ksni::MenuItem<PortSlayerTray> {
+    let intervals: Vec<u64> = vec![2, 5, 10, 30, 0];
+
+    let submenu_items: Vec<ksni::MenuItem<PortSlayerTray>> = intervals
+        .iter()
+        .map(|&secs| {
+            let indicator = if secs == current_secs { "●" } else { "○" };
+            let label = format!("{} {}", indicator, interval_label(secs));
+
+            StandardItem {
+                label,
+                activate: Box::new(move |tray: &mut PortSlayerTray| {
+                    log::info!(
+                        "Intervalo de actualización cambiado a: {}",
+                        interval_label(secs)
+                    );
+                    tray.scan_interval.store(secs, Ordering::Relaxed);
+                }),
+                ..Default::default()
+            }
+            .into()
+        })
+        .collect();
+
+    SubMenu {
+        label: trf(Text::IntervalMenu, &[&interval_label(current_secs)]),
+        submenu: submenu_items,
+        ..Default::default()
+    }
+    .into()
+}
+
+/// Texto de un intervalo de actualización ("10 s" o "manual").
+fn interval_label(secs: u64) -> String {
+    if secs == 0 {
+        tr(Text::IntervalManual).to_string()
+    } else {
+        format!("{} s", secs)
+    }
+}
+
 /// Construye el item mostrado cuando no hay puertos abiertos.
 fn build_empty_message() -> ksni::MenuItem<PortSlayerTray> {
     StandardItem {
-        label: "✅ No hay puertos abiertos".into(),
-        enabled: false,
+        label: tr(Text::NoPorts).into(),
+        enabled: false,
+        ..Default::default()
+    }
+    .into()
+}
+
+/// Construye la línea de un puerto fijado en el que no escucha nadie.
+fn build_closed_pin_item(port: u16) -> ksni::MenuItem<PortSlayerTray> {
+    StandardItem {
+        label: trf(Text::PinnedClosed, &[&port]),
+        enabled: false,
+        ..Default::default()
+    }
+    .into()
+}
+
+/// Construye el item de un túnel: "🚇 TCP 5432 → db:5432 vía bastion
+/// (ssh, PID 2211)", con 🧟 si es huérfano y un submenu para cerrarlo.
+fn build_tunnel_item(tunnel: &Tunnel) -> ksni::MenuItem<PortSlayerTray> {
+    let port_info = &tunnel.port;
+    let target = match &tunnel.via {
+        Some(host) => trf(Text::TunnelVia, &[&tunnel.target, host]),
+        None => tunnel.target.clone(),
+    };
+    let label = trf(
+        Text::TunnelItem,
+        &[
+            &port_info.protocol.to_uppercase(),
+            &port_info.port,
+            &target,
+            &tunnel.kind.label(),
+            &port_info.pid,
+        ],
+    );
+    let label = if tunnel.orphaned {
+        format!("🚇 🧟 {}", label)
+    } else {
+        format!("🚇 {}", label)
+    };
+    let (pid, port_num, protocol) = (port_info.pid, port_info.port, port_info.protocol.clone());
+    let mut submenu = Vec::new();
+    if tunnel.orphaned {
+        submenu.push(
+            StandardItem {
+                label: tr(Text::TunnelOrphaned).into(),
+                enabled: false,
+                ..Default::default()
+            }
+            .into(),
+        );
+    }
+    submenu.push(
+        StandardItem {
+            label: tr(Text::CloseTunnel).into(),
+            activate: Box::new(move |tray: &mut PortSlayerTray| {
+                kill_port(tray, pid, port_num, &protocol);
+            }),
+            ..Default::default()
+        }
+        .into(),
+    );
+    SubMenu {
+        label,
+        submenu,
+        ..Default::default()
+    }
+    .into()
+}
+
+/// Construye el encabezado con el conteo de puertos.
+///
+/// # Arguments
+/// * `total` - Total de puertos que coinciden con el filtro
+/// * `filter` - Filtro activo para mostrar en la etiqueta
+fn build_count_header(total: usize, filter: ProtocolFilter) -> ksni::MenuItem<PortSlayerTray> {
+    let filter_label = match filter {
+        ProtocolFilter::All => "".to_string(),
+        _ => format!(" ({})", protocol_label(filter)),
+    };
+
+    StandardItem {
+        label: trf(Text::PortsFound, &[&total, &filter_label]),
+        enabled: false,
+        ..Default::default()
+    }
+    .into()
+}
+
+/// Construye el item "⚔️ Cerrar Todos" del menú.
+///
+/// # Arguments
+/// * `total` - Cantidad de puertos para mostrar en la etiqueta
+/// * `protected_pids` - PIDs marcados "no matar" que se respetarán
+fn build_kill_all_item(total: usize, protected_pids: Vec<u32>) -> ksni::MenuItem<PortSlayerTray> {
+    StandardItem {
+        label: trf(Text::KillAll, &[&total]),
+        activate: Box::new(move |tray: &mut PortSlayerTray| {
+            log::info!("Cerrando todos los puertos...");
+            match port_scanner::kill_all_port_processes(&protected_pids) {
+                Ok(count) => {
+                    log::info!("{} procesos terminados", count);
+                }
+                Err(e) => {
+                    log::error!("Error al cerrar puertos: {}", e);
+                }
+            }
+            tray.refresh_ports();
+        }),
+        ..Default::default()
+    }
+    .into()
+}
+
+/// Construye el submenu de un puerto con opción de cerrarlo y de copiar
+/// su información al portapapeles.
+///
+/// El estilo del ícono cambia según si el proceso es conocido o no (las
+/// marcas de color dependen del tema de íconos, ver `theme`):
+/// - 🔴 Puerto con PID conocido (se puede cerrar)
+/// - 🟡 Puerto sin PID (desconocido, ej: Docker sin permisos)
+/// - 🔒 Puerto marcado "no matar" por una etiqueta (deshabilitado)
+///
+/// Los favoritos del espacio de trabajo activo se marcan con ⭐ y los
+/// puertos reservados con 🛡 (o 🚨 si los ocupa otro proceso). Los que
+/// escuchan en todas las interfaces llevan 🌐, o 🧱 si el firewall
+/// bloquea las conexiones entrantes (ver `firewall`).
+/// Si el puerto tiene etiquetas, se añaden al final con su color y nota.
+/// Si tiene conexiones establecidas, el submenu pide confirmación y
+/// muestra qué pares se desconectarían. Los procesos
+/// que suelen relanzarse tras un kill se marcan con 🔁 y ofrecen
+/// detener su proceso padre. Los adoptados como servicio de systemd
+/// (`portslayer adopt`) se marcan con ⚙ y se detienen o reinician con
+/// `systemctl --user`.
+///
+/// # Arguments
+/// * `port_info` - Información del puerto a mostrar
+/// * `tags` - Etiquetas del usuario
+/// * `impact` - Conexiones que se cortarían al cerrarlo
+/// * `favorite` - Si es favorito en el espacio de trabajo activo
+/// * `pinned` - Si se muestra en la sección de puertos fijados
+/// * `reservation` - Reserva que cubre el puerto, si la hay
+/// * `advice` - Propuesta de detener el padre, si suele relanzarse
+fn build_port_item(
+    port_info: &port_scanner::PortInfo,
+    tags: &TagStore,
+    impact: &KillImpact,
+    favorite: bool,
+    pinned: bool,
+    reservation: Option<&Reservation>,
+    advice: Option<RespawnAdvice>,
+) -> ksni::MenuItem<PortSlayerTray> {
+    let pid = port_info.pid;
+    let port_num = port_info.port;
+
+    // Ícono según si el puerto está protegido o si el PID es conocido
+    let protected = tags.is_protected(port_info);
+    let palette = theme::palette();
+    let icon = if protected {
+        "🔒"
+    } else if pid > 0 {
+        palette.known_pid
+    } else {
+        palette.unknown_pid
+    };
+
+    // Etiqueta con formato: "🔴 TCP 8080 (0.0.0.0) → node [PID 1234]"
+    let mut label = format!("{} {}", icon, port_info);
+    if favorite {
+        label = format!("⭐ {}", label);
+    }
+    // Puertos reservados: 🛡 si lo ocupa su dueño, 🚨 si es un intruso
+    match reservation {
+        Some(r) if r.is_intruder(port_info) => label = format!("🚨 {}", label),
+        Some(_) => label = format!("🛡 {}", label),
+        None => {}
+    }
+    if pinned {
+        label = format!("📌 {}", label);
+    }
+    let scheduling = if pid > 0 {
+        throttle::scheduling(pid)
+    } else {
+        None
+    };
+    if scheduling.is_some_and(|s| s.frozen) {
+        label = format!("⏸ {}", label);
+    }
+    if port_info.listens_on_all_interfaces() {
+        let firewall = firewall::cached();
+        match firewall::reachable(port_info, firewall.as_deref()) {
+            Some(false) => label.push_str(" 🧱"),
+            _ => label.push_str(" 🌐"),
+        }
+    }
+    for tag in tags.tags_for(port_info) {
+        label.push_str(&format!(" {} {}", tag.color.emoji(), tag.note));
+    }
+    if let Some(advice) = &advice {
+        label.push_str(&format!(" 🔁 {}%", advice.rate));
+    }
+    // Tráfico del último muestreo; el puerto más activo lleva ⚡
+    if let Some(rate) =
+        traffic::rate(port_num).filter(|rate| rate.total() >= traffic::MIN_SHOWN_BYTES_PER_SEC)
+    {
+        let busiest = if traffic::busiest() == Some(port_num) {
+            "⚡"
+        } else {
+            ""
+        };
+        label.push_str(&format!(
+            " ⇅{} {}",
+            busiest,
+            traffic::format_rate(rate.total())
+        ));
+    }
+
+    // Habilitar botón salvo en puertos protegidos o de Windows (si PID=0
+    // usa pkexec fuser)
+    let can_kill = !protected && port_info.origin.is_linux();
+    let protocol = port_info.protocol.clone();
+    let danger = safety::assess(pid, &port_info.process_name).filter(|_| pid > 0);
+
+    let item = if let (true, Some(danger)) = (can_kill, danger) {
+        label = format!("⚠️ {}", label);
+        build_critical_submenu(label, danger, pid, port_num, protocol)
+    } else if let (true, Some(unit)) = (can_kill, adopt::managed_unit(pid)) {
+        label = format!("⚙ {}", label);
+        build_service_submenu(label, unit, pid, port_num, protocol)
+    } else if can_kill && impact.established > 0 {
+        label.push_str(&format!(" 🔗 {}", impact.established));
+        let dependency_note = impact.dependency_note(&port_info.process_name);
+        build_impact_submenu(
+            label,
+            impact,
+            dependency_note,
+            advice,
+            pid,
+            port_num,
+            protocol,
+        )
+    } else if let (true, Some(advice)) = (can_kill, advice) {
+        build_respawn_submenu(label, advice, pid, port_num, protocol)
+    } else {
+        SubMenu {
+            label,
+            submenu: vec![StandardItem {
+                label: tr(Text::Kill).into(),
+                enabled: can_kill,
+                activate: Box::new(move |tray: &mut PortSlayerTray| {
+                    kill_port(tray, pid, port_num, &protocol);
+                }),
+                ..Default::default()
+            }
+            .into()],
+            ..Default::default()
+        }
+        .into()
+    };
+
+    // Al principio del submenu, cuánto lleva en marcha el proceso y la
+    // cola de accept() de los TCP en escucha. Los procesos con PID se
+    // pueden frenar sin cerrarlos, y todos los puertos ofrecen copiar su
+    // información al final del submenu (si hay alguna herramienta de
+    // portapapeles)
+    let ksni::MenuItem::SubMenu(mut submenu) = item else {
+        return item;
+    };
+    let mut details = Vec::new();
+    if let Some(uptime) = port_info.uptime(history::unix_now()) {
+        details.push(trf(Text::ProcessUptime, &[&format_uptime(uptime)]));
+    }
+    if let Some(queue) = port_info.queue {
+        let text = if queue.is_full() {
+            Text::ListenQueueFull
+        } else {
+            Text::ListenQueue
+        };
+        details.push(trf(text, &[&queue.depth, &queue.backlog]));
+    }
+    let details = details.into_iter().map(|label| {
+        StandardItem {
+            label,
+            enabled: false,
+            ..Default::default()
+        }
+        .into()
+    });
+    submenu.submenu.splice(0..0, details);
+    if let Some(scheduling) = scheduling {
+        submenu.submenu.push(ksni::MenuItem::Separator);
+        submenu
+            .submenu
+            .push(build_throttle_submenu(pid, scheduling, danger.is_some()));
+    }
+    if capabilities::get_capabilities().clipboard {
+        submenu.submenu.push(ksni::MenuItem::Separator);
+        submenu.submenu.push(build_copy_submenu(port_info));
+    }
+    submenu.into()
+}
+
+/// Construye el submenu de acciones que frenan un proceso sin cerrarlo:
+/// prioridad de CPU, prioridad de disco y congelar/reanudar (los procesos
+/// críticos no se ofrecen para congelar).
+fn build_throttle_submenu(
+    pid: u32,
+    scheduling: Scheduling,
+    critical: bool,
+) -> ksni::MenuItem<PortSlayerTray> {
+    let action_item = |label: String, action: Action| -> ksni::MenuItem<PortSlayerTray> {
+        StandardItem {
+            label,
+            activate: Box::new(move |tray: &mut PortSlayerTray| {
+                throttle_process(tray, pid, action);
+            }),
+            ..Default::default()
+        }
+        .into()
+    };
+
+    let mut submenu = vec![StandardItem {
+        label: trf(Text::CurrentPriority, &[&scheduling.nice]),
+        enabled: false,
+        ..Default::default()
+    }
+    .into()];
+    for nice in [throttle::DEFAULT_NICE, throttle::LOWEST_NICE] {
+        if scheduling.nice < nice {
+            submenu.push(action_item(
+                trf(Text::LowerPriority, &[&nice]),
+                Action::Renice(nice),
+            ));
+        }
+    }
+    if scheduling.nice != 0 {
+        submenu.push(action_item(
+            tr(Text::NormalPriority).into(),
+            Action::Renice(0),
+        ));
+    }
+    submenu.push(ksni::MenuItem::Separator);
+    submenu.push(action_item(
+        tr(Text::IoIdle).into(),
+        Action::Ionice(IoClass::Idle),
+    ));
+    submenu.push(action_item(
+        tr(Text::IoNormal).into(),
+        Action::Ionice(IoClass::BestEffort),
+    ));
+    if scheduling.frozen {
+        submenu.push(ksni::MenuItem::Separator);
+        submenu.push(action_item(tr(Text::Thaw).into(), Action::Thaw));
+    } else if !critical {
+        submenu.push(ksni::MenuItem::Separator);
+        submenu.push(action_item(tr(Text::Freeze).into(), Action::Freeze));
+    }
+
+    SubMenu {
+        label: tr(Text::ThrottleMenu).into(),
+        submenu,
+        ..Default::default()
+    }
+    .into()
+}
+
+/// Construye el submenu "📋 Copiar" de un puerto.
+///
+/// Los formatos que necesitan PID se deshabilitan si el PID no es visible.
+fn build_copy_submenu(port_info: &port_scanner::PortInfo) -> ksni::MenuItem<PortSlayerTray> {
+    let submenu = CopyFormat::ALL
+        .iter()
+        .map(|format| {
+            let text = format.render(port_info);
+            StandardItem {
+                label: format.label().into(),
+                enabled: port_info.pid > 0 || !format.needs_pid(),
+                activate: Box::new(move |_: &mut PortSlayerTray| match clipboard::copy(&text) {
+                    Ok(()) => log::info!("Copiado al portapapeles: {}", text),
+                    Err(e) => log::error!("{}", e),
+                }),
+                ..Default::default()
+            }
+            .into()
+        })
+        .collect();
+
+    SubMenu {
+        label: tr(Text::CopyMenu).into(),
+        submenu,
+        ..Default::default()
+    }
+    .into()
+}
+
+/// Construye el item que copia `docker logs <contenedor>` de un puerto
+/// publicado por Docker.
+fn build_docker_logs_item(binding: &Binding) -> ksni::MenuItem<PortSlayerTray> {
+    let command = binding.logs_command();
+    StandardItem {
+        label: trf(Text::DockerLogs, &[&binding.container]),
+        activate: Box::new(
+            move |_: &mut PortSlayerTray| match clipboard::copy(&command) {
+                Ok(()) => log::info!("Copiado al portapapeles: {}", command),
+                Err(e) => log::error!("{}", e),
+            },
+        ),
         ..Default::default()
     }
     .into()
 }
 
-/// Construye el encabezado con el conteo de puertos.
-///
-/// # Arguments
-/// * `total` - Total de puertos que coinciden con el filtro
-/// * `filter` - Filtro activo para mostrar en la etiqueta
-fn build_count_header(total: usize, filter: ProtocolFilter) -> ksni::MenuItem<PortSlayerTray> {
-    let filter_label = match filter {
-        ProtocolFilter::All => "".to_string(),
-        _ => format!(" ({})", filter.label()),
-    };
-
-    StandardItem {
-        label: format!("📡 {} puertos encontrados{}", total, filter_label),
-        enabled: false,
+/// Construye el submenu "✂️ Cortar una conexión (N)" de un puerto: un
+/// elemento por cliente (🖥 local, 🌐 remoto) que corta solo esa conexión
+/// con `ss -K`, sin matar al servidor.
+fn build_drop_submenu(clients: &[&Connection]) -> ksni::MenuItem<PortSlayerTray> {
+    let submenu = clients
+        .iter()
+        .take(MAX_DROP_ITEMS)
+        .map(|conn| {
+            let icon = if conn.is_local_peer() { "🖥" } else { "🌐" };
+            let conn = (*conn).clone();
+            StandardItem {
+                label: format!("{} {}", icon, conn.peer()),
+                activate: Box::new(move |tray: &mut PortSlayerTray| {
+                    if let Err(e) = connections::drop_connection(&conn) {
+                        log::error!("{}", e);
+                    }
+                    tray.refresh_ports();
+                }),
+                ..Default::default()
+            }
+            .into()
+        })
+        .collect();
+    SubMenu {
+        label: trf(Text::DropConnections, &[&clients.len()]),
+        submenu,
         ..Default::default()
     }
     .into()
 }
 
-/// Construye el item "⚔️ Cerrar Todos" del menú.
-///
-/// # Arguments
-/// * `total` - Cantidad de puertos para mostrar en la etiqueta
-fn build_kill_all_item(total: usize) -> ksni::MenuItem<PortSlayerTray> {
-    StandardItem {
-        label: format!("⚔️ Cerrar Todos ({} puertos)", total),
-        activate: Box::new(|tray: &mut PortSlayerTray| {
-            log::info!("Cerrando todos los puertos...");
-            match port_scanner::kill_all_port_processes() {
-                Ok(count) => {
-                    log::info!("{} procesos terminados", count);
+/// Construye el submenu de un puerto retenido en TIME_WAIT: "⏳ 8080: 23
+/// conexiones en TIME_WAIT (~41 s)", con la opción de destruirlas con
+/// `ss -K` como segundo paso.
+fn build_time_wait_submenu(lingering: &Lingering) -> ksni::MenuItem<PortSlayerTray> {
+    let port = lingering.port;
+    let secs = lingering.clears_in_secs.unwrap_or(60);
+    SubMenu {
+        label: trf(Text::TimeWaitHeld, &[&port, &lingering.count, &secs]),
+        submenu: vec![StandardItem {
+            label: tr(Text::TimeWaitDestroy).into(),
+            activate: Box::new(move |tray: &mut PortSlayerTray| {
+                if let Err(e) = time_wait::destroy(port) {
+                    log::error!("{}", e);
                 }
-                Err(e) => {
-                    log::error!("Error al cerrar puertos: {}", e);
+                tray.refresh_ports();
+            }),
+            ..Default::default()
+        }
+        .into()],
+        ..Default::default()
+    }
+    .into()
+}
+
+/// Manifiesto del directorio actual; uno inválido se registra y se ignora.
+fn discover_manifest() -> Option<(PathBuf, Manifest)> {
+    Manifest::discover().unwrap_or_else(|e| {
+        log::error!("{}", e);
+        None
+    })
+}
+
+/// Construye el submenu de un proyecto de Docker Compose: "📦 myapp:
+/// 8080, 5432, 6379", con la opción de bajarlo entero.
+fn build_compose_submenu(project: &str, ports: &[u16]) -> ksni::MenuItem<PortSlayerTray> {
+    let port_list: Vec<String> = ports.iter().map(u16::to_string).collect();
+    let project = project.to_string();
+    SubMenu {
+        label: trf(Text::ComposeProject, &[&project, &port_list.join(", ")]),
+        submenu: vec![StandardItem {
+            label: tr(Text::ComposeDown).into(),
+            activate: Box::new(move |tray: &mut PortSlayerTray| {
+                if let Err(e) = docker::compose_down(&project) {
+                    log::error!("{}", e);
                 }
+                tray.refresh_ports();
+            }),
+            ..Default::default()
+        }
+        .into()],
+        ..Default::default()
+    }
+    .into()
+}
+
+/// Construye el submenu de confirmación de un proceso crítico (ver
+/// `safety`): el kill pide un segundo paso, "⚔️ Cerrar igualmente".
+fn build_critical_submenu(
+    label: String,
+    danger: Danger,
+    pid: u32,
+    port_num: u16,
+    protocol: String,
+) -> ksni::MenuItem<PortSlayerTray> {
+    SubMenu {
+        label,
+        submenu: vec![
+            StandardItem {
+                label: trf(Text::CriticalWarning, &[&danger.describe()]),
+                enabled: false,
+                ..Default::default()
             }
-            tray.refresh_ports();
-        }),
+            .into(),
+            ksni::MenuItem::Separator,
+            StandardItem {
+                label: tr(Text::KillAnyway).into(),
+                activate: Box::new(move |tray: &mut PortSlayerTray| {
+                    safety::confirm(pid);
+                    kill_port(tray, pid, port_num, &protocol);
+                }),
+                ..Default::default()
+            }
+            .into(),
+        ],
+        ..Default::default()
+    }
+    .into()
+}
+
+/// Construye el submenu para matar al padre de un zombi sin hilos vivos
+/// (ver `zombie::kill_parent`): el padre suele ser una shell o un IDE,
+/// así que pide un segundo paso que lo nombra.
+fn build_kill_parent_submenu(
+    pid: u32,
+    parent: u32,
+    name: String,
+) -> ksni::MenuItem<PortSlayerTray> {
+    SubMenu {
+        label: trf(Text::ZombieKillParent, &[&name, &parent]),
+        submenu: vec![
+            StandardItem {
+                label: tr(Text::ZombieParentWarning).into(),
+                enabled: false,
+                ..Default::default()
+            }
+            .into(),
+            ksni::MenuItem::Separator,
+            StandardItem {
+                label: trf(Text::ZombieKillParentConfirm, &[&name, &parent]),
+                activate: Box::new(move |tray: &mut PortSlayerTray| {
+                    if let Err(e) = zombie::kill_parent(pid) {
+                        log::error!("{}", e);
+                    }
+                    tray.refresh_ports();
+                }),
+                ..Default::default()
+            }
+            .into(),
+        ],
         ..Default::default()
     }
     .into()
 }
 
-/// Construye un item individual de puerto con opción de cerrarlo.
+/// Construye el submenu de confirmación de un puerto con conexiones activas.
 ///
-/// El estilo del ícono cambia según si el proceso es conocido o no:
-/// - 🔴 Puerto con PID conocido (se puede cerrar)
-/// - 🟡 Puerto sin PID (desconocido, ej: Docker sin permisos)
+/// Muestra la nota de dependencias (⛓) si algún servicio local usa el
+/// puerto, lista los pares locales (🖥) y remotos (🌐) que se
+/// desconectarían y ofrece "⚔️ Cerrar igualmente".
+fn build_impact_submenu(
+    label: String,
+    impact: &KillImpact,
+    dependency_note: Option<String>,
+    advice: Option<RespawnAdvice>,
+    pid: u32,
+    port_num: u16,
+    protocol: String,
+) -> ksni::MenuItem<PortSlayerTray> {
+    let mut submenu: Vec<ksni::MenuItem<PortSlayerTray>> = vec![StandardItem {
+        label: format!("⚠️ {}", impact.summary()),
+        enabled: false,
+        ..Default::default()
+    }
+    .into()];
+
+    if let Some(note) = dependency_note {
+        submenu.push(
+            StandardItem {
+                label: format!("⛓ {}", note),
+                enabled: false,
+                ..Default::default()
+            }
+            .into(),
+        );
+    }
+
+    let peers = impact
+        .local_peers
+        .iter()
+        .map(|p| ("🖥", p))
+        .chain(impact.remote_peers.iter().map(|p| ("🌐", p)));
+    for (icon, peer) in peers {
+        submenu.push(
+            StandardItem {
+                label: trf(
+                    Text::PeerConnections,
+                    &[&icon, &peer.address, &peer.connections],
+                ),
+                enabled: false,
+                ..Default::default()
+            }
+            .into(),
+        );
+    }
+
+    submenu.push(ksni::MenuItem::Separator);
+    if let Some(advice) = advice {
+        submenu.push(build_stop_parent_item(
+            advice,
+            pid,
+            port_num,
+            protocol.clone(),
+        ));
+    }
+    submenu.push(
+        StandardItem {
+            label: tr(Text::KillAnyway).into(),
+            activate: Box::new(move |tray: &mut PortSlayerTray| {
+                kill_port(tray, pid, port_num, &protocol);
+            }),
+            ..Default::default()
+        }
+        .into(),
+    );
+
+    SubMenu {
+        label,
+        submenu,
+        ..Default::default()
+    }
+    .into()
+}
+
+/// Construye el submenu de un proceso gestionado por un servicio adoptado.
 ///
-/// # Arguments
-/// * `port_info` - Información del puerto a mostrar
-fn build_port_item(port_info: &port_scanner::PortInfo) -> ksni::MenuItem<PortSlayerTray> {
-    let pid = port_info.pid;
-    let port_num = port_info.port;
+/// Matar el proceso no sirve de mucho (systemd lo relanza si falla), así
+/// que se ofrece reiniciar o detener el servicio.
+fn build_service_submenu(
+    label: String,
+    unit: String,
+    pid: u32,
+    port_num: u16,
+    protocol: String,
+) -> ksni::MenuItem<PortSlayerTray> {
+    let actions = [
+        (tr(Text::RestartService), "restart"),
+        (tr(Text::StopService), "stop"),
+    ];
+    let mut submenu: Vec<ksni::MenuItem<PortSlayerTray>> = vec![
+        StandardItem {
+            label: format!("⚙ {}", unit),
+            enabled: false,
+            ..Default::default()
+        }
+        .into(),
+        ksni::MenuItem::Separator,
+    ];
+    for (item_label, action) in actions {
+        let unit = unit.clone();
+        submenu.push(
+            StandardItem {
+                label: item_label.into(),
+                activate: Box::new(move |tray: &mut PortSlayerTray| {
+                    log::info!("systemctl --user {} {}", action, unit);
+                    if let Err(e) = adopt::systemctl(&[action, &unit]) {
+                        log::error!("{}", e);
+                    }
+                    tray.refresh_ports();
+                }),
+                ..Default::default()
+            }
+            .into(),
+        );
+    }
+    submenu.push(
+        StandardItem {
+            label: tr(Text::KillAnyway).into(),
+            activate: Box::new(move |tray: &mut PortSlayerTray| {
+                kill_port(tray, pid, port_num, &protocol);
+            }),
+            ..Default::default()
+        }
+        .into(),
+    );
 
-    // Ícono según si el PID es conocido o no
-    let icon = if pid > 0 { "🔴" } else { "🟡" };
+    SubMenu {
+        label,
+        submenu,
+        ..Default::default()
+    }
+    .into()
+}
 
-    // Etiqueta con formato: "🔴 TCP 8080 (0.0.0.0) → node [PID 1234]"
-    let label = format!("{} {}", icon, port_info);
+/// Construye el submenu de un proceso que suele relanzarse tras un kill.
+///
+/// La primera opción detiene el proceso padre (el supervisor que lo
+/// relanza) y después el proceso; "⚔️ Cerrar igualmente" solo mata el
+/// proceso como siempre.
+fn build_respawn_submenu(
+    label: String,
+    advice: RespawnAdvice,
+    pid: u32,
+    port_num: u16,
+    protocol: String,
+) -> ksni::MenuItem<PortSlayerTray> {
+    let summary = trf(Text::RespawnSummary, &[&advice.rate, &advice.parent_name]);
+    let submenu = vec![
+        StandardItem {
+            label: summary,
+            enabled: false,
+            ..Default::default()
+        }
+        .into(),
+        ksni::MenuItem::Separator,
+        build_stop_parent_item(advice, pid, port_num, protocol.clone()),
+        StandardItem {
+            label: tr(Text::KillAnyway).into(),
+            activate: Box::new(move |tray: &mut PortSlayerTray| {
+                kill_port(tray, pid, port_num, &protocol);
+            }),
+            ..Default::default()
+        }
+        .into(),
+    ];
 
-    // Habilitar botón para todos (si PID=0 usa pkexec fuser)
-    let can_kill = true;
-    let protocol = port_info.protocol.clone();
+    SubMenu {
+        label,
+        submenu,
+        ..Default::default()
+    }
+    .into()
+}
 
+/// Construye el item "⏹ Detener el padre" de un proceso que se relanza.
+fn build_stop_parent_item(
+    advice: RespawnAdvice,
+    pid: u32,
+    port_num: u16,
+    protocol: String,
+) -> ksni::MenuItem<PortSlayerTray> {
     StandardItem {
-        label,
-        enabled: can_kill,
+        label: trf(Text::StopParent, &[&advice.parent_name, &advice.parent_pid]),
         activate: Box::new(move |tray: &mut PortSlayerTray| {
-            if pid == 0 {
-                log::warn!("Puerto {} sin PID, usando fuser con pkexec", port_num);
-                match port_scanner::kill_port_by_number(port_num, &protocol) {
-                    Ok(()) => log::info!("Puerto {} cerrado exitosamente vía fuser", port_num),
-                    Err(e) => log::error!("Error cerrando puerto {}: {}", port_num, e),
-                }
-            } else {
-                log::info!("Cerrando puerto {} (PID: {})", port_num, pid);
-                match port_scanner::kill_process(pid) {
-                    Ok(()) => {
-                        log::info!("Puerto {} cerrado exitosamente", port_num);
-                    }
-                    Err(e) => {
-                        log::error!("Error cerrando puerto {}: {}", port_num, e);
-                    }
-                }
+            log::info!(
+                "Deteniendo el proceso padre {} (PID {})",
+                advice.parent_name,
+                advice.parent_pid
+            );
+            if let Err(e) = port_scanner::kill_process(advice.parent_pid) {
+                log::error!("Error deteniendo el PID {}: {}", advice.parent_pid, e);
             }
-            tray.refresh_ports();
+            kill_port(tray, pid, port_num, &protocol);
         }),
         ..Default::default()
     }
     .into()
 }
 
+/// Huella de lo que el menú muestra además del escaneo: fechas de
+/// modificación de los archivos del usuario, espacio de trabajo activo,
+/// ventana de mantenimiento en curso, fuentes en cuarentena, operaciones
+/// en curso, posición de la reproducción, tráfico por puerto, servicios
+/// identificados y salud HTTP.
+fn menu_inputs_stamp(config: &Config) -> u64 {
+    let files = [
+        Config::path(),
+        TagStore::path(),
+        ReservationStore::path(),
+        PinStore::path(),
+        WatchStore::path(),
+        effectiveness::kills_path(),
+        restart::snapshots_path(),
+        autostart::path(),
+    ];
+    let modified: Vec<Option<SystemTime>> = files
+        .iter()
+        .map(|path| fs::metadata(path).and_then(|m| m.modified()).ok())
+        .collect();
+    let maintenance = maintenance::active_window(&config.maintenance, maintenance::local_now())
+        .map(|(window, end)| (window.name.clone(), end.to_string()));
+
+    let mut hasher = DefaultHasher::new();
+    (
+        modified,
+        workspace::active_name(),
+        maintenance,
+        sources::degraded(),
+        port_scanner::last_fd_scan().is_some_and(|r| !r.complete()),
+        shutdown::summary().pending.len(),
+        replay::status(),
+        traffic::shown(),
+        fingerprint::identified(),
+        health::snapshot(),
+    )
+        .hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Cierra un puerto y refresca la lista.
+///
+/// Con PID conocido usa `kill` (capturando antes el proceso para poder
+/// reiniciarlo); si no, `fuser` vía pkexec.
+fn kill_port(tray: &mut PortSlayerTray, pid: u32, port_num: u16, protocol: &str) {
+    if pid == 0 {
+        log::warn!("Puerto {} sin PID, usando fuser con pkexec", port_num);
+        match port_scanner::kill_port_by_number(port_num, protocol) {
+            Ok(()) => log::info!("Puerto {} cerrado exitosamente vía fuser", port_num),
+            Err(e) => log::error!("Error cerrando puerto {}: {}", port_num, e),
+        }
+    } else {
+        log::info!("Cerrando puerto {} (PID: {})", port_num, pid);
+        restart::remember(pid, port_num, protocol);
+        let target = KillTarget::capture(pid, port_num, protocol);
+        match port_scanner::kill_process(pid) {
+            Ok(()) => {
+                log::info!("Puerto {} cerrado exitosamente", port_num);
+                if let Some(target) = target {
+                    effectiveness::track(target);
+                }
+            }
+            Err(e) => {
+                log::error!("Error cerrando puerto {}: {}", port_num, e);
+            }
+        }
+    }
+    tray.refresh_ports();
+}
+
+/// Bloquea o desbloquea un puerto en el firewall y refresca la lista.
+fn toggle_block(tray: &mut PortSlayerTray, port: u16, protocol: &str, blocked: bool) {
+    let result = if blocked {
+        firewall::unblock(port, protocol).map(|_| ())
+    } else {
+        firewall::block(port, protocol)
+    };
+    if let Err(e) = result {
+        log::error!("{}", e);
+    }
+    tray.refresh_ports();
+}
+
+/// Vigila o deja de vigilar un puerto desde el tray.
+fn toggle_watch(tray: &mut PortSlayerTray, port: u16, watched: bool) {
+    let mut store = WatchStore::load();
+    if watched {
+        store.unwatch(port);
+    } else {
+        store.watch(WatchEntry {
+            port,
+            on: Vec::new(),
+        });
+    }
+    if let Err(e) = store.save() {
+        log::error!("No se pudieron guardar los puertos vigilados: {}", e);
+    }
+    tray.watches = store;
+}
+
+/// Frena un proceso sin cerrarlo y refresca la lista.
+fn throttle_process(tray: &mut PortSlayerTray, pid: u32, action: Action) {
+    if let Err(e) = throttle::apply(pid, action) {
+        log::error!("{}", e);
+    }
+    tray.refresh_ports();
+}
+
 /// Construye los items de navegación entre páginas.
 ///
 /// Genera tres items:
@@ -403,7 +2469,7 @@ fn build_navigation_items(
     let can_go_prev = current_page > 0;
     items.push(
         StandardItem {
-            label: "◀ Anterior".into(),
+            label: tr(Text::PreviousPage).into(),
             enabled: can_go_prev,
             activate: Box::new(|tray: &mut PortSlayerTray| {
                 if tray.current_page > 0 {
@@ -419,7 +2485,7 @@ fn build_navigation_items(
     // Indicador de página actual (no clickeable)
     items.push(
         StandardItem {
-            label: format!("📄 Página {}/{}", current_page + 1, total_pages),
+            label: trf(Text::PageOf, &[&(current_page + 1), &total_pages]),
             enabled: false,
             ..Default::default()
         }
@@ -430,7 +2496,7 @@ fn build_navigation_items(
     let can_go_next = current_page + 1 < total_pages;
     items.push(
         StandardItem {
-            label: "▶ Siguiente".into(),
+            label: tr(Text::NextPage).into(),
             enabled: can_go_next,
             activate: Box::new(move |tray: &mut PortSlayerTray| {
                 if tray.current_page + 1 < total_pages {
@@ -446,19 +2512,94 @@ fn build_navigation_items(
     items
 }
 
-/// Construye el item "❌ Salir" del menú.
-fn build_exit_item() -> ksni::MenuItem<PortSlayerTray> {
+/// Construye el item "📜 Abrir log" (ver `logging`).
+fn build_open_log_item() -> ksni::MenuItem<PortSlayerTray> {
     StandardItem {
-        label: "❌ Salir".into(),
+        label: tr(Text::OpenLog).into(),
         activate: Box::new(|_: &mut PortSlayerTray| {
-            log::info!("PortSlayer cerrándose...");
-            process::exit(0);
+            if let Err(e) = logging::open_log() {
+                log::error!("{}", e);
+            }
+        }),
+        ..Default::default()
+    }
+    .into()
+}
+
+/// Construye la casilla "🚀 Iniciar con la sesión" (entrada en
+/// `~/.config/autostart`).
+fn build_autostart_item(enabled: bool) -> ksni::MenuItem<PortSlayerTray> {
+    CheckmarkItem {
+        label: tr(Text::Autostart).into(),
+        checked: enabled,
+        activate: Box::new(move |_: &mut PortSlayerTray| {
+            if let Err(e) = autostart::set_enabled(!enabled) {
+                log::error!("{}", e);
+            }
         }),
         ..Default::default()
     }
     .into()
 }
 
+/// Construye el item "❌ Salir" del menú.
+///
+/// Con operaciones en curso (kills, comprobaciones) es un submenu con el
+/// resumen de la sesión que ofrece esperar a que terminen o salir ya.
+fn build_exit_item(summary: &Summary) -> ksni::MenuItem<PortSlayerTray> {
+    if summary.pending.is_empty() {
+        return StandardItem {
+            label: tr(Text::Exit).into(),
+            activate: Box::new(|_: &mut PortSlayerTray| shutdown::exit(None)),
+            ..Default::default()
+        }
+        .into();
+    }
+
+    let info = |label: String| -> ksni::MenuItem<PortSlayerTray> {
+        StandardItem {
+            label,
+            enabled: false,
+            ..Default::default()
+        }
+        .into()
+    };
+    let mut submenu = vec![info(trf(
+        Text::ExitSummary,
+        &[
+            &summary.completed,
+            &summary.pending.len(),
+            &summary.failed.len(),
+        ],
+    ))];
+    submenu.extend(summary.pending.iter().map(|p| info(format!("⏳ {}", p))));
+    submenu.extend(summary.failed.iter().map(|f| info(format!("❌ {}", f))));
+    submenu.extend([
+        ksni::MenuItem::Separator,
+        StandardItem {
+            label: tr(Text::ExitWait).into(),
+            activate: Box::new(|_: &mut PortSlayerTray| {
+                // Esperar fuera del hilo del menú para que el tray siga vivo
+                std::thread::spawn(|| shutdown::exit(Some(shutdown::WAIT_TIMEOUT)));
+            }),
+            ..Default::default()
+        }
+        .into(),
+        StandardItem {
+            label: tr(Text::ExitNow).into(),
+            activate: Box::new(|_: &mut PortSlayerTray| shutdown::exit(None)),
+            ..Default::default()
+        }
+        .into(),
+    ]);
+    SubMenu {
+        label: trf(Text::ExitPending, &[&summary.pending.len()]),
+        submenu,
+        ..Default::default()
+    }
+    .into()
+}
+
 // ─────────────────────────────────────────────────────────────
 // Inicio del servicio system tray
 // ─────────────────────────────────────────────────────────────
@@ -466,7 +2607,7 @@ fn build_exit_item() -> ksni::MenuItem<PortSlayerTray> {
 /// Inicia el system tray y ejecuta el loop principal.
 ///
 /// Crea el ícono en la bandeja del sistema y lanza un hilo de
-/// actualización automática que refresca los puertos cada 10 segundos.
+/// actualización automática que refresca los puertos periódicamente.
 ///
 /// # Panics
 /// Si no se puede crear el servicio del system tray (ej: no hay
@@ -474,20 +2615,116 @@ fn build_exit_item() -> ksni::MenuItem<PortSlayerTray> {
 pub fn run_tray() {
     log::info!("Iniciando PortSlayer system tray...");
 
+    // El diálogo de pkexec no debe retrasar la aparición del ícono
+    if Config::load().privileged_helper {
+        std::thread::spawn(|| {
+            if let Err(e) = port_scanner::start_helper() {
+                log::warn!("{}", e);
+            }
+        });
+    }
+
     let tray = PortSlayerTray::new();
     let ports_handle = tray.ports_handle();
+    let automation = Arc::clone(&tray.automation);
+    let scan_interval = Arc::clone(&tray.scan_interval);
+    let paused = Arc::clone(&tray.paused);
 
     // Crear el servicio del system tray
     let service = ksni::TrayService::new(tray);
     let handle = service.handle();
 
-    // Hilo de actualización automática cada 10 segundos
+    // Hilo de actualización automática (cada 10 segundos por defecto).
+    // Avanza de segundo en segundo para aplicar enseguida los cambios de
+    // intervalo hechos desde el menú; con intervalo 0 o en pausa no
+    // escanea (al reanudar, el intervalo vencido escanea enseguida).
+    // En cada intervalo solo se escanea a fondo si cambió /proc/net, y el
+    // menú solo se reconstruye si cambió algo de lo que muestra.
     std::thread::spawn(move || {
+        let mut engine = RuleEngine::new();
+        let mut cpu_monitor = CpuMonitor::new();
+        let mut traffic_monitor = TrafficMonitor::new();
+        let mut watch_monitor = WatchMonitor::new();
+        let mut detector = ChangeDetector::default();
+        let mut last_inputs = None;
+        let mut last_probed = None;
+        let mut elapsed = 0;
         loop {
-            std::thread::sleep(std::time::Duration::from_secs(10));
+            std::thread::sleep(std::time::Duration::from_secs(1));
+            elapsed += 1;
+            let interval = scan_interval.load(Ordering::Relaxed);
+            if interval == 0 || elapsed < interval || paused.load(Ordering::Relaxed) {
+                continue;
+            }
+            elapsed = 0;
+
+            // Los picos de CPU se muestrean en cada intervalo, aunque los
+            // puertos no hayan cambiado
+            let config = Config::load();
+            if let Ok(ports) = ports_handle.lock() {
+                cpu::run_after_scan(&mut cpu_monitor, &config, &ports);
+            }
+            // Igual que la CPU, el tráfico cambia sin que cambien los
+            // puertos: si cambia lo que muestra el menú, se escanea igual
+            let mut traffic_changed = false;
+            if config.traffic_stats && !replay::is_active() {
+                let before = traffic::shown();
+                traffic_monitor.sample();
+                traffic_changed = traffic::shown() != before;
+            }
+            // Las huellas de servicios y la salud HTTP llegan en segundo
+            // plano, después del escaneo que las pidió
+            let probed = Some((fingerprint::identified(), health::snapshot()));
+            let probes_changed = probed != last_probed;
+            last_probed = probed;
+            // La sesión reproducida avanza aunque /proc/net no cambie
+            if !replay::is_active()
+                && !detector.should_scan()
+                && !traffic_changed
+                && !probes_changed
+            {
+                continue;
+            }
 
-            // Escanear puertos actualizados
+            // Escanear puertos y conexiones actualizados
             let new_ports = port_scanner::scan_open_ports();
+            let new_connections = connections::scan_established();
+            firewall::rules();
+            firewall_manager::policy();
+            docker::bindings();
+            kubernetes::pods();
+            if !replay::is_active() {
+                if config.service_fingerprints {
+                    fingerprint::request(&new_ports);
+                }
+                if config.http_health {
+                    health::request(&new_ports);
+                }
+            }
+
+            // Evaluar las reglas automáticas si están habilitadas
+            if automation.load(Ordering::Relaxed) {
+                rules::run_after_scan(&mut engine, &config, &new_ports, &new_connections);
+            }
+            if !replay::is_active() {
+                watchlist::run_after_scan(&mut watch_monitor, &config, &new_ports);
+            }
+
+            // Sin cambios en el escaneo ni en los archivos del usuario no
+            // hace falta reconstruir el menú. Los puertos se comparan con
+            // el estado compartido, que también cambia al actualizar a mano.
+            let inputs = (
+                port_scanner::scan_checksum(&new_connections),
+                menu_inputs_stamp(&config),
+            );
+            let same_ports = ports_handle.lock().is_ok_and(|ports| {
+                port_scanner::scan_checksum(&ports) == port_scanner::scan_checksum(&new_ports)
+            });
+            if same_ports && last_inputs == Some(inputs) {
+                log::debug!("Escaneo sin cambios: no se reconstruye el menú");
+                continue;
+            }
+            last_inputs = Some(inputs);
 
             // Actualizar el estado compartido
             if let Ok(mut ports) = ports_handle.lock() {
@@ -495,7 +2732,17 @@ pub fn run_tray() {
             }
 
             // Notificar al tray para reconstruir el menú
-            handle.update(|_tray: &mut PortSlayerTray| {
+            handle.update(move |tray: &mut PortSlayerTray| {
+                tray.tags = TagStore::load();
+                tray.config = Config::load();
+                tray.reservations = ReservationStore::load();
+                tray.pins = PinStore::load();
+                tray.kill_stats = KillStats::load();
+                tray.connections = new_connections;
+                tray.restartable = restart::load_snapshots();
+                tray.blocks = BlockStore::load();
+                tray.watches = WatchStore::load();
+                tray.sync_fallback();
                 log::debug!("Menú actualizado automáticamente");
             });
         }
@@ -0,0 +1,741 @@
+/// Módulo de la tabla interactiva en la terminal (`portslayer tui`).
+///
+/// Para servidores sin tray ni escritorio: una tabla de los puertos
+/// abiertos que se actualiza sola, con búsqueda difusa, orden por
+/// columnas, selección múltiple y atajos para cerrar procesos:
+///
+/// ```text
+/// ↑↓ mover  / buscar  s ordenar  espacio marcar  Enter detalles
+/// x matar (SIGKILL)  t terminar (SIGTERM)  r actualizar  q salir
+/// ```
+///
+/// Se dibuja con secuencias ANSI en la pantalla alternativa de la
+/// terminal, en modo raw (termios), sin bibliotecas de interfaz. Matar
+/// es el kill de siempre (`port_scanner::kill_process`, con sus avisos
+/// de procesos críticos y sus permisos); terminar envía `SIGTERM` para
+/// que el proceso cierre limpio. Las dos piden confirmación.
+///
+/// `portslayer quick-kill` abre la misma tabla como selector rápido,
+/// pensado para un atajo de teclado del escritorio: se escribe para
+/// filtrar, Enter mata el proceso elegido sin más pasos y la tabla se
+/// cierra.
+///
+/// El tamaño se lee con `TIOCGWINSZ` en cada vuelta del bucle (como mucho
+/// cada décima de segundo, lo que espera la lectura de teclas) y la tabla
+/// se redibuja si cambió, sin manejar `SIGWINCH`.
+use nix::errno::Errno;
+use nix::libc;
+use nix::sys::signal::{self, SaFlags, SigAction, SigHandler, SigSet, Signal};
+use nix::sys::termios::{self, SetArg, SpecialCharacterIndices};
+use nix::unistd::Pid;
+use std::collections::BTreeSet;
+use std::io::{self, IsTerminal, Read, Write};
+use std::os::fd::AsRawFd;
+use std::panic;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Once, OnceLock};
+use std::time::{Duration, Instant};
+
+use crate::history;
+use crate::port_scanner::{self, PortInfo};
+use crate::project;
+use crate::safety;
+
+/// Tamaño de la terminal si no se puede leer
+const DEFAULT_SIZE: (usize, usize) = (24, 80);
+
+/// Líneas fijas de la pantalla: título, cabecera, ayuda y mensaje
+const CHROME_LINES: usize = 4;
+
+nix::ioctl_read_bad!(window_size, libc::TIOCGWINSZ, libc::winsize);
+
+/// Columna por la que se ordena la tabla
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SortKey {
+    Port,
+    Process,
+    Pid,
+    Uptime,
+}
+
+impl SortKey {
+    /// Siguiente columna (tecla `s`).
+    fn next(self) -> Self {
+        match self {
+            SortKey::Port => SortKey::Process,
+            SortKey::Process => SortKey::Pid,
+            SortKey::Pid => SortKey::Uptime,
+            SortKey::Uptime => SortKey::Port,
+        }
+    }
+
+    /// Nombre de la columna en el título.
+    fn label(self) -> &'static str {
+        match self {
+            SortKey::Port => "puerto",
+            SortKey::Process => "proceso",
+            SortKey::Pid => "PID",
+            SortKey::Uptime => "activo",
+        }
+    }
+}
+
+/// Tecla leída de la terminal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Key {
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Enter,
+    Escape,
+    Backspace,
+    Interrupt,
+    Char(char),
+}
+
+/// Señal pendiente de confirmar (`y`/`n`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pending {
+    Kill,
+    Terminate,
+}
+
+/// Estado de la tabla
+struct Tui {
+    ports: Vec<PortInfo>,
+    query: String,
+    searching: bool,
+    sort: SortKey,
+    /// Puertos marcados: protocolo, puerto y PID
+    marked: BTreeSet<(String, u16, u32)>,
+    cursor: usize,
+    details: bool,
+    pending: Option<Pending>,
+    message: String,
+    /// Modo `quick-kill`: se busca al escribir, Enter mata y sale
+    quick: bool,
+}
+
+/// Modo de la terminal antes de la tabla, para restaurarlo al salir
+static ORIGINAL: OnceLock<libc::termios> = OnceLock::new();
+
+/// Si la terminal sigue en modo raw (se restaura una sola vez)
+static ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Señales que cierran el proceso con la tabla abierta. Ctrl+C no
+/// genera `SIGINT` en modo raw: llega como tecla.
+const FATAL_SIGNALS: [Signal; 3] = [Signal::SIGTERM, Signal::SIGHUP, Signal::SIGQUIT];
+
+/// Modo raw de la terminal mientras dura la tabla; al soltarlo se
+/// restauran el modo anterior y la pantalla normal.
+///
+/// El perfil release compila con `panic = "abort"`, así que un pánico
+/// no llega a ejecutar `Drop`: un hook de pánico y un manejador de las
+/// señales de cierre restauran la terminal antes de que muera el proceso.
+struct RawMode;
+
+impl RawMode {
+    /// Pasa la terminal a modo raw, con lecturas que esperan como mucho
+    /// una décima de segundo (para poder redibujar sin pulsar nada).
+    fn enable() -> Result<Self, String> {
+        let stdin = io::stdin();
+        let original = termios::tcgetattr(&stdin)
+            .map_err(|e| format!("No se pudo leer el modo de la terminal: {}", e))?;
+        let mut raw = original.clone();
+        termios::cfmakeraw(&mut raw);
+        raw.control_chars[SpecialCharacterIndices::VMIN as usize] = 0;
+        raw.control_chars[SpecialCharacterIndices::VTIME as usize] = 1;
+
+        let _ = ORIGINAL.set(original.into());
+        install_restore_hooks();
+        ACTIVE.store(true, Ordering::SeqCst);
+        if let Err(e) = termios::tcsetattr(&stdin, SetArg::TCSANOW, &raw) {
+            ACTIVE.store(false, Ordering::SeqCst);
+            return Err(format!("No se pudo pasar la terminal a modo raw: {}", e));
+        }
+        // Pantalla alternativa y cursor oculto
+        print!("\x1b[?1049h\x1b[?25l");
+        let _ = io::stdout().flush();
+        Ok(Self)
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = io::stdout().flush();
+        restore_terminal();
+    }
+}
+
+/// Instala (una vez por proceso) el hook de pánico y el manejador de
+/// señales que restauran la terminal.
+fn install_restore_hooks() {
+    static INSTALLED: Once = Once::new();
+    INSTALLED.call_once(|| {
+        // Restaurar antes del mensaje, para que se lea en la pantalla normal
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            restore_terminal();
+            previous(info);
+        }));
+
+        let action = SigAction::new(
+            SigHandler::Handler(restore_and_die),
+            SaFlags::SA_RESETHAND,
+            SigSet::empty(),
+        );
+        for fatal in FATAL_SIGNALS {
+            // SAFETY: el manejador solo llama a funciones seguras en
+            // señales (write, tcsetattr, raise)
+            let _ = unsafe { signal::sigaction(fatal, &action) };
+        }
+    });
+}
+
+/// Vuelve a la pantalla normal y al modo de la terminal anterior a la
+/// tabla, si sigue en modo raw. Solo usa llamadas seguras dentro de un
+/// manejador de señales.
+fn restore_terminal() {
+    if !ACTIVE.swap(false, Ordering::SeqCst) {
+        return;
+    }
+    let reset = b"\x1b[?25h\x1b[?1049l";
+    // SAFETY: `reset` y `original` viven mientras duran las llamadas
+    unsafe {
+        libc::write(libc::STDOUT_FILENO, reset.as_ptr().cast(), reset.len());
+        if let Some(original) = ORIGINAL.get() {
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, original);
+        }
+    }
+}
+
+/// Manejador de `FATAL_SIGNALS`: restaura la terminal y vuelve a lanzar
+/// la señal, que con `SA_RESETHAND` ya tiene la acción por defecto.
+extern "C" fn restore_and_die(signal: libc::c_int) {
+    restore_terminal();
+    // SAFETY: raise es seguro dentro de un manejador de señales
+    unsafe {
+        libc::raise(signal);
+    }
+}
+
+/// Abre la tabla interactiva hasta que se pulsa `q` (o Ctrl+C).
+///
+/// Con `quick` es el selector de `portslayer quick-kill`: lo que se
+/// escribe filtra, las flechas eligen, Enter mata el proceso y cierra la
+/// tabla, y Esc la cierra sin hacer nada.
+///
+/// # Arguments
+/// * `interval` - Tiempo entre escaneos
+/// * `quick` - Abrir en modo `quick-kill`
+///
+/// # Returns
+/// `Err(String)` si la entrada o la salida no son una terminal.
+pub fn run(interval: Duration, quick: bool) -> Result<(), String> {
+    if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        return Err("portslayer tui necesita una terminal (usa list o watch en scripts)".into());
+    }
+    let _raw = RawMode::enable()?;
+    let mut tui = Tui::new(port_scanner::scan_open_ports());
+    tui.quick = quick;
+    tui.searching = quick;
+    let mut scanned = Instant::now();
+    let mut buffer = [0u8; 32];
+    // Solo se redibuja si cambió algo: una tecla, un escaneo o el tamaño
+    let mut drawn: Option<(usize, usize)> = None;
+    loop {
+        let size = terminal_size();
+        if drawn != Some(size) {
+            tui.draw(size);
+            drawn = Some(size);
+        }
+        let read = io::stdin().read(&mut buffer).unwrap_or(0);
+        let mut refresh = false;
+        for key in parse_keys(&buffer[..read]) {
+            refresh |= key == Key::Char('r') && !tui.searching && tui.pending.is_none();
+            if !tui.handle(key) {
+                return Ok(());
+            }
+            drawn = None;
+        }
+        if refresh || scanned.elapsed() >= interval {
+            tui.ports = port_scanner::scan_open_ports();
+            scanned = Instant::now();
+            drawn = None;
+        }
+    }
+}
+
+impl Tui {
+    fn new(ports: Vec<PortInfo>) -> Self {
+        Self {
+            ports,
+            query: String::new(),
+            searching: false,
+            sort: SortKey::Port,
+            marked: BTreeSet::new(),
+            cursor: 0,
+            details: false,
+            pending: None,
+            message: String::new(),
+            quick: false,
+        }
+    }
+
+    /// Puertos que coinciden con la búsqueda, en el orden elegido.
+    fn visible(&self) -> Vec<&PortInfo> {
+        let mut rows: Vec<&PortInfo> = self
+            .ports
+            .iter()
+            .filter(|p| {
+                let text = format!("{} {} {} {}", p.port, p.protocol, p.process_name, p.pid);
+                fuzzy_match(&self.query, &text)
+            })
+            .collect();
+        match self.sort {
+            SortKey::Port => rows.sort_by_key(|p| (p.port, p.protocol.clone())),
+            SortKey::Process => rows.sort_by_key(|p| (p.process_name.to_lowercase(), p.port)),
+            SortKey::Pid => rows.sort_by_key(|p| (p.pid, p.port)),
+            // Los más recientes primero; sin hora de inicio, al final
+            SortKey::Uptime => rows.sort_by_key(|p| (std::cmp::Reverse(p.started_at), p.port)),
+        }
+        rows
+    }
+
+    /// Atiende una tecla. Devuelve `false` para salir.
+    fn handle(&mut self, key: Key) -> bool {
+        if key == Key::Interrupt {
+            return false;
+        }
+        if self.quick {
+            return self.handle_quick(key);
+        }
+        if let Some(pending) = self.pending.take() {
+            self.message = match key {
+                Key::Char('y') | Key::Char('s') => self.send(pending).unwrap_or_else(|e| e),
+                _ => "Cancelado".to_string(),
+            };
+            return true;
+        }
+        let rows = self.visible().len();
+        if self.searching {
+            match key {
+                Key::Enter | Key::Escape => self.searching = false,
+                Key::Backspace => {
+                    self.query.pop();
+                }
+                Key::Char(c) => self.query.push(c),
+                _ => {}
+            }
+            self.cursor = 0;
+            return true;
+        }
+        self.message.clear();
+        match key {
+            Key::Char('q') => return false,
+            Key::Up | Key::Char('k') => self.cursor = self.cursor.saturating_sub(1),
+            Key::Down | Key::Char('j') => self.cursor += 1,
+            Key::PageUp => self.cursor = self.cursor.saturating_sub(10),
+            Key::PageDown => self.cursor += 10,
+            Key::Char('/') => self.searching = true,
+            Key::Escape => self.query.clear(),
+            Key::Char('s') => self.sort = self.sort.next(),
+            Key::Enter => self.details = !self.details,
+            Key::Char(' ') => {
+                if let Some(port) = self.visible().get(self.cursor) {
+                    let id = (port.protocol.clone(), port.port, port.pid);
+                    if !self.marked.remove(&id) {
+                        self.marked.insert(id);
+                    }
+                }
+                self.cursor += 1;
+            }
+            Key::Char(c @ ('x' | 't')) => {
+                let pids = self.targets();
+                if pids.is_empty() {
+                    self.message = "Nada que cerrar: sin PID conocido".to_string();
+                } else {
+                    let (pending, verb) = match c {
+                        'x' => (Pending::Kill, "¿Matar (SIGKILL)"),
+                        _ => (Pending::Terminate, "¿Terminar (SIGTERM)"),
+                    };
+                    self.pending = Some(pending);
+                    self.message = format!("{} PID {}? (y/n)", verb, join(&pids));
+                }
+            }
+            _ => {}
+        }
+        self.cursor = self.cursor.min(rows.saturating_sub(1));
+        true
+    }
+
+    /// Atiende una tecla en modo `quick-kill`. Devuelve `false` para salir
+    /// (Esc, o tras un kill que funcionó).
+    fn handle_quick(&mut self, key: Key) -> bool {
+        match key {
+            Key::Escape => return false,
+            Key::Up => self.cursor = self.cursor.saturating_sub(1),
+            Key::Down => self.cursor += 1,
+            Key::PageUp => self.cursor = self.cursor.saturating_sub(10),
+            Key::PageDown => self.cursor += 10,
+            Key::Backspace => {
+                self.query.pop();
+                self.cursor = 0;
+            }
+            Key::Char(c) => {
+                self.query.push(c);
+                self.cursor = 0;
+            }
+            Key::Enter if self.targets().is_empty() => {
+                self.message = "Nada que cerrar: sin PID conocido".to_string();
+            }
+            Key::Enter => match self.send(Pending::Kill) {
+                Ok(_) => return false,
+                Err(e) => self.message = e,
+            },
+            Key::Interrupt => return false,
+        }
+        self.cursor = self.cursor.min(self.visible().len().saturating_sub(1));
+        true
+    }
+
+    /// PIDs a los que van las señales: los marcados o, sin marcas, el de
+    /// la fila actual.
+    fn targets(&self) -> Vec<u32> {
+        let mut pids: Vec<u32> = if self.marked.is_empty() {
+            self.visible()
+                .get(self.cursor)
+                .map(|p| p.pid)
+                .into_iter()
+                .collect()
+        } else {
+            self.marked.iter().map(|(_, _, pid)| *pid).collect()
+        };
+        pids.retain(|pid| *pid > 0);
+        pids.sort_unstable();
+        pids.dedup();
+        pids
+    }
+
+    /// Envía la señal confirmada y resume el resultado.
+    ///
+    /// # Returns
+    /// `Err(String)` con los kills que fallaron.
+    fn send(&mut self, pending: Pending) -> Result<String, String> {
+        let pids = self.targets();
+        let failures: Vec<String> = pids
+            .iter()
+            .filter_map(|pid| {
+                let result = match pending {
+                    Pending::Kill => port_scanner::kill_process(*pid),
+                    Pending::Terminate => terminate(*pid),
+                };
+                result.err().map(|e| format!("PID {}: {}", pid, e))
+            })
+            .collect();
+        self.marked.clear();
+        self.ports = port_scanner::scan_open_ports();
+        match failures.is_empty() {
+            true => Ok(format!("Cerrado PID {}", join(&pids))),
+            false => Err(failures.join("; ")),
+        }
+    }
+
+    /// Redibuja la pantalla completa.
+    fn draw(&self, (height, width): (usize, usize)) {
+        let rows = self.visible();
+        let now = history::unix_now();
+        let details = match (self.details, rows.get(self.cursor)) {
+            (true, Some(port)) => details(port, now),
+            _ => Vec::new(),
+        };
+        let space = height.saturating_sub(CHROME_LINES + details.len()).max(1);
+        let first = self.cursor.saturating_sub(space - 1);
+
+        let mut frame = String::from("\x1b[H\x1b[2J");
+        let search = match (self.searching, self.query.is_empty()) {
+            (true, _) => format!("  buscar: {}▏", self.query),
+            (false, false) => format!("  buscar: {}", self.query),
+            (false, true) => String::new(),
+        };
+        let title = format!(
+            "PortSlayer — {} de {} puertos, por {}{}{}",
+            rows.len(),
+            self.ports.len(),
+            self.sort.label(),
+            match self.marked.len() {
+                0 => String::new(),
+                n => format!(", {} marcados", n),
+            },
+            search
+        );
+        push_line(&mut frame, &format!("\x1b[1m{}\x1b[0m", fit(&title, width)));
+        let header = format!(
+            "  {:<5} {:<6} {:<24} {:<8} {:<8} PROCESO",
+            "PROTO", "PUERTO", "DIRECCIÓN", "PID", "ACTIVO"
+        );
+        push_line(
+            &mut frame,
+            &format!("\x1b[7m{}\x1b[0m", fit(&header, width)),
+        );
+        for (index, port) in rows.iter().enumerate().skip(first).take(space) {
+            let id = (port.protocol.clone(), port.port, port.pid);
+            let mark = if self.marked.contains(&id) {
+                "●"
+            } else {
+                " "
+            };
+            let pid = match port.pid {
+                0 => "-".to_string(),
+                pid => pid.to_string(),
+            };
+            let uptime = port.uptime(now).map(port_scanner::format_uptime);
+            let line = format!(
+                "{} {:<5} {:<6} {:<24} {:<8} {:<8} {}",
+                mark,
+                port.protocol.to_uppercase(),
+                port.port,
+                port.local_address,
+                pid,
+                uptime.as_deref().unwrap_or("-"),
+                port.process_name
+            );
+            let line = fit(&line, width);
+            match index == self.cursor {
+                true => push_line(&mut frame, &format!("\x1b[7m{}\x1b[0m", line)),
+                false => push_line(&mut frame, &line),
+            }
+        }
+        for line in details {
+            push_line(&mut frame, &format!("\x1b[2m{}\x1b[0m", fit(&line, width)));
+        }
+        // Ayuda y mensaje, pegados al final de la pantalla
+        frame.push_str(&format!("\x1b[{};1H", height.saturating_sub(1).max(1)));
+        let help = match self.quick {
+            true => "escribe para buscar  ↑↓ elegir  Enter matar (SIGKILL)  Esc salir",
+            false => {
+                "↑↓ mover  / buscar  s ordenar  espacio marcar  Enter detalles  \
+                 x matar  t terminar  r actualizar  q salir"
+            }
+        };
+        push_line(&mut frame, &format!("\x1b[2m{}\x1b[0m", fit(help, width)));
+        frame.push_str(&fit(&self.message, width));
+        print!("{}", frame);
+        let _ = io::stdout().flush();
+    }
+}
+
+/// Líneas del detalle de un puerto (tecla Enter).
+fn details(port: &PortInfo, now: u64) -> Vec<String> {
+    let mut lines = vec![format!("  {}", port)];
+    if let Some(uid) = port.uid {
+        lines.push(format!("  usuario: {}", port_scanner::user_name(uid)));
+    }
+    if let Some(uptime) = port.uptime(now) {
+        let uptime = port_scanner::format_uptime(uptime);
+        lines.push(format!("  en marcha desde hace {}", uptime));
+    }
+    if let Some(project) = project::project_for(port.pid) {
+        lines.push(format!("  proyecto: {}", project));
+    }
+    let cmdline = std::fs::read(format!("/proc/{}/cmdline", port.pid)).unwrap_or_default();
+    let cmdline: Vec<String> = cmdline
+        .split(|b| *b == 0)
+        .filter(|arg| !arg.is_empty())
+        .map(|arg| String::from_utf8_lossy(arg).into_owned())
+        .collect();
+    if port.pid > 0 && !cmdline.is_empty() {
+        lines.push(format!("  $ {}", cmdline.join(" ")));
+    }
+    lines
+}
+
+/// Envía `SIGTERM` a un proceso, con las mismas comprobaciones de
+/// procesos críticos que un kill.
+fn terminate(pid: u32) -> Result<(), String> {
+    safety::check(pid)?;
+    let raw = i32::try_from(pid).map_err(|_| "PID inválido".to_string())?;
+    match signal::kill(Pid::from_raw(raw), Signal::SIGTERM) {
+        Ok(()) => {
+            log::info!("SIGTERM enviado al proceso {}", pid);
+            Ok(())
+        }
+        Err(Errno::EPERM) => Err("sin permisos (prueba con x, que pide privilegios)".into()),
+        Err(Errno::ESRCH) => Err("el proceso ya no existe".into()),
+        Err(errno) => Err(errno.desc().to_string()),
+    }
+}
+
+/// Indica si todos los caracteres de la búsqueda aparecen en el texto y
+/// en el mismo orden, sin distinguir mayúsculas ("ndv" → "node-dev").
+fn fuzzy_match(query: &str, text: &str) -> bool {
+    let mut text = text.chars().flat_map(char::to_lowercase);
+    query
+        .chars()
+        .flat_map(char::to_lowercase)
+        .filter(|c| !c.is_whitespace())
+        .all(|wanted| text.any(|c| c == wanted))
+}
+
+/// Interpreta los bytes leídos de la terminal en modo raw.
+fn parse_keys(bytes: &[u8]) -> Vec<Key> {
+    let text = String::from_utf8_lossy(bytes);
+    let mut keys = Vec::new();
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        let key = match c {
+            '\x1b' if chars.peek() == Some(&'[') => {
+                chars.next();
+                match chars.next() {
+                    Some('A') => Key::Up,
+                    Some('B') => Key::Down,
+                    Some(digit @ ('5' | '6')) if chars.next_if_eq(&'~').is_some() => match digit {
+                        '5' => Key::PageUp,
+                        _ => Key::PageDown,
+                    },
+                    _ => continue,
+                }
+            }
+            '\x1b' => Key::Escape,
+            '\r' | '\n' => Key::Enter,
+            '\x7f' | '\x08' => Key::Backspace,
+            '\x03' => Key::Interrupt,
+            c if c.is_control() => continue,
+            c => Key::Char(c),
+        };
+        keys.push(key);
+    }
+    keys
+}
+
+/// Filas y columnas de la terminal.
+fn terminal_size() -> (usize, usize) {
+    let mut size = libc::winsize {
+        ws_row: 0,
+        ws_col: 0,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    };
+    // SAFETY: TIOCGWINSZ solo escribe en `size`, que vive en esta función
+    let read = unsafe { window_size(io::stdout().as_raw_fd(), &mut size) };
+    match read {
+        Ok(_) if size.ws_row > 0 && size.ws_col > 0 => {
+            (usize::from(size.ws_row), usize::from(size.ws_col))
+        }
+        _ => DEFAULT_SIZE,
+    }
+}
+
+/// Recorta una línea al ancho de la terminal.
+fn fit(line: &str, width: usize) -> String {
+    line.chars().take(width).collect()
+}
+
+/// Añade una línea al cuadro (en modo raw, `\n` no vuelve al principio).
+fn push_line(frame: &mut String, line: &str) {
+    frame.push_str(line);
+    frame.push_str("\r\n");
+}
+
+/// PIDs separados por comas.
+fn join(pids: &[u32]) -> String {
+    pids.iter()
+        .map(u32::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verifica la búsqueda difusa, el orden, la selección múltiple y la
+    /// lectura de las teclas
+    #[test]
+    fn test_table_navigation() {
+        let port = |port: u16, pid: u32, process_name: &str| PortInfo {
+            protocol: "tcp".into(),
+            port,
+            local_address: "127.0.0.1".into(),
+            pid,
+            process_name: process_name.into(),
+            ..Default::default()
+        };
+        let mut tui = Tui::new(vec![
+            port(5432, 880, "postgres"),
+            port(3000, 4242, "node-dev"),
+            port(5173, 4300, "vite"),
+        ]);
+        assert!(fuzzy_match("ndv", "3000 tcp node-dev 4242"));
+        assert!(!fuzzy_match("vdn", "3000 tcp node-dev 4242"));
+
+        let ports: Vec<u16> = tui.visible().iter().map(|p| p.port).collect();
+        assert_eq!(ports, [3000, 5173, 5432]);
+        tui.handle(Key::Char('s'));
+        let processes: Vec<&str> = tui
+            .visible()
+            .iter()
+            .map(|p| p.process_name.as_str())
+            .collect();
+        assert_eq!(processes, ["node-dev", "postgres", "vite"]);
+
+        // Marcar las dos primeras filas y cancelar el kill
+        tui.handle(Key::Char(' '));
+        tui.handle(Key::Char(' '));
+        assert_eq!(tui.targets(), [880, 4242]);
+        tui.handle(Key::Char('x'));
+        assert_eq!(tui.pending, Some(Pending::Kill));
+        tui.handle(Key::Char('n'));
+        assert_eq!((tui.pending, tui.message.as_str()), (None, "Cancelado"));
+
+        for key in parse_keys(b"/vi\r") {
+            tui.handle(key);
+        }
+        assert_eq!(tui.visible().len(), 1);
+        assert!(!tui.searching);
+        assert_eq!(
+            parse_keys(b"\x1b[A\x1b[6~\x1bq\x03"),
+            [
+                Key::Up,
+                Key::PageDown,
+                Key::Escape,
+                Key::Char('q'),
+                Key::Interrupt
+            ]
+        );
+    }
+
+    /// Verifica el modo `quick-kill`: escribir filtra y Esc sale
+    #[test]
+    fn test_quick_kill_keys() {
+        let port = |port: u16, pid: u32, process_name: &str| PortInfo {
+            protocol: "tcp".into(),
+            port,
+            pid,
+            process_name: process_name.into(),
+            ..Default::default()
+        };
+        let mut tui = Tui::new(vec![port(3000, 4242, "node-dev"), port(53, 0, "")]);
+        tui.quick = true;
+        tui.searching = true;
+
+        // `q` y `s` se escriben en la búsqueda en lugar de ser atajos
+        for key in parse_keys(b"53q") {
+            assert!(tui.handle(key));
+        }
+        assert_eq!(tui.query, "53q");
+        assert!(tui.visible().is_empty());
+        tui.handle(Key::Backspace);
+        assert_eq!(tui.visible().len(), 1);
+
+        // Sin PID no hay nada que matar: la tabla sigue abierta
+        assert!(tui.handle(Key::Enter));
+        assert!(tui.message.starts_with("Nada que cerrar"));
+        assert!(!tui.handle(Key::Escape));
+    }
+}
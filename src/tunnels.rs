@@ -0,0 +1,264 @@
+/// Módulo de túneles: reenvíos de puertos con `ssh -L`/`ssh -D` y `socat`.
+///
+/// Un `ssh -fNL 5432:db:5432 bastion` olvidado o un `socat` lanzado para
+/// una prueba dejan puertos abiertos que nadie recuerda. Entre los
+/// procesos que escuchan se reconocen estos reenvíos por su línea de
+/// comandos y se muestra a dónde llevan ("5432 → db:5432 vía bastion").
+///
+/// Un túnel es huérfano si su padre ya no es la terminal que lo lanzó
+/// sino `init` o el `systemd` de la sesión, que adoptan los procesos
+/// sueltos: la señal típica de un túnel olvidado.
+///
+/// El tray los agrupa en su propia sección con "Cerrar túnel"; también
+/// se listan con `portslayer tunnels` y `GET /tunnels`.
+use serde::Serialize;
+use std::fs;
+
+use crate::port_scanner::PortInfo;
+
+/// Opciones de `ssh` que llevan argumento (ver `man ssh`)
+const SSH_OPTIONS_WITH_ARG: &str = "BbcDEeFIiJLlmOoPpQRSWw";
+
+/// Procesos que adoptan a los huérfanos
+const REAPERS: [&str; 2] = ["systemd", "init"];
+
+/// Programa que mantiene el túnel
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TunnelKind {
+    /// `ssh -L` (reenvío local) o `ssh -D` (proxy SOCKS)
+    Ssh,
+    /// Relé de `socat`
+    Socat,
+}
+
+impl TunnelKind {
+    /// Nombre para mostrar.
+    pub fn label(&self) -> &'static str {
+        match self {
+            TunnelKind::Ssh => "ssh",
+            TunnelKind::Socat => "socat",
+        }
+    }
+}
+
+/// Reenvío de puerto detectado
+#[derive(Debug, Clone, Serialize)]
+pub struct Tunnel {
+    /// Puerto local que escucha
+    pub port: PortInfo,
+    /// Programa del túnel
+    pub kind: TunnelKind,
+    /// Destino del reenvío (ej: "db:5432", "SOCKS", "TCP:10.0.0.5:80")
+    pub target: String,
+    /// Servidor ssh por el que pasa el túnel
+    pub via: Option<String>,
+    /// El proceso que lo lanzó ya no existe
+    pub orphaned: bool,
+}
+
+/// Túneles entre los puertos abiertos, en el orden del escaneo.
+pub fn detect(ports: &[PortInfo]) -> Vec<Tunnel> {
+    ports
+        .iter()
+        .filter(|p| p.pid > 0 && matches!(p.process_name.as_str(), "ssh" | "socat"))
+        .filter_map(|p| {
+            let args = read_cmdline(p.pid)?;
+            let (kind, target, via) = parse_tunnel(&args, p.port)?;
+            Some(Tunnel {
+                port: p.clone(),
+                kind,
+                target,
+                via,
+                orphaned: is_orphaned(p.pid),
+            })
+        })
+        .collect()
+}
+
+/// Tipo, destino y servidor ssh del túnel que escucha en `port`, según
+/// su línea de comandos.
+fn parse_tunnel(args: &[String], port: u16) -> Option<(TunnelKind, String, Option<String>)> {
+    let program = args.first()?.rsplit('/').next()?;
+    match program {
+        "ssh" => parse_ssh(&args[1..], port).map(|(target, via)| (TunnelKind::Ssh, target, via)),
+        "socat" => parse_socat(&args[1..], port).map(|target| (TunnelKind::Socat, target, None)),
+        _ => None,
+    }
+}
+
+/// Destino del reenvío `-L`/`-D` de `ssh` que escucha en `port` y
+/// servidor por el que pasa.
+fn parse_ssh(args: &[String], port: u16) -> Option<(String, Option<String>)> {
+    let mut forwards: Vec<(char, String)> = Vec::new();
+    let mut host = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let Some(flags) = arg.strip_prefix('-').filter(|f| !f.is_empty()) else {
+            // El primer argumento que no es opción es el servidor
+            host = host.or(Some(arg.clone()));
+            continue;
+        };
+        // Las opciones se pueden agrupar ("-fNL 8080:db:80", "-L8080:db:80")
+        for (i, flag) in flags.char_indices() {
+            if !SSH_OPTIONS_WITH_ARG.contains(flag) {
+                continue;
+            }
+            let rest = &flags[i + flag.len_utf8()..];
+            let value = if rest.is_empty() {
+                args.next().cloned()
+            } else {
+                Some(rest.to_string())
+            };
+            if let (Some(value), 'L' | 'D') = (value, flag) {
+                forwards.push((flag, value));
+            }
+            break;
+        }
+    }
+
+    let target = forwards.iter().find_map(|(flag, spec)| {
+        let parts = split_spec(spec);
+        match (flag, parts.as_slice()) {
+            // [bind:]puerto (proxy SOCKS)
+            ('D', [.., listen]) if listen.parse() == Ok(port) => Some("SOCKS".to_string()),
+            // [bind:]puerto:host:puerto_remoto
+            ('L', [.., listen, target_host, target_port]) if listen.parse() == Ok(port) => {
+                Some(format!("{}:{}", target_host, target_port))
+            }
+            // [bind:]puerto:/ruta/socket
+            ('L', [.., listen, socket]) if listen.parse() == Ok(port) => Some(socket.clone()),
+            _ => None,
+        }
+    })?;
+    Some((target, host))
+}
+
+/// Divide una especificación de reenvío por `:` respetando las
+/// direcciones IPv6 entre corchetes ("[::1]:8080:db:80").
+fn split_spec(spec: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut current = String::new();
+    let mut bracket = false;
+    for c in spec.chars() {
+        match c {
+            '[' => bracket = true,
+            ']' => bracket = false,
+            ':' if !bracket => parts.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    parts.push(current);
+    parts
+}
+
+/// Destino de un relé de `socat` cuya dirección `*-LISTEN` usa `port`.
+fn parse_socat(args: &[String], port: u16) -> Option<String> {
+    let addresses: Vec<&String> = args.iter().filter(|a| !a.starts_with('-')).collect();
+    let [first, second] = addresses.as_slice() else {
+        return None;
+    };
+    let listens_on = |address: &str| {
+        let (kind, rest) = address.split_once(':')?;
+        let kind = kind.to_uppercase();
+        let listen_port = rest.split(',').next()?.parse::<u16>().ok()?;
+        (kind.contains("-LISTEN") || kind.contains("-RECVFROM")).then_some(listen_port)
+    };
+    let target = if listens_on(first) == Some(port) {
+        second
+    } else if listens_on(second) == Some(port) {
+        first
+    } else {
+        return None;
+    };
+    // Sin las opciones (",fork,reuseaddr")
+    target.split(',').next().map(str::to_string)
+}
+
+/// Argumentos de la línea de comandos de un proceso.
+fn read_cmdline(pid: u32) -> Option<Vec<String>> {
+    let raw = fs::read(format!("/proc/{}/cmdline", pid)).ok()?;
+    let args: Vec<String> = raw
+        .split(|b| *b == 0)
+        .filter(|arg| !arg.is_empty())
+        .map(|arg| String::from_utf8_lossy(arg).into_owned())
+        .collect();
+    (!args.is_empty()).then_some(args)
+}
+
+/// Indica si el proceso fue adoptado por `init` o el `systemd` de la sesión.
+fn is_orphaned(pid: u32) -> bool {
+    let Ok(stat) = fs::read_to_string(format!("/proc/{}/stat", pid)) else {
+        return false;
+    };
+    // Tras el nombre entre paréntesis: estado, PPID, ...
+    let ppid = stat
+        .rfind(')')
+        .and_then(|end| stat[end + 1..].split_whitespace().nth(1))
+        .and_then(|ppid| ppid.parse::<u32>().ok());
+    match ppid {
+        Some(1) => true,
+        Some(ppid) => fs::read_to_string(format!("/proc/{}/comm", ppid))
+            .is_ok_and(|comm| REAPERS.contains(&comm.trim())),
+        None => false,
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(str::to_string).collect()
+    }
+
+    /// Verifica los reenvíos de ssh en sus distintas formas
+    #[test]
+    fn test_parse_ssh() {
+        assert_eq!(
+            parse_tunnel(&args("ssh -fNL 5432:db.internal:5432 bastion"), 5432),
+            Some((
+                TunnelKind::Ssh,
+                "db.internal:5432".into(),
+                Some("bastion".into())
+            ))
+        );
+        assert_eq!(
+            parse_tunnel(
+                &args("/usr/bin/ssh -p 2222 -L[::1]:8080:web:80 -D 1080 dev@box"),
+                8080
+            ),
+            Some((TunnelKind::Ssh, "web:80".into(), Some("dev@box".into())))
+        );
+        assert_eq!(
+            parse_ssh(&args("-p 2222 -L[::1]:8080:web:80 -D 1080 dev@box"), 1080),
+            Some(("SOCKS".into(), Some("dev@box".into())))
+        );
+        // Un ssh interactivo no es un túnel
+        assert_eq!(parse_ssh(&args("-p 2222 dev@box"), 2222), None);
+    }
+
+    /// Verifica los relés de socat
+    #[test]
+    fn test_parse_socat() {
+        assert_eq!(
+            parse_tunnel(
+                &args("socat TCP-LISTEN:8080,fork,reuseaddr TCP:10.0.0.5:80"),
+                8080
+            ),
+            Some((TunnelKind::Socat, "TCP:10.0.0.5:80".into(), None))
+        );
+        assert_eq!(
+            parse_socat(
+                &args("-d -d UNIX-CONNECT:/run/app.sock TCP4-LISTEN:9000"),
+                9000
+            ),
+            Some("UNIX-CONNECT:/run/app.sock".into())
+        );
+        assert_eq!(parse_socat(&args("TCP-LISTEN:8080 TCP:x:80"), 9090), None);
+    }
+}
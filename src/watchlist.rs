@@ -0,0 +1,387 @@
+/// Módulo de puertos vigilados.
+///
+/// Un puerto vigilado avisa con una notificación de escritorio cuando se
+/// abre, cuando se cierra o cuando pasa a ocuparlo otro proceso (otro
+/// PID: también un reinicio). Cada puerto elige de qué avisa (`on`) y,
+/// si no dice nada, avisa de todo.
+///
+/// La lista sale de dos sitios:
+///
+/// - la configuración, que no se modifica desde PortSlayer:
+///
+///   ```toml
+///   [[watch]]
+///   port = 5432
+///
+///   [[watch]]
+///   port = 3000
+///   on = ["close"]               # "open" | "close" | "owner"
+///   ```
+///
+/// - `~/.config/portslayer/watchlist.json`, que se gestiona desde el
+///   tray (👁 en el submenu de cada puerto), con `portslayer watchlist`
+///   y con `POST`/`DELETE /watchlist/{puerto}`.
+///
+/// Se comprueba tras cada escaneo del tray y del daemon; el primer
+/// escaneo solo toma nota del estado, y las ventanas de mantenimiento
+/// suprimen los avisos como con las reglas.
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use crate::config::Config;
+use crate::i18n::{trf, Text};
+use crate::maintenance;
+use crate::paths;
+use crate::port_scanner::PortInfo;
+use crate::rules;
+
+/// Nombre del archivo de puertos vigilados dentro del directorio de configuración
+const WATCHLIST_FILE: &str = "watchlist.json";
+
+/// Cambio de un puerto vigilado
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WatchEvent {
+    /// Empieza a escuchar alguien
+    Open,
+    /// Deja de escuchar
+    Close,
+    /// Lo ocupa otro proceso
+    Owner,
+}
+
+impl WatchEvent {
+    /// Nombre en la configuración y en la CLI ("open", "close", "owner").
+    pub fn name(&self) -> &'static str {
+        match self {
+            WatchEvent::Open => "open",
+            WatchEvent::Close => "close",
+            WatchEvent::Owner => "owner",
+        }
+    }
+}
+
+/// Todos los cambios (lo que se vigila si no se indica otra cosa)
+const ALL_EVENTS: [WatchEvent; 3] = [WatchEvent::Open, WatchEvent::Close, WatchEvent::Owner];
+
+/// Puerto vigilado y de qué cambios avisa
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WatchEntry {
+    pub port: u16,
+    /// Cambios de los que avisar (vacío = todos)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub on: Vec<WatchEvent>,
+}
+
+impl WatchEntry {
+    /// Indica si avisa de un cambio.
+    pub fn alerts_on(&self, event: WatchEvent) -> bool {
+        self.on.is_empty() || self.on.contains(&event)
+    }
+
+    /// Cambios de los que avisa.
+    pub fn events(&self) -> &[WatchEvent] {
+        if self.on.is_empty() {
+            &ALL_EVENTS
+        } else {
+            &self.on
+        }
+    }
+}
+
+/// Puertos vigilados persistidos en disco
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WatchStore {
+    /// Puertos en el orden en que se añadieron
+    pub entries: Vec<WatchEntry>,
+}
+
+impl WatchStore {
+    /// Ruta del archivo de puertos vigilados.
+    pub fn path() -> PathBuf {
+        paths::config_dir().join(WATCHLIST_FILE)
+    }
+
+    /// Carga los puertos vigilados; vacío si el archivo no existe o no es válido.
+    pub fn load() -> Self {
+        let path = Self::path();
+        match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                log::error!(
+                    "Archivo de puertos vigilados inválido {}: {}",
+                    path.display(),
+                    e
+                );
+                Self::default()
+            }),
+            Err(_) => Self::default(),
+        }
+    }
+
+    /// Guarda los puertos vigilados en disco.
+    pub fn save(&self) -> io::Result<()> {
+        let path = Self::path();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(path, json)
+    }
+
+    /// Vigila un puerto, o cambia de qué avisa si ya se vigilaba.
+    ///
+    /// # Returns
+    /// `false` si ya se vigilaba igual.
+    pub fn watch(&mut self, entry: WatchEntry) -> bool {
+        match self.entries.iter_mut().find(|e| e.port == entry.port) {
+            Some(existing) if *existing == entry => false,
+            Some(existing) => {
+                *existing = entry;
+                true
+            }
+            None => {
+                self.entries.push(entry);
+                true
+            }
+        }
+    }
+
+    /// Deja de vigilar un puerto.
+    ///
+    /// # Returns
+    /// `false` si no se vigilaba.
+    pub fn unwatch(&mut self, port: u16) -> bool {
+        let before = self.entries.len();
+        self.entries.retain(|e| e.port != port);
+        self.entries.len() != before
+    }
+
+    /// Indica si un puerto se vigila desde el archivo.
+    pub fn is_watched(&self, port: u16) -> bool {
+        self.entries.iter().any(|e| e.port == port)
+    }
+}
+
+/// Puertos vigilados: los de la configuración y después los del archivo
+/// (un puerto en ambos se queda con lo que diga la configuración).
+pub fn entries(config: &Config, store: &WatchStore) -> Vec<WatchEntry> {
+    let mut entries = config.watch.clone();
+    for entry in &store.entries {
+        if !entries.iter().any(|e| e.port == entry.port) {
+            entries.push(entry.clone());
+        }
+    }
+    entries
+}
+
+/// Proceso que ocupa un puerto vigilado
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Owner {
+    pub pid: u32,
+    pub process_name: String,
+}
+
+/// Cambio detectado en un puerto vigilado
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WatchAlert {
+    Opened(u16, Owner),
+    Closed(u16, Owner),
+    OwnerChanged(u16, Owner, Owner),
+}
+
+impl WatchAlert {
+    /// Texto de la notificación.
+    pub fn message(&self) -> String {
+        match self {
+            WatchAlert::Opened(port, owner) => {
+                trf(Text::WatchOpened, &[port, &owner.process_name, &owner.pid])
+            }
+            WatchAlert::Closed(port, owner) => {
+                trf(Text::WatchClosed, &[port, &owner.process_name, &owner.pid])
+            }
+            WatchAlert::OwnerChanged(port, from, to) => trf(
+                Text::WatchOwnerChanged,
+                &[
+                    port,
+                    &from.process_name,
+                    &from.pid,
+                    &to.process_name,
+                    &to.pid,
+                ],
+            ),
+        }
+    }
+}
+
+/// Dueño de un puerto en un escaneo: el de menor PID si hay varios
+/// (TCP y UDP, o varios procesos con `SO_REUSEPORT`).
+pub fn owner(port: u16, ports: &[PortInfo]) -> Option<Owner> {
+    ports
+        .iter()
+        .filter(|p| p.port == port && p.netns.is_none())
+        .min_by_key(|p| p.pid)
+        .map(|p| Owner {
+            pid: p.pid,
+            process_name: p.process_name.clone(),
+        })
+}
+
+/// Estado de los puertos vigilados entre escaneos
+#[derive(Debug, Default)]
+pub struct WatchMonitor {
+    /// Dueño de cada puerto vigilado en el último escaneo
+    owners: HashMap<u16, Option<Owner>>,
+}
+
+impl WatchMonitor {
+    /// Crea un monitor sin escaneos previos.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compara un escaneo con el anterior y devuelve los cambios de los
+    /// que hay que avisar. Un puerto recién vigilado solo se anota.
+    pub fn check(&mut self, entries: &[WatchEntry], ports: &[PortInfo]) -> Vec<WatchAlert> {
+        let mut alerts = Vec::new();
+        let mut owners = HashMap::new();
+        for entry in entries {
+            let current = owner(entry.port, ports);
+            if let Some(previous) = self.owners.get(&entry.port) {
+                let alert = match (previous, &current) {
+                    (None, Some(now)) if entry.alerts_on(WatchEvent::Open) => {
+                        Some(WatchAlert::Opened(entry.port, now.clone()))
+                    }
+                    (Some(before), None) if entry.alerts_on(WatchEvent::Close) => {
+                        Some(WatchAlert::Closed(entry.port, before.clone()))
+                    }
+                    (Some(before), Some(now))
+                        if before != now && entry.alerts_on(WatchEvent::Owner) =>
+                    {
+                        Some(WatchAlert::OwnerChanged(
+                            entry.port,
+                            before.clone(),
+                            now.clone(),
+                        ))
+                    }
+                    _ => None,
+                };
+                alerts.extend(alert);
+            }
+            owners.insert(entry.port, current);
+        }
+        self.owners = owners;
+        alerts
+    }
+}
+
+/// Comprueba los puertos vigilados y notifica sus cambios (salvo en
+/// mantenimiento).
+///
+/// # Arguments
+/// * `monitor` - Estado de los puertos vigilados entre escaneos
+/// * `config` - Configuración con los puertos vigilados y las ventanas
+/// * `ports` - Puertos del escaneo recién hecho
+pub fn run_after_scan(monitor: &mut WatchMonitor, config: &Config, ports: &[PortInfo]) {
+    let alerts = monitor.check(&entries(config, &WatchStore::load()), ports);
+    if alerts.is_empty() {
+        return;
+    }
+    if let Some((window, _)) =
+        maintenance::active_window(&config.maintenance, maintenance::local_now())
+    {
+        log::debug!(
+            "Avisos de puertos vigilados suprimidos por la ventana '{}'",
+            window.name
+        );
+        return;
+    }
+    for alert in alerts {
+        let message = alert.message();
+        log::info!("{}", message);
+        rules::notify(&message);
+    }
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn listener(port: u16, pid: u32, process_name: &str) -> PortInfo {
+        PortInfo {
+            protocol: "tcp".into(),
+            port,
+            local_address: "127.0.0.1".into(),
+            pid,
+            process_name: process_name.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Verifica los avisos de apertura, cambio de dueño y cierre
+    #[test]
+    fn test_watch_monitor() {
+        let entries = [
+            WatchEntry {
+                port: 5432,
+                on: Vec::new(),
+            },
+            WatchEntry {
+                port: 3000,
+                on: vec![WatchEvent::Close],
+            },
+        ];
+        let mut monitor = WatchMonitor::new();
+        // El primer escaneo solo se anota
+        assert!(monitor
+            .check(&entries, &[listener(3000, 7, "node")])
+            .is_empty());
+
+        let alerts = monitor.check(
+            &entries,
+            &[listener(3000, 8, "node"), listener(5432, 9, "postgres")],
+        );
+        let postgres = Owner {
+            pid: 9,
+            process_name: "postgres".into(),
+        };
+        // El 3000 cambió de dueño, pero solo avisa al cerrarse
+        assert_eq!(alerts, [WatchAlert::Opened(5432, postgres.clone())]);
+
+        let alerts = monitor.check(&entries, &[listener(5432, 10, "postgres")]);
+        assert_eq!(alerts.len(), 2);
+        assert!(matches!(&alerts[0], WatchAlert::OwnerChanged(5432, from, _) if *from == postgres));
+        assert!(matches!(&alerts[1], WatchAlert::Closed(3000, o) if o.pid == 8));
+    }
+
+    /// Verifica la lista combinada de configuración y archivo
+    #[test]
+    fn test_entries() {
+        let config = Config::parse("[[watch]]\nport = 3000\non = [\"close\"]\n").unwrap();
+        let mut store = WatchStore::default();
+        assert!(store.watch(WatchEntry {
+            port: 3000,
+            on: Vec::new(),
+        }));
+        assert!(store.watch(WatchEntry {
+            port: 6379,
+            on: Vec::new(),
+        }));
+        assert!(!store.watch(WatchEntry {
+            port: 6379,
+            on: Vec::new(),
+        }));
+        let entries = entries(&config, &store);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].events(), [WatchEvent::Close]);
+        assert_eq!(entries[1].events(), ALL_EVENTS);
+        assert!(store.unwatch(6379));
+        assert!(!store.is_watched(6379));
+    }
+}
@@ -0,0 +1,153 @@
+/// Módulo de espacios de trabajo.
+///
+/// Un espacio de trabajo es un perfil con nombre ("work", "homelab",
+/// "gamedev"...) definido en `config.toml` con sus propios favoritos,
+/// listas de puertos/procesos a ignorar y reglas. Solo uno está activo
+/// a la vez; el nombre activo se guarda en el directorio de estado para
+/// que se pueda cambiar desde el tray o el CLI sin editar la configuración.
+///
+/// Ejemplo:
+/// ```toml
+/// [workspaces.work]
+/// favorites = [3000, 5432]
+/// ignore_ports = [22, 631]
+/// ignore_processes = ["cupsd", "systemd-*"]
+///
+/// [[workspaces.work.rules]]
+/// name = "sin docker en el trabajo"
+/// process = "docker-proxy"
+/// action = "notify"
+/// ```
+use serde::Deserialize;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::paths;
+use crate::port_scanner::{self, PortInfo};
+use crate::rules::Rule;
+
+/// Archivo (en el directorio de estado) con el nombre del espacio activo
+const ACTIVE_FILE: &str = "workspace";
+
+/// Perfil de un espacio de trabajo
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct Workspace {
+    /// Puertos favoritos: se muestran primero y marcados con ⭐
+    pub favorites: Vec<u16>,
+    /// Puertos que no se muestran en la lista
+    pub ignore_ports: Vec<u16>,
+    /// Procesos (admiten comodines) que no se muestran en la lista
+    pub ignore_processes: Vec<String>,
+    /// Reglas que se suman a las globales mientras el espacio está activo
+    pub rules: Vec<Rule>,
+}
+
+impl Workspace {
+    /// Indica si un puerto es favorito en este espacio.
+    pub fn is_favorite(&self, port_info: &PortInfo) -> bool {
+        self.favorites.contains(&port_info.port)
+    }
+
+    /// Indica si un puerto está oculto por las listas de ignorados.
+    pub fn is_ignored(&self, port_info: &PortInfo) -> bool {
+        self.ignore_ports.contains(&port_info.port)
+            || self
+                .ignore_processes
+                .iter()
+                .any(|pattern| port_scanner::glob_match(pattern, &port_info.process_name))
+    }
+
+    /// Quita los puertos ignorados y coloca los favoritos al principio.
+    ///
+    /// El orden relativo del resto de puertos se conserva.
+    pub fn apply(&self, ports: &[PortInfo]) -> Vec<PortInfo> {
+        let mut visible: Vec<PortInfo> = ports
+            .iter()
+            .filter(|p| !self.is_ignored(p))
+            .cloned()
+            .collect();
+        visible.sort_by_key(|p| !self.is_favorite(p));
+        visible
+    }
+}
+
+/// Ruta del archivo con el espacio activo.
+fn active_path() -> PathBuf {
+    paths::state_dir().join(ACTIVE_FILE)
+}
+
+/// Nombre del espacio de trabajo activo (`None` si no hay ninguno).
+pub fn active_name() -> Option<String> {
+    fs::read_to_string(active_path())
+        .ok()
+        .map(|name| name.trim().to_string())
+        .filter(|name| !name.is_empty())
+}
+
+/// Cambia el espacio de trabajo activo.
+///
+/// # Arguments
+/// * `name` - Nombre del espacio, o `None` para desactivarlos todos
+pub fn set_active(name: Option<&str>) -> Result<(), String> {
+    let path = active_path();
+    match name {
+        Some(name) => {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+            }
+            fs::write(&path, name)
+                .map_err(|e| format!("No se pudo guardar {}: {}", path.display(), e))?;
+            log::info!("Espacio de trabajo activo: {}", name);
+        }
+        None => {
+            if path.exists() {
+                fs::remove_file(&path)
+                    .map_err(|e| format!("No se pudo borrar {}: {}", path.display(), e))?;
+            }
+            log::info!("Sin espacio de trabajo activo");
+        }
+    }
+    Ok(())
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn port(port: u16, process_name: &str) -> PortInfo {
+        PortInfo {
+            protocol: "tcp".into(),
+            port,
+            local_address: "0.0.0.0".into(),
+            pid: 1,
+            process_name: process_name.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Verifica que se ocultan los ignorados y los favoritos van primero
+    #[test]
+    fn test_apply_workspace() {
+        let workspace = Workspace {
+            favorites: vec![5432],
+            ignore_ports: vec![22],
+            ignore_processes: vec!["cups*".into()],
+            ..Default::default()
+        };
+        let ports = vec![
+            port(22, "sshd"),
+            port(631, "cupsd"),
+            port(3000, "node"),
+            port(5432, "postgres"),
+            port(8080, "java"),
+        ];
+
+        let visible: Vec<u16> = workspace.apply(&ports).iter().map(|p| p.port).collect();
+        assert_eq!(visible, vec![5432, 3000, 8080]);
+    }
+}
@@ -0,0 +1,255 @@
+/// Módulo de los procesos zombi y huérfanos.
+///
+/// Un socket puede quedar a nombre de un proceso que ya no se comporta
+/// como tal:
+///
+/// - **Zombi** (estado `Z` en `/proc/[pid]/stat`): terminó, pero su padre
+///   no lo ha recogido con `wait()`. Casi siempre es el líder de un
+///   proceso multihilo cuyos hilos siguen vivos con el socket, y un
+///   SIGKILL al PID mata a todo el grupo. Solo si ya no le queda ningún
+///   hilo vivo la limpieza pasa por matar al padre, para que init lo
+///   herede y lo recoja; eso es una acción aparte y confirmada
+///   ([`kill_parent`]), porque el padre suele ser una shell o un IDE.
+/// - **Huérfano**: su sesión (la terminal que lo lanzó) ya no existe y lo
+///   adoptó init o un subreaper (`systemd --user`). Es el servidor de
+///   desarrollo que sobrevive a la terminal cerrada: se mata como
+///   cualquier otro, pero conviene saber que no tiene a quién volver.
+///
+/// Los servicios arrancados por systemd son líderes de su propia sesión,
+/// así que no cuentan como huérfanos.
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+
+use crate::i18n::{tr, trf, Text};
+use crate::port_scanner::{self, KillFailure};
+
+/// Estado anómalo del dueño de un socket
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum Defunct {
+    /// Terminó y su padre no lo recoge
+    Zombie {
+        /// Padre que lo retiene (`None` si es init o el kernel)
+        #[serde(skip_serializing_if = "Option::is_none")]
+        parent_pid: Option<u32>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        parent_name: Option<String>,
+    },
+    /// Su sesión terminó y lo adoptó otro proceso
+    Orphan {
+        /// Sesión (PID del líder) que ya no existe
+        session: u32,
+    },
+}
+
+impl Defunct {
+    /// Marca del puerto: "🧟 zombi (lo retiene bash [PID 12])" o "👻 huérfano".
+    pub fn label(&self) -> String {
+        match self {
+            Defunct::Zombie {
+                parent_pid: Some(pid),
+                parent_name,
+            } => trf(
+                Text::ProcessZombieHeld,
+                &[&parent_name.as_deref().unwrap_or("?"), pid],
+            ),
+            Defunct::Zombie { .. } => tr(Text::ProcessZombie).to_string(),
+            Defunct::Orphan { .. } => tr(Text::ProcessOrphan).to_string(),
+        }
+    }
+}
+
+/// Campos de `/proc/[pid]/stat` que importan aquí
+#[derive(Debug, PartialEq)]
+struct Stat {
+    state: char,
+    ppid: u32,
+    session: u32,
+}
+
+/// Estado anómalo de un proceso, si lo tiene.
+pub fn detect(pid: u32) -> Option<Defunct> {
+    if pid <= 1 {
+        return None;
+    }
+    let stat = read_stat(pid)?;
+    let parent = read_stat(stat.ppid);
+    classify(pid, &stat, parent.as_ref(), |session| {
+        Path::new(&format!("/proc/{}", session)).exists()
+    })
+}
+
+/// Padre que retiene a un zombi sin hilos vivos (PID y nombre): el único
+/// al que matar para liberarlo.
+///
+/// # Returns
+/// Un error si no es un zombi, si aún tiene hilos vivos (basta con el
+/// SIGKILL normal) o si su padre es init.
+pub fn reaping_parent(pid: u32) -> Result<(u32, String), String> {
+    match detect(pid) {
+        Some(defunct) => parent_to_reap(&defunct, live_threads(pid)).ok_or(match defunct {
+            Defunct::Zombie {
+                parent_pid: None, ..
+            } => trf(Text::ZombieNoParent, &[&pid]),
+            _ => trf(Text::ZombieNotHeld, &[&pid]),
+        }),
+        None => Err(trf(Text::ZombieNotHeld, &[&pid])),
+    }
+}
+
+/// Mata al padre de un zombi sin hilos vivos (ver [`reaping_parent`]).
+///
+/// # Returns
+/// El PID del padre, o un error si el proceso no es un zombi retenido o
+/// no se pudo matar al padre.
+pub fn kill_parent(pid: u32) -> Result<u32, KillFailure> {
+    let (parent, name) = reaping_parent(pid).map_err(KillFailure::refused)?;
+    log::info!(
+        "PID {} es un zombi sin hilos vivos; se mata a su padre {} (PID {})",
+        pid,
+        name,
+        parent
+    );
+    port_scanner::try_kill_process(parent).map(|()| parent)
+}
+
+/// Decide si hay que matar al padre: solo con un zombi sin hilos vivos
+/// y con un padre que no sea init.
+fn parent_to_reap(defunct: &Defunct, live_threads: usize) -> Option<(u32, String)> {
+    match defunct {
+        Defunct::Zombie {
+            parent_pid: Some(parent),
+            parent_name,
+        } if live_threads == 0 => Some((*parent, parent_name.clone().unwrap_or("?".into()))),
+        _ => None,
+    }
+}
+
+/// Hilos de un proceso que no han terminado (`/proc/[pid]/task`).
+fn live_threads(pid: u32) -> usize {
+    let Ok(tasks) = fs::read_dir(format!("/proc/{}/task", pid)) else {
+        return 0;
+    };
+    tasks
+        .flatten()
+        .filter_map(|task| fs::read_to_string(task.path().join("stat")).ok())
+        .filter_map(|stat| parse_stat(&stat))
+        .filter(|stat| stat.state != 'Z' && stat.state != 'X')
+        .count()
+}
+
+/// Lee `/proc/[pid]/stat`.
+fn read_stat(pid: u32) -> Option<Stat> {
+    parse_stat(&fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?)
+}
+
+/// Extrae estado, PPID y sesión de `/proc/[pid]/stat` ("pid (comm)
+/// estado ppid pgrp sesión ..."); el nombre puede tener paréntesis.
+fn parse_stat(stat: &str) -> Option<Stat> {
+    let (_, rest) = stat.rsplit_once(')')?;
+    let mut fields = rest.split_whitespace();
+    let state = fields.next()?.chars().next()?;
+    let ppid = fields.next()?.parse().ok()?;
+    let session = fields.nth(1)?.parse().ok()?;
+    Some(Stat {
+        state,
+        ppid,
+        session,
+    })
+}
+
+/// Clasifica un proceso según su stat y el de su padre (`alive` dice si
+/// un PID sigue existiendo).
+fn classify(
+    pid: u32,
+    stat: &Stat,
+    parent: Option<&Stat>,
+    alive: impl Fn(u32) -> bool,
+) -> Option<Defunct> {
+    if stat.state == 'Z' || stat.state == 'X' {
+        let parent_pid = Some(stat.ppid).filter(|ppid| *ppid > 1);
+        return Some(Defunct::Zombie {
+            parent_pid,
+            parent_name: parent_pid.and_then(|ppid| {
+                fs::read_to_string(format!("/proc/{}/comm", ppid))
+                    .ok()
+                    .map(|name| name.trim().to_string())
+            }),
+        });
+    }
+    // Huérfano: el líder de su sesión murió y lo adoptó alguien de fuera
+    let orphaned = stat.session > 1
+        && stat.session != pid
+        && !alive(stat.session)
+        && parent.is_none_or(|parent| parent.session != stat.session);
+    orphaned.then_some(Defunct::Orphan {
+        session: stat.session,
+    })
+}
+
+// ─────────────────────────────────────────────────────────────
+// Tests unitarios
+// ─────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Verifica la lectura del stat y la clasificación de zombis,
+    /// huérfanos y procesos normales
+    #[test]
+    fn test_classify_defunct() {
+        let stat = |text: &str| parse_stat(text).unwrap();
+        assert_eq!(
+            stat("4242 (node (dev)) S 4200 4242 4100 34816"),
+            Stat {
+                state: 'S',
+                ppid: 4200,
+                session: 4100
+            }
+        );
+
+        let zombie = stat("4242 (node) Z 1 4242 4100 0");
+        assert_eq!(
+            classify(4242, &zombie, None, |_| true),
+            Some(Defunct::Zombie {
+                parent_pid: None,
+                parent_name: None
+            })
+        );
+
+        // La terminal (sesión 4100) se cerró y lo adoptó systemd --user
+        let orphan = stat("4242 (node) S 1500 4242 4100 0");
+        let reaper = stat("1500 (systemd) S 1 1500 1500 0");
+        assert_eq!(
+            classify(4242, &orphan, Some(&reaper), |pid| pid != 4100),
+            Some(Defunct::Orphan { session: 4100 })
+        );
+        // Con la terminal abierta es un proceso normal
+        assert_eq!(classify(4242, &orphan, Some(&reaper), |_| true), None);
+        // Un servicio es líder de su sesión
+        let service = stat("800 (postgres) S 1 800 800 0");
+        assert_eq!(classify(800, &service, None, |_| false), None);
+    }
+
+    /// Verifica que solo se propone matar al padre de un zombi sin hilos
+    /// vivos y con un padre distinto de init
+    #[test]
+    fn test_parent_to_reap() {
+        let zombie = Defunct::Zombie {
+            parent_pid: Some(12),
+            parent_name: Some("bash".into()),
+        };
+        // Con hilos vivos basta el SIGKILL al propio PID
+        assert_eq!(parent_to_reap(&zombie, 3), None);
+        assert_eq!(parent_to_reap(&zombie, 0), Some((12, "bash".into())));
+
+        let reaped_by_init = Defunct::Zombie {
+            parent_pid: None,
+            parent_name: None,
+        };
+        assert_eq!(parent_to_reap(&reaped_by_init, 0), None);
+        assert_eq!(parent_to_reap(&Defunct::Orphan { session: 4100 }, 0), None);
+    }
+}